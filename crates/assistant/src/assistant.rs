@@ -8,6 +8,7 @@ mod inline_assistant;
 mod model_selector;
 mod prompt_library;
 mod prompts;
+mod request_log;
 mod slash_command;
 mod streaming_diff;
 mod terminal_inline_assistant;
@@ -34,8 +35,8 @@ use serde::{Deserialize, Serialize};
 use settings::{update_settings_file, Settings, SettingsStore};
 use slash_command::{
     active_command, default_command, diagnostics_command, docs_command, fetch_command,
-    file_command, now_command, project_command, prompt_command, search_command, symbols_command,
-    tabs_command, term_command, workflow_command,
+    file_command, git_command, now_command, project_command, prompt_command, search_command,
+    symbols_command, tabs_command, term_command, workflow_command,
 };
 use std::sync::Arc;
 pub(crate) use streaming_diff::*;
@@ -56,7 +57,12 @@ actions!(
         DeployPromptLibrary,
         ConfirmCommand,
         ToggleModelSelector,
-        DebugWorkflowSteps
+        DebugWorkflowSteps,
+        ForkContext,
+        RegenerateResponse,
+        PreviousResponse,
+        NextResponse,
+        OpenRequestLog
     ]
 );
 
@@ -280,6 +286,7 @@ fn register_slash_commands(prompt_builder: Option<Arc<PromptBuilder>>, cx: &mut
     slash_command_registry.register_command(term_command::TermSlashCommand, true);
     slash_command_registry.register_command(now_command::NowSlashCommand, true);
     slash_command_registry.register_command(diagnostics_command::DiagnosticsSlashCommand, true);
+    slash_command_registry.register_command(git_command::GitSlashCommand, true);
     if let Some(prompt_builder) = prompt_builder {
         slash_command_registry.register_command(
             workflow_command::WorkflowSlashCommand::new(prompt_builder),
@@ -3,6 +3,7 @@ use crate::{
     humanize_token_count,
     prompt_library::open_prompt_library,
     prompts::PromptBuilder,
+    request_log::RequestLogView,
     slash_command::{
         default_command::DefaultSlashCommand,
         docs_command::{DocsSlashCommand, DocsSlashCommandArgs},
@@ -11,10 +12,12 @@ use crate::{
     },
     terminal_inline_assistant::TerminalInlineAssistant,
     Assist, ConfirmCommand, Context, ContextEvent, ContextId, ContextStore, CycleMessageRole,
-    DebugWorkflowSteps, DeployHistory, DeployPromptLibrary, InlineAssist, InlineAssistId,
-    InlineAssistant, InsertIntoEditor, MessageStatus, ModelSelector, PendingSlashCommand,
-    PendingSlashCommandStatus, QuoteSelection, RemoteContextMetadata, ResolvedWorkflowStep,
-    SavedContextMetadata, Split, ToggleFocus, ToggleModelSelector,
+    DebugWorkflowSteps, DeployHistory, DeployPromptLibrary, ForkContext, InlineAssist,
+    InlineAssistId, InlineAssistant, InsertIntoEditor, Message, MessageId, MessageStatus,
+    ModelSelector, NextResponse, OpenRequestLog, PendingSlashCommand, PendingSlashCommandStatus,
+    PreviousResponse, QuoteSelection, RegenerateResponse, RemoteContextMetadata,
+    ResolvedWorkflowStep, SavedContextMetadata, Split, ToggleFocus, ToggleModelSelector,
+    TokenBudget, TokenBudgetSectionKind, TrimmingPolicy,
 };
 use crate::{ContextStoreEvent, ShowConfiguration};
 use anyhow::{anyhow, Result};
@@ -99,7 +102,8 @@ pub fn init(cx: &mut AppContext) {
                 .register_action(AssistantPanel::inline_assist)
                 .register_action(ContextEditor::quote_selection)
                 .register_action(ContextEditor::insert_selection)
-                .register_action(AssistantPanel::show_configuration);
+                .register_action(AssistantPanel::show_configuration)
+                .register_action(RequestLogView::open);
         },
     )
     .detach();
@@ -168,6 +172,7 @@ struct SavedContextPickerDelegate {
 
 enum SavedContextPickerEvent {
     Confirmed(ContextMetadata),
+    OpenSaved(PathBuf),
 }
 
 enum InlineAssistTarget {
@@ -256,10 +261,17 @@ impl PickerDelegate for SavedContextPickerDelegate {
                     .justify_between()
                     .gap_2()
                     .child(
-                        h_flex().flex_1().overflow_x_hidden().child(
-                            Label::new(context.summary.clone().unwrap_or(DEFAULT_TAB_TITLE.into()))
+                        h_flex()
+                            .flex_1()
+                            .gap_2()
+                            .overflow_x_hidden()
+                            .child(
+                                Label::new(
+                                    context.summary.clone().unwrap_or(DEFAULT_TAB_TITLE.into()),
+                                )
                                 .size(LabelSize::Small),
-                        ),
+                            )
+                            .children(context.forked_from.clone().map(render_forked_from)),
                     )
                     .child(
                         h_flex()
@@ -290,7 +302,24 @@ impl PickerDelegate for SavedContextPickerDelegate {
                 .child(
                     h_flex()
                         .flex_1()
+                        .gap_2()
                         .child(Label::new(context.title.clone()).size(LabelSize::Small))
+                        .children(context.forked_from.as_ref().map(|parent| {
+                            match parent.path.clone() {
+                                Some(path) => Button::new(
+                                    ("open-parent", ix),
+                                    format!("Forked from {}", parent.title),
+                                )
+                                .label_size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .tooltip(|cx| Tooltip::text("Open Parent Context", cx))
+                                .on_click(cx.listener(move |_, _, cx| {
+                                    cx.emit(SavedContextPickerEvent::OpenSaved(path.clone()))
+                                }))
+                                .into_any_element(),
+                                None => render_forked_from(parent.title.clone()).into_any_element(),
+                            }
+                        }))
                         .overflow_x_hidden(),
                 )
                 .child(
@@ -407,6 +436,7 @@ impl AssistantPanel {
                                         .action("New Context", Box::new(NewFile))
                                         .action("History", Box::new(DeployHistory))
                                         .action("Prompt Library", Box::new(DeployPromptLibrary))
+                                        .action("Request Log", Box::new(OpenRequestLog))
                                         .action("Configure", Box::new(ShowConfiguration))
                                         .action(zoom_label, Box::new(ToggleZoom))
                                 });
@@ -1057,7 +1087,7 @@ impl AssistantPanel {
     }
 
     fn deploy_prompt_library(&mut self, _: &DeployPromptLibrary, cx: &mut ViewContext<Self>) {
-        open_prompt_library(self.languages.clone(), cx).detach_and_log_err(cx);
+        open_prompt_library(self.fs.clone(), self.languages.clone(), cx).detach_and_log_err(cx);
     }
 
     fn toggle_model_selector(&mut self, _: &ToggleModelSelector, cx: &mut ViewContext<Self>) {
@@ -1167,6 +1197,40 @@ impl AssistantPanel {
         })
     }
 
+    fn fork_context(
+        &mut self,
+        context: Model<Context>,
+        message_id: MessageId,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<View<ContextEditor>>> {
+        let context = self
+            .context_store
+            .update(cx, |store, cx| store.fork_context(&context, message_id, cx));
+        let fs = self.fs.clone();
+        let workspace = self.workspace.clone();
+        let lsp_adapter_delegate = make_lsp_adapter_delegate(&self.project, cx).log_err();
+
+        cx.spawn(|this, mut cx| async move {
+            let context = context.await?;
+            let assistant_panel = this.clone();
+            this.update(&mut cx, |this, cx| {
+                let editor = cx.new_view(|cx| {
+                    ContextEditor::for_context(
+                        context,
+                        fs,
+                        workspace,
+                        this.project.clone(),
+                        lsp_adapter_delegate,
+                        assistant_panel,
+                        cx,
+                    )
+                });
+                this.show_context(editor.clone(), cx);
+                anyhow::Ok(editor)
+            })?
+        })
+    }
+
     fn is_authenticated(&mut self, cx: &mut ViewContext<Self>) -> bool {
         LanguageModelRegistry::read_global(cx)
             .active_provider()
@@ -1618,6 +1682,7 @@ pub struct ContextEditor {
     active_workflow_step: Option<ActiveWorkflowStep>,
     assistant_panel: WeakView<AssistantPanel>,
     error_message: Option<SharedString>,
+    show_token_limit_notice: bool,
 }
 
 const DEFAULT_TAB_TITLE: &str = "New Context";
@@ -1677,6 +1742,7 @@ impl ContextEditor {
             active_workflow_step: None,
             assistant_panel,
             error_message: None,
+            show_token_limit_notice: false,
         };
         this.update_message_headers(cx);
         this.insert_slash_command_output_sections(sections, cx);
@@ -1711,11 +1777,56 @@ impl ContextEditor {
     fn assist(&mut self, _: &Assist, cx: &mut ViewContext<Self>) {
         if !self.apply_active_workflow_step(cx) {
             self.error_message = None;
-            self.send_to_model(cx);
+            self.show_token_limit_notice = false;
+            if let Some(TokenState::NoTokensLeft { .. }) = token_state(&self.context, cx) {
+                self.trim_and_send_to_model(cx);
+            } else {
+                self.send_to_model(cx);
+            }
             cx.notify();
         }
     }
 
+    /// Trims the context with the configured policies before sending it, or offers to trim it
+    /// when none are configured.
+    fn trim_and_send_to_model(&mut self, cx: &mut ViewContext<Self>) {
+        let policies = AssistantSettings::get_global(cx).auto_trim_policies.clone();
+        if policies.is_empty() {
+            self.show_token_limit_notice = true;
+            return;
+        }
+
+        let trim = self
+            .context
+            .update(cx, |context, cx| context.trim_to_budget(policies, cx));
+        cx.spawn(|this, mut cx| async move {
+            let fits = trim.await?;
+            this.update(&mut cx, |this, cx| {
+                if fits {
+                    this.send_to_model(cx);
+                } else {
+                    this.show_token_limit_notice = true;
+                }
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn trim_context(&mut self, policy: TrimmingPolicy, cx: &mut ViewContext<Self>) {
+        let trim = self
+            .context
+            .update(cx, |context, cx| context.trim(policy, cx));
+        cx.spawn(|this, mut cx| async move {
+            trim.await?;
+            this.update(&mut cx, |this, cx| {
+                this.show_token_limit_notice = false;
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn apply_workflow_step(&mut self, range: Range<language::Anchor>, cx: &mut ViewContext<Self>) {
         self.show_workflow_step(range.clone(), cx);
 
@@ -1930,6 +2041,61 @@ impl ContextEditor {
         });
     }
 
+    fn fork_context(&mut self, _: &ForkContext, cx: &mut ViewContext<Self>) {
+        if let Some(message) = self.message_at_newest_cursor(cx) {
+            Self::fork_context_at(self.context.clone(), message.id, &self.assistant_panel, cx);
+        }
+    }
+
+    fn fork_context_at(
+        context: Model<Context>,
+        message_id: MessageId,
+        assistant_panel: &WeakView<AssistantPanel>,
+        cx: &mut WindowContext,
+    ) {
+        assistant_panel
+            .update(cx, |panel, cx| {
+                panel
+                    .fork_context(context, message_id, cx)
+                    .detach_and_log_err(cx)
+            })
+            .ok();
+    }
+
+    fn regenerate_response(&mut self, _: &RegenerateResponse, cx: &mut ViewContext<Self>) {
+        if let Some(message) = self.message_at_newest_cursor(cx) {
+            self.context.update(cx, |context, cx| {
+                context.regenerate(message.id, cx);
+            });
+        }
+    }
+
+    fn previous_response(&mut self, _: &PreviousResponse, cx: &mut ViewContext<Self>) {
+        if let Some(message) = self.message_at_newest_cursor(cx) {
+            if let Some(ix) = message.alternate_ix.checked_sub(1) {
+                self.context.update(cx, |context, cx| {
+                    context.select_alternate(message.id, ix, cx);
+                });
+            }
+        }
+    }
+
+    fn next_response(&mut self, _: &NextResponse, cx: &mut ViewContext<Self>) {
+        if let Some(message) = self.message_at_newest_cursor(cx) {
+            self.context.update(cx, |context, cx| {
+                context.select_alternate(message.id, message.alternate_ix + 1, cx);
+            });
+        }
+    }
+
+    fn message_at_newest_cursor(&self, cx: &AppContext) -> Option<Message> {
+        let cursor = self.editor.read(cx).selections.newest::<usize>(cx).head();
+        self.context
+            .read(cx)
+            .messages_for_offsets([cursor], cx)
+            .pop()
+    }
+
     fn cursors(&self, cx: &AppContext) -> Vec<usize> {
         let selections = self.editor.read(cx).selections.all::<usize>(cx);
         selections
@@ -2791,6 +2957,7 @@ impl ContextEditor {
                     style: BlockStyle::Sticky,
                     render: Box::new({
                         let context = self.context.clone();
+                        let assistant_panel = self.assistant_panel.clone();
                         move |cx| {
                             let message_id = message.id;
                             let sender = ButtonLike::new("role")
@@ -2836,6 +3003,7 @@ impl ContextEditor {
                                         cx,
                                     )
                                 });
+                            let can_switch_responses = context.read(cx).can_switch_responses();
                             h_flex()
                                 .id(("message_header", message_id.as_u64()))
                                 .pl(cx.gutter_dimensions.full_width())
@@ -2860,6 +3028,74 @@ impl ContextEditor {
                                         None
                                     },
                                 )
+                                .child(
+                                    h_flex()
+                                        .ml_auto()
+                                        .mr(cx.gutter_dimensions.margin)
+                                        .gap_1()
+                                        .when(message.alternate_count > 1, |this| {
+                                            this.child(render_alternate_navigation(
+                                                &message,
+                                                &context,
+                                                can_switch_responses,
+                                            ))
+                                        })
+                                        .when(
+                                            message.role == Role::Assistant
+                                                && message.status != MessageStatus::Pending
+                                                && can_switch_responses,
+                                            |this| {
+                                                this.child(
+                                                    IconButton::new(
+                                                        "regenerate-response",
+                                                        IconName::RotateCw,
+                                                    )
+                                                    .icon_size(IconSize::Small)
+                                                    .icon_color(Color::Muted)
+                                                    .tooltip(|cx| {
+                                                        Tooltip::for_action(
+                                                            "Regenerate Response",
+                                                            &RegenerateResponse,
+                                                            cx,
+                                                        )
+                                                    })
+                                                    .on_click({
+                                                        let context = context.clone();
+                                                        move |_, cx| {
+                                                            context.update(cx, |context, cx| {
+                                                                context.regenerate(message_id, cx);
+                                                            });
+                                                        }
+                                                    }),
+                                                )
+                                            },
+                                        )
+                                        .child(
+                                            IconButton::new("fork-context", IconName::Copy)
+                                                .icon_size(IconSize::Small)
+                                                .icon_color(Color::Muted)
+                                                .tooltip(|cx| {
+                                                    Tooltip::with_meta(
+                                                        "Fork Context",
+                                                        Some(&ForkContext),
+                                                        "Start a new context from this message",
+                                                        cx,
+                                                    )
+                                                })
+                                                .on_click({
+                                                    let context = context.clone();
+                                                    let assistant_panel = assistant_panel.clone();
+                                                    move |_, cx| {
+                                                        ContextEditor::fork_context_at(
+                                                            context.clone(),
+                                                            message_id,
+                                                            &assistant_panel,
+                                                            cx,
+                                                        )
+                                                    }
+                                                }),
+                                        ),
+                                )
                                 .into_any_element()
                         }
                     }),
@@ -3200,6 +3436,41 @@ impl ContextEditor {
                     )
                     .into_any_element(),
             )
+        } else if self.show_token_limit_notice {
+            Some(
+                h_flex()
+                    .p_3()
+                    .gap_2()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .bg(cx.theme().colors().editor_background)
+                    .justify_between()
+                    .child(
+                        h_flex()
+                            .gap_3()
+                            .child(
+                                Icon::new(IconName::ExclamationTriangle)
+                                    .size(IconSize::Small)
+                                    .color(Color::Warning),
+                            )
+                            .child(Label::new("This context exceeds the model's token limit.")),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .children(TrimmingPolicy::ALL.into_iter().map(|policy| {
+                                Button::new(policy.label(), policy.label())
+                                    .size(ButtonSize::Compact)
+                                    .style(ButtonStyle::Filled)
+                                    .on_click(
+                                        cx.listener(move |this, _, cx| {
+                                            this.trim_context(policy, cx)
+                                        }),
+                                    )
+                            })),
+                    )
+                    .into_any_element(),
+            )
         } else {
             None
         }
@@ -3300,6 +3571,10 @@ impl Render for ContextEditor {
             .on_action(cx.listener(ContextEditor::assist))
             .on_action(cx.listener(ContextEditor::split))
             .on_action(cx.listener(ContextEditor::debug_workflow_steps))
+            .on_action(cx.listener(ContextEditor::fork_context))
+            .on_action(cx.listener(ContextEditor::regenerate_response))
+            .on_action(cx.listener(ContextEditor::previous_response))
+            .on_action(cx.listener(ContextEditor::next_response))
             .size_full()
             .children(self.render_notice(cx))
             .child(
@@ -3322,6 +3597,133 @@ impl Render for ContextEditor {
     }
 }
 
+struct TokenBudgetPopover {
+    context: Model<Context>,
+    budget: Option<TokenBudget>,
+    focus_handle: FocusHandle,
+    _update_budget: Task<()>,
+}
+
+impl TokenBudgetPopover {
+    fn new(context: Model<Context>, cx: &mut ViewContext<Self>) -> Self {
+        let mut this = Self {
+            context,
+            budget: None,
+            focus_handle: cx.focus_handle(),
+            _update_budget: Task::ready(()),
+        };
+        this.update_budget(cx);
+        this
+    }
+
+    fn update_budget(&mut self, cx: &mut ViewContext<Self>) {
+        let budget = self.context.read(cx).token_budget(cx);
+        self._update_budget = cx.spawn(|this, mut cx| async move {
+            let budget = budget.await.log_err();
+            this.update(&mut cx, |this, cx| {
+                this.budget = budget;
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn trim(&mut self, policy: TrimmingPolicy, cx: &mut ViewContext<Self>) {
+        let trim = self
+            .context
+            .update(cx, |context, cx| context.trim(policy, cx));
+        self._update_budget = cx.spawn(|this, mut cx| async move {
+            trim.await.log_err();
+            this.update(&mut cx, |this, cx| this.update_budget(cx)).ok();
+        });
+    }
+}
+
+impl EventEmitter<DismissEvent> for TokenBudgetPopover {}
+
+impl FocusableView for TokenBudgetPopover {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for TokenBudgetPopover {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let sections = match &self.budget {
+            Some(budget) => v_flex()
+                .gap_0p5()
+                .children(budget.sections.iter().map(|section| {
+                    let (icon, indent) = match section.kind {
+                        TokenBudgetSectionKind::Message { .. } => (IconName::MessageBubbles, false),
+                        TokenBudgetSectionKind::SlashCommandOutput { icon } => (icon, true),
+                    };
+                    h_flex()
+                        .gap_2()
+                        .justify_between()
+                        .child(
+                            h_flex()
+                                .gap_1()
+                                .overflow_x_hidden()
+                                .when(indent, |this| this.pl_4())
+                                .child(Icon::new(icon).size(IconSize::Small).color(Color::Muted))
+                                .child(Label::new(section.label.clone()).size(LabelSize::Small)),
+                        )
+                        .child(
+                            Label::new(humanize_token_count(section.token_count))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                }))
+                .child(
+                    h_flex()
+                        .mt_1()
+                        .justify_between()
+                        .child(Label::new("Total").size(LabelSize::Small))
+                        .child(
+                            Label::new(format!(
+                                "{} / {}",
+                                humanize_token_count(budget.token_count()),
+                                humanize_token_count(budget.max_token_count)
+                            ))
+                            .size(LabelSize::Small)
+                            .color(if budget.is_exceeded() {
+                                Color::Error
+                            } else {
+                                Color::Muted
+                            }),
+                        ),
+                ),
+            None => v_flex().child(
+                Label::new("Counting tokens…")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            ),
+        };
+
+        v_flex()
+            .mt_2()
+            .w_80()
+            .py_2()
+            .px_3()
+            .gap_1()
+            .elevation_2(cx)
+            .bg(cx.theme().colors().surface_background)
+            .occlude()
+            .child(Label::new("Token Usage").weight(FontWeight::SEMIBOLD))
+            .child(sections)
+            .child(
+                v_flex()
+                    .mt_1()
+                    .gap_0p5()
+                    .children(TrimmingPolicy::ALL.into_iter().map(|policy| {
+                        Button::new(policy.label(), policy.label())
+                            .full_width()
+                            .on_click(cx.listener(move |this, _, cx| this.trim(policy, cx)))
+                    })),
+            )
+    }
+}
+
 struct ErrorPopover {
     error: SharedString,
     focus_handle: FocusHandle,
@@ -3702,19 +4104,31 @@ impl ContextEditorToolbarItem {
                 (color, token_count, max_token_count)
             }
         };
+        let context = context.clone();
         Some(
-            h_flex()
-                .gap_0p5()
-                .child(
-                    Label::new(humanize_token_count(token_count))
-                        .size(LabelSize::Small)
-                        .color(token_count_color),
-                )
-                .child(Label::new("/").size(LabelSize::Small).color(Color::Muted))
-                .child(
-                    Label::new(humanize_token_count(max_token_count))
-                        .size(LabelSize::Small)
-                        .color(Color::Muted),
+            PopoverMenu::new("token-budget-popover")
+                .menu(move |cx| {
+                    let context = context.clone();
+                    Some(cx.new_view(|cx| TokenBudgetPopover::new(context, cx)))
+                })
+                .trigger(
+                    ButtonLike::new("token-budget")
+                        .child(
+                            h_flex()
+                                .gap_0p5()
+                                .child(
+                                    Label::new(humanize_token_count(token_count))
+                                        .size(LabelSize::Small)
+                                        .color(token_count_color),
+                                )
+                                .child(Label::new("/").size(LabelSize::Small).color(Color::Muted))
+                                .child(
+                                    Label::new(humanize_token_count(max_token_count))
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                ),
+                        )
+                        .tooltip(|cx| Tooltip::text("Show Token Usage", cx)),
                 ),
         )
     }
@@ -3869,19 +4283,23 @@ impl ContextHistory {
         event: &SavedContextPickerEvent,
         cx: &mut ViewContext<Self>,
     ) {
-        let SavedContextPickerEvent::Confirmed(context) = event;
         self.assistant_panel
-            .update(cx, |assistant_panel, cx| match context {
-                ContextMetadata::Remote(metadata) => {
+            .update(cx, |assistant_panel, cx| match event {
+                SavedContextPickerEvent::Confirmed(ContextMetadata::Remote(metadata)) => {
                     assistant_panel
                         .open_remote_context(metadata.id.clone(), cx)
                         .detach_and_log_err(cx);
                 }
-                ContextMetadata::Saved(metadata) => {
+                SavedContextPickerEvent::Confirmed(ContextMetadata::Saved(metadata)) => {
                     assistant_panel
                         .open_saved_context(metadata.path.clone(), cx)
                         .detach_and_log_err(cx);
                 }
+                SavedContextPickerEvent::OpenSaved(path) => {
+                    assistant_panel
+                        .open_saved_context(path.clone(), cx)
+                        .detach_and_log_err(cx);
+                }
             })
             .ok();
     }
@@ -4097,6 +4515,60 @@ impl Item for ConfigurationView {
 
 type ToggleFold = Arc<dyn Fn(bool, &mut WindowContext) + Send + Sync>;
 
+fn render_alternate_navigation(
+    message: &Message,
+    context: &Model<Context>,
+    can_switch_responses: bool,
+) -> impl IntoElement {
+    let message_id = message.id;
+    let alternate_ix = message.alternate_ix;
+    h_flex()
+        .gap_1()
+        .child(
+            IconButton::new("previous-response", IconName::ChevronLeft)
+                .icon_size(IconSize::Small)
+                .disabled(!can_switch_responses || alternate_ix == 0)
+                .tooltip(|cx| Tooltip::for_action("Previous Response", &PreviousResponse, cx))
+                .on_click({
+                    let context = context.clone();
+                    move |_, cx| {
+                        context.update(cx, |context, cx| {
+                            context.select_alternate(
+                                message_id,
+                                alternate_ix.saturating_sub(1),
+                                cx,
+                            );
+                        });
+                    }
+                }),
+        )
+        .child(
+            Label::new(format!("{}/{}", alternate_ix + 1, message.alternate_count))
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+        )
+        .child(
+            IconButton::new("next-response", IconName::ChevronRight)
+                .icon_size(IconSize::Small)
+                .disabled(!can_switch_responses || alternate_ix + 1 >= message.alternate_count)
+                .tooltip(|cx| Tooltip::for_action("Next Response", &NextResponse, cx))
+                .on_click({
+                    let context = context.clone();
+                    move |_, cx| {
+                        context.update(cx, |context, cx| {
+                            context.select_alternate(message_id, alternate_ix + 1, cx);
+                        });
+                    }
+                }),
+        )
+}
+
+fn render_forked_from(title: String) -> impl IntoElement {
+    Label::new(format!("Forked from {title}"))
+        .size(LabelSize::Small)
+        .color(Color::Muted)
+}
+
 fn render_slash_command_output_toggle(
     row: MultiBufferRow,
     is_folded: bool,
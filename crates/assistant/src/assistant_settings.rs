@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use crate::TrimmingPolicy;
 use anthropic::Model as AnthropicModel;
 use fs::Fs;
 use gpui::{AppContext, Pixels};
@@ -53,6 +54,8 @@ pub struct AssistantSettings {
     pub default_width: Pixels,
     pub default_height: Pixels,
     pub default_model: LanguageModelSelection,
+    pub auto_trim_policies: Vec<TrimmingPolicy>,
+    pub project_default_prompts: bool,
     pub using_outdated_settings_version: bool,
 }
 
@@ -222,6 +225,8 @@ impl AssistantSettingsContent {
                                 })
                             }
                         }),
+                    auto_trim_policies: None,
+                    project_default_prompts: None,
                 },
                 VersionedAssistantSettingsContent::V2(settings) => settings.clone(),
             },
@@ -240,6 +245,8 @@ impl AssistantSettingsContent {
                         .id()
                         .to_string(),
                 }),
+                auto_trim_policies: None,
+                project_default_prompts: None,
             },
         }
     }
@@ -355,6 +362,8 @@ impl Default for VersionedAssistantSettingsContent {
             default_width: None,
             default_height: None,
             default_model: None,
+            auto_trim_policies: None,
+            project_default_prompts: None,
         })
     }
 }
@@ -383,6 +392,16 @@ pub struct AssistantSettingsContentV2 {
     default_height: Option<f32>,
     /// The default model to use when creating new contexts.
     default_model: Option<LanguageModelSelection>,
+    /// The ways of trimming a context that are applied, in order, when sending it would exceed
+    /// the token limit of the model. When empty, trimming is offered instead.
+    ///
+    /// Default: []
+    auto_trim_policies: Option<Vec<TrimmingPolicy>>,
+    /// Whether prompts in a project's `.zed/prompts` folder that are marked as default are
+    /// included in that project's default prompt.
+    ///
+    /// Default: false
+    project_default_prompts: Option<bool>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
@@ -393,7 +412,7 @@ pub struct LanguageModelSelection {
 }
 
 fn providers_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
-    schemars::schema::SchemaObject {
+    let known_providers = schemars::schema::SchemaObject {
         enum_values: Some(vec![
             "anthropic".into(),
             "google".into(),
@@ -403,6 +422,25 @@ fn providers_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema:
             "copilot_chat".into(),
         ]),
         ..Default::default()
+    };
+    // Each server in `language_models.openai_compatible` is its own provider.
+    let openai_compatible_providers = schemars::schema::SchemaObject {
+        instance_type: Some(schemars::schema::InstanceType::String.into()),
+        string: Some(Box::new(schemars::schema::StringValidation {
+            pattern: Some("^openai_compatible/".into()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+    schemars::schema::SchemaObject {
+        subschemas: Some(Box::new(schemars::schema::SubschemaValidation {
+            any_of: Some(vec![
+                known_providers.into(),
+                openai_compatible_providers.into(),
+            ]),
+            ..Default::default()
+        })),
+        ..Default::default()
     }
     .into()
 }
@@ -507,6 +545,11 @@ impl Settings for AssistantSettings {
                 &mut settings.default_model,
                 value.default_model.map(Into::into),
             );
+            merge(&mut settings.auto_trim_policies, value.auto_trim_policies);
+            merge(
+                &mut settings.project_default_prompts,
+                value.project_default_prompts,
+            );
         }
 
         Ok(settings)
@@ -564,6 +607,8 @@ mod tests {
                             dock: None,
                             default_width: None,
                             default_height: None,
+                            auto_trim_policies: None,
+                            project_default_prompts: None,
                         }),
                     )
                 },
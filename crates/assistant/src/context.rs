@@ -24,8 +24,8 @@ use language::{
     Point, ToOffset,
 };
 use language_model::{
-    LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage, LanguageModelTool,
-    Role,
+    LanguageModel, LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage,
+    LanguageModelTool, Role,
 };
 use open_ai::Model as OpenAiModel;
 use paths::contexts_dir;
@@ -42,11 +42,17 @@ use std::{
     time::{Duration, Instant},
 };
 use telemetry_events::AssistantKind;
-use ui::{SharedString, WindowContext};
+use ui::{IconName, SharedString, WindowContext};
 use util::{post_inc, ResultExt, TryFutureExt};
 use uuid::Uuid;
 use workspace::Workspace;
 
+mod token_budget;
+pub use token_budget::*;
+
+/// The title of forks of contexts that weren't summarized yet.
+const FORKED_CONTEXT_TITLE: &str = "Forked Context";
+
 #[derive(Clone, Eq, PartialEq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct ContextId(String);
 
@@ -86,6 +92,14 @@ pub enum ContextOperation {
         sections: Vec<SlashCommandOutputSection<language::Anchor>>,
         version: clock::Global,
     },
+    /// Changes the icon of the output sections with the given range, or removes them when there
+    /// is no icon.
+    UpdateSlashCommandOutputSection {
+        range: Range<language::Anchor>,
+        icon: Option<IconName>,
+        timestamp: clock::Lamport,
+        version: clock::Global,
+    },
     BufferOperation(language::Operation),
 }
 
@@ -111,6 +125,10 @@ impl ContextOperation {
                             message.status.context("invalid status")?,
                         ),
                         timestamp: id.0,
+                        alternates: MessageAlternates::from_proto(
+                            message.alternates,
+                            message.alternate_ix,
+                        ),
                     },
                     version: language::proto::deserialize_version(&insert.version),
                 })
@@ -125,6 +143,10 @@ impl ContextOperation {
                     timestamp: language::proto::deserialize_timestamp(
                         update.timestamp.context("invalid timestamp")?,
                     ),
+                    alternates: MessageAlternates::from_proto(
+                        update.alternates,
+                        update.alternate_ix,
+                    ),
                 },
                 version: language::proto::deserialize_version(&update.version),
             }),
@@ -162,6 +184,21 @@ impl ContextOperation {
                     version: language::proto::deserialize_version(&finished.version),
                 })
             }
+            proto::context_operation::Variant::UpdateSlashCommandOutputSection(update) => {
+                Ok(Self::UpdateSlashCommandOutputSection {
+                    range: language::proto::deserialize_anchor_range(
+                        update.range.context("invalid range")?,
+                    )?,
+                    icon: update
+                        .icon_name
+                        .map(|icon_name| icon_name.parse())
+                        .transpose()?,
+                    timestamp: language::proto::deserialize_timestamp(
+                        update.timestamp.context("invalid timestamp")?,
+                    ),
+                    version: language::proto::deserialize_version(&update.version),
+                })
+            }
             proto::context_operation::Variant::BufferOperation(op) => Ok(Self::BufferOperation(
                 language::proto::deserialize_operation(
                     op.operation.context("invalid buffer operation")?,
@@ -184,6 +221,8 @@ impl ContextOperation {
                             start: Some(language::proto::serialize_anchor(&anchor.start)),
                             role: metadata.role.to_proto() as i32,
                             status: Some(metadata.status.to_proto()),
+                            alternates: metadata.alternates.others.clone(),
+                            alternate_ix: metadata.alternates.current_ix as u32,
                         }),
                         version: language::proto::serialize_version(version),
                     },
@@ -200,6 +239,8 @@ impl ContextOperation {
                         role: metadata.role.to_proto() as i32,
                         status: Some(metadata.status.to_proto()),
                         timestamp: Some(language::proto::serialize_timestamp(metadata.timestamp)),
+                        alternates: metadata.alternates.others.clone(),
+                        alternate_ix: metadata.alternates.current_ix as u32,
                         version: language::proto::serialize_version(version),
                     },
                 )),
@@ -243,6 +284,26 @@ impl ContextOperation {
                     },
                 )),
             },
+            Self::UpdateSlashCommandOutputSection {
+                range,
+                icon,
+                timestamp,
+                version,
+            } => proto::ContextOperation {
+                variant: Some(
+                    proto::context_operation::Variant::UpdateSlashCommandOutputSection(
+                        proto::context_operation::UpdateSlashCommandOutputSection {
+                            timestamp: Some(language::proto::serialize_timestamp(*timestamp)),
+                            range: Some(language::proto::serialize_anchor_range(range.clone())),
+                            icon_name: icon.map(|icon| {
+                                let icon_name: &'static str = icon.into();
+                                icon_name.to_string()
+                            }),
+                            version: language::proto::serialize_version(version),
+                        },
+                    ),
+                ),
+            },
             Self::BufferOperation(operation) => proto::ContextOperation {
                 variant: Some(proto::context_operation::Variant::BufferOperation(
                     proto::context_operation::BufferOperation {
@@ -259,6 +320,7 @@ impl ContextOperation {
             Self::UpdateMessage { metadata, .. } => metadata.timestamp,
             Self::UpdateSummary { summary, .. } => summary.timestamp,
             Self::SlashCommandFinished { id, .. } => id.0,
+            Self::UpdateSlashCommandOutputSection { timestamp, .. } => *timestamp,
             Self::BufferOperation(_) => {
                 panic!("reading the timestamp of a buffer operation is not supported")
             }
@@ -271,7 +333,8 @@ impl ContextOperation {
             Self::InsertMessage { version, .. }
             | Self::UpdateMessage { version, .. }
             | Self::UpdateSummary { version, .. }
-            | Self::SlashCommandFinished { version, .. } => version,
+            | Self::SlashCommandFinished { version, .. }
+            | Self::UpdateSlashCommandOutputSection { version, .. } => version,
             Self::BufferOperation(_) => {
                 panic!("reading the version of a buffer operation is not supported")
             }
@@ -317,6 +380,61 @@ pub struct MessageMetadata {
     pub role: Role,
     status: MessageStatus,
     timestamp: clock::Lamport,
+    #[serde(default, skip_serializing_if = "MessageAlternates::is_empty")]
+    alternates: MessageAlternates,
+}
+
+/// The responses generated for the same request, of which the one in the buffer is shown.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MessageAlternates {
+    /// The responses that aren't shown, in the order they were generated.
+    others: Vec<String>,
+    /// The position of the shown response among all of them.
+    current_ix: usize,
+}
+
+impl MessageAlternates {
+    fn from_proto(others: Vec<String>, current_ix: u32) -> Self {
+        let current_ix = (current_ix as usize).min(others.len());
+        Self { others, current_ix }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.others.is_empty()
+    }
+
+    pub fn count(&self) -> usize {
+        self.others.len() + 1
+    }
+
+    pub fn current_ix(&self) -> usize {
+        self.current_ix
+    }
+
+    /// Keeps the shown response, making room for a new one after all the others.
+    fn push(&mut self, shown_text: String) {
+        self.others.insert(self.current_ix, shown_text);
+        self.current_ix = self.others.len();
+    }
+
+    /// Keeps the shown response and takes the one at the given position to be shown instead.
+    fn select(&mut self, ix: usize, shown_text: String) -> Option<String> {
+        if ix == self.current_ix || ix >= self.count() {
+            return None;
+        }
+        self.others.insert(self.current_ix, shown_text);
+        self.current_ix = ix;
+        Some(self.others.remove(ix))
+    }
+}
+
+/// The context and message that a context was forked from.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextParent {
+    pub id: ContextId,
+    pub message_id: MessageId,
+    /// The summary of the parent context when it was forked.
+    pub title: String,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -327,6 +445,9 @@ pub struct Message {
     pub anchor: language::Anchor,
     pub role: Role,
     pub status: MessageStatus,
+    /// The number of responses generated for this message, and which one is shown.
+    pub alternate_count: usize,
+    pub alternate_ix: usize,
 }
 
 impl Message {
@@ -620,6 +741,7 @@ pub struct Context {
     workflow_steps: Vec<WorkflowStep>,
     project: Option<Model<Project>>,
     prompt_builder: Arc<PromptBuilder>,
+    parent: Option<ContextParent>,
 }
 
 impl EventEmitter<ContextEvent> for Context {}
@@ -694,6 +816,7 @@ impl Context {
             language_registry,
             workflow_steps: Vec::new(),
             prompt_builder,
+            parent: None,
         };
 
         let first_message_id = MessageId(clock::Lamport {
@@ -710,6 +833,7 @@ impl Context {
                 role: Role::User,
                 status: MessageStatus::Done,
                 timestamp: first_message_id.0,
+                alternates: MessageAlternates::default(),
             },
         );
         this.message_anchors.push(message);
@@ -755,13 +879,50 @@ impl Context {
                     }
                 })
                 .collect(),
+            parent: self.parent.clone(),
         }
     }
 
+    /// Serializes the messages up to and including the given one as a new context, which links
+    /// back to this one.
+    pub fn fork(&self, message_id: MessageId, cx: &AppContext) -> Option<SavedContext> {
+        let (_, content_range) = self.message_with_content_range(message_id, cx)?;
+        let end = content_range.end;
+        let title = self
+            .summary
+            .as_ref()
+            .map(|summary| summary.text.clone())
+            .filter(|text| !text.is_empty())
+            .unwrap_or_else(|| FORKED_CONTEXT_TITLE.to_string());
+
+        let mut saved_context = self.serialize(cx);
+        saved_context.id = Some(ContextId::new());
+        saved_context.text.truncate(end);
+        saved_context
+            .messages
+            .retain(|message| message.start <= end);
+        for message in &mut saved_context.messages {
+            // Responses being streamed into this context won't be streamed into the fork.
+            if message.metadata.status == MessageStatus::Pending {
+                message.metadata.status = MessageStatus::Done;
+            }
+        }
+        saved_context
+            .slash_command_output_sections
+            .retain(|section| section.range.end <= end);
+        saved_context.summary = title.clone();
+        saved_context.parent = Some(ContextParent {
+            id: self.id.clone(),
+            message_id,
+            title,
+        });
+        Some(saved_context)
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn deserialize(
         saved_context: SavedContext,
-        path: PathBuf,
+        path: Option<PathBuf>,
         language_registry: Arc<LanguageRegistry>,
         prompt_builder: Arc<PromptBuilder>,
         project: Option<Model<Project>>,
@@ -779,7 +940,8 @@ impl Context {
             telemetry,
             cx,
         );
-        this.path = Some(path);
+        this.path = path;
+        this.parent = saved_context.parent.clone();
         this.buffer.update(cx, |buffer, cx| {
             buffer.set_text(saved_context.text.as_str(), cx)
         });
@@ -938,6 +1100,9 @@ impl Context {
                         });
                     }
                 }
+                ContextOperation::UpdateSlashCommandOutputSection { range, icon, .. } => {
+                    self.update_slash_command_output_section(&range, icon, cx);
+                }
                 ContextOperation::BufferOperation(_) => unreachable!(),
             }
 
@@ -992,6 +1157,14 @@ impl Context {
                         observed_start && observed_end
                     })
             }
+            ContextOperation::UpdateSlashCommandOutputSection { range, .. } => {
+                let version = &self.buffer.read(cx).version;
+                [range.start, range.end].iter().all(|anchor| {
+                    *anchor == language::Anchor::MIN
+                        || *anchor == language::Anchor::MAX
+                        || version.observed(anchor.timestamp)
+                })
+            }
             ContextOperation::BufferOperation(_) => {
                 panic!("buffer operations should always be applied")
             }
@@ -1007,6 +1180,10 @@ impl Context {
         &self.buffer
     }
 
+    pub fn parent(&self) -> Option<&ContextParent> {
+        self.parent.as_ref()
+    }
+
     pub fn path(&self) -> Option<&Path> {
         self.path.as_deref()
     }
@@ -1591,10 +1768,121 @@ impl Context {
             .insert_message_after(assistant_message.id, Role::User, MessageStatus::Done, cx)
             .unwrap();
 
+        self.stream_response(request, assistant_message.id, model, cx);
+        Some(user_message)
+    }
+
+    /// Whether responses can be regenerated or switched, which only the host does. Both
+    /// replace the text of a message along with its alternates, and doing so concurrently on
+    /// several replicas would leave the text out of sync with the alternates.
+    pub fn can_switch_responses(&self) -> bool {
+        self.replica_id() == ReplicaId::default()
+    }
+
+    /// Generates a new response for an assistant message, keeping the current one as an
+    /// alternate that can be shown again.
+    pub fn regenerate(&mut self, message_id: MessageId, cx: &mut ModelContext<Self>) -> bool {
+        if !self.can_switch_responses() {
+            return false;
+        }
+        let Some(provider) = LanguageModelRegistry::read_global(cx).active_provider() else {
+            return false;
+        };
+        let Some(model) = LanguageModelRegistry::read_global(cx).active_model() else {
+            return false;
+        };
+        if !provider.is_authenticated(cx) {
+            log::info!("completion provider has no credentials");
+            return false;
+        }
+        let Some((message, content_range)) = self.message_with_content_range(message_id, cx) else {
+            return false;
+        };
+        if message.role != Role::Assistant || message.status == MessageStatus::Pending {
+            return false;
+        }
+
+        let request = self.completion_request_for_messages(
+            self.messages(cx)
+                .take_while(|message| message.id != message_id)
+                .collect(),
+            cx,
+        );
+        let shown_text = self.buffer.update(cx, |buffer, cx| {
+            let text = buffer.text_for_range(content_range.clone()).collect();
+            buffer.edit([(content_range, "")], None, cx);
+            text
+        });
+        self.update_metadata(message_id, cx, |metadata| {
+            metadata.alternates.push(shown_text);
+            metadata.status = MessageStatus::Pending;
+        });
+        self.stream_response(request, message_id, model, cx);
+        true
+    }
+
+    /// Shows the response at the given position among the ones generated for a message.
+    pub fn select_alternate(
+        &mut self,
+        message_id: MessageId,
+        ix: usize,
+        cx: &mut ModelContext<Self>,
+    ) -> bool {
+        if !self.can_switch_responses() {
+            return false;
+        }
+        let Some((message, content_range)) = self.message_with_content_range(message_id, cx) else {
+            return false;
+        };
+        if message.status == MessageStatus::Pending {
+            return false;
+        }
+        let Some(metadata) = self.messages_metadata.get(&message_id) else {
+            return false;
+        };
+
+        let mut alternates = metadata.alternates.clone();
+        let shown_text = self
+            .buffer
+            .read(cx)
+            .text_for_range(content_range.clone())
+            .collect();
+        let Some(new_text) = alternates.select(ix, shown_text) else {
+            return false;
+        };
+        self.buffer.update(cx, |buffer, cx| {
+            buffer.edit([(content_range, new_text)], None, cx);
+        });
+        self.update_metadata(message_id, cx, |metadata| metadata.alternates = alternates);
+        true
+    }
+
+    /// Returns a message along with the range of its text, excluding the newline separating it
+    /// from the next message.
+    fn message_with_content_range(
+        &self,
+        message_id: MessageId,
+        cx: &AppContext,
+    ) -> Option<(Message, Range<usize>)> {
+        let mut messages = self.messages(cx).peekable();
+        let message = messages.find(|message| message.id == message_id)?;
+        let mut content_range = message.offset_range.clone();
+        if messages.peek().is_some() {
+            content_range.end = content_range.end.saturating_sub(1).max(content_range.start);
+        }
+        Some((message, content_range))
+    }
+
+    fn stream_response(
+        &mut self,
+        request: LanguageModelRequest,
+        assistant_message_id: MessageId,
+        model: Arc<dyn LanguageModel>,
+        cx: &mut ModelContext<Self>,
+    ) {
         let task = cx.spawn({
             |this, mut cx| async move {
                 let stream = model.stream_completion(request, &cx);
-                let assistant_message_id = assistant_message.id;
                 let mut response_latency = None;
                 let stream_completion = async {
                     let request_start = Instant::now();
@@ -1685,13 +1973,19 @@ impl Context {
             id: post_inc(&mut self.completion_count),
             _task: task,
         });
-
-        Some(user_message)
     }
 
     pub fn to_completion_request(&self, cx: &AppContext) -> LanguageModelRequest {
-        let messages = self
-            .messages(cx)
+        self.completion_request_for_messages(self.messages(cx).collect(), cx)
+    }
+
+    fn completion_request_for_messages(
+        &self,
+        messages: Vec<Message>,
+        cx: &AppContext,
+    ) -> LanguageModelRequest {
+        let messages = messages
+            .into_iter()
             .filter(|message| matches!(message.status, MessageStatus::Done))
             .map(|message| message.to_request_message(self.buffer.read(cx)));
 
@@ -1737,6 +2031,49 @@ impl Context {
         }
     }
 
+    /// Changes the icon of the slash command output sections with the given range, or removes
+    /// them when there is no icon.
+    pub fn set_slash_command_output_section_icon(
+        &mut self,
+        range: Range<language::Anchor>,
+        icon: Option<IconName>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let version = self.version.clone();
+        let timestamp = self.next_timestamp();
+        self.update_slash_command_output_section(&range, icon, cx);
+        self.push_op(
+            ContextOperation::UpdateSlashCommandOutputSection {
+                range,
+                icon,
+                timestamp,
+                version,
+            },
+            cx,
+        );
+    }
+
+    fn update_slash_command_output_section(
+        &mut self,
+        range: &Range<language::Anchor>,
+        icon: Option<IconName>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        match icon {
+            Some(icon) => {
+                for section in &mut self.slash_command_output_sections {
+                    if section.range == *range {
+                        section.icon = icon;
+                    }
+                }
+            }
+            None => self
+                .slash_command_output_sections
+                .retain(|section| section.range != *range),
+        }
+        cx.notify();
+    }
+
     fn insert_message_after(
         &mut self,
         message_id: MessageId,
@@ -1778,6 +2115,7 @@ impl Context {
                 role,
                 status,
                 timestamp: anchor.id.0,
+                alternates: MessageAlternates::default(),
             };
             self.insert_message(anchor.clone(), metadata.clone(), cx);
             self.push_op(
@@ -1842,6 +2180,7 @@ impl Context {
                 role,
                 status: MessageStatus::Done,
                 timestamp: suffix.id.0,
+                alternates: MessageAlternates::default(),
             };
             self.insert_message(suffix.clone(), suffix_metadata.clone(), cx);
             self.push_op(
@@ -1891,6 +2230,7 @@ impl Context {
                         role,
                         status: MessageStatus::Done,
                         timestamp: selection.id.0,
+                        alternates: MessageAlternates::default(),
                     };
                     self.insert_message(selection.clone(), selection_metadata.clone(), cx);
                     self.push_op(
@@ -2083,6 +2423,8 @@ impl Context {
                     anchor: message_anchor.start,
                     role: metadata.role,
                     status: metadata.status.clone(),
+                    alternate_count: metadata.alternates.count(),
+                    alternate_ix: metadata.alternates.current_ix(),
                 });
             }
             None
@@ -2224,6 +2566,8 @@ pub struct SavedContext {
     pub summary: String,
     pub slash_command_output_sections:
         Vec<assistant_slash_command::SlashCommandOutputSection<usize>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<ContextParent>,
 }
 
 impl SavedContext {
@@ -2283,6 +2627,7 @@ impl SavedContext {
                         role: message.metadata.role,
                         status: message.metadata.status,
                         timestamp: message.metadata.timestamp,
+                        alternates: message.metadata.alternates,
                     },
                     version: version.clone(),
                 });
@@ -2299,6 +2644,7 @@ impl SavedContext {
                     role: metadata.role,
                     status: metadata.status,
                     timestamp,
+                    alternates: metadata.alternates,
                 },
                 version: version.clone(),
             });
@@ -2393,12 +2739,14 @@ impl SavedContextV0_3_0 {
                             role: metadata.role,
                             status: metadata.status.clone(),
                             timestamp,
+                            alternates: MessageAlternates::default(),
                         },
                     })
                 })
                 .collect(),
             summary: self.summary,
             slash_command_output_sections: self.slash_command_output_sections,
+            parent: None,
         }
     }
 }
@@ -2467,6 +2815,18 @@ pub struct SavedContextMetadata {
     pub title: String,
     pub path: PathBuf,
     pub mtime: chrono::DateTime<chrono::Local>,
+    pub id: Option<ContextId>,
+    pub forked_from: Option<SavedContextParent>,
+}
+
+/// The context that a saved context was forked from.
+#[derive(Clone)]
+pub struct SavedContextParent {
+    pub id: ContextId,
+    /// The parent's title, which is kept up to date for as long as the parent is saved.
+    pub title: String,
+    /// The path the parent is saved at, if it's still saved.
+    pub path: Option<PathBuf>,
 }
 
 #[cfg(test)]
@@ -3200,6 +3560,133 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_regenerating_and_forking(cx: &mut TestAppContext) {
+        let settings_store = cx.update(SettingsStore::test);
+        cx.set_global(settings_store);
+        cx.update(LanguageModelRegistry::test);
+        let model = cx.read(|cx| {
+            LanguageModelRegistry::read_global(cx)
+                .active_model()
+                .unwrap()
+        });
+        cx.update(assistant_panel::init);
+        let registry = Arc::new(LanguageRegistry::test(cx.executor()));
+        let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+        let context = cx.new_model(|cx| {
+            Context::local(registry.clone(), None, None, prompt_builder.clone(), cx)
+        });
+        let buffer = context.read_with(cx, |context, _| context.buffer.clone());
+        let user_message_id = context.read_with(cx, |context, _| context.message_anchors[0].id);
+        buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "Hello")], None, cx));
+
+        context.update(cx, |context, cx| context.assist(cx));
+        cx.run_until_parked();
+        model
+            .as_fake()
+            .stream_last_completion_response("One".into());
+        model.as_fake().end_last_completion_stream();
+        cx.run_until_parked();
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            "Hello\nOne\n"
+        );
+        let assistant_message_id =
+            context.read_with(cx, |context, _| context.message_anchors[1].id);
+
+        assert!(context.update(cx, |context, cx| context
+            .regenerate(assistant_message_id, cx)));
+        assert_eq!(buffer.read_with(cx, |buffer, _| buffer.text()), "Hello\n\n");
+        cx.run_until_parked();
+        model
+            .as_fake()
+            .stream_last_completion_response("Two".into());
+        model.as_fake().end_last_completion_stream();
+        cx.run_until_parked();
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            "Hello\nTwo\n"
+        );
+        assert_eq!(alternates(&context, assistant_message_id, cx), (1, 2));
+
+        assert!(context.update(cx, |context, cx| {
+            context.select_alternate(assistant_message_id, 0, cx)
+        }));
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            "Hello\nOne\n"
+        );
+        assert_eq!(alternates(&context, assistant_message_id, cx), (0, 2));
+
+        // Alternates are preserved when the context is saved and loaded again.
+        let serialized_context = context.read_with(cx, |context, cx| context.serialize(cx));
+        let deserialized_context = cx.new_model(|cx| {
+            Context::deserialize(
+                serialized_context,
+                None,
+                registry.clone(),
+                prompt_builder.clone(),
+                None,
+                None,
+                cx,
+            )
+        });
+        assert_eq!(
+            alternates(&deserialized_context, assistant_message_id, cx),
+            (0, 2)
+        );
+        assert!(deserialized_context.update(cx, |context, cx| {
+            context.select_alternate(assistant_message_id, 1, cx)
+        }));
+        assert_eq!(
+            deserialized_context.read_with(cx, |context, cx| context.buffer.read(cx).text()),
+            "Hello\nTwo\n"
+        );
+
+        // Forking keeps the messages up to and including the given one.
+        let context_id = context.read_with(cx, |context, _| context.id().clone());
+        let fork = context
+            .read_with(cx, |context, cx| context.fork(assistant_message_id, cx))
+            .unwrap();
+        assert!(fork.id.as_ref() != Some(&context_id));
+        assert_eq!(fork.text, "Hello\nOne");
+        assert_eq!(fork.messages.len(), 2);
+        assert!(
+            fork.parent
+                == Some(ContextParent {
+                    id: context_id.clone(),
+                    message_id: assistant_message_id,
+                    title: FORKED_CONTEXT_TITLE.into(),
+                })
+        );
+
+        let fork = context
+            .read_with(cx, |context, cx| context.fork(user_message_id, cx))
+            .unwrap();
+        let forked_context = cx.new_model(|cx| {
+            Context::deserialize(
+                fork,
+                None,
+                registry.clone(),
+                prompt_builder.clone(),
+                None,
+                None,
+                cx,
+            )
+        });
+        assert_eq!(
+            forked_context.read_with(cx, |context, cx| context.buffer.read(cx).text()),
+            "Hello"
+        );
+        assert_eq!(
+            cx.read(|cx| messages(&forked_context, cx)),
+            [(user_message_id, Role::User, 0..5)]
+        );
+        assert!(forked_context.read_with(cx, |context, _| {
+            context.parent().map(|parent| &parent.id) == Some(&context_id)
+        }));
+    }
+
     #[gpui::test(iterations = 100)]
     async fn test_random_context_collaboration(cx: &mut TestAppContext, mut rng: StdRng) {
         let min_peers = env::var("MIN_PEERS")
@@ -3479,6 +3966,21 @@ mod tests {
             .collect()
     }
 
+    fn alternates(
+        context: &Model<Context>,
+        message_id: MessageId,
+        cx: &TestAppContext,
+    ) -> (usize, usize) {
+        cx.read(|cx| {
+            let message = context
+                .read(cx)
+                .messages(cx)
+                .find(|message| message.id == message_id)
+                .unwrap();
+            (message.alternate_ix, message.alternate_count)
+        })
+    }
+
     #[derive(Clone)]
     struct FakeSlashCommand(String);
 
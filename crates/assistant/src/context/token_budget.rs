@@ -0,0 +1,802 @@
+//! Breaks down the tokens used by a context, and trims contexts that don't fit within the token
+//! limit of the active model.
+
+use std::{ops::Range, path::PathBuf, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use assistant_slash_command::SlashCommandOutputSection;
+use futures::{future, FutureExt, StreamExt};
+use gpui::{AppContext, AsyncAppContext, Context as _, ModelContext, SharedString, Task};
+use language::{Buffer, LanguageRegistry, OffsetRangeExt, ParseStatus};
+use language_model::{
+    LanguageModel, LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage, Role,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ui::IconName;
+
+use super::{Context, MessageId, MessageStatus};
+
+/// The number of most recent messages that are never summarized.
+const KEPT_MESSAGE_COUNT: usize = 2;
+
+const SUMMARIZE_EARLIER_MESSAGES_PROMPT: &str = "Summarize the conversation so far, keeping \
+    any details, decisions and code that are needed to continue it. Respond with the summary only.";
+
+const EARLIER_MESSAGES_SUMMARY_HEADER: &str = "Summary of the earlier conversation:";
+
+/// A way of reducing the number of tokens used by a context.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TrimmingPolicy {
+    /// Remove the oldest slash command output from the context.
+    DropOldestToolOutput,
+    /// Replace all but the most recent messages with a summary written by the model.
+    SummarizeEarlierMessages,
+    /// Replace the contents of the oldest inserted file with its outline.
+    TruncateFilesToOutline,
+}
+
+impl TrimmingPolicy {
+    pub const ALL: [TrimmingPolicy; 3] = [
+        TrimmingPolicy::DropOldestToolOutput,
+        TrimmingPolicy::TruncateFilesToOutline,
+        TrimmingPolicy::SummarizeEarlierMessages,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TrimmingPolicy::DropOldestToolOutput => "Drop Oldest Tool Output",
+            TrimmingPolicy::SummarizeEarlierMessages => "Summarize Earlier Messages",
+            TrimmingPolicy::TruncateFilesToOutline => "Truncate Files to Outline",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TokenBudgetSectionKind {
+    /// The text of a message, excluding the slash command outputs in it.
+    Message {
+        id: MessageId,
+        role: Role,
+    },
+    SlashCommandOutput {
+        icon: IconName,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenBudgetSection {
+    pub kind: TokenBudgetSectionKind,
+    pub label: SharedString,
+    pub range: Range<usize>,
+    pub token_count: usize,
+}
+
+/// The number of tokens used by each part of a context.
+#[derive(Clone, Debug)]
+pub struct TokenBudget {
+    pub sections: Vec<TokenBudgetSection>,
+    pub max_token_count: usize,
+}
+
+impl TokenBudget {
+    pub fn token_count(&self) -> usize {
+        self.sections
+            .iter()
+            .map(|section| section.token_count)
+            .sum()
+    }
+
+    pub fn is_exceeded(&self) -> bool {
+        self.token_count() > self.max_token_count
+    }
+}
+
+impl Context {
+    /// Counts the tokens of every message and slash command output that will be sent to the
+    /// active model.
+    pub fn token_budget(&self, cx: &AppContext) -> Task<Result<TokenBudget>> {
+        let Some(model) = LanguageModelRegistry::read_global(cx).active_model() else {
+            return Task::ready(Err(anyhow!("no active model")));
+        };
+
+        let buffer = self.buffer.read(cx);
+        let output_sections = self.top_level_output_sections(buffer);
+        let mut sections = Vec::new();
+        let mut requests = Vec::new();
+        for message in self.messages(cx) {
+            if message.status != MessageStatus::Done {
+                continue;
+            }
+
+            let message_ix = sections.len();
+            let mut message_text = String::new();
+            let mut offset = message.offset_range.start;
+            for section in output_sections.iter().filter(|section| {
+                section.range.start >= message.offset_range.start
+                    && section.range.end <= message.offset_range.end
+            }) {
+                message_text.extend(buffer.text_for_range(offset..section.range.start));
+                offset = section.range.end;
+                sections.push(TokenBudgetSection {
+                    kind: TokenBudgetSectionKind::SlashCommandOutput { icon: section.icon },
+                    label: section.label.clone(),
+                    range: section.range.clone(),
+                    token_count: 0,
+                });
+                requests.push(count_request(
+                    message.role,
+                    buffer.text_for_range(section.range.clone()).collect(),
+                ));
+            }
+            message_text.extend(buffer.text_for_range(offset..message.offset_range.end));
+
+            let label = match message.role {
+                Role::User => "You",
+                Role::Assistant => "Assistant",
+                Role::System => "System",
+            };
+            sections.insert(
+                message_ix,
+                TokenBudgetSection {
+                    kind: TokenBudgetSectionKind::Message {
+                        id: message.id,
+                        role: message.role,
+                    },
+                    label: label.into(),
+                    range: message.offset_range.clone(),
+                    token_count: 0,
+                },
+            );
+            requests.insert(message_ix, count_request(message.role, message_text));
+        }
+
+        let token_counts = requests
+            .into_iter()
+            .map(|request| model.count_tokens(request, cx))
+            .collect::<Vec<_>>();
+        let max_token_count = model.max_token_count();
+        cx.background_executor().spawn(async move {
+            let token_counts = future::try_join_all(token_counts).await?;
+            for (section, token_count) in sections.iter_mut().zip(token_counts) {
+                section.token_count = token_count;
+            }
+            Ok(TokenBudget {
+                sections,
+                max_token_count,
+            })
+        })
+    }
+
+    /// Applies the given policy once, returning whether anything could be trimmed.
+    pub fn trim(
+        &mut self,
+        policy: TrimmingPolicy,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<bool>> {
+        match policy {
+            TrimmingPolicy::DropOldestToolOutput => {
+                Task::ready(Ok(self.drop_oldest_output_section(cx)))
+            }
+            TrimmingPolicy::SummarizeEarlierMessages => self.summarize_earlier_messages(cx),
+            TrimmingPolicy::TruncateFilesToOutline => self.truncate_oldest_file_to_outline(cx),
+        }
+    }
+
+    /// Applies the given policies in order until the context fits within the token limit of the
+    /// active model, moving on to the next policy when one has nothing left to trim.
+    ///
+    /// Resolves to whether the context fits.
+    pub fn trim_to_budget(
+        &mut self,
+        policies: Vec<TrimmingPolicy>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<bool>> {
+        let Some(model) = LanguageModelRegistry::read_global(cx).active_model() else {
+            return Task::ready(Err(anyhow!("no active model")));
+        };
+
+        cx.spawn(|this, mut cx| async move {
+            let mut policies = policies.into_iter().peekable();
+            loop {
+                let request = this.update(&mut cx, |this, cx| this.to_completion_request(cx))?;
+                let token_count = cx.update(|cx| model.count_tokens(request, cx))?.await?;
+                if token_count <= model.max_token_count() {
+                    return Ok(true);
+                }
+
+                let Some(policy) = policies.peek().copied() else {
+                    return Ok(false);
+                };
+                let trimmed = this
+                    .update(&mut cx, |this, cx| this.trim(policy, cx))?
+                    .await?;
+                if !trimmed {
+                    policies.next();
+                }
+            }
+        })
+    }
+
+    /// Returns the slash command output sections that aren't nested in other ones.
+    fn top_level_output_sections(&self, buffer: &Buffer) -> Vec<SlashCommandOutputSection<usize>> {
+        let mut sections = Vec::<SlashCommandOutputSection<usize>>::new();
+        for section in &self.slash_command_output_sections {
+            let range = section.range.to_offset(buffer);
+            if range.is_empty() {
+                continue;
+            }
+            if sections
+                .last()
+                .map_or(true, |last| range.start >= last.range.end)
+            {
+                sections.push(SlashCommandOutputSection {
+                    range,
+                    icon: section.icon,
+                    label: section.label.clone(),
+                });
+            }
+        }
+        sections
+    }
+
+    fn drop_oldest_output_section(&mut self, cx: &mut ModelContext<Self>) -> bool {
+        let buffer = self.buffer.read(cx);
+        let Some(range) = self
+            .top_level_output_sections(buffer)
+            .into_iter()
+            .next()
+            .map(|section| section.range)
+        else {
+            return false;
+        };
+
+        let dropped_sections = self
+            .slash_command_output_sections
+            .iter()
+            .filter(|section| {
+                let section_range = section.range.to_offset(buffer);
+                section_range.start >= range.start && section_range.end <= range.end
+            })
+            .map(|section| section.range.clone())
+            .collect::<Vec<_>>();
+        for section_range in dropped_sections {
+            self.set_slash_command_output_section_icon(section_range, None, cx);
+        }
+        self.buffer.update(cx, |buffer, cx| {
+            buffer.edit([(range, "")], None, cx);
+        });
+        true
+    }
+
+    /// Replaces the contents of the oldest file section that has an outline, skipping the ones
+    /// whose language is unknown or that have no outline.
+    fn truncate_oldest_file_to_outline(
+        &mut self,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<bool>> {
+        let buffer = self.buffer.read(cx);
+        let files = self
+            .slash_command_output_sections
+            .iter()
+            .filter_map(|section| {
+                if section.icon != IconName::File {
+                    return None;
+                }
+                let range = section.range.to_offset(buffer);
+                let text = buffer.text_for_range(range.clone()).collect::<String>();
+                let content_range = fenced_content_range(&text)?;
+                Some((
+                    section.range.clone(),
+                    section.label.clone(),
+                    text[content_range.clone()].to_string(),
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        let language_registry = self.language_registry.clone();
+        cx.spawn(|this, mut cx| async move {
+            for (section_range, label, content) in files {
+                let Some(outline) =
+                    file_outline(&label, content, &language_registry, &mut cx).await?
+                else {
+                    continue;
+                };
+
+                let truncated = this.update(&mut cx, |this, cx| {
+                    let buffer = this.buffer.read(cx);
+                    let range = section_range.to_offset(buffer);
+                    let text = buffer.text_for_range(range.clone()).collect::<String>();
+                    let Some(content_range) = fenced_content_range(&text) else {
+                        return false;
+                    };
+                    let content_range =
+                        range.start + content_range.start..range.start + content_range.end;
+                    this.buffer.update(cx, |buffer, cx| {
+                        buffer.edit([(content_range, outline)], None, cx);
+                    });
+                    // Outlines aren't file sections, so this one won't be truncated again.
+                    this.set_slash_command_output_section_icon(
+                        section_range,
+                        Some(IconName::ListTree),
+                        cx,
+                    );
+                    true
+                })?;
+                if truncated {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        })
+    }
+
+    /// Summarizes as many of the earlier messages as fit within the token limit of the model,
+    /// along with the prompt asking for the summary.
+    fn summarize_earlier_messages(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<bool>> {
+        let Some(model) = LanguageModelRegistry::read_global(cx).active_model() else {
+            return Task::ready(Err(anyhow!("no active model")));
+        };
+
+        let messages = self.messages(cx).collect::<Vec<_>>();
+        let Some(summarized_count) = messages.len().checked_sub(KEPT_MESSAGE_COUNT) else {
+            return Task::ready(Ok(false));
+        };
+        let earlier_messages = &messages[..summarized_count];
+        if earlier_messages.len() < 2
+            || earlier_messages
+                .iter()
+                .any(|message| message.status == MessageStatus::Pending)
+        {
+            return Task::ready(Ok(false));
+        }
+
+        // Keep the newline separating each message from the one that follows it.
+        let buffer = self.buffer.read(cx);
+        let message_ranges = earlier_messages
+            .iter()
+            .map(|message| {
+                buffer.anchor_after(message.offset_range.start)
+                    ..buffer.anchor_before(message.offset_range.end.saturating_sub(1))
+            })
+            .collect::<Vec<_>>();
+        let request_messages = earlier_messages
+            .iter()
+            .map(|message| {
+                (message.status == MessageStatus::Done).then(|| message.to_request_message(buffer))
+            })
+            .collect::<Vec<_>>();
+        let token_counts = request_messages
+            .iter()
+            .map(|message| match message {
+                Some(message) => {
+                    model.count_tokens(count_request(message.role, message.content.clone()), cx)
+                }
+                None => future::ready(Ok(0)).boxed(),
+            })
+            .collect::<Vec<_>>();
+        let prompt_token_count = model.count_tokens(
+            count_request(Role::User, SUMMARIZE_EARLIER_MESSAGES_PROMPT.into()),
+            cx,
+        );
+
+        cx.spawn(|this, mut cx| async move {
+            let mut token_count = prompt_token_count.await?;
+            let summarized_count = future::try_join_all(token_counts)
+                .await?
+                .into_iter()
+                .take_while(|message_token_count| {
+                    token_count += message_token_count;
+                    token_count <= model.max_token_count()
+                })
+                .count();
+            if summarized_count < 2 {
+                return Ok(false);
+            }
+
+            let range = message_ranges[0].start..message_ranges[summarized_count - 1].end;
+            let request = LanguageModelRequest {
+                messages: request_messages
+                    .into_iter()
+                    .take(summarized_count)
+                    .flatten()
+                    .chain([LanguageModelRequestMessage {
+                        role: Role::User,
+                        content: SUMMARIZE_EARLIER_MESSAGES_PROMPT.into(),
+                    }])
+                    .collect(),
+                stop: Vec::new(),
+                temperature: 1.0,
+            };
+            this.update(&mut cx, |_, cx| {
+                summarize_into_range(model, request, range, cx)
+            })?
+            .await
+        })
+    }
+}
+
+/// Returns the outline of a file, or `None` if its language is unknown or has no outline.
+async fn file_outline(
+    label: &str,
+    content: String,
+    language_registry: &Arc<LanguageRegistry>,
+    cx: &mut AsyncAppContext,
+) -> Result<Option<String>> {
+    let path = path_for_file_section_label(label);
+    let language = match language_registry.language_for_file_path(&path).await {
+        Ok(language) => language,
+        Err(error) => {
+            log::info!("not truncating {label} to its outline: {error}");
+            return Ok(None);
+        }
+    };
+
+    let outline_buffer =
+        cx.new_model(|cx| Buffer::local(content, cx).with_language(language, cx))?;
+    let mut parse_status = outline_buffer.read_with(cx, |buffer, _| buffer.parse_status())?;
+    while *parse_status.borrow() != ParseStatus::Idle {
+        parse_status.changed().await?;
+    }
+    let snapshot = outline_buffer.read_with(cx, |buffer, _| buffer.snapshot())?;
+    Ok(snapshot.outline(None).map(|outline| {
+        outline
+            .items
+            .into_iter()
+            .map(|item| format!("{}{}\n", "    ".repeat(item.depth), item.text))
+            .collect::<String>()
+    }))
+}
+
+fn summarize_into_range(
+    model: Arc<dyn LanguageModel>,
+    request: LanguageModelRequest,
+    range: Range<language::Anchor>,
+    cx: &mut ModelContext<Context>,
+) -> Task<Result<bool>> {
+    cx.spawn(|this, mut cx| async move {
+        let mut chunks = model.stream_completion(request, &cx).await?;
+        let mut summary = String::new();
+        while let Some(chunk) = chunks.next().await {
+            summary.push_str(&chunk?);
+        }
+        let summary = summary.trim();
+        if summary.is_empty() {
+            return Ok(false);
+        }
+
+        this.update(&mut cx, |this, cx| {
+            this.buffer.update(cx, |buffer, cx| {
+                let range = range.to_offset(buffer);
+                buffer.edit(
+                    [(
+                        range,
+                        format!("{EARLIER_MESSAGES_SUMMARY_HEADER}\n{summary}"),
+                    )],
+                    None,
+                    cx,
+                );
+            });
+            true
+        })
+    })
+}
+
+/// Returns the range of the text between the fences of a code block.
+fn fenced_content_range(text: &str) -> Option<Range<usize>> {
+    if !text.starts_with("```") || !text.ends_with("```") {
+        return None;
+    }
+    let start = text.find('\n')? + 1;
+    let end = text.len() - "```".len();
+    (start <= end).then_some(start..end)
+}
+
+/// File sections are labeled with their path, optionally followed by a line range.
+fn path_for_file_section_label(label: &str) -> PathBuf {
+    match label.rsplit_once(':') {
+        Some((path, rows))
+            if rows.split_once('-').map_or(false, |(start, end)| {
+                start.parse::<u32>().is_ok() && end.parse::<u32>().is_ok()
+            }) =>
+        {
+            PathBuf::from(path)
+        }
+        _ => PathBuf::from(label),
+    }
+}
+
+fn count_request(role: Role, content: String) -> LanguageModelRequest {
+    LanguageModelRequest {
+        messages: vec![LanguageModelRequestMessage { role, content }],
+        stop: Vec::new(),
+        temperature: 1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assistant_panel, prompts::PromptBuilder, ContextEvent, ContextOperation};
+    use gpui::TestAppContext;
+    use language::{tree_sitter_rust, Language, LanguageConfig, LanguageMatcher, LanguageRegistry};
+    use parking_lot::Mutex;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_token_budget_and_trimming(cx: &mut TestAppContext) {
+        let settings_store = cx.update(SettingsStore::test);
+        cx.set_global(settings_store);
+        cx.update(LanguageModelRegistry::test);
+        let model = cx.read(|cx| {
+            LanguageModelRegistry::read_global(cx)
+                .active_model()
+                .unwrap()
+        });
+        cx.update(assistant_panel::init);
+        let registry = Arc::new(LanguageRegistry::test(cx.executor()));
+        let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+        let context = cx.new_model(|cx| {
+            Context::local(registry.clone(), None, None, prompt_builder.clone(), cx)
+        });
+        let buffer = context.read_with(cx, |context, _| context.buffer.clone());
+
+        let message_0 = context.read_with(cx, |context, _| context.message_anchors[0].id);
+        context.update(cx, |context, cx| {
+            let message_1 = context
+                .insert_message_after(message_0, Role::Assistant, MessageStatus::Done, cx)
+                .unwrap();
+            let message_2 = context
+                .insert_message_after(message_1.id, Role::User, MessageStatus::Done, cx)
+                .unwrap();
+            context
+                .insert_message_after(message_2.id, Role::Assistant, MessageStatus::Done, cx)
+                .unwrap();
+        });
+        let file = "```rs a.rs\nfn a() {}\n```";
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit(
+                [
+                    (0..0, format!("Question\n{file}")),
+                    (1..1, "Answer".into()),
+                    (2..2, "More".into()),
+                    (3..3, "Reply".into()),
+                ],
+                None,
+                cx,
+            );
+        });
+        context.update(cx, |context, cx| {
+            let buffer = context.buffer.read(cx);
+            let range = buffer.anchor_after(9)..buffer.anchor_before(9 + file.len());
+            context
+                .slash_command_output_sections
+                .push(SlashCommandOutputSection {
+                    range,
+                    icon: IconName::File,
+                    label: "a.rs".into(),
+                });
+        });
+
+        let budget = context
+            .read_with(cx, |context, cx| context.token_budget(cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            budget
+                .sections
+                .iter()
+                .map(|section| (section.label.as_ref(), section.range.clone()))
+                .collect::<Vec<_>>(),
+            [
+                ("You", 0..9 + file.len() + 1),
+                ("a.rs", 9..9 + file.len()),
+                ("Assistant", 34..41),
+                ("You", 41..46),
+                ("Assistant", 46..51),
+            ]
+        );
+
+        assert!(context
+            .update(cx, |context, cx| context
+                .trim(TrimmingPolicy::DropOldestToolOutput, cx))
+            .await
+            .unwrap());
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            "Question\n\nAnswer\nMore\nReply"
+        );
+        assert!(!context
+            .update(cx, |context, cx| context
+                .trim(TrimmingPolicy::DropOldestToolOutput, cx))
+            .await
+            .unwrap());
+
+        let summarize = context.update(cx, |context, cx| {
+            context.trim(TrimmingPolicy::SummarizeEarlierMessages, cx)
+        });
+        cx.run_until_parked();
+        model
+            .as_fake()
+            .stream_last_completion_response("They talked.".into());
+        model.as_fake().end_last_completion_stream();
+        assert!(summarize.await.unwrap());
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            format!("{EARLIER_MESSAGES_SUMMARY_HEADER}\nThey talked.\nMore\nReply")
+        );
+        assert_eq!(
+            context.read_with(cx, |context, cx| context.messages(cx).count()),
+            3
+        );
+    }
+
+    #[gpui::test]
+    async fn test_truncating_files_and_trimming_to_budget(cx: &mut TestAppContext) {
+        let settings_store = cx.update(SettingsStore::test);
+        cx.set_global(settings_store);
+        cx.update(LanguageModelRegistry::test);
+        let model = cx.read(|cx| {
+            LanguageModelRegistry::read_global(cx)
+                .active_model()
+                .unwrap()
+        });
+        cx.update(assistant_panel::init);
+        let registry = Arc::new(LanguageRegistry::test(cx.executor()));
+        registry.add(Arc::new(rust_lang()));
+        let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+        let context = cx.new_model(|cx| {
+            Context::local(registry.clone(), None, None, prompt_builder.clone(), cx)
+        });
+        let buffer = context.read_with(cx, |context, _| context.buffer.clone());
+        let operations = Arc::new(Mutex::new(Vec::new()));
+        cx.update(|cx| {
+            let operations = operations.clone();
+            cx.subscribe(&context, move |_, event, _| {
+                if let ContextEvent::Operation(operation) = event {
+                    operations.lock().push(operation.clone());
+                }
+            })
+            .detach();
+        });
+
+        // The file in an unknown language is skipped, and the one after it is truncated.
+        let notes = "```txt b.txt\nsome notes\n```";
+        let file = "```rs a.rs\nfn a() {\n    1\n}\n```";
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(0..0, format!("Question\n{notes}\n{file}"))], None, cx)
+        });
+        context.update(cx, |context, cx| {
+            let buffer = context.buffer.read(cx);
+            let notes_start = "Question\n".len();
+            let file_start = notes_start + notes.len() + 1;
+            for (range, label) in [
+                (notes_start..notes_start + notes.len(), "b.txt"),
+                (file_start..file_start + file.len(), "a.rs"),
+            ] {
+                context
+                    .slash_command_output_sections
+                    .push(SlashCommandOutputSection {
+                        range: buffer.anchor_after(range.start)..buffer.anchor_before(range.end),
+                        icon: IconName::File,
+                        label: label.into(),
+                    });
+            }
+        });
+
+        assert!(context
+            .update(cx, |context, cx| context
+                .trim(TrimmingPolicy::TruncateFilesToOutline, cx))
+            .await
+            .unwrap());
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            format!("Question\n{notes}\n```rs a.rs\nfn a\n```")
+        );
+        assert_eq!(
+            context.read_with(cx, |context, _| context
+                .slash_command_output_sections
+                .iter()
+                .map(|section| (section.label.clone(), section.icon))
+                .collect::<Vec<_>>()),
+            [
+                ("b.txt".into(), IconName::File),
+                ("a.rs".into(), IconName::ListTree)
+            ]
+        );
+        assert!(operations.lock().iter().any(|operation| matches!(
+            operation,
+            ContextOperation::UpdateSlashCommandOutputSection {
+                icon: Some(IconName::ListTree),
+                ..
+            }
+        )));
+        assert!(!context
+            .update(cx, |context, cx| context
+                .trim(TrimmingPolicy::TruncateFilesToOutline, cx))
+            .await
+            .unwrap());
+
+        // Trimming stops as soon as the context fits.
+        let token_count = context
+            .read_with(cx, |context, cx| {
+                model.count_tokens(context.to_completion_request(cx), cx)
+            })
+            .await
+            .unwrap();
+        model.as_fake().set_max_token_count(token_count - 1);
+        assert!(context
+            .update(cx, |context, cx| context
+                .trim_to_budget(vec![TrimmingPolicy::DropOldestToolOutput], cx))
+            .await
+            .unwrap());
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            "Question\n\n```rs a.rs\nfn a\n```"
+        );
+        assert_eq!(
+            context.read_with(cx, |context, _| context.slash_command_output_sections.len()),
+            1
+        );
+
+        // Trimming fails once the policies have nothing left to trim.
+        model.as_fake().set_max_token_count(1);
+        assert!(!context
+            .update(cx, |context, cx| context.trim_to_budget(
+                vec![
+                    TrimmingPolicy::TruncateFilesToOutline,
+                    TrimmingPolicy::DropOldestToolOutput
+                ],
+                cx
+            ))
+            .await
+            .unwrap());
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            "Question\n\n"
+        );
+        assert!(context.read_with(cx, |context, _| context
+            .slash_command_output_sections
+            .is_empty()));
+    }
+
+    fn rust_lang() -> Language {
+        Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::language()),
+        )
+        .with_outline_query(
+            r#"
+            (function_item
+                "fn" @context
+                name: (_) @name) @item
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fenced_content_range() {
+        let text = "```rs src/main.rs\nfn main() {}\n```";
+        assert_eq!(&text[fenced_content_range(text).unwrap()], "fn main() {}\n");
+        assert_eq!(fenced_content_range("```rs empty.rs\n```"), Some(15..15));
+        assert_eq!(fenced_content_range("not fenced"), None);
+
+        assert_eq!(
+            path_for_file_section_label("src/main.rs:3-10"),
+            PathBuf::from("src/main.rs")
+        );
+        assert_eq!(
+            path_for_file_section_label("C:/src/main.rs"),
+            PathBuf::from("C:/src/main.rs")
+        );
+    }
+}
@@ -1,12 +1,13 @@
 use crate::{
-    prompts::PromptBuilder, Context, ContextEvent, ContextId, ContextOperation, ContextVersion,
-    SavedContext, SavedContextMetadata,
+    prompts::PromptBuilder, Context, ContextEvent, ContextId, ContextOperation, ContextParent,
+    ContextVersion, MessageId, SavedContext, SavedContextMetadata, SavedContextParent,
 };
 use anyhow::{anyhow, Context as _, Result};
 use client::{proto, telemetry::Telemetry, Client, TypedEnvelope};
 use clock::ReplicaId;
+use collections::HashMap;
 use fs::Fs;
-use futures::StreamExt;
+use futures::{Future, StreamExt};
 use fuzzy::StringMatchCandidate;
 use gpui::{
     AppContext, AsyncAppContext, Context as _, EventEmitter, Model, ModelContext, Task, WeakModel,
@@ -15,6 +16,7 @@ use language::LanguageRegistry;
 use paths::contexts_dir;
 use project::Project;
 use regex::Regex;
+use serde::Deserialize;
 use std::{
     cmp::Reverse,
     ffi::OsStr,
@@ -29,6 +31,7 @@ pub fn init(client: &Arc<Client>) {
     client.add_model_message_handler(ContextStore::handle_advertise_contexts);
     client.add_model_request_handler(ContextStore::handle_open_context);
     client.add_model_request_handler(ContextStore::handle_create_context);
+    client.add_model_request_handler(ContextStore::handle_fork_context);
     client.add_model_message_handler(ContextStore::handle_update_context);
     client.add_model_request_handler(ContextStore::handle_synchronize_contexts);
 }
@@ -37,6 +40,8 @@ pub fn init(client: &Arc<Client>) {
 pub struct RemoteContextMetadata {
     pub id: ContextId,
     pub summary: Option<String>,
+    /// The summary of the context this one was forked from.
+    pub forked_from: Option<String>,
 }
 
 pub struct ContextStore {
@@ -148,6 +153,7 @@ impl ContextStore {
                 .map(|context| RemoteContextMetadata {
                     id: ContextId::from_proto(context.context_id),
                     summary: context.summary,
+                    forked_from: context.forked_from,
                 })
                 .collect();
             cx.notify();
@@ -212,6 +218,41 @@ impl ContextStore {
         })
     }
 
+    async fn handle_fork_context(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ForkContext>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ForkContextResponse> {
+        let (context_id, operations) = this.update(&mut cx, |this, cx| {
+            if this.project.read(cx).is_remote() {
+                return Err(anyhow!("can only fork contexts as the host"));
+            }
+
+            let parent_id = ContextId::from_proto(envelope.payload.context_id);
+            let message_id = MessageId(language::proto::deserialize_timestamp(
+                envelope.payload.message_id.context("invalid message id")?,
+            ));
+            let parent = this
+                .loaded_context_for_id(&parent_id, cx)
+                .context("context not found")?;
+            let context = this.fork_local_context(&parent, message_id, cx)?;
+            let context_id = context.read(cx).id().clone();
+            cx.emit(ContextStoreEvent::ContextCreated(context_id.clone()));
+
+            anyhow::Ok((
+                context_id,
+                context
+                    .read(cx)
+                    .serialize_ops(&ContextVersion::default(), cx),
+            ))
+        })??;
+        let operations = operations.await;
+        Ok(proto::ForkContextResponse {
+            context_id: context_id.to_proto(),
+            context: Some(proto::Context { operations }),
+        })
+    }
+
     async fn handle_update_context(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::UpdateContext>,
@@ -360,17 +401,89 @@ impl ContextStore {
             return Task::ready(Err(anyhow!("cannot create remote contexts as the host")));
         }
 
+        let request = self.client.request(proto::CreateContext { project_id });
+        self.open_created_remote_context(
+            async move {
+                let response = request.await?;
+                Ok((response.context_id, response.context))
+            },
+            cx,
+        )
+    }
+
+    /// Forks a context at the given message into a new context, which is saved when it's local.
+    pub fn fork_context(
+        &mut self,
+        context: &Model<Context>,
+        message_id: MessageId,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Model<Context>>> {
+        let project = self.project.read(cx);
+        if project.is_local() {
+            return Task::ready(self.fork_local_context(context, message_id, cx));
+        }
+        let Some(project_id) = project.remote_id() else {
+            return Task::ready(Err(anyhow!("project was not remote")));
+        };
+
+        let request = self.client.request(proto::ForkContext {
+            project_id,
+            context_id: context.read(cx).id().to_proto(),
+            message_id: Some(language::proto::serialize_timestamp(message_id.0)),
+        });
+        self.open_created_remote_context(
+            async move {
+                let response = request.await?;
+                Ok((response.context_id, response.context))
+            },
+            cx,
+        )
+    }
+
+    fn fork_local_context(
+        &mut self,
+        context: &Model<Context>,
+        message_id: MessageId,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<Model<Context>> {
+        let saved_context = context
+            .read(cx)
+            .fork(message_id, cx)
+            .context("message not found")?;
+        let fork = cx.new_model(|cx| {
+            let mut fork = Context::deserialize(
+                saved_context,
+                None,
+                self.languages.clone(),
+                self.prompt_builder.clone(),
+                Some(self.project.clone()),
+                Some(self.telemetry.clone()),
+                cx,
+            );
+            fork.save(None, self.fs.clone(), cx);
+            fork
+        });
+        self.register_context(&fork, cx);
+        Ok(fork)
+    }
+
+    /// Opens a context that the host created in response to a request.
+    fn open_created_remote_context(
+        &mut self,
+        response: impl 'static + Future<Output = Result<(String, Option<proto::Context>)>>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Model<Context>>> {
+        let project = self.project.read(cx);
         let replica_id = project.replica_id();
         let capability = project.capability();
         let language_registry = self.languages.clone();
         let project = self.project.clone();
         let telemetry = self.telemetry.clone();
         let prompt_builder = self.prompt_builder.clone();
-        let request = self.client.request(proto::CreateContext { project_id });
         cx.spawn(|this, mut cx| async move {
-            let response = request.await?;
-            let context_id = ContextId::from_proto(response.context_id);
-            let context_proto = response.context.context("invalid context")?;
+            let (context_id, context_proto) = response.await?;
+            let context_id = ContextId::from_proto(context_id);
+            let context_proto = context_proto.context("invalid context")?;
             let context = cx.new_model(|cx| {
                 Context::new(
                     context_id.clone(),
@@ -433,7 +546,7 @@ impl ContextStore {
             let context = cx.new_model(|cx| {
                 Context::deserialize(
                     saved_context,
-                    path.clone(),
+                    Some(path.clone()),
                     languages,
                     prompt_builder,
                     Some(project),
@@ -603,6 +716,7 @@ impl ContextStore {
                     Some(proto::ContextMetadata {
                         context_id: context.id().to_proto(),
                         summary: context.summary().map(|summary| summary.text.clone()),
+                        forked_from: context.parent().map(|parent| parent.title.clone()),
                     })
                 } else {
                     None
@@ -708,6 +822,7 @@ impl ContextStore {
 
     fn reload(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
         let fs = self.fs.clone();
+        let previous_contexts = self.contexts_metadata.clone();
         cx.spawn(|this, mut cx| async move {
             fs.create_dir(contexts_dir()).await?;
 
@@ -734,20 +849,79 @@ impl ContextStore {
                     }
 
                     if let Some(title) = re.replace(file_name, "").lines().next() {
+                        let mtime = metadata.mtime.into();
+                        let (id, forked_from) = match previous_contexts
+                            .iter()
+                            .find(|context| context.path == path && context.mtime == mtime)
+                        {
+                            Some(previous) => (previous.id.clone(), previous.forked_from.clone()),
+                            None => Self::load_context_header(fs.as_ref(), &path).await,
+                        };
                         contexts.push(SavedContextMetadata {
                             title: title.to_string(),
                             path,
-                            mtime: metadata.mtime.into(),
+                            mtime,
+                            id,
+                            forked_from,
                         });
                     }
                 }
             }
             contexts.sort_unstable_by_key(|context| Reverse(context.mtime));
 
+            // Link forks to their parents, which may have been renamed or deleted since.
+            let parents = contexts
+                .iter()
+                .filter_map(|context| {
+                    Some((
+                        context.id.clone()?,
+                        (context.path.clone(), context.title.clone()),
+                    ))
+                })
+                .collect::<HashMap<_, _>>();
+            for parent in contexts
+                .iter_mut()
+                .filter_map(|context| context.forked_from.as_mut())
+            {
+                match parents.get(&parent.id) {
+                    Some((path, title)) => {
+                        parent.path = Some(path.clone());
+                        parent.title = title.clone();
+                    }
+                    None => parent.path = None,
+                }
+            }
+
             this.update(&mut cx, |this, cx| {
                 this.contexts_metadata = contexts;
                 cx.notify();
             })
         })
     }
+
+    /// Reads the id of the saved context, along with the context it was forked from.
+    async fn load_context_header(
+        fs: &dyn Fs,
+        path: &Path,
+    ) -> (Option<ContextId>, Option<SavedContextParent>) {
+        #[derive(Deserialize)]
+        struct SavedContextHeader {
+            #[serde(default)]
+            id: Option<ContextId>,
+            #[serde(default)]
+            parent: Option<ContextParent>,
+        }
+
+        let Some(header) = fs.load(path).await.log_err().and_then(|saved_context| {
+            serde_json::from_str::<SavedContextHeader>(&saved_context).log_err()
+        }) else {
+            return (None, None);
+        };
+        let forked_from = header.parent.map(|parent| SavedContextParent {
+            id: parent.id,
+            title: parent.title,
+            path: None,
+        });
+        (header.id, forked_from)
+    }
 }
@@ -1,3 +1,6 @@
+mod project_prompts;
+mod prompt_file;
+
 use crate::{
     slash_command::SlashCommandCompletionProvider, AssistantPanel, InlineAssist, InlineAssistant,
 };
@@ -5,15 +8,17 @@ use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
 use collections::{HashMap, HashSet};
 use editor::{actions::Tab, CurrentLineHighlight, Editor, EditorElement, EditorEvent, EditorStyle};
+use fs::Fs;
 use futures::{
     future::{self, BoxFuture, Shared},
     FutureExt,
 };
 use fuzzy::StringMatchCandidate;
 use gpui::{
-    actions, point, size, transparent_black, AppContext, BackgroundExecutor, Bounds, EventEmitter,
-    Global, HighlightStyle, PromptLevel, ReadGlobal, Subscription, Task, TextStyle,
-    TitlebarOptions, UpdateGlobal, View, WindowBounds, WindowHandle, WindowOptions,
+    actions, point, size, transparent_black, AnyElement, AppContext, BackgroundExecutor, Bounds,
+    EntityId, EventEmitter, Global, HighlightStyle, PathPromptOptions, PromptLevel, ReadGlobal,
+    Subscription, Task, TextStyle, TitlebarOptions, UpdateGlobal, View, WindowBounds, WindowHandle,
+    WindowOptions,
 };
 use heed::{
     types::{SerdeBincode, SerdeJson, Str},
@@ -25,13 +30,15 @@ use language_model::{
 };
 use parking_lot::RwLock;
 use picker::{Picker, PickerDelegate};
+use project::WorktreeId;
+use prompt_file::{load_prompt_files, PromptFile};
 use rope::Rope;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
 use std::{
     cmp::Reverse,
     future::Future,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{atomic::AtomicBool, Arc},
     time::Duration,
 };
@@ -51,7 +58,10 @@ actions!(
         NewPrompt,
         DeletePrompt,
         DuplicatePrompt,
-        ToggleDefaultPrompt
+        ToggleDefaultPrompt,
+        ImportPrompts,
+        ExportPrompt,
+        ExportAllPrompts
     ]
 );
 
@@ -63,7 +73,8 @@ pub fn init(cx: &mut AppContext) {
         .then(|result| future::ready(result.map(Arc::new).map_err(Arc::new)))
         .boxed()
         .shared();
-    cx.set_global(GlobalPromptStore(prompt_store_future))
+    cx.set_global(GlobalPromptStore(prompt_store_future));
+    project_prompts::init(cx);
 }
 
 const BUILT_IN_TOOLTIP_TEXT: &'static str = concat!(
@@ -71,6 +82,11 @@ const BUILT_IN_TOOLTIP_TEXT: &'static str = concat!(
     "It's read-only, but you can remove it from your default prompt."
 );
 
+const PROJECT_TOOLTIP_TEXT: &'static str = concat!(
+    "This prompt is loaded from a project's .zed/prompts folder.\n",
+    "It's read-only, but you can duplicate it to make changes."
+);
+
 /// This function opens a new prompt library window if one doesn't exist already.
 /// If one exists, it brings it to the foreground.
 ///
@@ -78,6 +94,7 @@ const BUILT_IN_TOOLTIP_TEXT: &'static str = concat!(
 /// initialized. If it was initialized successfully, it returns a window handle
 /// to a prompt library.
 pub fn open_prompt_library(
+    fs: Arc<dyn Fs>,
     language_registry: Arc<LanguageRegistry>,
     cx: &mut AppContext,
 ) -> Task<Result<WindowHandle<PromptLibrary>>> {
@@ -106,7 +123,7 @@ pub fn open_prompt_library(
                         window_bounds: Some(WindowBounds::Windowed(bounds)),
                         ..Default::default()
                     },
-                    |cx| cx.new_view(|cx| PromptLibrary::new(store, language_registry, cx)),
+                    |cx| cx.new_view(|cx| PromptLibrary::new(fs, store, language_registry, cx)),
                 )
             })?
         })
@@ -114,12 +131,14 @@ pub fn open_prompt_library(
 }
 
 pub struct PromptLibrary {
+    fs: Arc<dyn Fs>,
     store: Arc<PromptStore>,
     language_registry: Arc<LanguageRegistry>,
     prompt_editors: HashMap<PromptId, PromptEditor>,
     active_prompt_id: Option<PromptId>,
     picker: View<Picker<PromptPickerDelegate>>,
     pending_load: Task<()>,
+    _watch_project_prompts: Task<()>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -173,7 +192,7 @@ impl PickerDelegate for PromptPickerDelegate {
     }
 
     fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
-        let search = self.store.search(query);
+        let search = self.store.search(query, ProjectPromptScope::All);
         let prev_prompt_id = self.matches.get(self.selected_index).map(|mat| mat.id);
         cx.spawn(|this, mut cx| async move {
             let (matches, selected_index) = cx
@@ -238,19 +257,8 @@ impl PickerDelegate for PromptPickerDelegate {
             .end_hover_slot(
                 h_flex()
                     .gap_2()
-                    .child(if prompt_id.is_built_in() {
-                        div()
-                            .id("built-in-prompt")
-                            .child(Icon::new(IconName::FileLock).color(Color::Muted))
-                            .tooltip(move |cx| {
-                                Tooltip::with_meta(
-                                    "Built-in prompt",
-                                    None,
-                                    BUILT_IN_TOOLTIP_TEXT,
-                                    cx,
-                                )
-                            })
-                            .into_any()
+                    .child(if prompt_id.is_read_only() {
+                        render_read_only_indicator(prompt_id)
                     } else {
                         IconButton::new("delete-prompt", IconName::Trash)
                             .icon_color(Color::Muted)
@@ -263,6 +271,7 @@ impl PickerDelegate for PromptPickerDelegate {
                     })
                     .child(
                         IconButton::new("toggle-default-prompt", IconName::Sparkle)
+                            .disabled(prompt_id.is_project())
                             .selected(default)
                             .selected_icon(IconName::SparkleFilled)
                             .icon_color(if default { Color::Accent } else { Color::Muted })
@@ -300,6 +309,7 @@ impl PickerDelegate for PromptPickerDelegate {
 
 impl PromptLibrary {
     fn new(
+        fs: Arc<dyn Fs>,
         store: Arc<PromptStore>,
         language_registry: Arc<LanguageRegistry>,
         cx: &mut ViewContext<Self>,
@@ -317,12 +327,27 @@ impl PromptLibrary {
             picker.focus(cx);
             picker
         });
+        let mut project_prompts_changed = store.project_prompts_changed();
+        let watch_project_prompts = cx.spawn(|this, mut cx| async move {
+            while project_prompts_changed.changed().await.is_ok() {
+                let updated = this.update(&mut cx, |this, cx| {
+                    this.picker.update(cx, |picker, cx| picker.refresh(cx));
+                    cx.notify();
+                });
+                if updated.is_err() {
+                    break;
+                }
+            }
+        });
+
         Self {
+            fs,
             store: store.clone(),
             language_registry,
             prompt_editors: HashMap::default(),
             active_prompt_id: None,
             pending_load: Task::ready(()),
+            _watch_project_prompts: watch_project_prompts,
             _subscriptions: vec![cx.subscribe(&picker, Self::handle_picker_event)],
             picker,
         }
@@ -373,7 +398,7 @@ impl PromptLibrary {
     pub fn save_prompt(&mut self, prompt_id: PromptId, cx: &mut ViewContext<Self>) {
         const SAVE_THROTTLE: Duration = Duration::from_millis(500);
 
-        if prompt_id.is_built_in() {
+        if prompt_id.is_read_only() {
             return;
         }
 
@@ -457,6 +482,11 @@ impl PromptLibrary {
     }
 
     pub fn toggle_default_for_prompt(&mut self, prompt_id: PromptId, cx: &mut ViewContext<Self>) {
+        // Project prompts are made default in their front matter.
+        if prompt_id.is_project() {
+            return;
+        }
+
         if let Some(prompt_metadata) = self.store.metadata(prompt_id) {
             self.store
                 .save_metadata(prompt_id, prompt_metadata.title, !prompt_metadata.default)
@@ -486,7 +516,7 @@ impl PromptLibrary {
                             let mut editor = Editor::auto_width(cx);
                             editor.set_placeholder_text("Untitled", cx);
                             editor.set_text(prompt_metadata.title.unwrap_or_default(), cx);
-                            editor.set_read_only(prompt_id.is_built_in());
+                            editor.set_read_only(prompt_id.is_read_only());
                             editor
                         });
                         let body_editor = cx.new_view(|cx| {
@@ -498,7 +528,7 @@ impl PromptLibrary {
                             });
 
                             let mut editor = Editor::for_buffer(buffer, None, cx);
-                            editor.set_read_only(prompt_id.is_built_in());
+                            editor.set_read_only(prompt_id.is_read_only());
                             editor.set_soft_wrap_mode(SoftWrap::EditorWidth, cx);
                             editor.set_show_gutter(false, cx);
                             editor.set_show_wrap_guides(false, cx);
@@ -575,6 +605,10 @@ impl PromptLibrary {
     }
 
     pub fn delete_prompt(&mut self, prompt_id: PromptId, cx: &mut ViewContext<Self>) {
+        if prompt_id.is_project() {
+            return;
+        }
+
         if let Some(metadata) = self.store.metadata(prompt_id) {
             let confirmation = cx.prompt(
                 PromptLevel::Warning,
@@ -645,6 +679,125 @@ impl PromptLibrary {
         }
     }
 
+    /// Imports prompts from Markdown files, or folders of them, as new prompts in the library.
+    pub fn import_prompts(&mut self, cx: &mut ViewContext<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: true,
+            multiple: true,
+        });
+        let fs = self.fs.clone();
+        let store = self.store.clone();
+        cx.spawn(|this, mut cx| async move {
+            let Some(paths) = paths.await?? else {
+                return Ok(());
+            };
+
+            let prompts = load_prompt_files(fs.as_ref(), paths).await;
+            for (_, prompt) in prompts {
+                store
+                    .save(
+                        PromptId::new(),
+                        Some(prompt.title.into()),
+                        prompt.default,
+                        prompt.body.into(),
+                    )
+                    .await?;
+            }
+
+            this.update(&mut cx, |this, cx| {
+                this.picker.update(cx, |picker, cx| picker.refresh(cx));
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    pub fn export_active_prompt(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(active_prompt_id) = self.active_prompt_id {
+            self.export_prompts(vec![active_prompt_id], cx);
+        }
+    }
+
+    pub fn export_all_prompts(&mut self, cx: &mut ViewContext<Self>) {
+        let prompt_ids = self
+            .store
+            .all_prompt_metadata()
+            .into_iter()
+            .map(|metadata| metadata.id)
+            .filter(|prompt_id| !prompt_id.is_read_only())
+            .collect();
+        self.export_prompts(prompt_ids, cx);
+    }
+
+    /// Exports prompts as Markdown files into a folder, such as a project's `.zed/prompts`.
+    fn export_prompts(&mut self, prompt_ids: Vec<PromptId>, cx: &mut ViewContext<Self>) {
+        if prompt_ids.is_empty() {
+            return;
+        }
+
+        let directory = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+        });
+        let fs = self.fs.clone();
+        let store = self.store.clone();
+        cx.spawn(|this, mut cx| async move {
+            let Some(directory) = directory.await??.and_then(|paths| paths.into_iter().next())
+            else {
+                return Ok(());
+            };
+
+            let mut file_names = HashSet::default();
+            let mut files = Vec::new();
+            for prompt_id in prompt_ids {
+                let prompt = store.prompt_file(prompt_id).await?;
+                let mut file_name = prompt.file_name();
+                let mut suffix = 1;
+                while !file_names.insert(file_name.clone()) {
+                    suffix += 1;
+                    file_name =
+                        format!("{}-{suffix}.md", prompt.file_name().trim_end_matches(".md"));
+                }
+                files.push((directory.join(file_name), prompt));
+            }
+
+            let mut existing_file_names = Vec::new();
+            for (path, _) in &files {
+                if fs.is_file(path).await {
+                    existing_file_names
+                        .push(path.file_name().unwrap_or_default().to_string_lossy());
+                }
+            }
+            if !existing_file_names.is_empty() {
+                let message = if existing_file_names.len() == 1 {
+                    format!("Replace the existing file \"{}\"?", existing_file_names[0])
+                } else {
+                    format!("Replace {} existing files?", existing_file_names.len())
+                };
+                let detail = existing_file_names.join("\n");
+                let answer = this.update(&mut cx, |_, cx| {
+                    cx.prompt(
+                        PromptLevel::Warning,
+                        &message,
+                        Some(&detail),
+                        &["Replace", "Cancel"],
+                    )
+                })?;
+                if answer.await? != 0 {
+                    return Ok(());
+                }
+            }
+
+            for (path, prompt) in files {
+                fs.atomic_write(path, prompt.to_markdown()).await?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn focus_active_prompt(&mut self, _: &Tab, cx: &mut ViewContext<Self>) {
         if let Some(active_prompt) = self.active_prompt_id {
             self.prompt_editors[&active_prompt]
@@ -812,6 +965,28 @@ impl PromptLibrary {
                     .w_full()
                     .flex_none()
                     .justify_end()
+                    .child(
+                        IconButton::new("import-prompts", IconName::ArrowDownFromLine)
+                            .style(ButtonStyle::Transparent)
+                            .shape(IconButtonShape::Square)
+                            .tooltip(move |cx| {
+                                Tooltip::for_action("Import Prompts", &ImportPrompts, cx)
+                            })
+                            .on_click(|_, cx| {
+                                cx.dispatch_action(Box::new(ImportPrompts));
+                            }),
+                    )
+                    .child(
+                        IconButton::new("export-all-prompts", IconName::ArrowUpFromLine)
+                            .style(ButtonStyle::Transparent)
+                            .shape(IconButtonShape::Square)
+                            .tooltip(move |cx| {
+                                Tooltip::for_action("Export All Prompts", &ExportAllPrompts, cx)
+                            })
+                            .on_click(|_, cx| {
+                                cx.dispatch_action(Box::new(ExportAllPrompts));
+                            }),
+                    )
                     .child(
                         IconButton::new("new-prompt", IconName::Plus)
                             .style(ButtonStyle::Transparent)
@@ -968,22 +1143,8 @@ impl PromptLibrary {
                                                             )
                                                     },
                                                 ))
-                                                .child(if prompt_id.is_built_in() {
-                                                    div()
-                                                        .id("built-in-prompt")
-                                                        .child(
-                                                            Icon::new(IconName::FileLock)
-                                                                .color(Color::Muted),
-                                                        )
-                                                        .tooltip(move |cx| {
-                                                            Tooltip::with_meta(
-                                                                "Built-in prompt",
-                                                                None,
-                                                                BUILT_IN_TOOLTIP_TEXT,
-                                                                cx,
-                                                            )
-                                                        })
-                                                        .into_any()
+                                                .child(if prompt_id.is_read_only() {
+                                                    render_read_only_indicator(prompt_id)
                                                 } else {
                                                     IconButton::new(
                                                         "delete-prompt",
@@ -1027,11 +1188,31 @@ impl PromptLibrary {
                                                         ));
                                                     }),
                                                 )
+                                                .child(
+                                                    IconButton::new(
+                                                        "export-prompt",
+                                                        IconName::ArrowUpFromLine,
+                                                    )
+                                                    .style(ButtonStyle::Transparent)
+                                                    .shape(IconButtonShape::Square)
+                                                    .size(ButtonSize::Large)
+                                                    .tooltip(move |cx| {
+                                                        Tooltip::for_action(
+                                                            "Export Prompt",
+                                                            &ExportPrompt,
+                                                            cx,
+                                                        )
+                                                    })
+                                                    .on_click(|_, cx| {
+                                                        cx.dispatch_action(Box::new(ExportPrompt));
+                                                    }),
+                                                )
                                                 .child(
                                                     IconButton::new(
                                                         "toggle-default-prompt",
                                                         IconName::Sparkle,
                                                     )
+                                                    .disabled(prompt_id.is_project())
                                                     .style(ButtonStyle::Transparent)
                                                     .selected(prompt_metadata.default)
                                                     .selected_icon(IconName::SparkleFilled)
@@ -1089,6 +1270,9 @@ impl Render for PromptLibrary {
             .on_action(cx.listener(|this, &ToggleDefaultPrompt, cx| {
                 this.toggle_default_for_active_prompt(cx)
             }))
+            .on_action(cx.listener(|this, &ImportPrompts, cx| this.import_prompts(cx)))
+            .on_action(cx.listener(|this, &ExportPrompt, cx| this.export_active_prompt(cx)))
+            .on_action(cx.listener(|this, &ExportAllPrompts, cx| this.export_all_prompts(cx)))
             .size_full()
             .overflow_hidden()
             .font(ui_font)
@@ -1098,6 +1282,20 @@ impl Render for PromptLibrary {
     }
 }
 
+fn render_read_only_indicator(prompt_id: PromptId) -> AnyElement {
+    let (title, meta) = if prompt_id.is_project() {
+        ("Project prompt", PROJECT_TOOLTIP_TEXT)
+    } else {
+        ("Built-in prompt", BUILT_IN_TOOLTIP_TEXT)
+    };
+
+    div()
+        .id("read-only-prompt")
+        .child(Icon::new(IconName::FileLock).color(Color::Muted))
+        .tooltip(move |cx| Tooltip::with_meta(title, None, meta, cx))
+        .into_any()
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PromptMetadata {
     pub id: PromptId,
@@ -1109,8 +1307,14 @@ pub struct PromptMetadata {
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(tag = "kind")]
 pub enum PromptId {
-    User { uuid: Uuid },
+    User {
+        uuid: Uuid,
+    },
     EditWorkflow,
+    /// A prompt loaded from a project's `.zed/prompts` folder, identified by the file's path.
+    Project {
+        uuid: Uuid,
+    },
 }
 
 impl PromptId {
@@ -1120,7 +1324,21 @@ impl PromptId {
         }
     }
 
+    pub fn for_project_file(abs_path: &Path) -> PromptId {
+        PromptId::Project {
+            uuid: Uuid::new_v5(&Uuid::NAMESPACE_URL, abs_path.to_string_lossy().as_bytes()),
+        }
+    }
+
     pub fn is_built_in(&self) -> bool {
+        matches!(self, PromptId::EditWorkflow)
+    }
+
+    pub fn is_project(&self) -> bool {
+        matches!(self, PromptId::Project { .. })
+    }
+
+    pub fn is_read_only(&self) -> bool {
         !matches!(self, PromptId::User { .. })
     }
 }
@@ -1131,6 +1349,35 @@ pub struct PromptStore {
     metadata_cache: RwLock<MetadataCache>,
     metadata: Database<SerdeJson<PromptId>, SerdeJson<PromptMetadata>>,
     bodies: Database<SerdeJson<PromptId>, Str>,
+    /// Prompts loaded from the `.zed/prompts` folders of open projects, which aren't stored in
+    /// the database.
+    project_prompts: RwLock<HashMap<ProjectPromptSource, Vec<ProjectPrompt>>>,
+    project_prompts_changed_tx: async_watch::Sender<()>,
+    project_prompts_changed_rx: async_watch::Receiver<()>,
+}
+
+/// The project prompts that are listed along with the prompts in the library.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProjectPromptScope {
+    /// The prompts of all open projects, as in the prompt library.
+    All,
+    /// Only the prompts loaded from the given project.
+    Project(EntityId),
+    /// No project prompts.
+    None,
+}
+
+/// A worktree of a project from which prompts are loaded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ProjectPromptSource {
+    pub project: EntityId,
+    pub worktree: WorktreeId,
+}
+
+#[derive(Clone, Debug)]
+pub struct ProjectPrompt {
+    pub path: PathBuf,
+    pub file: PromptFile,
 }
 
 #[derive(Default)]
@@ -1214,12 +1461,17 @@ impl PromptStore {
                 let metadata_cache = MetadataCache::from_db(metadata, &txn)?;
                 txn.commit()?;
 
+                let (project_prompts_changed_tx, project_prompts_changed_rx) =
+                    async_watch::channel(());
                 Ok(PromptStore {
                     executor,
                     env: db_env,
                     metadata_cache: RwLock::new(metadata_cache),
                     metadata,
                     bodies,
+                    project_prompts: RwLock::default(),
+                    project_prompts_changed_tx,
+                    project_prompts_changed_rx,
                 })
             }
         })
@@ -1300,6 +1552,14 @@ impl PromptStore {
     }
 
     pub fn load(&self, id: PromptId) -> Task<Result<String>> {
+        if id.is_project() {
+            let prompt = self
+                .project_prompt(id)
+                .map(|prompt| prompt.file.body)
+                .ok_or_else(|| anyhow!("prompt not found"));
+            return Task::ready(prompt);
+        }
+
         let env = self.env.clone();
         let bodies = self.bodies;
         self.executor.spawn(async move {
@@ -1313,13 +1573,14 @@ impl PromptStore {
         })
     }
 
-    pub fn default_prompt_metadata(&self) -> Vec<PromptMetadata> {
+    pub fn default_prompt_metadata(&self, scope: ProjectPromptScope) -> Vec<PromptMetadata> {
+        let in_scope = self.scope_filter(scope);
         return self
             .metadata_cache
             .read()
             .metadata
             .iter()
-            .filter(|metadata| metadata.default)
+            .filter(|metadata| metadata.default && in_scope(metadata))
             .cloned()
             .collect::<Vec<_>>();
     }
@@ -1342,21 +1603,141 @@ impl PromptStore {
         })
     }
 
+    pub fn all_prompt_metadata(&self) -> Vec<PromptMetadata> {
+        self.metadata_cache.read().metadata.clone()
+    }
+
+    /// Loads a prompt with its metadata, as it would be exported to a file.
+    pub fn prompt_file(&self, id: PromptId) -> Task<Result<PromptFile>> {
+        let Some(metadata) = self.metadata(id) else {
+            return Task::ready(Err(anyhow!("prompt not found")));
+        };
+        let body = self.load(id);
+        self.executor.spawn(async move {
+            Ok(PromptFile {
+                title: metadata
+                    .title
+                    .map_or_else(|| "Untitled".to_string(), |title| title.to_string()),
+                default: metadata.default,
+                body: body.await?,
+            })
+        })
+    }
+
+    /// Replaces the prompts loaded from a worktree's `.zed/prompts` folder.
+    pub fn set_project_prompts(&self, source: ProjectPromptSource, prompts: Vec<ProjectPrompt>) {
+        let mut project_prompts = self.project_prompts.write();
+        if prompts.is_empty() {
+            if project_prompts.remove(&source).is_none() {
+                return;
+            }
+        } else {
+            project_prompts.insert(source, prompts);
+        }
+        self.update_project_prompt_metadata(&project_prompts);
+    }
+
+    /// Removes the prompts of all worktrees of a project, once it's closed.
+    pub fn remove_project_prompts(&self, project: EntityId) {
+        let mut project_prompts = self.project_prompts.write();
+        let source_count = project_prompts.len();
+        project_prompts.retain(|source, _| source.project != project);
+        if project_prompts.len() != source_count {
+            self.update_project_prompt_metadata(&project_prompts);
+        }
+    }
+
+    fn update_project_prompt_metadata(
+        &self,
+        project_prompts: &HashMap<ProjectPromptSource, Vec<ProjectPrompt>>,
+    ) {
+        let mut cache = self.metadata_cache.write();
+        // Prompts that were already loaded keep their position among prompts with the same title.
+        let previously_saved_at = cache
+            .metadata
+            .iter()
+            .filter(|metadata| metadata.id.is_project())
+            .map(|metadata| (metadata.id, metadata.saved_at))
+            .collect::<HashMap<_, _>>();
+        cache.metadata.retain(|metadata| !metadata.id.is_project());
+        cache.metadata_by_id.retain(|id, _| !id.is_project());
+        for prompt in project_prompts.values().flatten() {
+            let id = PromptId::for_project_file(&prompt.path);
+            cache.insert(PromptMetadata {
+                id,
+                title: Some(prompt.file.title.clone().into()),
+                default: prompt.file.default,
+                saved_at: previously_saved_at
+                    .get(&id)
+                    .copied()
+                    .unwrap_or_else(Utc::now),
+            });
+        }
+        drop(cache);
+
+        self.project_prompts_changed_tx.send(()).ok();
+    }
+
+    fn project_prompt(&self, id: PromptId) -> Option<ProjectPrompt> {
+        self.project_prompts
+            .read()
+            .values()
+            .flatten()
+            .find(|prompt| PromptId::for_project_file(&prompt.path) == id)
+            .cloned()
+    }
+
+    /// Returns whether prompts are listed in the given scope, which only limits project prompts.
+    fn scope_filter(&self, scope: ProjectPromptScope) -> impl Fn(&PromptMetadata) -> bool {
+        let project_prompt_ids = match scope {
+            ProjectPromptScope::All => None,
+            ProjectPromptScope::Project(project) => Some(
+                self.project_prompts
+                    .read()
+                    .iter()
+                    .filter(|(source, _)| source.project == project)
+                    .flat_map(|(_, prompts)| prompts)
+                    .map(|prompt| PromptId::for_project_file(&prompt.path))
+                    .collect::<HashSet<_>>(),
+            ),
+            ProjectPromptScope::None => Some(HashSet::default()),
+        };
+        move |metadata| {
+            !metadata.id.is_project()
+                || project_prompt_ids
+                    .as_ref()
+                    .map_or(true, |ids| ids.contains(&metadata.id))
+        }
+    }
+
+    /// Resolves whenever the prompts loaded from projects change.
+    pub fn project_prompts_changed(&self) -> async_watch::Receiver<()> {
+        self.project_prompts_changed_rx.clone()
+    }
+
     fn metadata(&self, id: PromptId) -> Option<PromptMetadata> {
         self.metadata_cache.read().metadata_by_id.get(&id).cloned()
     }
 
-    pub fn id_for_title(&self, title: &str) -> Option<PromptId> {
+    pub fn id_for_title(&self, title: &str, scope: ProjectPromptScope) -> Option<PromptId> {
+        let in_scope = self.scope_filter(scope);
         let metadata_cache = self.metadata_cache.read();
-        let metadata = metadata_cache
-            .metadata
-            .iter()
-            .find(|metadata| metadata.title.as_ref().map(|title| &***title) == Some(title))?;
+        let metadata = metadata_cache.metadata.iter().find(|metadata| {
+            metadata.title.as_ref().map(|title| &***title) == Some(title) && in_scope(metadata)
+        })?;
         Some(metadata.id)
     }
 
-    pub fn search(&self, query: String) -> Task<Vec<PromptMetadata>> {
-        let cached_metadata = self.metadata_cache.read().metadata.clone();
+    pub fn search(&self, query: String, scope: ProjectPromptScope) -> Task<Vec<PromptMetadata>> {
+        let in_scope = self.scope_filter(scope);
+        let cached_metadata = self
+            .metadata_cache
+            .read()
+            .metadata
+            .iter()
+            .filter(|metadata| in_scope(metadata))
+            .cloned()
+            .collect::<Vec<_>>();
         let executor = self.executor.clone();
         self.executor.spawn(async move {
             let mut matches = if query.is_empty() {
@@ -1398,8 +1779,8 @@ impl PromptStore {
         default: bool,
         body: Rope,
     ) -> Task<Result<()>> {
-        if id.is_built_in() {
-            return Task::ready(Err(anyhow!("built-in prompts cannot be saved")));
+        if id.is_read_only() {
+            return Task::ready(Err(anyhow!("read-only prompts cannot be saved")));
         }
 
         let prompt_metadata = PromptMetadata {
@@ -1432,6 +1813,10 @@ impl PromptStore {
         mut title: Option<SharedString>,
         default: bool,
     ) -> Task<Result<()>> {
+        if id.is_project() {
+            return Task::ready(Err(anyhow!("project prompts cannot be saved")));
+        }
+
         let mut cache = self.metadata_cache.write();
 
         if id.is_built_in() {
@@ -1473,3 +1858,145 @@ pub struct GlobalPromptStore(
 );
 
 impl Global for GlobalPromptStore {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+
+    fn project_prompt(path: &str, title: &str, default: bool) -> ProjectPrompt {
+        ProjectPrompt {
+            path: PathBuf::from(path),
+            file: PromptFile {
+                title: title.to_string(),
+                default,
+                body: format!("Body of {title}"),
+            },
+        }
+    }
+
+    fn titles(prompts: Vec<PromptMetadata>) -> Vec<String> {
+        prompts
+            .into_iter()
+            .filter_map(|metadata| Some(metadata.title?.to_string()))
+            .collect()
+    }
+
+    #[gpui::test]
+    async fn test_project_prompts(cx: &mut TestAppContext) {
+        let db_dir = tempfile::tempdir().unwrap();
+        let store = PromptStore::new(db_dir.path().to_path_buf(), cx.executor())
+            .await
+            .unwrap();
+        store
+            .save(
+                PromptId::new(),
+                Some("Global".into()),
+                true,
+                "Global body".into(),
+            )
+            .await
+            .unwrap();
+
+        let project_a = EntityId::from(1u64);
+        let project_b = EntityId::from(2u64);
+        let source_a = ProjectPromptSource {
+            project: project_a,
+            worktree: WorktreeId::from_usize(1),
+        };
+        let source_b = ProjectPromptSource {
+            project: project_b,
+            worktree: WorktreeId::from_usize(2),
+        };
+        let mut changed = store.project_prompts_changed();
+
+        store.set_project_prompts(
+            source_a,
+            vec![
+                project_prompt("/a/.zed/prompts/review.md", "Review", true),
+                project_prompt("/a/.zed/prompts/test.md", "Test", false),
+            ],
+        );
+        store.set_project_prompts(
+            source_b,
+            vec![project_prompt("/b/.zed/prompts/deploy.md", "Deploy", true)],
+        );
+        assert!(changed.changed().await.is_ok());
+
+        // Project prompts are only listed for their own project.
+        assert_eq!(
+            titles(store.search(String::new(), ProjectPromptScope::All).await),
+            ["Deploy", "Global", "Review", "Test"]
+        );
+        assert_eq!(
+            titles(
+                store
+                    .search(String::new(), ProjectPromptScope::Project(project_a))
+                    .await
+            ),
+            ["Global", "Review", "Test"]
+        );
+        assert_eq!(
+            titles(store.search(String::new(), ProjectPromptScope::None).await),
+            ["Global"]
+        );
+        assert_eq!(
+            store.id_for_title("Deploy", ProjectPromptScope::Project(project_a)),
+            None
+        );
+
+        // Project prompts are only default for their own project, once the user opted in.
+        assert_eq!(
+            titles(store.default_prompt_metadata(ProjectPromptScope::None)),
+            ["Global"]
+        );
+        assert_eq!(
+            titles(store.default_prompt_metadata(ProjectPromptScope::Project(project_a))),
+            ["Global", "Review"]
+        );
+
+        let review_id = store
+            .id_for_title("Review", ProjectPromptScope::Project(project_a))
+            .unwrap();
+        assert_eq!(
+            review_id,
+            PromptId::for_project_file(Path::new("/a/.zed/prompts/review.md"))
+        );
+        assert_eq!(store.load(review_id).await.unwrap(), "Body of Review");
+        assert!(store
+            .save_metadata(review_id, Some("Renamed".into()), false)
+            .await
+            .is_err());
+
+        // Reloading a prompt keeps the time it was first loaded at.
+        let saved_at = store.metadata(review_id).unwrap().saved_at;
+        store.set_project_prompts(
+            source_a,
+            vec![project_prompt("/a/.zed/prompts/review.md", "Review", true)],
+        );
+        assert_eq!(store.metadata(review_id).unwrap().saved_at, saved_at);
+        assert_eq!(
+            titles(
+                store
+                    .search(String::new(), ProjectPromptScope::Project(project_a))
+                    .await
+            ),
+            ["Global", "Review"]
+        );
+
+        // Removing a project only removes its own prompts.
+        store.remove_project_prompts(project_a);
+        assert!(store.load(review_id).await.is_err());
+        assert_eq!(store.metadata(review_id).map(|metadata| metadata.id), None);
+        assert_eq!(
+            titles(store.search(String::new(), ProjectPromptScope::All).await),
+            ["Deploy", "Global"]
+        );
+
+        store.set_project_prompts(source_b, Vec::new());
+        assert_eq!(
+            titles(store.search(String::new(), ProjectPromptScope::All).await),
+            ["Global"]
+        );
+    }
+}
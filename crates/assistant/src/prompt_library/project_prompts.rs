@@ -0,0 +1,104 @@
+//! Loads the prompts in the `.zed/prompts` folder of each worktree of local projects, so teams can
+//! version prompts alongside their code. They're reloaded whenever the folder changes.
+
+use gpui::{AppContext, Model, ViewContext};
+use paths::local_prompts_folder_relative_path;
+use project::{Project, Worktree};
+use workspace::Workspace;
+
+use super::prompt_file::load_prompt_files;
+use super::{ProjectPrompt, ProjectPromptSource, PromptStore};
+
+pub(super) fn init(cx: &mut AppContext) {
+    cx.observe_new_views(
+        |workspace: &mut Workspace, cx: &mut ViewContext<Workspace>| {
+            let project = workspace.project().clone();
+            if !project.read(cx).is_local() {
+                return;
+            }
+
+            for worktree in project.read(cx).visible_worktrees(cx).collect::<Vec<_>>() {
+                load_worktree_prompts(&project, &worktree, cx);
+            }
+
+            cx.subscribe(&project, |_, project, event, cx| match event {
+                project::Event::WorktreeAdded => {
+                    for worktree in project.read(cx).visible_worktrees(cx).collect::<Vec<_>>() {
+                        load_worktree_prompts(&project, &worktree, cx);
+                    }
+                }
+                project::Event::WorktreeRemoved(worktree_id) => {
+                    let source = ProjectPromptSource {
+                        project: project.entity_id(),
+                        worktree: *worktree_id,
+                    };
+                    let store = PromptStore::global(cx);
+                    cx.background_executor()
+                        .spawn(async move {
+                            store.await?.set_project_prompts(source, Vec::new());
+                            anyhow::Ok(())
+                        })
+                        .detach_and_log_err(cx);
+                }
+                project::Event::WorktreeUpdatedEntries(worktree_id, changes) => {
+                    let prompts_changed = changes
+                        .iter()
+                        .any(|(path, _, _)| path.starts_with(local_prompts_folder_relative_path()));
+                    if prompts_changed {
+                        if let Some(worktree) = project.read(cx).worktree_for_id(*worktree_id, cx) {
+                            load_worktree_prompts(&project, &worktree, cx);
+                        }
+                    }
+                }
+                _ => {}
+            })
+            .detach();
+
+            let project_id = project.entity_id();
+            cx.on_release(move |_, _, cx| {
+                let store = PromptStore::global(cx);
+                cx.background_executor()
+                    .spawn(async move {
+                        store.await?.remove_project_prompts(project_id);
+                        anyhow::Ok(())
+                    })
+                    .detach_and_log_err(cx);
+            })
+            .detach();
+        },
+    )
+    .detach();
+}
+
+fn load_worktree_prompts(
+    project: &Model<Project>,
+    worktree: &Model<Worktree>,
+    cx: &mut AppContext,
+) {
+    let source = ProjectPromptSource {
+        project: project.entity_id(),
+        worktree: worktree.read(cx).id(),
+    };
+    let prompts_dir = worktree
+        .read(cx)
+        .abs_path()
+        .join(local_prompts_folder_relative_path());
+    let fs = project.read(cx).fs().clone();
+    let store = PromptStore::global(cx);
+
+    cx.background_executor()
+        .spawn(async move {
+            let prompts = if fs.is_dir(&prompts_dir).await {
+                load_prompt_files(fs.as_ref(), vec![prompts_dir]).await
+            } else {
+                Vec::new()
+            };
+            let prompts = prompts
+                .into_iter()
+                .map(|(path, file)| ProjectPrompt { path, file })
+                .collect();
+            store.await?.set_project_prompts(source, prompts);
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+}
@@ -0,0 +1,243 @@
+//! Prompts stored as Markdown files, with their metadata in front matter:
+//!
+//! ```markdown
+//! ---
+//! title: "Review"
+//! default: true
+//! ---
+//! Review the following code...
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use fs::Fs;
+use futures::StreamExt as _;
+use text::LineEnding;
+use util::ResultExt as _;
+
+const FRONT_MATTER_DELIMITER: &str = "---";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PromptFile {
+    pub title: String,
+    pub default: bool,
+    pub body: String,
+}
+
+impl PromptFile {
+    /// Parses a prompt file, titling it after the file when the front matter has no title.
+    pub fn parse(path: &Path, contents: &str) -> Result<Self> {
+        let mut contents = contents.to_string();
+        LineEnding::normalize(&mut contents);
+
+        let mut title = None;
+        let mut default = false;
+        let mut body = contents.as_str();
+
+        if let Some(rest) = contents
+            .strip_prefix(FRONT_MATTER_DELIMITER)
+            .and_then(|rest| rest.strip_prefix('\n'))
+        {
+            let (front_matter, rest) = match rest.find(&format!("\n{FRONT_MATTER_DELIMITER}")) {
+                Some(end) => (
+                    &rest[..end],
+                    &rest[end + 1 + FRONT_MATTER_DELIMITER.len()..],
+                ),
+                None if rest.starts_with(FRONT_MATTER_DELIMITER) => {
+                    ("", &rest[FRONT_MATTER_DELIMITER.len()..])
+                }
+                None => return Err(anyhow!("unterminated front matter in {path:?}")),
+            };
+            body = rest.strip_prefix('\n').unwrap_or(rest);
+
+            for line in front_matter.lines() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let (key, value) = line
+                    .split_once(':')
+                    .ok_or_else(|| anyhow!("invalid front matter line {line:?} in {path:?}"))?;
+                let value = unquote(value.trim());
+                match key.trim() {
+                    "title" => title = Some(value),
+                    "default" => {
+                        default = match value.as_str() {
+                            "true" => true,
+                            "false" => false,
+                            _ => return Err(anyhow!("invalid default {value:?} in {path:?}")),
+                        }
+                    }
+                    // Leave room for metadata added by other tools.
+                    _ => {}
+                }
+            }
+        }
+
+        let title = title
+            .filter(|title| !title.is_empty())
+            .or_else(|| {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+            })
+            .ok_or_else(|| anyhow!("no title for prompt in {path:?}"))?;
+
+        Ok(Self {
+            title,
+            default,
+            body: body.to_string(),
+        })
+    }
+
+    pub fn to_markdown(&self) -> String {
+        // A JSON string is also a valid YAML string, and keeps titles with colons intact.
+        let title = serde_json::to_string(&self.title).unwrap_or_default();
+        let mut markdown = format!(
+            "{FRONT_MATTER_DELIMITER}\ntitle: {title}\ndefault: {}\n{FRONT_MATTER_DELIMITER}\n",
+            self.default
+        );
+        markdown.push_str(&self.body);
+        if !markdown.ends_with('\n') {
+            markdown.push('\n');
+        }
+        markdown
+    }
+
+    /// The name of the file the prompt is exported to.
+    pub fn file_name(&self) -> String {
+        let mut name = String::new();
+        for c in self.title.trim().chars() {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                name.extend(c.to_lowercase());
+            } else if !name.ends_with('-') {
+                name.push('-');
+            }
+        }
+        let name = name.trim_matches('-');
+        if name.is_empty() {
+            "untitled.md".to_string()
+        } else {
+            format!("{name}.md")
+        }
+    }
+}
+
+pub fn is_prompt_file(path: &Path) -> bool {
+    path.extension()
+        .map_or(false, |extension| extension == "md")
+}
+
+/// Loads the prompt files at the given paths, including the ones directly inside directories.
+///
+/// Files that can't be read or parsed are logged and skipped.
+pub async fn load_prompt_files(fs: &dyn Fs, paths: Vec<PathBuf>) -> Vec<(PathBuf, PromptFile)> {
+    let mut file_paths = Vec::new();
+    for path in paths {
+        if fs.is_dir(&path).await {
+            let Some(mut entries) = fs.read_dir(&path).await.log_err() else {
+                continue;
+            };
+            while let Some(entry) = entries.next().await {
+                if let Some(entry) = entry.log_err() {
+                    if is_prompt_file(&entry) {
+                        file_paths.push(entry);
+                    }
+                }
+            }
+        } else {
+            file_paths.push(path);
+        }
+    }
+    file_paths.sort();
+
+    let mut prompts = Vec::new();
+    for path in file_paths {
+        let prompt = match fs.load(&path).await {
+            Ok(contents) => PromptFile::parse(&path, &contents),
+            Err(error) => Err(error),
+        };
+        if let Some(prompt) = prompt.log_err() {
+            prompts.push((path, prompt));
+        }
+    }
+    prompts
+}
+
+fn unquote(value: &str) -> String {
+    if value.starts_with('"') {
+        if let Ok(value) = serde_json::from_str::<String>(value) {
+            return value;
+        }
+    }
+    if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        return value[1..value.len() - 1].replace("''", "'");
+    }
+    value.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_prompt_file() {
+        let prompt = PromptFile::parse(
+            Path::new("/project/.zed/prompts/review.md"),
+            indoc! {r#"
+                ---
+                title: "Review: Rust"
+                default: true
+                author: someone
+                ---
+                Review the following code.
+            "#},
+        )
+        .unwrap();
+        assert_eq!(
+            prompt,
+            PromptFile {
+                title: "Review: Rust".into(),
+                default: true,
+                body: "Review the following code.\n".into(),
+            }
+        );
+
+        let prompt = PromptFile::parse(
+            Path::new("/project/.zed/prompts/explain.md"),
+            "Explain this.\r\n",
+        )
+        .unwrap();
+        assert_eq!(prompt.title, "explain");
+        assert!(!prompt.default);
+        assert_eq!(prompt.body, "Explain this.\n");
+
+        let prompt =
+            PromptFile::parse(Path::new("single.md"), "---\ntitle: 'It''s quoted'\n---\n").unwrap();
+        assert_eq!(prompt.title, "It's quoted");
+        assert_eq!(prompt.body, "");
+
+        assert!(PromptFile::parse(Path::new("broken.md"), "---\ntitle: x\n").is_err());
+        assert!(PromptFile::parse(Path::new("broken.md"), "---\ndefault: yes\n---\n").is_err());
+    }
+
+    #[test]
+    fn test_prompt_file_round_trip() {
+        let prompt = PromptFile {
+            title: "Write \"tests\": Rust".into(),
+            default: false,
+            body: "---\nNot front matter.".into(),
+        };
+        let markdown = prompt.to_markdown();
+        assert_eq!(
+            markdown,
+            "---\ntitle: \"Write \\\"tests\\\": Rust\"\ndefault: false\n---\n---\nNot front matter.\n"
+        );
+
+        let parsed = PromptFile::parse(Path::new("x.md"), &markdown).unwrap();
+        assert_eq!(parsed.title, prompt.title);
+        assert_eq!(parsed.body, "---\nNot front matter.\n");
+        assert_eq!(prompt.file_name(), "write-tests-rust.md");
+    }
+}
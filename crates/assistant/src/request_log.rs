@@ -0,0 +1,679 @@
+use crate::OpenRequestLog;
+use chrono::{DateTime, Local};
+use editor::Editor;
+use fs::Fs;
+use gpui::{
+    AppContext, EventEmitter, FocusHandle, FocusableView, Model, Render, Subscription, Task, View,
+};
+use language::LanguageRegistry;
+use language_model::{
+    settings::AllLanguageModelSettings, LanguageModel, LanguageModelRegistry,
+    LanguageModelRequestLog, LanguageModelRequestLogEntry, LanguageModelRequestLogEntrySummary,
+    LanguageModelRequestLogEvent,
+};
+use picker::{Picker, PickerDelegate};
+use settings::{update_settings_file, Settings, SettingsStore};
+use similar::TextDiff;
+use std::sync::Arc;
+use ui::{
+    prelude::*,
+    utils::{format_distance_from_now, DateTimeType},
+    ContextMenu, ListItem, ListItemSpacing, PopoverMenu, Tooltip,
+};
+use util::ResultExt;
+use workspace::{item::Item, Workspace};
+
+/// Browses the requests recorded by the [`LanguageModelRequestLog`], shows how two of them differ,
+/// and replays them against other models.
+pub struct RequestLogView {
+    log: Model<LanguageModelRequestLog>,
+    fs: Arc<dyn Fs>,
+    picker: View<Picker<RequestLogPickerDelegate>>,
+    editor: View<Editor>,
+    selected_entry: Option<LanguageModelRequestLogEntry>,
+    diff_base: Option<LanguageModelRequestLogEntry>,
+    /// The entry to select once the entries have been reloaded.
+    entry_to_select: Option<i64>,
+    pending_replay: Option<Task<()>>,
+    pending_reload: Task<()>,
+    pending_selection: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl RequestLogView {
+    pub fn open(workspace: &mut Workspace, _: &OpenRequestLog, cx: &mut ViewContext<Workspace>) {
+        if let Some(existing) = workspace.item_of_type::<RequestLogView>(cx) {
+            workspace.activate_item(&existing, true, true, cx);
+            return;
+        }
+
+        let Some(log) = LanguageModelRequestLog::global(cx) else {
+            return;
+        };
+        let fs = workspace.app_state().fs.clone();
+        let languages = workspace.app_state().languages.clone();
+        let view = cx.new_view(|cx| Self::new(log, fs, languages, cx));
+        workspace.add_item_to_active_pane(Box::new(view), None, true, cx);
+    }
+
+    fn new(
+        log: Model<LanguageModelRequestLog>,
+        fs: Arc<dyn Fs>,
+        languages: Arc<LanguageRegistry>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let picker = cx.new_view(|cx| {
+            Picker::uniform_list(RequestLogPickerDelegate::new(log.clone()), cx)
+                .modal(false)
+                .max_height(None)
+        });
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::multi_line(cx);
+            editor.set_read_only(true);
+            editor.set_show_gutter(false, cx);
+            editor
+        });
+
+        let markdown = languages.language_for_name("Markdown");
+        cx.spawn(|this, mut cx| async move {
+            let markdown = markdown.await.log_err();
+            this.update(&mut cx, |this, cx| {
+                if let Some(buffer) = this.editor.read(cx).buffer().read(cx).as_singleton() {
+                    buffer.update(cx, |buffer, cx| {
+                        buffer.set_language(markdown, cx);
+                        buffer.set_language_registry(languages);
+                    });
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+
+        let subscriptions = vec![
+            cx.subscribe(&log, |this, _, _: &LanguageModelRequestLogEvent, cx| {
+                this.reload_entries(cx)
+            }),
+            cx.subscribe(&picker, Self::handle_picker_event),
+            cx.observe_global::<SettingsStore>(|_, cx| cx.notify()),
+        ];
+
+        let mut this = Self {
+            log,
+            fs,
+            picker,
+            editor,
+            selected_entry: None,
+            diff_base: None,
+            entry_to_select: None,
+            pending_replay: None,
+            pending_reload: Task::ready(()),
+            pending_selection: Task::ready(()),
+            _subscriptions: subscriptions,
+        };
+        this.reload_entries(cx);
+        this
+    }
+
+    fn reload_entries(&mut self, cx: &mut ViewContext<Self>) {
+        let entries = self.log.read(cx).entries(cx);
+        self.pending_reload = cx.spawn(|this, mut cx| async move {
+            let Some(entries) = entries.await.log_err() else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                if let Some(diff_base) = this.diff_base.as_ref() {
+                    if !entries.iter().any(|entry| entry.id == diff_base.id) {
+                        this.diff_base = None;
+                    }
+                }
+
+                let entry_to_select = this
+                    .entry_to_select
+                    .take()
+                    .or_else(|| this.selected_entry.as_ref().map(|entry| entry.id));
+                this.picker.update(cx, |picker, cx| {
+                    picker.delegate.set_entries(entries, entry_to_select);
+                    picker.refresh(cx);
+                });
+            })
+            .ok();
+        });
+    }
+
+    fn handle_picker_event(
+        &mut self,
+        _: View<Picker<RequestLogPickerDelegate>>,
+        event: &RequestLogPickerEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        match event {
+            RequestLogPickerEvent::Selected(id) => self.select_entry(*id, cx),
+            RequestLogPickerEvent::Confirmed => {
+                self.editor.focus_handle(cx).focus(cx);
+            }
+        }
+    }
+
+    /// Loads the full request and response of the selected entry before showing it.
+    fn select_entry(&mut self, id: Option<i64>, cx: &mut ViewContext<Self>) {
+        if self.selected_entry.as_ref().map(|entry| entry.id) == id {
+            self.pending_selection = Task::ready(());
+            return;
+        }
+        let Some(id) = id else {
+            self.pending_selection = Task::ready(());
+            self.selected_entry = None;
+            self.update_editor(cx);
+            return;
+        };
+
+        let entry = self.log.read(cx).entry(id, cx);
+        self.pending_selection = cx.spawn(|this, mut cx| async move {
+            let entry = entry.await.log_err().flatten();
+            this.update(&mut cx, |this, cx| {
+                this.selected_entry = entry;
+                this.update_editor(cx);
+            })
+            .ok();
+        });
+    }
+
+    fn update_editor(&mut self, cx: &mut ViewContext<Self>) {
+        let text = match (&self.selected_entry, &self.diff_base) {
+            (Some(entry), Some(diff_base)) if diff_base.id != entry.id => {
+                diff_entries(diff_base, entry)
+            }
+            (Some(entry), _) => entry.to_markdown(),
+            (None, _) => String::new(),
+        };
+        self.editor
+            .update(cx, |editor, cx| editor.set_text(text, cx));
+        cx.notify();
+    }
+
+    fn toggle_diff_base(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(entry) = self.selected_entry.as_ref() else {
+            return;
+        };
+        if self.diff_base.as_ref().map(|base| base.id) == Some(entry.id) {
+            self.diff_base = None;
+        } else {
+            self.diff_base = Some(entry.clone());
+        }
+        self.update_editor(cx);
+    }
+
+    fn replay(&mut self, model: Arc<dyn LanguageModel>, cx: &mut ViewContext<Self>) {
+        let Some(entry) = self.selected_entry.clone() else {
+            return;
+        };
+        let replay = self.log.update(cx, |log, cx| log.replay(&entry, model, cx));
+        self.pending_replay = Some(cx.spawn(|this, mut cx| async move {
+            let replay_id = replay.await.log_err();
+            this.update(&mut cx, |this, cx| {
+                this.pending_replay = None;
+                if let Some(replay_id) = replay_id {
+                    // Show how the replayed response differs from the original one.
+                    this.diff_base = Some(entry);
+                    this.entry_to_select = Some(replay_id);
+                    this.reload_entries(cx);
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    fn delete_selected_entry(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(entry) = self.selected_entry.take() {
+            self.log
+                .update(cx, |log, cx| log.delete_entry(entry.id, cx))
+                .detach_and_log_err(cx);
+            self.update_editor(cx);
+        }
+    }
+
+    fn clear(&mut self, cx: &mut ViewContext<Self>) {
+        self.selected_entry = None;
+        self.diff_base = None;
+        self.update_editor(cx);
+        self.log
+            .update(cx, |log, cx| log.clear(cx))
+            .detach_and_log_err(cx);
+    }
+
+    fn enable_logging(&mut self, cx: &mut ViewContext<Self>) {
+        update_settings_file::<AllLanguageModelSettings>(self.fs.clone(), cx, |settings, _| {
+            settings.log_requests = Some(true);
+        });
+    }
+
+    fn render_logging_disabled_notice(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        h_flex()
+            .p_2()
+            .gap_2()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Icon::new(IconName::ExclamationTriangle).color(Color::Warning))
+                    .child(
+                        Label::new("Requests aren't being recorded.")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .child(
+                Button::new("enable-request-log", "Enable Recording")
+                    .style(ButtonStyle::Filled)
+                    .on_click(cx.listener(|this, _, cx| this.enable_logging(cx))),
+            )
+    }
+
+    fn render_toolbar(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let selected_id = self.selected_entry.as_ref().map(|entry| entry.id);
+        let is_diff_base =
+            selected_id.is_some() && self.diff_base.as_ref().map(|base| base.id) == selected_id;
+        let is_replaying = self.pending_replay.is_some();
+        let this = cx.view().downgrade();
+
+        h_flex()
+            .p_2()
+            .gap_2()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                Label::new(match (&self.diff_base, selected_id) {
+                    (Some(base), Some(selected_id)) if base.id != selected_id => {
+                        format!("Comparing #{} with #{}", base.id, selected_id)
+                    }
+                    (Some(base), _) => format!("Comparing with #{}", base.id),
+                    (None, _) => "Select an entry to compare against".to_string(),
+                })
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Button::new("toggle-diff-base", "Compare")
+                            .icon(IconName::Split)
+                            .icon_position(IconPosition::Start)
+                            .icon_size(IconSize::Small)
+                            .selected(is_diff_base)
+                            .disabled(selected_id.is_none())
+                            .tooltip(move |cx| {
+                                Tooltip::text(
+                                    if is_diff_base {
+                                        "Stop Comparing"
+                                    } else {
+                                        "Compare Other Entries with This One"
+                                    },
+                                    cx,
+                                )
+                            })
+                            .on_click(cx.listener(|this, _, cx| this.toggle_diff_base(cx))),
+                    )
+                    .child(
+                        PopoverMenu::new("replay-request-menu")
+                            .trigger(
+                                Button::new("replay-request", "Replay")
+                                    .icon(IconName::HistoryRerun)
+                                    .icon_position(IconPosition::Start)
+                                    .icon_size(IconSize::Small)
+                                    .disabled(selected_id.is_none() || is_replaying),
+                            )
+                            .menu(move |cx| {
+                                let models =
+                                    LanguageModelRegistry::read_global(cx).available_models(cx);
+                                let this = this.clone();
+                                Some(ContextMenu::build(cx, |mut menu, _| {
+                                    menu = menu.header("Replay Against");
+                                    for model in models {
+                                        let label = format!(
+                                            "{} / {}",
+                                            model.provider_name().0,
+                                            model.name().0
+                                        );
+                                        let this = this.clone();
+                                        menu = menu.entry(label, None, move |cx| {
+                                            this.update(cx, |this, cx| {
+                                                this.replay(model.clone(), cx)
+                                            })
+                                            .ok();
+                                        });
+                                    }
+                                    menu
+                                }))
+                            }),
+                    )
+                    .child(
+                        IconButton::new("delete-request", IconName::Trash)
+                            .icon_size(IconSize::Small)
+                            .disabled(selected_id.is_none())
+                            .tooltip(|cx| Tooltip::text("Delete Entry", cx))
+                            .on_click(cx.listener(|this, _, cx| this.delete_selected_entry(cx))),
+                    )
+                    .child(
+                        IconButton::new("clear-request-log", IconName::XCircle)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::text("Clear Log", cx))
+                            .on_click(cx.listener(|this, _, cx| this.clear(cx))),
+                    ),
+            )
+    }
+}
+
+impl Render for RequestLogView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let logging_enabled = AllLanguageModelSettings::get_global(cx).log_requests;
+
+        v_flex()
+            .key_context("RequestLog")
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .when(!logging_enabled, |this| {
+                this.child(self.render_logging_disabled_notice(cx))
+            })
+            .child(
+                h_flex()
+                    .flex_1()
+                    .min_h_0()
+                    .child(
+                        div()
+                            .w_1_3()
+                            .h_full()
+                            .border_r_1()
+                            .border_color(cx.theme().colors().border)
+                            .child(self.picker.clone()),
+                    )
+                    .child(
+                        v_flex()
+                            .flex_1()
+                            .h_full()
+                            .child(self.render_toolbar(cx))
+                            .child(div().flex_1().min_h_0().p_2().child(self.editor.clone())),
+                    ),
+            )
+    }
+}
+
+impl FocusableView for RequestLogView {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<()> for RequestLogView {}
+
+impl Item for RequestLogView {
+    type Event = ();
+
+    fn tab_content_text(&self, _cx: &WindowContext) -> Option<SharedString> {
+        Some("Request Log".into())
+    }
+}
+
+struct RequestLogPickerDelegate {
+    log: Model<LanguageModelRequestLog>,
+    entries: Vec<LanguageModelRequestLogEntrySummary>,
+    matches: Vec<usize>,
+    selected_index: usize,
+    /// The entry to select the next time the matches are updated.
+    entry_to_select: Option<i64>,
+}
+
+enum RequestLogPickerEvent {
+    Selected(Option<i64>),
+    Confirmed,
+}
+
+impl EventEmitter<RequestLogPickerEvent> for Picker<RequestLogPickerDelegate> {}
+
+impl RequestLogPickerDelegate {
+    fn new(log: Model<LanguageModelRequestLog>) -> Self {
+        Self {
+            log,
+            entries: Vec::new(),
+            matches: Vec::new(),
+            selected_index: 0,
+            entry_to_select: None,
+        }
+    }
+
+    fn set_entries(
+        &mut self,
+        entries: Vec<LanguageModelRequestLogEntrySummary>,
+        entry_to_select: Option<i64>,
+    ) {
+        self.entries = entries;
+        self.entry_to_select = entry_to_select;
+    }
+
+    fn selected_entry(&self) -> Option<&LanguageModelRequestLogEntrySummary> {
+        let ix = *self.matches.get(self.selected_index)?;
+        Some(&self.entries[ix])
+    }
+
+    fn set_matches(
+        &mut self,
+        matches: Vec<usize>,
+        entry_to_select: Option<i64>,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) {
+        self.matches = matches;
+        let selected_index = entry_to_select
+            .and_then(|id| {
+                self.matches
+                    .iter()
+                    .position(|ix| self.entries[*ix].id == id)
+            })
+            .unwrap_or(0);
+        self.set_selected_index(selected_index, cx);
+        cx.notify();
+    }
+}
+
+impl PickerDelegate for RequestLogPickerDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+        cx.emit(RequestLogPickerEvent::Selected(
+            self.selected_entry().map(|entry| entry.id),
+        ));
+    }
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Search requests and responses...".into()
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let entry_to_select = self
+            .entry_to_select
+            .take()
+            .or_else(|| self.selected_entry().map(|entry| entry.id));
+
+        if query.trim().is_empty() {
+            self.set_matches((0..self.entries.len()).collect(), entry_to_select, cx);
+            return Task::ready(());
+        }
+
+        let search = self.log.read(cx).search(query, cx);
+        cx.spawn(|picker, mut cx| async move {
+            let Some(matching_ids) = search.await.log_err() else {
+                return;
+            };
+            picker
+                .update(&mut cx, |picker, cx| {
+                    let delegate = &mut picker.delegate;
+                    let matches = delegate
+                        .entries
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, entry)| matching_ids.contains(&entry.id))
+                        .map(|(ix, _)| ix)
+                        .collect();
+                    delegate.set_matches(matches, entry_to_select, cx);
+                })
+                .ok();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        cx.emit(RequestLogPickerEvent::Confirmed);
+    }
+
+    fn dismissed(&mut self, _cx: &mut ViewContext<Picker<Self>>) {}
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let entry = &self.entries[*self.matches.get(ix)?];
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(
+                    v_flex()
+                        .w_full()
+                        .overflow_x_hidden()
+                        .child(
+                            h_flex()
+                                .w_full()
+                                .justify_between()
+                                .gap_2()
+                                .child(
+                                    h_flex()
+                                        .gap_1()
+                                        .child(
+                                            Label::new(format!("#{}", entry.id))
+                                                .size(LabelSize::Small)
+                                                .color(Color::Muted),
+                                        )
+                                        .child(
+                                            Label::new(entry.model_id.0.clone())
+                                                .size(LabelSize::Small),
+                                        )
+                                        .children(entry.replay_of.map(|replay_of| {
+                                            Label::new(format!("replay of #{replay_of}"))
+                                                .size(LabelSize::Small)
+                                                .color(Color::Muted)
+                                        }))
+                                        .children(entry.failed.then(|| {
+                                            Icon::new(IconName::XCircle)
+                                                .size(IconSize::Small)
+                                                .color(Color::Error)
+                                        })),
+                                )
+                                .child(
+                                    Label::new(format_distance_from_now(
+                                        DateTimeType::Local(DateTime::<Local>::from(
+                                            entry.recorded_at,
+                                        )),
+                                        false,
+                                        true,
+                                        true,
+                                    ))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                                ),
+                        )
+                        .child(
+                            Label::new(entry.preview.clone())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .single_line(),
+                        ),
+                ),
+        )
+    }
+}
+
+/// Renders a unified diff between two entries' Markdown representations.
+fn diff_entries(
+    base: &LanguageModelRequestLogEntry,
+    entry: &LanguageModelRequestLogEntry,
+) -> String {
+    let base_text = base.to_markdown();
+    let text = entry.to_markdown();
+    let diff = TextDiff::from_lines(&base_text, &text)
+        .unified_diff()
+        .context_radius(3)
+        .header(&format!("#{}", base.id), &format!("#{}", entry.id))
+        .to_string();
+    if diff.is_empty() {
+        format!("Entries #{} and #{} are identical.\n", base.id, entry.id)
+    } else {
+        format!("```diff\n{diff}```\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language_model::{
+        LanguageModelId, LanguageModelProviderId, LanguageModelRequest,
+        LanguageModelRequestMessage, Role,
+    };
+    use std::time::SystemTime;
+
+    #[test]
+    fn test_diff_entries() {
+        let entry = |id: i64, response: &str| LanguageModelRequestLogEntry {
+            id,
+            recorded_at: SystemTime::now(),
+            provider_id: LanguageModelProviderId::from("fake".to_string()),
+            model_id: LanguageModelId::from("fake".to_string()),
+            request: LanguageModelRequest {
+                messages: vec![LanguageModelRequestMessage {
+                    role: Role::User,
+                    content: "Hello".into(),
+                }],
+                stop: Vec::new(),
+                temperature: 1.0,
+            },
+            tool: None,
+            response: response.into(),
+            error: None,
+            replay_of: None,
+        };
+
+        assert_eq!(
+            diff_entries(&entry(1, "Hi"), &entry(2, "Hi")),
+            "Entries #1 and #2 are identical.\n"
+        );
+        assert_eq!(
+            diff_entries(&entry(1, "Hi"), &entry(2, "Hello there")),
+            concat!(
+                "```diff\n",
+                "--- #1\n",
+                "+++ #2\n",
+                "@@ -9,4 +9,4 @@\n",
+                " \n",
+                " # Response\n",
+                " \n",
+                "-Hi\n",
+                "+Hello there\n",
+                "```\n",
+            )
+        );
+    }
+}
@@ -23,6 +23,7 @@ pub mod diagnostics_command;
 pub mod docs_command;
 pub mod fetch_command;
 pub mod file_command;
+pub mod git_command;
 pub mod now_command;
 pub mod project_command;
 pub mod prompt_command;
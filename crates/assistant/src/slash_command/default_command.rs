@@ -1,9 +1,13 @@
 use super::{SlashCommand, SlashCommandOutput};
-use crate::prompt_library::PromptStore;
+use crate::{
+    assistant_settings::AssistantSettings,
+    prompt_library::{ProjectPromptScope, PromptStore},
+};
 use anyhow::{anyhow, Result};
 use assistant_slash_command::{ArgumentCompletion, SlashCommandOutputSection};
 use gpui::{AppContext, Task, WeakView};
 use language::LspAdapterDelegate;
+use settings::Settings;
 use std::{
     fmt::Write,
     sync::{atomic::AtomicBool, Arc},
@@ -43,14 +47,21 @@ impl SlashCommand for DefaultSlashCommand {
     fn run(
         self: Arc<Self>,
         _argument: Option<&str>,
-        _workspace: WeakView<Workspace>,
+        workspace: WeakView<Workspace>,
         _delegate: Option<Arc<dyn LspAdapterDelegate>>,
         cx: &mut WindowContext,
     ) -> Task<Result<SlashCommandOutput>> {
+        // Project prompts could otherwise add themselves to every context in the project.
+        let scope = workspace
+            .upgrade()
+            .filter(|_| AssistantSettings::get_global(cx).project_default_prompts)
+            .map_or(ProjectPromptScope::None, |workspace| {
+                ProjectPromptScope::Project(workspace.read(cx).project().entity_id())
+            });
         let store = PromptStore::global(cx);
         cx.background_executor().spawn(async move {
             let store = store.await?;
-            let prompts = store.default_prompt_metadata();
+            let prompts = store.default_prompt_metadata(scope);
 
             let mut text = String::new();
             text.push('\n');
@@ -0,0 +1,614 @@
+use super::{create_label_for_command, SlashCommand, SlashCommandOutput};
+use anyhow::{anyhow, Context as _, Result};
+use assistant_slash_command::{ArgumentCompletion, SlashCommandOutputSection};
+use chrono::DateTime;
+use fs::Fs;
+use git::log::{Commit, CommitDetails};
+use git::repository::{GitDiffBase, GitRepository, RepoPath};
+use gpui::{AppContext, Model, Task, WeakView};
+use language::LspAdapterDelegate;
+use project::{Project, ProjectPath, RepositoryEntry};
+use similar::TextDiff;
+use std::fmt::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{atomic::AtomicBool, Arc};
+use ui::prelude::*;
+use workspace::Workspace;
+use worktree::Snapshot;
+
+/// The number of commits listed by `/git log`, and offered as completions for `/git show`.
+const LOG_LIMIT: usize = 50;
+const COMPLETION_LIMIT: usize = 20;
+
+pub(crate) struct GitSlashCommand;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum GitSlashCommandArgs {
+    /// The working copy changes against the index, or against the given revision.
+    Diff {
+        revision: Option<String>,
+    },
+    /// The changes staged for the next commit.
+    Staged,
+    Log {
+        path: Option<PathBuf>,
+    },
+    Show {
+        revision: String,
+    },
+}
+
+impl GitSlashCommandArgs {
+    const SUBCOMMANDS: [&'static str; 4] = ["diff", "staged", "log", "show"];
+
+    fn parse(argument: Option<&str>) -> Result<Self> {
+        let mut args = argument.unwrap_or("").split_whitespace();
+        let subcommand = args.next().unwrap_or("diff");
+        let operand = args.next().map(str::to_string);
+        if args.next().is_some() {
+            return Err(anyhow!("too many arguments to /git {subcommand}"));
+        }
+
+        match subcommand {
+            "diff" => Ok(Self::Diff { revision: operand }),
+            "staged" if operand.is_none() => Ok(Self::Staged),
+            "staged" => Err(anyhow!("/git staged takes no arguments")),
+            "log" => Ok(Self::Log {
+                path: operand.map(PathBuf::from),
+            }),
+            "show" => Ok(Self::Show {
+                revision: operand.context("/git show requires a commit")?,
+            }),
+            _ => Err(anyhow!(
+                "unknown git subcommand {subcommand:?}, expected one of {}",
+                Self::SUBCOMMANDS.join(", ")
+            )),
+        }
+    }
+}
+
+impl SlashCommand for GitSlashCommand {
+    fn name(&self) -> String {
+        "git".into()
+    }
+
+    fn label(&self, cx: &AppContext) -> language::CodeLabel {
+        create_label_for_command("git", &["diff|staged|log|show"], cx)
+    }
+
+    fn description(&self) -> String {
+        "insert git diffs, logs or commits".into()
+    }
+
+    fn menu_text(&self) -> String {
+        "Insert Git Changes".into()
+    }
+
+    fn requires_argument(&self) -> bool {
+        false
+    }
+
+    fn complete_argument(
+        self: Arc<Self>,
+        query: String,
+        _cancel: Arc<AtomicBool>,
+        workspace: Option<WeakView<Workspace>>,
+        cx: &mut AppContext,
+    ) -> Task<Result<Vec<ArgumentCompletion>>> {
+        let Some((subcommand, operand)) = query.split_once(' ') else {
+            return Task::ready(Ok(GitSlashCommandArgs::SUBCOMMANDS
+                .iter()
+                .filter(|subcommand| subcommand.starts_with(query.trim()))
+                .map(|subcommand| ArgumentCompletion {
+                    label: subcommand.to_string(),
+                    new_text: subcommand.to_string(),
+                    run_command: matches!(*subcommand, "diff" | "staged" | "log"),
+                })
+                .collect()));
+        };
+        if subcommand != "show" {
+            return Task::ready(Ok(Vec::new()));
+        }
+
+        let Some(workspace) = workspace.and_then(|workspace| workspace.upgrade()) else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let project = workspace.read(cx).project().clone();
+        let Some(root) = root_project_path(&project, cx) else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let commits = project.read(cx).git_log(root, None, COMPLETION_LIMIT, cx);
+        let operand = operand.trim().to_string();
+        cx.background_executor().spawn(async move {
+            Ok(commits
+                .await?
+                .into_iter()
+                .map(|commit| (commit.sha.to_string(), commit.summary))
+                .filter(|(sha, _)| sha.starts_with(&operand))
+                .map(|(sha, summary)| ArgumentCompletion {
+                    label: format!("{} {summary}", &sha[..7]),
+                    new_text: format!("show {sha}"),
+                    run_command: true,
+                })
+                .collect())
+        })
+    }
+
+    fn run(
+        self: Arc<Self>,
+        argument: Option<&str>,
+        workspace: WeakView<Workspace>,
+        _delegate: Option<Arc<dyn LspAdapterDelegate>>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<SlashCommandOutput>> {
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let args = match GitSlashCommandArgs::parse(argument) {
+            Ok(args) => args,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let project = workspace.read(cx).project().clone();
+
+        match args {
+            GitSlashCommandArgs::Diff { revision } => {
+                let label = match &revision {
+                    Some(revision) => format!("Changes since {revision}"),
+                    None => "Unstaged Changes".to_string(),
+                };
+                let base = match revision {
+                    Some(revision) => GitDiffBase::Revision(revision),
+                    None => GitDiffBase::Index,
+                };
+                let repositories = match local_repositories(&project, cx) {
+                    Ok(repositories) => repositories,
+                    Err(error) => return Task::ready(Err(error)),
+                };
+                let fs = project.read(cx).fs().clone();
+                cx.background_executor().spawn(async move {
+                    let mut diffs = Vec::new();
+                    for repository in repositories {
+                        diffs.extend(repository.working_copy_diffs(&base, fs.as_ref()).await?);
+                    }
+                    Ok(build_diff_output(&label, diffs))
+                })
+            }
+            GitSlashCommandArgs::Staged => {
+                let repositories = match local_repositories(&project, cx) {
+                    Ok(repositories) => repositories,
+                    Err(error) => return Task::ready(Err(error)),
+                };
+                cx.background_executor().spawn(async move {
+                    let mut diffs = Vec::new();
+                    for repository in repositories {
+                        diffs.extend(repository.staged_diffs()?);
+                    }
+                    Ok(build_diff_output("Staged Changes", diffs))
+                })
+            }
+            GitSlashCommandArgs::Log { path } => {
+                let project_path = match &path {
+                    Some(path) => project.read(cx).find_project_path(path, cx),
+                    None => root_project_path(&project, cx),
+                };
+                let Some(project_path) = project_path else {
+                    return Task::ready(Err(match path {
+                        Some(path) => anyhow!("no such path in the project: {path:?}"),
+                        None => anyhow!("no worktree to list the commits of"),
+                    }));
+                };
+                let commits = project.read(cx).git_log(project_path, None, LOG_LIMIT, cx);
+                cx.background_executor().spawn(async move {
+                    let label = match path {
+                        Some(path) => format!("Git Log ({})", path.display()),
+                        None => "Git Log".to_string(),
+                    };
+                    Ok(build_log_output(&label, &commits.await?))
+                })
+            }
+            GitSlashCommandArgs::Show { revision } => {
+                let Some(root) = root_project_path(&project, cx) else {
+                    return Task::ready(Err(anyhow!("no worktree to show the commit of")));
+                };
+                let commits = project
+                    .read(cx)
+                    .git_log(root.clone(), Some(revision.clone()), 1, cx);
+                cx.spawn(|mut cx| async move {
+                    let commit = commits
+                        .await?
+                        .into_iter()
+                        .next()
+                        .with_context(|| format!("unknown revision {revision:?}"))?;
+                    let details = project
+                        .update(&mut cx, |project, cx| {
+                            project.git_commit_details(root, commit.sha, cx)
+                        })?
+                        .await?;
+                    Ok(build_show_output(details))
+                })
+            }
+        }
+    }
+}
+
+/// The root of the first visible worktree, from which the repository containing it is found.
+fn root_project_path(project: &Model<Project>, cx: &AppContext) -> Option<ProjectPath> {
+    let worktree = project.read(cx).visible_worktrees(cx).next()?;
+    Some(ProjectPath {
+        worktree_id: worktree.read(cx).id(),
+        path: Path::new("").into(),
+    })
+}
+
+/// A change to a file, with its contents before and after.
+#[derive(Debug)]
+struct FileDiff {
+    path: PathBuf,
+    old_text: Option<String>,
+    new_text: Option<String>,
+}
+
+struct LocalRepository {
+    repo: Arc<dyn GitRepository>,
+    repo_entry: RepositoryEntry,
+    snapshot: Snapshot,
+    worktree_abs_path: Arc<Path>,
+}
+
+/// The repositories of the project's visible worktrees, which are only available to read
+/// directly in local projects.
+fn local_repositories(project: &Model<Project>, cx: &AppContext) -> Result<Vec<LocalRepository>> {
+    let mut repositories = Vec::new();
+    for worktree in project.read(cx).visible_worktrees(cx) {
+        let worktree = worktree.read(cx);
+        let Some(local_worktree) = worktree.as_local() else {
+            return Err(anyhow!(
+                "diffs are only available for local projects, but {:?} is remote",
+                worktree.root_name()
+            ));
+        };
+        let snapshot = worktree.snapshot();
+        for (_, repo_entry) in snapshot.repositories() {
+            if let Some(local_repo) = local_worktree.get_local_repo(repo_entry) {
+                repositories.push(LocalRepository {
+                    repo: local_repo.repo().clone(),
+                    repo_entry: repo_entry.clone(),
+                    snapshot: snapshot.clone(),
+                    worktree_abs_path: worktree.abs_path(),
+                });
+            }
+        }
+    }
+    if repositories.is_empty() {
+        return Err(anyhow!("the project has no git repositories"));
+    }
+    Ok(repositories)
+}
+
+impl LocalRepository {
+    /// The path shown for a file of the repository, which starts with the worktree's root name.
+    fn display_path(&self, path: &RepoPath) -> Option<PathBuf> {
+        let path = self.repo_entry.unrelativize(&self.snapshot, path)?;
+        Some(Path::new(self.snapshot.root_name()).join(path))
+    }
+
+    async fn working_copy_diffs(&self, base: &GitDiffBase, fs: &dyn Fs) -> Result<Vec<FileDiff>> {
+        let mut diffs = Vec::new();
+        for path in self.repo.changed_paths(base)? {
+            let Some(worktree_path) = self.repo_entry.unrelativize(&self.snapshot, &path) else {
+                continue;
+            };
+            let new_text = fs
+                .load(&self.worktree_abs_path.join(&worktree_path))
+                .await
+                .ok();
+            let old_text = self
+                .repo
+                .load_diff_base_text(base, &path)
+                .filter(|text| !text.is_empty() || new_text.is_none());
+            if let Some(path) = self.display_path(&path) {
+                diffs.push(FileDiff {
+                    path,
+                    old_text,
+                    new_text,
+                });
+            }
+        }
+        Ok(diffs)
+    }
+
+    fn staged_diffs(&self) -> Result<Vec<FileDiff>> {
+        let mut diffs = Vec::new();
+        for (path, _) in self.repo.statuses(Path::new(""))?.entries.iter() {
+            let old_text = self
+                .repo
+                .load_revision_text("HEAD", path)
+                .filter(|text| !text.is_empty());
+            let new_text = self.repo.load_index_text(path);
+            // Untracked files have a status, but nothing staged.
+            if old_text == new_text || (old_text.is_none() && new_text.is_none()) {
+                continue;
+            }
+            if let Some(path) = self.display_path(path) {
+                diffs.push(FileDiff {
+                    path,
+                    old_text,
+                    new_text,
+                });
+            }
+        }
+        Ok(diffs)
+    }
+}
+
+/// Renders a change to a file as a unified diff, as `git diff` does.
+fn unified_diff(diff: &FileDiff) -> String {
+    let path = diff.path.to_string_lossy();
+    let old_header = match diff.old_text {
+        Some(_) => format!("a/{path}"),
+        None => "/dev/null".to_string(),
+    };
+    let new_header = match diff.new_text {
+        Some(_) => format!("b/{path}"),
+        None => "/dev/null".to_string(),
+    };
+    let old_text = diff.old_text.as_deref().unwrap_or("");
+    let new_text = diff.new_text.as_deref().unwrap_or("");
+    TextDiff::from_lines(old_text, new_text)
+        .unified_diff()
+        .context_radius(3)
+        .header(&old_header, &new_header)
+        .to_string()
+}
+
+/// Writes a section for each file diff, returning the sections' ranges and labels.
+fn write_file_diffs(
+    text: &mut String,
+    diffs: &[FileDiff],
+) -> Vec<SlashCommandOutputSection<usize>> {
+    let mut sections = Vec::new();
+    for diff in diffs {
+        let start = text.len();
+        writeln!(text, "```diff {}", diff.path.display()).unwrap();
+        if diff.old_text.is_none() && diff.new_text.is_none() {
+            writeln!(text, "Binary files differ").unwrap();
+        } else {
+            text.push_str(&unified_diff(diff));
+            if !text.ends_with('\n') {
+                text.push('\n');
+            }
+        }
+        writeln!(text, "```").unwrap();
+        sections.push(SlashCommandOutputSection {
+            range: start..text.len() - 1,
+            icon: IconName::File,
+            label: diff.path.to_string_lossy().into_owned().into(),
+        });
+    }
+    sections
+}
+
+fn build_diff_output(label: &str, diffs: Vec<FileDiff>) -> SlashCommandOutput {
+    let mut text = String::new();
+    writeln!(text, "{label}:").unwrap();
+    if diffs.is_empty() {
+        writeln!(text, "No changes").unwrap();
+    }
+    let mut sections = write_file_diffs(&mut text, &diffs);
+    sections.insert(0, root_section(0..text.len() - 1, label.to_string()));
+    SlashCommandOutput {
+        text,
+        sections,
+        run_commands_in_text: false,
+    }
+}
+
+fn build_log_output(label: &str, commits: &[Commit]) -> SlashCommandOutput {
+    let mut text = String::new();
+    writeln!(text, "{label}:").unwrap();
+    if commits.is_empty() {
+        writeln!(text, "No commits").unwrap();
+    }
+    for commit in commits {
+        write!(
+            text,
+            "{} {} ({}, {})",
+            &commit.sha.to_string()[..7],
+            commit.summary,
+            commit.author_name,
+            format_timestamp(commit.timestamp)
+        )
+        .unwrap();
+        if !commit.refs.is_empty() {
+            let refs = commit
+                .refs
+                .iter()
+                .map(|commit_ref| commit_ref.name.as_str())
+                .collect::<Vec<_>>();
+            write!(text, " [{}]", refs.join(", ")).unwrap();
+        }
+        text.push('\n');
+    }
+    SlashCommandOutput {
+        sections: vec![root_section(0..text.len() - 1, label.to_string())],
+        text,
+        run_commands_in_text: false,
+    }
+}
+
+fn build_show_output(details: CommitDetails) -> SlashCommandOutput {
+    let commit = &details.commit;
+    let mut text = String::new();
+    writeln!(text, "commit {}", commit.sha).unwrap();
+    writeln!(
+        text,
+        "Author: {} <{}>",
+        commit.author_name, commit.author_email
+    )
+    .unwrap();
+    writeln!(text, "Date: {}", format_timestamp(commit.timestamp)).unwrap();
+    text.push('\n');
+    for line in details.message.trim_end().lines() {
+        writeln!(text, "    {line}").unwrap();
+    }
+    text.push('\n');
+
+    let diffs = details
+        .files
+        .into_iter()
+        .map(|file| FileDiff {
+            path: file.path.0.clone(),
+            old_text: file.old_text,
+            new_text: file.new_text,
+        })
+        .collect::<Vec<_>>();
+    let mut sections = write_file_diffs(&mut text, &diffs);
+    let label = format!("{} {}", &commit.sha.to_string()[..7], commit.summary);
+    sections.insert(0, root_section(0..text.len() - 1, label));
+    SlashCommandOutput {
+        text,
+        sections,
+        run_commands_in_text: false,
+    }
+}
+
+fn root_section(range: Range<usize>, label: String) -> SlashCommandOutputSection<usize> {
+    SlashCommandOutputSection {
+        range,
+        icon: IconName::FileGit,
+        label: label.into(),
+    }
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|date| date.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_local_repositories(cx: &mut TestAppContext) {
+        let settings_store = cx.update(SettingsStore::test);
+        cx.set_global(settings_store);
+        cx.update(Project::init_settings);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "plain": { "a.txt": "a" },
+                "repo": { ".git": {}, "b.txt": "b" },
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), [Path::new("/root/plain")], cx).await;
+        cx.run_until_parked();
+        let error = cx
+            .read(|cx| local_repositories(&project, cx))
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "the project has no git repositories");
+
+        let project = Project::test(fs, [Path::new("/root/repo")], cx).await;
+        cx.run_until_parked();
+        let repositories = cx.read(|cx| local_repositories(&project, cx)).unwrap();
+        assert_eq!(repositories.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_git_slash_command_args() {
+        assert_eq!(
+            GitSlashCommandArgs::parse(None).unwrap(),
+            GitSlashCommandArgs::Diff { revision: None }
+        );
+        assert_eq!(
+            GitSlashCommandArgs::parse(Some("diff main")).unwrap(),
+            GitSlashCommandArgs::Diff {
+                revision: Some("main".into())
+            }
+        );
+        assert_eq!(
+            GitSlashCommandArgs::parse(Some(" staged ")).unwrap(),
+            GitSlashCommandArgs::Staged
+        );
+        assert_eq!(
+            GitSlashCommandArgs::parse(Some("log src/main.rs")).unwrap(),
+            GitSlashCommandArgs::Log {
+                path: Some("src/main.rs".into())
+            }
+        );
+        assert_eq!(
+            GitSlashCommandArgs::parse(Some("show abc123")).unwrap(),
+            GitSlashCommandArgs::Show {
+                revision: "abc123".into()
+            }
+        );
+
+        assert!(GitSlashCommandArgs::parse(Some("show")).is_err());
+        assert!(GitSlashCommandArgs::parse(Some("staged main")).is_err());
+        assert!(GitSlashCommandArgs::parse(Some("diff a b")).is_err());
+        assert!(GitSlashCommandArgs::parse(Some("push")).is_err());
+    }
+
+    #[test]
+    fn test_build_diff_output() {
+        let output = build_diff_output(
+            "Staged Changes",
+            vec![
+                FileDiff {
+                    path: "project/a.txt".into(),
+                    old_text: Some("one\ntwo\n".into()),
+                    new_text: Some("one\nthree\n".into()),
+                },
+                FileDiff {
+                    path: "project/b.txt".into(),
+                    old_text: None,
+                    new_text: Some("new\n".into()),
+                },
+            ],
+        );
+        assert!(output.text.starts_with(concat!(
+            "Staged Changes:\n",
+            "```diff project/a.txt\n",
+            "--- a/project/a.txt\n",
+            "+++ b/project/a.txt\n",
+            "@@ -1,2 +1,2 @@\n",
+            " one\n",
+            "-two\n",
+            "+three\n",
+            "```\n",
+        )));
+        assert!(output.text.contains(concat!(
+            "```diff project/b.txt\n",
+            "--- /dev/null\n",
+            "+++ b/project/b.txt\n",
+        )));
+        assert!(output.text.ends_with("+new\n```\n"));
+
+        let labels = output
+            .sections
+            .iter()
+            .map(|section| {
+                (
+                    section.label.to_string(),
+                    &output.text[section.range.clone()],
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(labels[0].0, "Staged Changes");
+        assert_eq!(labels[0].1, output.text.trim_end());
+        assert_eq!(labels[1].0, "project/a.txt");
+        assert!(labels[1].1.starts_with("```diff project/a.txt\n"));
+        assert!(labels[1].1.ends_with("+three\n```"));
+        assert_eq!(labels[2].0, "project/b.txt");
+    }
+}
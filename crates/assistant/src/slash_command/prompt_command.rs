@@ -1,8 +1,8 @@
 use super::{SlashCommand, SlashCommandOutput};
-use crate::prompt_library::PromptStore;
+use crate::prompt_library::{ProjectPromptScope, PromptStore};
 use anyhow::{anyhow, Context, Result};
 use assistant_slash_command::{ArgumentCompletion, SlashCommandOutputSection};
-use gpui::{AppContext, Task, WeakView};
+use gpui::{AppContext, Task, View, WeakView};
 use language::LspAdapterDelegate;
 use std::sync::{atomic::AtomicBool, Arc};
 use ui::prelude::*;
@@ -31,12 +31,13 @@ impl SlashCommand for PromptSlashCommand {
         self: Arc<Self>,
         query: String,
         _cancellation_flag: Arc<AtomicBool>,
-        _workspace: Option<WeakView<Workspace>>,
+        workspace: Option<WeakView<Workspace>>,
         cx: &mut AppContext,
     ) -> Task<Result<Vec<ArgumentCompletion>>> {
+        let scope = project_prompt_scope(workspace.and_then(|workspace| workspace.upgrade()), cx);
         let store = PromptStore::global(cx);
         cx.background_executor().spawn(async move {
-            let prompts = store.await?.search(query).await;
+            let prompts = store.await?.search(query, scope).await;
             Ok(prompts
                 .into_iter()
                 .filter_map(|prompt| {
@@ -54,7 +55,7 @@ impl SlashCommand for PromptSlashCommand {
     fn run(
         self: Arc<Self>,
         title: Option<&str>,
-        _workspace: WeakView<Workspace>,
+        workspace: WeakView<Workspace>,
         _delegate: Option<Arc<dyn LspAdapterDelegate>>,
         cx: &mut WindowContext,
     ) -> Task<Result<SlashCommandOutput>> {
@@ -62,6 +63,7 @@ impl SlashCommand for PromptSlashCommand {
             return Task::ready(Err(anyhow!("missing prompt name")));
         };
 
+        let scope = project_prompt_scope(workspace.upgrade(), cx);
         let store = PromptStore::global(cx);
         let title = SharedString::from(title.to_string());
         let prompt = cx.background_executor().spawn({
@@ -69,7 +71,7 @@ impl SlashCommand for PromptSlashCommand {
             async move {
                 let store = store.await?;
                 let prompt_id = store
-                    .id_for_title(&title)
+                    .id_for_title(&title, scope)
                     .with_context(|| format!("no prompt found with title {:?}", title))?;
                 let body = store.load(prompt_id).await?;
                 anyhow::Ok(body)
@@ -93,3 +95,10 @@ impl SlashCommand for PromptSlashCommand {
         })
     }
 }
+
+/// Only the prompts of the workspace's own project can be inserted.
+fn project_prompt_scope(workspace: Option<View<Workspace>>, cx: &AppContext) -> ProjectPromptScope {
+    workspace.map_or(ProjectPromptScope::None, |workspace| {
+        ProjectPromptScope::Project(workspace.read(cx).project().entity_id())
+    })
+}
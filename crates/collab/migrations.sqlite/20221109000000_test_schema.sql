@@ -95,6 +95,9 @@ CREATE TABLE "worktree_repositories" (
     "worktree_id" INTEGER NOT NULL,
     "work_directory_id" INTEGER NOT NULL,
     "branch" VARCHAR,
+    "upstream" VARCHAR,
+    "upstream_ahead" INTEGER NOT NULL DEFAULT 0,
+    "upstream_behind" INTEGER NOT NULL DEFAULT 0,
     "scan_id" INTEGER NOT NULL,
     "is_deleted" BOOL NOT NULL,
     PRIMARY KEY(project_id, worktree_id, work_directory_id),
@@ -0,0 +1,3 @@
+ALTER TABLE worktree_repositories ADD COLUMN upstream VARCHAR;
+ALTER TABLE worktree_repositories ADD COLUMN upstream_ahead INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE worktree_repositories ADD COLUMN upstream_behind INTEGER NOT NULL DEFAULT 0;
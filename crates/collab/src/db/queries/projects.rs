@@ -366,13 +366,25 @@ impl Database {
 
             if !update.updated_repositories.is_empty() {
                 worktree_repository::Entity::insert_many(update.updated_repositories.iter().map(
-                    |repository| worktree_repository::ActiveModel {
-                        project_id: ActiveValue::set(project_id),
-                        worktree_id: ActiveValue::set(worktree_id),
-                        work_directory_id: ActiveValue::set(repository.work_directory_id as i64),
-                        scan_id: ActiveValue::set(update.scan_id as i64),
-                        branch: ActiveValue::set(repository.branch.clone()),
-                        is_deleted: ActiveValue::set(false),
+                    |repository| {
+                        let upstream = repository.upstream.as_ref();
+                        worktree_repository::ActiveModel {
+                            project_id: ActiveValue::set(project_id),
+                            worktree_id: ActiveValue::set(worktree_id),
+                            work_directory_id: ActiveValue::set(
+                                repository.work_directory_id as i64,
+                            ),
+                            scan_id: ActiveValue::set(update.scan_id as i64),
+                            branch: ActiveValue::set(repository.branch.clone()),
+                            upstream: ActiveValue::set(upstream.map(|u| u.name.clone())),
+                            upstream_ahead: ActiveValue::set(
+                                upstream.map_or(0, |u| u.ahead as i32),
+                            ),
+                            upstream_behind: ActiveValue::set(
+                                upstream.map_or(0, |u| u.behind as i32),
+                            ),
+                            is_deleted: ActiveValue::set(false),
+                        }
                     },
                 ))
                 .on_conflict(
@@ -384,6 +396,9 @@ impl Database {
                     .update_columns([
                         worktree_repository::Column::ScanId,
                         worktree_repository::Column::Branch,
+                        worktree_repository::Column::Upstream,
+                        worktree_repository::Column::UpstreamAhead,
+                        worktree_repository::Column::UpstreamBehind,
                     ])
                     .to_owned(),
                 )
@@ -750,6 +765,7 @@ impl Database {
                         db_repository_entry.work_directory_id as u64,
                         proto::RepositoryEntry {
                             work_directory_id: db_repository_entry.work_directory_id as u64,
+                            upstream: db_repository_entry.proto_upstream(),
                             branch: db_repository_entry.branch,
                         },
                     );
@@ -695,6 +695,7 @@ impl Database {
                     } else {
                         worktree.updated_repositories.push(proto::RepositoryEntry {
                             work_directory_id: db_repository.work_directory_id as u64,
+                            upstream: db_repository.proto_upstream(),
                             branch: db_repository.branch,
                         });
                     }
@@ -1,4 +1,5 @@
 use crate::db::ProjectId;
+use rpc::proto;
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
@@ -12,9 +13,22 @@ pub struct Model {
     pub work_directory_id: i64,
     pub scan_id: i64,
     pub branch: Option<String>,
+    pub upstream: Option<String>,
+    pub upstream_ahead: i32,
+    pub upstream_behind: i32,
     pub is_deleted: bool,
 }
 
+impl Model {
+    pub fn proto_upstream(&self) -> Option<proto::GitUpstream> {
+        Some(proto::GitUpstream {
+            name: self.upstream.clone()?,
+            ahead: self.upstream_ahead as u64,
+            behind: self.upstream_behind as u64,
+        })
+    }
+}
+
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {}
 
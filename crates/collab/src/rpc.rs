@@ -574,9 +574,61 @@ impl Server {
             .add_request_handler(user_handler(
                 forward_mutating_project_request::<proto::LinkedEditingRange>,
             ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::PrepareCallHierarchy>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetIncomingCalls>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetOutgoingCalls>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::PrepareTypeHierarchy>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetSupertypes>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetSubtypes>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetSemanticTokens>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetSemanticTokensDelta>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetFoldingRanges>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetCodeLens>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::ResolveCodeLens>,
+            ))
+            .add_request_handler(user_handler(
+                forward_mutating_project_request::<proto::ExecuteCodeLensCommand>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetSelectionRanges>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetDocumentLinks>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GitLog>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GitCommitDetails>,
+            ))
             .add_message_handler(create_buffer_for_peer)
             .add_request_handler(update_buffer)
             .add_message_handler(broadcast_project_message_from_host::<proto::RefreshInlayHints>)
+            .add_message_handler(
+                broadcast_project_message_from_host::<proto::RefreshSemanticTokens>,
+            )
+            .add_message_handler(broadcast_project_message_from_host::<proto::RefreshCodeLens>)
             .add_message_handler(broadcast_project_message_from_host::<proto::UpdateBufferFile>)
             .add_message_handler(broadcast_project_message_from_host::<proto::BufferReloaded>)
             .add_message_handler(broadcast_project_message_from_host::<proto::BufferSaved>)
@@ -625,6 +677,9 @@ impl Server {
             .add_request_handler(user_handler(
                 forward_mutating_project_request::<proto::CreateContext>,
             ))
+            .add_request_handler(user_handler(
+                forward_mutating_project_request::<proto::ForkContext>,
+            ))
             .add_request_handler(user_handler(
                 forward_mutating_project_request::<proto::SynchronizeContexts>,
             ))
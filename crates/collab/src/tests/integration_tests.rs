@@ -12,7 +12,10 @@ use client::{User, RECEIVE_TIMEOUT};
 use collections::{HashMap, HashSet};
 use fs::{FakeFs, Fs as _, RemoveOptions};
 use futures::{channel::mpsc, StreamExt as _};
-use git::repository::GitFileStatus;
+use git::{
+    log::{Commit, CommitDetails, CommitFileChange},
+    repository::{GitDiffBase, GitFileStatus, Upstream},
+};
 use gpui::{
     px, size, AppContext, BackgroundExecutor, Model, Modifiers, MouseButton, MouseDownEvent,
     TestAppContext, UpdateGlobal,
@@ -2850,6 +2853,407 @@ async fn test_git_branch_name(
     });
 }
 
+#[gpui::test]
+async fn test_git_upstream(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+    cx_c: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    let client_c = server.create_client(cx_c, "user_c").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b), (&client_c, cx_c)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/dir",
+            json!({
+            ".git": {},
+            }),
+        )
+        .await;
+    let dot_git = Path::new("/dir/.git");
+    client_a.fs().set_branch_name(dot_git, Some("main"));
+    client_a.fs().set_upstream_for_repo(
+        dot_git,
+        "main",
+        Some(Upstream {
+            name: "origin/main".into(),
+            ahead: 0,
+            behind: 3,
+        }),
+    );
+
+    let (project_local, _worktree_id) = client_a.build_local_project("/dir", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| {
+            call.share_project(project_local.clone(), cx)
+        })
+        .await
+        .unwrap();
+    let project_remote = client_b.build_dev_server_project(project_id, cx_b).await;
+    executor.run_until_parked();
+
+    #[track_caller]
+    fn assert_upstream(upstream: Option<(&str, usize, usize)>, project: &Project, cx: &AppContext) {
+        let worktree = project.visible_worktrees(cx).next().unwrap();
+        let root_entry = worktree.read(cx).snapshot().root_git_entry().unwrap();
+        assert_eq!(
+            root_entry.upstream().map(|upstream| (
+                upstream.name.as_ref(),
+                upstream.ahead,
+                upstream.behind
+            )),
+            upstream
+        );
+    }
+
+    project_local.read_with(cx_a, |project, cx| {
+        assert_upstream(Some(("origin/main", 0, 3)), project, cx)
+    });
+    project_remote.read_with(cx_b, |project, cx| {
+        assert_upstream(Some(("origin/main", 0, 3)), project, cx)
+    });
+
+    // Fast-forwarding to the upstream catches the branch up with it.
+    let repo = project_local
+        .read_with(cx_a, |project, cx| project.get_first_worktree_root_repo(cx))
+        .unwrap();
+    repo.merge_fast_forward("origin/main").unwrap();
+    client_a.fs().with_git_state(dot_git, true, |_| {});
+    executor.run_until_parked();
+
+    project_local.read_with(cx_a, |project, cx| {
+        assert_upstream(Some(("origin/main", 0, 0)), project, cx)
+    });
+    project_remote.read_with(cx_b, |project, cx| {
+        assert_upstream(Some(("origin/main", 0, 0)), project, cx)
+    });
+
+    // Guests joining later load the upstream from the database.
+    client_a.fs().set_upstream_for_repo(
+        dot_git,
+        "main",
+        Some(Upstream {
+            name: "origin/main".into(),
+            ahead: 2,
+            behind: 1,
+        }),
+    );
+    executor.run_until_parked();
+    assert!(repo.merge_fast_forward("origin/main").is_err());
+
+    let project_remote_c = client_c.build_dev_server_project(project_id, cx_c).await;
+    executor.run_until_parked();
+    project_remote_c.read_with(cx_c, |project, cx| {
+        assert_upstream(Some(("origin/main", 2, 1)), project, cx)
+    });
+
+    client_a.fs().set_upstream_for_repo(dot_git, "main", None);
+    executor.run_until_parked();
+    project_remote.read_with(cx_b, |project, cx| assert_upstream(None, project, cx));
+}
+
+#[gpui::test]
+async fn test_git_diff_base_selection(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                "a.txt": "one\ntwo\nthree\n",
+                "b.txt": "new file\n",
+            }),
+        )
+        .await;
+    let dot_git = Path::new("/dir/.git");
+    let merge_base = "1111111111111111111111111111111111111111"
+        .parse::<git::Oid>()
+        .unwrap();
+    client_a.fs().set_index_for_repo(
+        dot_git,
+        &[
+            (Path::new("a.txt"), "one\ntwo\nthree\n".into()),
+            (Path::new("b.txt"), "new file\n".into()),
+        ],
+    );
+    client_a.fs().set_revision_contents_for_repo(
+        dot_git,
+        &merge_base.to_string(),
+        &[(Path::new("a.txt"), "one\nthree\n".into())],
+    );
+    client_a
+        .fs()
+        .set_merge_base_for_repo(dot_git, "main", merge_base);
+
+    let (project_local, worktree_id) = client_a.build_local_project("/dir", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| {
+            call.share_project(project_local.clone(), cx)
+        })
+        .await
+        .unwrap();
+    let project_remote = client_b.build_dev_server_project(project_id, cx_b).await;
+
+    let buffer_local = project_local
+        .update(cx_a, |p, cx| p.open_buffer((worktree_id, "a.txt"), cx))
+        .await
+        .unwrap();
+    let buffer_remote = project_remote
+        .update(cx_b, |p, cx| p.open_buffer((worktree_id, "a.txt"), cx))
+        .await
+        .unwrap();
+    executor.run_until_parked();
+
+    // By default, buffers are diffed against the index.
+    buffer_remote.read_with(cx_b, |buffer, _| {
+        assert_eq!(
+            buffer.diff_base().map(|rope| rope.to_string()).as_deref(),
+            Some("one\ntwo\nthree\n")
+        );
+    });
+    let changed_paths = project_local
+        .read_with(cx_a, |project, cx| project.git_changed_paths(cx))
+        .await
+        .unwrap();
+    assert!(changed_paths.is_empty());
+
+    // Diffing against the merge base shows every change made on the branch.
+    project_local.update(cx_a, |project, cx| {
+        project.set_git_diff_base(GitDiffBase::MergeBase("main".into()), cx)
+    });
+    executor.run_until_parked();
+
+    buffer_local.read_with(cx_a, |buffer, _| {
+        git::diff::assert_hunks(
+            buffer.snapshot().git_diff_hunks_in_row_range(0..4),
+            &buffer,
+            "one\nthree\n",
+            &[(1..2, "", "two\n")],
+        );
+    });
+    buffer_remote.read_with(cx_b, |buffer, _| {
+        assert_eq!(
+            buffer.diff_base().map(|rope| rope.to_string()).as_deref(),
+            Some("one\nthree\n")
+        );
+    });
+
+    let changed_paths = project_local
+        .read_with(cx_a, |project, cx| project.git_changed_paths(cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        changed_paths,
+        vec![
+            (worktree_id, Path::new("a.txt")).into(),
+            (worktree_id, Path::new("b.txt")).into(),
+        ]
+    );
+
+    // Newly opened buffers use the selected diff base too.
+    let buffer_b = project_local
+        .update(cx_a, |p, cx| p.open_buffer((worktree_id, "b.txt"), cx))
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    buffer_b.read_with(cx_a, |buffer, _| {
+        assert_eq!(
+            buffer.diff_base().map(|rope| rope.to_string()).as_deref(),
+            Some("")
+        );
+    });
+
+    // Worktrees added afterwards are diffed against the selected base as well.
+    client_a
+        .fs()
+        .insert_tree(
+            "/other",
+            json!({
+                ".git": {},
+                "c.txt": "one
+two
+",
+            }),
+        )
+        .await;
+    let other_dot_git = Path::new("/other/.git");
+    client_a.fs().set_index_for_repo(
+        other_dot_git,
+        &[(
+            Path::new("c.txt"),
+            "one
+two
+"
+            .into(),
+        )],
+    );
+    client_a.fs().set_revision_contents_for_repo(
+        other_dot_git,
+        &merge_base.to_string(),
+        &[(
+            Path::new("c.txt"),
+            "one
+"
+            .into(),
+        )],
+    );
+    client_a
+        .fs()
+        .set_merge_base_for_repo(other_dot_git, "main", merge_base);
+    let (other_worktree, _) = project_local
+        .update(cx_a, |p, cx| p.find_or_create_worktree("/other", true, cx))
+        .await
+        .unwrap();
+    let other_worktree_id = other_worktree.read_with(cx_a, |worktree, _| worktree.id());
+    let buffer_c = project_local
+        .update(cx_a, |p, cx| {
+            p.open_buffer((other_worktree_id, "c.txt"), cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    buffer_c.read_with(cx_a, |buffer, _| {
+        assert_eq!(
+            buffer.diff_base().map(|rope| rope.to_string()).as_deref(),
+            Some("one\n")
+        );
+    });
+    project_local.read_with(cx_a, |project, _| {
+        assert_eq!(
+            project.git_diff_base(),
+            Some(GitDiffBase::MergeBase("main".into()))
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_git_log(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                "a.txt": "one\ntwo\n",
+                "b.txt": "three\n",
+            }),
+        )
+        .await;
+
+    fn commit_details(
+        sha: &str,
+        summary: &str,
+        files: &[(&str, Option<&str>, &str)],
+    ) -> CommitDetails {
+        CommitDetails {
+            commit: Commit {
+                sha: sha.parse().unwrap(),
+                parents: Vec::new(),
+                author_name: "Joe Schmoe".into(),
+                author_email: "joe@example.com".into(),
+                timestamp: 1709741400,
+                summary: summary.into(),
+                refs: Vec::new(),
+            },
+            message: format!("{summary}\n"),
+            files: files
+                .iter()
+                .map(|(path, old_text, new_text)| CommitFileChange {
+                    path: Path::new(path).into(),
+                    old_path: None,
+                    old_text: old_text.map(Into::into),
+                    new_text: Some(new_text.to_string()),
+                })
+                .collect(),
+        }
+    }
+    let commits = vec![
+        commit_details(
+            "2222222222222222222222222222222222222222",
+            "Change a",
+            &[("a.txt", Some("one\n"), "one\ntwo\n")],
+        ),
+        commit_details(
+            "1111111111111111111111111111111111111111",
+            "Add a and b",
+            &[("a.txt", None, "one\n"), ("b.txt", None, "three\n")],
+        ),
+    ];
+    client_a
+        .fs()
+        .set_commits_for_repo(Path::new("/dir/.git"), commits.clone());
+
+    let (project_a, worktree_id) = client_a.build_local_project("/dir", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_dev_server_project(project_id, cx_b).await;
+    executor.run_until_parked();
+
+    let log = project_b
+        .update(cx_b, |project, cx| {
+            project.git_log((worktree_id, "").into(), None, 10, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        log,
+        commits
+            .iter()
+            .map(|details| details.commit.clone())
+            .collect::<Vec<_>>()
+    );
+
+    let file_history = project_b
+        .update(cx_b, |project, cx| {
+            project.git_log((worktree_id, "b.txt").into(), None, 10, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(file_history, vec![commits[1].commit.clone()]);
+
+    let details = project_b
+        .update(cx_b, |project, cx| {
+            project.git_commit_details((worktree_id, "").into(), commits[0].commit.sha, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(details, commits[0]);
+}
+
 #[gpui::test]
 async fn test_git_status_sync(
     executor: BackgroundExecutor,
@@ -4861,6 +5265,127 @@ async fn test_references(
     });
 }
 
+#[gpui::test(iterations = 10)]
+async fn test_call_hierarchy(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a.language_registry().add(rust_lang());
+    let mut fake_language_servers = client_a.language_registry().register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/root",
+            json!({
+                "one.rs": "fn one() {}",
+                "two.rs": "fn two() {\n    one::one();\n}",
+            }),
+        )
+        .await;
+    let (project_a, worktree_id) = client_a.build_local_project("/root", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_dev_server_project(project_id, cx_b).await;
+
+    let open_buffer = project_b.update(cx_b, |p, cx| p.open_buffer((worktree_id, "one.rs"), cx));
+    let buffer_b = cx_b.executor().spawn(open_buffer).await.unwrap();
+
+    let fake_language_server = fake_language_servers.next().await.unwrap();
+    let one = lsp::CallHierarchyItem {
+        name: "one".to_string(),
+        kind: lsp::SymbolKind::FUNCTION,
+        tags: None,
+        detail: None,
+        uri: lsp::Url::from_file_path("/root/one.rs").unwrap(),
+        range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, 11)),
+        selection_range: lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 6)),
+        data: None,
+    };
+    let two = lsp::CallHierarchyItem {
+        name: "two".to_string(),
+        uri: lsp::Url::from_file_path("/root/two.rs").unwrap(),
+        range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(2, 1)),
+        ..one.clone()
+    };
+    fake_language_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>({
+        let one = one.clone();
+        move |_, _| {
+            let one = one.clone();
+            async move { Ok(Some(vec![one])) }
+        }
+    });
+    fake_language_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>({
+        let two = two.clone();
+        move |params, _| {
+            let two = two.clone();
+            async move {
+                assert_eq!(params.item.name, "one");
+                Ok(Some(vec![lsp::CallHierarchyIncomingCall {
+                    from: two,
+                    from_ranges: vec![lsp::Range::new(
+                        lsp::Position::new(1, 9),
+                        lsp::Position::new(1, 12),
+                    )],
+                }]))
+            }
+        }
+    });
+
+    // The guest prepares the hierarchy and expands it into another file.
+    let prepared = project_b
+        .update(cx_b, |project, cx| {
+            project.prepare_call_hierarchy(&buffer_b, 4, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(prepared.len(), 1);
+    assert_eq!(prepared[0].lsp_item, one);
+    assert_eq!(prepared[0].location.buffer, buffer_b);
+
+    let incoming = project_b
+        .update(cx_b, |project, cx| project.incoming_calls(&prepared[0], cx))
+        .await
+        .unwrap();
+    assert_eq!(incoming.len(), 1);
+    assert_eq!(incoming[0].lsp_item, two);
+    incoming[0].location.buffer.read_with(cx_b, |buffer, _| {
+        assert_eq!(buffer.file().unwrap().path().as_ref(), Path::new("two.rs"));
+        assert_eq!(
+            incoming[0].location.range.to_point(buffer),
+            Point::new(0, 3)..Point::new(0, 6)
+        );
+        assert_eq!(
+            incoming[0]
+                .call_sites
+                .iter()
+                .map(|range| range.to_point(buffer))
+                .collect::<Vec<_>>(),
+            vec![Point::new(1, 9)..Point::new(1, 12)]
+        );
+    });
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_project_search(
     executor: BackgroundExecutor,
@@ -6573,3 +7098,139 @@ async fn test_context_collaboration_with_reconnect(
         assert!(context.buffer().read(cx).read_only());
     });
 }
+
+#[gpui::test]
+async fn test_context_alternates_and_forks(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a.fs().insert_tree("/a", Default::default()).await;
+    let (project_a, _) = client_a.build_local_project("/a", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_dev_server_project(project_id, cx_b).await;
+    executor.run_until_parked();
+
+    let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+    let context_store_a = cx_a
+        .update(|cx| ContextStore::new(project_a.clone(), prompt_builder.clone(), cx))
+        .await
+        .unwrap();
+    let context_store_b = cx_b
+        .update(|cx| ContextStore::new(project_b.clone(), prompt_builder.clone(), cx))
+        .await
+        .unwrap();
+    let model_a = cx_a.read(|cx| {
+        language_model::LanguageModelRegistry::read_global(cx)
+            .active_model()
+            .unwrap()
+    });
+
+    // The host generates two responses to the same message.
+    let context_a = context_store_a.update(cx_a, |store, cx| store.create(cx));
+    context_a.update(cx_a, |context, cx| {
+        context
+            .buffer()
+            .update(cx, |buffer, cx| buffer.edit([(0..0, "Hello")], None, cx));
+        context.assist(cx);
+    });
+    executor.run_until_parked();
+    model_a
+        .as_fake()
+        .stream_last_completion_response("One".into());
+    model_a.as_fake().end_last_completion_stream();
+    executor.run_until_parked();
+    let message_id =
+        context_a.read_with(cx_a, |context, cx| context.messages(cx).nth(1).unwrap().id);
+    assert!(context_a.update(cx_a, |context, cx| context.regenerate(message_id, cx)));
+    executor.run_until_parked();
+    model_a
+        .as_fake()
+        .stream_last_completion_response("Two".into());
+    model_a.as_fake().end_last_completion_stream();
+    executor.run_until_parked();
+
+    // A guest joining the context sees all the responses.
+    let context_b = context_store_b
+        .update(cx_b, |store, cx| {
+            let host_contexts = store.host_contexts().to_vec();
+            assert_eq!(host_contexts.len(), 1);
+            store.open_remote_context(host_contexts[0].id.clone(), cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+
+    #[track_caller]
+    fn assert_response(
+        context: &Model<assistant::Context>,
+        message_id: assistant::MessageId,
+        expected: (&str, usize, usize),
+        cx: &TestAppContext,
+    ) {
+        cx.read(|cx| {
+            let context = context.read(cx);
+            let message = context
+                .messages(cx)
+                .find(|message| message.id == message_id)
+                .unwrap();
+            assert_eq!(
+                (
+                    context.buffer().read(cx).text().as_str(),
+                    message.alternate_ix,
+                    message.alternate_count
+                ),
+                expected
+            );
+        });
+    }
+
+    assert_response(&context_a, message_id, ("Hello\nTwo\n", 1, 2), cx_a);
+    assert_response(&context_b, message_id, ("Hello\nTwo\n", 1, 2), cx_b);
+
+    // Only the host switches between responses, which guests then see.
+    let switched_b = context_b.update(cx_b, |context, cx| {
+        context.select_alternate(message_id, 0, cx)
+    });
+    assert!(!switched_b);
+    let switched_a = context_a.update(cx_a, |context, cx| {
+        context.select_alternate(message_id, 0, cx)
+    });
+    assert!(switched_a);
+    executor.run_until_parked();
+    assert_response(&context_a, message_id, ("Hello\nOne\n", 0, 2), cx_a);
+    assert_response(&context_b, message_id, ("Hello\nOne\n", 0, 2), cx_b);
+
+    // Guests fork contexts through the host, which advertises the fork.
+    let fork_b = context_store_b
+        .update(cx_b, |store, cx| {
+            store.fork_context(&context_b, message_id, cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    assert_eq!(
+        fork_b.read_with(cx_b, |context, cx| context.buffer().read(cx).text()),
+        "Hello\nOne"
+    );
+    let fork_id = fork_b.read_with(cx_b, |context, _| context.id().clone());
+    context_store_b.read_with(cx_b, |store, _| {
+        let fork = store
+            .host_contexts()
+            .iter()
+            .find(|context| context.id == fork_id)
+            .unwrap();
+        assert!(fork.forked_from.is_some());
+    });
+}
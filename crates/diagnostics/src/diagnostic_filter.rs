@@ -0,0 +1,82 @@
+use language::Diagnostic;
+use project::project_settings::{DiagnosticPattern, DiagnosticSeverityContent};
+
+/// How the diagnostics in the project diagnostics view are grouped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum DiagnosticGrouping {
+    #[default]
+    None,
+    Code,
+    Source,
+}
+
+impl DiagnosticGrouping {
+    pub(crate) fn next(self) -> Self {
+        match self {
+            Self::None => Self::Code,
+            Self::Code => Self::Source,
+            Self::Source => Self::None,
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::None => "Group by Code",
+            Self::Code => "Group by Source",
+            Self::Source => "Ungroup",
+        }
+    }
+
+    /// The key of the group a diagnostic belongs to. Diagnostics without a code or
+    /// source are grouped under `None`, as is everything when grouping is disabled.
+    pub(crate) fn key(self, server_name: Option<&str>, diagnostic: &Diagnostic) -> Option<String> {
+        match self {
+            Self::None => None,
+            Self::Code => diagnostic.code.clone(),
+            Self::Source => diagnostic
+                .source
+                .clone()
+                .or_else(|| server_name.map(ToString::to_string)),
+        }
+    }
+
+    pub(crate) fn missing_key_label(self) -> &'static str {
+        match self {
+            Self::None => "",
+            Self::Code => "no code",
+            Self::Source => "no source",
+        }
+    }
+}
+
+/// Parses the query typed into the diagnostics filter.
+///
+/// `source:`, `code:`, `severity:` and `path:` prefixed terms restrict the
+/// corresponding field, and all remaining words are matched against the message.
+pub(crate) fn parse_filter_query(query: &str) -> DiagnosticPattern {
+    let mut pattern = DiagnosticPattern::default();
+    let mut message_words = Vec::new();
+    for word in query.split_whitespace() {
+        match word.split_once(':') {
+            Some(("source", source)) if !source.is_empty() => {
+                pattern.source = Some(source.to_string())
+            }
+            Some(("code", code)) if !code.is_empty() => pattern.code = Some(code.to_string()),
+            Some(("path", path)) if !path.is_empty() => pattern.path = Some(path.to_string()),
+            Some(("severity", severity)) => {
+                pattern.severity = match severity.to_lowercase().as_str() {
+                    "error" => Some(DiagnosticSeverityContent::Error),
+                    "warning" => Some(DiagnosticSeverityContent::Warning),
+                    "info" => Some(DiagnosticSeverityContent::Info),
+                    "hint" => Some(DiagnosticSeverityContent::Hint),
+                    _ => None,
+                }
+            }
+            _ => message_words.push(word),
+        }
+    }
+    if !message_words.is_empty() {
+        pattern.message = Some(message_words.join(" "));
+    }
+    pattern
+}
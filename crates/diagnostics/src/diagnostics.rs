@@ -1,3 +1,4 @@
+mod diagnostic_filter;
 pub mod items;
 mod project_diagnostics_settings;
 mod toolbar_controls;
@@ -6,7 +7,8 @@ mod toolbar_controls;
 mod diagnostics_tests;
 
 use anyhow::Result;
-use collections::{BTreeSet, HashSet};
+use collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use diagnostic_filter::{parse_filter_query, DiagnosticGrouping};
 use editor::{
     diagnostic_block_renderer,
     display_map::{BlockDisposition, BlockProperties, BlockStyle, CustomBlockId, RenderBlock},
@@ -25,10 +27,11 @@ use gpui::{
     WeakView, WindowContext,
 };
 use language::{
-    Bias, Buffer, Diagnostic, DiagnosticEntry, DiagnosticSeverity, Point, Selection, SelectionGoal,
+    Bias, Buffer, Diagnostic, DiagnosticEntry, DiagnosticGroup, DiagnosticSeverity, Point,
+    Selection, SelectionGoal,
 };
 use lsp::LanguageServerId;
-use project::{DiagnosticSummary, Project, ProjectPath};
+use project::{project_settings::DiagnosticMatcher, DiagnosticSummary, Project, ProjectPath};
 use project_diagnostics_settings::ProjectDiagnosticsSettings;
 use settings::Settings;
 use std::{
@@ -46,7 +49,7 @@ use workspace::{
     ItemNavHistory, ToolbarItemLocation, Workspace,
 };
 
-actions!(diagnostics, [Deploy, ToggleWarnings]);
+actions!(diagnostics, [Deploy, ToggleWarnings, CycleGrouping]);
 
 pub fn init(cx: &mut AppContext) {
     ProjectDiagnosticsSettings::register(cx);
@@ -64,6 +67,10 @@ struct ProjectDiagnosticsEditor {
     path_states: Vec<PathState>,
     paths_to_update: BTreeSet<(ProjectPath, LanguageServerId)>,
     include_warnings: bool,
+    filter_editor: View<Editor>,
+    filter: Option<DiagnosticMatcher>,
+    grouping: DiagnosticGrouping,
+    group_header_blocks: Vec<CustomBlockId>,
     context: u32,
     update_paths_tx: UnboundedSender<(ProjectPath, Option<LanguageServerId>)>,
     _update_excerpts_task: Task<Result<()>>,
@@ -71,6 +78,7 @@ struct ProjectDiagnosticsEditor {
 }
 
 struct PathState {
+    group_key: Option<String>,
     path: ProjectPath,
     diagnostic_groups: Vec<DiagnosticGroupState>,
 }
@@ -95,7 +103,11 @@ impl Render for ProjectDiagnosticsEditor {
                 .items_center()
                 .justify_center()
                 .size_full()
-                .child(Label::new("No problems in workspace"))
+                .child(Label::new(if self.filter.is_some() {
+                    "No problems match the filter"
+                } else {
+                    "No problems in workspace"
+                }))
         } else {
             div().size_full().child(self.editor.clone())
         };
@@ -107,6 +119,7 @@ impl Render for ProjectDiagnosticsEditor {
             })
             .size_full()
             .on_action(cx.listener(Self::toggle_warnings))
+            .on_action(cx.listener(Self::cycle_grouping))
             .child(child)
     }
 }
@@ -182,6 +195,18 @@ impl ProjectDiagnosticsEditor {
         })
         .detach();
 
+        let filter_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Filter (source: code: severity: path:)", cx);
+            editor
+        });
+        cx.subscribe(&filter_editor, |this, _, event: &EditorEvent, cx| {
+            if let EditorEvent::BufferEdited = event {
+                this.update_filter(cx);
+            }
+        })
+        .detach();
+
         let (update_excerpts_tx, mut update_excerpts_rx) = mpsc::unbounded();
 
         let project = project_handle.read(cx);
@@ -196,6 +221,10 @@ impl ProjectDiagnosticsEditor {
             path_states: Default::default(),
             paths_to_update: Default::default(),
             include_warnings: ProjectDiagnosticsSettings::get_global(cx).include_warnings,
+            filter_editor,
+            filter: None,
+            grouping: DiagnosticGrouping::default(),
+            group_header_blocks: Vec::new(),
             update_paths_tx: update_excerpts_tx,
             _update_excerpts_task: cx.spawn(move |this, mut cx| async move {
                 while let Some((path, language_server_id)) = update_excerpts_rx.next().await {
@@ -248,6 +277,37 @@ impl ProjectDiagnosticsEditor {
         cx.notify();
     }
 
+    fn cycle_grouping(&mut self, _: &CycleGrouping, cx: &mut ViewContext<Self>) {
+        self.grouping = self.grouping.next();
+        self.enqueue_update_all_excerpts(cx);
+        cx.notify();
+    }
+
+    fn update_filter(&mut self, cx: &mut ViewContext<Self>) {
+        let query = self.filter_editor.read(cx).text(cx);
+        let pattern = parse_filter_query(&query);
+        self.filter = if pattern.is_empty() {
+            None
+        } else {
+            pattern.matcher().log_err()
+        };
+        self.enqueue_update_all_excerpts(cx);
+        cx.notify();
+    }
+
+    /// The number of diagnostic groups shown under each group key, when grouping is enabled.
+    fn group_counts(&self) -> Vec<(Option<String>, usize)> {
+        if self.grouping == DiagnosticGrouping::None {
+            return Vec::new();
+        }
+        let mut counts = BTreeMap::<Option<String>, usize>::default();
+        for path_state in &self.path_states {
+            *counts.entry(path_state.group_key.clone()).or_default() +=
+                path_state.diagnostic_groups.len();
+        }
+        counts.into_iter().collect()
+    }
+
     fn focus_in(&mut self, cx: &mut ViewContext<Self>) {
         if self.focus_handle.is_focused(cx) && !self.path_states.is_empty() {
             self.editor.focus_handle(cx).focus(cx)
@@ -303,15 +363,158 @@ impl ProjectDiagnosticsEditor {
 
         let was_empty = self.path_states.is_empty();
         let snapshot = buffer.read(cx).snapshot();
-        let path_ix = match self
+        let max_severity = if self.include_warnings {
+            DiagnosticSeverity::WARNING
+        } else {
+            DiagnosticSeverity::ERROR
+        };
+        let server_names = self
+            .project
+            .read(cx)
+            .language_server_statuses()
+            .map(|(id, status)| (id, status.name.clone()))
+            .collect::<HashMap<_, _>>();
+        let mut new_groups_by_key = BTreeMap::<Option<String>, Vec<_>>::default();
+        for (language_server_id, group) in snapshot.diagnostic_groups(server_to_update) {
+            let primary = &group.entries[group.primary_ix].diagnostic;
+            let server_name = server_names.get(&language_server_id).map(String::as_str);
+            if primary.severity > max_severity
+                || self.filter.as_ref().map_or(false, |filter| {
+                    !filter.matches(&path_to_update.path, server_name, primary)
+                })
+            {
+                continue;
+            }
+            new_groups_by_key
+                .entry(self.grouping.key(server_name, primary))
+                .or_default()
+                .push((language_server_id, group));
+        }
+
+        let mut group_keys = self
             .path_states
-            .binary_search_by_key(&&path_to_update, |e| &e.path)
-        {
+            .iter()
+            .filter(|path_state| path_state.path == path_to_update)
+            .map(|path_state| path_state.group_key.clone())
+            .collect::<BTreeSet<_>>();
+        group_keys.extend(new_groups_by_key.keys().cloned());
+        if group_keys.is_empty() {
+            group_keys.insert(None);
+        }
+
+        let mut first_path_ix = None;
+        for group_key in group_keys {
+            let new_groups = new_groups_by_key.remove(&group_key).unwrap_or_default();
+            let path_ix = self.update_path_state(
+                group_key,
+                &path_to_update,
+                server_to_update,
+                new_groups,
+                &buffer,
+                cx,
+            );
+            first_path_ix.get_or_insert(path_ix);
+        }
+        // The states of the path may have moved while updating its other groups.
+        let path_ix = self
+            .path_states
+            .iter()
+            .position(|path_state| path_state.path == path_to_update)
+            .or(first_path_ix)
+            .unwrap_or_default();
+        self.update_group_headers(cx);
+        let excerpts_snapshot = self.excerpts.read(cx).snapshot(cx);
+
+        self.editor.update(cx, |editor, cx| {
+            let groups;
+            let mut selections;
+            let new_excerpt_ids_by_selection_id;
+            if was_empty {
+                groups = self.path_states.first()?.diagnostic_groups.as_slice();
+                new_excerpt_ids_by_selection_id = [(0, ExcerptId::min())].into_iter().collect();
+                selections = vec![Selection {
+                    id: 0,
+                    start: 0,
+                    end: 0,
+                    reversed: false,
+                    goal: SelectionGoal::None,
+                }];
+            } else {
+                groups = self.path_states.get(path_ix)?.diagnostic_groups.as_slice();
+                new_excerpt_ids_by_selection_id =
+                    editor.change_selections(Some(Autoscroll::fit()), cx, |s| s.refresh());
+                selections = editor.selections.all::<usize>(cx);
+            }
+
+            // If any selection has lost its position, move it to start of the next primary diagnostic.
+            let snapshot = editor.snapshot(cx);
+            for selection in &mut selections {
+                if let Some(new_excerpt_id) = new_excerpt_ids_by_selection_id.get(&selection.id) {
+                    let group_ix = match groups.binary_search_by(|probe| {
+                        probe
+                            .excerpts
+                            .last()
+                            .unwrap()
+                            .cmp(new_excerpt_id, &snapshot.buffer_snapshot)
+                    }) {
+                        Ok(ix) | Err(ix) => ix,
+                    };
+                    if let Some(group) = groups.get(group_ix) {
+                        if let Some(offset) = excerpts_snapshot
+                            .anchor_in_excerpt(
+                                group.excerpts[group.primary_excerpt_ix],
+                                group.primary_diagnostic.range.start,
+                            )
+                            .map(|anchor| anchor.to_offset(&excerpts_snapshot))
+                        {
+                            selection.start = offset;
+                            selection.end = offset;
+                        }
+                    }
+                }
+            }
+            editor.change_selections(None, cx, |s| {
+                s.select(selections);
+            });
+            Some(())
+        });
+
+        if self.path_states.is_empty() {
+            if self.editor.focus_handle(cx).is_focused(cx) {
+                cx.focus(&self.focus_handle);
+            }
+        } else if self.focus_handle.is_focused(cx) {
+            let focus_handle = self.editor.focus_handle(cx);
+            cx.focus(&focus_handle);
+        }
+
+        #[cfg(test)]
+        self.check_invariants(cx);
+
+        cx.notify();
+    }
+
+    /// Reconciles the excerpts of a single group of a path with its new diagnostic
+    /// groups, returning the index of the path's state.
+    fn update_path_state(
+        &mut self,
+        group_key: Option<String>,
+        path_to_update: &ProjectPath,
+        server_to_update: Option<LanguageServerId>,
+        new_groups: Vec<(LanguageServerId, DiagnosticGroup<language::Anchor>)>,
+        buffer: &Model<Buffer>,
+        cx: &mut ViewContext<Self>,
+    ) -> usize {
+        let snapshot = buffer.read(cx).snapshot();
+        let path_ix = match self.path_states.binary_search_by(|probe| {
+            (&probe.group_key, &probe.path).cmp(&(&group_key, path_to_update))
+        }) {
             Ok(ix) => ix,
             Err(ix) => {
                 self.path_states.insert(
                     ix,
                     PathState {
+                        group_key,
                         path: path_to_update.clone(),
                         diagnostic_groups: Default::default(),
                     },
@@ -335,23 +538,12 @@ impl ProjectDiagnosticsEditor {
         let mut blocks_to_add = Vec::new();
         let mut blocks_to_remove = HashSet::default();
         let mut first_excerpt_id = None;
-        let max_severity = if self.include_warnings {
-            DiagnosticSeverity::WARNING
-        } else {
-            DiagnosticSeverity::ERROR
-        };
         let excerpts_snapshot = self.excerpts.update(cx, |excerpts, cx| {
             let mut old_groups = mem::take(&mut path_state.diagnostic_groups)
                 .into_iter()
                 .enumerate()
                 .peekable();
-            let mut new_groups = snapshot
-                .diagnostic_groups(server_to_update)
-                .into_iter()
-                .filter(|(_, group)| {
-                    group.entries[group.primary_ix].diagnostic.severity <= max_severity
-                })
-                .peekable();
+            let mut new_groups = new_groups.into_iter().peekable();
             loop {
                 let mut to_insert = None;
                 let mut to_remove = None;
@@ -528,92 +720,83 @@ impl ProjectDiagnosticsEditor {
             self.path_states.remove(path_ix);
         }
 
-        self.editor.update(cx, |editor, cx| {
-            let groups;
-            let mut selections;
-            let new_excerpt_ids_by_selection_id;
-            if was_empty {
-                groups = self.path_states.first()?.diagnostic_groups.as_slice();
-                new_excerpt_ids_by_selection_id = [(0, ExcerptId::min())].into_iter().collect();
-                selections = vec![Selection {
-                    id: 0,
-                    start: 0,
-                    end: 0,
-                    reversed: false,
-                    goal: SelectionGoal::None,
-                }];
-            } else {
-                groups = self.path_states.get(path_ix)?.diagnostic_groups.as_slice();
-                new_excerpt_ids_by_selection_id =
-                    editor.change_selections(Some(Autoscroll::fit()), cx, |s| s.refresh());
-                selections = editor.selections.all::<usize>(cx);
-            }
+        path_ix
+    }
 
-            // If any selection has lost its position, move it to start of the next primary diagnostic.
-            let snapshot = editor.snapshot(cx);
-            for selection in &mut selections {
-                if let Some(new_excerpt_id) = new_excerpt_ids_by_selection_id.get(&selection.id) {
-                    let group_ix = match groups.binary_search_by(|probe| {
-                        probe
-                            .excerpts
-                            .last()
-                            .unwrap()
-                            .cmp(new_excerpt_id, &snapshot.buffer_snapshot)
-                    }) {
-                        Ok(ix) | Err(ix) => ix,
-                    };
-                    if let Some(group) = groups.get(group_ix) {
-                        if let Some(offset) = excerpts_snapshot
-                            .anchor_in_excerpt(
-                                group.excerpts[group.primary_excerpt_ix],
-                                group.primary_diagnostic.range.start,
-                            )
-                            .map(|anchor| anchor.to_offset(&excerpts_snapshot))
-                        {
-                            selection.start = offset;
-                            selection.end = offset;
-                        }
-                    }
-                }
+    /// Replaces the headers shown above the first excerpt of every group, when grouping is enabled.
+    fn update_group_headers(&mut self, cx: &mut ViewContext<Self>) {
+        let excerpts_snapshot = self.excerpts.read(cx).snapshot(cx);
+        let group_counts = self.group_counts().into_iter().collect::<HashMap<_, _>>();
+        let mut headers = Vec::new();
+        let mut prev_group_key = None;
+        for path_state in &self.path_states {
+            if prev_group_key == Some(&path_state.group_key) {
+                continue;
             }
-            editor.change_selections(None, cx, |s| {
-                s.select(selections);
+            prev_group_key = Some(&path_state.group_key);
+            let Some(count) = group_counts.get(&path_state.group_key) else {
+                continue;
+            };
+            let Some(position) = path_state
+                .diagnostic_groups
+                .first()
+                .and_then(|group| group.excerpts.first())
+                .and_then(|excerpt_id| {
+                    excerpts_snapshot.anchor_in_excerpt(*excerpt_id, language::Anchor::MIN)
+                })
+            else {
+                continue;
+            };
+            let label = path_state
+                .group_key
+                .clone()
+                .unwrap_or_else(|| self.grouping.missing_key_label().to_string());
+            headers.push(BlockProperties {
+                position,
+                height: 2,
+                style: BlockStyle::Sticky,
+                render: group_header_renderer(label.into(), *count),
+                disposition: BlockDisposition::Above,
+                priority: 1,
             });
-            Some(())
-        });
-
-        if self.path_states.is_empty() {
-            if self.editor.focus_handle(cx).is_focused(cx) {
-                cx.focus(&self.focus_handle);
-            }
-        } else if self.focus_handle.is_focused(cx) {
-            let focus_handle = self.editor.focus_handle(cx);
-            cx.focus(&focus_handle);
         }
 
-        #[cfg(test)]
-        self.check_invariants(cx);
-
-        cx.notify();
+        let old_blocks = mem::take(&mut self.group_header_blocks);
+        self.group_header_blocks = self.editor.update(cx, |editor, cx| {
+            editor.remove_blocks(old_blocks.into_iter().collect(), None, cx);
+            editor.insert_blocks(headers, None, cx)
+        });
     }
 
     #[cfg(test)]
     fn check_invariants(&self, cx: &mut ViewContext<Self>) {
-        let mut excerpts = Vec::new();
-        for (id, buffer, _) in self.excerpts.read(cx).snapshot(cx).excerpts() {
-            if let Some(file) = buffer.file() {
-                excerpts.push((id, file.path().clone()));
-            }
+        let keys = self
+            .path_states
+            .iter()
+            .map(|path_state| (&path_state.group_key, &path_state.path))
+            .collect::<Vec<_>>();
+        if keys.windows(2).any(|pair| pair[0] >= pair[1]) {
+            panic!("path states are not sorted by group and path {:?}", keys);
         }
 
-        let mut prev_path = None;
-        for (_, path) in &excerpts {
-            if let Some(prev_path) = prev_path {
-                if path < prev_path {
-                    panic!("excerpts are not sorted by path {:?}", excerpts);
-                }
-            }
-            prev_path = Some(path);
+        let excerpt_ids = self
+            .excerpts
+            .read(cx)
+            .snapshot(cx)
+            .excerpts()
+            .map(|(id, _, _)| id)
+            .collect::<Vec<_>>();
+        let path_state_excerpt_ids = self
+            .path_states
+            .iter()
+            .flat_map(|path_state| &path_state.diagnostic_groups)
+            .flat_map(|group| group.excerpts.iter().copied())
+            .collect::<Vec<_>>();
+        if excerpt_ids != path_state_excerpt_ids {
+            panic!(
+                "excerpts {:?} are not in the order of the path states {:?}",
+                excerpt_ids, path_state_excerpt_ids
+            );
         }
     }
 }
@@ -845,6 +1028,32 @@ fn diagnostic_header_renderer(diagnostic: Diagnostic) -> RenderBlock {
     })
 }
 
+const GROUP_HEADER: &'static str = "group header";
+
+fn group_header_renderer(label: SharedString, count: usize) -> RenderBlock {
+    Box::new(move |cx| {
+        h_flex()
+            .id(GROUP_HEADER)
+            .h(2. * cx.line_height())
+            .pl_10()
+            .pr_5()
+            .w_full()
+            .gap_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(Label::new(label.clone()).color(Color::Accent))
+            .child(
+                Label::new(match count {
+                    1 => "1 problem".to_string(),
+                    count => format!("{count} problems"),
+                })
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+            )
+            .into_any_element()
+    })
+}
+
 fn compare_diagnostics(
     old: &DiagnosticEntry<language::Anchor>,
     new: &DiagnosticEntry<language::Anchor>,
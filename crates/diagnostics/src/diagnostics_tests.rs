@@ -427,6 +427,179 @@ async fn test_diagnostics(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_diagnostics_grouping_and_filtering(cx: &mut TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/test",
+        json!({
+            "a.rs": "
+                fn a() {}
+
+                fn b() {}
+
+                fn c() {}
+            "
+            .unindent(),
+            "b.rs": "fn d() {}\n",
+        }),
+    )
+    .await;
+
+    let language_server_id = LanguageServerId(0);
+    let project = Project::test(fs.clone(), ["/test".as_ref()], cx).await;
+    let window = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+    let cx = &mut VisualTestContext::from_window(*window, cx);
+    let workspace = window.root(cx).unwrap();
+
+    let error = |row: u32, code: &str, message: &str, group_id: usize| DiagnosticEntry {
+        range: Unclipped(PointUtf16::new(row, 3))..Unclipped(PointUtf16::new(row, 4)),
+        diagnostic: Diagnostic {
+            code: Some(code.to_string()),
+            message: message.to_string(),
+            severity: DiagnosticSeverity::ERROR,
+            is_primary: true,
+            group_id,
+            ..Default::default()
+        },
+    };
+    project.update(cx, |project, cx| {
+        project
+            .update_diagnostic_entries(
+                language_server_id,
+                PathBuf::from("/test/a.rs"),
+                None,
+                vec![
+                    error(0, "E1", "first error", 0),
+                    error(4, "E2", "second error", 1),
+                ],
+                cx,
+            )
+            .unwrap();
+        project
+            .update_diagnostic_entries(
+                language_server_id,
+                PathBuf::from("/test/b.rs"),
+                None,
+                vec![error(0, "E1", "third error", 0)],
+                cx,
+            )
+            .unwrap();
+    });
+
+    let view = window.build_view(cx, |cx| {
+        ProjectDiagnosticsEditor::new_with_context(0, project.clone(), workspace.downgrade(), cx)
+    });
+    let editor = view.update(cx, |view, _| view.editor.clone());
+    cx.run_until_parked();
+
+    let path_states = |view: &View<ProjectDiagnosticsEditor>, cx: &mut VisualTestContext| {
+        view.update(cx, |view, _| {
+            view.path_states
+                .iter()
+                .map(|path_state| {
+                    (
+                        path_state.group_key.clone(),
+                        path_state.path.path.to_string_lossy().to_string(),
+                        path_state.diagnostic_groups.len(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+    };
+    let headers = |editor: &View<Editor>, cx: &mut VisualTestContext| {
+        editor_blocks(editor, cx)
+            .into_iter()
+            .map(|(_, name)| name.to_string())
+            .filter(|name| name == GROUP_HEADER || name == DIAGNOSTIC_HEADER)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        path_states(&view, cx),
+        [(None, "a.rs".to_string(), 2), (None, "b.rs".to_string(), 1)]
+    );
+    assert_eq!(
+        headers(&editor, cx),
+        [DIAGNOSTIC_HEADER, DIAGNOSTIC_HEADER, DIAGNOSTIC_HEADER]
+    );
+
+    // Grouping by code lists the paths under each code, with a header per code.
+    view.update(cx, |view, cx| view.cycle_grouping(&CycleGrouping, cx));
+    cx.run_until_parked();
+    assert_eq!(
+        path_states(&view, cx),
+        [
+            (Some("E1".to_string()), "a.rs".to_string(), 1),
+            (Some("E1".to_string()), "b.rs".to_string(), 1),
+            (Some("E2".to_string()), "a.rs".to_string(), 1),
+        ]
+    );
+    assert_eq!(
+        headers(&editor, cx),
+        [
+            GROUP_HEADER,
+            DIAGNOSTIC_HEADER,
+            DIAGNOSTIC_HEADER,
+            GROUP_HEADER,
+            DIAGNOSTIC_HEADER
+        ]
+    );
+    assert_eq!(
+        view.update(cx, |view, _| view.group_counts()),
+        [(Some("E1".to_string()), 2), (Some("E2".to_string()), 1)]
+    );
+
+    // Filtering keeps the grouping and drops the groups that no longer match.
+    let filter_editor = view.update(cx, |view, _| view.filter_editor.clone());
+    filter_editor.update(cx, |editor, cx| editor.set_text("code:E2", cx));
+    cx.run_until_parked();
+    assert_eq!(
+        path_states(&view, cx),
+        [(Some("E2".to_string()), "a.rs".to_string(), 1)]
+    );
+    assert_eq!(headers(&editor, cx), [GROUP_HEADER, DIAGNOSTIC_HEADER]);
+
+    filter_editor.update(cx, |editor, cx| editor.set_text("third", cx));
+    cx.run_until_parked();
+    assert_eq!(
+        path_states(&view, cx),
+        [(Some("E1".to_string()), "b.rs".to_string(), 1)]
+    );
+
+    filter_editor.update(cx, |editor, cx| editor.set_text("path:c.rs", cx));
+    cx.run_until_parked();
+    assert!(path_states(&view, cx).is_empty());
+
+    // Diagnostics without a source are grouped together.
+    filter_editor.update(cx, |editor, cx| editor.set_text("", cx));
+    view.update(cx, |view, cx| view.cycle_grouping(&CycleGrouping, cx));
+    cx.run_until_parked();
+    assert_eq!(
+        path_states(&view, cx),
+        [(None, "a.rs".to_string(), 2), (None, "b.rs".to_string(), 1)]
+    );
+    assert_eq!(
+        headers(&editor, cx),
+        [
+            GROUP_HEADER,
+            DIAGNOSTIC_HEADER,
+            DIAGNOSTIC_HEADER,
+            DIAGNOSTIC_HEADER
+        ]
+    );
+
+    // Ungrouping removes the group headers.
+    view.update(cx, |view, cx| view.cycle_grouping(&CycleGrouping, cx));
+    cx.run_until_parked();
+    assert_eq!(
+        headers(&editor, cx),
+        [DIAGNOSTIC_HEADER, DIAGNOSTIC_HEADER, DIAGNOSTIC_HEADER]
+    );
+}
+
 #[gpui::test]
 async fn test_diagnostics_multiple_servers(cx: &mut TestAppContext) {
     init_test(cx);
@@ -1018,3 +1191,29 @@ fn editor_blocks(
     });
     blocks
 }
+
+#[test]
+fn test_parse_filter_query() {
+    use project::project_settings::{DiagnosticPattern, DiagnosticSeverityContent};
+
+    assert!(parse_filter_query("  ").is_empty());
+    assert_eq!(
+        parse_filter_query(
+            "source:rustc code:E0308 severity:Error path:src/**/*.rs mismatched types"
+        ),
+        DiagnosticPattern {
+            source: Some("rustc".into()),
+            code: Some("E0308".into()),
+            severity: Some(DiagnosticSeverityContent::Error),
+            path: Some("src/**/*.rs".into()),
+            message: Some("mismatched types".into()),
+        }
+    );
+    assert_eq!(
+        parse_filter_query("code: unused"),
+        DiagnosticPattern {
+            message: Some("code: unused".into()),
+            ..Default::default()
+        }
+    );
+}
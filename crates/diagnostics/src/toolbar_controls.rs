@@ -1,9 +1,11 @@
-use crate::ProjectDiagnosticsEditor;
+use crate::{diagnostic_filter::DiagnosticGrouping, CycleGrouping, ProjectDiagnosticsEditor};
 use gpui::{EventEmitter, ParentElement, Render, View, ViewContext, WeakView};
 use ui::prelude::*;
 use ui::{IconButton, IconName, Tooltip};
 use workspace::{item::ItemHandle, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView};
 
+const MAX_GROUP_SUMMARIES: usize = 5;
+
 pub struct ToolbarControls {
     editor: Option<WeakView<ProjectDiagnosticsEditor>>,
 }
@@ -13,10 +15,16 @@ impl Render for ToolbarControls {
         let mut include_warnings = false;
         let mut has_stale_excerpts = false;
         let mut is_updating = false;
+        let mut filter_editor = None;
+        let mut grouping = DiagnosticGrouping::None;
+        let mut group_counts = Vec::new();
 
         if let Some(editor) = self.editor() {
             let editor = editor.read(cx);
             include_warnings = editor.include_warnings;
+            filter_editor = Some(editor.filter_editor.clone());
+            grouping = editor.grouping;
+            group_counts = editor.group_counts();
             has_stale_excerpts = !editor.paths_to_update.is_empty();
             is_updating = editor.update_paths_tx.len() > 0
                 || editor
@@ -34,6 +42,40 @@ impl Render for ToolbarControls {
         };
 
         h_flex()
+            .gap_1()
+            .children(group_counts.into_iter().take(MAX_GROUP_SUMMARIES).map(
+                |(group_key, count)| {
+                    Label::new(format!(
+                        "{}: {count}",
+                        group_key.as_deref().unwrap_or(grouping.missing_key_label())
+                    ))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+                },
+            ))
+            .when_some(filter_editor, |div, filter_editor| {
+                div.child(
+                    h_flex()
+                        .w(rems(16.))
+                        .px_1()
+                        .rounded_md()
+                        .border_1()
+                        .border_color(cx.theme().colors().border)
+                        .child(filter_editor),
+                )
+            })
+            .child(
+                IconButton::new("cycle-grouping", IconName::ListTree)
+                    .selected(grouping != DiagnosticGrouping::None)
+                    .tooltip(move |cx| Tooltip::for_action(grouping.label(), &CycleGrouping, cx))
+                    .on_click(cx.listener(|this, _, cx| {
+                        if let Some(editor) = this.editor() {
+                            editor.update(cx, |editor, cx| {
+                                editor.cycle_grouping(&CycleGrouping, cx);
+                            });
+                        }
+                    })),
+            )
             .when(has_stale_excerpts, |div| {
                 div.child(
                     IconButton::new("update-excerpts", IconName::Update)
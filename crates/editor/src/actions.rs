@@ -81,6 +81,25 @@ pub struct FoldAt {
     pub buffer_row: MultiBufferRow,
 }
 
+#[derive(PartialEq, Clone, Deserialize)]
+pub struct FoldAtLevel {
+    /// How deeply nested the folded ranges are, where top-level ranges are at level 1.
+    #[serde(default = "default_fold_level")]
+    pub level: u32,
+}
+
+impl Default for FoldAtLevel {
+    fn default() -> Self {
+        Self {
+            level: default_fold_level(),
+        }
+    }
+}
+
+fn default_fold_level() -> u32 {
+    1
+}
+
 #[derive(PartialEq, Clone, Deserialize, Default)]
 pub struct UnfoldAt {
     pub buffer_row: MultiBufferRow,
@@ -141,6 +160,7 @@ impl_actions!(
         ExpandExcerptsUp,
         ExpandExcerptsDown,
         FoldAt,
+        FoldAtLevel,
         MoveDownByLines,
         MovePageDown,
         MovePageUp,
@@ -206,6 +226,9 @@ gpui::actions!(
         ExpandMacroRecursively,
         FindAllReferences,
         Fold,
+        FoldAll,
+        FoldAllComments,
+        FoldAllImports,
         FoldSelectedRanges,
         Format,
         GoToDefinition,
@@ -308,6 +331,7 @@ gpui::actions!(
         Transpose,
         Undo,
         UndoSelection,
+        UnfoldAll,
         UnfoldLines,
         UniqueLinesCaseInsensitive,
         UniqueLinesCaseSensitive,
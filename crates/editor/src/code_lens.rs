@@ -0,0 +1,327 @@
+use std::time::Duration;
+
+use collections::{BTreeMap, HashMap, HashSet};
+use gpui::{div, Task, ViewContext, WeakView};
+use language::{language_settings::language_settings, Point};
+use multi_buffer::{Anchor, MultiBufferRow, ToPoint as _};
+use project::CodeLens;
+use text::BufferId;
+use ui::{h_flex, prelude::*};
+use util::ResultExt;
+use workspace::notifications::NotifyTaskExt;
+
+use crate::{
+    display_map::{
+        BlockContext, BlockDisposition, BlockProperties, BlockStyle, CustomBlockId, RenderBlock,
+    },
+    Editor, EditorMode,
+};
+
+const CODE_LENS_DEBOUNCE: Duration = Duration::from_millis(250);
+
+#[derive(Default)]
+pub(super) struct CodeLensState {
+    /// The latest resolved lenses of each buffer, along with the buffer version they were requested at.
+    lenses: HashMap<BufferId, (clock::Global, Vec<CodeLens>)>,
+    /// Whether code lenses were enabled for each buffer when it was last refreshed.
+    enabled: HashMap<BufferId, bool>,
+    blocks: HashMap<CustomBlockId, CodeLensBlock>,
+    refresh_task: Option<Task<Option<()>>>,
+}
+
+/// The lenses shown above a line.
+struct CodeLensBlock {
+    position: Anchor,
+    buffer_id: BufferId,
+    /// The commands of the lenses, along with whether the language server can run them.
+    commands: Vec<(lsp::Command, bool)>,
+}
+
+/// Requests and resolves the code lenses of the buffers in the editor.
+///
+/// Buffers that didn't change since their last lenses were received are skipped,
+/// unless `force` is set, as it is when a language server asks for a refresh, or
+/// code lenses were enabled or disabled for them since.
+pub(super) fn refresh_code_lens(editor: &mut Editor, force: bool, cx: &mut ViewContext<Editor>) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let all_buffers = editor.buffer.read(cx).all_buffers();
+    editor.code_lens.enabled.retain(|buffer_id, _| {
+        all_buffers
+            .iter()
+            .any(|buffer| buffer.read(cx).remote_id() == *buffer_id)
+    });
+
+    let mut buffers = Vec::new();
+    for buffer in all_buffers {
+        let snapshot = buffer.read(cx);
+        if snapshot.language().is_none() {
+            continue;
+        }
+        let buffer_id = snapshot.remote_id();
+        let enabled = language_settings(snapshot.language(), snapshot.file(), cx).code_lens;
+        let setting_changed = editor.code_lens.enabled.insert(buffer_id, enabled) != Some(enabled);
+        let buffer_changed = editor
+            .code_lens
+            .lenses
+            .get(&buffer_id)
+            .map_or(true, |(version, _)| {
+                snapshot.version().changed_since(version)
+            });
+        if force || setting_changed || buffer_changed {
+            buffers.push(buffer);
+        }
+    }
+    if buffers.is_empty() {
+        return;
+    }
+
+    editor.code_lens.refresh_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(CODE_LENS_DEBOUNCE).await;
+
+        let requests = project
+            .update(&mut cx, |project, cx| {
+                buffers
+                    .into_iter()
+                    .map(|buffer| {
+                        let buffer_id = buffer.read(cx).remote_id();
+                        let version = buffer.read(cx).version();
+                        let request = project.code_lens(&buffer, cx);
+                        let project = cx.handle();
+                        let mut cx = cx.to_async();
+                        async move {
+                            let lenses = request.await?;
+                            let resolves = project.update(&mut cx, |project, cx| {
+                                lenses
+                                    .into_iter()
+                                    .map(|lens| {
+                                        if lens.command().is_some() {
+                                            Task::ready(Ok(lens))
+                                        } else {
+                                            project.resolve_code_lens(&buffer, lens, cx)
+                                        }
+                                    })
+                                    .collect::<Vec<_>>()
+                            })?;
+                            let lenses = futures::future::join_all(resolves)
+                                .await
+                                .into_iter()
+                                .filter_map(|lens| lens.log_err())
+                                .collect::<Vec<_>>();
+                            anyhow::Ok((buffer_id, version, lenses))
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .ok()?;
+        let responses = futures::future::join_all(requests).await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                for (buffer_id, version, lenses) in responses
+                    .into_iter()
+                    .flat_map(|response| response.log_err())
+                {
+                    editor.code_lens.lenses.insert(buffer_id, (version, lenses));
+                }
+                update_code_lens_blocks(editor, cx);
+            })
+            .ok()
+    }));
+}
+
+/// Shows a block above every line that has resolved lenses.
+///
+/// Blocks of lines that still have lenses are kept in place, so that updating them
+/// doesn't move the text around them.
+pub(super) fn update_code_lens_blocks(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let multi_buffer = editor.buffer.read(cx);
+    let snapshot = multi_buffer.snapshot(cx);
+
+    let mut lines = BTreeMap::<Point, (Anchor, BufferId, Vec<(lsp::Command, bool)>)>::new();
+    editor.code_lens.lenses.retain(|buffer_id, (_, lenses)| {
+        let Some(buffer) = multi_buffer.buffer(*buffer_id) else {
+            return false;
+        };
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        for (excerpt_id, excerpt_range) in multi_buffer.excerpts_for_buffer(&buffer, cx) {
+            let context = excerpt_range.context;
+            for lens in lenses.iter() {
+                let Some(command) = lens.command() else {
+                    continue;
+                };
+                if lens
+                    .range
+                    .start
+                    .cmp(&context.start, &buffer_snapshot)
+                    .is_lt()
+                    || lens.range.start.cmp(&context.end, &buffer_snapshot).is_gt()
+                {
+                    continue;
+                }
+                let Some(position) = snapshot.anchor_in_excerpt(excerpt_id, lens.range.start)
+                else {
+                    continue;
+                };
+                let row = position.to_point(&snapshot).row;
+                let line_start = Point::new(row, 0);
+                let indent = snapshot.indent_size_for_line(MultiBufferRow(row));
+                lines
+                    .entry(line_start)
+                    .or_insert_with(|| {
+                        (
+                            snapshot.anchor_before(Point::new(row, indent.len)),
+                            *buffer_id,
+                            Vec::new(),
+                        )
+                    })
+                    .2
+                    .push((command.clone(), lens.executable));
+            }
+        }
+        true
+    });
+
+    let mut old_blocks_by_row = HashMap::default();
+    for (block_id, block) in &editor.code_lens.blocks {
+        old_blocks_by_row
+            .entry(block.position.to_point(&snapshot).row)
+            .or_insert(*block_id);
+    }
+
+    let weak_editor = cx.view().downgrade();
+    let mut blocks = HashMap::default();
+    let mut renderers = HashMap::default();
+    let mut new_blocks = Vec::new();
+    for (line_start, (position, buffer_id, commands)) in lines {
+        let Some(block_id) = old_blocks_by_row.remove(&line_start.row) else {
+            new_blocks.push(CodeLensBlock {
+                position,
+                buffer_id,
+                commands,
+            });
+            continue;
+        };
+        let Some(old_block) = editor.code_lens.blocks.remove(&block_id) else {
+            continue;
+        };
+        if old_block.buffer_id != buffer_id || old_block.commands != commands {
+            renderers.insert(
+                block_id,
+                render_code_lens_block(weak_editor.clone(), buffer_id, commands.clone()),
+            );
+        }
+        blocks.insert(
+            block_id,
+            CodeLensBlock {
+                position: old_block.position,
+                buffer_id,
+                commands,
+            },
+        );
+    }
+
+    let removed_block_ids = editor
+        .code_lens
+        .blocks
+        .drain()
+        .map(|(block_id, _)| block_id)
+        .collect::<HashSet<_>>();
+    if !removed_block_ids.is_empty() {
+        editor.remove_blocks(removed_block_ids, None, cx);
+    }
+    if !renderers.is_empty() {
+        editor.replace_blocks(renderers, None, cx);
+    }
+    if !new_blocks.is_empty() {
+        let block_ids = editor.insert_blocks(
+            new_blocks.iter().map(|block| BlockProperties {
+                position: block.position,
+                height: 1,
+                style: BlockStyle::Flex,
+                render: render_code_lens_block(
+                    weak_editor.clone(),
+                    block.buffer_id,
+                    block.commands.clone(),
+                ),
+                disposition: BlockDisposition::Above,
+                priority: 0,
+            }),
+            None,
+            cx,
+        );
+        blocks.extend(block_ids.into_iter().zip(new_blocks));
+    }
+    editor.code_lens.blocks = blocks;
+}
+
+fn render_code_lens_block(
+    editor: WeakView<Editor>,
+    buffer_id: BufferId,
+    commands: Vec<(lsp::Command, bool)>,
+) -> RenderBlock {
+    Box::new(move |cx: &mut BlockContext| {
+        let mut lenses = h_flex().id(cx.block_id).pl(cx.anchor_x).gap_1();
+        for (ix, (command, executable)) in commands.iter().enumerate() {
+            if ix > 0 {
+                lenses = lenses.child(Label::new("|").size(LabelSize::Small).color(Color::Muted));
+            }
+            let label = Label::new(command.title.clone())
+                .size(LabelSize::Small)
+                .color(Color::Muted);
+            // Commands that the server can't run are meant for the client, which implements none.
+            if !executable {
+                lenses = lenses.child(label);
+                continue;
+            }
+            lenses = lenses.child(
+                div()
+                    .id(ix)
+                    .cursor_pointer()
+                    .child(label)
+                    .hover(|style| style.text_decoration_1())
+                    .on_click({
+                        let editor = editor.clone();
+                        let command = command.clone();
+                        move |_, cx| {
+                            editor
+                                .update(cx, |editor, cx| {
+                                    execute_code_lens_command(
+                                        editor,
+                                        buffer_id,
+                                        command.clone(),
+                                        cx,
+                                    )
+                                })
+                                .ok();
+                        }
+                    }),
+            );
+        }
+        lenses.into_any_element()
+    })
+}
+
+fn execute_code_lens_command(
+    editor: &mut Editor,
+    buffer_id: BufferId,
+    command: lsp::Command,
+    cx: &mut ViewContext<Editor>,
+) {
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+    let Some(buffer) = editor.buffer.read(cx).buffer(buffer_id) else {
+        return;
+    };
+    project
+        .update(cx, |project, cx| {
+            project.execute_code_lens_command(&buffer, command, cx)
+        })
+        .detach_and_notify_err(cx);
+}
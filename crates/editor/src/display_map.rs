@@ -43,8 +43,8 @@ pub(crate) use inlay_map::Inlay;
 use inlay_map::{InlayMap, InlaySnapshot};
 pub use inlay_map::{InlayOffset, InlayPoint};
 use language::{
-    language_settings::language_settings, ChunkRenderer, OffsetUtf16, Point,
-    Subscription as BufferSubscription,
+    language_settings::language_settings, ChunkRenderer, FoldingRange, HighlightId, OffsetUtf16,
+    Point, Subscription as BufferSubscription,
 };
 use lsp::DiagnosticSeverity;
 use multi_buffer::{
@@ -62,7 +62,7 @@ use std::{
 };
 use sum_tree::{Bias, TreeMap};
 use tab_map::{TabMap, TabSnapshot};
-use text::LineIndent;
+use text::{BufferId, LineIndent};
 use ui::WindowContext;
 use wrap_map::{WrapMap, WrapSnapshot};
 
@@ -82,6 +82,16 @@ pub trait ToDisplayPoint {
 
 type TextHighlights = TreeMap<Option<TypeId>, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>;
 type InlayHighlights = TreeMap<TypeId, TreeMap<InlayId, (HighlightStyle, InlayHighlight)>>;
+type SemanticTokenHighlights = Arc<[(Range<Anchor>, HighlightId)]>;
+
+/// Foldable ranges reported by language servers or syntax queries.
+#[derive(Clone, Default)]
+pub(crate) struct FoldingRanges {
+    /// Sorted by start, with enclosing ranges before the ranges they contain.
+    pub ranges: Arc<[FoldingRange<Anchor>]>,
+    /// The buffers for which folding ranges are known, and indentation isn't used to suggest folds.
+    pub buffer_ids: Arc<HashSet<BufferId>>,
+}
 
 /// Decides how text in a [`MultiBuffer`] should be displayed in a buffer, handling inlay hints,
 /// folding, hard tabs, soft wrapping, custom blocks (like diagnostics), and highlighting.
@@ -105,6 +115,10 @@ pub struct DisplayMap {
     text_highlights: TextHighlights,
     /// Regions of inlays that should be highlighted.
     inlay_highlights: InlayHighlights,
+    /// Non-overlapping regions highlighted by semantic tokens from a language server, sorted by position.
+    semantic_token_highlights: SemanticTokenHighlights,
+    /// Ranges that can be folded, which supersede indentation based fold range suggestions.
+    folding_ranges: FoldingRanges,
     /// A container for explicitly foldable ranges, which supersede indentation based fold range suggestions.
     crease_map: CreaseMap,
     fold_placeholder: FoldPlaceholder,
@@ -156,6 +170,8 @@ impl DisplayMap {
             fold_placeholder,
             text_highlights: Default::default(),
             inlay_highlights: Default::default(),
+            semantic_token_highlights: Arc::new([]),
+            folding_ranges: FoldingRanges::default(),
             clip_at_line_ends: false,
             masked: false,
         }
@@ -183,6 +199,8 @@ impl DisplayMap {
             crease_snapshot: self.crease_map.snapshot(),
             text_highlights: self.text_highlights.clone(),
             inlay_highlights: self.inlay_highlights.clone(),
+            semantic_token_highlights: self.semantic_token_highlights.clone(),
+            folding_ranges: self.folding_ranges.clone(),
             clip_at_line_ends: self.clip_at_line_ends,
             masked: self.masked,
             fold_placeholder: self.fold_placeholder.clone(),
@@ -370,6 +388,14 @@ impl DisplayMap {
         }
     }
 
+    pub(crate) fn set_semantic_token_highlights(&mut self, highlights: SemanticTokenHighlights) {
+        self.semantic_token_highlights = highlights;
+    }
+
+    pub(crate) fn set_folding_ranges(&mut self, folding_ranges: FoldingRanges) {
+        self.folding_ranges = folding_ranges;
+    }
+
     pub fn text_highlights(&self, type_id: TypeId) -> Option<(HighlightStyle, &[Range<Anchor>])> {
         let highlights = self.text_highlights.get(&Some(type_id))?;
         Some((highlights.0, &highlights.1))
@@ -445,6 +471,7 @@ impl DisplayMap {
 pub(crate) struct Highlights<'a> {
     pub text_highlights: Option<&'a TextHighlights>,
     pub inlay_highlights: Option<&'a InlayHighlights>,
+    pub semantic_token_highlights: Option<&'a SemanticTokenHighlights>,
     pub styles: HighlightStyles,
 }
 
@@ -472,6 +499,8 @@ pub struct DisplaySnapshot {
     block_snapshot: BlockSnapshot,
     text_highlights: TextHighlights,
     inlay_highlights: InlayHighlights,
+    semantic_token_highlights: SemanticTokenHighlights,
+    folding_ranges: FoldingRanges,
     clip_at_line_ends: bool,
     masked: bool,
     pub(crate) fold_placeholder: FoldPlaceholder,
@@ -656,6 +685,7 @@ impl DisplaySnapshot {
             Highlights {
                 text_highlights: Some(&self.text_highlights),
                 inlay_highlights: Some(&self.inlay_highlights),
+                semantic_token_highlights: Some(&self.semantic_token_highlights),
                 styles: highlight_styles,
             },
         )
@@ -951,6 +981,49 @@ impl DisplaySnapshot {
             .unwrap_or(false)
     }
 
+    /// Whether the folds of the given row come from folding ranges rather than indentation.
+    fn has_folding_ranges(&self, buffer_row: MultiBufferRow) -> bool {
+        self.buffer_snapshot
+            .buffer_line_for_row(buffer_row)
+            .map_or(false, |(buffer, _)| {
+                self.folding_ranges.buffer_ids.contains(&buffer.remote_id())
+            })
+    }
+
+    /// Returns the outermost folding range that starts on the given row.
+    pub(crate) fn folding_range_for_row(
+        &self,
+        buffer_row: MultiBufferRow,
+    ) -> Option<&FoldingRange<Anchor>> {
+        let ranges = &self.folding_ranges.ranges;
+        let row_start = self
+            .buffer_snapshot
+            .anchor_before(Point::new(buffer_row.0, 0));
+        let ix = ranges.partition_point(|range| {
+            range
+                .range
+                .start
+                .cmp(&row_start, &self.buffer_snapshot)
+                .is_lt()
+        });
+        ranges
+            .get(ix)
+            .filter(|range| range.range.start.to_point(&self.buffer_snapshot).row == buffer_row.0)
+    }
+
+    pub(crate) fn folding_ranges(&self) -> &[FoldingRange<Anchor>] {
+        &self.folding_ranges.ranges
+    }
+
+    /// Whether a fold can start on the given row, either from a folding range or from indentation.
+    pub fn starts_fold(&self, buffer_row: MultiBufferRow) -> bool {
+        if self.has_folding_ranges(buffer_row) {
+            self.folding_range_for_row(buffer_row).is_some()
+        } else {
+            self.starts_indent(buffer_row)
+        }
+    }
+
     pub fn foldable_range(
         &self,
         buffer_row: MultiBufferRow,
@@ -964,6 +1037,15 @@ impl DisplaySnapshot {
                 crease.range.to_point(&self.buffer_snapshot),
                 crease.placeholder.clone(),
             ))
+        } else if self.has_folding_ranges(buffer_row) {
+            if self.is_line_folded(buffer_row) {
+                return None;
+            }
+            let range = self.folding_range_for_row(buffer_row)?;
+            Some((
+                range.range.to_point(&self.buffer_snapshot),
+                self.fold_placeholder.clone(),
+            ))
         } else if self.starts_indent(MultiBufferRow(start.row))
             && !self.is_line_folded(MultiBufferRow(start.row))
         {
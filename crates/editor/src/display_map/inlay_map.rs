@@ -1,7 +1,7 @@
 use crate::{HighlightStyles, InlayId};
 use collections::{BTreeMap, BTreeSet};
 use gpui::HighlightStyle;
-use language::{Chunk, Edit, Point, TextSummary};
+use language::{Chunk, Edit, HighlightId, Point, TextSummary};
 use multi_buffer::{
     Anchor, MultiBufferChunks, MultiBufferRow, MultiBufferRows, MultiBufferSnapshot, ToOffset,
 };
@@ -220,6 +220,8 @@ pub struct InlayChunks<'a> {
     highlight_styles: HighlightStyles,
     highlight_endpoints: Peekable<vec::IntoIter<HighlightEndpoint>>,
     active_highlights: BTreeMap<Option<TypeId>, HighlightStyle>,
+    semantic_token_endpoints: Peekable<vec::IntoIter<(InlayOffset, Option<HighlightId>)>>,
+    active_semantic_token: Option<HighlightId>,
     highlights: Highlights<'a>,
     snapshot: &'a InlaySnapshot,
 }
@@ -264,6 +266,15 @@ impl<'a> Iterator for InlayChunks<'a> {
                 break;
             }
         }
+        while let Some((offset, highlight_id)) = self.semantic_token_endpoints.peek().copied() {
+            if offset <= self.output_offset {
+                self.active_semantic_token = highlight_id;
+                self.semantic_token_endpoints.next();
+            } else {
+                next_highlight_endpoint = next_highlight_endpoint.min(offset);
+                break;
+            }
+        }
 
         let chunk = match self.transforms.item()? {
             Transform::Isomorphic(_) => {
@@ -288,6 +299,9 @@ impl<'a> Iterator for InlayChunks<'a> {
                     text: prefix,
                     ..chunk.clone()
                 };
+                if self.active_semantic_token.is_some() {
+                    prefix.syntax_highlight_id = self.active_semantic_token;
+                }
                 if !self.active_highlights.is_empty() {
                     let mut highlight_style = HighlightStyle::default();
                     for active_highlight in self.active_highlights.values() {
@@ -1041,6 +1055,16 @@ impl InlaySnapshot {
             }
         }
         highlight_endpoints.sort();
+        let mut semantic_token_endpoints = Vec::new();
+        if language_aware {
+            if let Some(semantic_token_highlights) = highlights.semantic_token_highlights {
+                self.apply_semantic_token_highlights(
+                    &range,
+                    semantic_token_highlights,
+                    &mut semantic_token_endpoints,
+                );
+            }
+        }
         let buffer_range = self.to_buffer_offset(range.start)..self.to_buffer_offset(range.end);
         let buffer_chunks = self.buffer.chunks(buffer_range, language_aware);
 
@@ -1055,6 +1079,8 @@ impl InlaySnapshot {
             highlight_styles: highlights.styles,
             highlight_endpoints: highlight_endpoints.into_iter().peekable(),
             active_highlights: Default::default(),
+            semantic_token_endpoints: semantic_token_endpoints.into_iter().peekable(),
+            active_semantic_token: None,
             highlights,
             snapshot: self,
         }
@@ -1118,6 +1144,33 @@ impl InlaySnapshot {
         }
     }
 
+    /// Semantic tokens don't overlap, so each token start switches the active
+    /// highlight and each token end clears it.
+    fn apply_semantic_token_highlights(
+        &self,
+        range: &Range<InlayOffset>,
+        semantic_token_highlights: &[(Range<Anchor>, HighlightId)],
+        semantic_token_endpoints: &mut Vec<(InlayOffset, Option<HighlightId>)>,
+    ) {
+        let buffer_start = self.to_buffer_offset(range.start);
+        let buffer_end = self.to_buffer_offset(range.end);
+        let start_ix = semantic_token_highlights.partition_point(|(token_range, _)| {
+            token_range.end.to_offset(&self.buffer) <= buffer_start
+        });
+        for (token_range, highlight_id) in &semantic_token_highlights[start_ix..] {
+            let token_start = token_range.start.to_offset(&self.buffer);
+            if token_start >= buffer_end {
+                break;
+            }
+            let token_end = token_range.end.to_offset(&self.buffer);
+            if token_start < token_end {
+                semantic_token_endpoints
+                    .push((self.to_inlay_offset(token_start), Some(*highlight_id)));
+                semantic_token_endpoints.push((self.to_inlay_offset(token_end), None));
+            }
+        }
+    }
+
     #[cfg(test)]
     pub fn text(&self) -> String {
         self.chunks(Default::default()..self.len(), false, Highlights::default())
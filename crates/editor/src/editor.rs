@@ -15,11 +15,13 @@
 pub mod actions;
 mod blame_entry_tooltip;
 mod blink_manager;
+mod code_lens;
 mod debounced_delay;
 pub mod display_map;
 mod editor_settings;
 mod editor_settings_controls;
 mod element;
+mod folding_ranges;
 mod git;
 mod highlight_matching_bracket;
 mod hover_links;
@@ -36,6 +38,7 @@ mod persistence;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
+mod semantic_tokens;
 pub mod tasks;
 
 #[cfg(test)]
@@ -88,10 +91,12 @@ pub use items::MAX_TAB_TITLE_LEN;
 use itertools::Itertools;
 use language::{
     char_kind,
-    language_settings::{self, all_language_settings, InlayHintSettings},
+    language_settings::{
+        self, all_language_settings, language_settings, ExpandSelectionSource, InlayHintSettings,
+    },
     markdown, point_from_lsp, AutoindentMode, BracketPair, Buffer, Capability, CharKind, CodeLabel,
-    CursorShape, Diagnostic, Documentation, IndentKind, IndentSize, Language, OffsetRangeExt,
-    Point, Selection, SelectionGoal, TransactionId,
+    CursorShape, Diagnostic, Documentation, FoldingRangeKind, IndentKind, IndentSize, Language,
+    OffsetRangeExt, Point, Selection, SelectionGoal, TransactionId,
 };
 use language::{point_to_lsp, BufferRow, Runnable, RunnableRange};
 use linked_editing_ranges::refresh_linked_ranges;
@@ -466,12 +471,15 @@ pub struct Editor {
     selection_history: SelectionHistory,
     autoclose_regions: Vec<AutocloseRegion>,
     snippet_stack: InvalidationStack<SnippetState>,
-    select_larger_syntax_node_stack: Vec<Box<[Selection<usize>]>>,
+    /// For each selection, the selections it was expanded from by each `SelectLargerSyntaxNode`.
+    select_larger_syntax_node_stack: HashMap<usize, Vec<Box<[Selection<usize>]>>>,
+    select_larger_syntax_node_task: Option<Task<()>>,
     ime_transaction: Option<TransactionId>,
     active_diagnostics: Option<ActiveDiagnosticGroup>,
     soft_wrap_mode_override: Option<language_settings::SoftWrap>,
     project: Option<Model<Project>>,
     completion_provider: Option<Box<dyn CompletionProvider>>,
+    hover_provider: Option<Box<dyn HoverProvider>>,
     collaboration_hub: Option<Box<dyn CollaborationHub>>,
     blink_manager: Model<BlinkManager>,
     show_cursor_names: bool,
@@ -507,6 +515,9 @@ pub struct Editor {
     document_highlights_task: Option<Task<()>>,
     linked_editing_range_task: Option<Task<Option<()>>>,
     linked_edit_ranges: linked_editing_ranges::LinkedEditingRanges,
+    semantic_tokens: semantic_tokens::SemanticTokensState,
+    folding_ranges: folding_ranges::FoldingRangesState,
+    code_lens: code_lens::CodeLensState,
     pending_rename: Option<RenameState>,
     searchable: bool,
     cursor_shape: CursorShape,
@@ -523,6 +534,7 @@ pub struct Editor {
     hover_state: HoverState,
     gutter_hovered: bool,
     hovered_link_state: Option<HoveredLinkState>,
+    document_links: hover_links::DocumentLinks,
     inline_completion_provider: Option<RegisteredInlineCompletionProvider>,
     active_inline_completion: Option<(Inlay, Option<Range<Anchor>>)>,
     show_inline_completions: bool,
@@ -1729,6 +1741,10 @@ impl Editor {
                 project_subscriptions.push(cx.subscribe(project, |editor, _, event, cx| {
                     if let project::Event::RefreshInlayHints = event {
                         editor.refresh_inlay_hints(InlayHintRefreshReason::RefreshRequested, cx);
+                    } else if let project::Event::RefreshSemanticTokens = event {
+                        semantic_tokens::refresh_semantic_tokens(editor, true, cx);
+                    } else if let project::Event::RefreshCodeLens = event {
+                        code_lens::refresh_code_lens(editor, true, cx);
                     } else if let project::Event::SnippetEdit(id, snippet_edits) = event {
                         if let Some(buffer) = editor.buffer.read(cx).buffer(*id) {
                             let focus_handle = editor.focus_handle(cx);
@@ -1786,11 +1802,13 @@ impl Editor {
             selection_history: Default::default(),
             autoclose_regions: Default::default(),
             snippet_stack: Default::default(),
-            select_larger_syntax_node_stack: Vec::new(),
+            select_larger_syntax_node_stack: HashMap::default(),
+            select_larger_syntax_node_task: None,
             ime_transaction: Default::default(),
             active_diagnostics: None,
             soft_wrap_mode_override,
             completion_provider: project.clone().map(|project| Box::new(project) as _),
+            hover_provider: None,
             collaboration_hub: project.clone().map(|project| Box::new(project) as _),
             project,
             blink_manager: blink_manager.clone(),
@@ -1843,6 +1861,7 @@ impl Editor {
             remote_id: None,
             hover_state: Default::default(),
             hovered_link_state: Default::default(),
+            document_links: Default::default(),
             inline_completion_provider: None,
             active_inline_completion: None,
             inlay_hint_cache: InlayHintCache::new(inlay_hint_settings),
@@ -1892,12 +1911,18 @@ impl Editor {
             ],
             tasks_update_task: None,
             linked_edit_ranges: Default::default(),
+            semantic_tokens: Default::default(),
+            folding_ranges: Default::default(),
+            code_lens: Default::default(),
             previous_search_ranges: None,
             breadcrumb_header: None,
             focused_block: None,
         };
         this.tasks_update_task = Some(this.refresh_runnables(cx));
         this._subscriptions.extend(project_subscriptions);
+        semantic_tokens::refresh_semantic_tokens(&mut this, false, cx);
+        folding_ranges::refresh_folding_ranges(&mut this, false, cx);
+        code_lens::refresh_code_lens(&mut this, false, cx);
 
         this.end_selection(cx);
         this.scroll_manager.show_scrollbar(cx);
@@ -2127,6 +2152,15 @@ impl Editor {
         self.completion_provider = Some(provider);
     }
 
+    /// Sets a provider whose hovers are shown alongside those of the project's language servers.
+    pub fn set_hover_provider(&mut self, provider: Option<Box<dyn HoverProvider>>) {
+        self.hover_provider = provider;
+    }
+
+    pub fn project(&self) -> Option<&Model<Project>> {
+        self.project.as_ref()
+    }
+
     pub fn set_inline_completion_provider<T>(
         &mut self,
         provider: Option<Model<T>>,
@@ -8407,44 +8441,129 @@ impl Editor {
         _: &SelectLargerSyntaxNode,
         cx: &mut ViewContext<Self>,
     ) {
-        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
-        let buffer = self.buffer.read(cx).snapshot(cx);
-        let old_selections = self.selections.all::<usize>(cx).into_boxed_slice();
+        if self.select_larger_syntax_node_via_lsp(cx) {
+            return;
+        }
 
-        let mut stack = mem::take(&mut self.select_larger_syntax_node_stack);
-        let mut selected_larger_node = false;
-        let new_selections = old_selections
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let old_selections = self.selections.all::<usize>(cx);
+        let new_ranges = old_selections
             .iter()
-            .map(|selection| {
-                let old_range = selection.start..selection.end;
-                let mut new_range = old_range.clone();
-                while let Some(containing_range) =
-                    buffer.range_for_syntax_ancestor(new_range.clone())
-                {
-                    new_range = containing_range;
-                    if !display_map.intersects_fold(new_range.start)
-                        && !display_map.intersects_fold(new_range.end)
-                    {
-                        break;
+            .map(|selection| larger_syntax_node_range(selection.range(), &display_map))
+            .collect();
+        self.expand_selections(old_selections, new_ranges, cx);
+    }
+
+    /// Expands the selections to the ranges reported by the language server, when the
+    /// language of the buffer is configured to do so. Returns whether a request was made.
+    fn select_larger_syntax_node_via_lsp(&mut self, cx: &mut ViewContext<Self>) -> bool {
+        let Some(project) = self.project.clone() else {
+            return false;
+        };
+        let Some(buffer) = self.buffer.read(cx).as_singleton() else {
+            return false;
+        };
+        let source = {
+            let buffer = buffer.read(cx);
+            language_settings(buffer.language(), buffer.file(), cx).expand_selection
+        };
+        if source != ExpandSelectionSource::LanguageServer {
+            return false;
+        }
+
+        let old_selections = self.selections.all::<usize>(cx);
+        let positions = {
+            let buffer = buffer.read(cx);
+            old_selections
+                .iter()
+                .map(|selection| buffer.anchor_after(selection.start))
+                .collect()
+        };
+        let request = project.update(cx, |project, cx| {
+            project.selection_ranges(&buffer, positions, cx)
+        });
+        self.select_larger_syntax_node_task = Some(cx.spawn(|editor, mut cx| async move {
+            let chains = request.await.log_err().unwrap_or_default();
+            editor
+                .update(&mut cx, |editor, cx| {
+                    // Don't expand selections that changed while waiting for the language server.
+                    if editor.selections.all::<usize>(cx) != old_selections {
+                        return;
                     }
-                }
+                    let display_map = editor.display_map.update(cx, |map, cx| map.snapshot(cx));
+                    let snapshot = buffer.read(cx).snapshot();
+                    let new_ranges = old_selections
+                        .iter()
+                        .enumerate()
+                        .map(|(ix, selection)| {
+                            let range = selection.range();
+                            chains
+                                .get(ix)
+                                .and_then(|chain| {
+                                    chain
+                                        .iter()
+                                        .map(|candidate| candidate.to_offset(&snapshot))
+                                        .find(|candidate| {
+                                            candidate.start <= range.start
+                                                && range.end <= candidate.end
+                                                && *candidate != range
+                                        })
+                                })
+                                .unwrap_or_else(|| larger_syntax_node_range(range, &display_map))
+                        })
+                        .collect();
+                    editor.expand_selections(old_selections, new_ranges, cx);
+                })
+                .ok();
+        }));
+        true
+    }
 
-                selected_larger_node |= new_range != old_range;
-                Selection {
-                    id: selection.id,
-                    start: new_range.start,
-                    end: new_range.end,
-                    goal: SelectionGoal::None,
-                    reversed: selection.reversed,
-                }
+    /// Replaces each selection with the corresponding range, remembering what every
+    /// resulting selection was expanded from so that `SelectSmallerSyntaxNode` can restore it.
+    fn expand_selections(
+        &mut self,
+        old_selections: Vec<Selection<usize>>,
+        new_ranges: Vec<Range<usize>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if old_selections
+            .iter()
+            .zip(&new_ranges)
+            .all(|(selection, new_range)| selection.range() == *new_range)
+        {
+            return;
+        }
+
+        let new_selections = old_selections
+            .iter()
+            .zip(&new_ranges)
+            .map(|(selection, new_range)| Selection {
+                id: selection.id,
+                start: new_range.start,
+                end: new_range.end,
+                goal: SelectionGoal::None,
+                reversed: selection.reversed,
             })
             .collect::<Vec<_>>();
+        let mut stack = mem::take(&mut self.select_larger_syntax_node_stack);
+        self.change_selections(Some(Autoscroll::fit()), cx, |s| {
+            s.select(new_selections);
+        });
 
-        if selected_larger_node {
-            stack.push(old_selections);
-            self.change_selections(Some(Autoscroll::fit()), cx, |s| {
-                s.select(new_selections);
-            });
+        // Selections that grew into each other get merged, in which case the resulting
+        // selection remembers all of them. Selections that didn't grow are recorded too,
+        // so that shrinking undoes the same step for every selection.
+        for selection in self.selections.all::<usize>(cx) {
+            let expanded_from = old_selections
+                .iter()
+                .zip(&new_ranges)
+                .filter(|(_, new_range)| {
+                    selection.start <= new_range.start && new_range.end <= selection.end
+                })
+                .map(|(old_selection, _)| old_selection.clone())
+                .collect::<Box<[_]>>();
+            stack.entry(selection.id).or_default().push(expanded_from);
         }
         self.select_larger_syntax_node_stack = stack;
     }
@@ -8455,9 +8574,23 @@ impl Editor {
         cx: &mut ViewContext<Self>,
     ) {
         let mut stack = mem::take(&mut self.select_larger_syntax_node_stack);
-        if let Some(selections) = stack.pop() {
+        let mut shrunk = false;
+        let mut new_selections = Vec::new();
+        for selection in self.selections.all::<usize>(cx) {
+            match stack
+                .get_mut(&selection.id)
+                .and_then(|expanded_from| expanded_from.pop())
+            {
+                Some(expanded_from) => {
+                    shrunk = true;
+                    new_selections.extend(expanded_from.into_vec());
+                }
+                None => new_selections.push(selection),
+            }
+        }
+        if shrunk {
             self.change_selections(Some(Autoscroll::fit()), cx, |s| {
-                s.select(selections.to_vec());
+                s.select(new_selections);
             });
         }
         self.select_larger_syntax_node_stack = stack;
@@ -9091,6 +9224,9 @@ impl Editor {
                     cx.open_url(&url);
                     Task::ready(Ok(None))
                 }
+                HoverLink::File(project_path, position) => {
+                    self.compute_file_target_location(project_path, position, cx)
+                }
             };
             cx.spawn(|editor, mut cx| async move {
                 let target = target_task.await.context("target resolution task")?;
@@ -9190,6 +9326,7 @@ impl Editor {
                                 }),
                                 HoverLink::InlayHint(_, _) => None,
                                 HoverLink::Url(_) => None,
+                                HoverLink::File(_, _) => None,
                             })
                             .unwrap_or(tab_kind.to_string());
                         let location_tasks = definitions
@@ -9200,6 +9337,9 @@ impl Editor {
                                     editor.compute_target_location(lsp_location, server_id, cx)
                                 }
                                 HoverLink::Url(_) => Task::ready(Ok(None)),
+                                HoverLink::File(project_path, position) => {
+                                    editor.compute_file_target_location(project_path, position, cx)
+                                }
                             })
                             .collect::<Vec<_>>();
                         (title, location_tasks, editor.workspace().clone())
@@ -9231,6 +9371,33 @@ impl Editor {
         }
     }
 
+    /// Opens a file linked from the buffer, pointing at the given position or at its start.
+    fn compute_file_target_location(
+        &self,
+        project_path: ProjectPath,
+        position: Option<Point>,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<anyhow::Result<Option<Location>>> {
+        let Some(project) = self.project.clone() else {
+            return Task::Ready(Some(Ok(None)));
+        };
+
+        cx.spawn(move |_, mut cx| async move {
+            let buffer = project
+                .update(&mut cx, |project, cx| project.open_buffer(project_path, cx))?
+                .await
+                .context("open linked file")?;
+            let position = buffer.update(&mut cx, |buffer, _| {
+                let point = buffer.clip_point(position.unwrap_or_default(), Bias::Left);
+                buffer.anchor_before(point)
+            })?;
+            Ok(Some(Location {
+                buffer,
+                range: position..position,
+            }))
+        })
+    }
+
     fn compute_target_location(
         &self,
         lsp_location: lsp::Location,
@@ -10082,6 +10249,73 @@ impl Editor {
         self.fold_ranges(ranges, true, cx);
     }
 
+    pub fn fold_all(&mut self, _: &FoldAll, cx: &mut ViewContext<Self>) {
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let max_row = display_map.buffer_snapshot.max_buffer_row();
+        let fold_ranges = (0..=max_row.0)
+            .filter_map(|row| display_map.foldable_range(MultiBufferRow(row)))
+            .collect::<Vec<_>>();
+        self.fold_ranges(fold_ranges, true, cx);
+    }
+
+    pub fn unfold_all(&mut self, _: &UnfoldAll, cx: &mut ViewContext<Self>) {
+        let len = self.buffer.read(cx).len(cx);
+        self.unfold_ranges([0..len], true, true, cx);
+    }
+
+    pub fn fold_all_comments(&mut self, _: &FoldAllComments, cx: &mut ViewContext<Self>) {
+        self.fold_folding_ranges_of_kind(FoldingRangeKind::Comment, cx);
+    }
+
+    pub fn fold_all_imports(&mut self, _: &FoldAllImports, cx: &mut ViewContext<Self>) {
+        self.fold_folding_ranges_of_kind(FoldingRangeKind::Imports, cx);
+    }
+
+    fn fold_folding_ranges_of_kind(&mut self, kind: FoldingRangeKind, cx: &mut ViewContext<Self>) {
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let fold_ranges = display_map
+            .folding_ranges()
+            .iter()
+            .filter(|range| range.kind == Some(kind))
+            .map(|range| {
+                (
+                    range.range.to_point(&display_map.buffer_snapshot),
+                    display_map.fold_placeholder.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        self.fold_ranges(fold_ranges, true, cx);
+    }
+
+    /// Folds the folding ranges nested exactly `level` deep, where top-level ranges are at level 1.
+    /// Ranges containing a selection are left unfolded.
+    pub fn fold_at_level(&mut self, action: &FoldAtLevel, cx: &mut ViewContext<Self>) {
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let buffer = &display_map.buffer_snapshot;
+        let selections = self.selections.all::<Point>(cx);
+
+        let mut enclosing_ends = Vec::<Point>::new();
+        let mut fold_ranges = Vec::new();
+        for range in display_map.folding_ranges() {
+            let range = range.range.to_point(buffer);
+            while enclosing_ends
+                .last()
+                .map_or(false, |enclosing_end| *enclosing_end < range.end)
+            {
+                enclosing_ends.pop();
+            }
+            enclosing_ends.push(range.end);
+
+            let contains_selection = selections.iter().any(|selection| {
+                range.start.row <= selection.head().row && selection.head() <= range.end
+            });
+            if enclosing_ends.len() == action.level as usize && !contains_selection {
+                fold_ranges.push((range, display_map.fold_placeholder.clone()));
+            }
+        }
+        self.fold_ranges(fold_ranges, true, cx);
+    }
+
     pub fn fold_ranges<T: ToOffset + Clone>(
         &mut self,
         ranges: impl IntoIterator<Item = (Range<T>, FoldPlaceholder)>,
@@ -11264,6 +11498,8 @@ impl Editor {
                 let Some(project) = &self.project else { return };
                 let telemetry = project.read(cx).client().telemetry().clone();
                 refresh_linked_ranges(self, cx);
+                semantic_tokens::refresh_semantic_tokens(self, false, cx);
+                code_lens::refresh_code_lens(self, false, cx);
                 telemetry.log_edit_event("editor");
             }
             multi_buffer::Event::ExcerptsAdded {
@@ -11278,9 +11514,17 @@ impl Editor {
                     excerpts: excerpts.clone(),
                 });
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                semantic_tokens::refresh_semantic_tokens(self, false, cx);
+                folding_ranges::update_folding_ranges(self, cx);
+                folding_ranges::refresh_folding_ranges(self, false, cx);
+                code_lens::update_code_lens_blocks(self, cx);
+                code_lens::refresh_code_lens(self, false, cx);
             }
             multi_buffer::Event::ExcerptsRemoved { ids } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::ExcerptsRemoved(ids.clone()), cx);
+                semantic_tokens::update_semantic_token_highlights(self, cx);
+                folding_ranges::update_folding_ranges(self, cx);
+                code_lens::update_code_lens_blocks(self, cx);
                 cx.emit(EditorEvent::ExcerptsRemoved { ids: ids.clone() })
             }
             multi_buffer::Event::ExcerptsEdited { ids } => {
@@ -11291,11 +11535,15 @@ impl Editor {
             }
             multi_buffer::Event::Reparsed(buffer_id) => {
                 self.tasks_update_task = Some(self.refresh_runnables(cx));
+                folding_ranges::refresh_folding_ranges(self, false, cx);
 
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
             }
             multi_buffer::Event::LanguageChanged(buffer_id) => {
                 linked_editing_ranges::refresh_linked_ranges(self, cx);
+                semantic_tokens::refresh_semantic_tokens(self, true, cx);
+                folding_ranges::refresh_folding_ranges(self, true, cx);
+                code_lens::refresh_code_lens(self, true, cx);
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
                 cx.notify();
             }
@@ -11338,6 +11586,11 @@ impl Editor {
             )),
             cx,
         );
+        // The theme may have changed, and with it the styles of the semantic tokens.
+        // They are only requested again for buffers where they were enabled or disabled.
+        semantic_tokens::update_semantic_token_highlights(self, cx);
+        semantic_tokens::refresh_semantic_tokens(self, false, cx);
+        code_lens::refresh_code_lens(self, false, cx);
         let editor_settings = EditorSettings::get_global(cx);
         self.scroll_manager.vertical_scroll_margin = editor_settings.vertical_scroll_margin;
         self.show_breadcrumbs = editor_settings.toolbar.breadcrumbs;
@@ -11977,6 +12230,15 @@ pub trait CompletionProvider {
     ) -> bool;
 }
 
+pub trait HoverProvider {
+    fn hover(
+        &self,
+        buffer: &Model<Buffer>,
+        buffer_position: text::Anchor,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<Vec<project::Hover>>;
+}
+
 fn snippet_completions(
     project: &Project,
     buffer: &Model<Buffer>,
@@ -12160,6 +12422,24 @@ fn inlay_hint_settings(
         .inlay_hints
 }
 
+/// Returns the range of the smallest syntax node that strictly contains the given range
+/// and doesn't start or end inside a fold.
+fn larger_syntax_node_range(range: Range<usize>, display_map: &DisplaySnapshot) -> Range<usize> {
+    let mut new_range = range;
+    while let Some(containing_range) = display_map
+        .buffer_snapshot
+        .range_for_syntax_ancestor(new_range.clone())
+    {
+        new_range = containing_range;
+        if !display_map.intersects_fold(new_range.start)
+            && !display_map.intersects_fold(new_range.end)
+        {
+            break;
+        }
+    }
+    new_range
+}
+
 fn consume_contiguous_rows(
     contiguous_row_selections: &mut Vec<Selection<Point>>,
     selection: &Selection<Point>,
@@ -12340,7 +12620,7 @@ impl EditorSnapshot {
                 cx,
             ))
         } else if folded
-            || (self.starts_indent(buffer_row) && (row_contains_cursor || self.gutter_hovered))
+            || (self.starts_fold(buffer_row) && (row_contains_cursor || self.gutter_hovered))
         {
             Some(
                 Disclosure::new(("indent-fold-indicator", buffer_row.0), !folded)
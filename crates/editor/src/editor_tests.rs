@@ -912,6 +912,199 @@ fn test_fold_action(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_fold_all_and_fold_at_level(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorLspTestContext::new(
+        rust_lang_with_folds(),
+        lsp::ServerCapabilities::default(),
+        cx,
+    )
+    .await;
+    cx.set_state(indoc! {"
+        use std::fmt;
+        use std::io;
+        ˇ
+        // one
+        // two
+        fn main() {
+            if true {
+                let x = 1;
+            }
+        }
+    "});
+    wait_for_folding_ranges(&mut cx);
+
+    cx.update_editor(|editor, cx| {
+        editor.fold_all(&FoldAll, cx);
+        assert_eq!(
+            editor.display_text(cx),
+            indoc! {"
+                use std::fmt;⋯
+
+                // one⋯
+                fn main() {⋯
+                }
+            "}
+        );
+
+        editor.unfold_all(&UnfoldAll, cx);
+        assert_eq!(
+            editor.display_text(cx),
+            editor.buffer.read(cx).read(cx).text()
+        );
+
+        editor.fold_all_comments(&FoldAllComments, cx);
+        assert_eq!(
+            editor.display_text(cx),
+            indoc! {"
+                use std::fmt;
+                use std::io;
+
+                // one⋯
+                fn main() {
+                    if true {
+                        let x = 1;
+                    }
+                }
+            "}
+        );
+        editor.unfold_all(&UnfoldAll, cx);
+
+        editor.fold_at_level(&FoldAtLevel { level: 2 }, cx);
+        assert_eq!(
+            editor.display_text(cx),
+            indoc! {"
+                use std::fmt;
+                use std::io;
+
+                // one
+                // two
+                fn main() {
+                    if true {⋯
+                    }
+                }
+            "}
+        );
+        editor.unfold_all(&UnfoldAll, cx);
+
+        // Ranges containing a selection stay unfolded.
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(7, 8)..Point::new(7, 8)])
+        });
+        editor.fold_at_level(&FoldAtLevel { level: 1 }, cx);
+        assert_eq!(
+            editor.display_text(cx),
+            indoc! {"
+                use std::fmt;⋯
+
+                // one⋯
+                fn main() {
+                    if true {
+                        let x = 1;
+                    }
+                }
+            "}
+        );
+    });
+
+    let action = serde_json::from_value::<FoldAtLevel>(json!({})).unwrap();
+    assert_eq!(action.level, 1);
+}
+
+#[gpui::test]
+async fn test_folding_range_sources(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorLspTestContext::new(
+        rust_lang_with_folds(),
+        lsp::ServerCapabilities {
+            folding_range_provider: Some(lsp::FoldingRangeProviderCapability::Simple(true)),
+            ..Default::default()
+        },
+        cx,
+    )
+    .await;
+    let lsp_ranges = Arc::new(Mutex::new(vec![lsp::FoldingRange {
+        start_line: 6,
+        end_line: 7,
+        ..Default::default()
+    }]));
+    cx.lsp
+        .handle_request::<lsp::request::FoldingRangeRequest, _, _>({
+            let lsp_ranges = lsp_ranges.clone();
+            move |_, _| {
+                let lsp_ranges = lsp_ranges.clone();
+                async move { Ok(Some(lsp_ranges.lock().clone())) }
+            }
+        });
+
+    let text = indoc! {"
+        use std::fmt;
+        use std::io;
+        ˇ
+        // one
+        // two
+        fn main() {
+            if true {
+                let x = 1;
+            }
+        }
+    "};
+    let fold_rows = |cx: &mut EditorLspTestContext| {
+        let snapshot = cx.update_editor(|editor, cx| editor.snapshot(cx));
+        (0..10)
+            .filter(|row| snapshot.starts_fold(MultiBufferRow(*row)))
+            .collect::<Vec<_>>()
+    };
+
+    // The language server's ranges take precedence over the folds query.
+    cx.set_state(text);
+    wait_for_folding_ranges(&mut cx);
+    assert_eq!(fold_rows(&mut cx), [6]);
+
+    // Without ranges from the language server, the folds query is used.
+    lsp_ranges.lock().clear();
+    cx.set_state(text);
+    wait_for_folding_ranges(&mut cx);
+    assert_eq!(fold_rows(&mut cx), [0, 3, 5, 6]);
+
+    // Without either, folds are suggested based on indentation.
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(rust_lang()), cx));
+    wait_for_folding_ranges(&mut cx);
+    assert_eq!(fold_rows(&mut cx), [5, 6]);
+}
+
+fn rust_lang_with_folds() -> Language {
+    Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["rs".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
+    )
+    .with_folds_query(
+        r#"
+        (use_declaration) @fold.imports
+        (line_comment) @fold.comment
+        (block) @fold
+        "#,
+    )
+    .unwrap()
+}
+
+fn wait_for_folding_ranges(cx: &mut EditorLspTestContext) {
+    cx.run_until_parked();
+    cx.executor()
+        .advance_clock(folding_ranges::FOLDING_RANGES_DEBOUNCE * 2);
+    cx.run_until_parked();
+}
+
 #[gpui::test]
 fn test_fold_action_whitespace_sensitive_language(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -4884,6 +5077,99 @@ async fn test_select_larger_smaller_syntax_node(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_select_larger_smaller_syntax_node_via_lsp(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.expand_selection =
+            Some(language_settings::ExpandSelectionSource::LanguageServer)
+    });
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            selection_range_provider: Some(lsp::SelectionRangeProviderCapability::Simple(true)),
+            ..Default::default()
+        },
+        cx,
+    )
+    .await;
+
+    cx.set_state(indoc! {"
+        fn main() {
+            let x = foo(ˇ1, ˇ2);
+        }
+    "});
+
+    // Reports the nested ranges on the second line that contain each position.
+    cx.handle_request::<lsp::request::SelectionRangeRequest, _, _>(|_, params, _| async move {
+        Ok(Some(
+            params
+                .positions
+                .into_iter()
+                .map(|position| {
+                    let mut selection_range = None;
+                    for (start, end) in [(4, 22), (15, 21), (19, 20), (16, 17)] {
+                        if start <= position.character && position.character < end {
+                            selection_range = Some(lsp::SelectionRange {
+                                range: lsp::Range::new(
+                                    lsp::Position::new(1, start),
+                                    lsp::Position::new(1, end),
+                                ),
+                                parent: selection_range.map(Box::new),
+                            });
+                        }
+                    }
+                    selection_range.unwrap()
+                })
+                .collect(),
+        ))
+    });
+
+    cx.update_editor(|editor, cx| editor.select_larger_syntax_node(&SelectLargerSyntaxNode, cx));
+    cx.run_until_parked();
+    cx.assert_editor_state(indoc! {"
+        fn main() {
+            let x = foo(«1ˇ», «2ˇ»);
+        }
+    "});
+
+    // The selections grow into each other and get merged.
+    cx.update_editor(|editor, cx| editor.select_larger_syntax_node(&SelectLargerSyntaxNode, cx));
+    cx.run_until_parked();
+    cx.assert_editor_state(indoc! {"
+        fn main() {
+            let x = foo«(1, 2)ˇ»;
+        }
+    "});
+
+    cx.update_editor(|editor, cx| editor.select_larger_syntax_node(&SelectLargerSyntaxNode, cx));
+    cx.run_until_parked();
+    cx.assert_editor_state(indoc! {"
+        fn main() {
+            «let x = foo(1, 2);ˇ»
+        }
+    "});
+
+    // Shrinking restores each of the selections that were merged.
+    cx.update_editor(|editor, cx| editor.select_smaller_syntax_node(&SelectSmallerSyntaxNode, cx));
+    cx.assert_editor_state(indoc! {"
+        fn main() {
+            let x = foo«(1, 2)ˇ»;
+        }
+    "});
+    cx.update_editor(|editor, cx| editor.select_smaller_syntax_node(&SelectSmallerSyntaxNode, cx));
+    cx.assert_editor_state(indoc! {"
+        fn main() {
+            let x = foo(«1ˇ», «2ˇ»);
+        }
+    "});
+    cx.update_editor(|editor, cx| editor.select_smaller_syntax_node(&SelectSmallerSyntaxNode, cx));
+    cx.assert_editor_state(indoc! {"
+        fn main() {
+            let x = foo(ˇ1, ˇ2);
+        }
+    "});
+}
+
 #[gpui::test]
 async fn test_autoindent_selections(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
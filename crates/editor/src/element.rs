@@ -328,6 +328,11 @@ impl EditorElement {
         register_action(view, cx, Editor::unfold_lines);
         register_action(view, cx, Editor::unfold_at);
         register_action(view, cx, Editor::fold_selected_ranges);
+        register_action(view, cx, Editor::fold_all);
+        register_action(view, cx, Editor::unfold_all);
+        register_action(view, cx, Editor::fold_all_comments);
+        register_action(view, cx, Editor::fold_all_imports);
+        register_action(view, cx, Editor::fold_at_level);
         register_action(view, cx, Editor::show_completions);
         register_action(view, cx, Editor::toggle_code_actions);
         register_action(view, cx, Editor::open_excerpts);
@@ -0,0 +1,190 @@
+use std::{cmp::Reverse, sync::Arc, time::Duration};
+
+use collections::{HashMap, HashSet};
+use gpui::{Task, ViewContext};
+use language::FoldingRange;
+use multi_buffer::{Anchor, AnchorRangeExt};
+use text::BufferId;
+use util::ResultExt;
+
+use crate::{display_map::FoldingRanges, Editor, EditorMode};
+
+pub(super) const FOLDING_RANGES_DEBOUNCE: Duration = Duration::from_millis(100);
+
+#[derive(Default)]
+pub(super) struct FoldingRangesState {
+    /// The folding ranges of each buffer that has any, along with the buffer version they were computed at.
+    ranges: HashMap<BufferId, (clock::Global, Vec<FoldingRange<text::Anchor>>)>,
+    refresh_task: Option<Task<Option<()>>>,
+}
+
+/// Recomputes the folding ranges of the buffers in the editor.
+///
+/// Ranges reported by the buffer's language server are preferred, falling back to
+/// the ranges of the language's folds query. Buffers that have neither keep
+/// suggesting folds based on indentation.
+pub(super) fn refresh_folding_ranges(
+    editor: &mut Editor,
+    force: bool,
+    cx: &mut ViewContext<Editor>,
+) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+
+    let buffers = editor
+        .buffer
+        .read(cx)
+        .all_buffers()
+        .into_iter()
+        .filter(|buffer| {
+            let buffer = buffer.read(cx);
+            buffer.language().is_some()
+                && (force
+                    || editor
+                        .folding_ranges
+                        .ranges
+                        .get(&buffer.remote_id())
+                        .map_or(true, |(version, _)| buffer.version().changed_since(version)))
+        })
+        .collect::<Vec<_>>();
+    if buffers.is_empty() {
+        return;
+    }
+
+    let project = editor.project.clone();
+    editor.folding_ranges.refresh_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor()
+            .timer(FOLDING_RANGES_DEBOUNCE)
+            .await;
+
+        let requests = cx
+            .update(|cx| {
+                buffers
+                    .into_iter()
+                    .map(|buffer| {
+                        let snapshot = buffer.read(cx).snapshot();
+                        let buffer_id = snapshot.remote_id();
+                        let version = snapshot.version().clone();
+                        let lsp_request = project.as_ref().map(|project| {
+                            project.update(cx, |project, cx| project.folding_ranges(&buffer, cx))
+                        });
+                        let background = cx.background_executor().clone();
+                        async move {
+                            if let Some(lsp_request) = lsp_request {
+                                if let Some(ranges) = lsp_request.await.log_err() {
+                                    if !ranges.is_empty() {
+                                        return (buffer_id, version, Some(ranges));
+                                    }
+                                }
+                            }
+                            if !snapshot.has_syntax_folding_ranges() {
+                                return (buffer_id, version, None);
+                            }
+                            let ranges = background
+                                .spawn(async move {
+                                    snapshot
+                                        .folding_ranges()
+                                        .into_iter()
+                                        .map(|range| FoldingRange {
+                                            range: snapshot.anchor_after(range.range.start)
+                                                ..snapshot.anchor_before(range.range.end),
+                                            kind: range.kind,
+                                        })
+                                        .collect::<Vec<_>>()
+                                })
+                                .await;
+                            (buffer_id, version, Some(ranges))
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .ok()?;
+        let responses = futures::future::join_all(requests).await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                for (buffer_id, version, ranges) in responses {
+                    match ranges {
+                        Some(ranges) => {
+                            editor
+                                .folding_ranges
+                                .ranges
+                                .insert(buffer_id, (version, ranges));
+                        }
+                        None => {
+                            editor.folding_ranges.ranges.remove(&buffer_id);
+                        }
+                    }
+                }
+                update_folding_ranges(editor, cx);
+            })
+            .ok()
+    }));
+}
+
+/// Lays out the folding ranges of every buffer in multi-buffer order for the display map.
+///
+/// Only ranges starting within an excerpt are kept, and they are clipped to its end.
+pub(super) fn update_folding_ranges(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let multi_buffer = editor.buffer.read(cx);
+    let snapshot = multi_buffer.snapshot(cx);
+
+    let mut ranges = Vec::<FoldingRange<Anchor>>::new();
+    let mut buffer_ids = HashSet::default();
+    editor
+        .folding_ranges
+        .ranges
+        .retain(|buffer_id, (_, buffer_ranges)| {
+            let Some(buffer) = multi_buffer.buffer(*buffer_id) else {
+                return false;
+            };
+            buffer_ids.insert(*buffer_id);
+            let buffer_snapshot = buffer.read(cx).snapshot();
+            for (excerpt_id, excerpt_range) in multi_buffer.excerpts_for_buffer(&buffer, cx) {
+                let context = excerpt_range.context;
+                for range in buffer_ranges.iter() {
+                    if range
+                        .range
+                        .start
+                        .cmp(&context.start, &buffer_snapshot)
+                        .is_lt()
+                        || range
+                            .range
+                            .start
+                            .cmp(&context.end, &buffer_snapshot)
+                            .is_ge()
+                    {
+                        continue;
+                    }
+                    let end = if range.range.end.cmp(&context.end, &buffer_snapshot).is_gt() {
+                        context.end
+                    } else {
+                        range.range.end
+                    };
+                    if let Some((start, end)) = snapshot
+                        .anchor_in_excerpt(excerpt_id, range.range.start)
+                        .zip(snapshot.anchor_in_excerpt(excerpt_id, end))
+                    {
+                        ranges.push(FoldingRange {
+                            range: start..end,
+                            kind: range.kind,
+                        });
+                    }
+                }
+            }
+            true
+        });
+    ranges.sort_by_cached_key(|range| {
+        let range = range.range.to_offset(&snapshot);
+        (range.start, Reverse(range.end))
+    });
+
+    editor.display_map.update(cx, |display_map, _| {
+        display_map.set_folding_ranges(FoldingRanges {
+            ranges: Arc::from(ranges),
+            buffer_ids: Arc::new(buffer_ids),
+        })
+    });
+    cx.notify();
+}
@@ -4,17 +4,28 @@ use crate::{
     Anchor, Editor, EditorSnapshot, FindAllReferences, GoToDefinition, GoToTypeDefinition, InlayId,
     PointForPosition, SelectPhase,
 };
+use collections::HashMap;
+use futures::{future::Shared, FutureExt as _};
 use gpui::{px, AppContext, AsyncWindowContext, Model, Modifiers, Task, ViewContext};
-use language::{Bias, ToOffset};
+use language::{Bias, Point, ToOffset};
 use linkify::{LinkFinder, LinkKind};
 use lsp::LanguageServerId;
 use project::{
-    HoverBlock, HoverBlockKind, InlayHintLabelPartTooltip, InlayHintTooltip, LocationLink,
-    ResolveState,
+    DocumentLink, HoverBlock, HoverBlockKind, InlayHintLabelPartTooltip, InlayHintTooltip,
+    LocationLink, Project, ProjectPath, ResolveState,
 };
-use std::ops::Range;
+use std::{
+    ops::Range,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+use text::BufferId;
 use theme::ActiveTheme as _;
-use util::{maybe, ResultExt, TryFutureExt};
+use util::{
+    maybe,
+    paths::{home_dir, PathWithPosition},
+    ResultExt, TryFutureExt,
+};
 
 #[derive(Debug)]
 pub struct HoveredLinkState {
@@ -63,6 +74,8 @@ impl RangeInEditor {
 #[derive(Debug, Clone)]
 pub enum HoverLink {
     Url(String),
+    /// A file of the project, along with the position to open it at.
+    File(ProjectPath, Option<Point>),
     Text(LocationLink),
     InlayHint(lsp::Location, LanguageServerId),
 }
@@ -504,55 +517,71 @@ pub fn show_link_definition(
         editor.hide_hovered_link(cx)
     }
     let project = editor.project.clone();
+    let document_links = match &trigger_point {
+        TriggerPoint::Text(_) => document_links(editor, &buffer, cx),
+        TriggerPoint::InlayHint(..) => None,
+    };
 
     let snapshot = snapshot.buffer_snapshot.clone();
     hovered_link_state.task = Some(cx.spawn(|this, mut cx| {
         async move {
             let result = match &trigger_point {
                 TriggerPoint::Text(_) => {
-                    if let Some((url_range, url)) = find_url(&buffer, buffer_position, cx.clone()) {
-                        this.update(&mut cx, |_, _| {
-                            let range = maybe!({
-                                let start =
-                                    snapshot.anchor_in_excerpt(excerpt_id, url_range.start)?;
-                                let end = snapshot.anchor_in_excerpt(excerpt_id, url_range.end)?;
-                                Some(RangeInEditor::Text(start..end))
-                            });
-                            (range, vec![HoverLink::Url(url)])
-                        })
-                        .ok()
-                    } else if let Some(project) = project {
-                        // query the LSP for definition info
-                        project
-                            .update(&mut cx, |project, cx| match preferred_kind {
-                                LinkDefinitionKind::Symbol => {
-                                    project.definition(&buffer, buffer_position, cx)
-                                }
-
-                                LinkDefinitionKind::Type => {
-                                    project.type_definition(&buffer, buffer_position, cx)
-                                }
-                            })?
+                    let range_in_editor = |link_range: Range<text::Anchor>| {
+                        let start = snapshot.anchor_in_excerpt(excerpt_id, link_range.start)?;
+                        let end = snapshot.anchor_in_excerpt(excerpt_id, link_range.end)?;
+                        Some(RangeInEditor::Text(start..end))
+                    };
+                    let link = match find_url(&buffer, buffer_position, cx.clone()) {
+                        Some((url_range, url)) => Some((url_range, HoverLink::Url(url))),
+                        None => {
+                            find_document_link(
+                                project.as_ref(),
+                                document_links,
+                                &buffer,
+                                buffer_position,
+                                cx.clone(),
+                            )
                             .await
-                            .ok()
-                            .map(|definition_result| {
-                                (
-                                    definition_result.iter().find_map(|link| {
-                                        link.origin.as_ref().and_then(|origin| {
-                                            let start = snapshot.anchor_in_excerpt(
-                                                excerpt_id,
-                                                origin.range.start,
-                                            )?;
-                                            let end = snapshot
-                                                .anchor_in_excerpt(excerpt_id, origin.range.end)?;
-                                            Some(RangeInEditor::Text(start..end))
-                                        })
-                                    }),
-                                    definition_result.into_iter().map(HoverLink::Text).collect(),
-                                )
-                            })
+                        }
+                    };
+                    if let Some((link_range, link)) = link {
+                        Some((range_in_editor(link_range), vec![link]))
                     } else {
-                        None
+                        // query the LSP for definition info
+                        let definitions = match &project {
+                            Some(project) => project
+                                .update(&mut cx, |project, cx| match preferred_kind {
+                                    LinkDefinitionKind::Symbol => {
+                                        project.definition(&buffer, buffer_position, cx)
+                                    }
+
+                                    LinkDefinitionKind::Type => {
+                                        project.type_definition(&buffer, buffer_position, cx)
+                                    }
+                                })?
+                                .await
+                                .ok()
+                                .filter(|definitions| !definitions.is_empty()),
+                            None => None,
+                        };
+                        match definitions {
+                            Some(definition_result) => Some((
+                                definition_result.iter().find_map(|link| {
+                                    link.origin
+                                        .as_ref()
+                                        .and_then(|origin| range_in_editor(origin.range.clone()))
+                                }),
+                                definition_result.into_iter().map(HoverLink::Text).collect(),
+                            )),
+                            // Paths in the text are only followed when there's nothing else to go to.
+                            None => {
+                                find_file(project.as_ref(), &buffer, buffer_position, cx.clone())
+                                    .map(|(file_range, link)| {
+                                        (range_in_editor(file_range), vec![link])
+                                    })
+                            }
+                        }
                     }
                 }
                 TriggerPoint::InlayHint(highlight, lsp_location, server_id) => Some((
@@ -686,6 +715,212 @@ pub(crate) fn find_url(
     None
 }
 
+/// The document links of each buffer, which are requested at most once per buffer version.
+#[derive(Default)]
+pub(crate) struct DocumentLinks {
+    links: HashMap<BufferId, (clock::Global, Shared<Task<Arc<[DocumentLink]>>>)>,
+}
+
+/// Returns the document links of the buffer, reusing the ones requested for its current version.
+fn document_links(
+    editor: &mut Editor,
+    buffer: &Model<language::Buffer>,
+    cx: &mut ViewContext<Editor>,
+) -> Option<Shared<Task<Arc<[DocumentLink]>>>> {
+    let project = editor.project.clone()?;
+    let multi_buffer = editor.buffer.read(cx);
+    editor
+        .document_links
+        .links
+        .retain(|buffer_id, _| multi_buffer.buffer(*buffer_id).is_some());
+
+    let (buffer_id, version) = {
+        let buffer = buffer.read(cx);
+        (buffer.remote_id(), buffer.version())
+    };
+    if let Some((links_version, links)) = editor.document_links.links.get(&buffer_id) {
+        if *links_version == version {
+            return Some(links.clone());
+        }
+    }
+
+    let request = project.update(cx, |project, cx| project.document_links(buffer, cx));
+    let links = cx
+        .spawn(|_, _| async move { Arc::from(request.await.log_err().unwrap_or_default()) })
+        .shared();
+    editor
+        .document_links
+        .links
+        .insert(buffer_id, (version, links.clone()));
+    Some(links)
+}
+
+/// Finds the link that the buffer's language server reports at the given position.
+async fn find_document_link(
+    project: Option<&Model<Project>>,
+    links: Option<Shared<Task<Arc<[DocumentLink]>>>>,
+    buffer: &Model<language::Buffer>,
+    position: text::Anchor,
+    cx: AsyncWindowContext,
+) -> Option<(Range<text::Anchor>, HoverLink)> {
+    let project = project?;
+    let links = links?.await;
+    let snapshot = buffer.read_with(&cx, |buffer, _| buffer.snapshot()).ok()?;
+    let link = links.iter().find(|link| {
+        link.range.start.cmp(&position, &snapshot).is_le()
+            && link.range.end.cmp(&position, &snapshot).is_ge()
+    })?;
+    let target = link.target.clone()?;
+
+    let project_path = if target.scheme() == "file" {
+        let abs_path = target.to_file_path().ok()?;
+        project
+            .read_with(&cx, |project, cx| {
+                let (worktree, path) = project.find_worktree(&abs_path, cx)?;
+                Some(ProjectPath {
+                    worktree_id: worktree.read(cx).id(),
+                    path: path.into(),
+                })
+            })
+            .ok()
+            .flatten()
+    } else {
+        None
+    };
+    let hover_link = match project_path {
+        Some(project_path) => HoverLink::File(
+            project_path,
+            target.fragment().and_then(position_from_fragment),
+        ),
+        None => HoverLink::Url(target.to_string()),
+    };
+    Some((link.range.clone(), hover_link))
+}
+
+/// Parses the `L12`, `L12,5` or `12,5` fragment that language servers append to file links.
+fn position_from_fragment(fragment: &str) -> Option<Point> {
+    let fragment = fragment.strip_prefix('L').unwrap_or(fragment);
+    let (row, column) = match fragment.split_once(',') {
+        Some((row, column)) => (row, Some(column)),
+        None => (fragment, None),
+    };
+    let row = row.parse::<u32>().ok()?;
+    let column = column.and_then(|column| column.parse::<u32>().ok());
+    Some(Point::new(
+        row.saturating_sub(1),
+        column.unwrap_or(1).saturating_sub(1),
+    ))
+}
+
+/// Finds a `path/to/file.rs:12:5` reference to a file of the project at the given position.
+pub(crate) fn find_file(
+    project: Option<&Model<Project>>,
+    buffer: &Model<language::Buffer>,
+    position: text::Anchor,
+    mut cx: AsyncWindowContext,
+) -> Option<(Range<text::Anchor>, HoverLink)> {
+    const LIMIT: usize = 1024;
+
+    let project = project?;
+    let snapshot = buffer.update(&mut cx, |buffer, _| buffer.snapshot()).ok()?;
+    let is_delimiter = |ch: char| {
+        ch.is_whitespace()
+            || matches!(
+                ch,
+                '"' | '\'' | '`' | '(' | ')' | '[' | ']' | '{' | '}' | '<' | '>' | ',' | ';'
+            )
+    };
+
+    let offset = position.to_offset(&snapshot);
+    let mut token_start = offset;
+    for ch in snapshot.reversed_chars_at(offset).take(LIMIT) {
+        if is_delimiter(ch) {
+            break;
+        }
+        token_start -= ch.len_utf8();
+    }
+    let mut token_end = offset;
+    for ch in snapshot.chars_at(offset).take(LIMIT) {
+        if is_delimiter(ch) {
+            break;
+        }
+        token_end += ch.len_utf8();
+    }
+
+    // Ignore punctuation after the path, such as the end of a sentence.
+    let token = snapshot
+        .text_for_range(token_start..token_end)
+        .collect::<String>();
+    let token = token.trim_end_matches(['.', ':']);
+    let token_end = token_start + token.len();
+    if token.is_empty() || offset > token_end {
+        return None;
+    }
+
+    let path_with_position = PathWithPosition::parse_str(token);
+    let project_path = project
+        .read_with(&cx, |project, cx| {
+            resolve_project_path(project, buffer.read(cx), &path_with_position.path, cx)
+        })
+        .ok()??;
+    let position = path_with_position.row.map(|row| {
+        Point::new(
+            row.saturating_sub(1),
+            path_with_position.column.unwrap_or(1).saturating_sub(1),
+        )
+    });
+    Some((
+        snapshot.anchor_before(token_start)..snapshot.anchor_after(token_end),
+        HoverLink::File(project_path, position),
+    ))
+}
+
+/// Resolves a path to a file of the project. Relative paths are looked up in the
+/// directory of the buffer first, and then in the root of each worktree.
+fn resolve_project_path(
+    project: &Project,
+    buffer: &language::Buffer,
+    path: &Path,
+    cx: &AppContext,
+) -> Option<ProjectPath> {
+    let is_file = |project_path: &ProjectPath| {
+        project
+            .entry_for_path(project_path, cx)
+            .map_or(false, |entry| entry.is_file())
+    };
+
+    let path = match path.strip_prefix("~") {
+        Ok(path) => home_dir().join(path),
+        Err(_) => path.to_path_buf(),
+    };
+    if path.is_absolute() {
+        let (worktree, path) = project.find_worktree(&path, cx)?;
+        let project_path = ProjectPath {
+            worktree_id: worktree.read(cx).id(),
+            path: path.into(),
+        };
+        return is_file(&project_path).then_some(project_path);
+    }
+
+    let path = path
+        .components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .collect::<PathBuf>();
+    let relative_to_buffer = project::File::from_dyn(buffer.file()).and_then(|file| {
+        Some(ProjectPath {
+            worktree_id: file.worktree_id(cx),
+            path: file.path().parent()?.join(&path).into(),
+        })
+    });
+    relative_to_buffer
+        .into_iter()
+        .chain(project.visible_worktrees(cx).map(|worktree| ProjectPath {
+            worktree_id: worktree.read(cx).id(),
+            path: path.clone().into(),
+        }))
+        .find(|project_path| is_file(project_path))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -701,6 +936,7 @@ mod tests {
     use indoc::indoc;
     use language::language_settings::InlayHintSettings;
     use lsp::request::{GotoDefinition, GotoTypeDefinition};
+    use std::sync::atomic::{self, AtomicUsize};
     use util::assert_set_eq;
     use workspace::item::Item;
 
@@ -1268,4 +1504,191 @@ mod tests {
         cx.simulate_click(screen_coord, Modifiers::secondary_key());
         assert_eq!(cx.opened_url(), Some("https://zed.dev/releases".into()));
     }
+
+    #[gpui::test]
+    async fn test_document_links(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                document_link_provider: Some(lsp::DocumentLinkOptions {
+                    resolve_provider: None,
+                    work_done_progress_options: Default::default(),
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            // Read the [docs] before changing this.ˇ
+        "});
+        let link_range = cx.lsp_range(indoc! {"
+            // Read the [«docs»] before changing this.
+        "});
+        let request_count = Arc::new(AtomicUsize::new(0));
+        let mut requests = cx.handle_request::<lsp::request::DocumentLinkRequest, _, _>({
+            let request_count = request_count.clone();
+            move |_, _, _| {
+                request_count.fetch_add(1, atomic::Ordering::SeqCst);
+                async move {
+                    Ok(Some(vec![lsp::DocumentLink {
+                        range: link_range,
+                        target: Some(lsp::Url::parse("https://zed.dev/docs").unwrap()),
+                        tooltip: None,
+                        data: None,
+                    }]))
+                }
+            }
+        });
+
+        let screen_coord = cx.pixel_position(indoc! {"
+            // Read the [doˇcs] before changing this.
+        "});
+        cx.simulate_mouse_move(screen_coord, None, Modifiers::secondary_key());
+        requests.next().await;
+        cx.run_until_parked();
+        cx.assert_editor_text_highlights::<HoveredLinkState>(indoc! {"
+            // Read the [«docsˇ»] before changing this.
+        "});
+
+        cx.simulate_click(screen_coord, Modifiers::secondary_key());
+        assert_eq!(cx.opened_url(), Some("https://zed.dev/docs".into()));
+
+        // The links are only requested again once the buffer changes.
+        let other_coord = cx.pixel_position(indoc! {"
+            // Reˇad the [docs] before changing this.
+        "});
+        cx.simulate_mouse_move(other_coord, None, Modifiers::secondary_key());
+        cx.run_until_parked();
+        cx.simulate_mouse_move(screen_coord, None, Modifiers::secondary_key());
+        cx.run_until_parked();
+        assert_eq!(request_count.load(atomic::Ordering::SeqCst), 1);
+
+        cx.set_state(indoc! {"
+            // Read the [docs] before changing this.
+            ˇ
+        "});
+        cx.simulate_mouse_move(other_coord, None, Modifiers::secondary_key());
+        cx.run_until_parked();
+        assert_eq!(request_count.load(atomic::Ordering::SeqCst), 2);
+    }
+
+    #[gpui::test]
+    async fn test_file_paths(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            // See dir/file.rs:3:5.
+            fn main() {
+                let x = 1;ˇ
+            }
+        "});
+
+        // Paths that don't exist in the project aren't links.
+        let screen_coord = cx.pixel_position(indoc! {"
+            // Sˇee dir/file.rs:3:5.
+            fn main() {
+                let x = 1;
+            }
+        "});
+        cx.simulate_mouse_move(screen_coord, None, Modifiers::secondary_key());
+        cx.run_until_parked();
+        cx.assert_editor_text_highlights::<HoveredLinkState>(indoc! {"
+            // See dir/file.rs:3:5.
+            fn main() {
+                let x = 1;
+            }
+        "});
+
+        // Paths are resolved relative to the worktree, ignoring trailing punctuation.
+        let screen_coord = cx.pixel_position(indoc! {"
+            // See dir/fiˇle.rs:3:5.
+            fn main() {
+                let x = 1;
+            }
+        "});
+        cx.simulate_mouse_move(screen_coord, None, Modifiers::secondary_key());
+        cx.run_until_parked();
+        cx.assert_editor_text_highlights::<HoveredLinkState>(indoc! {"
+            // See «dir/file.rs:3:5ˇ».
+            fn main() {
+                let x = 1;
+            }
+        "});
+
+        cx.simulate_click(screen_coord, Modifiers::secondary_key());
+        cx.run_until_parked();
+        cx.assert_editor_state(indoc! {"
+            // See dir/file.rs:3:5.
+            fn main() {
+                ˇlet x = 1;
+            }
+        "});
+    }
+
+    #[gpui::test]
+    async fn test_definitions_take_precedence_over_file_paths(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                definition_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            mod dir;
+            fn main() {
+                dir::file::run();ˇ
+            }
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            mod dir;
+            fn main() {
+                dir::«file»::run();
+            }
+        "});
+        let target_range = cx.lsp_range(indoc! {"
+            mod «dir»;
+            fn main() {
+                dir::file::run();
+            }
+        "});
+        let mut requests = cx.handle_request::<GotoDefinition, _, _>(move |url, _, _| async move {
+            Ok(Some(lsp::GotoDefinitionResponse::Link(vec![
+                lsp::LocationLink {
+                    origin_selection_range: Some(symbol_range),
+                    target_uri: url.clone(),
+                    target_range,
+                    target_selection_range: target_range,
+                },
+            ])))
+        });
+
+        let screen_coord = cx.pixel_position(indoc! {"
+            mod dir;
+            fn main() {
+                dir::fiˇle::run();
+            }
+        "});
+        cx.simulate_mouse_move(screen_coord, None, Modifiers::secondary_key());
+        requests.next().await;
+        cx.run_until_parked();
+        cx.assert_editor_text_highlights::<HoveredLinkState>(indoc! {"
+            mod dir;
+            fn main() {
+                dir::«fileˇ»::run();
+            }
+        "});
+    }
 }
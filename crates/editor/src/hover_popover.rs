@@ -268,6 +268,11 @@ fn show_hover(
                     project.hover(&buffer, buffer_position, cx)
                 })
             })?;
+            let provider_hover_request = this.update(&mut cx, |this, cx| {
+                this.hover_provider
+                    .as_ref()
+                    .map(|provider| provider.hover(&buffer, buffer_position, cx))
+            })?;
 
             if let Some(delay) = delay {
                 delay.await;
@@ -377,7 +382,10 @@ fn show_hover(
                 this.hover_state.diagnostic_popover = diagnostic_popover;
             })?;
 
-            let hovers_response = hover_request.await;
+            let mut hovers_response = hover_request.await;
+            if let Some(provider_hover_request) = provider_hover_request {
+                hovers_response.extend(provider_hover_request.await);
+            }
             let language_registry = project.update(&mut cx, |p, _| p.languages().clone())?;
             let snapshot = this.update(&mut cx, |this, cx| this.snapshot(cx))?;
             let mut hover_highlights = Vec::with_capacity(hovers_response.len());
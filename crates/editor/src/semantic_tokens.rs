@@ -0,0 +1,190 @@
+use std::{ops::Range, sync::Arc, time::Duration};
+
+use collections::HashMap;
+use gpui::{Task, ViewContext};
+use language::{language_settings::language_settings, HighlightId, SemanticTokenHighlightMap};
+use multi_buffer::Anchor;
+use project::SemanticTokens;
+use text::BufferId;
+use theme::ActiveTheme;
+use util::ResultExt;
+
+use crate::{Editor, EditorMode};
+
+const SEMANTIC_TOKENS_DEBOUNCE: Duration = Duration::from_millis(150);
+
+#[derive(Default)]
+pub(super) struct SemanticTokensState {
+    /// The latest tokens for each buffer, along with the buffer version they were requested at.
+    tokens: HashMap<BufferId, (clock::Global, SemanticTokens)>,
+    /// Whether semantic tokens were enabled for each buffer when it was last refreshed.
+    enabled: HashMap<BufferId, bool>,
+    refresh_task: Option<Task<Option<()>>>,
+}
+
+/// Requests semantic tokens for the buffers in the editor.
+///
+/// Buffers that didn't change since their last tokens were received are skipped,
+/// unless `force` is set, as it is when a language server asks for a refresh, or
+/// semantic tokens were enabled or disabled for them since.
+pub(super) fn refresh_semantic_tokens(
+    editor: &mut Editor,
+    force: bool,
+    cx: &mut ViewContext<Editor>,
+) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let all_buffers = editor.buffer.read(cx).all_buffers();
+    editor.semantic_tokens.enabled.retain(|buffer_id, _| {
+        all_buffers
+            .iter()
+            .any(|buffer| buffer.read(cx).remote_id() == *buffer_id)
+    });
+
+    let mut buffers = Vec::new();
+    for buffer in all_buffers {
+        let snapshot = buffer.read(cx);
+        if snapshot.language().is_none() {
+            continue;
+        }
+        let buffer_id = snapshot.remote_id();
+        let enabled = language_settings(snapshot.language(), snapshot.file(), cx).semantic_tokens;
+        let setting_changed =
+            editor.semantic_tokens.enabled.insert(buffer_id, enabled) != Some(enabled);
+        let buffer_changed = editor
+            .semantic_tokens
+            .tokens
+            .get(&buffer_id)
+            .map_or(true, |(version, _)| {
+                snapshot.version().changed_since(version)
+            });
+        if force || setting_changed || buffer_changed {
+            buffers.push(buffer);
+        }
+    }
+    if buffers.is_empty() {
+        return;
+    }
+
+    editor.semantic_tokens.refresh_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor()
+            .timer(SEMANTIC_TOKENS_DEBOUNCE)
+            .await;
+
+        let requests = project
+            .update(&mut cx, |project, cx| {
+                buffers
+                    .into_iter()
+                    .map(|buffer| {
+                        let buffer_id = buffer.read(cx).remote_id();
+                        let version = buffer.read(cx).version();
+                        let request = project.semantic_tokens(&buffer, cx);
+                        async move { (buffer_id, version, request.await) }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .ok()?;
+        let responses = futures::future::join_all(requests).await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                for (buffer_id, version, response) in responses {
+                    match response.log_err() {
+                        Some(Some(tokens)) => {
+                            editor
+                                .semantic_tokens
+                                .tokens
+                                .insert(buffer_id, (version, tokens));
+                        }
+                        Some(None) => {
+                            editor.semantic_tokens.tokens.remove(&buffer_id);
+                        }
+                        // Keep the previous tokens, which are still anchored correctly.
+                        None => {}
+                    }
+                }
+                update_semantic_token_highlights(editor, cx);
+            })
+            .ok()
+    }));
+}
+
+/// Maps the tokens of every buffer to highlights in the current theme and
+/// lays them out in multi-buffer order for the display map.
+pub(super) fn update_semantic_token_highlights(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let multi_buffer = editor.buffer.read(cx);
+    let snapshot = multi_buffer.snapshot(cx);
+    let theme = cx.theme().syntax().clone();
+
+    let mut highlights = Vec::<(Range<Anchor>, HighlightId)>::new();
+    editor
+        .semantic_tokens
+        .tokens
+        .retain(|buffer_id, (_, tokens)| {
+            let Some(buffer) = multi_buffer.buffer(*buffer_id) else {
+                return false;
+            };
+            let buffer_snapshot = buffer.read(cx).snapshot();
+            let highlight_map = SemanticTokenHighlightMap::new(
+                &tokens.token_types[..],
+                &tokens.token_modifiers[..],
+                &theme,
+            );
+            for (excerpt_id, excerpt_range) in multi_buffer.excerpts_for_buffer(&buffer, cx) {
+                let context = excerpt_range.context;
+                for token in &tokens.tokens {
+                    if token
+                        .range
+                        .end
+                        .cmp(&context.start, &buffer_snapshot)
+                        .is_le()
+                        || token
+                            .range
+                            .start
+                            .cmp(&context.end, &buffer_snapshot)
+                            .is_ge()
+                    {
+                        continue;
+                    }
+                    let Some(highlight_id) =
+                        highlight_map.get(token.token_type, token.token_modifiers)
+                    else {
+                        continue;
+                    };
+                    let start = if token
+                        .range
+                        .start
+                        .cmp(&context.start, &buffer_snapshot)
+                        .is_lt()
+                    {
+                        context.start
+                    } else {
+                        token.range.start
+                    };
+                    let end = if token.range.end.cmp(&context.end, &buffer_snapshot).is_gt() {
+                        context.end
+                    } else {
+                        token.range.end
+                    };
+                    if let Some((start, end)) = snapshot
+                        .anchor_in_excerpt(excerpt_id, start)
+                        .zip(snapshot.anchor_in_excerpt(excerpt_id, end))
+                    {
+                        highlights.push((start..end, highlight_id));
+                    }
+                }
+            }
+            true
+        });
+    highlights.sort_by(|(a, _), (b, _)| a.start.cmp(&b.start, &snapshot));
+
+    editor.display_map.update(cx, |display_map, _| {
+        display_map.set_semantic_token_highlights(Arc::from(highlights))
+    });
+    cx.notify();
+}
@@ -1016,6 +1016,51 @@ impl FakeFs {
         });
     }
 
+    pub fn set_revision_contents_for_repo(
+        &self,
+        dot_git: &Path,
+        revision: &str,
+        contents: &[(&Path, String)],
+    ) {
+        self.with_git_state(dot_git, true, |state| {
+            state.revision_contents.insert(
+                revision.to_string(),
+                contents
+                    .iter()
+                    .map(|(path, content)| (path.to_path_buf(), content.clone()))
+                    .collect(),
+            );
+        });
+    }
+
+    pub fn set_merge_base_for_repo(&self, dot_git: &Path, revision: &str, merge_base: git::Oid) {
+        self.with_git_state(dot_git, true, |state| {
+            state.merge_bases.insert(revision.to_string(), merge_base);
+        });
+    }
+
+    pub fn set_upstream_for_repo(
+        &self,
+        dot_git: &Path,
+        branch: &str,
+        upstream: Option<git::repository::Upstream>,
+    ) {
+        self.with_git_state(dot_git, true, |state| match upstream {
+            Some(upstream) => {
+                state.upstreams.insert(branch.to_string(), upstream);
+            }
+            None => {
+                state.upstreams.remove(branch);
+            }
+        });
+    }
+
+    pub fn set_commits_for_repo(&self, dot_git: &Path, commits: Vec<git::log::CommitDetails>) {
+        self.with_git_state(dot_git, true, |state| {
+            state.commits = commits;
+        });
+    }
+
     pub fn set_status_for_repo_via_working_copy_change(
         &self,
         dot_git: &Path,
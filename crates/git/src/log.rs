@@ -0,0 +1,315 @@
+use crate::repository::RepoPath;
+use crate::Oid;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// A commit listed by `git log`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    pub sha: Oid,
+    pub parents: Vec<Oid>,
+    pub author_name: String,
+    pub author_email: String,
+    /// Author timestamp, normalized to Unix Epoch format.
+    pub timestamp: i64,
+    pub summary: String,
+    /// The branches and tags pointing at this commit.
+    pub refs: Vec<CommitRef>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitRefKind {
+    Head,
+    LocalBranch,
+    RemoteBranch,
+    Tag,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CommitRef {
+    pub kind: CommitRefKind,
+    /// The short name of the ref, e.g. `main`, `origin/main` or `v1.0`.
+    pub name: String,
+}
+
+/// The full message of a commit along with the files it changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitDetails {
+    pub commit: Commit,
+    pub message: String,
+    pub files: Vec<CommitFileChange>,
+}
+
+/// The contents of a file before and after a commit.
+///
+/// `old_text` is `None` for added files and `new_text` is `None` for deleted ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitFileChange {
+    pub path: RepoPath,
+    pub old_path: Option<RepoPath>,
+    pub old_text: Option<String>,
+    pub new_text: Option<String>,
+}
+
+const FIELD_SEPARATOR: char = '\x1f';
+const RECORD_SEPARATOR: char = '\x1e';
+const GIT_LOG_FORMAT: &str = "--format=%H%x1f%P%x1f%an%x1f%ae%x1f%at%x1f%D%x1f%s%x1e";
+const GIT_LOG_NO_COMMITS: &str = "does not have any commits yet";
+
+/// Runs `git log` for the given revision range, or `HEAD` when there is none.
+///
+/// The revisions of the range are resolved to commit shas first, so that they can't be mistaken
+/// for options of `git log`.
+///
+/// When a path is given, only the commits touching it are listed, following renames of files.
+pub fn log(
+    git_binary: &Path,
+    working_directory: &Path,
+    path: Option<&Path>,
+    range: Option<&str>,
+    limit: usize,
+) -> Result<Vec<Commit>> {
+    let range = match range {
+        Some(range) => resolve_range(git_binary, working_directory, range)?,
+        None => "HEAD".to_string(),
+    };
+
+    let mut child = git_command(git_binary, working_directory);
+    child
+        .args([
+            "--no-optional-locks",
+            "log",
+            "--decorate=full",
+            GIT_LOG_FORMAT,
+        ])
+        .arg(format!("--max-count={limit}"));
+    if path.map_or(false, |path| working_directory.join(path).is_file()) {
+        child.arg("--follow");
+    }
+    child.arg(range).arg("--");
+    if let Some(path) = path {
+        child.arg(path.as_os_str());
+    }
+
+    let output = child
+        .output()
+        .map_err(|e| anyhow!("Failed to start git log process: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains(GIT_LOG_NO_COMMITS) {
+            return Ok(Vec::new());
+        }
+        return Err(anyhow!("git log process failed: {}", stderr));
+    }
+
+    parse_git_log(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Resolves both ends of a `start..end` or `start...end` range, or the single revision given.
+fn resolve_range(git_binary: &Path, working_directory: &Path, range: &str) -> Result<String> {
+    for separator in ["...", ".."] {
+        if let Some((start, end)) = range.split_once(separator) {
+            let mut resolve = |revision: &str| {
+                if revision.is_empty() {
+                    resolve_revision(git_binary, working_directory, "HEAD")
+                } else {
+                    resolve_revision(git_binary, working_directory, revision)
+                }
+            };
+            let start = resolve(start)?;
+            let end = resolve(end)?;
+            return Ok(format!("{start}{separator}{end}"));
+        }
+    }
+    resolve_revision(git_binary, working_directory, range)
+}
+
+/// Returns the sha of the commit named by the given revision.
+fn resolve_revision(git_binary: &Path, working_directory: &Path, revision: &str) -> Result<String> {
+    if revision.is_empty() || revision.starts_with('-') {
+        return Err(anyhow!("invalid revision {revision:?}"));
+    }
+
+    let output = git_command(git_binary, working_directory)
+        .args([
+            "--no-optional-locks",
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            "--end-of-options",
+        ])
+        .arg(format!("{revision}^{{commit}}"))
+        .output()
+        .map_err(|e| anyhow!("Failed to start git rev-parse process: {}", e))?;
+
+    if !output.status.success() {
+        return Err(anyhow!("unknown revision {revision:?}"));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn git_command(git_binary: &Path, working_directory: &Path) -> Command {
+    let mut child = Command::new(git_binary);
+    child
+        .current_dir(working_directory)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        child.creation_flags(windows::Win32::System::Threading::CREATE_NO_WINDOW.0);
+    }
+
+    child
+}
+
+// parse_git_log parses the output of `git log` with the `GIT_LOG_FORMAT` above,
+// where every commit is terminated by a record separator and has these fields,
+// separated by unit separators:
+//
+//     <sha> <parent shas> <author name> <author email> <author time> <decorations> <summary>
+//
+// With `--decorate=full`, the decorations look like this:
+//
+//     HEAD -> refs/heads/main, tag: refs/tags/v1.0, refs/remotes/origin/main
+fn parse_git_log(output: &str) -> Result<Vec<Commit>> {
+    output
+        .split_terminator(RECORD_SEPARATOR)
+        .map(|record| record.trim_start_matches('\n'))
+        .filter(|record| !record.is_empty())
+        .map(|record| {
+            let mut fields = record.split(FIELD_SEPARATOR);
+            let mut next_field = |name: &str| {
+                fields
+                    .next()
+                    .with_context(|| format!("missing {name} in git log entry {record:?}"))
+            };
+
+            let sha = next_field("sha")?.parse::<Oid>()?;
+            let parents = next_field("parents")?
+                .split_whitespace()
+                .map(|parent| parent.parse::<Oid>())
+                .collect::<Result<Vec<_>>>()?;
+            let author_name = next_field("author name")?.to_string();
+            let author_email = next_field("author email")?.to_string();
+            let timestamp = next_field("author time")?
+                .parse::<i64>()
+                .context("failed to parse author time")?;
+            let refs = parse_decorations(next_field("decorations")?);
+            let summary = next_field("summary")?.to_string();
+
+            Ok(Commit {
+                sha,
+                parents,
+                author_name,
+                author_email,
+                timestamp,
+                summary,
+                refs,
+            })
+        })
+        .collect()
+}
+
+fn parse_decorations(decorations: &str) -> Vec<CommitRef> {
+    decorations
+        .split(", ")
+        .flat_map(|decoration| decoration.split(" -> "))
+        .filter_map(|decoration| {
+            let decoration = decoration.trim();
+            let (kind, name) = if decoration == "HEAD" {
+                (CommitRefKind::Head, decoration)
+            } else if let Some(name) = decoration.strip_prefix("tag: refs/tags/") {
+                (CommitRefKind::Tag, name)
+            } else if let Some(name) = decoration.strip_prefix("refs/heads/") {
+                (CommitRefKind::LocalBranch, name)
+            } else if let Some(name) = decoration.strip_prefix("refs/remotes/") {
+                // Remote HEADs only repeat the remote's default branch.
+                if name.ends_with("/HEAD") {
+                    return None;
+                }
+                (CommitRefKind::RemoteBranch, name)
+            } else {
+                return None;
+            };
+            Some(CommitRef {
+                kind,
+                name: name.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_git_log() {
+        let output = [
+            "1111111111111111111111111111111111111111\x1f2222222222222222222222222222222222222222\x1fJoe Schmoe\x1fjoe@example.com\x1f1709741400\x1fHEAD -> refs/heads/main, tag: refs/tags/v1.0, refs/remotes/origin/main, refs/remotes/origin/HEAD\x1fFix the thing\x1e\n",
+            "2222222222222222222222222222222222222222\x1f\x1fJane Doe\x1fjane@example.com\x1f1709740000\x1f\x1fInitial commit\x1e\n",
+        ]
+        .concat();
+
+        let commits = parse_git_log(&output).unwrap();
+        assert_eq!(commits.len(), 2);
+
+        assert_eq!(
+            commits[0].sha,
+            "1111111111111111111111111111111111111111"
+                .parse::<Oid>()
+                .unwrap()
+        );
+        assert_eq!(
+            commits[0].parents,
+            vec!["2222222222222222222222222222222222222222"
+                .parse::<Oid>()
+                .unwrap()]
+        );
+        assert_eq!(commits[0].author_name, "Joe Schmoe");
+        assert_eq!(commits[0].author_email, "joe@example.com");
+        assert_eq!(commits[0].timestamp, 1709741400);
+        assert_eq!(commits[0].summary, "Fix the thing");
+        assert_eq!(
+            commits[0].refs,
+            vec![
+                CommitRef {
+                    kind: CommitRefKind::Head,
+                    name: "HEAD".into()
+                },
+                CommitRef {
+                    kind: CommitRefKind::LocalBranch,
+                    name: "main".into()
+                },
+                CommitRef {
+                    kind: CommitRefKind::Tag,
+                    name: "v1.0".into()
+                },
+                CommitRef {
+                    kind: CommitRefKind::RemoteBranch,
+                    name: "origin/main".into()
+                },
+            ]
+        );
+
+        assert!(commits[1].parents.is_empty());
+        assert!(commits[1].refs.is_empty());
+        assert_eq!(commits[1].summary, "Initial commit");
+    }
+
+    #[test]
+    fn test_resolve_revision_rejects_options() {
+        let working_directory = Path::new("/nonexistent");
+        for revision in ["--output=/tmp/log", "-p", ""] {
+            assert!(resolve_revision(Path::new("git"), working_directory, revision).is_err());
+        }
+        assert!(resolve_range(Path::new("git"), working_directory, "main..--output=x").is_err());
+    }
+}
@@ -1,7 +1,8 @@
-use crate::GitHostingProviderRegistry;
+use crate::log::{Commit, CommitDetails, CommitFileChange};
 use crate::{blame::Blame, status::GitStatus};
-use anyhow::{Context, Result};
-use collections::HashMap;
+use crate::{GitHostingProviderRegistry, Oid};
+use anyhow::{anyhow, Context, Result};
+use collections::{BTreeSet, HashMap, HashSet};
 use git2::BranchType;
 use parking_lot::Mutex;
 use rope::Rope;
@@ -9,6 +10,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
     path::{Component, Path, PathBuf},
+    process::{Command, Stdio},
     sync::Arc,
 };
 use sum_tree::MapSeekTarget;
@@ -20,6 +22,40 @@ pub struct Branch {
     pub name: Box<str>,
     /// Timestamp of most recent commit, normalized to Unix Epoch format.
     pub unix_timestamp: Option<i64>,
+    pub upstream: Option<Upstream>,
+}
+
+/// The remote-tracking branch a local branch is configured to follow.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Upstream {
+    /// The short name of the remote-tracking branch, e.g. `origin/main`.
+    pub name: Box<str>,
+    /// The number of commits on the local branch that are not on the upstream.
+    pub ahead: usize,
+    /// The number of commits on the upstream that are not on the local branch.
+    pub behind: usize,
+}
+
+/// The revision that buffers are diffed against.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "revision")]
+pub enum GitDiffBase {
+    /// The staged contents of files.
+    #[default]
+    Index,
+    /// The contents of files at the current commit.
+    Head,
+    /// The contents of files at a branch, tag or commit.
+    Revision(String),
+    /// The contents of files at the point where the current branch forked from the given one.
+    MergeBase(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StashEntry {
+    /// The position of the entry in the stash, where `0` is the most recent one.
+    pub index: usize,
+    pub message: String,
 }
 
 pub trait GitRepository: Send + Sync {
@@ -29,6 +65,18 @@ pub trait GitRepository: Send + Sync {
     /// Note that for symlink entries, this will return the contents of the symlink, not the target.
     fn load_index_text(&self, relative_file_path: &Path) -> Option<String>;
 
+    /// Loads a file's contents at the given revision.
+    /// Files that don't exist at an existing revision are loaded as empty.
+    fn load_revision_text(&self, revision: &str, relative_file_path: &Path) -> Option<String>;
+
+    /// Loads the contents a file is diffed against for the given base.
+    fn load_diff_base_text(&self, base: &GitDiffBase, relative_file_path: &Path) -> Option<String> {
+        match self.diff_base_revision(base).log_err()? {
+            Some(revision) => self.load_revision_text(&revision, relative_file_path),
+            None => self.load_index_text(relative_file_path),
+        }
+    }
+
     /// Returns the URL of the remote with the given name.
     fn remote_url(&self, name: &str) -> Option<String>;
     fn branch_name(&self) -> Option<String>;
@@ -45,8 +93,64 @@ pub trait GitRepository: Send + Sync {
     fn branches(&self) -> Result<Vec<Branch>>;
     fn change_branch(&self, _: &str) -> Result<()>;
     fn create_branch(&self, _: &str) -> Result<()>;
+    /// Returns whether all commits of a local branch are reachable from HEAD.
+    fn is_branch_merged(&self, name: &str) -> Result<bool>;
+    /// Deletes a local branch, refusing to delete one that isn't merged into HEAD unless `force` is set.
+    fn delete_branch(&self, name: &str, force: bool) -> Result<()>;
+    fn rename_branch(&self, old_name: &str, new_name: &str) -> Result<()>;
+
+    /// Returns the upstream of the current branch, if it has one.
+    fn upstream(&self) -> Option<Upstream>;
+
+    /// Fetches from a configured remote, or from the repository at the given URL or path.
+    fn fetch(&self, remote: &str) -> Result<()>;
+
+    /// Moves the current branch forward to the given revision, failing if the histories diverged.
+    fn merge_fast_forward(&self, revision: &str) -> Result<()>;
+
+    fn stash_push(&self, message: Option<&str>) -> Result<()>;
+    fn stash_pop(&self, index: usize) -> Result<()>;
+    fn stash_list(&self) -> Result<Vec<StashEntry>>;
+
+    /// Returns the best common ancestor of two revisions.
+    fn merge_base(&self, revision_a: &str, revision_b: &str) -> Result<Oid>;
+
+    /// Resolves a diff base to the revision it refers to, or `None` for the index.
+    fn diff_base_revision(&self, base: &GitDiffBase) -> Result<Option<String>> {
+        Ok(match base {
+            GitDiffBase::Index => None,
+            GitDiffBase::Head => Some("HEAD".to_string()),
+            GitDiffBase::Revision(revision) => Some(revision.clone()),
+            GitDiffBase::MergeBase(revision) => {
+                Some(self.merge_base("HEAD", revision)?.to_string())
+            }
+        })
+    }
+
+    /// Lists the paths whose contents in the working copy differ from the given revision.
+    fn changed_paths_since_revision(&self, revision: &str) -> Result<Vec<RepoPath>>;
+
+    /// Lists the paths whose contents in the working copy differ from the given diff base.
+    fn changed_paths(&self, base: &GitDiffBase) -> Result<Vec<RepoPath>> {
+        match self.diff_base_revision(base)? {
+            Some(revision) => self.changed_paths_since_revision(&revision),
+            None => Ok(self
+                .statuses(Path::new(""))?
+                .entries
+                .iter()
+                .map(|(path, _)| path.clone())
+                .collect()),
+        }
+    }
 
     fn blame(&self, path: &Path, content: Rope) -> Result<crate::blame::Blame>;
+
+    /// Lists at most `limit` commits of the given revision range (or `HEAD`), newest first.
+    /// When a path is given, only the commits that changed it are listed.
+    fn log(&self, path: Option<&Path>, range: Option<&str>, limit: usize) -> Result<Vec<Commit>>;
+
+    /// Loads the message of a commit and the contents of the files it changed.
+    fn commit_details(&self, sha: Oid) -> Result<CommitDetails>;
 }
 
 impl std::fmt::Debug for dyn GitRepository {
@@ -75,6 +179,61 @@ impl RealGitRepository {
     }
 }
 
+impl RealGitRepository {
+    fn run_git_command(&self, args: &[&str]) -> Result<String> {
+        let working_directory = self
+            .repository
+            .lock()
+            .workdir()
+            .context("failed to read git work directory")?
+            .to_path_buf();
+
+        let mut child = Command::new(&self.git_binary_path);
+        child
+            .current_dir(working_directory)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            child.creation_flags(windows::Win32::System::Threading::CREATE_NO_WINDOW.0);
+        }
+
+        let output = child
+            .output()
+            .map_err(|e| anyhow!("Failed to start git {} process: {}", args[0], e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("git {} process failed: {}", args[0], stderr));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+fn branch_upstream(repo: &git2::Repository, branch: &git2::Branch) -> Option<Upstream> {
+    let upstream = branch.upstream().ok()?;
+    let name = upstream.name().ok().flatten().map(Box::from)?;
+    let (ahead, behind) = repo
+        .graph_ahead_behind(branch.get().target()?, upstream.get().target()?)
+        .log_err()?;
+    Some(Upstream {
+        name,
+        ahead,
+        behind,
+    })
+}
+
+/// Rejects remotes that git would parse as options.
+fn validate_remote_name(remote: &str) -> Result<()> {
+    if remote.is_empty() || remote.starts_with('-') {
+        anyhow::bail!("invalid remote {remote:?}");
+    }
+    Ok(())
+}
+
 // https://git-scm.com/book/en/v2/Git-Internals-Git-Objects
 const GIT_MODE_SYMLINK: u32 = 0o120000;
 
@@ -109,6 +268,37 @@ impl GitRepository for RealGitRepository {
         None
     }
 
+    fn load_revision_text(&self, revision: &str, relative_file_path: &Path) -> Option<String> {
+        fn logic(
+            repo: &git2::Repository,
+            revision: &str,
+            relative_file_path: &Path,
+        ) -> Result<Option<String>> {
+            let tree = repo.revparse_single(revision)?.peel_to_tree()?;
+            check_path_to_repo_path_errors(relative_file_path)?;
+
+            let entry = match tree.get_path(relative_file_path) {
+                Ok(entry) => entry,
+                Err(error) if error.code() == git2::ErrorCode::NotFound => {
+                    return Ok(Some(String::new()))
+                }
+                Err(error) => return Err(error.into()),
+            };
+            if entry.filemode() as u32 == GIT_MODE_SYMLINK {
+                return Ok(None);
+            }
+
+            let content = repo.find_blob(entry.id())?.content().to_owned();
+            Ok(Some(String::from_utf8(content)?))
+        }
+
+        match logic(&self.repository.lock(), revision, relative_file_path) {
+            Ok(value) => return value,
+            Err(err) => log::error!("Error loading text at revision {revision}: {:?}", err),
+        }
+        None
+    }
+
     fn remote_url(&self, name: &str) -> Option<String> {
         let repo = self.repository.lock();
         let remote = repo.find_remote(name).ok()?;
@@ -144,6 +334,7 @@ impl GitRepository for RealGitRepository {
                 branch.ok().and_then(|(branch, _)| {
                     let is_head = branch.is_head();
                     let name = branch.name().ok().flatten().map(Box::from)?;
+                    let upstream = branch_upstream(&repo, &branch);
                     let timestamp = branch.get().peel_to_commit().ok()?.time();
                     let unix_timestamp = timestamp.seconds();
                     let timezone_offset = timestamp.offset_minutes();
@@ -155,6 +346,7 @@ impl GitRepository for RealGitRepository {
                         is_head,
                         name,
                         unix_timestamp: Some(unix_timestamp.to_offset(utc_offset).unix_timestamp()),
+                        upstream,
                     })
                 })
             })
@@ -183,6 +375,123 @@ impl GitRepository for RealGitRepository {
         Ok(())
     }
 
+    fn is_branch_merged(&self, name: &str) -> Result<bool> {
+        let repo = self.repository.lock();
+        let branch = repo.find_branch(name, BranchType::Local)?;
+        let branch_oid = branch.get().peel_to_commit()?.id();
+        let head_oid = repo.head()?.peel_to_commit()?.id();
+        Ok(branch_oid == head_oid || repo.graph_descendant_of(head_oid, branch_oid)?)
+    }
+
+    fn delete_branch(&self, name: &str, force: bool) -> Result<()> {
+        if !force && !self.is_branch_merged(name)? {
+            anyhow::bail!("the branch {name:?} is not fully merged");
+        }
+        let repo = self.repository.lock();
+        let mut branch = repo.find_branch(name, BranchType::Local)?;
+        if branch.is_head() {
+            anyhow::bail!("cannot delete the current branch {name:?}");
+        }
+        branch.delete()?;
+        Ok(())
+    }
+
+    fn rename_branch(&self, old_name: &str, new_name: &str) -> Result<()> {
+        let repo = self.repository.lock();
+        let mut branch = repo.find_branch(old_name, BranchType::Local)?;
+        branch.rename(new_name, false)?;
+        Ok(())
+    }
+
+    fn upstream(&self) -> Option<Upstream> {
+        let repo = self.repository.lock();
+        let head = repo.head().ok()?;
+        if !head.is_branch() {
+            return None;
+        }
+        branch_upstream(&repo, &git2::Branch::wrap(head))
+    }
+
+    fn fetch(&self, remote: &str) -> Result<()> {
+        validate_remote_name(remote)?;
+        self.run_git_command(&["fetch", "--", remote])?;
+        Ok(())
+    }
+
+    fn merge_fast_forward(&self, revision: &str) -> Result<()> {
+        let repo = self.repository.lock();
+        let target = repo.revparse_single(revision)?.peel_to_commit()?;
+        let annotated_target = repo.find_annotated_commit(target.id())?;
+        let (analysis, _) = repo.merge_analysis(&[&annotated_target])?;
+        if analysis.is_up_to_date() {
+            return Ok(());
+        }
+        if !analysis.is_fast_forward() {
+            anyhow::bail!("cannot fast-forward to {revision:?}, the branches have diverged");
+        }
+
+        // Update the working copy before moving the branch, so that local changes
+        // conflicting with the new commits are detected against the current HEAD.
+        repo.checkout_tree(
+            target.as_object(),
+            Some(git2::build::CheckoutBuilder::new().safe()),
+        )?;
+        let mut head = repo.head()?;
+        head.set_target(target.id(), &format!("merge {revision}: Fast-forward"))?;
+        Ok(())
+    }
+
+    fn stash_push(&self, message: Option<&str>) -> Result<()> {
+        let mut repo = self.repository.lock();
+        let signature = repo.signature()?;
+        repo.stash_save2(&signature, message, None)?;
+        Ok(())
+    }
+
+    fn stash_pop(&self, index: usize) -> Result<()> {
+        let mut repo = self.repository.lock();
+        repo.stash_pop(index, None)?;
+        Ok(())
+    }
+
+    fn merge_base(&self, revision_a: &str, revision_b: &str) -> Result<Oid> {
+        let repo = self.repository.lock();
+        let commit_a = repo.revparse_single(revision_a)?.peel_to_commit()?;
+        let commit_b = repo.revparse_single(revision_b)?.peel_to_commit()?;
+        Ok(Oid(repo.merge_base(commit_a.id(), commit_b.id())?))
+    }
+
+    fn changed_paths_since_revision(&self, revision: &str) -> Result<Vec<RepoPath>> {
+        let repo = self.repository.lock();
+        let tree = repo.revparse_single(revision)?.peel_to_tree()?;
+        let mut options = git2::DiffOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+        let diff = repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut options))?;
+        Ok(diff
+            .deltas()
+            .filter_map(|delta| {
+                delta
+                    .new_file()
+                    .path()
+                    .or_else(|| delta.old_file().path())
+                    .map(RepoPath::from)
+            })
+            .collect())
+    }
+
+    fn stash_list(&self) -> Result<Vec<StashEntry>> {
+        let mut repo = self.repository.lock();
+        let mut entries = Vec::new();
+        repo.stash_foreach(|index, message, _| {
+            entries.push(StashEntry {
+                index,
+                message: message.to_string(),
+            });
+            true
+        })?;
+        Ok(entries)
+    }
+
     fn blame(&self, path: &Path, content: Rope) -> Result<crate::blame::Blame> {
         let working_directory = self
             .repository
@@ -203,6 +512,73 @@ impl GitRepository for RealGitRepository {
             self.hosting_provider_registry.clone(),
         )
     }
+
+    fn log(&self, path: Option<&Path>, range: Option<&str>, limit: usize) -> Result<Vec<Commit>> {
+        let working_directory = self
+            .repository
+            .lock()
+            .workdir()
+            .context("failed to read git work directory")?
+            .to_path_buf();
+        crate::log::log(
+            &self.git_binary_path,
+            &working_directory,
+            path,
+            range,
+            limit,
+        )
+    }
+
+    fn commit_details(&self, sha: Oid) -> Result<CommitDetails> {
+        let repo = self.repository.lock();
+        let commit = repo.find_commit(sha.0)?;
+        let tree = commit.tree()?;
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+
+        let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+        diff.find_similar(None)?;
+
+        let load_blob = |id: git2::Oid| -> Result<Option<String>> {
+            if id.is_zero() {
+                return Ok(None);
+            }
+            let blob = repo.find_blob(id)?;
+            // Binary files can't be displayed as text, so they are listed without contents.
+            Ok(String::from_utf8(blob.content().to_owned()).ok())
+        };
+        let mut files = Vec::new();
+        for delta in diff.deltas() {
+            let new_path = delta.new_file().path().map(RepoPath::from);
+            let old_path = delta.old_file().path().map(RepoPath::from);
+            let Some(path) = new_path.clone().or_else(|| old_path.clone()) else {
+                continue;
+            };
+            files.push(CommitFileChange {
+                old_path: old_path.filter(|old_path| Some(old_path) != new_path.as_ref()),
+                path,
+                old_text: load_blob(delta.old_file().id())?,
+                new_text: load_blob(delta.new_file().id())?,
+            });
+        }
+
+        let author = commit.author();
+        Ok(CommitDetails {
+            commit: Commit {
+                sha,
+                parents: commit.parent_ids().map(Oid).collect(),
+                author_name: author.name().unwrap_or_default().to_string(),
+                author_email: author.email().unwrap_or_default().to_string(),
+                timestamp: author.when().seconds(),
+                summary: commit.summary().unwrap_or_default().to_string(),
+                refs: Vec::new(),
+            },
+            message: commit.message().unwrap_or_default().to_string(),
+            files,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -216,6 +592,22 @@ pub struct FakeGitRepositoryState {
     pub blames: HashMap<PathBuf, Blame>,
     pub worktree_statuses: HashMap<RepoPath, GitFileStatus>,
     pub branch_name: Option<String>,
+    /// The local branches other than the current one.
+    pub branches: BTreeSet<String>,
+    /// The local branches with commits that aren't reachable from HEAD.
+    pub unmerged_branches: HashSet<String>,
+    /// The upstreams of local branches, keyed by branch name.
+    pub upstreams: HashMap<String, Upstream>,
+    /// The remotes that can be fetched from.
+    pub remotes: HashSet<String>,
+    /// The stash, most recent entry first, holding the statuses it took from the working copy.
+    pub stashes: Vec<(String, HashMap<RepoPath, GitFileStatus>)>,
+    /// The contents of files at revisions, keyed by revision.
+    pub revision_contents: HashMap<String, HashMap<PathBuf, String>>,
+    /// The merge bases of HEAD with other revisions, keyed by revision.
+    pub merge_bases: HashMap<String, Oid>,
+    /// The commits of the repository, newest first.
+    pub commits: Vec<CommitDetails>,
 }
 
 impl FakeGitRepository {
@@ -232,6 +624,12 @@ impl GitRepository for FakeGitRepository {
         state.index_contents.get(path).cloned()
     }
 
+    fn load_revision_text(&self, revision: &str, path: &Path) -> Option<String> {
+        let state = self.state.lock();
+        let contents = state.revision_contents.get(revision)?;
+        Some(contents.get(path).cloned().unwrap_or_default())
+    }
+
     fn remote_url(&self, _name: &str) -> Option<String> {
         None
     }
@@ -265,21 +663,188 @@ impl GitRepository for FakeGitRepository {
     }
 
     fn branches(&self) -> Result<Vec<Branch>> {
-        Ok(vec![])
+        let state = self.state.lock();
+        Ok(state
+            .branch_name
+            .iter()
+            .chain(state.branches.iter())
+            .map(|name| Branch {
+                is_head: state.branch_name.as_ref() == Some(name),
+                name: name.as_str().into(),
+                unix_timestamp: None,
+                upstream: state.upstreams.get(name).cloned(),
+            })
+            .collect())
     }
 
     fn change_branch(&self, name: &str) -> Result<()> {
         let mut state = self.state.lock();
-        state.branch_name = Some(name.to_owned());
+        state.branches.remove(name);
+        if let Some(previous_branch) = state.branch_name.replace(name.to_owned()) {
+            state.branches.insert(previous_branch);
+        }
         Ok(())
     }
 
     fn create_branch(&self, name: &str) -> Result<()> {
         let mut state = self.state.lock();
-        state.branch_name = Some(name.to_owned());
+        if let Some(previous_branch) = state.branch_name.replace(name.to_owned()) {
+            state.branches.insert(previous_branch);
+        }
+        Ok(())
+    }
+
+    fn is_branch_merged(&self, name: &str) -> Result<bool> {
+        let state = self.state.lock();
+        if state.branch_name.as_deref() != Some(name) && !state.branches.contains(name) {
+            anyhow::bail!("no branch named {name:?}");
+        }
+        Ok(!state.unmerged_branches.contains(name))
+    }
+
+    fn delete_branch(&self, name: &str, force: bool) -> Result<()> {
+        if !force && !self.is_branch_merged(name)? {
+            anyhow::bail!("the branch {name:?} is not fully merged");
+        }
+        let mut state = self.state.lock();
+        if state.branch_name.as_deref() == Some(name) {
+            anyhow::bail!("cannot delete the current branch {name:?}");
+        }
+        if !state.branches.remove(name) {
+            anyhow::bail!("no branch named {name:?}");
+        }
+        state.upstreams.remove(name);
+        state.unmerged_branches.remove(name);
         Ok(())
     }
 
+    fn rename_branch(&self, old_name: &str, new_name: &str) -> Result<()> {
+        let mut state = self.state.lock();
+        if state.branch_name.as_deref() == Some(new_name) || state.branches.contains(new_name) {
+            anyhow::bail!("a branch named {new_name:?} already exists");
+        }
+        if state.branch_name.as_deref() == Some(old_name) {
+            state.branch_name = Some(new_name.to_owned());
+        } else if state.branches.remove(old_name) {
+            state.branches.insert(new_name.to_owned());
+        } else {
+            anyhow::bail!("no branch named {old_name:?}");
+        }
+        if let Some(upstream) = state.upstreams.remove(old_name) {
+            state.upstreams.insert(new_name.to_owned(), upstream);
+        }
+        Ok(())
+    }
+
+    fn upstream(&self) -> Option<Upstream> {
+        let state = self.state.lock();
+        state.upstreams.get(state.branch_name.as_ref()?).cloned()
+    }
+
+    fn fetch(&self, remote: &str) -> Result<()> {
+        validate_remote_name(remote)?;
+        let state = self.state.lock();
+        if !state.remotes.contains(remote) {
+            anyhow::bail!("{remote:?} does not appear to be a git repository");
+        }
+        Ok(())
+    }
+
+    fn merge_fast_forward(&self, revision: &str) -> Result<()> {
+        let mut state = self.state.lock();
+        let branch_name = state.branch_name.clone().context("HEAD is detached")?;
+        if state.branches.contains(revision) {
+            return Ok(());
+        }
+        let upstream = state
+            .upstreams
+            .get_mut(&branch_name)
+            .filter(|upstream| upstream.name.as_ref() == revision)
+            .with_context(|| format!("unknown revision {revision:?}"))?;
+        if upstream.ahead > 0 && upstream.behind > 0 {
+            anyhow::bail!("cannot fast-forward to {revision:?}, the branches have diverged");
+        }
+        upstream.behind = 0;
+        Ok(())
+    }
+
+    fn stash_push(&self, message: Option<&str>) -> Result<()> {
+        let mut state = self.state.lock();
+        if state.worktree_statuses.is_empty() {
+            anyhow::bail!("no local changes to save");
+        }
+        let message = match message {
+            Some(message) => message.to_owned(),
+            None => format!(
+                "WIP on {}",
+                state.branch_name.as_deref().unwrap_or("(no branch)")
+            ),
+        };
+        let statuses = std::mem::take(&mut state.worktree_statuses);
+        state.stashes.insert(0, (message, statuses));
+        Ok(())
+    }
+
+    fn stash_pop(&self, index: usize) -> Result<()> {
+        let mut state = self.state.lock();
+        if index >= state.stashes.len() {
+            anyhow::bail!("no stash entry at index {index}");
+        }
+        let (_, statuses) = state.stashes.remove(index);
+        state.worktree_statuses.extend(statuses);
+        Ok(())
+    }
+
+    fn merge_base(&self, revision_a: &str, revision_b: &str) -> Result<Oid> {
+        let state = self.state.lock();
+        if revision_a != "HEAD" {
+            anyhow::bail!("merge bases are only known for HEAD, not {revision_a:?}");
+        }
+        state
+            .merge_bases
+            .get(revision_b)
+            .copied()
+            .with_context(|| format!("no merge base between HEAD and {revision_b:?}"))
+    }
+
+    // The index contents stand in for the working copy, along with the paths that have a status.
+    fn changed_paths_since_revision(&self, revision: &str) -> Result<Vec<RepoPath>> {
+        let state = self.state.lock();
+        let contents = state
+            .revision_contents
+            .get(revision)
+            .with_context(|| format!("unknown revision {revision:?}"))?;
+        let mut paths = contents
+            .iter()
+            .filter(|(path, text)| state.index_contents.get(*path) != Some(text))
+            .map(|(path, _)| RepoPath::from(path.as_path()))
+            .chain(
+                state
+                    .index_contents
+                    .keys()
+                    .filter(|path| !contents.contains_key(*path))
+                    .map(|path| RepoPath::from(path.as_path())),
+            )
+            .chain(state.worktree_statuses.keys().cloned())
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths.dedup();
+        Ok(paths)
+    }
+
+    fn stash_list(&self) -> Result<Vec<StashEntry>> {
+        let state = self.state.lock();
+        Ok(state
+            .stashes
+            .iter()
+            .enumerate()
+            .map(|(index, (message, _))| StashEntry {
+                index,
+                message: message.clone(),
+            })
+            .collect())
+    }
+
     fn blame(&self, path: &Path, _content: Rope) -> Result<crate::blame::Blame> {
         let state = self.state.lock();
         state
@@ -288,6 +853,51 @@ impl GitRepository for FakeGitRepository {
             .with_context(|| format!("failed to get blame for {:?}", path))
             .cloned()
     }
+
+    fn log(&self, path: Option<&Path>, range: Option<&str>, limit: usize) -> Result<Vec<Commit>> {
+        let state = self.state.lock();
+        let is_revision = |details: &CommitDetails, revision: &str| {
+            details.commit.sha.to_string().starts_with(revision)
+                || details
+                    .commit
+                    .refs
+                    .iter()
+                    .any(|commit_ref| commit_ref.name == revision)
+        };
+        let (excluded, included) = match range.and_then(|range| range.split_once("..")) {
+            Some((excluded, included)) => (Some(excluded), Some(included)),
+            None => (None, range),
+        };
+        let start = match included {
+            Some(revision) => state
+                .commits
+                .iter()
+                .position(|details| is_revision(details, revision))
+                .with_context(|| format!("unknown revision {revision:?}"))?,
+            None => 0,
+        };
+        Ok(state.commits[start..]
+            .iter()
+            .take_while(|details| excluded.map_or(true, |excluded| !is_revision(details, excluded)))
+            .filter(|details| {
+                path.map_or(true, |path| {
+                    details.files.iter().any(|file| file.path.starts_with(path))
+                })
+            })
+            .take(limit)
+            .map(|details| details.commit.clone())
+            .collect())
+    }
+
+    fn commit_details(&self, sha: Oid) -> Result<CommitDetails> {
+        let state = self.state.lock();
+        state
+            .commits
+            .iter()
+            .find(|details| details.commit.sha == sha)
+            .with_context(|| format!("failed to get commit {sha}"))
+            .cloned()
+    }
 }
 
 fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
@@ -0,0 +1,328 @@
+use std::{
+    any::{Any, TypeId},
+    time::Duration,
+};
+
+use collections::HashMap;
+use editor::{actions::ExpandAllHunkDiffs, Editor, EditorEvent, DEFAULT_MULTIBUFFER_CONTEXT};
+use git::repository::GitDiffBase;
+use gpui::{
+    AnyElement, AnyView, AppContext, EventEmitter, FocusHandle, FocusableView, Model, Render,
+    Subscription, Task, View, WeakView,
+};
+use language::{Buffer, Capability};
+use multi_buffer::MultiBuffer;
+use project::{Project, ProjectPath};
+use text::{BufferId, ToPoint as _};
+use ui::prelude::*;
+use util::ResultExt;
+use workspace::{
+    item::{Item, ItemEvent, TabContentParams},
+    Workspace,
+};
+
+use crate::{default_branch, ReviewBranch, ShowChanges};
+
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Lists every changed hunk in the project, compared with the project's diff base.
+pub struct ChangesView {
+    project: Model<Project>,
+    workspace: WeakView<Workspace>,
+    editor: View<Editor>,
+    multibuffer: Model<MultiBuffer>,
+    base: Option<GitDiffBase>,
+    /// The buffers of the changed files, whose hunks are listed again when their diff base changes.
+    buffer_subscriptions: HashMap<BufferId, Subscription>,
+    is_loading: bool,
+    _refresh_task: Task<Option<()>>,
+    _subscription: Subscription,
+}
+
+impl ChangesView {
+    pub fn new(
+        project: Model<Project>,
+        workspace: WeakView<Workspace>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let multibuffer = cx.new_model(|_| MultiBuffer::new(0, Capability::ReadWrite));
+        let editor = cx.new_view(|cx| {
+            Editor::for_multibuffer(multibuffer.clone(), Some(project.clone()), true, cx)
+        });
+        cx.subscribe(&editor, |_, _, event: &EditorEvent, cx| {
+            cx.emit(event.clone());
+        })
+        .detach();
+        let subscription = cx.subscribe(&project, |this, _, event, cx| match event {
+            project::Event::WorktreeUpdatedGitRepositories | project::Event::GitDiffBaseChanged => {
+                this.schedule_refresh(cx);
+            }
+            _ => {}
+        });
+
+        let mut this = Self {
+            project,
+            workspace,
+            editor,
+            multibuffer,
+            base: None,
+            buffer_subscriptions: HashMap::default(),
+            is_loading: false,
+            _refresh_task: Task::ready(None),
+            _subscription: subscription,
+        };
+        this.schedule_refresh(cx);
+        this
+    }
+
+    pub fn deploy(workspace: &mut Workspace, _: &ShowChanges, cx: &mut ViewContext<Workspace>) {
+        Self::open(workspace, cx);
+    }
+
+    /// Compares the project with the point where the current branch forked from the default branch,
+    /// and lists every change made since.
+    pub fn review_branch(
+        workspace: &mut Workspace,
+        _: &ReviewBranch,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let project = workspace.project().clone();
+        let default_branch = default_branch(&project, cx);
+        project.update(cx, |project, cx| {
+            project.set_git_diff_base(GitDiffBase::MergeBase(default_branch), cx)
+        });
+        Self::open(workspace, cx);
+    }
+
+    fn open(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+        if let Some(existing) = workspace.item_of_type::<Self>(cx) {
+            workspace.activate_item(&existing, true, true, cx);
+        } else {
+            let project = workspace.project().clone();
+            let workspace_handle = cx.view().downgrade();
+            let view = cx.new_view(|cx| Self::new(project, workspace_handle, cx));
+            workspace.add_item_to_active_pane(Box::new(view), None, true, cx);
+        }
+    }
+
+    fn schedule_refresh(&mut self, cx: &mut ViewContext<Self>) {
+        self.is_loading = true;
+        self._refresh_task = cx.spawn(|this, mut cx| async move {
+            cx.background_executor().timer(REFRESH_DEBOUNCE).await;
+            let paths = this
+                .update(&mut cx, |this, cx| {
+                    this.project.read(cx).git_changed_paths(cx)
+                })
+                .ok()?;
+            let mut paths = paths.await.log_err()?;
+            paths.sort();
+
+            let mut buffers = Vec::new();
+            for path in paths {
+                let buffer = this
+                    .update(&mut cx, |this, cx| {
+                        this.project
+                            .update(cx, |project, cx| project.open_buffer(path, cx))
+                    })
+                    .ok()?;
+                // Deleted files can't be opened, and have no hunks to show.
+                if let Ok(buffer) = buffer.await {
+                    buffers.push(buffer);
+                }
+            }
+
+            this.update(&mut cx, |this, cx| this.set_buffers(buffers, cx))
+                .ok()
+        });
+        cx.notify();
+    }
+
+    fn set_buffers(&mut self, buffers: Vec<Model<Buffer>>, cx: &mut ViewContext<Self>) {
+        self.base = self.project.read(cx).git_diff_base();
+        self.is_loading = false;
+
+        let mut buffer_subscriptions = HashMap::default();
+        for buffer in &buffers {
+            let buffer_id = buffer.read(cx).remote_id();
+            let subscription = self
+                .buffer_subscriptions
+                .remove(&buffer_id)
+                .unwrap_or_else(|| {
+                    cx.subscribe(buffer, |this, _, event, cx| {
+                        if let language::Event::DiffBaseChanged = event {
+                            this.schedule_refresh(cx);
+                        }
+                    })
+                });
+            buffer_subscriptions.insert(buffer_id, subscription);
+        }
+        self.buffer_subscriptions = buffer_subscriptions;
+
+        self.multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.clear(cx);
+            for buffer in buffers {
+                let snapshot = buffer.read(cx).snapshot();
+                let hunk_ranges = snapshot
+                    .git_diff_hunks_in_row_range(0..snapshot.max_point().row + 1)
+                    .map(|hunk| {
+                        hunk.buffer_range.start.to_point(&snapshot)
+                            ..hunk.buffer_range.end.to_point(&snapshot)
+                    })
+                    .collect::<Vec<_>>();
+                if !hunk_ranges.is_empty() {
+                    multibuffer.push_excerpts_with_context_lines(
+                        buffer,
+                        hunk_ranges,
+                        DEFAULT_MULTIBUFFER_CONTEXT,
+                        cx,
+                    );
+                }
+            }
+        });
+        self.editor.update(cx, |editor, cx| {
+            editor.expand_all_hunk_diffs(&ExpandAllHunkDiffs, cx)
+        });
+        cx.notify();
+    }
+
+    fn base_description(&self) -> String {
+        match &self.base {
+            None | Some(GitDiffBase::Index) => "Unstaged changes".to_string(),
+            Some(GitDiffBase::Head) => "Changes since HEAD".to_string(),
+            Some(GitDiffBase::Revision(revision)) => format!("Changes since {revision}"),
+            Some(GitDiffBase::MergeBase(revision)) => {
+                format!("Changes since merge base with {revision}")
+            }
+        }
+    }
+}
+
+impl Render for ChangesView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        if self.multibuffer.read(cx).excerpt_ids().is_empty() {
+            let message = if self.is_loading {
+                "Loading changes…"
+            } else {
+                "No changes"
+            };
+            h_flex()
+                .size_full()
+                .justify_center()
+                .bg(cx.theme().colors().editor_background)
+                .child(Label::new(message).color(Color::Muted))
+                .into_any_element()
+        } else {
+            div()
+                .size_full()
+                .bg(cx.theme().colors().editor_background)
+                .child(self.editor.clone())
+                .into_any_element()
+        }
+    }
+}
+
+impl EventEmitter<EditorEvent> for ChangesView {}
+
+impl FocusableView for ChangesView {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Item for ChangesView {
+    type Event = EditorEvent;
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn deactivated(&mut self, cx: &mut ViewContext<Self>) {
+        self.editor.update(cx, |editor, cx| editor.deactivated(cx));
+    }
+
+    fn navigate(&mut self, data: Box<dyn Any>, cx: &mut ViewContext<Self>) -> bool {
+        self.editor
+            .update(cx, |editor, cx| editor.navigate(data, cx))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _: &WindowContext) -> AnyElement {
+        Label::new("Changes")
+            .color(params.text_color())
+            .into_any_element()
+    }
+
+    fn tab_tooltip_text(&self, _: &AppContext) -> Option<SharedString> {
+        Some(self.base_description().into())
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("git changes")
+    }
+
+    fn is_singleton(&self, _: &AppContext) -> bool {
+        false
+    }
+
+    fn is_dirty(&self, cx: &AppContext) -> bool {
+        self.multibuffer.read(cx).is_dirty(cx)
+    }
+
+    fn has_conflict(&self, cx: &AppContext) -> bool {
+        self.multibuffer.read(cx).has_conflict(cx)
+    }
+
+    fn can_save(&self, _: &AppContext) -> bool {
+        true
+    }
+
+    fn save(
+        &mut self,
+        format: bool,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.editor
+            .update(cx, |editor, cx| editor.save(format, project, cx))
+    }
+
+    fn save_as(
+        &mut self,
+        _: Model<Project>,
+        _: ProjectPath,
+        _: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        unreachable!()
+    }
+
+    fn reload(
+        &mut self,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.editor
+            .update(cx, |editor, cx| editor.reload(project, cx))
+    }
+
+    fn clone_on_split(
+        &self,
+        _: Option<workspace::WorkspaceId>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Self>> {
+        Some(cx.new_view(|cx| Self::new(self.project.clone(), self.workspace.clone(), cx)))
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a View<Self>,
+        _: &'a AppContext,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+}
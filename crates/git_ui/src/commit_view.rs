@@ -0,0 +1,364 @@
+use std::{
+    any::{Any, TypeId},
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use editor::{actions::ExpandAllHunkDiffs, Editor, EditorEvent, DEFAULT_MULTIBUFFER_CONTEXT};
+use git::{
+    log::{CommitDetails, CommitFileChange},
+    Oid,
+};
+use gpui::{
+    AnyElement, AnyView, AppContext, EventEmitter, FocusHandle, FocusableView, Model, Render, Task,
+    View, WeakView,
+};
+use language::{Buffer, Capability, LanguageRegistry, LocalFile};
+use multi_buffer::MultiBuffer;
+use project::{Project, ProjectPath};
+use text::{Buffer as TextBuffer, ToPoint as _};
+use time_format::TimestampFormat;
+use ui::prelude::*;
+use util::ResultExt;
+use workspace::{
+    item::{Item, ItemEvent, TabContentParams},
+    Workspace,
+};
+
+use crate::log_view::commit_timestamp;
+
+/// Shows the message of a commit, along with the hunks of every file it changed.
+pub struct CommitView {
+    sha: Oid,
+    /// A path within the repository the commit belongs to.
+    repository_path: ProjectPath,
+    details: Option<CommitDetails>,
+    editor: View<Editor>,
+    multibuffer: Model<MultiBuffer>,
+    project: Model<Project>,
+    workspace: WeakView<Workspace>,
+    _load_task: Task<Option<()>>,
+}
+
+impl CommitView {
+    pub fn new(
+        sha: Oid,
+        repository_path: ProjectPath,
+        project: Model<Project>,
+        workspace: WeakView<Workspace>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let multibuffer = cx.new_model(|_| {
+            MultiBuffer::new(0, Capability::ReadOnly).with_title(sha.display_short())
+        });
+        let editor = cx.new_view(|cx| {
+            Editor::for_multibuffer(multibuffer.clone(), Some(project.clone()), false, cx)
+        });
+        cx.subscribe(&editor, |_, _, event: &EditorEvent, cx| {
+            cx.emit(event.clone());
+        })
+        .detach();
+
+        let details = project
+            .read(cx)
+            .git_commit_details(repository_path.clone(), sha, cx);
+        let languages = project.read(cx).languages().clone();
+        let worktree_id = repository_path.worktree_id.to_usize();
+        let load_task = cx.spawn(|this, mut cx| async move {
+            let details = details.await.log_err()?;
+            for file in &details.files {
+                let Some(buffer) =
+                    build_commit_buffer(file, worktree_id, languages.clone(), &mut cx).await
+                else {
+                    continue;
+                };
+                this.update(&mut cx, |this, cx| {
+                    let snapshot = buffer.read(cx).snapshot();
+                    let hunk_ranges = snapshot
+                        .git_diff_hunks_in_row_range(0..snapshot.max_point().row + 1)
+                        .map(|hunk| {
+                            hunk.buffer_range.start.to_point(&snapshot)
+                                ..hunk.buffer_range.end.to_point(&snapshot)
+                        })
+                        .collect::<Vec<_>>();
+                    this.multibuffer.update(cx, |multibuffer, cx| {
+                        multibuffer.push_excerpts_with_context_lines(
+                            buffer,
+                            hunk_ranges,
+                            DEFAULT_MULTIBUFFER_CONTEXT,
+                            cx,
+                        );
+                    });
+                })
+                .ok()?;
+            }
+            this.update(&mut cx, |this, cx| {
+                this.details = Some(details);
+                this.editor.update(cx, |editor, cx| {
+                    editor.expand_all_hunk_diffs(&ExpandAllHunkDiffs, cx)
+                });
+                cx.notify();
+            })
+            .ok()
+        });
+
+        Self {
+            sha,
+            repository_path,
+            details: None,
+            editor,
+            multibuffer,
+            project,
+            workspace,
+            _load_task: load_task,
+        }
+    }
+
+    pub fn open(
+        sha: Oid,
+        repository_path: ProjectPath,
+        workspace: &mut Workspace,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let existing = workspace
+            .items_of_type::<Self>(cx)
+            .find(|view| view.read(cx).sha == sha);
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, cx);
+        } else {
+            let project = workspace.project().clone();
+            let workspace_handle = cx.view().downgrade();
+            let view =
+                cx.new_view(|cx| Self::new(sha, repository_path, project, workspace_handle, cx));
+            workspace.add_item_to_active_pane(Box::new(view), None, true, cx);
+        }
+    }
+
+    fn render_header(&self, details: &CommitDetails) -> impl IntoElement {
+        let body = details
+            .message
+            .split_once('\n')
+            .map(|(_, body)| body.trim())
+            .filter(|body| !body.is_empty());
+        v_flex()
+            .p_2()
+            .gap_1()
+            .child(Label::new(details.commit.summary.clone()).size(LabelSize::Large))
+            .children(body.map(|body| Label::new(body.to_string()).color(Color::Muted)))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Label::new(format!(
+                            "{} <{}>",
+                            details.commit.author_name, details.commit.author_email
+                        ))
+                        .size(LabelSize::Small),
+                    )
+                    .child(
+                        Label::new(commit_timestamp(
+                            &details.commit,
+                            TimestampFormat::MediumAbsolute,
+                        ))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                    )
+                    .child(
+                        Label::new(self.sha.to_string())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+    }
+}
+
+/// Creates a read-only buffer with the contents of a file after the commit,
+/// whose diff base is the file's contents before it.
+async fn build_commit_buffer(
+    file: &CommitFileChange,
+    worktree_id: usize,
+    languages: Arc<LanguageRegistry>,
+    cx: &mut gpui::AsyncAppContext,
+) -> Option<Model<Buffer>> {
+    // Binary files have no contents to show.
+    if file.old_text.is_none() && file.new_text.is_none() {
+        return None;
+    }
+
+    let language = languages.language_for_file_path(&file.path).await.ok();
+    let commit_file = Arc::new(CommitFile {
+        path: file.path.0.as_path().into(),
+        worktree_id,
+        is_deleted: file.new_text.is_none(),
+    });
+    let new_text = file.new_text.clone().unwrap_or_default();
+    let old_text = file.old_text.clone().unwrap_or_default();
+    let buffer = cx
+        .new_model(|cx| {
+            let mut buffer = Buffer::build(
+                TextBuffer::new(0, cx.entity_id().as_non_zero_u64().into(), new_text),
+                Some(old_text),
+                Some(commit_file),
+                Capability::ReadOnly,
+            );
+            buffer.set_language_registry(languages);
+            buffer.set_language(language, cx);
+            buffer
+        })
+        .ok()?;
+    let diff = buffer
+        .update(cx, |buffer, cx| buffer.git_diff_recalc(cx))
+        .ok()?;
+    if let Some(diff) = diff {
+        diff.await;
+    }
+    Some(buffer)
+}
+
+/// The file of a buffer showing the contents of a path at a commit.
+struct CommitFile {
+    /// The path relative to the root of the repository.
+    path: Arc<Path>,
+    worktree_id: usize,
+    is_deleted: bool,
+}
+
+impl language::File for CommitFile {
+    fn as_local(&self) -> Option<&dyn LocalFile> {
+        None
+    }
+
+    fn mtime(&self) -> Option<SystemTime> {
+        None
+    }
+
+    fn path(&self) -> &Arc<Path> {
+        &self.path
+    }
+
+    fn full_path(&self, _: &AppContext) -> PathBuf {
+        self.path.to_path_buf()
+    }
+
+    fn file_name<'a>(&'a self, _: &'a AppContext) -> &'a OsStr {
+        self.path.file_name().unwrap_or_default()
+    }
+
+    fn worktree_id(&self) -> usize {
+        self.worktree_id
+    }
+
+    fn is_deleted(&self) -> bool {
+        self.is_deleted
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn to_proto(&self, _: &AppContext) -> rpc::proto::File {
+        rpc::proto::File {
+            worktree_id: self.worktree_id as u64,
+            entry_id: None,
+            path: self.path.to_string_lossy().into(),
+            mtime: None,
+            is_deleted: self.is_deleted,
+        }
+    }
+
+    fn is_private(&self) -> bool {
+        false
+    }
+}
+
+impl Render for CommitView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .children(
+                self.details
+                    .as_ref()
+                    .map(|details| self.render_header(details)),
+            )
+            .child(self.editor.clone())
+    }
+}
+
+impl EventEmitter<EditorEvent> for CommitView {}
+
+impl FocusableView for CommitView {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Item for CommitView {
+    type Event = EditorEvent;
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn deactivated(&mut self, cx: &mut ViewContext<Self>) {
+        self.editor.update(cx, |editor, cx| editor.deactivated(cx));
+    }
+
+    fn navigate(&mut self, data: Box<dyn Any>, cx: &mut ViewContext<Self>) -> bool {
+        self.editor
+            .update(cx, |editor, cx| editor.navigate(data, cx))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _: &WindowContext) -> AnyElement {
+        Label::new(format!("Commit {}", self.sha.display_short()))
+            .color(params.text_color())
+            .into_any_element()
+    }
+
+    fn tab_tooltip_text(&self, _: &AppContext) -> Option<SharedString> {
+        self.details
+            .as_ref()
+            .map(|details| details.commit.summary.clone().into())
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("commit view")
+    }
+
+    fn is_singleton(&self, _: &AppContext) -> bool {
+        false
+    }
+
+    fn clone_on_split(
+        &self,
+        _: Option<workspace::WorkspaceId>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Self>> {
+        Some(cx.new_view(|cx| {
+            Self::new(
+                self.sha,
+                self.repository_path.clone(),
+                self.project.clone(),
+                self.workspace.clone(),
+                cx,
+            )
+        }))
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a View<Self>,
+        _: &'a AppContext,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use std::sync::Arc;
+
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use git::repository::GitDiffBase;
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model, Render, Task, View,
+    WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use project::Project;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+use crate::{default_branch, SelectDiffBase};
+
+/// A modal for choosing the revision that buffers are diffed against.
+pub struct DiffBasePicker {
+    picker: View<Picker<DiffBasePickerDelegate>>,
+}
+
+impl DiffBasePicker {
+    pub fn toggle(workspace: &mut Workspace, _: &SelectDiffBase, cx: &mut ViewContext<Workspace>) {
+        let project = workspace.project().clone();
+        let branches = project
+            .read(cx)
+            .get_first_worktree_root_repo(cx)
+            .and_then(|repo| repo.branches().log_err())
+            .unwrap_or_default();
+        let default_branch = default_branch(&project, cx);
+
+        let mut options = vec![
+            (GitDiffBase::Index, "Index (staged changes)".to_string()),
+            (GitDiffBase::Head, "HEAD (last commit)".to_string()),
+            (
+                GitDiffBase::MergeBase(default_branch.clone()),
+                format!("Merge base with {default_branch}"),
+            ),
+        ];
+        options.extend(
+            branches
+                .iter()
+                .filter(|branch| !branch.is_head)
+                .map(|branch| {
+                    (
+                        GitDiffBase::Revision(branch.name.to_string()),
+                        format!("Branch {}", branch.name),
+                    )
+                }),
+        );
+
+        workspace.toggle_modal(cx, move |cx| {
+            let delegate = DiffBasePickerDelegate::new(cx.view().downgrade(), project, options, cx);
+            let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+            Self { picker }
+        });
+    }
+}
+
+impl Render for DiffBasePicker {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for DiffBasePicker {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for DiffBasePicker {}
+impl ModalView for DiffBasePicker {}
+
+pub struct DiffBasePickerDelegate {
+    diff_base_picker: WeakView<DiffBasePicker>,
+    project: Model<Project>,
+    current_base: Option<GitDiffBase>,
+    options: Vec<(GitDiffBase, String)>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    /// The query, which can be confirmed as a revision when it isn't empty.
+    query: String,
+}
+
+impl DiffBasePickerDelegate {
+    fn new(
+        diff_base_picker: WeakView<DiffBasePicker>,
+        project: Model<Project>,
+        options: Vec<(GitDiffBase, String)>,
+        cx: &AppContext,
+    ) -> Self {
+        let current_base = project.read(cx).git_diff_base();
+        Self {
+            diff_base_picker,
+            project,
+            current_base,
+            options,
+            matches: Vec::new(),
+            selected_index: 0,
+            query: String::new(),
+        }
+    }
+
+    fn selected_base(&self) -> Option<GitDiffBase> {
+        match self.matches.get(self.selected_index) {
+            Some(mat) => Some(self.options[mat.candidate_id].0.clone()),
+            None if !self.query.is_empty() => Some(GitDiffBase::Revision(self.query.clone())),
+            None => None,
+        }
+    }
+}
+
+impl PickerDelegate for DiffBasePickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Compare with a branch or commit...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len() + !self.query.is_empty() as usize
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .options
+            .iter()
+            .enumerate()
+            .map(|(id, (_, label))| StringMatchCandidate::new(id, label.clone()))
+            .collect::<Vec<_>>();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.query = query;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.match_count().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(base) = self.selected_base() {
+            self.project
+                .update(cx, |project, cx| project.set_git_diff_base(base, cx));
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.diff_base_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let (label, positions, is_current) = match self.matches.get(ix) {
+            Some(mat) => (
+                mat.string.clone(),
+                mat.positions.clone(),
+                self.current_base.as_ref() == Some(&self.options[mat.candidate_id].0),
+            ),
+            None => (
+                format!("Revision {}", self.query),
+                Vec::new(),
+                self.current_base == Some(GitDiffBase::Revision(self.query.clone())),
+            ),
+        };
+        let label = if is_current {
+            format!("{label} (current)")
+        } else {
+            label
+        };
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(HighlightedLabel::new(label, positions)),
+        )
+    }
+}
@@ -0,0 +1,50 @@
+mod changes_view;
+mod commit_view;
+mod diff_base_picker;
+mod log_view;
+
+use gpui::{actions, AppContext, Model};
+use project::Project;
+use workspace::Workspace;
+
+pub use changes_view::ChangesView;
+pub use commit_view::CommitView;
+pub use diff_base_picker::DiffBasePicker;
+pub use log_view::GitLogView;
+
+actions!(
+    git,
+    [
+        ShowLog,
+        ShowFileHistory,
+        ShowChanges,
+        SelectDiffBase,
+        ReviewBranch
+    ]
+);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(GitLogView::deploy);
+        workspace.register_action(GitLogView::deploy_file_history);
+        workspace.register_action(ChangesView::deploy);
+        workspace.register_action(ChangesView::review_branch);
+        workspace.register_action(DiffBasePicker::toggle);
+    })
+    .detach();
+}
+
+/// The branch that other branches are reviewed against: `main`, or `master` in repositories without one.
+fn default_branch(project: &Model<Project>, cx: &AppContext) -> String {
+    const DEFAULT_BRANCHES: [&str; 2] = ["main", "master"];
+    let branches = project
+        .read(cx)
+        .get_first_worktree_root_repo(cx)
+        .and_then(|repo| repo.branches().ok())
+        .unwrap_or_default();
+    DEFAULT_BRANCHES
+        .iter()
+        .find(|name| branches.iter().any(|branch| branch.name.as_ref() == **name))
+        .unwrap_or(&DEFAULT_BRANCHES[0])
+        .to_string()
+}
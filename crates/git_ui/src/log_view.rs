@@ -0,0 +1,388 @@
+use std::{path::Path, time::Duration};
+
+use git::log::{Commit, CommitRef, CommitRefKind};
+use gpui::{
+    uniform_list, AnyElement, AppContext, EventEmitter, FocusHandle, FocusableView, Model, Render,
+    Subscription, Task, UniformListScrollHandle, View, WeakView,
+};
+use project::{Project, ProjectPath};
+use time::{OffsetDateTime, UtcOffset};
+use time_format::TimestampFormat;
+use ui::{prelude::*, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{
+    item::{Item, ItemEvent, TabContentParams},
+    Workspace,
+};
+
+use crate::{CommitView, ShowFileHistory, ShowLog};
+
+/// The number of commits loaded at once, and added whenever more are requested.
+const LOG_PAGE_SIZE: usize = 500;
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Lists the commits of a repository, or of a single path within it.
+pub struct GitLogView {
+    project: Model<Project>,
+    workspace: WeakView<Workspace>,
+    /// The path whose history is listed. The root of a repository lists all of its commits.
+    project_path: ProjectPath,
+    commits: Vec<Commit>,
+    limit: usize,
+    selected_index: usize,
+    error: Option<SharedString>,
+    scroll_handle: UniformListScrollHandle,
+    focus_handle: FocusHandle,
+    is_loading: bool,
+    _load_task: Option<Task<Option<()>>>,
+    _reload_task: Task<Option<()>>,
+    _subscription: Subscription,
+}
+
+impl GitLogView {
+    pub fn new(
+        project_path: ProjectPath,
+        project: Model<Project>,
+        workspace: WeakView<Workspace>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let subscription = cx.subscribe(&project, |this, _, event, cx| {
+            if let project::Event::WorktreeUpdatedGitRepositories = event {
+                this.schedule_reload(cx);
+            }
+        });
+        let mut this = Self {
+            project,
+            workspace,
+            project_path,
+            commits: Vec::new(),
+            limit: LOG_PAGE_SIZE,
+            selected_index: 0,
+            error: None,
+            scroll_handle: UniformListScrollHandle::new(),
+            focus_handle: cx.focus_handle(),
+            is_loading: false,
+            _load_task: None,
+            _reload_task: Task::ready(None),
+            _subscription: subscription,
+        };
+        this.load(cx);
+        this
+    }
+
+    /// Opens the log of the repository containing the active item, or of the first worktree.
+    pub fn deploy(workspace: &mut Workspace, _: &ShowLog, cx: &mut ViewContext<Workspace>) {
+        let worktree_id = workspace
+            .active_item(cx)
+            .and_then(|item| item.project_path(cx))
+            .map(|project_path| project_path.worktree_id)
+            .or_else(|| {
+                workspace
+                    .project()
+                    .read(cx)
+                    .visible_worktrees(cx)
+                    .next()
+                    .map(|worktree| worktree.read(cx).id())
+            });
+        if let Some(worktree_id) = worktree_id {
+            Self::open(
+                ProjectPath {
+                    worktree_id,
+                    path: Path::new("").into(),
+                },
+                workspace,
+                cx,
+            );
+        }
+    }
+
+    /// Opens the history of the file in the active item.
+    pub fn deploy_file_history(
+        workspace: &mut Workspace,
+        _: &ShowFileHistory,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        if let Some(project_path) = workspace
+            .active_item(cx)
+            .and_then(|item| item.project_path(cx))
+        {
+            Self::open(project_path, workspace, cx);
+        }
+    }
+
+    fn open(project_path: ProjectPath, workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+        let existing = workspace
+            .items_of_type::<Self>(cx)
+            .find(|view| view.read(cx).project_path == project_path);
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, cx);
+        } else {
+            let project = workspace.project().clone();
+            let workspace_handle = cx.view().downgrade();
+            let view = cx.new_view(|cx| Self::new(project_path, project, workspace_handle, cx));
+            workspace.add_item_to_active_pane(Box::new(view), None, true, cx);
+        }
+    }
+
+    fn is_file_history(&self) -> bool {
+        !self.project_path.path.as_os_str().is_empty()
+    }
+
+    fn load(&mut self, cx: &mut ViewContext<Self>) {
+        let commits =
+            self.project
+                .read(cx)
+                .git_log(self.project_path.clone(), None, self.limit, cx);
+        self.is_loading = true;
+        self._load_task = Some(cx.spawn(|this, mut cx| async move {
+            let commits = commits.await;
+            this.update(&mut cx, |this, cx| {
+                match commits {
+                    Ok(commits) => {
+                        this.commits = commits;
+                        this.error = None;
+                    }
+                    Err(error) => {
+                        this.commits.clear();
+                        this.error = Some(error.to_string().into());
+                    }
+                }
+                this.selected_index = this
+                    .selected_index
+                    .min(this.commits.len().saturating_sub(1));
+                this.is_loading = false;
+                cx.notify();
+            })
+            .log_err()
+        }));
+        cx.notify();
+    }
+
+    /// Reloads the log once the repositories stop changing, as a single operation can update
+    /// them many times.
+    fn schedule_reload(&mut self, cx: &mut ViewContext<Self>) {
+        self._reload_task = cx.spawn(|this, mut cx| async move {
+            cx.background_executor().timer(RELOAD_DEBOUNCE).await;
+            this.update(&mut cx, |this, cx| this.load(cx)).ok()
+        });
+    }
+
+    fn has_more_commits(&self) -> bool {
+        self.commits.len() == self.limit
+    }
+
+    fn load_more(&mut self, cx: &mut ViewContext<Self>) {
+        self.limit += LOG_PAGE_SIZE;
+        self.load(cx);
+    }
+
+    fn select_index(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        if self.commits.is_empty() {
+            return;
+        }
+        self.selected_index = ix.min(self.commits.len() - 1);
+        self.scroll_handle.scroll_to_item(self.selected_index);
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, cx: &mut ViewContext<Self>) {
+        self.select_index(self.selected_index + 1, cx);
+    }
+
+    fn select_prev(&mut self, _: &menu::SelectPrev, cx: &mut ViewContext<Self>) {
+        self.select_index(self.selected_index.saturating_sub(1), cx);
+    }
+
+    fn select_first(&mut self, _: &menu::SelectFirst, cx: &mut ViewContext<Self>) {
+        self.select_index(0, cx);
+    }
+
+    fn select_last(&mut self, _: &menu::SelectLast, cx: &mut ViewContext<Self>) {
+        self.select_index(usize::MAX, cx);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        self.open_commit(self.selected_index, cx);
+    }
+
+    fn open_commit(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(commit) = self.commits.get(ix) else {
+            return;
+        };
+        let sha = commit.sha;
+        let project_path = self.project_path.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                CommitView::open(sha, project_path, workspace, cx);
+            })
+            .ok();
+    }
+
+    fn render_commit(&self, ix: usize, cx: &mut ViewContext<Self>) -> AnyElement {
+        let Some(commit) = self.commits.get(ix) else {
+            return ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .child(Label::new("Load more commits").color(Color::Accent))
+                .on_click(cx.listener(|this, _, cx| this.load_more(cx)))
+                .into_any_element();
+        };
+
+        ListItem::new(ix)
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .selected(ix == self.selected_index)
+            .start_slot(Label::new(commit.sha.display_short()).color(Color::Muted))
+            .child(
+                h_flex()
+                    .gap_1()
+                    .overflow_hidden()
+                    .children(commit.refs.iter().map(render_commit_ref))
+                    .child(Label::new(commit.summary.clone()).single_line()),
+            )
+            .end_slot(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Label::new(commit.author_name.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        Label::new(commit_timestamp(commit, TimestampFormat::Relative))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .on_click(cx.listener(move |this, _, cx| {
+                this.selected_index = ix;
+                this.open_commit(ix, cx);
+            }))
+            .into_any_element()
+    }
+}
+
+fn render_commit_ref(commit_ref: &CommitRef) -> impl IntoElement {
+    let color = match commit_ref.kind {
+        CommitRefKind::Head => Color::Accent,
+        CommitRefKind::LocalBranch => Color::Success,
+        CommitRefKind::RemoteBranch => Color::Info,
+        CommitRefKind::Tag => Color::Warning,
+    };
+    div().px_1().rounded_md().border_1().child(
+        Label::new(commit_ref.name.clone())
+            .size(LabelSize::XSmall)
+            .color(color),
+    )
+}
+
+pub(crate) fn commit_timestamp(commit: &Commit, format: TimestampFormat) -> String {
+    match OffsetDateTime::from_unix_timestamp(commit.timestamp) {
+        Ok(timestamp) => {
+            let local = chrono::Local::now().offset().local_minus_utc();
+            time_format::format_localized_timestamp(
+                timestamp,
+                OffsetDateTime::now_utc(),
+                UtcOffset::from_whole_seconds(local).unwrap(),
+                format,
+            )
+        }
+        Err(_) => "Error parsing date".to_string(),
+    }
+}
+
+impl Render for GitLogView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let content = if let Some(error) = self.error.clone() {
+            h_flex()
+                .size_full()
+                .justify_center()
+                .child(Label::new(error).color(Color::Error))
+                .into_any_element()
+        } else if self.commits.is_empty() {
+            let message = if self.is_loading {
+                "Loading commits…"
+            } else {
+                "No commits"
+            };
+            h_flex()
+                .size_full()
+                .justify_center()
+                .child(Label::new(message).color(Color::Muted))
+                .into_any_element()
+        } else {
+            let item_count = self.commits.len() + self.has_more_commits() as usize;
+            uniform_list(
+                cx.view().clone(),
+                "GitLogView",
+                item_count,
+                |this, range, cx| range.map(|ix| this.render_commit(ix, cx)).collect(),
+            )
+            .size_full()
+            .track_scroll(self.scroll_handle.clone())
+            .into_any_element()
+        };
+
+        v_flex()
+            .key_context("GitLogView")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::confirm))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(content)
+    }
+}
+
+impl EventEmitter<()> for GitLogView {}
+
+impl FocusableView for GitLogView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for GitLogView {
+    type Event = ();
+
+    fn to_item_events(_: &Self::Event, _: impl FnMut(ItemEvent)) {}
+
+    fn tab_content(&self, params: TabContentParams, _: &WindowContext) -> AnyElement {
+        let title = match self.project_path.path.file_name() {
+            Some(file_name) if self.is_file_history() => {
+                format!("History: {}", file_name.to_string_lossy())
+            }
+            _ => "Git Log".to_string(),
+        };
+        Label::new(title)
+            .color(params.text_color())
+            .into_any_element()
+    }
+
+    fn tab_tooltip_text(&self, _: &AppContext) -> Option<SharedString> {
+        self.is_file_history()
+            .then(|| self.project_path.path.to_string_lossy().to_string().into())
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("git log")
+    }
+
+    fn clone_on_split(
+        &self,
+        _: Option<workspace::WorkspaceId>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Self>> {
+        Some(cx.new_view(|cx| {
+            Self::new(
+                self.project_path.clone(),
+                self.project.clone(),
+                self.workspace.clone(),
+                cx,
+            )
+        }))
+    }
+}
@@ -13,6 +13,7 @@ use anyhow::{anyhow, Result};
 use derive_more::{Deref, DerefMut};
 use futures::{channel::oneshot, future::LocalBoxFuture, Future};
 use slotmap::SlotMap;
+use smallvec::SmallVec;
 use smol::future::FutureExt;
 
 pub use async_context::*;
@@ -27,11 +28,11 @@ use util::ResultExt;
 use crate::{
     current_platform, init_app_menus, Action, ActionRegistry, Any, AnyView, AnyWindowHandle,
     AssetCache, AssetSource, BackgroundExecutor, ClipboardItem, Context, DispatchPhase, DisplayId,
-    Entity, EventEmitter, ForegroundExecutor, Global, KeyBinding, Keymap, Keystroke, LayoutId,
-    Menu, MenuItem, OwnedMenu, PathPromptOptions, Pixels, Platform, PlatformDisplay, Point,
-    PromptBuilder, PromptHandle, PromptLevel, Render, RenderablePromptHandle, Reservation,
-    SharedString, SubscriberSet, Subscription, SvgRenderer, Task, TextSystem, View, ViewContext,
-    Window, WindowAppearance, WindowContext, WindowHandle, WindowId,
+    Entity, EventEmitter, ForegroundExecutor, Global, ImageData, KeyBinding, Keymap, Keystroke,
+    LayoutId, Menu, MenuItem, OwnedMenu, PathPromptOptions, Pixels, Platform, PlatformDisplay,
+    Point, PromptBuilder, PromptHandle, PromptLevel, Render, RenderablePromptHandle, Reservation,
+    SharedString, SubscriberSet, Subscription, SvgRenderer, SvgSize, Task, TextSystem, View,
+    ViewContext, Window, WindowAppearance, WindowContext, WindowHandle, WindowId,
 };
 
 mod async_context;
@@ -681,6 +682,19 @@ impl AppContext {
         self.svg_renderer.clone()
     }
 
+    /// Renders an SVG document into image data that can be displayed with [`img`](crate::img).
+    pub fn render_svg_image(&self, bytes: &[u8], scale_factor: f32) -> Result<Arc<ImageData>> {
+        let pixmap = self
+            .svg_renderer
+            .render_pixmap(bytes, SvgSize::ScaleFactor(scale_factor))?;
+        let buffer = image::ImageBuffer::from_raw(pixmap.width(), pixmap.height(), pixmap.take())
+            .ok_or_else(|| anyhow!("invalid SVG pixmap"))?;
+        Ok(Arc::new(ImageData::new(SmallVec::from_elem(
+            image::Frame::new(buffer),
+            1,
+        ))))
+    }
+
     pub(crate) fn push_effect(&mut self, effect: Effect) {
         match &effect {
             Effect::Notify { emitter } => {
@@ -1063,6 +1077,11 @@ impl AppContext {
         self.pending_effects.push_back(Effect::Refresh);
     }
 
+    /// Returns the keymap containing every key binding in the app.
+    pub fn keymap(&self) -> Rc<RefCell<Keymap>> {
+        self.keymap.clone()
+    }
+
     /// Clear all key bindings in the app.
     pub fn clear_key_bindings(&mut self) {
         self.keymap.borrow_mut().clear();
@@ -50,8 +50,8 @@
 ///  KeyBinding::new("cmd-k left", pane::SplitLeft, Some("Pane"))
 ///
 use crate::{
-    Action, ActionRegistry, DispatchPhase, EntityId, FocusId, KeyBinding, KeyContext, Keymap,
-    Keystroke, ModifiersChangedEvent, WindowContext,
+    Action, ActionRegistry, DispatchPhase, EntityId, FocusId, KeyBinding, KeyBindingMatch,
+    KeyContext, Keymap, Keystroke, ModifiersChangedEvent, WindowContext,
 };
 use collections::FxHashMap;
 use smallvec::SmallVec;
@@ -427,6 +427,28 @@ impl DispatchTree {
             .bindings_for_input(&input, &context_stack)
     }
 
+    /// Explains how the given input would be resolved against the keymap along the given
+    /// dispatch path, without dispatching anything.
+    pub fn explain_input(
+        &self,
+        input: &[Keystroke],
+        dispatch_path: &SmallVec<[DispatchNodeId; 32]>,
+    ) -> (Vec<KeyContext>, Vec<(KeyBinding, KeyBindingMatch)>) {
+        let context_stack: Vec<KeyContext> = dispatch_path
+            .iter()
+            .filter_map(|node_id| self.node(*node_id).context.clone())
+            .collect();
+
+        let keymap = self.keymap.borrow();
+        let bindings = keymap.bindings().collect::<Vec<_>>();
+        let matches = keymap
+            .explain_input(input, &context_stack)
+            .into_iter()
+            .map(|binding_match| (bindings[binding_match.binding_index].clone(), binding_match))
+            .collect();
+        (context_stack, matches)
+    }
+
     /// dispatch_key processes the keystroke
     /// input should be set to the value of `pending` from the previous call to dispatch_key.
     /// This returns three instructions to the input handler:
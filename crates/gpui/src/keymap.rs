@@ -14,6 +14,45 @@ use std::any::{Any, TypeId};
 #[derive(Copy, Clone, Eq, PartialEq, Default)]
 pub struct KeymapVersion(usize);
 
+/// A set of bindings that share the same keystrokes and the same context predicate.
+/// Whenever the predicate matches, only the binding that was added last takes effect,
+/// so all of the earlier bindings are shadowed.
+#[derive(Debug)]
+pub struct KeyBindingConflict {
+    /// The keystrokes shared by all of the conflicting bindings.
+    pub keystrokes: SmallVec<[Keystroke; 2]>,
+    /// The context predicate shared by all of the conflicting bindings.
+    pub context_predicate: Option<KeyBindingContextPredicate>,
+    /// The indices of the conflicting bindings in the keymap, in the order they were added.
+    /// The last index is the binding that takes effect.
+    pub binding_indices: SmallVec<[usize; 2]>,
+}
+
+/// How a binding relates to some input, as reported by [`Keymap::explain_input`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum KeyBindingMatchStatus {
+    /// The binding matches the input and is a candidate for dispatch. Candidates are
+    /// attempted in order, and the first one with a registered action handler wins.
+    Candidate,
+    /// The binding's keystrokes start with the input, so more keystrokes are needed.
+    Pending,
+    /// The binding matches the input, but it is a `null` binding, or is preceded by one.
+    Disabled,
+    /// The binding's keystrokes match, but its context predicate doesn't match the context stack.
+    ContextMismatch,
+}
+
+/// A binding whose keystrokes match some input, along with why it will or won't be dispatched.
+#[derive(Debug)]
+pub struct KeyBindingMatch {
+    /// The index of the binding in the keymap.
+    pub binding_index: usize,
+    /// The depth in the context stack at which the binding's predicate matched, if any.
+    pub depth: Option<usize>,
+    /// Whether the binding will be dispatched.
+    pub status: KeyBindingMatchStatus,
+}
+
 /// A collection of key bindings for the user's application.
 #[derive(Default)]
 pub struct Keymap {
@@ -133,6 +172,105 @@ impl Keymap {
         return (bindings, is_pending.unwrap_or_default());
     }
 
+    /// Find all groups of bindings that are bound to the same keystrokes under the same
+    /// context predicate, where the bindings don't all perform the same action.
+    ///
+    /// Groups are returned in the order their first binding was added to the keymap.
+    pub fn conflicts(&self) -> Vec<KeyBindingConflict> {
+        let mut indices_by_trigger = HashMap::<
+            (&[Keystroke], Option<&KeyBindingContextPredicate>),
+            SmallVec<[usize; 2]>,
+        >::default();
+        for (ix, binding) in self.bindings.iter().enumerate() {
+            indices_by_trigger
+                .entry((binding.keystrokes(), binding.predicate()))
+                .or_default()
+                .push(ix);
+        }
+
+        let mut conflicts = indices_by_trigger
+            .into_iter()
+            .filter(|(_, indices)| {
+                let last = self.bindings[*indices.last().unwrap()].action();
+                indices
+                    .iter()
+                    .any(|ix| !self.bindings[*ix].action().partial_eq(last))
+            })
+            .map(
+                |((keystrokes, context_predicate), binding_indices)| KeyBindingConflict {
+                    keystrokes: keystrokes.iter().cloned().collect(),
+                    context_predicate: context_predicate.cloned(),
+                    binding_indices,
+                },
+            )
+            .collect::<Vec<_>>();
+        conflicts.sort_by_key(|conflict| conflict.binding_indices[0]);
+        conflicts
+    }
+
+    /// Explain how the given input is resolved against the keymap in the given context,
+    /// reporting every binding whose keystrokes match the input.
+    ///
+    /// Matches are ordered by precedence, following the same rules as [`Keymap::bindings_for_input`]:
+    /// complete matches come first, deepest context first, followed by the pending bindings and
+    /// finally the bindings whose context predicate didn't match.
+    pub fn explain_input(
+        &self,
+        input: &[Keystroke],
+        context_stack: &[KeyContext],
+    ) -> Vec<KeyBindingMatch> {
+        let mut complete = Vec::new();
+        let mut pending = Vec::new();
+        let mut mismatched = Vec::new();
+
+        for (binding_index, binding) in self.bindings.iter().enumerate().rev() {
+            let Some(is_pending) = binding.match_keystrokes(input) else {
+                continue;
+            };
+            let depth = (0..=context_stack.len())
+                .rev()
+                .find(|depth| self.binding_enabled(binding, &context_stack[0..*depth]));
+            let Some(depth) = depth else {
+                mismatched.push(KeyBindingMatch {
+                    binding_index,
+                    depth: None,
+                    status: KeyBindingMatchStatus::ContextMismatch,
+                });
+                continue;
+            };
+
+            if is_pending {
+                pending.push(KeyBindingMatch {
+                    binding_index,
+                    depth: Some(depth),
+                    status: KeyBindingMatchStatus::Pending,
+                });
+            } else {
+                complete.push(KeyBindingMatch {
+                    binding_index,
+                    depth: Some(depth),
+                    status: KeyBindingMatchStatus::Candidate,
+                });
+            }
+        }
+
+        complete.sort_by(|a, b| a.depth.cmp(&b.depth).reverse());
+        let mut disabled = false;
+        for binding_match in &mut complete {
+            let binding = &self.bindings[binding_match.binding_index];
+            if binding.action.as_any().type_id() == (NoAction {}).type_id() {
+                disabled = true;
+            }
+            if disabled {
+                binding_match.status = KeyBindingMatchStatus::Disabled;
+            }
+        }
+
+        complete.extend(pending);
+        complete.extend(mismatched);
+        complete
+    }
+
     /// Check if the given binding is enabled, given a certain key context.
     fn binding_enabled(&self, binding: &KeyBinding, context: &[KeyContext]) -> bool {
         // If binding has a context predicate, it must match the current context,
@@ -183,6 +321,88 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_keymap_conflicts() {
+        let bindings = [
+            KeyBinding::new("ctrl-a", ActionAlpha {}, Some("editor")),
+            KeyBinding::new("ctrl-b", ActionBeta {}, Some("editor")),
+            KeyBinding::new("ctrl-a", ActionGamma {}, Some("pane")),
+            KeyBinding::new("ctrl-a", ActionDelta {}, Some("editor")),
+            KeyBinding::new("ctrl-b", ActionBeta {}, Some("editor")),
+            KeyBinding::new("ctrl-k ctrl-a", ActionAlpha {}, None),
+            KeyBinding::new("ctrl-k ctrl-a", NoAction {}, None),
+        ];
+
+        let keymap = Keymap::new(bindings.to_vec());
+        let conflicts = keymap.conflicts();
+
+        // rebinding to the same action is not a conflict
+        assert_eq!(conflicts.len(), 2);
+
+        assert_eq!(
+            conflicts[0].keystrokes.as_slice(),
+            &[Keystroke::parse("ctrl-a").unwrap()]
+        );
+        assert_eq!(
+            conflicts[0].context_predicate,
+            Some(KeyBindingContextPredicate::parse("editor").unwrap())
+        );
+        assert_eq!(conflicts[0].binding_indices.as_slice(), &[0, 3]);
+
+        assert_eq!(conflicts[1].context_predicate, None);
+        assert_eq!(conflicts[1].binding_indices.as_slice(), &[5, 6]);
+    }
+
+    #[test]
+    fn test_keymap_explain_input() {
+        let bindings = [
+            KeyBinding::new("ctrl-a", ActionAlpha {}, Some("workspace")),
+            KeyBinding::new("ctrl-a", ActionBeta {}, Some("editor")),
+            KeyBinding::new("ctrl-a", ActionGamma {}, Some("terminal")),
+            KeyBinding::new("ctrl-a ctrl-b", ActionDelta {}, None),
+            KeyBinding::new("ctrl-a", NoAction {}, Some("editor && mode == auto_height")),
+        ];
+
+        let keymap = Keymap::new(bindings.to_vec());
+        let input = [Keystroke::parse("ctrl-a").unwrap()];
+
+        let summarize = |context_stack: &[KeyContext]| {
+            keymap
+                .explain_input(&input, context_stack)
+                .into_iter()
+                .map(|m| (m.binding_index, m.depth, m.status))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            summarize(&[
+                KeyContext::parse("workspace").unwrap(),
+                KeyContext::parse("editor mode=full").unwrap(),
+            ]),
+            vec![
+                (1, Some(2), KeyBindingMatchStatus::Candidate),
+                (0, Some(1), KeyBindingMatchStatus::Candidate),
+                (3, Some(2), KeyBindingMatchStatus::Pending),
+                (4, None, KeyBindingMatchStatus::ContextMismatch),
+                (2, None, KeyBindingMatchStatus::ContextMismatch),
+            ]
+        );
+
+        assert_eq!(
+            summarize(&[
+                KeyContext::parse("workspace").unwrap(),
+                KeyContext::parse("editor mode=auto_height").unwrap(),
+            ]),
+            vec![
+                (4, Some(2), KeyBindingMatchStatus::Disabled),
+                (1, Some(2), KeyBindingMatchStatus::Disabled),
+                (0, Some(1), KeyBindingMatchStatus::Disabled),
+                (3, Some(2), KeyBindingMatchStatus::Pending),
+                (2, None, KeyBindingMatchStatus::ContextMismatch),
+            ]
+        );
+    }
+
     #[test]
     fn test_keymap_disabled() {
         let bindings = [
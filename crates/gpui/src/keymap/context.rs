@@ -345,6 +345,57 @@ impl KeyBindingContextPredicate {
     }
 }
 
+impl fmt::Display for KeyBindingContextPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Identifier(name) => write!(f, "{name}"),
+            Self::Equal(left, right) => write!(f, "{left} == {right}"),
+            Self::NotEqual(left, right) => write!(f, "{left} != {right}"),
+            Self::Not(pred) => {
+                write!(f, "!")?;
+                pred.fmt_operand(f, PRECEDENCE_NOT)
+            }
+            Self::Child(parent, child) => {
+                parent.fmt_operand(f, PRECEDENCE_CHILD)?;
+                write!(f, " > ")?;
+                child.fmt_operand(f, PRECEDENCE_CHILD + 1)
+            }
+            Self::And(left, right) => {
+                left.fmt_operand(f, PRECEDENCE_AND)?;
+                write!(f, " && ")?;
+                right.fmt_operand(f, PRECEDENCE_AND + 1)
+            }
+            Self::Or(left, right) => {
+                left.fmt_operand(f, PRECEDENCE_OR)?;
+                write!(f, " || ")?;
+                right.fmt_operand(f, PRECEDENCE_OR + 1)
+            }
+        }
+    }
+}
+
+impl KeyBindingContextPredicate {
+    fn precedence(&self) -> u32 {
+        match self {
+            Self::Identifier(_) | Self::Not(_) => PRECEDENCE_NOT,
+            Self::Equal(_, _) | Self::NotEqual(_, _) => PRECEDENCE_EQ,
+            Self::Child(_, _) => PRECEDENCE_CHILD,
+            Self::And(_, _) => PRECEDENCE_AND,
+            Self::Or(_, _) => PRECEDENCE_OR,
+        }
+    }
+
+    /// Writes this predicate as the operand of an operator with the given precedence,
+    /// wrapping it in parentheses if it would otherwise bind less tightly.
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>, min_precedence: u32) -> fmt::Result {
+        if self.precedence() < min_precedence {
+            write!(f, "({self})")
+        } else {
+            write!(f, "{self}")
+        }
+    }
+}
+
 const PRECEDENCE_CHILD: u32 = 1;
 const PRECEDENCE_OR: u32 = 2;
 const PRECEDENCE_AND: u32 = 3;
@@ -511,6 +562,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_display_round_trip() {
+        for source in [
+            "Editor",
+            "Editor && mode == full",
+            "!(a || b)",
+            "a || !b && c",
+            "(a || b) && c",
+            "Workspace > Pane > (Editor || Terminal)",
+            "vim_mode != insert && !menu",
+            "a && (b == c || d != e)",
+        ] {
+            let predicate = KeyBindingContextPredicate::parse(source).unwrap();
+            let displayed = predicate.to_string();
+            assert_eq!(
+                KeyBindingContextPredicate::parse(&displayed).unwrap(),
+                predicate,
+                "{source:?} was displayed as {displayed:?}"
+            );
+        }
+
+        assert_eq!(
+            KeyBindingContextPredicate::parse("(a||b)&&c")
+                .unwrap()
+                .to_string(),
+            "(a || b) && c"
+        );
+    }
+
     #[test]
     fn test_parse_parenthesized_expressions() {
         assert_eq!(
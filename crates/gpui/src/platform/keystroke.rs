@@ -121,6 +121,29 @@ impl Keystroke {
         })
     }
 
+    /// Produces a representation of this key that can be parsed by [`Keystroke::parse`],
+    /// i.e. the syntax used by keymap files. The ime_key is not included.
+    pub fn unparse(&self) -> String {
+        let mut str = String::new();
+        if self.modifiers.control {
+            str.push_str("ctrl-");
+        }
+        if self.modifiers.alt {
+            str.push_str("alt-");
+        }
+        if self.modifiers.platform {
+            str.push_str("cmd-");
+        }
+        if self.modifiers.shift {
+            str.push_str("shift-");
+        }
+        if self.modifiers.function {
+            str.push_str("fn-");
+        }
+        str.push_str(&self.key);
+        str
+    }
+
     /// Returns true if this keystroke left
     /// the ime system in an incomplete state.
     pub fn is_ime_in_progress(&self) -> bool {
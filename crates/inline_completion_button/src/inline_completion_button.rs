@@ -21,7 +21,8 @@ use workspace::{
     item::ItemHandle,
     notifications::NotificationId,
     ui::{
-        ButtonCommon, Clickable, ContextMenu, IconButton, IconName, IconSize, PopoverMenu, Tooltip,
+        ButtonCommon, Clickable, Color, ContextMenu, IconButton, IconName, IconSize, PopoverMenu,
+        Tooltip,
     },
     StatusItemView, Toast, Workspace,
 };
@@ -196,6 +197,50 @@ impl Render for InlineCompletionButton {
                         ),
                 );
             }
+
+            InlineCompletionProvider::LanguageModel => {
+                let enabled = self.editor_enabled.unwrap_or_else(|| {
+                    all_language_settings.inline_completions_enabled(None, None)
+                });
+                let has_provider = all_language_settings
+                    .inline_completions
+                    .language_model
+                    .provider
+                    .is_some();
+                let this = cx.view().clone();
+
+                div().child(
+                    PopoverMenu::new("language-model-completions")
+                        .menu(move |cx| {
+                            Some(
+                                this.update(cx, |this, cx| {
+                                    this.build_language_model_context_menu(cx)
+                                }),
+                            )
+                        })
+                        .anchor(AnchorCorner::BottomRight)
+                        .trigger(
+                            IconButton::new(
+                                "language-model-completions-icon",
+                                IconName::ZedAssistant,
+                            )
+                            .icon_color(if !has_provider {
+                                Color::Warning
+                            } else if enabled {
+                                Color::Default
+                            } else {
+                                Color::Muted
+                            })
+                            .tooltip(move |cx| {
+                                if has_provider {
+                                    Tooltip::text("Language Model Completions", cx)
+                                } else {
+                                    Tooltip::text("No Language Model Chosen for Completions", cx)
+                                }
+                            }),
+                        ),
+                )
+            }
         }
     }
 }
@@ -327,6 +372,28 @@ impl InlineCompletionButton {
         })
     }
 
+    fn build_language_model_context_menu(&self, cx: &mut ViewContext<Self>) -> View<ContextMenu> {
+        let fs = self.fs.clone();
+        let has_provider = all_language_settings(None, cx)
+            .inline_completions
+            .language_model
+            .provider
+            .is_some();
+        ContextMenu::build(cx, |mut menu, cx| {
+            if !has_provider {
+                // Nothing is sent to a model until the user chooses one for completions.
+                menu = menu
+                    .header("Set a Provider in inline_completions.language_model")
+                    .separator();
+            }
+            self.build_language_settings_menu(menu, cx)
+                .separator()
+                .entry("Use Copilot", None, move |cx| {
+                    set_completion_provider(fs.clone(), cx, InlineCompletionProvider::Copilot)
+                })
+        })
+    }
+
     pub fn update_enabled(&mut self, editor: View<Editor>, cx: &mut ViewContext<Self>) {
         let editor = editor.read(cx);
         let snapshot = editor.buffer().read(cx).snapshot(cx);
@@ -534,6 +534,23 @@ pub struct Runnable {
     pub buffer: BufferId,
 }
 
+/// A range of text that can be folded, keeping its first line visible.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FoldingRange<T> {
+    /// Starts at the end of the first line and ends at the end of the last folded line.
+    pub range: Range<T>,
+    pub kind: Option<FoldingRangeKind>,
+}
+
+/// The kinds of folding ranges that can be folded all at once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FoldingRangeKind {
+    Comment,
+    Imports,
+    /// A block delimited by `region` and `endregion` markers.
+    Region,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct IndentGuide {
     pub buffer_id: BufferId,
@@ -544,6 +561,34 @@ pub struct IndentGuide {
     pub settings: IndentGuideSettings,
 }
 
+enum RegionMarker {
+    Start,
+    End,
+}
+
+/// Recognizes `region` markers such as `// #region`, `# region` and `#region`,
+/// which have to follow a comment prefix or a `#`.
+fn region_marker(line: &str) -> Option<RegionMarker> {
+    let line = line.trim_start();
+    let text = line.trim_start_matches(['/', '#', '-', ';', '*', '<', '!']);
+    if text.len() == line.len() {
+        return None;
+    }
+    let text = text.trim_start();
+    let text = text.strip_prefix('#').unwrap_or(text);
+    let (marker, rest) = if let Some(rest) = text.strip_prefix("endregion") {
+        (RegionMarker::End, rest)
+    } else if let Some(rest) = text.strip_prefix("region") {
+        (RegionMarker::Start, rest)
+    } else {
+        return None;
+    };
+    if rest.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    Some(marker)
+}
+
 impl IndentGuide {
     pub fn indent_level(&self) -> u32 {
         self.depth * self.tab_size
@@ -3316,6 +3361,109 @@ impl BufferSnapshot {
         })
     }
 
+    /// Returns whether the buffer's language defines a `folds` query.
+    pub fn has_syntax_folding_ranges(&self) -> bool {
+        self.language
+            .as_ref()
+            .and_then(|language| language.grammar())
+            .map_or(false, |grammar| grammar.folds_config.is_some())
+    }
+
+    /// Returns the foldable ranges of the whole buffer, sorted by their start,
+    /// based on the `folds` query and on `region`/`endregion` marker comments.
+    ///
+    /// Consecutive comments and imports are merged into a single range.
+    pub fn folding_ranges(&self) -> Vec<FoldingRange<Point>> {
+        let mut syntax_matches = self.syntax.matches(0..self.len(), self, |grammar| {
+            grammar.folds_config.as_ref().map(|config| &config.query)
+        });
+        let configs = syntax_matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.folds_config.as_ref())
+            .collect::<Vec<_>>();
+
+        let mut node_ranges = Vec::new();
+        while let Some(mat) = syntax_matches.peek() {
+            if let Some(config) = configs[mat.grammar_index] {
+                for capture in mat.captures {
+                    let kind = if Some(capture.index) == config.comment_capture_ix {
+                        Some(FoldingRangeKind::Comment)
+                    } else if Some(capture.index) == config.imports_capture_ix {
+                        Some(FoldingRangeKind::Imports)
+                    } else if Some(capture.index) == config.fold_capture_ix {
+                        None
+                    } else {
+                        continue;
+                    };
+                    node_ranges.push((capture.node.byte_range(), kind));
+                }
+            }
+            syntax_matches.advance();
+        }
+        node_ranges.sort_by_key(|(range, _)| (range.start, Reverse(range.end)));
+        node_ranges.dedup_by_key(|(range, _)| range.clone());
+
+        let mut row_ranges = Vec::<(Range<u32>, Option<FoldingRangeKind>)>::new();
+        for (byte_range, kind) in node_ranges {
+            let start = byte_range.start.to_point(self);
+            let end = byte_range.end.to_point(self);
+            let mut end_row = end.row;
+            if end.column == 0 && end_row > start.row {
+                // Nodes that include their trailing newline end on the next line.
+                end_row -= 1;
+            } else if end_row > start.row {
+                // Keep a line that closes the folded block visible.
+                let closing_line = self.text_for_range(Point::new(end_row, 0)..end);
+                let starts_with_closing_bracket = closing_line
+                    .flat_map(|chunk| chunk.chars())
+                    .find(|c| !c.is_whitespace())
+                    .map_or(false, |c| matches!(c, '}' | ')' | ']'));
+                if starts_with_closing_bracket {
+                    end_row -= 1;
+                }
+            }
+
+            if kind.is_some() {
+                if let Some((last_rows, last_kind)) = row_ranges.last_mut() {
+                    if *last_kind == kind && start.row <= last_rows.end + 1 {
+                        last_rows.end = last_rows.end.max(end_row);
+                        continue;
+                    }
+                }
+            }
+            row_ranges.push((start.row..end_row, kind));
+        }
+
+        let mut region_starts = Vec::new();
+        for (row, line) in self.text().lines().enumerate() {
+            let row = row as u32;
+            match region_marker(line) {
+                Some(RegionMarker::Start) => region_starts.push(row),
+                Some(RegionMarker::End) => {
+                    if let Some(start_row) = region_starts.pop() {
+                        row_ranges.push((start_row..row, Some(FoldingRangeKind::Region)));
+                    }
+                }
+                None => {}
+            }
+        }
+
+        let mut folding_ranges = row_ranges
+            .into_iter()
+            .filter(|(rows, _)| rows.end > rows.start)
+            .map(|(rows, kind)| FoldingRange {
+                range: Point::new(rows.start, self.line_len(rows.start))
+                    ..Point::new(rows.end, self.line_len(rows.end)),
+                kind,
+            })
+            .collect::<Vec<_>>();
+        folding_ranges.sort_by_key(|folding_range| {
+            (folding_range.range.start, Reverse(folding_range.range.end))
+        });
+        folding_ranges
+    }
+
     pub fn indent_guides_in_range(
         &self,
         range: Range<Anchor>,
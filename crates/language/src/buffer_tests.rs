@@ -599,6 +599,58 @@ async fn test_resetting_language(cx: &mut gpui::TestAppContext) {
     assert_eq!(get_tree_sexp(&buffer, cx), "(document (object))");
 }
 
+#[gpui::test]
+fn test_folding_ranges(cx: &mut AppContext) {
+    let text = r#"
+        use std::fmt;
+        use std::io;
+
+        // one
+        // two
+        fn main() {
+            let x = 1;
+            // #region setup
+            let y = 2;
+            // #endregion
+        }
+    "#
+    .unindent();
+
+    let language = rust_lang()
+        .with_folds_query(
+            r#"
+            (use_declaration) @fold.imports
+            (line_comment) @fold.comment
+            (function_item body: (_) @fold)
+            "#,
+        )
+        .unwrap();
+    let buffer = cx.new_model(|cx| Buffer::local(text, cx).with_language(Arc::new(language), cx));
+    let snapshot = buffer.read(cx).snapshot();
+    assert!(snapshot.has_syntax_folding_ranges());
+    assert_eq!(
+        snapshot.folding_ranges(),
+        &[
+            FoldingRange {
+                range: Point::new(0, 13)..Point::new(1, 12),
+                kind: Some(FoldingRangeKind::Imports),
+            },
+            FoldingRange {
+                range: Point::new(3, 6)..Point::new(4, 6),
+                kind: Some(FoldingRangeKind::Comment),
+            },
+            FoldingRange {
+                range: Point::new(5, 11)..Point::new(9, 17),
+                kind: None,
+            },
+            FoldingRange {
+                range: Point::new(7, 20)..Point::new(9, 17),
+                kind: Some(FoldingRangeKind::Region),
+            },
+        ]
+    );
+}
+
 #[gpui::test]
 async fn test_outline(cx: &mut gpui::TestAppContext) {
     let text = r#"
@@ -1,3 +1,4 @@
+use collections::HashMap;
 use gpui::HighlightStyle;
 use std::sync::Arc;
 use theme::SyntaxTheme;
@@ -10,33 +11,25 @@ pub struct HighlightId(pub u32);
 
 const DEFAULT_SYNTAX_HIGHLIGHT_ID: HighlightId = HighlightId(u32::MAX);
 
+/// Maps the semantic token types and modifiers in a language server's legend
+/// to highlight ids in a theme.
+///
+/// Themes style tokens with `semantic.<type>` and `semantic.<type>.<modifier>` keys.
+/// Token types without such a key fall back to the tree-sitter capture that best
+/// describes them, and tokens that match nothing keep their syntax highlighting.
+#[derive(Clone, Debug, Default)]
+pub struct SemanticTokenHighlightMap {
+    token_types: Vec<Option<HighlightId>>,
+    modified_token_types: HashMap<(u32, u32), HighlightId>,
+}
+
 impl HighlightMap {
     pub(crate) fn new(capture_names: &[&str], theme: &SyntaxTheme) -> Self {
-        // For each capture name in the highlight query, find the longest
-        // key in the theme's syntax styles that matches all of the
-        // dot-separated components of the capture name.
         HighlightMap(
             capture_names
                 .iter()
                 .map(|capture_name| {
-                    theme
-                        .highlights
-                        .iter()
-                        .enumerate()
-                        .filter_map(|(i, (key, _))| {
-                            let mut len = 0;
-                            let capture_parts = capture_name.split('.');
-                            for key_part in key.split('.') {
-                                if capture_parts.clone().any(|part| part == key_part) {
-                                    len += 1;
-                                } else {
-                                    return None;
-                                }
-                            }
-                            Some((i, len))
-                        })
-                        .max_by_key(|(_, len)| *len)
-                        .map_or(DEFAULT_SYNTAX_HIGHLIGHT_ID, |(i, _)| HighlightId(i as u32))
+                    longest_matching_key(capture_name, theme).unwrap_or(DEFAULT_SYNTAX_HIGHLIGHT_ID)
                 })
                 .collect(),
         )
@@ -50,6 +43,112 @@ impl HighlightMap {
     }
 }
 
+impl SemanticTokenHighlightMap {
+    pub fn new(
+        token_types: &[impl AsRef<str>],
+        token_modifiers: &[impl AsRef<str>],
+        theme: &SyntaxTheme,
+    ) -> Self {
+        let key_ids = theme
+            .highlights
+            .iter()
+            .enumerate()
+            .map(|(i, (key, _))| (key.as_str(), HighlightId(i as u32)))
+            .collect::<HashMap<_, _>>();
+
+        let mut modified_token_types = HashMap::default();
+        let token_types = token_types
+            .iter()
+            .enumerate()
+            .map(|(type_ix, token_type)| {
+                let token_type = token_type.as_ref();
+                for (modifier_ix, modifier) in token_modifiers.iter().enumerate() {
+                    let key = format!("semantic.{token_type}.{}", modifier.as_ref());
+                    if let Some(id) = key_ids.get(key.as_str()) {
+                        modified_token_types.insert((type_ix as u32, modifier_ix as u32), *id);
+                    }
+                }
+
+                key_ids
+                    .get(format!("semantic.{token_type}").as_str())
+                    .copied()
+                    .or_else(|| {
+                        let capture_name = fallback_capture_name(token_type)?;
+                        longest_matching_key(capture_name, theme)
+                    })
+            })
+            .collect();
+
+        Self {
+            token_types,
+            modified_token_types,
+        }
+    }
+
+    /// Returns the highlight for a token, preferring a style for one of its modifiers
+    /// over the style for its type.
+    pub fn get(&self, token_type: u32, token_modifiers: u32) -> Option<HighlightId> {
+        if token_modifiers != 0 && !self.modified_token_types.is_empty() {
+            for modifier_ix in 0..u32::BITS {
+                if token_modifiers & (1 << modifier_ix) != 0 {
+                    if let Some(id) = self.modified_token_types.get(&(token_type, modifier_ix)) {
+                        return Some(*id);
+                    }
+                }
+            }
+        }
+        self.token_types.get(token_type as usize).copied().flatten()
+    }
+}
+
+/// Finds the longest key in the theme's syntax styles that matches all of the
+/// dot-separated components of the capture name.
+fn longest_matching_key(capture_name: &str, theme: &SyntaxTheme) -> Option<HighlightId> {
+    theme
+        .highlights
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (key, _))| {
+            let mut len = 0;
+            let capture_parts = capture_name.split('.');
+            for key_part in key.split('.') {
+                if capture_parts.clone().any(|part| part == key_part) {
+                    len += 1;
+                } else {
+                    return None;
+                }
+            }
+            Some((i, len))
+        })
+        .max_by_key(|(_, len)| *len)
+        .map(|(i, _)| HighlightId(i as u32))
+}
+
+/// The tree-sitter capture used to highlight a standard LSP token type
+/// when the theme doesn't style it explicitly.
+fn fallback_capture_name(token_type: &str) -> Option<&'static str> {
+    Some(match token_type {
+        "namespace" => "namespace",
+        "type" | "class" | "interface" | "struct" | "typeParameter" => "type",
+        "enum" => "enum",
+        "parameter" => "variable.parameter",
+        "variable" => "variable",
+        "property" | "event" => "property",
+        "enumMember" => "variant",
+        "function" => "function",
+        "method" => "function.method",
+        "macro" => "function.macro",
+        "keyword" | "modifier" => "keyword",
+        "comment" => "comment",
+        "string" => "string",
+        "number" => "number",
+        "regexp" => "string.regex",
+        "operator" => "operator",
+        "decorator" => "attribute",
+        _ => return None,
+    })
+}
+
 impl HighlightId {
     pub(crate) fn is_default(&self) -> bool {
         *self == DEFAULT_SYNTAX_HIGHLIGHT_ID
@@ -108,4 +207,31 @@ mod tests {
         assert_eq!(map.get(1).name(&theme), Some("function.async"));
         assert_eq!(map.get(2).name(&theme), Some("variable.builtin"));
     }
+
+    #[test]
+    fn test_semantic_token_highlight_map() {
+        let theme = SyntaxTheme {
+            highlights: [
+                ("function", rgba(0x100000ff)),
+                ("variable", rgba(0x200000ff)),
+                ("semantic.variable.mutable", rgba(0x300000ff)),
+                ("semantic.lifetime", rgba(0x400000ff)),
+            ]
+            .iter()
+            .map(|(name, color)| (name.to_string(), (*color).into()))
+            .collect(),
+        };
+
+        let token_types = &["method", "variable", "lifetime", "unknown"];
+        let token_modifiers = &["declaration", "mutable"];
+
+        let map = SemanticTokenHighlightMap::new(token_types, token_modifiers, &theme);
+        let name = |token_type, modifiers| map.get(token_type, modifiers)?.name(&theme);
+        assert_eq!(name(0, 0), Some("function"));
+        assert_eq!(name(1, 0b01), Some("variable"));
+        assert_eq!(name(1, 0b11), Some("semantic.variable.mutable"));
+        assert_eq!(name(2, 0), Some("semantic.lifetime"));
+        assert_eq!(name(3, 0), None);
+        assert_eq!(name(4, 0), None);
+    }
 }
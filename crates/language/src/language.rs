@@ -26,7 +26,7 @@ use async_trait::async_trait;
 use collections::{HashMap, HashSet};
 use futures::Future;
 use gpui::{AppContext, AsyncAppContext, Model, SharedString, Task};
-pub use highlight_map::HighlightMap;
+pub use highlight_map::{HighlightMap, SemanticTokenHighlightMap};
 use http_client::HttpClient;
 use lazy_static::lazy_static;
 use lsp::{CodeActionKind, LanguageServerBinary};
@@ -65,7 +65,7 @@ use util::serde::default_true;
 
 pub use buffer::Operation;
 pub use buffer::*;
-pub use diagnostic_set::DiagnosticEntry;
+pub use diagnostic_set::{DiagnosticEntry, DiagnosticGroup};
 pub use language_registry::{
     LanguageNotFound, LanguageQueries, LanguageRegistry, LanguageServerBinaryStatus,
     PendingLanguageServer, QUERY_FILENAME_PREFIXES,
@@ -840,6 +840,7 @@ pub struct Grammar {
     pub(crate) brackets_config: Option<BracketConfig>,
     pub(crate) redactions_config: Option<RedactionConfig>,
     pub(crate) runnable_config: Option<RunnableConfig>,
+    pub(crate) folds_config: Option<FoldConfig>,
     pub(crate) indents_config: Option<IndentConfig>,
     pub outline_config: Option<OutlineConfig>,
     pub embedding_config: Option<EmbeddingConfig>,
@@ -901,6 +902,13 @@ struct RunnableConfig {
     pub extra_captures: Vec<RunnableCapture>,
 }
 
+struct FoldConfig {
+    query: Query,
+    fold_capture_ix: Option<u32>,
+    comment_capture_ix: Option<u32>,
+    imports_capture_ix: Option<u32>,
+}
+
 struct OverrideConfig {
     query: Query,
     values: HashMap<u32, (String, LanguageConfigOverride)>,
@@ -943,6 +951,7 @@ impl Language {
                     override_config: None,
                     redactions_config: None,
                     runnable_config: None,
+                    folds_config: None,
                     error_query: Query::new(&ts_language, "(ERROR) @error").unwrap(),
                     ts_language,
                     highlight_map: Default::default(),
@@ -1003,6 +1012,11 @@ impl Language {
                 .with_runnable_query(query.as_ref())
                 .context("Error loading tests query")?;
         }
+        if let Some(query) = queries.folds {
+            self = self
+                .with_folds_query(query.as_ref())
+                .context("Error loading folds query")?;
+        }
         Ok(self)
     }
 
@@ -1308,6 +1322,34 @@ impl Language {
         Ok(self)
     }
 
+    pub fn with_folds_query(mut self, source: &str) -> Result<Self> {
+        let grammar = self
+            .grammar_mut()
+            .ok_or_else(|| anyhow!("cannot mutate grammar"))?;
+
+        let query = Query::new(&grammar.ts_language, source)?;
+        let mut fold_capture_ix = None;
+        let mut comment_capture_ix = None;
+        let mut imports_capture_ix = None;
+        get_capture_indices(
+            &query,
+            &mut [
+                ("fold", &mut fold_capture_ix),
+                ("fold.comment", &mut comment_capture_ix),
+                ("fold.imports", &mut imports_capture_ix),
+            ],
+        );
+
+        grammar.folds_config = Some(FoldConfig {
+            query,
+            fold_capture_ix,
+            comment_capture_ix,
+            imports_capture_ix,
+        });
+
+        Ok(self)
+    }
+
     fn grammar_mut(&mut self) -> Option<&mut Grammar> {
         Arc::get_mut(self.grammar.as_mut()?)
     }
@@ -126,6 +126,7 @@ pub const QUERY_FILENAME_PREFIXES: &[(
     ("overrides", |q| &mut q.overrides),
     ("redactions", |q| &mut q.redactions),
     ("runnables", |q| &mut q.runnables),
+    ("folds", |q| &mut q.folds),
 ];
 
 /// Tree-sitter language queries for a given language.
@@ -140,6 +141,7 @@ pub struct LanguageQueries {
     pub overrides: Option<Cow<'static, str>>,
     pub redactions: Option<Cow<'static, str>>,
     pub runnables: Option<Cow<'static, str>>,
+    pub folds: Option<Cow<'static, str>>,
 }
 
 #[derive(Clone, Default)]
@@ -17,7 +17,7 @@ use serde::{
 };
 use serde_json::Value;
 use settings::{add_references_to_properties, Settings, SettingsLocation, SettingsSources};
-use std::{num::NonZeroU32, path::Path, sync::Arc};
+use std::{num::NonZeroU32, path::Path, sync::Arc, time::Duration};
 use util::serde::default_true;
 
 impl<'a> Into<SettingsLocation<'a>> for &'a dyn File {
@@ -125,6 +125,12 @@ pub struct LanguageSettings {
     pub code_actions_on_format: HashMap<String, bool>,
     /// Whether to perform linked edits
     pub linked_edits: bool,
+    /// Whether to highlight code using semantic tokens from the language server
+    pub semantic_tokens: bool,
+    /// Whether to show the code lenses reported by the language server above the lines they refer to
+    pub code_lens: bool,
+    /// Where to get the ranges that selections are expanded to
+    pub expand_selection: ExpandSelectionSource,
     /// Task configuration for this language.
     pub tasks: LanguageTaskConfig,
 }
@@ -184,6 +190,8 @@ pub enum InlineCompletionProvider {
     #[default]
     Copilot,
     Supermaven,
+    /// Fill-in-the-middle completions from one of the configured language models.
+    LanguageModel,
 }
 
 /// The settings for inline completions, such as [GitHub Copilot](https://github.com/features/copilot)
@@ -194,6 +202,68 @@ pub struct InlineCompletionSettings {
     pub provider: InlineCompletionProvider,
     /// A list of globs representing files that inline completions should be disabled for.
     pub disabled_globs: Vec<GlobMatcher>,
+    /// The settings for the `language_model` provider.
+    pub language_model: LanguageModelInlineCompletionSettings,
+}
+
+/// The settings for inline completions that are supplied by a language model.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LanguageModelInlineCompletionSettings {
+    /// The id of the language model provider, e.g. "ollama". No completions are
+    /// requested while it is unset.
+    pub provider: Option<String>,
+    /// The id of the model. When unset, the provider's first model is used.
+    pub model: Option<String>,
+    /// The maximum number of characters before the cursor to send to the model.
+    pub max_prefix_chars: usize,
+    /// The maximum number of characters after the cursor to send to the model.
+    pub max_suffix_chars: usize,
+    /// The maximum number of tokens to generate for each completion.
+    pub max_tokens: usize,
+    /// The maximum number of lines to suggest at once.
+    pub max_lines: usize,
+    /// How long to wait after the last edit before requesting a completion.
+    pub debounce: Duration,
+}
+
+impl Default for LanguageModelInlineCompletionSettings {
+    fn default() -> Self {
+        Self {
+            provider: None,
+            model: None,
+            max_prefix_chars: 4096,
+            max_suffix_chars: 1024,
+            max_tokens: 128,
+            max_lines: 16,
+            debounce: Duration::from_millis(300),
+        }
+    }
+}
+
+impl LanguageModelInlineCompletionSettings {
+    fn merge(&mut self, content: &LanguageModelInlineCompletionSettingsContent) {
+        if let Some(provider) = content.provider.clone() {
+            self.provider = Some(provider);
+        }
+        if let Some(model) = content.model.clone() {
+            self.model = Some(model);
+        }
+        if let Some(max_prefix_chars) = content.max_prefix_chars {
+            self.max_prefix_chars = max_prefix_chars;
+        }
+        if let Some(max_suffix_chars) = content.max_suffix_chars {
+            self.max_suffix_chars = max_suffix_chars;
+        }
+        if let Some(max_tokens) = content.max_tokens {
+            self.max_tokens = max_tokens;
+        }
+        if let Some(max_lines) = content.max_lines {
+            self.max_lines = max_lines;
+        }
+        if let Some(debounce_ms) = content.debounce_ms {
+            self.debounce = Duration::from_millis(debounce_ms);
+        }
+    }
 }
 
 /// The settings for all languages.
@@ -347,6 +417,20 @@ pub struct LanguageSettingsContent {
     ///
     /// Default: true
     pub linked_edits: Option<bool>,
+    /// Whether to highlight code using the semantic tokens reported by the language server,
+    /// on top of the highlighting provided by tree-sitter.
+    ///
+    /// Default: true
+    pub semantic_tokens: Option<bool>,
+    /// Whether to show the code lenses reported by the language server, such as
+    /// reference counts or ways to run tests, above the lines they refer to.
+    ///
+    /// Default: true
+    pub code_lens: Option<bool>,
+    /// Where to get the ranges that `editor::SelectLargerSyntaxNode` expands selections to.
+    ///
+    /// Default: tree_sitter
+    pub expand_selection: Option<ExpandSelectionSource>,
     /// Task configuration for this language.
     ///
     /// Default: {}
@@ -359,6 +443,40 @@ pub struct InlineCompletionSettingsContent {
     /// A list of globs representing files that inline completions should be disabled for.
     #[serde(default)]
     pub disabled_globs: Option<Vec<String>>,
+    /// The settings for the `language_model` provider.
+    #[serde(default)]
+    pub language_model: Option<LanguageModelInlineCompletionSettingsContent>,
+}
+
+/// The contents of the settings for inline completions that are supplied by a language model.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct LanguageModelInlineCompletionSettingsContent {
+    /// The id of the language model provider, e.g. "ollama" or
+    /// "openai_compatible/<name>". No completions are requested while it is unset,
+    /// so that code is only sent to a provider chosen for completions.
+    pub provider: Option<String>,
+    /// The id of the model. When unset, the provider's first model is used.
+    pub model: Option<String>,
+    /// The maximum number of characters before the cursor to send to the model.
+    ///
+    /// Default: 4096
+    pub max_prefix_chars: Option<usize>,
+    /// The maximum number of characters after the cursor to send to the model.
+    ///
+    /// Default: 1024
+    pub max_suffix_chars: Option<usize>,
+    /// The maximum number of tokens to generate for each completion.
+    ///
+    /// Default: 128
+    pub max_tokens: Option<usize>,
+    /// The maximum number of lines to suggest at once.
+    ///
+    /// Default: 16
+    pub max_lines: Option<usize>,
+    /// How long to wait after the last edit before requesting a completion, in milliseconds.
+    ///
+    /// Default: 300
+    pub debounce_ms: Option<u64>,
 }
 
 /// The settings for enabling/disabling features.
@@ -497,6 +615,18 @@ impl<'de> Deserialize<'de> for FormatOnSave {
     }
 }
 
+/// Where selections are expanded to by `editor::SelectLargerSyntaxNode`.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpandSelectionSource {
+    /// Expand to the enclosing syntax node of the tree-sitter syntax tree.
+    #[default]
+    TreeSitter,
+    /// Expand to the enclosing range reported by the language server's `textDocument/selectionRange`,
+    /// falling back to tree-sitter when the server doesn't report any.
+    LanguageServer,
+}
+
 /// Controls how whitespace should be displayedin the editor.
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -885,6 +1015,14 @@ impl settings::Settings for AllLanguageSettings {
             .as_ref()
             .and_then(|c| c.disabled_globs.as_ref())
             .ok_or_else(Self::missing_default)?;
+        let mut language_model_completions = LanguageModelInlineCompletionSettings::default();
+        if let Some(content) = default_value
+            .inline_completions
+            .as_ref()
+            .and_then(|c| c.language_model.as_ref())
+        {
+            language_model_completions.merge(content);
+        }
 
         let mut file_types: HashMap<Arc<str>, GlobSet> = HashMap::default();
 
@@ -916,6 +1054,13 @@ impl settings::Settings for AllLanguageSettings {
             {
                 completion_globs = globs;
             }
+            if let Some(content) = user_settings
+                .inline_completions
+                .as_ref()
+                .and_then(|c| c.language_model.as_ref())
+            {
+                language_model_completions.merge(content);
+            }
 
             // A user's global settings override the default global settings and
             // all default language-specific settings.
@@ -967,6 +1112,7 @@ impl settings::Settings for AllLanguageSettings {
                     .iter()
                     .filter_map(|g| Some(globset::Glob::new(g).ok()?.compile_matcher()))
                     .collect(),
+                language_model: language_model_completions,
             },
             defaults,
             languages,
@@ -1042,6 +1188,9 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         src.code_actions_on_format.clone(),
     );
     merge(&mut settings.linked_edits, src.linked_edits);
+    merge(&mut settings.semantic_tokens, src.semantic_tokens);
+    merge(&mut settings.code_lens, src.code_lens);
+    merge(&mut settings.expand_selection, src.expand_selection);
     merge(&mut settings.tasks, src.tasks.clone());
 
     merge(
@@ -3,6 +3,7 @@ pub mod provider;
 mod rate_limiter;
 mod registry;
 mod request;
+mod request_log;
 mod role;
 pub mod settings;
 
@@ -16,6 +17,7 @@ use proto::Plan;
 pub(crate) use rate_limiter::*;
 pub use registry::*;
 pub use request::*;
+pub use request_log::*;
 pub use role::*;
 use schemars::JsonSchema;
 use serde::de::DeserializeOwned;
@@ -29,6 +31,7 @@ pub fn init(
     cx: &mut AppContext,
 ) {
     settings::init(fs, cx);
+    request_log::init(cx);
     registry::init(user_store, client, cx);
 }
 
@@ -76,6 +79,17 @@ pub trait LanguageModel: Send + Sync {
         cx: &AsyncAppContext,
     ) -> BoxFuture<'static, Result<serde_json::Value>>;
 
+    /// Streams the text that belongs between the request's prefix and suffix.
+    ///
+    /// Models that can't fill in the middle natively are asked to do so in a conversation.
+    fn stream_fim_completion(
+        &self,
+        request: LanguageModelFimRequest,
+        cx: &AsyncAppContext,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+        self.stream_completion(request.into_chat_request(), cx)
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     fn as_fake(&self) -> &provider::fake::FakeLanguageModel {
         unimplemented!()
@@ -32,8 +32,11 @@ pub fn provider_name() -> LanguageModelProviderName {
     LanguageModelProviderName::from("Fake".to_string())
 }
 
+/// Provides a single model, which is shared by everything that uses the provider.
 #[derive(Clone, Default)]
-pub struct FakeLanguageModelProvider;
+pub struct FakeLanguageModelProvider {
+    model: Arc<FakeLanguageModel>,
+}
 
 impl LanguageModelProviderState for FakeLanguageModelProvider {
     type ObservableEntity = ();
@@ -53,7 +56,7 @@ impl LanguageModelProvider for FakeLanguageModelProvider {
     }
 
     fn provided_models(&self, _: &AppContext) -> Vec<Arc<dyn LanguageModel>> {
-        vec![Arc::new(FakeLanguageModel::default())]
+        vec![self.model.clone()]
     }
 
     fn is_authenticated(&self, _: &AppContext) -> bool {
@@ -91,9 +94,15 @@ pub struct ToolUseRequest {
 pub struct FakeLanguageModel {
     current_completion_txs: Mutex<Vec<(LanguageModelRequest, mpsc::UnboundedSender<String>)>>,
     current_tool_use_txs: Mutex<Vec<(ToolUseRequest, oneshot::Sender<Result<serde_json::Value>>)>>,
+    max_token_count: Mutex<Option<usize>>,
 }
 
 impl FakeLanguageModel {
+    /// Overrides the token limit of the model, which counts one token per character.
+    pub fn set_max_token_count(&self, max_token_count: usize) {
+        *self.max_token_count.lock() = Some(max_token_count);
+    }
+
     pub fn pending_completions(&self) -> Vec<LanguageModelRequest> {
         self.current_completion_txs
             .lock()
@@ -174,15 +183,20 @@ impl LanguageModel for FakeLanguageModel {
     }
 
     fn max_token_count(&self) -> usize {
-        1000000
+        self.max_token_count.lock().unwrap_or(1000000)
     }
 
     fn count_tokens(
         &self,
-        _: LanguageModelRequest,
+        request: LanguageModelRequest,
         _: &AppContext,
     ) -> BoxFuture<'static, Result<usize>> {
-        futures::future::ready(Ok(0)).boxed()
+        let token_count = request
+            .messages
+            .iter()
+            .map(|message| message.content.chars().count())
+            .sum();
+        futures::future::ready(Ok(token_count)).boxed()
     }
 
     fn stream_completion(
@@ -3,8 +3,8 @@ use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
 use gpui::{AnyView, AppContext, AsyncAppContext, ModelContext, Subscription, Task};
 use http_client::HttpClient;
 use ollama::{
-    get_models, preload_model, stream_chat_completion, ChatMessage, ChatOptions, ChatRequest,
-    ChatResponseDelta, OllamaToolCall,
+    get_models, preload_model, stream_chat_completion, stream_generate, ChatMessage, ChatOptions,
+    ChatRequest, ChatResponseDelta, GenerateRequest, OllamaToolCall,
 };
 use serde_json::Value;
 use settings::{Settings, SettingsStore};
@@ -13,8 +13,8 @@ use ui::{prelude::*, ButtonLike, Indicator};
 use util::ResultExt;
 
 use crate::{
-    settings::AllLanguageModelSettings, LanguageModel, LanguageModelId, LanguageModelName,
-    LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
+    settings::AllLanguageModelSettings, LanguageModel, LanguageModelFimRequest, LanguageModelId,
+    LanguageModelName, LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
     LanguageModelProviderState, LanguageModelRequest, RateLimiter, Role,
 };
 
@@ -349,6 +349,46 @@ impl LanguageModel for OllamaLanguageModel {
             })
             .boxed()
     }
+
+    fn stream_fim_completion(
+        &self,
+        request: LanguageModelFimRequest,
+        cx: &AsyncAppContext,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+        // Ollama fills in the middle using the model's own template, as long as the model has one.
+        let request = GenerateRequest {
+            model: self.model.name.clone(),
+            prompt: request.prefix,
+            suffix: request.suffix,
+            stream: true,
+            keep_alive: self.model.keep_alive.clone().unwrap_or_default(),
+            options: Some(ChatOptions {
+                num_ctx: Some(self.model.max_tokens),
+                num_predict: Some(request.max_tokens as isize),
+                stop: Some(request.stop),
+                temperature: Some(request.temperature),
+                ..Default::default()
+            }),
+        };
+
+        let http_client = self.http_client.clone();
+        let Ok((api_url, low_speed_timeout)) = cx.update(|cx| {
+            let settings = &AllLanguageModelSettings::get_global(cx).ollama;
+            (settings.api_url.clone(), settings.low_speed_timeout)
+        }) else {
+            return futures::future::ready(Err(anyhow!("App state dropped"))).boxed();
+        };
+
+        let future = self.request_limiter.stream(async move {
+            let response =
+                stream_generate(http_client.as_ref(), &api_url, request, low_speed_timeout).await?;
+            Ok(response
+                .map(|delta| delta.map(|delta| delta.response))
+                .boxed())
+        });
+
+        async move { Ok(future.await?.boxed()) }.boxed()
+    }
 }
 
 struct ConfigurationView {
@@ -497,3 +537,82 @@ impl Render for ConfigurationView {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::AsyncReadExt as _;
+    use gpui::TestAppContext;
+    use http_client::{FakeHttpClient, Response};
+    use parking_lot::Mutex;
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_ollama_fim_completion(cx: &mut TestAppContext) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let http_client = FakeHttpClient::create({
+            let requests = requests.clone();
+            move |request| {
+                let requests = requests.clone();
+                async move {
+                    let uri = request.uri().to_string();
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await.unwrap();
+                    requests
+                        .lock()
+                        .push((uri, serde_json::from_str::<Value>(&body).unwrap()));
+
+                    let body = [
+                        json!({ "response": "let x", "done": false }),
+                        json!({ "response": " = 1;", "done": true }),
+                    ]
+                    .map(|delta| format!("{delta}\n"))
+                    .concat();
+                    Ok(Response::new(body.into()))
+                }
+            }
+        });
+
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            AllLanguageModelSettings::register(cx);
+        });
+        let model = OllamaLanguageModel {
+            id: LanguageModelId::from("qwen-coder".to_string()),
+            model: ollama::Model::new("qwen-coder"),
+            http_client,
+            request_limiter: RateLimiter::new(4),
+        };
+
+        let request = LanguageModelFimRequest {
+            prefix: "fn main() {\n    ".into(),
+            suffix: "\n}\n".into(),
+            language_name: Some("Rust".into()),
+            max_tokens: 16,
+            stop: vec!["\n\n".into()],
+            temperature: 0.2,
+        };
+        let completion = cx.update(|cx| model.stream_fim_completion(request, &cx.to_async()));
+        let chunks = completion.await.unwrap().collect::<Vec<_>>().await;
+        assert_eq!(
+            chunks
+                .into_iter()
+                .collect::<Result<Vec<_>>>()
+                .unwrap()
+                .concat(),
+            "let x = 1;"
+        );
+
+        let requests = requests.lock();
+        assert_eq!(requests.len(), 1);
+        let (uri, body) = &requests[0];
+        assert_eq!(uri, "http://localhost:11434/api/generate");
+        assert_eq!(body["model"], "qwen-coder");
+        assert_eq!(body["prompt"], "fn main() {\n    ");
+        assert_eq!(body["suffix"], "\n}\n");
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["options"]["num_predict"], 16);
+        assert_eq!(body["options"]["stop"], json!(["\n\n"]));
+    }
+}
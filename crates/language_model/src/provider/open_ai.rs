@@ -1,7 +1,7 @@
 use anyhow::{anyhow, bail, Result};
 use collections::BTreeMap;
 use editor::{Editor, EditorElement, EditorStyle};
-use futures::{future::BoxFuture, FutureExt, StreamExt};
+use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
 use gpui::{
     AnyView, AppContext, AsyncAppContext, FontStyle, ModelContext, Subscription, Task, TextStyle,
     View, WhiteSpace,
@@ -295,55 +295,69 @@ impl LanguageModel for OpenAiLanguageModel {
         cx: &AsyncAppContext,
     ) -> BoxFuture<'static, Result<serde_json::Value>> {
         let mut request = request.into_open_ai(self.model.id().into());
-        let mut function = FunctionDefinition {
-            name: tool_name.clone(),
-            description: None,
-            parameters: None,
-        };
-        let func = ToolDefinition::Function {
-            function: function.clone(),
-        };
-        request.tool_choice = Some(ToolChoice::Other(func.clone()));
-        // Fill in description and params separately, as they're not needed for tool_choice field.
-        function.description = Some(tool_description);
-        function.parameters = Some(schema);
-        request.tools = vec![ToolDefinition::Function { function }];
+        force_tool_use(&mut request, tool_name.clone(), tool_description, schema);
         let response = self.stream_completion(request, cx);
         self.request_limiter
-            .run(async move {
-                let mut response = response.await?;
-
-                // Call arguments are gonna be streamed in over multiple chunks.
-                let mut load_state = None;
-                while let Some(Ok(part)) = response.next().await {
-                    for choice in part.choices {
-                        let Some(tool_calls) = choice.delta.tool_calls else {
-                            continue;
-                        };
-
-                        for call in tool_calls {
-                            if let Some(func) = call.function {
-                                if func.name.as_deref() == Some(tool_name.as_str()) {
-                                    load_state = Some((String::default(), call.index));
-                                }
-                                if let Some((arguments, (output, index))) =
-                                    func.arguments.zip(load_state.as_mut())
-                                {
-                                    if call.index == *index {
-                                        output.push_str(&arguments);
-                                    }
-                                }
-                            }
+            .run(async move { collect_tool_call_arguments(response.await?, &tool_name).await })
+            .boxed()
+    }
+}
+
+/// Offers the given tool to the model, and requires the model to call it.
+pub(crate) fn force_tool_use(
+    request: &mut open_ai::Request,
+    tool_name: String,
+    tool_description: String,
+    schema: serde_json::Value,
+) {
+    let mut function = FunctionDefinition {
+        name: tool_name,
+        description: None,
+        parameters: None,
+    };
+    let func = ToolDefinition::Function {
+        function: function.clone(),
+    };
+    request.tool_choice = Some(ToolChoice::Other(func.clone()));
+    // Fill in description and params separately, as they're not needed for tool_choice field.
+    function.description = Some(tool_description);
+    function.parameters = Some(schema);
+    request.tools = vec![ToolDefinition::Function { function }];
+}
+
+/// Parses the arguments the model passed to the given tool from a streamed response.
+pub(crate) async fn collect_tool_call_arguments(
+    mut response: BoxStream<'static, Result<ResponseStreamEvent>>,
+    tool_name: &str,
+) -> Result<serde_json::Value> {
+    // Call arguments are gonna be streamed in over multiple chunks.
+    let mut load_state = None;
+    while let Some(Ok(part)) = response.next().await {
+        for choice in part.choices {
+            let Some(tool_calls) = choice.delta.tool_calls else {
+                continue;
+            };
+
+            for call in tool_calls {
+                if let Some(func) = call.function {
+                    if func.name.as_deref() == Some(tool_name) {
+                        load_state = Some((String::default(), call.index));
+                    }
+                    if let Some((arguments, (output, index))) =
+                        func.arguments.zip(load_state.as_mut())
+                    {
+                        if call.index == *index {
+                            output.push_str(&arguments);
                         }
                     }
                 }
-                if let Some((arguments, _)) = load_state {
-                    return Ok(serde_json::from_str(&arguments)?);
-                } else {
-                    bail!("tool not used");
-                }
-            })
-            .boxed()
+            }
+        }
+    }
+    if let Some((arguments, _)) = load_state {
+        Ok(serde_json::from_str(&arguments)?)
+    } else {
+        bail!("tool not used");
     }
 }
 
@@ -0,0 +1,995 @@
+use anyhow::{anyhow, Result};
+use collections::BTreeMap;
+use editor::{Editor, EditorElement, EditorStyle};
+use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
+use gpui::{
+    AnyView, AppContext, AsyncAppContext, FontStyle, ModelContext, Subscription, Task, TextStyle,
+    View, WhiteSpace,
+};
+use http_client::HttpClient;
+use open_ai::{
+    list_models, stream_completion_with_headers, stream_text_completion, ModelListing,
+    ResponseStreamEvent, TextCompletionRequest,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsStore};
+use std::{sync::Arc, time::Duration};
+use theme::ThemeSettings;
+use ui::{prelude::*, Icon, IconName};
+use util::ResultExt;
+
+use super::open_ai::{collect_tool_call_arguments, count_open_ai_tokens, force_tool_use};
+use crate::{
+    settings::AllLanguageModelSettings, LanguageModel, LanguageModelFimRequest, LanguageModelId,
+    LanguageModelName, LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
+    LanguageModelProviderState, LanguageModelRequest, RateLimiter,
+};
+
+const PROVIDER_ID_PREFIX: &str = "openai_compatible/";
+
+/// The context size of discovered models whose context size isn't reported by the server.
+pub const DEFAULT_MAX_TOKENS: usize = 8192;
+
+/// The settings for a single server that implements the OpenAI API.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpenAiCompatibleSettings {
+    pub name: String,
+    pub api_url: String,
+    pub headers: BTreeMap<String, String>,
+    pub low_speed_timeout: Option<Duration>,
+    pub available_models: Vec<AvailableModel>,
+    pub discover_models: bool,
+    pub max_tokens: usize,
+    pub supports_tools: bool,
+    pub fim_template: Option<String>,
+}
+
+impl OpenAiCompatibleSettings {
+    fn headers(&self) -> Vec<(String, String)> {
+        self.headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AvailableModel {
+    /// The name of the model, as expected by the server.
+    pub name: String,
+    /// The name to show in the model selector. Defaults to `name`.
+    pub display_name: Option<String>,
+    /// The context size of the model.
+    pub max_tokens: usize,
+    /// Whether the model supports tool calls. Defaults to the provider's `supports_tools`.
+    pub supports_tools: Option<bool>,
+}
+
+pub fn provider_id(name: &str) -> LanguageModelProviderId {
+    LanguageModelProviderId::from(format!("{PROVIDER_ID_PREFIX}{name}"))
+}
+
+pub fn is_openai_compatible_provider(id: &LanguageModelProviderId) -> bool {
+    id.0.starts_with(PROVIDER_ID_PREFIX)
+}
+
+pub struct OpenAiCompatibleLanguageModelProvider {
+    name: String,
+    http_client: Arc<dyn HttpClient>,
+    state: gpui::Model<State>,
+}
+
+pub struct State {
+    name: String,
+    http_client: Arc<dyn HttpClient>,
+    settings: Option<OpenAiCompatibleSettings>,
+    api_key: Option<String>,
+    discovered_models: Vec<ModelListing>,
+    _subscription: Subscription,
+}
+
+impl State {
+    fn settings_for(name: &str, cx: &AppContext) -> Option<OpenAiCompatibleSettings> {
+        AllLanguageModelSettings::get_global(cx)
+            .openai_compatible
+            .iter()
+            .find(|settings| settings.name == name)
+            .cloned()
+    }
+
+    /// Returns the configured models, along with the discovered models that weren't configured.
+    fn available_models(&self) -> Vec<(AvailableModel, bool)> {
+        let Some(settings) = self.settings.as_ref() else {
+            return Vec::new();
+        };
+
+        let mut models = BTreeMap::default();
+        for model in &self.discovered_models {
+            models.insert(
+                model.id.clone(),
+                AvailableModel {
+                    name: model.id.clone(),
+                    display_name: None,
+                    max_tokens: model.max_model_len.unwrap_or(settings.max_tokens),
+                    supports_tools: None,
+                },
+            );
+        }
+        for model in &settings.available_models {
+            models.insert(model.name.clone(), model.clone());
+        }
+
+        models
+            .into_values()
+            .map(|model| {
+                let supports_tools = model.supports_tools.unwrap_or(settings.supports_tools);
+                (model, supports_tools)
+            })
+            .collect()
+    }
+
+    fn is_authenticated(&self) -> bool {
+        !self.available_models().is_empty()
+    }
+
+    fn fetch_models(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let Some(settings) = self.settings.clone() else {
+            return Task::ready(Err(anyhow!("{} is no longer configured", self.name)));
+        };
+        if !settings.discover_models {
+            self.discovered_models.clear();
+            cx.notify();
+            return Task::ready(Ok(()));
+        }
+
+        let http_client = self.http_client.clone();
+        let api_key = self.api_key.clone();
+        cx.spawn(|this, mut cx| async move {
+            let models = list_models(
+                http_client.as_ref(),
+                &settings.api_url,
+                api_key.as_deref(),
+                &settings.headers(),
+                settings.low_speed_timeout,
+            )
+            .await?;
+
+            this.update(&mut cx, |this, cx| {
+                this.discovered_models = models;
+                cx.notify();
+            })
+        })
+    }
+
+    fn authenticate(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        if self.is_authenticated() {
+            return Task::ready(Ok(()));
+        }
+        let Some(api_url) = self
+            .settings
+            .as_ref()
+            .map(|settings| settings.api_url.clone())
+        else {
+            return Task::ready(Err(anyhow!("{} is no longer configured", self.name)));
+        };
+
+        // Servers that implement the OpenAI API don't necessarily require an API key, so a
+        // missing key isn't an error.
+        cx.spawn(|this, mut cx| async move {
+            let credentials = cx
+                .update(|cx| cx.read_credentials(&api_url))?
+                .await
+                .log_err()
+                .flatten();
+            let fetch_models = this.update(&mut cx, |this, cx| {
+                if let Some((_, api_key)) = credentials {
+                    this.api_key = String::from_utf8(api_key).log_err();
+                }
+                this.fetch_models(cx)
+            })?;
+            let fetched = fetch_models.await;
+
+            this.update(&mut cx, |this, _| {
+                if this.is_authenticated() {
+                    Ok(())
+                } else {
+                    fetched.and_then(|_| Err(anyhow!("no models are available from {api_url}")))
+                }
+            })?
+        })
+    }
+
+    fn set_api_key(&mut self, api_key: String, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let Some(api_url) = self
+            .settings
+            .as_ref()
+            .map(|settings| settings.api_url.clone())
+        else {
+            return Task::ready(Ok(()));
+        };
+        let write_credentials = cx.write_credentials(&api_url, "Bearer", api_key.as_bytes());
+
+        cx.spawn(|this, mut cx| async move {
+            write_credentials.await?;
+            this.update(&mut cx, |this, cx| {
+                this.api_key = Some(api_key);
+                this.fetch_models(cx)
+            })?
+            .await
+        })
+    }
+
+    fn reset_api_key(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let Some(api_url) = self
+            .settings
+            .as_ref()
+            .map(|settings| settings.api_url.clone())
+        else {
+            return Task::ready(Ok(()));
+        };
+        let delete_credentials = cx.delete_credentials(&api_url);
+        cx.spawn(|this, mut cx| async move {
+            delete_credentials.await.log_err();
+            this.update(&mut cx, |this, cx| {
+                this.api_key = None;
+                cx.notify();
+            })
+        })
+    }
+}
+
+impl OpenAiCompatibleLanguageModelProvider {
+    pub fn new(name: String, http_client: Arc<dyn HttpClient>, cx: &mut AppContext) -> Self {
+        let state = cx.new_model(|cx| State {
+            name: name.clone(),
+            http_client: http_client.clone(),
+            settings: State::settings_for(&name, cx),
+            api_key: None,
+            discovered_models: Vec::new(),
+            _subscription: cx.observe_global::<SettingsStore>(|this: &mut State, cx| {
+                let settings = State::settings_for(&this.name, cx);
+                if settings != this.settings {
+                    this.settings = settings;
+                    if this.settings.is_some() {
+                        this.fetch_models(cx).detach_and_log_err(cx);
+                    }
+                    cx.notify();
+                }
+            }),
+        });
+        state.update(cx, |state, cx| state.authenticate(cx).detach());
+
+        Self {
+            name,
+            http_client,
+            state,
+        }
+    }
+}
+
+impl LanguageModelProviderState for OpenAiCompatibleLanguageModelProvider {
+    type ObservableEntity = State;
+
+    fn observable_entity(&self) -> Option<gpui::Model<Self::ObservableEntity>> {
+        Some(self.state.clone())
+    }
+}
+
+impl LanguageModelProvider for OpenAiCompatibleLanguageModelProvider {
+    fn id(&self) -> LanguageModelProviderId {
+        provider_id(&self.name)
+    }
+
+    fn name(&self) -> LanguageModelProviderName {
+        LanguageModelProviderName::from(self.name.clone())
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::Server
+    }
+
+    fn provided_models(&self, cx: &AppContext) -> Vec<Arc<dyn LanguageModel>> {
+        self.state
+            .read(cx)
+            .available_models()
+            .into_iter()
+            .map(|(model, supports_tools)| {
+                Arc::new(OpenAiCompatibleLanguageModel {
+                    id: LanguageModelId::from(model.name.clone()),
+                    provider_name: self.name.clone(),
+                    model,
+                    supports_tools,
+                    state: self.state.clone(),
+                    http_client: self.http_client.clone(),
+                    request_limiter: RateLimiter::new(4),
+                }) as Arc<dyn LanguageModel>
+            })
+            .collect()
+    }
+
+    fn is_authenticated(&self, cx: &AppContext) -> bool {
+        self.state.read(cx).is_authenticated()
+    }
+
+    fn authenticate(&self, cx: &mut AppContext) -> Task<Result<()>> {
+        self.state.update(cx, |state, cx| state.authenticate(cx))
+    }
+
+    fn configuration_view(&self, cx: &mut WindowContext) -> AnyView {
+        cx.new_view(|cx| ConfigurationView::new(self.state.clone(), cx))
+            .into()
+    }
+
+    fn reset_credentials(&self, cx: &mut AppContext) -> Task<Result<()>> {
+        self.state.update(cx, |state, cx| state.reset_api_key(cx))
+    }
+}
+
+pub struct OpenAiCompatibleLanguageModel {
+    id: LanguageModelId,
+    provider_name: String,
+    model: AvailableModel,
+    supports_tools: bool,
+    state: gpui::Model<State>,
+    http_client: Arc<dyn HttpClient>,
+    request_limiter: RateLimiter,
+}
+
+impl OpenAiCompatibleLanguageModel {
+    fn stream_completion(
+        &self,
+        request: open_ai::Request,
+        cx: &AsyncAppContext,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<ResponseStreamEvent>>>> {
+        let http_client = self.http_client.clone();
+        let Ok((api_key, settings)) = cx.read_model(&self.state, |state, _| {
+            (state.api_key.clone(), state.settings.clone())
+        }) else {
+            return futures::future::ready(Err(anyhow!("App state dropped"))).boxed();
+        };
+        let provider_name = self.provider_name.clone();
+
+        let future = self.request_limiter.stream(async move {
+            let settings =
+                settings.ok_or_else(|| anyhow!("{provider_name} is no longer configured"))?;
+            let request = stream_completion_with_headers(
+                http_client.as_ref(),
+                &settings.api_url,
+                api_key.as_deref(),
+                &settings.headers(),
+                request,
+                settings.low_speed_timeout,
+            );
+            let response = request.await?;
+            Ok(response)
+        });
+
+        async move { Ok(future.await?.boxed()) }.boxed()
+    }
+}
+
+impl LanguageModel for OpenAiCompatibleLanguageModel {
+    fn id(&self) -> LanguageModelId {
+        self.id.clone()
+    }
+
+    fn name(&self) -> LanguageModelName {
+        LanguageModelName::from(
+            self.model
+                .display_name
+                .clone()
+                .unwrap_or_else(|| self.model.name.clone()),
+        )
+    }
+
+    fn provider_id(&self) -> LanguageModelProviderId {
+        provider_id(&self.provider_name)
+    }
+
+    fn provider_name(&self) -> LanguageModelProviderName {
+        LanguageModelProviderName::from(self.provider_name.clone())
+    }
+
+    fn telemetry_id(&self) -> String {
+        format!("openai_compatible/{}", self.model.name)
+    }
+
+    fn max_token_count(&self) -> usize {
+        self.model.max_tokens
+    }
+
+    fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AppContext,
+    ) -> BoxFuture<'static, Result<usize>> {
+        // The actual tokenizer depends on the served model, so this is an estimate.
+        let model = open_ai::Model::Custom {
+            name: self.model.name.clone(),
+            max_tokens: self.model.max_tokens,
+        };
+        count_open_ai_tokens(request, model, cx)
+    }
+
+    fn stream_completion(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AsyncAppContext,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+        let request = request.into_open_ai(self.model.name.clone());
+        let completions = self.stream_completion(request, cx);
+        async move { Ok(open_ai::extract_text_from_events(completions.await?).boxed()) }.boxed()
+    }
+
+    fn use_any_tool(
+        &self,
+        request: LanguageModelRequest,
+        tool_name: String,
+        tool_description: String,
+        schema: serde_json::Value,
+        cx: &AsyncAppContext,
+    ) -> BoxFuture<'static, Result<serde_json::Value>> {
+        if !self.supports_tools {
+            return futures::future::ready(Err(anyhow!(
+                "{} doesn't support tool calls. Set `supports_tools` to true in its settings if it does.",
+                self.model.name
+            )))
+            .boxed();
+        }
+
+        let mut request = request.into_open_ai(self.model.name.clone());
+        force_tool_use(&mut request, tool_name.clone(), tool_description, schema);
+        let response = self.stream_completion(request, cx);
+        self.request_limiter
+            .run(async move { collect_tool_call_arguments(response.await?, &tool_name).await })
+            .boxed()
+    }
+
+    fn stream_fim_completion(
+        &self,
+        request: LanguageModelFimRequest,
+        cx: &AsyncAppContext,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+        let Ok((api_key, settings)) = cx.read_model(&self.state, |state, _| {
+            (state.api_key.clone(), state.settings.clone())
+        }) else {
+            return futures::future::ready(Err(anyhow!("App state dropped"))).boxed();
+        };
+        let Some((settings, fim_template)) = settings.and_then(|settings| {
+            let fim_template = settings.fim_template.clone()?;
+            Some((settings, fim_template))
+        }) else {
+            return LanguageModel::stream_completion(self, request.into_chat_request(), cx);
+        };
+
+        let request = TextCompletionRequest {
+            model: self.model.name.clone(),
+            prompt: fill_fim_template(&fim_template, &request.prefix, &request.suffix),
+            stream: true,
+            max_tokens: Some(request.max_tokens),
+            stop: request.stop,
+            temperature: request.temperature,
+        };
+        let http_client = self.http_client.clone();
+        let future = self.request_limiter.stream(async move {
+            stream_text_completion(
+                http_client.as_ref(),
+                &settings.api_url,
+                api_key.as_deref(),
+                &settings.headers(),
+                request,
+                settings.low_speed_timeout,
+            )
+            .await
+        });
+
+        async move { Ok(future.await?.boxed()) }.boxed()
+    }
+}
+
+/// Substitutes the prefix and suffix into the template, without substituting placeholders
+/// that appear in the prefix or suffix themselves.
+fn fill_fim_template(template: &str, prefix: &str, suffix: &str) -> String {
+    template
+        .split("{suffix}")
+        .map(|part| part.replace("{prefix}", prefix))
+        .collect::<Vec<_>>()
+        .join(suffix)
+}
+
+struct ConfigurationView {
+    api_key_editor: View<Editor>,
+    state: gpui::Model<State>,
+    load_models_task: Option<Task<()>>,
+}
+
+impl ConfigurationView {
+    fn new(state: gpui::Model<State>, cx: &mut ViewContext<Self>) -> Self {
+        let api_key_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("API key (optional)", cx);
+            editor
+        });
+
+        cx.observe(&state, |_, _, cx| {
+            cx.notify();
+        })
+        .detach();
+
+        let mut this = Self {
+            api_key_editor,
+            state,
+            load_models_task: None,
+        };
+        this.load_models(cx);
+        this
+    }
+
+    fn load_models(&mut self, cx: &mut ViewContext<Self>) {
+        let state = self.state.clone();
+        self.load_models_task = Some(cx.spawn(|this, mut cx| async move {
+            if let Some(task) = state
+                .update(&mut cx, |state, cx| state.authenticate(cx))
+                .log_err()
+            {
+                // We don't log an error, because an unreachable server is also an error.
+                let _ = task.await;
+            }
+
+            this.update(&mut cx, |this, cx| {
+                this.load_models_task = None;
+                cx.notify();
+            })
+            .log_err();
+        }));
+        cx.notify();
+    }
+
+    fn refresh_models(&mut self, cx: &mut ViewContext<Self>) {
+        let task = self.state.update(cx, |state, cx| state.fetch_models(cx));
+        self.load_models_task = Some(cx.spawn(|this, mut cx| async move {
+            task.await.log_err();
+            this.update(&mut cx, |this, cx| {
+                this.load_models_task = None;
+                cx.notify();
+            })
+            .log_err();
+        }));
+        cx.notify();
+    }
+
+    fn save_api_key(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let api_key = self.api_key_editor.read(cx).text(cx);
+        if api_key.is_empty() {
+            return;
+        }
+
+        let state = self.state.clone();
+        cx.spawn(|_, mut cx| async move {
+            state
+                .update(&mut cx, |state, cx| state.set_api_key(api_key, cx))?
+                .await
+        })
+        .detach_and_log_err(cx);
+
+        cx.notify();
+    }
+
+    fn reset_api_key(&mut self, cx: &mut ViewContext<Self>) {
+        self.api_key_editor
+            .update(cx, |editor, cx| editor.set_text("", cx));
+
+        let state = self.state.clone();
+        cx.spawn(|_, mut cx| async move {
+            state
+                .update(&mut cx, |state, cx| state.reset_api_key(cx))?
+                .await
+        })
+        .detach_and_log_err(cx);
+
+        cx.notify();
+    }
+
+    fn render_api_key_editor(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let text_style = TextStyle {
+            color: cx.theme().colors().text,
+            font_family: settings.ui_font.family.clone(),
+            font_features: settings.ui_font.features.clone(),
+            font_fallbacks: settings.ui_font.fallbacks.clone(),
+            font_size: rems(0.875).into(),
+            font_weight: settings.ui_font.weight,
+            font_style: FontStyle::Normal,
+            line_height: relative(1.3),
+            background_color: None,
+            underline: None,
+            strikethrough: None,
+            white_space: WhiteSpace::Normal,
+        };
+        EditorElement::new(
+            &self.api_key_editor,
+            EditorStyle {
+                background: cx.theme().colors().editor_background,
+                local_player: cx.theme().players().local(),
+                text: text_style,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+impl Render for ConfigurationView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let state = self.state.read(cx);
+        let api_url = state
+            .settings
+            .as_ref()
+            .map(|settings| settings.api_url.clone())
+            .unwrap_or_default();
+        let model_count = state.available_models().len();
+        let has_api_key = state.api_key.is_some();
+
+        if self.load_models_task.is_some() {
+            return div()
+                .child(Label::new(format!("Connecting to {api_url}...")))
+                .into_any();
+        }
+
+        let status = if model_count > 0 {
+            h_flex()
+                .gap_1()
+                .child(Icon::new(IconName::Check).color(Color::Success))
+                .child(Label::new(format!(
+                    "{model_count} {} available from {api_url}.",
+                    if model_count == 1 { "model" } else { "models" }
+                )))
+        } else {
+            h_flex()
+                .gap_1()
+                .child(Icon::new(IconName::ExclamationTriangle).color(Color::Warning))
+                .child(Label::new(format!(
+                    "No models available from {api_url}. Make sure the server is running, or list its models in `available_models`."
+                )))
+        };
+
+        v_flex()
+            .size_full()
+            .gap_2()
+            .on_action(cx.listener(Self::save_api_key))
+            .child(
+                h_flex().w_full().justify_between().child(status).child(
+                    Button::new("refresh-models", "Refresh")
+                        .icon(Some(IconName::RotateCw))
+                        .icon_size(IconSize::Small)
+                        .icon_position(IconPosition::Start)
+                        .on_click(cx.listener(|this, _, cx| this.refresh_models(cx))),
+                ),
+            )
+            .child(if has_api_key {
+                h_flex()
+                    .w_full()
+                    .justify_between()
+                    .child(Label::new("API key configured."))
+                    .child(
+                        Button::new("reset-key", "Reset key")
+                            .icon(Some(IconName::Trash))
+                            .icon_size(IconSize::Small)
+                            .icon_position(IconPosition::Start)
+                            .on_click(cx.listener(|this, _, cx| this.reset_api_key(cx))),
+                    )
+                    .into_any()
+            } else {
+                h_flex()
+                    .w_full()
+                    .px_2()
+                    .py_1()
+                    .bg(cx.theme().colors().editor_background)
+                    .rounded_md()
+                    .child(self.render_api_key_editor(cx))
+                    .into_any()
+            })
+            .into_any()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        settings::OpenAiCompatibleSettingsContent, LanguageModelRegistry,
+        LanguageModelRequestMessage, Role,
+    };
+    use futures::AsyncReadExt as _;
+    use gpui::{Context as _, TestAppContext, UpdateGlobal};
+    use http_client::{FakeHttpClient, Response};
+    use parking_lot::Mutex;
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_openai_compatible_provider(cx: &mut TestAppContext) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let http_client = FakeHttpClient::create({
+            let requests = requests.clone();
+            move |request| {
+                let requests = requests.clone();
+                async move {
+                    let uri = request.uri().to_string();
+                    let header = |name: &str| {
+                        request
+                            .headers()
+                            .get(name)
+                            .and_then(|value| value.to_str().ok())
+                            .map(ToString::to_string)
+                    };
+                    requests
+                        .lock()
+                        .push((uri.clone(), header("authorization"), header("x-team")));
+
+                    let body = match uri.as_str() {
+                        "http://localhost:8000/v1/models" => json!({
+                            "object": "list",
+                            "data": [
+                                { "id": "qwen-coder", "object": "model", "max_model_len": 32768 },
+                                { "id": "llama", "object": "model" },
+                            ]
+                        })
+                        .to_string(),
+                        "http://localhost:8000/v1/chat/completions" => [
+                            completion_event("Hello"),
+                            completion_event(" world"),
+                            "data: [DONE]\n\n".to_string(),
+                        ]
+                        .concat(),
+                        _ => {
+                            return Ok(Response::builder()
+                                .status(404)
+                                .body(Default::default())
+                                .unwrap())
+                        }
+                    };
+                    Ok(Response::new(body.into()))
+                }
+            }
+        });
+
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            AllLanguageModelSettings::register(cx);
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<AllLanguageModelSettings>(cx, |settings| {
+                    settings.openai_compatible = Some(vec![OpenAiCompatibleSettingsContent {
+                        name: "vLLM".into(),
+                        api_url: "http://localhost:8000/v1/".into(),
+                        headers: Some(BTreeMap::from_iter([(
+                            "X-Team".to_string(),
+                            "editor".to_string(),
+                        )])),
+                        low_speed_timeout_in_seconds: None,
+                        available_models: Some(vec![AvailableModel {
+                            name: "llama".into(),
+                            display_name: Some("Llama".into()),
+                            max_tokens: 4096,
+                            supports_tools: Some(true),
+                        }]),
+                        discover_models: None,
+                        max_tokens: None,
+                        supports_tools: None,
+                        fim_template: None,
+                    }]);
+                });
+            });
+        });
+
+        let registry = cx.new_model(|_| LanguageModelRegistry::default());
+        registry.update(cx, |registry, cx| {
+            registry.sync_openai_compatible_providers(http_client.clone(), cx)
+        });
+        cx.run_until_parked();
+
+        let provider = registry
+            .read_with(cx, |registry, _| registry.provider(&provider_id("vLLM")))
+            .unwrap();
+        let models = cx.update(|cx| {
+            assert!(provider.is_authenticated(cx));
+            provider.provided_models(cx)
+        });
+        assert_eq!(
+            models
+                .iter()
+                .map(|model| (model.id().0, model.name().0, model.max_token_count()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("llama".into(), "Llama".into(), 4096),
+                ("qwen-coder".into(), "qwen-coder".into(), 32768),
+            ]
+        );
+        assert_eq!(models[0].provider_name().0, "vLLM");
+
+        let request = LanguageModelRequest {
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: "Hi".into(),
+            }],
+            stop: Vec::new(),
+            temperature: 1.0,
+        };
+        let completion =
+            cx.update(|cx| models[1].stream_completion(request.clone(), &cx.to_async()));
+        let chunks = completion.await.unwrap().collect::<Vec<_>>().await;
+        assert_eq!(
+            chunks
+                .into_iter()
+                .collect::<Result<Vec<_>>>()
+                .unwrap()
+                .concat(),
+            "Hello world"
+        );
+        assert_eq!(
+            requests.lock().last().cloned(),
+            Some((
+                "http://localhost:8000/v1/chat/completions".to_string(),
+                None,
+                Some("editor".to_string())
+            ))
+        );
+
+        // Discovered models don't support tool calls unless configured to.
+        let tool_use = cx.update(|cx| {
+            models[1].use_any_tool(
+                request,
+                "tool".into(),
+                "A tool".into(),
+                json!({}),
+                &cx.to_async(),
+            )
+        });
+        assert!(tool_use.await.is_err());
+
+        // Removing the provider from the settings unregisters it.
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<AllLanguageModelSettings>(cx, |settings| {
+                    settings.openai_compatible = Some(Vec::new());
+                });
+            });
+        });
+        registry.update(cx, |registry, cx| {
+            registry.sync_openai_compatible_providers(http_client.clone(), cx)
+        });
+        assert!(registry
+            .read_with(cx, |registry, _| registry.provider(&provider_id("vLLM")))
+            .is_none());
+    }
+
+    #[gpui::test]
+    async fn test_openai_compatible_fim_completions(cx: &mut TestAppContext) {
+        let prompts = Arc::new(Mutex::new(Vec::new()));
+        let http_client = FakeHttpClient::create({
+            let prompts = prompts.clone();
+            move |request| {
+                let prompts = prompts.clone();
+                async move {
+                    let uri = request.uri().to_string();
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await.unwrap();
+                    if uri != "http://localhost:8000/v1/completions" {
+                        return Ok(Response::builder()
+                            .status(404)
+                            .body(Default::default())
+                            .unwrap());
+                    }
+
+                    let request = serde_json::from_str::<serde_json::Value>(&body).unwrap();
+                    prompts.lock().push(request["prompt"].clone());
+                    let body = [
+                        text_completion_event("let x"),
+                        ": \n".to_string(),
+                        text_completion_event(" = 1;"),
+                        "data: [DONE]\n\n".to_string(),
+                    ]
+                    .concat();
+                    Ok(Response::new(body.into()))
+                }
+            }
+        });
+
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            AllLanguageModelSettings::register(cx);
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<AllLanguageModelSettings>(cx, |settings| {
+                    settings.openai_compatible = Some(vec![OpenAiCompatibleSettingsContent {
+                        name: "llama.cpp".into(),
+                        api_url: "http://localhost:8000/v1".into(),
+                        headers: None,
+                        low_speed_timeout_in_seconds: None,
+                        available_models: Some(vec![AvailableModel {
+                            name: "qwen-coder".into(),
+                            display_name: None,
+                            max_tokens: 4096,
+                            supports_tools: None,
+                        }]),
+                        discover_models: Some(false),
+                        max_tokens: None,
+                        supports_tools: None,
+                        fim_template: Some("<PRE>{prefix}<SUF>{suffix}<MID>".into()),
+                    }]);
+                });
+            });
+        });
+
+        let registry = cx.new_model(|_| LanguageModelRegistry::default());
+        registry.update(cx, |registry, cx| {
+            registry.sync_openai_compatible_providers(http_client.clone(), cx)
+        });
+        cx.run_until_parked();
+        let model = cx.update(|cx| {
+            registry
+                .read(cx)
+                .provider(&provider_id("llama.cpp"))
+                .unwrap()
+                .provided_models(cx)
+                .remove(0)
+        });
+
+        let request = LanguageModelFimRequest {
+            prefix: "fn main() {\n    ".into(),
+            suffix: "\n}\n".into(),
+            language_name: Some("Rust".into()),
+            max_tokens: 16,
+            stop: Vec::new(),
+            temperature: 0.2,
+        };
+        let completion = cx.update(|cx| model.stream_fim_completion(request, &cx.to_async()));
+        let chunks = completion.await.unwrap().collect::<Vec<_>>().await;
+        assert_eq!(
+            chunks
+                .into_iter()
+                .collect::<Result<Vec<_>>>()
+                .unwrap()
+                .concat(),
+            "let x = 1;"
+        );
+        assert_eq!(
+            prompts.lock().as_slice(),
+            [json!("<PRE>fn main() {\n    <SUF>\n}\n<MID>")]
+        );
+    }
+
+    #[test]
+    fn test_fill_fim_template() {
+        assert_eq!(
+            fill_fim_template("<PRE>{prefix}<SUF>{suffix}<MID>", "a", "b"),
+            "<PRE>a<SUF>b<MID>"
+        );
+        // Placeholders in the text itself are left alone.
+        assert_eq!(
+            fill_fim_template("{prefix}|{suffix}", "{suffix}", "{prefix}"),
+            "{suffix}|{prefix}"
+        );
+        assert_eq!(fill_fim_template("{suffix}{prefix}", "a", "b"), "ba");
+    }
+
+    fn text_completion_event(text: &str) -> String {
+        let event = json!({
+            "choices": [{ "index": 0, "text": text, "finish_reason": null }],
+        });
+        format!("data: {event}\n\n")
+    }
+
+    fn completion_event(content: &str) -> String {
+        let event = json!({
+            "created": 0,
+            "model": "qwen-coder",
+            "choices": [{
+                "index": 0,
+                "delta": { "role": "assistant", "content": content },
+                "finish_reason": null,
+            }],
+        });
+        format!("data: {event}\n\n")
+    }
+}
@@ -1,15 +1,22 @@
 use crate::{
     provider::{
-        anthropic::AnthropicLanguageModelProvider, cloud::CloudLanguageModelProvider,
-        copilot_chat::CopilotChatLanguageModelProvider, google::GoogleLanguageModelProvider,
-        ollama::OllamaLanguageModelProvider, open_ai::OpenAiLanguageModelProvider,
+        anthropic::AnthropicLanguageModelProvider,
+        cloud::CloudLanguageModelProvider,
+        copilot_chat::CopilotChatLanguageModelProvider,
+        google::GoogleLanguageModelProvider,
+        ollama::OllamaLanguageModelProvider,
+        open_ai::OpenAiLanguageModelProvider,
+        open_ai_compatible::{self, OpenAiCompatibleLanguageModelProvider},
     },
+    settings::AllLanguageModelSettings,
     LanguageModel, LanguageModelId, LanguageModelProvider, LanguageModelProviderId,
-    LanguageModelProviderState,
+    LanguageModelProviderState, LanguageModelRequestLog,
 };
 use client::{Client, UserStore};
 use collections::BTreeMap;
 use gpui::{AppContext, EventEmitter, Global, Model, ModelContext};
+use http_client::HttpClient;
+use settings::{Settings, SettingsStore};
 use std::sync::Arc;
 use ui::Context;
 
@@ -48,6 +55,13 @@ fn register_language_model_providers(
     );
     registry.register_provider(CopilotChatLanguageModelProvider::new(cx), cx);
 
+    let http_client: Arc<dyn HttpClient> = client.http_client();
+    registry.sync_openai_compatible_providers(http_client.clone(), cx);
+    cx.observe_global::<SettingsStore>(move |registry, cx| {
+        registry.sync_openai_compatible_providers(http_client.clone(), cx);
+    })
+    .detach();
+
     cx.observe_flag::<feature_flags::LanguageModels, _>(move |enabled, cx| {
         let user_store = user_store.clone();
         let client = client.clone();
@@ -103,7 +117,7 @@ impl LanguageModelRegistry {
 
     #[cfg(any(test, feature = "test-support"))]
     pub fn test(cx: &mut AppContext) -> crate::provider::fake::FakeLanguageModelProvider {
-        let fake_provider = crate::provider::fake::FakeLanguageModelProvider;
+        let fake_provider = crate::provider::fake::FakeLanguageModelProvider::default();
         let registry = cx.new_model(|cx| {
             let mut registry = Self::default();
             registry.register_provider(fake_provider.clone(), cx);
@@ -143,6 +157,47 @@ impl LanguageModelRegistry {
         }
     }
 
+    /// Registers a provider for each OpenAI-compatible server in the settings, and unregisters
+    /// the providers of servers that were removed from them.
+    pub fn sync_openai_compatible_providers(
+        &mut self,
+        http_client: Arc<dyn HttpClient>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let names = AllLanguageModelSettings::get_global(cx)
+            .openai_compatible
+            .iter()
+            .map(|settings| settings.name.clone())
+            .collect::<Vec<_>>();
+
+        let stale_ids = self
+            .providers
+            .keys()
+            .filter(|id| {
+                open_ai_compatible::is_openai_compatible_provider(id)
+                    && !names
+                        .iter()
+                        .any(|name| open_ai_compatible::provider_id(name) == **id)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        for id in stale_ids {
+            self.unregister_provider(id, cx);
+        }
+
+        for name in names {
+            if !self
+                .providers
+                .contains_key(&open_ai_compatible::provider_id(&name))
+            {
+                self.register_provider(
+                    OpenAiCompatibleLanguageModelProvider::new(name, http_client.clone(), cx),
+                    cx,
+                );
+            }
+        }
+    }
+
     pub fn providers(&self) -> Vec<Arc<dyn LanguageModelProvider>> {
         let zed_provider_id = LanguageModelProviderId(crate::provider::cloud::PROVIDER_ID.into());
         let mut providers = Vec::with_capacity(self.providers.len());
@@ -208,7 +263,7 @@ impl LanguageModelRegistry {
             if let Some(provider) = self.providers.get(&provider_id).cloned() {
                 self.active_model = Some(ActiveModel {
                     provider,
-                    model: Some(model),
+                    model: Some(LanguageModelRequestLog::record(model, cx)),
                 });
                 cx.emit(Event::ActiveModelChanged);
             } else {
@@ -239,7 +294,7 @@ mod tests {
         let registry = cx.new_model(|_| LanguageModelRegistry::default());
 
         registry.update(cx, |registry, cx| {
-            registry.register_provider(FakeLanguageModelProvider, cx);
+            registry.register_provider(FakeLanguageModelProvider::default(), cx);
         });
 
         let providers = registry.read(cx).providers();
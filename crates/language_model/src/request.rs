@@ -14,6 +14,53 @@ pub struct LanguageModelRequest {
     pub temperature: f32,
 }
 
+/// A request to fill in the middle (FIM) of some text, such as the code around the cursor.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct LanguageModelFimRequest {
+    /// The text before the insertion point.
+    pub prefix: String,
+    /// The text after the insertion point.
+    pub suffix: String,
+    /// The name of the text's language, if any.
+    pub language_name: Option<String>,
+    pub max_tokens: usize,
+    pub stop: Vec<String>,
+    pub temperature: f32,
+}
+
+impl LanguageModelFimRequest {
+    /// Phrases the request as a conversation, for models that can only chat.
+    pub fn into_chat_request(self) -> LanguageModelRequest {
+        let fence = match self.language_name.as_deref() {
+            Some(language_name) => format!("```{}", language_name.to_lowercase()),
+            None => "```".to_string(),
+        };
+        LanguageModelRequest {
+            messages: vec![
+                LanguageModelRequestMessage {
+                    role: Role::System,
+                    content: format!(
+                        "You complete code. Reply with only the text that replaces {FIM_CURSOR_MARKER} \
+                         in the user's message, without explanations or Markdown code fences. \
+                         Reply with nothing if nothing should be inserted."
+                    ),
+                },
+                LanguageModelRequestMessage {
+                    role: Role::User,
+                    content: format!(
+                        "{fence}\n{}{FIM_CURSOR_MARKER}{}\n```",
+                        self.prefix, self.suffix
+                    ),
+                },
+            ],
+            stop: self.stop,
+            temperature: self.temperature,
+        }
+    }
+}
+
+const FIM_CURSOR_MARKER: &str = "<|CURSOR|>";
+
 impl LanguageModelRequest {
     pub fn into_open_ai(self, model: String) -> open_ai::Request {
         open_ai::Request {
@@ -0,0 +1,865 @@
+use crate::{
+    settings::AllLanguageModelSettings, LanguageModel, LanguageModelAvailability,
+    LanguageModelFimRequest, LanguageModelId, LanguageModelName, LanguageModelProviderId,
+    LanguageModelProviderName, LanguageModelRequest, Role,
+};
+use anyhow::{anyhow, Result};
+use collections::HashSet;
+use db::sqlez_macros::sql;
+use db::{define_connection, query};
+use futures::{
+    channel::mpsc,
+    future::BoxFuture,
+    stream::{self, BoxStream},
+    FutureExt, StreamExt,
+};
+use gpui::{
+    AppContext, AsyncAppContext, Context as _, EventEmitter, Global, Model, ModelContext, Task,
+};
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsStore};
+use std::{
+    fmt::Write as _,
+    sync::{
+        atomic::{AtomicBool, Ordering::SeqCst},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use util::ResultExt;
+
+define_connection!(
+    // Current schema shape using pseudo-rust syntax:
+    // language_model_requests(
+    //   id: i64,
+    //   recorded_at_ms: i64,
+    //   provider_id: String,
+    //   model_id: String,
+    //   request: String, // JSON-encoded `LanguageModelRequest`
+    //   tool: Option<String>, // JSON-encoded `LanguageModelRequestLogTool`
+    //   response: String,
+    //   error: Option<String>,
+    //   replay_of: Option<i64>,
+    //   preview: Option<String>, // The first line of the last user message
+    // )
+    pub static ref DB: LanguageModelRequestLogDb<()> =
+        &[sql!(
+            CREATE TABLE language_model_requests(
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                recorded_at_ms INTEGER NOT NULL,
+                provider_id TEXT NOT NULL,
+                model_id TEXT NOT NULL,
+                request TEXT NOT NULL,
+                tool TEXT,
+                response TEXT NOT NULL,
+                error TEXT,
+                replay_of INTEGER
+            ) STRICT;
+        ),
+        sql!(
+            ALTER TABLE language_model_requests ADD COLUMN preview TEXT;
+        )];
+);
+
+/// The number of most recent entries kept in the log.
+const MAX_ENTRY_COUNT: i64 = 500;
+
+impl LanguageModelRequestLogDb {
+    query! {
+        async fn insert_entry(
+            recorded_at_ms: i64,
+            provider_id: String,
+            model_id: String,
+            request: String,
+            tool: Option<String>,
+            response: String,
+            error: Option<String>,
+            replay_of: Option<i64>,
+            preview: String
+        ) -> Result<Option<i64>> {
+            INSERT INTO language_model_requests
+                (recorded_at_ms, provider_id, model_id, request, tool, response, error, replay_of, preview)
+            VALUES
+                (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            RETURNING id
+        }
+    }
+
+    query! {
+        async fn prune_entries(kept_count: i64) -> Result<()> {
+            DELETE FROM language_model_requests
+            WHERE id NOT IN (
+                SELECT id FROM language_model_requests
+                ORDER BY id DESC
+                LIMIT ?
+            )
+        }
+    }
+
+    query! {
+        fn entry_summaries() -> Result<Vec<(i64, i64, String, String, Option<String>, bool, Option<i64>)>> {
+            SELECT id, recorded_at_ms, provider_id, model_id, preview, error IS NOT NULL, replay_of
+            FROM language_model_requests
+            ORDER BY id DESC
+        }
+    }
+
+    query! {
+        fn entry(id: i64) -> Result<Option<(i64, i64, String, String, String, Option<String>, String, Option<String>, Option<i64>)>> {
+            SELECT id, recorded_at_ms, provider_id, model_id, request, tool, response, error, replay_of
+            FROM language_model_requests
+            WHERE id = ?
+        }
+    }
+
+    query! {
+        fn entries_containing(text: String) -> Result<Vec<i64>> {
+            SELECT id
+            FROM language_model_requests
+            WHERE instr(lower(request), ?1) > 0 OR instr(lower(response), ?1) > 0
+        }
+    }
+
+    query! {
+        async fn delete_entry(id: i64) -> Result<()> {
+            DELETE FROM language_model_requests
+            WHERE id = ?
+        }
+    }
+
+    query! {
+        async fn clear_entries() -> Result<()> {
+            DELETE FROM language_model_requests
+        }
+    }
+}
+
+pub fn init(cx: &mut AppContext) {
+    let log = cx.new_model(LanguageModelRequestLog::new);
+    cx.set_global(GlobalLanguageModelRequestLog(log));
+}
+
+struct GlobalLanguageModelRequestLog(Model<LanguageModelRequestLog>);
+
+impl Global for GlobalLanguageModelRequestLog {}
+
+/// The tool a recorded request asked the model to use, if any.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LanguageModelRequestLogTool {
+    pub name: String,
+    pub description: String,
+    pub schema: serde_json::Value,
+}
+
+/// A request sent to a language model, along with the response it produced.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LanguageModelRequestLogEntry {
+    pub id: i64,
+    pub recorded_at: SystemTime,
+    pub provider_id: LanguageModelProviderId,
+    pub model_id: LanguageModelId,
+    pub request: LanguageModelRequest,
+    pub tool: Option<LanguageModelRequestLogTool>,
+    pub response: String,
+    /// The error the completion failed with, or `None` if it finished successfully.
+    pub error: Option<String>,
+    /// The entry this one was replayed from.
+    pub replay_of: Option<i64>,
+}
+
+impl LanguageModelRequestLogEntry {
+    /// Renders the entry as Markdown, for viewing, searching and diffing entries.
+    pub fn to_markdown(&self) -> String {
+        let mut text = String::new();
+        writeln!(
+            text,
+            "# Request\n\nModel: {}/{}\nTemperature: {}",
+            self.provider_id.0, self.model_id.0, self.request.temperature
+        )
+        .unwrap();
+        if !self.request.stop.is_empty() {
+            writeln!(text, "Stop: {:?}", self.request.stop).unwrap();
+        }
+        if let Some(tool) = &self.tool {
+            writeln!(text, "Tool: {} ({})", tool.name, tool.description).unwrap();
+        }
+        for message in &self.request.messages {
+            write!(text, "\n## {}\n\n{}\n", message.role, message.content).unwrap();
+        }
+        write!(text, "\n# Response\n\n{}\n", self.response).unwrap();
+        if let Some(error) = &self.error {
+            write!(text, "\n# Error\n\n{}\n", error).unwrap();
+        }
+        text
+    }
+
+    fn from_row(
+        (id, recorded_at_ms, provider_id, model_id, request, tool, response, error, replay_of): (
+            i64,
+            i64,
+            String,
+            String,
+            String,
+            Option<String>,
+            String,
+            Option<String>,
+            Option<i64>,
+        ),
+    ) -> Result<Self> {
+        Ok(Self {
+            id,
+            recorded_at: recorded_at_from_ms(recorded_at_ms),
+            provider_id: LanguageModelProviderId::from(provider_id),
+            model_id: LanguageModelId::from(model_id),
+            request: serde_json::from_str(&request)?,
+            tool: tool.map(|tool| serde_json::from_str(&tool)).transpose()?,
+            response,
+            error,
+            replay_of,
+        })
+    }
+}
+
+/// The parts of an entry shown when listing the log, which are loaded without the full request
+/// and response.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LanguageModelRequestLogEntrySummary {
+    pub id: i64,
+    pub recorded_at: SystemTime,
+    pub provider_id: LanguageModelProviderId,
+    pub model_id: LanguageModelId,
+    /// The first line of the last message the user sent.
+    pub preview: String,
+    pub failed: bool,
+    pub replay_of: Option<i64>,
+}
+
+impl LanguageModelRequestLogEntrySummary {
+    fn from_row(
+        (id, recorded_at_ms, provider_id, model_id, preview, failed, replay_of): (
+            i64,
+            i64,
+            String,
+            String,
+            Option<String>,
+            bool,
+            Option<i64>,
+        ),
+    ) -> Self {
+        Self {
+            id,
+            recorded_at: recorded_at_from_ms(recorded_at_ms),
+            provider_id: LanguageModelProviderId::from(provider_id),
+            model_id: LanguageModelId::from(model_id),
+            preview: preview.unwrap_or_default(),
+            failed,
+            replay_of,
+        }
+    }
+}
+
+fn recorded_at_from_ms(recorded_at_ms: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(recorded_at_ms.max(0) as u64)
+}
+
+pub enum LanguageModelRequestLogEvent {
+    EntryRecorded(i64),
+    EntriesRemoved,
+}
+
+/// Records the requests sent to language models and the responses they streamed back, so they
+/// can be inspected and replayed later. Recording is opt-in via the `language_models.log_requests`
+/// setting, and only the most recent [`MAX_ENTRY_COUNT`] entries are kept.
+pub struct LanguageModelRequestLog {
+    recorder: RequestRecorder,
+    _write_entries: Task<()>,
+    _settings_subscription: gpui::Subscription,
+}
+
+impl EventEmitter<LanguageModelRequestLogEvent> for LanguageModelRequestLog {}
+
+impl LanguageModelRequestLog {
+    fn new(cx: &mut ModelContext<Self>) -> Self {
+        let (entries_tx, mut entries_rx) = mpsc::unbounded::<PendingEntry>();
+        let write_entries = cx.spawn(|this, mut cx| async move {
+            while let Some(entry) = entries_rx.next().await {
+                if let Some(id) = entry.write().await.log_err() {
+                    this.update(&mut cx, |_, cx| {
+                        cx.emit(LanguageModelRequestLogEvent::EntryRecorded(id))
+                    })
+                    .ok();
+                }
+            }
+        });
+
+        let enabled = AllLanguageModelSettings::get_global(cx).log_requests;
+        Self {
+            recorder: RequestRecorder {
+                enabled: Arc::new(AtomicBool::new(enabled)),
+                entries_tx,
+            },
+            _write_entries: write_entries,
+            _settings_subscription: cx.observe_global::<SettingsStore>(|this, cx| {
+                let enabled = AllLanguageModelSettings::get_global(cx).log_requests;
+                this.recorder.enabled.store(enabled, SeqCst);
+            }),
+        }
+    }
+
+    pub fn global(cx: &AppContext) -> Option<Model<Self>> {
+        cx.try_global::<GlobalLanguageModelRequestLog>()
+            .map(|log| log.0.clone())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.recorder.enabled.load(SeqCst)
+    }
+
+    /// Wraps the given model so that requests sent through it are recorded while logging is
+    /// enabled.
+    pub(crate) fn record(model: Arc<dyn LanguageModel>, cx: &AppContext) -> Arc<dyn LanguageModel> {
+        if let Some(log) = cx.try_global::<GlobalLanguageModelRequestLog>() {
+            Arc::new(RecordingLanguageModel {
+                model,
+                recorder: log.0.read(cx).recorder.clone(),
+            })
+        } else {
+            model
+        }
+    }
+
+    /// Returns summaries of the recorded entries, most recent first.
+    pub fn entries(
+        &self,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<LanguageModelRequestLogEntrySummary>>> {
+        cx.background_executor().spawn(async move {
+            Ok(DB
+                .entry_summaries()?
+                .into_iter()
+                .map(LanguageModelRequestLogEntrySummary::from_row)
+                .collect())
+        })
+    }
+
+    /// Loads the full request and response of an entry.
+    pub fn entry(
+        &self,
+        id: i64,
+        cx: &AppContext,
+    ) -> Task<Result<Option<LanguageModelRequestLogEntry>>> {
+        cx.background_executor().spawn(async move {
+            DB.entry(id)?
+                .map(LanguageModelRequestLogEntry::from_row)
+                .transpose()
+        })
+    }
+
+    /// Returns the ids of the entries whose request or response contains every word of the
+    /// query, ignoring case.
+    pub fn search(&self, query: String, cx: &AppContext) -> Task<Result<HashSet<i64>>> {
+        cx.background_executor().spawn(async move {
+            let mut matching_ids: Option<HashSet<i64>> = None;
+            for word in query.to_lowercase().split_whitespace() {
+                let ids = DB.entries_containing(word.to_string())?;
+                matching_ids = Some(match matching_ids {
+                    Some(matching_ids) => ids
+                        .into_iter()
+                        .filter(|id| matching_ids.contains(id))
+                        .collect(),
+                    None => ids.into_iter().collect(),
+                });
+            }
+            match matching_ids {
+                Some(matching_ids) => Ok(matching_ids),
+                None => Ok(DB.entry_summaries()?.into_iter().map(|row| row.0).collect()),
+            }
+        })
+    }
+
+    pub fn delete_entry(&mut self, id: i64, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        cx.spawn(|this, mut cx| async move {
+            DB.delete_entry(id).await?;
+            this.update(&mut cx, |_, cx| {
+                cx.emit(LanguageModelRequestLogEvent::EntriesRemoved)
+            })
+        })
+    }
+
+    pub fn clear(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        cx.spawn(|this, mut cx| async move {
+            DB.clear_entries().await?;
+            this.update(&mut cx, |_, cx| {
+                cx.emit(LanguageModelRequestLogEvent::EntriesRemoved)
+            })
+        })
+    }
+
+    /// Sends a recorded request to the given model and records the response as a new entry,
+    /// regardless of whether logging is enabled. Returns the id of the new entry.
+    ///
+    /// `model` should be one of the models returned by [`crate::LanguageModelRegistry::available_models`],
+    /// so that the replayed request isn't recorded twice.
+    pub fn replay(
+        &mut self,
+        entry: &LanguageModelRequestLogEntry,
+        model: Arc<dyn LanguageModel>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<i64>> {
+        let mut pending =
+            PendingEntry::new(model.as_ref(), entry.request.clone(), entry.tool.clone());
+        pending.replay_of = Some(entry.id);
+
+        cx.spawn(|this, mut cx| async move {
+            if let Some(tool) = pending.tool.clone() {
+                let response = model
+                    .use_any_tool(
+                        pending.request.clone(),
+                        tool.name,
+                        tool.description,
+                        tool.schema,
+                        &cx,
+                    )
+                    .await;
+                pending.record_tool_response(&response);
+            } else {
+                match model.stream_completion(pending.request.clone(), &cx).await {
+                    Ok(mut chunks) => {
+                        while let Some(chunk) = chunks.next().await {
+                            match chunk {
+                                Ok(chunk) => pending.response.push_str(&chunk),
+                                Err(error) => {
+                                    pending.error = Some(error.to_string());
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                    Err(error) => pending.error = Some(error.to_string()),
+                }
+            }
+
+            let id = pending.write().await?;
+            this.update(&mut cx, |_, cx| {
+                cx.emit(LanguageModelRequestLogEvent::EntryRecorded(id))
+            })?;
+            Ok(id)
+        })
+    }
+}
+
+struct PendingEntry {
+    recorded_at: SystemTime,
+    provider_id: LanguageModelProviderId,
+    model_id: LanguageModelId,
+    request: LanguageModelRequest,
+    tool: Option<LanguageModelRequestLogTool>,
+    response: String,
+    error: Option<String>,
+    replay_of: Option<i64>,
+}
+
+impl PendingEntry {
+    fn new(
+        model: &dyn LanguageModel,
+        request: LanguageModelRequest,
+        tool: Option<LanguageModelRequestLogTool>,
+    ) -> Self {
+        Self {
+            recorded_at: SystemTime::now(),
+            provider_id: model.provider_id(),
+            model_id: model.id(),
+            request,
+            tool,
+            response: String::new(),
+            error: None,
+            replay_of: None,
+        }
+    }
+
+    fn record_tool_response(&mut self, response: &Result<serde_json::Value>) {
+        match response {
+            Ok(value) => {
+                self.response = serde_json::to_string_pretty(value).unwrap_or_default();
+            }
+            Err(error) => self.error = Some(error.to_string()),
+        }
+    }
+
+    async fn write(self) -> Result<i64> {
+        let recorded_at_ms = self
+            .recorded_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64;
+        let tool = self.tool.as_ref().map(serde_json::to_string).transpose()?;
+        let preview = self
+            .request
+            .messages
+            .iter()
+            .rev()
+            .find(|message| message.role == Role::User)
+            .and_then(|message| message.content.lines().find(|line| !line.trim().is_empty()))
+            .unwrap_or_default()
+            .to_string();
+        let id = DB
+            .insert_entry(
+                recorded_at_ms,
+                self.provider_id.0.to_string(),
+                self.model_id.0.to_string(),
+                serde_json::to_string(&self.request)?,
+                tool,
+                self.response,
+                self.error,
+                self.replay_of,
+                preview,
+            )
+            .await?
+            .ok_or_else(|| anyhow!("failed to insert language model request log entry"))?;
+        DB.prune_entries(MAX_ENTRY_COUNT).await?;
+        Ok(id)
+    }
+}
+
+/// Queues recorded entries for writing while logging is enabled.
+#[derive(Clone)]
+struct RequestRecorder {
+    enabled: Arc<AtomicBool>,
+    entries_tx: mpsc::UnboundedSender<PendingEntry>,
+}
+
+impl RequestRecorder {
+    fn start(
+        &self,
+        model: &dyn LanguageModel,
+        request: &LanguageModelRequest,
+        tool: Option<LanguageModelRequestLogTool>,
+    ) -> Option<PendingRecording> {
+        if self.enabled.load(SeqCst) {
+            Some(PendingRecording {
+                entry: Some(PendingEntry::new(model, request.clone(), tool)),
+                entries_tx: self.entries_tx.clone(),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Accumulates a response while it streams in, and queues the entry for writing once the
+/// response finishes, fails or is dropped.
+struct PendingRecording {
+    entry: Option<PendingEntry>,
+    entries_tx: mpsc::UnboundedSender<PendingEntry>,
+}
+
+impl PendingRecording {
+    fn push_chunk(&mut self, chunk: &str) {
+        if let Some(entry) = self.entry.as_mut() {
+            entry.response.push_str(chunk);
+        }
+    }
+
+    fn finish(&mut self, error: Option<String>) {
+        if let Some(mut entry) = self.entry.take() {
+            entry.error = error;
+            self.entries_tx.unbounded_send(entry).ok();
+        }
+    }
+
+    fn record_stream(
+        self,
+        chunks: BoxStream<'static, Result<String>>,
+    ) -> BoxStream<'static, Result<String>> {
+        stream::unfold((chunks, self), |(mut chunks, mut recording)| async move {
+            match chunks.next().await {
+                Some(Ok(chunk)) => {
+                    recording.push_chunk(&chunk);
+                    Some((Ok(chunk), (chunks, recording)))
+                }
+                Some(Err(error)) => {
+                    recording.finish(Some(error.to_string()));
+                    Some((Err(error), (chunks, recording)))
+                }
+                None => {
+                    recording.finish(None);
+                    None
+                }
+            }
+        })
+        .boxed()
+    }
+}
+
+impl Drop for PendingRecording {
+    fn drop(&mut self) {
+        self.finish(Some("Canceled before the response finished".into()));
+    }
+}
+
+/// Wraps a [`LanguageModel`], recording the requests sent through it to the
+/// [`LanguageModelRequestLog`] when logging is enabled.
+struct RecordingLanguageModel {
+    model: Arc<dyn LanguageModel>,
+    recorder: RequestRecorder,
+}
+
+impl LanguageModel for RecordingLanguageModel {
+    fn id(&self) -> LanguageModelId {
+        self.model.id()
+    }
+
+    fn name(&self) -> LanguageModelName {
+        self.model.name()
+    }
+
+    fn provider_id(&self) -> LanguageModelProviderId {
+        self.model.provider_id()
+    }
+
+    fn provider_name(&self) -> LanguageModelProviderName {
+        self.model.provider_name()
+    }
+
+    fn telemetry_id(&self) -> String {
+        self.model.telemetry_id()
+    }
+
+    fn availability(&self) -> LanguageModelAvailability {
+        self.model.availability()
+    }
+
+    fn max_token_count(&self) -> usize {
+        self.model.max_token_count()
+    }
+
+    fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AppContext,
+    ) -> BoxFuture<'static, Result<usize>> {
+        self.model.count_tokens(request, cx)
+    }
+
+    fn stream_completion(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AsyncAppContext,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+        let recording = self.recorder.start(self.model.as_ref(), &request, None);
+        let completion = self.model.stream_completion(request, cx);
+        let Some(mut recording) = recording else {
+            return completion;
+        };
+
+        async move {
+            match completion.await {
+                Ok(chunks) => Ok(recording.record_stream(chunks)),
+                Err(error) => {
+                    recording.finish(Some(error.to_string()));
+                    Err(error)
+                }
+            }
+        }
+        .boxed()
+    }
+
+    fn use_any_tool(
+        &self,
+        request: LanguageModelRequest,
+        name: String,
+        description: String,
+        schema: serde_json::Value,
+        cx: &AsyncAppContext,
+    ) -> BoxFuture<'static, Result<serde_json::Value>> {
+        let tool = LanguageModelRequestLogTool {
+            name: name.clone(),
+            description: description.clone(),
+            schema: schema.clone(),
+        };
+        let recording = self
+            .recorder
+            .start(self.model.as_ref(), &request, Some(tool));
+        let response = self
+            .model
+            .use_any_tool(request, name, description, schema, cx);
+        let Some(mut recording) = recording else {
+            return response;
+        };
+
+        async move {
+            let response = response.await;
+            if let Some(entry) = recording.entry.as_mut() {
+                entry.record_tool_response(&response);
+                let error = entry.error.take();
+                recording.finish(error);
+            }
+            response
+        }
+        .boxed()
+    }
+
+    // Fill-in-the-middle requests are made as the user types, so they aren't recorded.
+    fn stream_fim_completion(
+        &self,
+        request: LanguageModelFimRequest,
+        cx: &AsyncAppContext,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+        self.model.stream_fim_completion(request, cx)
+    }
+
+    #[cfg(any(test, feature = "test-support"))]
+    fn as_fake(&self) -> &crate::provider::fake::FakeLanguageModel {
+        self.model.as_fake()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LanguageModelRegistry, LanguageModelRequestMessage, Role};
+    use gpui::{TestAppContext, UpdateGlobal};
+
+    #[gpui::test]
+    async fn test_recording_and_replaying_requests(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            AllLanguageModelSettings::register(cx);
+            init(cx);
+            LanguageModelRegistry::test(cx);
+        });
+        let log = cx.update(|cx| LanguageModelRequestLog::global(cx).unwrap());
+        log.update(cx, |log, cx| log.clear(cx)).await.unwrap();
+
+        let request = LanguageModelRequest {
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: "Hello".into(),
+            }],
+            stop: Vec::new(),
+            temperature: 1.0,
+        };
+        let model = cx.update(|cx| {
+            LanguageModelRegistry::read_global(cx)
+                .active_model()
+                .unwrap()
+        });
+
+        // Nothing is recorded until logging is enabled.
+        let completion = cx.update(|cx| model.stream_completion(request.clone(), &cx.to_async()));
+        let mut chunks = completion.await.unwrap();
+        model.as_fake().stream_last_completion_response("Hi".into());
+        model.as_fake().end_last_completion_stream();
+        while chunks.next().await.is_some() {}
+        cx.run_until_parked();
+        let entries = log.update(cx, |log, cx| log.entries(cx)).await.unwrap();
+        assert!(entries.is_empty());
+
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<AllLanguageModelSettings>(cx, |settings| {
+                    settings.log_requests = Some(true);
+                });
+            });
+        });
+
+        let completion = cx.update(|cx| model.stream_completion(request.clone(), &cx.to_async()));
+        let mut chunks = completion.await.unwrap();
+        model
+            .as_fake()
+            .stream_last_completion_response("Hi ".into());
+        model
+            .as_fake()
+            .stream_last_completion_response("there".into());
+        model.as_fake().end_last_completion_stream();
+        while chunks.next().await.is_some() {}
+        cx.run_until_parked();
+
+        let entries = log.update(cx, |log, cx| log.entries(cx)).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].preview, "Hello");
+        assert!(!entries[0].failed);
+        assert_eq!(
+            entries[0].model_id,
+            crate::provider::fake::language_model_id()
+        );
+        let entry = load_entry(&log, entries[0].id, cx).await;
+        assert_eq!(entry.request, request);
+        assert_eq!(entry.response, "Hi there");
+        assert_eq!(entry.error, None);
+
+        // Dropping a response before it finishes records it as canceled.
+        let completion = cx.update(|cx| model.stream_completion(request.clone(), &cx.to_async()));
+        let mut chunks = completion.await.unwrap();
+        model
+            .as_fake()
+            .stream_last_completion_response("Partial".into());
+        chunks.next().await;
+        drop(chunks);
+        model.as_fake().end_last_completion_stream();
+        cx.run_until_parked();
+
+        let entries = log.update(cx, |log, cx| log.entries(cx)).await.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].failed);
+        let entry = load_entry(&log, entries[0].id, cx).await;
+        assert_eq!(entry.response, "Partial");
+        assert!(entry.error.is_some());
+
+        // Searching matches the words of the query anywhere in the requests and responses.
+        let search = |query: &str, cx: &mut TestAppContext| {
+            log.read_with(cx, |log, cx| log.search(query.into(), cx))
+        };
+        assert_eq!(
+            search("HELLO partial", cx).await.unwrap(),
+            HashSet::from_iter([entries[0].id])
+        );
+        assert_eq!(search("there", cx).await.unwrap().len(), 1);
+        assert_eq!(search("", cx).await.unwrap().len(), 2);
+        assert!(search("missing", cx).await.unwrap().is_empty());
+
+        // Replaying re-sends the recorded request and links the new entry to the original.
+        let replay_model =
+            cx.update(|cx| LanguageModelRegistry::read_global(cx).available_models(cx)[0].clone());
+        let original = load_entry(&log, entries[1].id, cx).await;
+        let replay = log.update(cx, |log, cx| {
+            log.replay(&original, replay_model.clone(), cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(replay_model.as_fake().pending_completions(), vec![request]);
+        replay_model
+            .as_fake()
+            .stream_last_completion_response("Hello!".into());
+        replay_model.as_fake().end_last_completion_stream();
+        let replay_id = replay.await.unwrap();
+
+        let entries = log.update(cx, |log, cx| log.entries(cx)).await.unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].id, replay_id);
+        assert_eq!(entries[0].replay_of, Some(entries[2].id));
+        assert_eq!(load_entry(&log, replay_id, cx).await.response, "Hello!");
+
+        // Only the most recent entries are kept.
+        DB.prune_entries(2).await.unwrap();
+        let kept_ids = log
+            .update(cx, |log, cx| log.entries(cx))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect::<Vec<_>>();
+        assert_eq!(kept_ids, [entries[0].id, entries[1].id]);
+    }
+
+    async fn load_entry(
+        log: &Model<LanguageModelRequestLog>,
+        id: i64,
+        cx: &mut TestAppContext,
+    ) -> LanguageModelRequestLogEntry {
+        log.read_with(cx, |log, cx| log.entry(id, cx))
+            .await
+            .unwrap()
+            .unwrap()
+    }
+}
@@ -1,6 +1,7 @@
 use std::{sync::Arc, time::Duration};
 
 use anyhow::Result;
+use collections::BTreeMap;
 use gpui::AppContext;
 use project::Fs;
 use schemars::JsonSchema;
@@ -15,6 +16,7 @@ use crate::provider::{
     google::GoogleSettings,
     ollama::OllamaSettings,
     open_ai::OpenAiSettings,
+    open_ai_compatible::OpenAiCompatibleSettings,
 };
 
 /// Initializes the language model settings.
@@ -58,6 +60,8 @@ pub struct AllLanguageModelSettings {
     pub zed_dot_dev: ZedDotDevSettings,
     pub google: GoogleSettings,
     pub copilot_chat: CopilotChatSettings,
+    pub openai_compatible: Vec<OpenAiCompatibleSettings>,
+    pub log_requests: bool,
 }
 
 #[derive(Default, Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
@@ -69,6 +73,14 @@ pub struct AllLanguageModelSettingsContent {
     pub zed_dot_dev: Option<ZedDotDevSettingsContent>,
     pub google: Option<GoogleSettingsContent>,
     pub copilot_chat: Option<CopilotChatSettingsContent>,
+    /// Servers that implement the OpenAI API, such as vLLM or llama.cpp. Each one is
+    /// offered as a separate provider.
+    pub openai_compatible: Option<Vec<OpenAiCompatibleSettingsContent>>,
+    /// Whether to record every request sent to a language model, along with its response, to a
+    /// local log that can be browsed with `assistant: open request log`.
+    ///
+    /// Default: false
+    pub log_requests: Option<bool>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
@@ -212,6 +224,55 @@ pub struct CopilotChatSettingsContent {
     low_speed_timeout_in_seconds: Option<u64>,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct OpenAiCompatibleSettingsContent {
+    /// The name of the provider, shown in the model selector.
+    pub name: String,
+    /// The base URL of the API, e.g. "http://localhost:8000/v1".
+    pub api_url: String,
+    /// Additional headers to send with every request.
+    pub headers: Option<BTreeMap<String, String>>,
+    pub low_speed_timeout_in_seconds: Option<u64>,
+    /// The models served by this provider. These are offered in addition to the discovered
+    /// models, and override discovered models with the same name.
+    pub available_models: Option<Vec<provider::open_ai_compatible::AvailableModel>>,
+    /// Whether to list the served models using the `/models` endpoint.
+    ///
+    /// Default: true
+    pub discover_models: Option<bool>,
+    /// The context size of discovered models whose context size isn't reported by the server.
+    ///
+    /// Default: 8192
+    pub max_tokens: Option<usize>,
+    /// Whether discovered models support tool calls.
+    ///
+    /// Default: false
+    pub supports_tools: Option<bool>,
+    /// The prompt to send to the `/completions` endpoint for fill-in-the-middle completions,
+    /// with `{prefix}` and `{suffix}` placeholders, e.g.
+    /// "<|fim_prefix|>{prefix}<|fim_suffix|>{suffix}<|fim_middle|>". When unset, the model is
+    /// asked to fill in the middle in a conversation.
+    pub fim_template: Option<String>,
+}
+
+impl OpenAiCompatibleSettingsContent {
+    fn settings(&self) -> OpenAiCompatibleSettings {
+        OpenAiCompatibleSettings {
+            name: self.name.clone(),
+            api_url: self.api_url.trim_end_matches('/').to_string(),
+            headers: self.headers.clone().unwrap_or_default(),
+            low_speed_timeout: self.low_speed_timeout_in_seconds.map(Duration::from_secs),
+            available_models: self.available_models.clone().unwrap_or_default(),
+            discover_models: self.discover_models.unwrap_or(true),
+            max_tokens: self
+                .max_tokens
+                .unwrap_or(provider::open_ai_compatible::DEFAULT_MAX_TOKENS),
+            supports_tools: self.supports_tools.unwrap_or(false),
+            fim_template: self.fim_template.clone(),
+        }
+    }
+}
+
 impl settings::Settings for AllLanguageModelSettings {
     const KEY: Option<&'static str> = Some("language_models");
 
@@ -329,6 +390,18 @@ impl settings::Settings for AllLanguageModelSettings {
                 settings.copilot_chat.low_speed_timeout =
                     Some(Duration::from_secs(low_speed_timeout));
             }
+
+            merge(
+                &mut settings.openai_compatible,
+                value.openai_compatible.as_ref().map(|providers| {
+                    providers
+                        .iter()
+                        .map(OpenAiCompatibleSettingsContent::settings)
+                        .collect()
+                }),
+            );
+
+            merge(&mut settings.log_requests, value.log_requests);
         }
 
         Ok(settings)
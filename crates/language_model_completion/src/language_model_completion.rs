@@ -0,0 +1,491 @@
+use anyhow::Result;
+use client::telemetry::Telemetry;
+use editor::{Direction, InlineCompletionProvider};
+use futures::StreamExt as _;
+use gpui::{AppContext, EntityId, Model, ModelContext, Task};
+use language::{
+    language_settings::{all_language_settings, LanguageModelInlineCompletionSettings},
+    Anchor, Buffer, BufferSnapshot, ToOffset,
+};
+use language_model::{
+    LanguageModel, LanguageModelFimRequest, LanguageModelProviderId, LanguageModelRegistry,
+};
+use std::{ops::Range, path::Path, sync::Arc};
+use text::{Bias, Point};
+
+/// Supplies inline completions by asking a language model to fill in the text at the cursor.
+pub struct LanguageModelCompletionProvider {
+    completion: Option<CurrentCompletion>,
+    file_extension: Option<String>,
+    pending_refresh: Task<Result<()>>,
+    telemetry: Option<Arc<Telemetry>>,
+}
+
+struct CurrentCompletion {
+    buffer_id: EntityId,
+    /// Where the completion was requested. Text typed here is matched against the completion.
+    position: Anchor,
+    text: String,
+}
+
+impl LanguageModelCompletionProvider {
+    pub fn new() -> Self {
+        Self {
+            completion: None,
+            file_extension: None,
+            pending_refresh: Task::ready(Ok(())),
+            telemetry: None,
+        }
+    }
+
+    pub fn with_telemetry(mut self, telemetry: Arc<Telemetry>) -> Self {
+        self.telemetry = Some(telemetry);
+        self
+    }
+
+    fn report_completion_event(&self, accepted: bool) {
+        if self.completion.is_some() {
+            if let Some(telemetry) = self.telemetry.as_ref() {
+                telemetry.report_inline_completion_event(
+                    Self::name().to_string(),
+                    accepted,
+                    self.file_extension.clone(),
+                );
+            }
+        }
+    }
+}
+
+impl Default for LanguageModelCompletionProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the model configured for inline completions.
+///
+/// There is no fallback to the assistant's model, so that the code around the cursor is only
+/// ever sent to a provider that was chosen for completions.
+fn completion_model(
+    settings: &LanguageModelInlineCompletionSettings,
+    cx: &AppContext,
+) -> Option<Arc<dyn LanguageModel>> {
+    let provider_id = settings.provider.clone()?;
+    let provider = LanguageModelRegistry::read_global(cx)
+        .provider(&LanguageModelProviderId::from(provider_id))?;
+    if !provider.is_authenticated(cx) {
+        return None;
+    }
+    let mut models = provider.provided_models(cx).into_iter();
+    match settings.model.as_deref() {
+        Some(model_id) => models.find(|model| model.id().0.as_ref() == model_id),
+        None => models.next(),
+    }
+}
+
+impl InlineCompletionProvider for LanguageModelCompletionProvider {
+    fn name() -> &'static str {
+        "language_model"
+    }
+
+    fn is_enabled(&self, buffer: &Model<Buffer>, cursor_position: Anchor, cx: &AppContext) -> bool {
+        let buffer = buffer.read(cx);
+        let file = buffer.file();
+        let language = buffer.language_at(cursor_position);
+        let settings = all_language_settings(file, cx);
+        settings.inline_completions_enabled(language.as_ref(), file.map(|f| f.path().as_ref()))
+            && completion_model(&settings.inline_completions.language_model, cx).is_some()
+    }
+
+    fn refresh(
+        &mut self,
+        buffer: Model<Buffer>,
+        cursor_position: Anchor,
+        debounce: bool,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let settings = all_language_settings(buffer.read(cx).file(), cx)
+            .inline_completions
+            .language_model
+            .clone();
+        let Some(model) = completion_model(&settings, cx) else {
+            return;
+        };
+
+        let snapshot = buffer.read(cx).snapshot();
+        let offset = cursor_position.to_offset(&snapshot);
+        let position = snapshot.anchor_before(offset);
+        let (prefix, suffix) = fim_context(
+            &snapshot,
+            offset,
+            settings.max_prefix_chars,
+            settings.max_suffix_chars,
+        );
+        let request = LanguageModelFimRequest {
+            prefix,
+            suffix: suffix.clone(),
+            language_name: snapshot
+                .language_at(offset)
+                .map(|language| language.name().to_string()),
+            max_tokens: settings.max_tokens,
+            stop: Vec::new(),
+            temperature: 0.2,
+        };
+
+        // Replacing the pending refresh drops it, which cancels its request.
+        self.pending_refresh = cx.spawn(|this, mut cx| async move {
+            if debounce {
+                cx.background_executor().timer(settings.debounce).await;
+            }
+
+            let mut chunks = model.stream_fim_completion(request, &cx).await?;
+            let mut text = String::new();
+            loop {
+                let chunk = chunks.next().await;
+                let is_finished = chunk.is_none();
+                if let Some(chunk) = chunk {
+                    text.push_str(&chunk?);
+                }
+                let (completion, is_complete) =
+                    clean_completion(&text, &suffix, settings.max_lines, is_finished);
+                this.update(&mut cx, |this, cx| {
+                    this.completion = Some(CurrentCompletion {
+                        buffer_id: buffer.entity_id(),
+                        position,
+                        text: completion,
+                    });
+                    this.file_extension = buffer.read(cx).file().and_then(|file| {
+                        Some(
+                            Path::new(file.file_name(cx))
+                                .extension()?
+                                .to_str()?
+                                .to_string(),
+                        )
+                    });
+                    cx.notify();
+                })?;
+                if is_finished || is_complete {
+                    break;
+                }
+            }
+
+            Ok(())
+        });
+    }
+
+    fn cycle(
+        &mut self,
+        _buffer: Model<Buffer>,
+        _cursor_position: Anchor,
+        _direction: Direction,
+        _cx: &mut ModelContext<Self>,
+    ) {
+    }
+
+    fn accept(&mut self, _cx: &mut ModelContext<Self>) {
+        self.report_completion_event(true);
+        self.pending_refresh = Task::ready(Ok(()));
+        self.completion = None;
+    }
+
+    fn discard(
+        &mut self,
+        should_report_inline_completion_event: bool,
+        _cx: &mut ModelContext<Self>,
+    ) {
+        if should_report_inline_completion_event {
+            self.report_completion_event(false);
+        }
+        self.pending_refresh = Task::ready(Ok(()));
+        self.completion = None;
+    }
+
+    fn active_completion_text<'a>(
+        &'a self,
+        buffer: &Model<Buffer>,
+        cursor_position: Anchor,
+        cx: &'a AppContext,
+    ) -> Option<(&'a str, Option<Range<Anchor>>)> {
+        let completion = self.completion.as_ref()?;
+        if completion.buffer_id != buffer.entity_id() {
+            return None;
+        }
+
+        let buffer = buffer.read(cx);
+        if !completion.position.is_valid(buffer) {
+            return None;
+        }
+
+        // Keep suggesting the rest of the completion while the user types it.
+        let start = completion.position.to_offset(buffer);
+        let end = cursor_position.to_offset(buffer);
+        if end < start {
+            return None;
+        }
+        let typed = buffer.text_for_range(start..end).collect::<String>();
+        let completion_text = completion.text.strip_prefix(typed.as_str())?.trim_end();
+        if completion_text.trim().is_empty() {
+            None
+        } else {
+            Some((completion_text, None))
+        }
+    }
+}
+
+/// Returns the text before and after `offset`, limited to the given number of characters.
+///
+/// Both windows are trimmed to whole lines, unless the cursor's own line doesn't fit.
+pub fn fim_context(
+    snapshot: &BufferSnapshot,
+    offset: usize,
+    max_prefix_chars: usize,
+    max_suffix_chars: usize,
+) -> (String, String) {
+    let cursor_row = snapshot.offset_to_point(offset).row;
+    let mut prefix_start =
+        snapshot.clip_offset(offset.saturating_sub(max_prefix_chars), Bias::Right);
+    let prefix_start_point = snapshot.offset_to_point(prefix_start);
+    if prefix_start_point.column > 0 && prefix_start_point.row < cursor_row {
+        prefix_start = snapshot.point_to_offset(Point::new(prefix_start_point.row + 1, 0));
+    }
+
+    let mut suffix_end = snapshot.clip_offset(
+        offset.saturating_add(max_suffix_chars).min(snapshot.len()),
+        Bias::Left,
+    );
+    let suffix_end_point = snapshot.offset_to_point(suffix_end);
+    if suffix_end_point.column < snapshot.line_len(suffix_end_point.row)
+        && suffix_end_point.row > cursor_row
+    {
+        suffix_end = snapshot.point_to_offset(Point::new(suffix_end_point.row, 0));
+    }
+
+    (
+        snapshot.text_for_range(prefix_start..offset).collect(),
+        snapshot.text_for_range(offset..suffix_end).collect(),
+    )
+}
+
+/// Trims a completion, which is partial until `is_finished`, to what should be suggested, and
+/// returns whether the rest of the response can be ignored.
+///
+/// Completions stop at the first line that repeats the text after the cursor, or after
+/// `max_lines` lines. Completions that open with a Markdown code fence also stop at the fence
+/// that closes it.
+pub fn clean_completion(
+    text: &str,
+    suffix: &str,
+    max_lines: usize,
+    is_finished: bool,
+) -> (String, bool) {
+    let mut text = text;
+    // Models that can only chat may wrap the completion in a code fence.
+    let is_fenced = text.starts_with("```");
+    if is_fenced {
+        match text.find('\n') {
+            Some(fence_end) => text = &text[fence_end + 1..],
+            None => return (String::new(), false),
+        }
+    }
+
+    let next_suffix_line = suffix.lines().map(str::trim).find(|line| !line.is_empty());
+    let mut end = 0;
+    for (row, line) in text.split_inclusive('\n').enumerate() {
+        let is_complete_line = is_finished || line.ends_with('\n');
+        let is_repeated_suffix =
+            row > 0 && is_complete_line && Some(line.trim()) == next_suffix_line;
+        let is_closing_fence = is_fenced && line.trim_end() == "```";
+        if row >= max_lines || is_closing_fence || is_repeated_suffix {
+            return (text[..end].trim_end_matches('\n').to_string(), true);
+        }
+        end += line.len();
+    }
+    (text.to_string(), false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{Context as _, TestAppContext, UpdateGlobal};
+    use language::language_settings::{
+        AllLanguageSettings, LanguageModelInlineCompletionSettingsContent,
+    };
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    fn test_fim_context(cx: &mut AppContext) {
+        let store = SettingsStore::test(cx);
+        cx.set_global(store);
+        language::init(cx);
+
+        let buffer = cx.new_model(|cx| Buffer::local("one\ntwo\nthree\nfour\nfive\n", cx));
+        let snapshot = buffer.read(cx).snapshot();
+        let offset = snapshot.point_to_offset(Point::new(2, 2));
+
+        // The windows are trimmed to whole lines.
+        assert_eq!(
+            fim_context(&snapshot, offset, 7, 6),
+            ("two\nth".to_string(), "ree\n".to_string())
+        );
+        // Unless the cursor's line doesn't fit.
+        assert_eq!(
+            fim_context(&snapshot, offset, 1, 1),
+            ("h".to_string(), "r".to_string())
+        );
+        // Windows larger than the buffer include all of it.
+        assert_eq!(
+            fim_context(&snapshot, offset, 100, 100),
+            ("one\ntwo\nth".to_string(), "ree\nfour\nfive\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_clean_completion() {
+        // Partial lines are kept while streaming.
+        assert_eq!(
+            clean_completion("let x = 1;\n    let y", "\n}\n", 16, false),
+            ("let x = 1;\n    let y".to_string(), false)
+        );
+        // Completions stop before the text after the cursor.
+        assert_eq!(
+            clean_completion("let x = 1;\n}\n\nfn other", "\n}\n", 16, false),
+            ("let x = 1;".to_string(), true)
+        );
+        // And after the maximum number of lines.
+        assert_eq!(
+            clean_completion("a\nb\nc\n", "", 2, false),
+            ("a\nb".to_string(), true)
+        );
+        // Code fences are removed.
+        assert_eq!(
+            clean_completion("```rust\nlet x = 1;\n```\n", "", 16, false),
+            ("let x = 1;".to_string(), true)
+        );
+        assert_eq!(
+            clean_completion("```ru", "", 16, false),
+            (String::new(), false)
+        );
+        // Fences within unfenced completions are part of the text, e.g. in Markdown.
+        assert_eq!(
+            clean_completion("Usage:\n\n```sh\nmake\n```\n", "", 16, true),
+            ("Usage:\n\n```sh\nmake\n```\n".to_string(), false)
+        );
+        // Only a bare fence closes a fenced completion.
+        assert_eq!(
+            clean_completion("```md\n```sh\nmake\n```\n", "", 16, false),
+            ("```sh\nmake".to_string(), true)
+        );
+        // Once the response is finished, its last line is complete too.
+        assert_eq!(
+            clean_completion("let x = 1;\n}", "\n}\n", 16, false),
+            ("let x = 1;\n}".to_string(), false)
+        );
+        assert_eq!(
+            clean_completion("let x = 1;\n}", "\n}\n", 16, true),
+            ("let x = 1;".to_string(), true)
+        );
+    }
+
+    #[gpui::test]
+    async fn test_language_model_completion_provider(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            language::init(cx);
+            LanguageModelRegistry::test(cx);
+        });
+
+        let buffer = cx.new_model(|cx| Buffer::local("fn main() {\n    \n}\n", cx));
+        let provider = cx.new_model(|_| LanguageModelCompletionProvider::new());
+        let cursor = buffer.read_with(cx, |buffer, _| buffer.anchor_after(Point::new(1, 4)));
+
+        // Nothing is completed until a provider is chosen.
+        assert!(!cx.update(|cx| provider.read(cx).is_enabled(&buffer, cursor, cx)));
+        provider.update(cx, |provider, cx| {
+            provider.refresh(buffer.clone(), cursor, false, cx)
+        });
+        cx.run_until_parked();
+        let model = cx.update(|cx| {
+            LanguageModelRegistry::read_global(cx)
+                .active_model()
+                .unwrap()
+        });
+        assert_eq!(model.as_fake().completion_count(), 0);
+
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                    settings
+                        .inline_completions
+                        .get_or_insert_with(Default::default)
+                        .language_model = Some(LanguageModelInlineCompletionSettingsContent {
+                        provider: Some("fake".into()),
+                        ..Default::default()
+                    });
+                });
+            });
+        });
+        assert!(cx.update(|cx| provider.read(cx).is_enabled(&buffer, cursor, cx)));
+
+        provider.update(cx, |provider, cx| {
+            provider.refresh(buffer.clone(), cursor, false, cx)
+        });
+        cx.run_until_parked();
+
+        let fake_model = model.as_fake();
+        let request = fake_model.pending_completions().pop().unwrap();
+        assert!(request.messages[1]
+            .content
+            .contains("fn main() {\n    <|CURSOR|>\n}\n"));
+
+        fake_model.stream_last_completion_response("let x = 1;\n".into());
+        fake_model.stream_last_completion_response("}\nfn other() {}".into());
+        cx.run_until_parked();
+        cx.update(|cx| {
+            assert_eq!(
+                provider
+                    .read(cx)
+                    .active_completion_text(&buffer, cursor, cx)
+                    .map(|(text, _)| text),
+                Some("let x = 1;")
+            );
+        });
+
+        // Typing the start of the completion suggests the rest of it.
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(Point::new(1, 4)..Point::new(1, 4), "let")], None, cx)
+        });
+        let cursor = buffer.read_with(cx, |buffer, _| buffer.anchor_after(Point::new(1, 7)));
+        cx.update(|cx| {
+            assert_eq!(
+                provider
+                    .read(cx)
+                    .active_completion_text(&buffer, cursor, cx)
+                    .map(|(text, _)| text),
+                Some(" x = 1;")
+            );
+        });
+
+        // Typing something else hides it.
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(Point::new(1, 7)..Point::new(1, 7), "!")], None, cx)
+        });
+        let cursor = buffer.read_with(cx, |buffer, _| buffer.anchor_after(Point::new(1, 8)));
+        cx.update(|cx| {
+            assert!(provider
+                .read(cx)
+                .active_completion_text(&buffer, cursor, cx)
+                .is_none());
+        });
+
+        // Completions are only shown in languages that allow them.
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                    settings.defaults.show_inline_completions = Some(false);
+                });
+            });
+            assert!(!provider.read(cx).is_enabled(&buffer, cursor, cx));
+        });
+    }
+}
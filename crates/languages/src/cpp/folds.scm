@@ -0,0 +1,6 @@
+(_ "{" "}") @fold
+(_ "(" ")") @fold
+
+(preproc_include) @fold.imports
+
+(comment) @fold.comment
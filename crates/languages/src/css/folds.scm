@@ -0,0 +1,5 @@
+(block) @fold
+
+(import_statement) @fold.imports
+
+(comment) @fold.comment
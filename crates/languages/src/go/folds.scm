@@ -0,0 +1,6 @@
+(_ "{" "}") @fold
+(_ "(" ")") @fold
+
+(import_declaration) @fold.imports
+
+(comment) @fold.comment
@@ -0,0 +1,15 @@
+[
+  (function_definition)
+  (class_definition)
+] @fold
+
+(_ "(" ")") @fold
+(_ "[" "]") @fold
+(_ "{" "}") @fold
+
+[
+  (import_statement)
+  (import_from_statement)
+] @fold.imports
+
+(comment) @fold.comment
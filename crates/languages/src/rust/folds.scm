@@ -0,0 +1,13 @@
+(_ "{" "}") @fold
+(_ "(" ")") @fold
+(_ "[" "]") @fold
+
+[
+  (use_declaration)
+  (extern_crate_declaration)
+] @fold.imports
+
+[
+  (line_comment)
+  (block_comment)
+] @fold.comment
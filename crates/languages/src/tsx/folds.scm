@@ -0,0 +1,9 @@
+(_ "{" "}") @fold
+(_ "(" ")") @fold
+(_ "[" "]") @fold
+
+(jsx_element) @fold
+
+(import_statement) @fold.imports
+
+(comment) @fold.comment
@@ -0,0 +1,7 @@
+(_ "{" "}") @fold
+(_ "(" ")") @fold
+(_ "[" "]") @fold
+
+(import_statement) @fold.imports
+
+(comment) @fold.comment
@@ -0,0 +1,3 @@
+(block_mapping_pair) @fold
+
+(comment) @fold.comment
@@ -173,6 +173,24 @@ pub struct ChatResponseDelta {
     pub done: bool,
 }
 
+/// A request for a raw completion, which Ollama fills in the middle of when given a `suffix`.
+#[derive(Serialize, Debug)]
+pub struct GenerateRequest {
+    pub model: String,
+    pub prompt: String,
+    pub suffix: String,
+    pub stream: bool,
+    pub keep_alive: KeepAlive,
+    pub options: Option<ChatOptions>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GenerateResponseDelta {
+    pub response: String,
+    #[allow(unused)]
+    pub done: bool,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct LocalModelsResponse {
     pub models: Vec<LocalModelListing>,
@@ -280,6 +298,50 @@ pub async fn stream_chat_completion(
     }
 }
 
+pub async fn stream_generate(
+    client: &dyn HttpClient,
+    api_url: &str,
+    request: GenerateRequest,
+    low_speed_timeout: Option<Duration>,
+) -> Result<BoxStream<'static, Result<GenerateResponseDelta>>> {
+    let uri = format!("{api_url}/api/generate");
+    let mut request_builder = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Content-Type", "application/json");
+
+    if let Some(low_speed_timeout) = low_speed_timeout {
+        request_builder = request_builder.low_speed_timeout(100, low_speed_timeout);
+    };
+
+    let request = request_builder.body(AsyncBody::from(serde_json::to_string(&request)?))?;
+    let mut response = client.send(request).await?;
+    if response.status().is_success() {
+        let reader = BufReader::new(response.into_body());
+
+        Ok(reader
+            .lines()
+            .filter_map(|line| async move {
+                match line {
+                    Ok(line) => Some(
+                        serde_json::from_str(&line).context("Unable to parse generate response"),
+                    ),
+                    Err(e) => Some(Err(e.into())),
+                }
+            })
+            .boxed())
+    } else {
+        let mut body = String::new();
+        response.body_mut().read_to_string(&mut body).await?;
+
+        Err(anyhow!(
+            "Failed to connect to Ollama API: {} {}",
+            response.status(),
+            body,
+        ))
+    }
+}
+
 pub async fn get_models(
     client: &dyn HttpClient,
     api_url: &str,
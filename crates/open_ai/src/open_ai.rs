@@ -252,13 +252,39 @@ pub async fn stream_completion(
     api_key: &str,
     request: Request,
     low_speed_timeout: Option<Duration>,
+) -> Result<BoxStream<'static, Result<ResponseStreamEvent>>> {
+    stream_completion_with_headers(
+        client,
+        api_url,
+        Some(api_key),
+        &[],
+        request,
+        low_speed_timeout,
+    )
+    .await
+}
+
+/// Like [`stream_completion`], but for servers that implement the OpenAI API, which may not
+/// require an API key and may expect additional headers on every request.
+pub async fn stream_completion_with_headers(
+    client: &dyn HttpClient,
+    api_url: &str,
+    api_key: Option<&str>,
+    headers: &[(String, String)],
+    request: Request,
+    low_speed_timeout: Option<Duration>,
 ) -> Result<BoxStream<'static, Result<ResponseStreamEvent>>> {
     let uri = format!("{api_url}/chat/completions");
     let mut request_builder = HttpRequest::builder()
         .method(Method::POST)
         .uri(uri)
-        .header("Content-Type", "application/json")
-        .header("Authorization", format!("Bearer {}", api_key));
+        .header("Content-Type", "application/json");
+    if let Some(api_key) = api_key {
+        request_builder = request_builder.header("Authorization", format!("Bearer {}", api_key));
+    }
+    for (name, value) in headers {
+        request_builder = request_builder.header(name.as_str(), value.as_str());
+    }
 
     if let Some(low_speed_timeout) = low_speed_timeout {
         request_builder = request_builder.low_speed_timeout(100, low_speed_timeout);
@@ -319,6 +345,62 @@ pub async fn stream_completion(
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ModelListing {
+    pub id: String,
+    /// The context size of the model, as reported by vLLM.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_model_len: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ModelListingResponse {
+    pub data: Vec<ModelListing>,
+}
+
+/// Lists the models served at `{api_url}/models`.
+pub async fn list_models(
+    client: &dyn HttpClient,
+    api_url: &str,
+    api_key: Option<&str>,
+    headers: &[(String, String)],
+    low_speed_timeout: Option<Duration>,
+) -> Result<Vec<ModelListing>> {
+    let uri = format!("{api_url}/models");
+    let mut request_builder = HttpRequest::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header("Accept", "application/json");
+    if let Some(api_key) = api_key {
+        request_builder = request_builder.header("Authorization", format!("Bearer {}", api_key));
+    }
+    for (name, value) in headers {
+        request_builder = request_builder.header(name.as_str(), value.as_str());
+    }
+
+    if let Some(low_speed_timeout) = low_speed_timeout {
+        request_builder = request_builder.low_speed_timeout(100, low_speed_timeout);
+    };
+
+    let request = request_builder.body(AsyncBody::default())?;
+    let mut response = client.send(request).await?;
+
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+
+    if response.status().is_success() {
+        let response: ModelListingResponse =
+            serde_json::from_str(&body).context("Unable to parse model listing")?;
+        Ok(response.data)
+    } else {
+        Err(anyhow!(
+            "Failed to list models: {} {}",
+            response.status(),
+            body,
+        ))
+    }
+}
+
 #[derive(Copy, Clone, Serialize, Deserialize)]
 pub enum OpenAiEmbeddingModel {
     #[serde(rename = "text-embedding-3-small")]
@@ -384,6 +466,88 @@ pub fn embed<'a>(
     }
 }
 
+/// A request to the legacy `/completions` endpoint, which continues a raw prompt.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TextCompletionRequest {
+    pub model: String,
+    pub prompt: String,
+    pub stream: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<usize>,
+    pub stop: Vec<String>,
+    pub temperature: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TextCompletionChoice {
+    pub index: u32,
+    pub text: String,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TextCompletionEvent {
+    pub choices: Vec<TextCompletionChoice>,
+}
+
+pub async fn stream_text_completion(
+    client: &dyn HttpClient,
+    api_url: &str,
+    api_key: Option<&str>,
+    headers: &[(String, String)],
+    request: TextCompletionRequest,
+    low_speed_timeout: Option<Duration>,
+) -> Result<BoxStream<'static, Result<String>>> {
+    let uri = format!("{api_url}/completions");
+    let mut request_builder = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Content-Type", "application/json");
+    if let Some(api_key) = api_key {
+        request_builder = request_builder.header("Authorization", format!("Bearer {}", api_key));
+    }
+    for (name, value) in headers {
+        request_builder = request_builder.header(name.as_str(), value.as_str());
+    }
+
+    if let Some(low_speed_timeout) = low_speed_timeout {
+        request_builder = request_builder.low_speed_timeout(100, low_speed_timeout);
+    };
+
+    let request = request_builder.body(AsyncBody::from(serde_json::to_string(&request)?))?;
+    let mut response = client.send(request).await?;
+    if response.status().is_success() {
+        let reader = BufReader::new(response.into_body());
+        Ok(reader
+            .lines()
+            .filter_map(|line| async move {
+                match line {
+                    Ok(line) => {
+                        let line = line.strip_prefix("data: ")?;
+                        if line == "[DONE]" {
+                            None
+                        } else {
+                            match serde_json::from_str::<TextCompletionEvent>(line) {
+                                Ok(mut event) => Some(Ok(event.choices.pop()?.text)),
+                                Err(error) => Some(Err(anyhow!(error))),
+                            }
+                        }
+                    }
+                    Err(error) => Some(Err(anyhow!(error))),
+                }
+            })
+            .boxed())
+    } else {
+        let mut body = String::new();
+        response.body_mut().read_to_string(&mut body).await?;
+        Err(anyhow!(
+            "Failed to connect to OpenAI API: {} {}",
+            response.status(),
+            body,
+        ))
+    }
+}
+
 pub fn extract_text_from_events(
     response: impl Stream<Item = Result<ResponseStreamEvent>>,
 ) -> impl Stream<Item = Result<String>> {
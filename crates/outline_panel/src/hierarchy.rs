@@ -0,0 +1,409 @@
+use collections::HashMap;
+use editor::{scroll::Autoscroll, Editor};
+use gpui::{
+    div, px, uniform_list, AnyElement, ClickEvent, Div, ElementId, InteractiveElement, IntoElement,
+    Model, ModelContext, MouseButton, ParentElement, Stateful, Styled, Task, ViewContext,
+};
+use language::{Buffer, OffsetRangeExt};
+use project::{HierarchyItem, Project};
+use settings::Settings;
+use util::ResultExt;
+use workspace::{
+    ui::{
+        h_flex, v_flex, ActiveTheme, ButtonCommon, Clickable, Color, FluentBuilder, IconButton,
+        IconName, IconSize, Label, LabelCommon, LabelSize, ListItem, Selectable, StyledTypography,
+        Tooltip,
+    },
+    Workspace,
+};
+
+use crate::{
+    outline_panel_settings::OutlinePanelSettings, CloseHierarchy, FlipHierarchyDirection,
+    OutlinePanel,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HierarchyDirection {
+    IncomingCalls,
+    OutgoingCalls,
+    Supertypes,
+    Subtypes,
+}
+
+impl HierarchyDirection {
+    fn title(&self) -> &'static str {
+        match self {
+            Self::IncomingCalls => "Incoming Calls",
+            Self::OutgoingCalls => "Outgoing Calls",
+            Self::Supertypes => "Supertypes",
+            Self::Subtypes => "Subtypes",
+        }
+    }
+
+    fn flipped(&self) -> Self {
+        match self {
+            Self::IncomingCalls => Self::OutgoingCalls,
+            Self::OutgoingCalls => Self::IncomingCalls,
+            Self::Supertypes => Self::Subtypes,
+            Self::Subtypes => Self::Supertypes,
+        }
+    }
+
+    fn is_call_hierarchy(&self) -> bool {
+        matches!(self, Self::IncomingCalls | Self::OutgoingCalls)
+    }
+
+    fn children(
+        &self,
+        project: &mut Project,
+        item: &HierarchyItem,
+        cx: &mut ModelContext<Project>,
+    ) -> Task<anyhow::Result<Vec<HierarchyItem>>> {
+        match self {
+            Self::IncomingCalls => project.incoming_calls(item, cx),
+            Self::OutgoingCalls => project.outgoing_calls(item, cx),
+            Self::Supertypes => project.supertypes(item, cx),
+            Self::Subtypes => project.subtypes(item, cx),
+        }
+    }
+}
+
+enum HierarchyChildren {
+    Unresolved,
+    Loading,
+    Loaded(Vec<usize>),
+}
+
+struct HierarchyNode {
+    item: HierarchyItem,
+    depth: usize,
+    expanded: bool,
+    children: HierarchyChildren,
+}
+
+/// A lazily expanded call or type hierarchy, rooted at the items the language server
+/// returned for the position it was requested at.
+pub(crate) struct Hierarchy {
+    direction: HierarchyDirection,
+    roots: Vec<HierarchyItem>,
+    nodes: Vec<HierarchyNode>,
+    visible_nodes: Vec<usize>,
+    selected_node: Option<usize>,
+    fetch_tasks: HashMap<usize, Task<()>>,
+}
+
+impl Hierarchy {
+    fn new(direction: HierarchyDirection, roots: Vec<HierarchyItem>) -> Self {
+        let mut hierarchy = Self {
+            direction,
+            roots: Vec::new(),
+            nodes: Vec::new(),
+            visible_nodes: Vec::new(),
+            selected_node: None,
+            fetch_tasks: HashMap::default(),
+        };
+        for root in &roots {
+            hierarchy.push_node(root.clone(), 0);
+        }
+        hierarchy.roots = roots;
+        hierarchy.update_visible_nodes();
+        hierarchy
+    }
+
+    fn push_node(&mut self, item: HierarchyItem, depth: usize) -> usize {
+        self.nodes.push(HierarchyNode {
+            item,
+            depth,
+            expanded: false,
+            children: HierarchyChildren::Unresolved,
+        });
+        self.nodes.len() - 1
+    }
+
+    fn root_nodes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.depth == 0)
+            .map(|(ix, _)| ix)
+    }
+
+    fn set_children(&mut self, parent: usize, items: Vec<HierarchyItem>) {
+        let depth = self.nodes[parent].depth + 1;
+        let children = items
+            .into_iter()
+            .map(|item| self.push_node(item, depth))
+            .collect();
+        self.nodes[parent].children = HierarchyChildren::Loaded(children);
+        self.update_visible_nodes();
+    }
+
+    fn update_visible_nodes(&mut self) {
+        self.visible_nodes.clear();
+        let mut stack = self.root_nodes().collect::<Vec<_>>();
+        stack.reverse();
+        while let Some(ix) = stack.pop() {
+            self.visible_nodes.push(ix);
+            let node = &self.nodes[ix];
+            if let (true, HierarchyChildren::Loaded(children)) = (node.expanded, &node.children) {
+                stack.extend(children.iter().rev().copied());
+            }
+        }
+    }
+}
+
+impl OutlinePanel {
+    pub(crate) fn show_hierarchy(
+        &mut self,
+        direction: HierarchyDirection,
+        buffer: Model<Buffer>,
+        position: language::Anchor,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let roots = self.project.update(cx, |project, cx| {
+            if direction.is_call_hierarchy() {
+                project.prepare_call_hierarchy(&buffer, position, cx)
+            } else {
+                project.prepare_type_hierarchy(&buffer, position, cx)
+            }
+        });
+        self.hierarchy_task = cx.spawn(|outline_panel, mut cx| async move {
+            let roots = roots.await.log_err().unwrap_or_default();
+            outline_panel
+                .update(&mut cx, |outline_panel, cx| {
+                    outline_panel.set_hierarchy(Hierarchy::new(direction, roots), cx);
+                })
+                .ok();
+        });
+    }
+
+    fn set_hierarchy(&mut self, hierarchy: Hierarchy, cx: &mut ViewContext<Self>) {
+        let roots = hierarchy.root_nodes().collect::<Vec<_>>();
+        self.hierarchy = Some(hierarchy);
+        for root in roots {
+            self.toggle_hierarchy_node(root, cx);
+        }
+        cx.notify();
+    }
+
+    pub(crate) fn flip_hierarchy_direction(
+        &mut self,
+        _: &FlipHierarchyDirection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(hierarchy) = self.hierarchy.take() {
+            let direction = hierarchy.direction.flipped();
+            self.set_hierarchy(Hierarchy::new(direction, hierarchy.roots), cx);
+        }
+    }
+
+    pub(crate) fn close_hierarchy(&mut self, _: &CloseHierarchy, cx: &mut ViewContext<Self>) {
+        self.hierarchy_task = Task::ready(());
+        if self.hierarchy.take().is_some() {
+            cx.notify();
+        }
+    }
+
+    fn toggle_hierarchy_node(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(hierarchy) = self.hierarchy.as_mut() else {
+            return;
+        };
+        let node = &mut hierarchy.nodes[ix];
+        node.expanded = !node.expanded;
+        if node.expanded && matches!(node.children, HierarchyChildren::Unresolved) {
+            node.children = HierarchyChildren::Loading;
+            let direction = hierarchy.direction;
+            let item = node.item.clone();
+            let children = self
+                .project
+                .update(cx, |project, cx| direction.children(project, &item, cx));
+            let fetch_task = cx.spawn(|outline_panel, mut cx| async move {
+                let children = children.await.log_err().unwrap_or_default();
+                outline_panel
+                    .update(&mut cx, |outline_panel, cx| {
+                        if let Some(hierarchy) = outline_panel.hierarchy.as_mut() {
+                            hierarchy.fetch_tasks.remove(&ix);
+                            hierarchy.set_children(ix, children);
+                            cx.notify();
+                        }
+                    })
+                    .ok();
+            });
+            hierarchy.fetch_tasks.insert(ix, fetch_task);
+        }
+        hierarchy.update_visible_nodes();
+        cx.notify();
+    }
+
+    fn open_hierarchy_node(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(hierarchy) = self.hierarchy.as_mut() else {
+            return;
+        };
+        hierarchy.selected_node = Some(ix);
+        let item = &hierarchy.nodes[ix].item;
+        let location = item.location.clone();
+        // Incoming calls lead to the call itself rather than to the caller's name.
+        let range = item
+            .call_sites
+            .first()
+            .cloned()
+            .unwrap_or_else(|| location.range.clone());
+        self.workspace
+            .update(cx, |workspace: &mut Workspace, cx| {
+                let range = range.to_point(location.buffer.read(cx));
+                let pane = workspace.active_pane().clone();
+                let editor =
+                    workspace.open_project_item::<Editor>(pane, location.buffer, true, false, cx);
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                        s.select_ranges([range])
+                    });
+                });
+            })
+            .log_err();
+        cx.notify();
+    }
+
+    pub(crate) fn render_hierarchy(
+        &self,
+        hierarchy: &Hierarchy,
+        cx: &mut ViewContext<Self>,
+    ) -> AnyElement {
+        let header = h_flex()
+            .px_2()
+            .py_1()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                Label::new(hierarchy.direction.title())
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        IconButton::new("flip-hierarchy-direction", IconName::ChevronUpDown)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| {
+                                Tooltip::for_action("Flip Direction", &FlipHierarchyDirection, cx)
+                            })
+                            .on_click(cx.listener(|outline_panel, _, cx| {
+                                outline_panel.flip_hierarchy_direction(&FlipHierarchyDirection, cx)
+                            })),
+                    )
+                    .child(
+                        IconButton::new("close-hierarchy", IconName::Close)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::for_action("Close", &CloseHierarchy, cx))
+                            .on_click(cx.listener(|outline_panel, _, cx| {
+                                outline_panel.close_hierarchy(&CloseHierarchy, cx)
+                            })),
+                    ),
+            );
+
+        let body = if hierarchy.nodes.is_empty() {
+            v_flex()
+                .justify_center()
+                .size_full()
+                .child(
+                    h_flex()
+                        .justify_center()
+                        .child(Label::new("No hierarchy available")),
+                )
+                .into_any_element()
+        } else {
+            uniform_list(
+                cx.view().clone(),
+                "hierarchy-entries",
+                hierarchy.visible_nodes.len(),
+                |outline_panel, range, cx| {
+                    let Some(hierarchy) = outline_panel.hierarchy.as_ref() else {
+                        return Vec::new();
+                    };
+                    hierarchy.visible_nodes[range]
+                        .iter()
+                        .map(|&ix| outline_panel.render_hierarchy_node(hierarchy, ix, cx))
+                        .collect()
+                },
+            )
+            .size_full()
+            .into_any_element()
+        };
+
+        v_flex()
+            .size_full()
+            .child(header)
+            .child(body)
+            .into_any_element()
+    }
+
+    fn render_hierarchy_node(
+        &self,
+        hierarchy: &Hierarchy,
+        ix: usize,
+        cx: &mut ViewContext<Self>,
+    ) -> Stateful<Div> {
+        let settings = OutlinePanelSettings::get_global(cx);
+        let node = &hierarchy.nodes[ix];
+        let is_active = hierarchy.selected_node == Some(ix);
+        let toggle_state = match &node.children {
+            HierarchyChildren::Loaded(children) if children.is_empty() => None,
+            _ => Some(node.expanded),
+        };
+        let item_id = ElementId::NamedInteger("hierarchy-node".into(), ix);
+        let detail = node.item.lsp_item.detail.clone().or_else(|| {
+            let path = node.item.location.buffer.read(cx).file()?.path().clone();
+            Some(path.to_string_lossy().into_owned())
+        });
+
+        div()
+            .text_ui(cx)
+            .id(item_id.clone())
+            .child(
+                ListItem::new(item_id)
+                    .indent_level(node.depth)
+                    .indent_step_size(px(settings.indent_size))
+                    .selected(is_active)
+                    .toggle(toggle_state)
+                    .on_toggle(cx.listener(move |outline_panel, _, cx| {
+                        outline_panel.toggle_hierarchy_node(ix, cx)
+                    }))
+                    .child(
+                        h_flex()
+                            .h_6()
+                            .gap_2()
+                            .ml_1()
+                            .child(Label::new(node.item.lsp_item.name.clone()))
+                            .when_some(detail, |row, detail| {
+                                row.child(
+                                    Label::new(detail)
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                )
+                            }),
+                    )
+                    .on_click(cx.listener(move |outline_panel, event: &ClickEvent, cx| {
+                        if event.down.button == MouseButton::Right || event.down.first_mouse {
+                            return;
+                        }
+                        outline_panel.open_hierarchy_node(ix, cx);
+                    })),
+            )
+            .border_1()
+            .border_r_2()
+            .rounded_none()
+            .hover(|style| {
+                if is_active {
+                    style
+                } else {
+                    let hover_color = cx.theme().colors().ghost_element_hover;
+                    style.bg(hover_color).border_color(hover_color)
+                }
+            })
+            .when(is_active && self.focus_handle.contains_focused(cx), |div| {
+                div.border_color(Color::Selected.color(cx))
+            })
+    }
+}
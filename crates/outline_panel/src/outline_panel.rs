@@ -1,3 +1,4 @@
+mod hierarchy;
 mod outline_panel_settings;
 
 use std::{
@@ -27,6 +28,7 @@ use gpui::{
     Styled, Subscription, Task, UniformListScrollHandle, View, ViewContext, VisualContext,
     WeakView, WindowContext,
 };
+use hierarchy::{Hierarchy, HierarchyDirection};
 use itertools::Itertools;
 use language::{BufferId, BufferSnapshot, OffsetRangeExt, OutlineItem};
 use menu::{Cancel, SelectFirst, SelectLast, SelectNext, SelectPrev};
@@ -63,6 +65,10 @@ actions!(
         UnfoldDirectory,
         FoldDirectory,
         SelectParent,
+        ShowCallHierarchy,
+        ShowTypeHierarchy,
+        FlipHierarchyDirection,
+        CloseHierarchy,
     ]
 );
 
@@ -73,6 +79,7 @@ type Outline = OutlineItem<language::Anchor>;
 
 pub struct OutlinePanel {
     fs: Arc<dyn Fs>,
+    workspace: WeakView<Workspace>,
     width: Option<Pixels>,
     project: Model<Project>,
     active: bool,
@@ -95,6 +102,8 @@ pub struct OutlinePanel {
     excerpts: HashMap<BufferId, HashMap<ExcerptId, Excerpt>>,
     cached_entries_with_depth: Vec<CachedEntry>,
     filter_editor: View<Editor>,
+    hierarchy: Option<Hierarchy>,
+    hierarchy_task: Task<()>,
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -261,10 +270,40 @@ pub fn init(assets: impl AssetSource, cx: &mut AppContext) {
         workspace.register_action(|workspace, _: &ToggleFocus, cx| {
             workspace.toggle_panel_focus::<OutlinePanel>(cx);
         });
+        workspace.register_action(|workspace, _: &ShowCallHierarchy, cx| {
+            show_hierarchy(workspace, HierarchyDirection::IncomingCalls, cx);
+        });
+        workspace.register_action(|workspace, _: &ShowTypeHierarchy, cx| {
+            show_hierarchy(workspace, HierarchyDirection::Supertypes, cx);
+        });
     })
     .detach();
 }
 
+fn show_hierarchy(
+    workspace: &mut Workspace,
+    direction: HierarchyDirection,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let Some(editor) = workspace
+        .active_item(cx)
+        .and_then(|item| item.act_as::<Editor>(cx))
+    else {
+        return;
+    };
+    let Some((buffer, position)) = editor.update(cx, |editor, cx| {
+        let head = editor.selections.newest_anchor().head();
+        editor.buffer().read(cx).text_anchor_for_position(head, cx)
+    }) else {
+        return;
+    };
+    if let Some(outline_panel) = workspace.focus_panel::<OutlinePanel>(cx) {
+        outline_panel.update(cx, |outline_panel, cx| {
+            outline_panel.show_hierarchy(direction, buffer, position, cx)
+        });
+    }
+}
+
 impl OutlinePanel {
     pub async fn load(
         workspace: WeakView<Workspace>,
@@ -358,6 +397,7 @@ impl OutlinePanel {
                 active: false,
                 project: project.clone(),
                 fs: workspace.app_state().fs.clone(),
+                workspace: workspace.weak_handle(),
                 scroll_handle: UniformListScrollHandle::new(),
                 focus_handle,
                 filter_editor,
@@ -377,6 +417,8 @@ impl OutlinePanel {
                 outline_fetch_tasks: HashMap::default(),
                 excerpts: HashMap::default(),
                 cached_entries_with_depth: Vec::new(),
+                hierarchy: None,
+                hierarchy_task: Task::ready(()),
                 _subscriptions: vec![
                     settings_subscription,
                     icons_subscription,
@@ -2887,6 +2929,8 @@ impl Render for OutlinePanel {
             .on_action(cx.listener(Self::copy_relative_path))
             .on_action(cx.listener(Self::unfold_directory))
             .on_action(cx.listener(Self::fold_directory))
+            .on_action(cx.listener(Self::flip_hierarchy_direction))
+            .on_action(cx.listener(Self::close_hierarchy))
             .when(project.is_local(), |el| {
                 el.on_action(cx.listener(Self::reveal_in_finder))
                     .on_action(cx.listener(Self::open_in_terminal))
@@ -2907,7 +2951,9 @@ impl Render for OutlinePanel {
             )
             .track_focus(&self.focus_handle);
 
-        if self.cached_entries_with_depth.is_empty() {
+        if let Some(hierarchy) = self.hierarchy.as_ref() {
+            outline_panel.child(self.render_hierarchy(hierarchy, cx))
+        } else if self.cached_entries_with_depth.is_empty() {
             let header = if self.updating_fs_entries {
                 "Loading outlines"
             } else if query.is_some() {
@@ -5,7 +5,7 @@ use crate::{
 use anyhow::{anyhow, Context as _, Result};
 use collections::{hash_map, HashMap};
 use futures::{channel::oneshot, stream::FuturesUnordered, StreamExt as _};
-use git::blame::Blame;
+use git::{blame::Blame, repository::GitDiffBase};
 use gpui::{
     AppContext, AsyncAppContext, Context as _, EventEmitter, Model, ModelContext, Task, WeakModel,
 };
@@ -189,19 +189,57 @@ impl BufferStore {
         cx: &mut ModelContext<Self>,
     ) {
         debug_assert!(worktree_handle.read(cx).is_local());
+        self.reload_local_diff_bases(
+            worktree_handle,
+            |path| {
+                changed_repos
+                    .iter()
+                    .any(|(work_dir, _)| path.starts_with(work_dir))
+            },
+            cx,
+        );
+    }
+
+    /// Changes the revision that the buffers of the given local worktree are diffed against,
+    /// and reloads the diff bases of its open buffers.
+    pub fn set_git_diff_base(
+        &mut self,
+        worktree_handle: Model<Worktree>,
+        base: GitDiffBase,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(worktree) = worktree_handle.read(cx).as_local() else {
+            return;
+        };
+        if *worktree.git_diff_base() == base {
+            return;
+        }
+        worktree_handle.update(cx, |worktree, _| {
+            if let Some(worktree) = worktree.as_local_mut() {
+                worktree.set_git_diff_base(base);
+            }
+        });
+        self.reload_local_diff_bases(worktree_handle, |_| true, cx);
+    }
 
-        // Identify the loading buffers whose containing repository that has changed.
+    /// Reloads the diff base of every open or loading buffer of a local worktree whose path matches the predicate.
+    fn reload_local_diff_bases(
+        &mut self,
+        worktree_handle: Model<Worktree>,
+        mut should_reload: impl FnMut(&Path) -> bool,
+        cx: &mut ModelContext<Self>,
+    ) {
+        // Identify the loading buffers that need to be reloaded.
         let future_buffers = self
             .loading_buffers()
             .filter_map(|(project_path, receiver)| {
                 if project_path.worktree_id != worktree_handle.read(cx).id() {
                     return None;
                 }
-                let path = &project_path.path;
-                changed_repos
-                    .iter()
-                    .find(|(work_dir, _)| path.starts_with(work_dir))?;
-                let path = path.clone();
+                if !should_reload(&project_path.path) {
+                    return None;
+                }
+                let path = project_path.path.clone();
                 Some(async move {
                     Self::wait_for_loading_buffer(receiver)
                         .await
@@ -211,7 +249,7 @@ impl BufferStore {
             })
             .collect::<FuturesUnordered<_>>();
 
-        // Identify the current buffers whose containing repository has changed.
+        // Identify the current buffers that need to be reloaded.
         let current_buffers = self
             .buffers()
             .filter_map(|buffer| {
@@ -219,9 +257,9 @@ impl BufferStore {
                 if file.worktree != worktree_handle {
                     return None;
                 }
-                changed_repos
-                    .iter()
-                    .find(|(work_dir, _)| file.path.starts_with(work_dir))?;
+                if !should_reload(&file.path) {
+                    return None;
+                }
                 Some((buffer, file.path.clone()))
             })
             .collect::<Vec<_>>();
@@ -234,9 +272,11 @@ impl BufferStore {
             // Wait for all of the buffers to load.
             let future_buffers = future_buffers.collect::<Vec<_>>().await;
 
-            // Reload the diff base for every buffer whose containing git repository has changed.
-            let snapshot =
-                worktree_handle.update(&mut cx, |tree, _| tree.as_local().unwrap().snapshot())?;
+            // Reload the diff base for every buffer.
+            let (snapshot, git_diff_base) = worktree_handle.update(&mut cx, |tree, _| {
+                let tree = tree.as_local().unwrap();
+                (tree.snapshot(), tree.git_diff_base().clone())
+            })?;
             let diff_bases_by_buffer = cx
                 .background_executor()
                 .spawn(async move {
@@ -247,9 +287,11 @@ impl BufferStore {
                         .filter_map(|(buffer, path)| {
                             let (repo_entry, local_repo_entry) = snapshot.repo_for_path(&path)?;
                             let relative_path = repo_entry.relativize(&snapshot, &path).ok()?;
+                            let git_diff_base = &git_diff_base;
                             Some(async move {
-                                let base_text =
-                                    local_repo_entry.repo().load_index_text(&relative_path);
+                                let base_text = local_repo_entry
+                                    .repo()
+                                    .load_diff_base_text(git_diff_base, &relative_path);
                                 Some((buffer, base_text))
                             })
                         })
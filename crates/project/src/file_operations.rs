@@ -0,0 +1,195 @@
+use std::{path::Path, sync::Arc, time::Duration};
+
+use futures::FutureExt as _;
+use globset::GlobBuilder;
+use gpui::{ModelContext, Task};
+use language::CachedLspAdapter;
+use lsp::LanguageServer;
+use util::ResultExt;
+use worktree::WorktreeId;
+
+use crate::Project;
+
+const WILL_RENAME_FILES_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Whether a path matches any of the filters a language server registered for a file operation.
+fn matches_file_operation_filters(
+    filters: &[lsp::FileOperationFilter],
+    abs_path: &Path,
+    is_dir: bool,
+) -> bool {
+    filters.iter().any(|filter| {
+        if filter
+            .scheme
+            .as_deref()
+            .map_or(false, |scheme| scheme != "file")
+        {
+            return false;
+        }
+        let pattern = &filter.pattern;
+        match pattern.matches {
+            Some(lsp::FileOperationPatternKind::File) if is_dir => return false,
+            Some(lsp::FileOperationPatternKind::Folder) if !is_dir => return false,
+            _ => {}
+        }
+        let ignore_case = pattern
+            .options
+            .as_ref()
+            .and_then(|options| options.ignore_case)
+            .unwrap_or(false);
+        GlobBuilder::new(&pattern.glob)
+            .literal_separator(true)
+            .case_insensitive(ignore_case)
+            .build()
+            .log_err()
+            .map_or(false, |glob| glob.compile_matcher().is_match(abs_path))
+    })
+}
+
+fn file_uri(abs_path: &Path) -> Option<String> {
+    lsp::Url::from_file_path(abs_path)
+        .log_err()
+        .map(|uri| uri.to_string())
+}
+
+impl Project {
+    /// Returns the running language servers of the worktree that registered for a file operation on the given path.
+    fn file_operation_servers(
+        &self,
+        worktree_id: WorktreeId,
+        abs_path: &Path,
+        is_dir: bool,
+        registration: impl Fn(
+            lsp::WorkspaceFileOperationsServerCapabilities,
+        ) -> Option<lsp::FileOperationRegistrationOptions>,
+    ) -> Vec<(Arc<CachedLspAdapter>, Arc<LanguageServer>)> {
+        self.language_servers_for_worktree(worktree_id)
+            .filter(|(_, _, server)| {
+                server
+                    .capabilities()
+                    .workspace
+                    .and_then(|workspace| workspace.file_operations)
+                    .and_then(&registration)
+                    .map_or(false, |options| {
+                        matches_file_operation_filters(&options.filters, abs_path, is_dir)
+                    })
+            })
+            .map(|(adapter, _, server)| (adapter.clone(), server.clone()))
+            .collect()
+    }
+
+    /// Sends `workspace/willRenameFiles` to the interested language servers and applies
+    /// the edits they respond with, such as updated imports, before the entry is renamed.
+    ///
+    /// The rename doesn't wait for servers that take too long to respond, and their edits are dropped.
+    pub(crate) fn will_rename_entry(
+        &self,
+        worktree_id: WorktreeId,
+        old_abs_path: &Path,
+        new_abs_path: &Path,
+        is_dir: bool,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<()> {
+        let servers =
+            self.file_operation_servers(worktree_id, old_abs_path, is_dir, |operations| {
+                operations.will_rename
+            });
+        let Some((old_uri, new_uri)) = file_uri(old_abs_path).zip(file_uri(new_abs_path)) else {
+            return Task::ready(());
+        };
+        if servers.is_empty() {
+            return Task::ready(());
+        }
+
+        let params = lsp::RenameFilesParams {
+            files: vec![lsp::FileRename { old_uri, new_uri }],
+        };
+        cx.spawn(move |this, mut cx| async move {
+            let mut timeout = cx
+                .background_executor()
+                .timer(WILL_RENAME_FILES_TIMEOUT)
+                .fuse();
+            for (adapter, server) in servers {
+                let edit = futures::select! {
+                    edit = server
+                        .request::<lsp::request::WillRenameFiles>(params.clone())
+                        .fuse() => edit,
+                    _ = timeout => {
+                        log::warn!(
+                            "language server {} didn't respond to willRenameFiles in time",
+                            server.name()
+                        );
+                        return;
+                    }
+                };
+                let Some(Some(edit)) = edit.log_err() else {
+                    continue;
+                };
+                let Some(this) = this.upgrade() else {
+                    return;
+                };
+                Self::deserialize_workspace_edit(this, edit, true, adapter, server, &mut cx)
+                    .await
+                    .log_err();
+            }
+        })
+    }
+
+    pub(crate) fn did_rename_entry(
+        &self,
+        worktree_id: WorktreeId,
+        old_abs_path: &Path,
+        new_abs_path: &Path,
+        is_dir: bool,
+    ) {
+        let servers =
+            self.file_operation_servers(worktree_id, old_abs_path, is_dir, |operations| {
+                operations.did_rename
+            });
+        let Some((old_uri, new_uri)) = file_uri(old_abs_path).zip(file_uri(new_abs_path)) else {
+            return;
+        };
+        for (_, server) in servers {
+            server
+                .notify::<lsp::notification::DidRenameFiles>(lsp::RenameFilesParams {
+                    files: vec![lsp::FileRename {
+                        old_uri: old_uri.clone(),
+                        new_uri: new_uri.clone(),
+                    }],
+                })
+                .log_err();
+        }
+    }
+
+    pub(crate) fn did_create_entry(&self, worktree_id: WorktreeId, abs_path: &Path, is_dir: bool) {
+        let servers = self.file_operation_servers(worktree_id, abs_path, is_dir, |operations| {
+            operations.did_create
+        });
+        let Some(uri) = file_uri(abs_path) else {
+            return;
+        };
+        for (_, server) in servers {
+            server
+                .notify::<lsp::notification::DidCreateFiles>(lsp::CreateFilesParams {
+                    files: vec![lsp::FileCreate { uri: uri.clone() }],
+                })
+                .log_err();
+        }
+    }
+
+    pub(crate) fn did_delete_entry(&self, worktree_id: WorktreeId, abs_path: &Path, is_dir: bool) {
+        let servers = self.file_operation_servers(worktree_id, abs_path, is_dir, |operations| {
+            operations.did_delete
+        });
+        let Some(uri) = file_uri(abs_path) else {
+            return;
+        };
+        for (_, server) in servers {
+            server
+                .notify::<lsp::notification::DidDeleteFiles>(lsp::DeleteFilesParams {
+                    files: vec![lsp::FileDelete { uri: uri.clone() }],
+                })
+                .log_err();
+        }
+    }
+}
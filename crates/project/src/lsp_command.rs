@@ -1,9 +1,10 @@
 mod signature_help;
 
 use crate::{
-    CodeAction, CoreCompletion, DocumentHighlight, Hover, HoverBlock, HoverBlockKind, InlayHint,
-    InlayHintLabel, InlayHintLabelPart, InlayHintLabelPartTooltip, InlayHintTooltip, Location,
-    LocationLink, MarkupContent, Project, ProjectTransaction, ResolveState,
+    CachedSemanticTokens, CodeAction, CodeLens, CoreCompletion, DocumentHighlight, DocumentLink,
+    HierarchyItem, Hover, HoverBlock, HoverBlockKind, InlayHint, InlayHintLabel,
+    InlayHintLabelPart, InlayHintLabelPartTooltip, InlayHintTooltip, Location, LocationLink,
+    MarkupContent, Project, ProjectTransaction, ResolveState, SemanticToken, SemanticTokens,
 };
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
@@ -15,9 +16,13 @@ use gpui::{AppContext, AsyncAppContext, Model};
 use language::{
     language_settings::{language_settings, InlayHintKind, LanguageSettings},
     point_from_lsp, point_to_lsp,
-    proto::{deserialize_anchor, deserialize_version, serialize_anchor, serialize_version},
+    proto::{
+        deserialize_anchor, deserialize_anchor_range, deserialize_version, serialize_anchor,
+        serialize_anchor_range, serialize_version,
+    },
     range_from_lsp, range_to_lsp, Anchor, Bias, Buffer, BufferSnapshot, CachedLspAdapter, CharKind,
-    OffsetRangeExt, PointUtf16, ToOffset, ToPointUtf16, Transaction, Unclipped,
+    FoldingRange, FoldingRangeKind, OffsetRangeExt, Point, PointUtf16, ToOffset, ToPointUtf16,
+    Transaction, Unclipped,
 };
 use lsp::{
     AdapterServerCapabilities, CodeActionKind, CodeActionOptions, CompletionContext,
@@ -173,6 +178,55 @@ pub(crate) struct LinkedEditingRange {
     pub position: Anchor,
 }
 
+pub(crate) struct GetSemanticTokens;
+
+pub(crate) struct GetSemanticTokensDelta {
+    pub previous_result_id: String,
+    pub previous_data: Vec<u32>,
+}
+
+pub(crate) struct GetFoldingRanges;
+
+pub(crate) struct GetCodeLens;
+
+pub(crate) struct ResolveCodeLens {
+    pub lens: CodeLens,
+}
+
+pub(crate) struct ExecuteCodeLensCommand {
+    pub command: lsp::Command,
+}
+
+pub(crate) struct GetSelectionRanges {
+    pub positions: Vec<Anchor>,
+}
+
+pub(crate) struct GetDocumentLinks;
+
+pub(crate) struct PrepareCallHierarchy {
+    pub position: PointUtf16,
+}
+
+pub(crate) struct GetIncomingCalls {
+    pub item: lsp::CallHierarchyItem,
+}
+
+pub(crate) struct GetOutgoingCalls {
+    pub item: lsp::CallHierarchyItem,
+}
+
+pub(crate) struct PrepareTypeHierarchy {
+    pub position: PointUtf16,
+}
+
+pub(crate) struct GetSupertypes {
+    pub item: lsp::TypeHierarchyItem,
+}
+
+pub(crate) struct GetSubtypes {
+    pub item: lsp::TypeHierarchyItem,
+}
+
 #[async_trait(?Send)]
 impl LspCommand for PrepareRename {
     type Response = Option<Range<Anchor>>;
@@ -2996,3 +3050,1927 @@ impl LspCommand for LinkedEditingRange {
         BufferId::new(message.buffer_id)
     }
 }
+
+#[async_trait(?Send)]
+impl LspCommand for PrepareCallHierarchy {
+    type Response = Vec<HierarchyItem>;
+    type LspRequest = lsp::request::CallHierarchyPrepare;
+    type ProtoRequest = proto::PrepareCallHierarchy;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        match &capabilities.server_capabilities.call_hierarchy_provider {
+            Some(lsp::CallHierarchyServerCapability::Simple(enabled)) => *enabled,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CallHierarchyPrepareParams {
+        lsp::CallHierarchyPrepareParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Url::from_file_path(path).unwrap(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::CallHierarchyItem>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        let items = message
+            .unwrap_or_default()
+            .into_iter()
+            .map(|item| (item, Vec::new()))
+            .collect();
+        hierarchy_items_from_lsp(items, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareCallHierarchy {
+        proto::PrepareCallHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(serialize_anchor(&buffer.anchor_before(self.position))),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::PrepareCallHierarchy,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<HierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::HierarchyItemsResponse {
+        hierarchy_items_to_proto(response, project, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::HierarchyItemsResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        hierarchy_items_from_proto(message, project, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::PrepareCallHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetIncomingCalls {
+    type Response = Vec<HierarchyItem>;
+    type LspRequest = lsp::request::CallHierarchyIncomingCalls;
+    type ProtoRequest = proto::GetIncomingCalls;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        match &capabilities.server_capabilities.call_hierarchy_provider {
+            Some(lsp::CallHierarchyServerCapability::Simple(enabled)) => *enabled,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CallHierarchyIncomingCallsParams {
+        lsp::CallHierarchyIncomingCallsParams {
+            item: self.item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::CallHierarchyIncomingCall>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        let items = message
+            .unwrap_or_default()
+            .into_iter()
+            .map(|call| (call.from, call.from_ranges))
+            .collect();
+        hierarchy_items_from_lsp(items, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetIncomingCalls {
+        proto::GetIncomingCalls {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.item).unwrap_or_default(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetIncomingCalls,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<HierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::HierarchyItemsResponse {
+        hierarchy_items_to_proto(response, project, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::HierarchyItemsResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        hierarchy_items_from_proto(message, project, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetIncomingCalls) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetOutgoingCalls {
+    type Response = Vec<HierarchyItem>;
+    type LspRequest = lsp::request::CallHierarchyOutgoingCalls;
+    type ProtoRequest = proto::GetOutgoingCalls;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        match &capabilities.server_capabilities.call_hierarchy_provider {
+            Some(lsp::CallHierarchyServerCapability::Simple(enabled)) => *enabled,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CallHierarchyOutgoingCallsParams {
+        lsp::CallHierarchyOutgoingCallsParams {
+            item: self.item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::CallHierarchyOutgoingCall>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        let items = message
+            .unwrap_or_default()
+            .into_iter()
+            // The ranges of outgoing calls lie within the caller, not the item being listed.
+            .map(|call| (call.to, Vec::new()))
+            .collect();
+        hierarchy_items_from_lsp(items, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetOutgoingCalls {
+        proto::GetOutgoingCalls {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.item).unwrap_or_default(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetOutgoingCalls,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<HierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::HierarchyItemsResponse {
+        hierarchy_items_to_proto(response, project, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::HierarchyItemsResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        hierarchy_items_from_proto(message, project, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetOutgoingCalls) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for PrepareTypeHierarchy {
+    type Response = Vec<HierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchyPrepare;
+    type ProtoRequest = proto::PrepareTypeHierarchy;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .type_hierarchy_provider
+            .is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchyPrepareParams {
+        lsp::TypeHierarchyPrepareParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Url::from_file_path(path).unwrap(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::CallHierarchyItem>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        let items = message
+            .unwrap_or_default()
+            .into_iter()
+            .map(|item| (item, Vec::new()))
+            .collect();
+        hierarchy_items_from_lsp(items, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareTypeHierarchy {
+        proto::PrepareTypeHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(serialize_anchor(&buffer.anchor_before(self.position))),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::PrepareTypeHierarchy,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<HierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::HierarchyItemsResponse {
+        hierarchy_items_to_proto(response, project, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::HierarchyItemsResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        hierarchy_items_from_proto(message, project, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::PrepareTypeHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSupertypes {
+    type Response = Vec<HierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchySupertypes;
+    type ProtoRequest = proto::GetSupertypes;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .type_hierarchy_provider
+            .is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchySupertypesParams {
+        lsp::TypeHierarchySupertypesParams {
+            item: self.item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::TypeHierarchyItem>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        let items = message
+            .unwrap_or_default()
+            .into_iter()
+            .map(|item| (item, Vec::new()))
+            .collect();
+        hierarchy_items_from_lsp(items, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSupertypes {
+        proto::GetSupertypes {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.item).unwrap_or_default(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSupertypes,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<HierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::HierarchyItemsResponse {
+        hierarchy_items_to_proto(response, project, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::HierarchyItemsResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        hierarchy_items_from_proto(message, project, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSupertypes) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSubtypes {
+    type Response = Vec<HierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchySubtypes;
+    type ProtoRequest = proto::GetSubtypes;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .type_hierarchy_provider
+            .is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchySubtypesParams {
+        lsp::TypeHierarchySubtypesParams {
+            item: self.item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::TypeHierarchyItem>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        let items = message
+            .unwrap_or_default()
+            .into_iter()
+            .map(|item| (item, Vec::new()))
+            .collect();
+        hierarchy_items_from_lsp(items, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSubtypes {
+        proto::GetSubtypes {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.item).unwrap_or_default(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSubtypes,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<HierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::HierarchyItemsResponse {
+        hierarchy_items_to_proto(response, project, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::HierarchyItemsResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        hierarchy_items_from_proto(message, project, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSubtypes) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+/// Converts hierarchy items along with the ranges of the calls made from them, if any.
+async fn hierarchy_items_from_lsp(
+    items: Vec<(lsp::CallHierarchyItem, Vec<lsp::Range>)>,
+    project: Model<Project>,
+    buffer: Model<Buffer>,
+    server_id: LanguageServerId,
+    mut cx: AsyncAppContext,
+) -> Result<Vec<HierarchyItem>> {
+    if items.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let (lsp_adapter, language_server) =
+        language_server_for_buffer(&project, &buffer, server_id, &mut cx)?;
+    let mut hierarchy_items = Vec::with_capacity(items.len());
+    for (lsp_item, call_ranges) in items {
+        let target_buffer = project
+            .update(&mut cx, |this, cx| {
+                this.open_local_buffer_via_lsp(
+                    lsp_item.uri.clone(),
+                    language_server.server_id(),
+                    lsp_adapter.name.clone(),
+                    cx,
+                )
+            })?
+            .await?;
+        let (range, call_sites) = target_buffer.update(&mut cx, |target_buffer, _| {
+            let anchor_range = |range: &lsp::Range| {
+                let start = target_buffer.clip_point_utf16(point_from_lsp(range.start), Bias::Left);
+                let end = target_buffer.clip_point_utf16(point_from_lsp(range.end), Bias::Left);
+                target_buffer.anchor_after(start)..target_buffer.anchor_before(end)
+            };
+            (
+                anchor_range(&lsp_item.selection_range),
+                call_ranges.iter().map(anchor_range).collect(),
+            )
+        })?;
+        hierarchy_items.push(HierarchyItem {
+            location: Location {
+                buffer: target_buffer,
+                range,
+            },
+            call_sites,
+            lsp_item,
+        });
+    }
+    Ok(hierarchy_items)
+}
+
+fn hierarchy_items_to_proto(
+    items: Vec<HierarchyItem>,
+    project: &mut Project,
+    peer_id: PeerId,
+    cx: &mut AppContext,
+) -> proto::HierarchyItemsResponse {
+    let items = items
+        .into_iter()
+        .map(|item| {
+            let buffer_id = project.create_buffer_for_peer(&item.location.buffer, peer_id, cx);
+            proto::HierarchyItem {
+                location: Some(proto::Location {
+                    start: Some(serialize_anchor(&item.location.range.start)),
+                    end: Some(serialize_anchor(&item.location.range.end)),
+                    buffer_id: buffer_id.into(),
+                }),
+                lsp_item: serde_json::to_vec(&item.lsp_item).unwrap_or_default(),
+                call_sites: item
+                    .call_sites
+                    .into_iter()
+                    .map(serialize_anchor_range)
+                    .collect(),
+            }
+        })
+        .collect();
+    proto::HierarchyItemsResponse { items }
+}
+
+async fn hierarchy_items_from_proto(
+    message: proto::HierarchyItemsResponse,
+    project: Model<Project>,
+    mut cx: AsyncAppContext,
+) -> Result<Vec<HierarchyItem>> {
+    let mut items = Vec::with_capacity(message.items.len());
+    for item in message.items {
+        let location = item.location.ok_or_else(|| anyhow!("missing location"))?;
+        let buffer_id = BufferId::new(location.buffer_id)?;
+        let buffer = project
+            .update(&mut cx, |this, cx| {
+                this.wait_for_remote_buffer(buffer_id, cx)
+            })?
+            .await?;
+        let start = location
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("missing location start"))?;
+        let end = location
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("missing location end"))?;
+        let call_sites = item
+            .call_sites
+            .into_iter()
+            .map(deserialize_anchor_range)
+            .collect::<Result<Vec<_>>>()?;
+        let anchors = call_sites
+            .iter()
+            .flat_map(|range| [range.start, range.end])
+            .chain([start, end]);
+        buffer
+            .update(&mut cx, |buffer, _| buffer.wait_for_anchors(anchors))?
+            .await?;
+        items.push(HierarchyItem {
+            location: Location {
+                buffer,
+                range: start..end,
+            },
+            call_sites,
+            lsp_item: serde_json::from_slice(&item.lsp_item)?,
+        });
+    }
+    Ok(items)
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSemanticTokens {
+    type Response = Option<SemanticTokens>;
+    type LspRequest = lsp::request::SemanticTokensFullRequest;
+    type ProtoRequest = proto::GetSemanticTokens;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        match semantic_tokens_options(&capabilities.server_capabilities)
+            .and_then(|options| options.full.as_ref())
+        {
+            Some(lsp::SemanticTokensFullOptions::Bool(enabled)) => *enabled,
+            Some(lsp::SemanticTokensFullOptions::Delta { .. }) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::SemanticTokensParams {
+        lsp::SemanticTokensParams {
+            text_document: lsp::TextDocumentIdentifier::new(
+                lsp::Url::from_file_path(path).unwrap(),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<lsp::SemanticTokensResult>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Option<SemanticTokens>> {
+        let (result_id, data) = match message {
+            Some(lsp::SemanticTokensResult::Tokens(tokens)) => {
+                (tokens.result_id, flatten_semantic_tokens(tokens.data))
+            }
+            Some(lsp::SemanticTokensResult::Partial(partial)) => {
+                (None, flatten_semantic_tokens(partial.data))
+            }
+            None => return Ok(None),
+        };
+        semantic_tokens_from_lsp(result_id, data, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSemanticTokens {
+        proto::GetSemanticTokens {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSemanticTokens,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        response: Option<SemanticTokens>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::SemanticTokensResponse {
+        semantic_tokens_to_proto(response, buffer_version)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::SemanticTokensResponse,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Option<SemanticTokens>> {
+        semantic_tokens_from_proto(message, project, buffer, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSemanticTokens) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSemanticTokensDelta {
+    type Response = Option<SemanticTokens>;
+    type LspRequest = lsp::request::SemanticTokensFullDeltaRequest;
+    type ProtoRequest = proto::GetSemanticTokensDelta;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        matches!(
+            semantic_tokens_options(&capabilities.server_capabilities)
+                .and_then(|options| options.full.as_ref()),
+            Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) })
+        )
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::SemanticTokensDeltaParams {
+        lsp::SemanticTokensDeltaParams {
+            text_document: lsp::TextDocumentIdentifier::new(
+                lsp::Url::from_file_path(path).unwrap(),
+            ),
+            previous_result_id: self.previous_result_id.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<lsp::SemanticTokensFullDeltaResult>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Option<SemanticTokens>> {
+        let (result_id, data) = match message {
+            Some(lsp::SemanticTokensFullDeltaResult::Tokens(tokens)) => {
+                (tokens.result_id, flatten_semantic_tokens(tokens.data))
+            }
+            Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(delta)) => (
+                delta.result_id,
+                apply_semantic_tokens_edits(self.previous_data, delta.edits)?,
+            ),
+            Some(lsp::SemanticTokensFullDeltaResult::PartialTokensDelta { edits }) => (
+                None,
+                apply_semantic_tokens_edits(self.previous_data, edits)?,
+            ),
+            None => return Ok(None),
+        };
+        semantic_tokens_from_lsp(result_id, data, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSemanticTokensDelta {
+        proto::GetSemanticTokensDelta {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            previous_result_id: self.previous_result_id.clone(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSemanticTokensDelta,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        // The guest only knows the result id, so the previous data has to come
+        // from the last result this host received for the buffer.
+        let buffer_id = buffer.update(&mut cx, |buffer, _| buffer.remote_id())?;
+        let previous_data = project.update(&mut cx, |project, _| {
+            project
+                .semantic_tokens
+                .get(&buffer_id)
+                .filter(|cached| cached.result_id == message.previous_result_id)
+                .map(|cached| cached.data.clone())
+        })?;
+        Ok(Self {
+            previous_data: previous_data
+                .ok_or_else(|| anyhow!("unknown semantic tokens result id"))?,
+            previous_result_id: message.previous_result_id,
+        })
+    }
+
+    fn response_to_proto(
+        response: Option<SemanticTokens>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::SemanticTokensResponse {
+        semantic_tokens_to_proto(response, buffer_version)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::SemanticTokensResponse,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Option<SemanticTokens>> {
+        semantic_tokens_from_proto(message, project, buffer, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSemanticTokensDelta) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+fn semantic_tokens_options(
+    capabilities: &ServerCapabilities,
+) -> Option<&lsp::SemanticTokensOptions> {
+    match capabilities.semantic_tokens_provider.as_ref()? {
+        lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(options) => Some(options),
+        lsp::SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(options) => {
+            Some(&options.semantic_tokens_options)
+        }
+    }
+}
+
+/// Converts tokens to the flat integer encoding that delta edits are expressed in.
+fn flatten_semantic_tokens(tokens: Vec<lsp::SemanticToken>) -> Vec<u32> {
+    tokens
+        .into_iter()
+        .flat_map(|token| {
+            [
+                token.delta_line,
+                token.delta_start,
+                token.length,
+                token.token_type,
+                token.token_modifiers_bitset,
+            ]
+        })
+        .collect()
+}
+
+fn apply_semantic_tokens_edits(
+    mut data: Vec<u32>,
+    mut edits: Vec<lsp::SemanticTokensEdit>,
+) -> Result<Vec<u32>> {
+    // All edits refer to offsets in the previous data, so apply them back to front.
+    edits.sort_by_key(|edit| edit.start);
+    for edit in edits.into_iter().rev() {
+        let start = edit.start as usize;
+        let end = start + edit.delete_count as usize;
+        if end > data.len() {
+            return Err(anyhow!("semantic tokens edit is out of bounds"));
+        }
+        data.splice(
+            start..end,
+            flatten_semantic_tokens(edit.data.unwrap_or_default()),
+        );
+    }
+    Ok(data)
+}
+
+async fn semantic_tokens_from_lsp(
+    result_id: Option<String>,
+    data: Vec<u32>,
+    project: Model<Project>,
+    buffer: Model<Buffer>,
+    server_id: LanguageServerId,
+    mut cx: AsyncAppContext,
+) -> Result<Option<SemanticTokens>> {
+    let (_, language_server) = language_server_for_buffer(&project, &buffer, server_id, &mut cx)?;
+    let Some(legend) = semantic_tokens_options(&language_server.capabilities())
+        .map(|options| options.legend.clone())
+    else {
+        return Ok(None);
+    };
+
+    let tokens = buffer.update(&mut cx, |buffer, _| decode_semantic_tokens(&data, buffer))?;
+    let buffer_id = buffer.update(&mut cx, |buffer, _| buffer.remote_id())?;
+    project.update(&mut cx, |project, _| match result_id.clone() {
+        Some(result_id) => {
+            project
+                .semantic_tokens
+                .insert(buffer_id, CachedSemanticTokens { result_id, data });
+        }
+        None => {
+            project.semantic_tokens.remove(&buffer_id);
+        }
+    })?;
+
+    Ok(Some(SemanticTokens {
+        result_id,
+        tokens,
+        token_types: legend
+            .token_types
+            .iter()
+            .map(|token_type| token_type.as_str().to_string())
+            .collect(),
+        token_modifiers: legend
+            .token_modifiers
+            .iter()
+            .map(|modifier| modifier.as_str().to_string())
+            .collect(),
+    }))
+}
+
+/// Decodes the relative LSP encoding, in which each token's position is
+/// relative to the start of the previous one.
+fn decode_semantic_tokens(data: &[u32], buffer: &Buffer) -> Vec<SemanticToken> {
+    let mut tokens = Vec::with_capacity(data.len() / 5);
+    let mut line = 0;
+    let mut column = 0;
+    for token in data.chunks_exact(5) {
+        let &[delta_line, delta_start, length, token_type, token_modifiers] = token else {
+            continue;
+        };
+        if delta_line == 0 {
+            column += delta_start;
+        } else {
+            line += delta_line;
+            column = delta_start;
+        }
+
+        let start = buffer.clip_point_utf16(Unclipped(PointUtf16::new(line, column)), Bias::Left);
+        let end = buffer.clip_point_utf16(
+            Unclipped(PointUtf16::new(line, column.saturating_add(length))),
+            Bias::Left,
+        );
+        if start < end {
+            tokens.push(SemanticToken {
+                range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                token_type,
+                token_modifiers,
+            });
+        }
+    }
+    tokens
+}
+
+fn semantic_tokens_to_proto(
+    response: Option<SemanticTokens>,
+    buffer_version: &clock::Global,
+) -> proto::SemanticTokensResponse {
+    proto::SemanticTokensResponse {
+        tokens: response.map(|response| proto::SemanticTokens {
+            result_id: response.result_id,
+            tokens: response
+                .tokens
+                .into_iter()
+                .map(|token| proto::SemanticToken {
+                    start: Some(serialize_anchor(&token.range.start)),
+                    end: Some(serialize_anchor(&token.range.end)),
+                    token_type: token.token_type,
+                    token_modifiers: token.token_modifiers,
+                })
+                .collect(),
+            token_types: response.token_types.to_vec(),
+            token_modifiers: response.token_modifiers.to_vec(),
+        }),
+        version: serialize_version(buffer_version),
+    }
+}
+
+/// Responses from the host aren't cached, so peers always request full tokens: the host's
+/// cache is shared by all of its peers, and a delta against a result another peer received
+/// since would be rejected.
+async fn semantic_tokens_from_proto(
+    message: proto::SemanticTokensResponse,
+    _: Model<Project>,
+    buffer: Model<Buffer>,
+    mut cx: AsyncAppContext,
+) -> Result<Option<SemanticTokens>> {
+    let Some(response) = message.tokens else {
+        return Ok(None);
+    };
+    buffer
+        .update(&mut cx, |buffer, _| {
+            buffer.wait_for_version(deserialize_version(&message.version))
+        })?
+        .await?;
+
+    let tokens = response
+        .tokens
+        .into_iter()
+        .filter_map(|token| {
+            Some(SemanticToken {
+                range: deserialize_anchor(token.start?)?..deserialize_anchor(token.end?)?,
+                token_type: token.token_type,
+                token_modifiers: token.token_modifiers,
+            })
+        })
+        .collect::<Vec<_>>();
+    buffer
+        .update(&mut cx, |buffer, _| {
+            buffer.wait_for_anchors(
+                tokens
+                    .iter()
+                    .flat_map(|token| [token.range.start, token.range.end]),
+            )
+        })?
+        .await?;
+
+    Ok(Some(SemanticTokens {
+        result_id: response.result_id,
+        tokens,
+        token_types: response.token_types.into(),
+        token_modifiers: response.token_modifiers.into(),
+    }))
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetFoldingRanges {
+    type Response = Vec<FoldingRange<Anchor>>;
+    type LspRequest = lsp::request::FoldingRangeRequest;
+    type ProtoRequest = proto::GetFoldingRanges;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        match &capabilities.server_capabilities.folding_range_provider {
+            Some(lsp::FoldingRangeProviderCapability::Simple(enabled)) => *enabled,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::FoldingRangeParams {
+        lsp::FoldingRangeParams {
+            text_document: lsp::TextDocumentIdentifier::new(
+                lsp::Url::from_file_path(path).unwrap(),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::FoldingRange>>,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<FoldingRange<Anchor>>> {
+        buffer.update(&mut cx, |buffer, _| {
+            let max_row = buffer.max_point().row;
+            let mut ranges = message
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|range| {
+                    let start_row = range.start_line.min(max_row);
+                    let end_row = range.end_line.min(max_row);
+                    if start_row >= end_row {
+                        return None;
+                    }
+                    let start = Point::new(start_row, buffer.line_len(start_row));
+                    let end = Point::new(end_row, buffer.line_len(end_row));
+                    Some(FoldingRange {
+                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                        kind: range.kind.map(|kind| match kind {
+                            lsp::FoldingRangeKind::Comment => FoldingRangeKind::Comment,
+                            lsp::FoldingRangeKind::Imports => FoldingRangeKind::Imports,
+                            lsp::FoldingRangeKind::Region => FoldingRangeKind::Region,
+                        }),
+                    })
+                })
+                .collect::<Vec<_>>();
+            ranges.sort_by(|a, b| {
+                a.range
+                    .start
+                    .cmp(&b.range.start, buffer)
+                    .then_with(|| b.range.end.cmp(&a.range.end, buffer))
+            });
+            ranges
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetFoldingRanges {
+        proto::GetFoldingRanges {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetFoldingRanges,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        response: Vec<FoldingRange<Anchor>>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetFoldingRangesResponse {
+        proto::GetFoldingRangesResponse {
+            ranges: response
+                .into_iter()
+                .map(|range| proto::FoldingRange {
+                    start: Some(serialize_anchor(&range.range.start)),
+                    end: Some(serialize_anchor(&range.range.end)),
+                    kind: range.kind.map(|kind| {
+                        match kind {
+                            FoldingRangeKind::Comment => proto::folding_range::Kind::Comment,
+                            FoldingRangeKind::Imports => proto::folding_range::Kind::Imports,
+                            FoldingRangeKind::Region => proto::folding_range::Kind::Region,
+                        }
+                        .into()
+                    }),
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetFoldingRangesResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<FoldingRange<Anchor>>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+
+        let ranges = message
+            .ranges
+            .into_iter()
+            .filter_map(|range| {
+                let kind = match range.kind.map(proto::folding_range::Kind::from_i32) {
+                    Some(Some(proto::folding_range::Kind::Comment)) => {
+                        Some(FoldingRangeKind::Comment)
+                    }
+                    Some(Some(proto::folding_range::Kind::Imports)) => {
+                        Some(FoldingRangeKind::Imports)
+                    }
+                    Some(Some(proto::folding_range::Kind::Region)) => {
+                        Some(FoldingRangeKind::Region)
+                    }
+                    Some(None) | None => None,
+                };
+                Some(FoldingRange {
+                    range: deserialize_anchor(range.start?)?..deserialize_anchor(range.end?)?,
+                    kind,
+                })
+            })
+            .collect::<Vec<_>>();
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_anchors(
+                    ranges
+                        .iter()
+                        .flat_map(|range| [range.range.start, range.range.end]),
+                )
+            })?
+            .await?;
+        Ok(ranges)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetFoldingRanges) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetCodeLens {
+    type Response = Vec<CodeLens>;
+    type LspRequest = lsp::request::CodeLensRequest;
+    type ProtoRequest = proto::GetCodeLens;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .code_lens_provider
+            .is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CodeLensParams {
+        lsp::CodeLensParams {
+            text_document: lsp::TextDocumentIdentifier::new(
+                lsp::Url::from_file_path(path).unwrap(),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::CodeLens>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CodeLens>> {
+        let commands = executable_commands(&project, server_id, &mut cx)?;
+        buffer.update(&mut cx, |buffer, _| {
+            message
+                .unwrap_or_default()
+                .into_iter()
+                .map(|lsp_lens| {
+                    let range = range_from_lsp(lsp_lens.range);
+                    let start = buffer.clip_point_utf16(range.start, Bias::Left);
+                    let end = buffer.clip_point_utf16(range.end, Bias::Left);
+                    CodeLens {
+                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                        executable: is_executable(&lsp_lens, &commands),
+                        lsp_lens,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetCodeLens {
+        proto::GetCodeLens {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetCodeLens,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        response: Vec<CodeLens>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetCodeLensResponse {
+        proto::GetCodeLensResponse {
+            lenses: response.iter().map(code_lens_to_proto).collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetCodeLensResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CodeLens>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        let lenses = message
+            .lenses
+            .into_iter()
+            .map(code_lens_from_proto)
+            .collect::<Result<Vec<_>>>()?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_anchors(
+                    lenses
+                        .iter()
+                        .flat_map(|lens| [lens.range.start, lens.range.end]),
+                )
+            })?
+            .await?;
+        Ok(lenses)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetCodeLens) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for ResolveCodeLens {
+    type Response = Option<CodeLens>;
+    type LspRequest = lsp::request::CodeLensResolve;
+    type ProtoRequest = proto::ResolveCodeLens;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .code_lens_provider
+            .as_ref()
+            .and_then(|options| options.resolve_provider)
+            .unwrap_or(false)
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CodeLens {
+        self.lens.lsp_lens.clone()
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: lsp::CodeLens,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Option<CodeLens>> {
+        let commands = executable_commands(&project, server_id, &mut cx)?;
+        // Keep the anchored range, which accounts for edits made since the lenses were requested.
+        Ok(Some(CodeLens {
+            range: self.lens.range,
+            executable: is_executable(&message, &commands),
+            lsp_lens: message,
+        }))
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::ResolveCodeLens {
+        proto::ResolveCodeLens {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lens: Some(code_lens_to_proto(&self.lens)),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::ResolveCodeLens,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let lens = code_lens_from_proto(message.lens.context("missing code lens")?)?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_anchors([lens.range.start, lens.range.end])
+            })?
+            .await?;
+        Ok(Self { lens })
+    }
+
+    fn response_to_proto(
+        response: Option<CodeLens>,
+        _: &mut Project,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::ResolveCodeLensResponse {
+        proto::ResolveCodeLensResponse {
+            lens: response.as_ref().map(code_lens_to_proto),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::ResolveCodeLensResponse,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Option<CodeLens>> {
+        let Some(lens) = message.lens else {
+            return Ok(None);
+        };
+        Ok(Some(CodeLens {
+            range: self.lens.range,
+            lsp_lens: serde_json::from_slice(&lens.lsp_lens)?,
+            executable: lens.executable,
+        }))
+    }
+
+    fn buffer_id_from_proto(message: &proto::ResolveCodeLens) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for ExecuteCodeLensCommand {
+    type Response = ();
+    type LspRequest = lsp::request::ExecuteCommand;
+    type ProtoRequest = proto::ExecuteCodeLensCommand;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .execute_command_provider
+            .as_ref()
+            .map_or(false, |options| {
+                options.commands.contains(&self.command.command)
+            })
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::ExecuteCommandParams {
+        lsp::ExecuteCommandParams {
+            command: self.command.command.clone(),
+            arguments: self.command.arguments.clone().unwrap_or_default(),
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        _: Option<serde_json::Value>,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: LanguageServerId,
+        _: AsyncAppContext,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::ExecuteCodeLensCommand {
+        proto::ExecuteCodeLensCommand {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_command: serde_json::to_vec(&self.command).unwrap_or_default(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::ExecuteCodeLensCommand,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        Ok(Self {
+            command: serde_json::from_slice(&message.lsp_command)?,
+        })
+    }
+
+    fn response_to_proto(
+        _: (),
+        _: &mut Project,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::Ack {
+        proto::Ack {}
+    }
+
+    async fn response_from_proto(
+        self,
+        _: proto::Ack,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn buffer_id_from_proto(message: &proto::ExecuteCodeLensCommand) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSelectionRanges {
+    /// For each position, the ranges containing it, from the innermost to the outermost.
+    type Response = Vec<Vec<Range<Anchor>>>;
+    type LspRequest = lsp::request::SelectionRangeRequest;
+    type ProtoRequest = proto::GetSelectionRanges;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        match &capabilities.server_capabilities.selection_range_provider {
+            Some(lsp::SelectionRangeProviderCapability::Simple(enabled)) => *enabled,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        buffer: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::SelectionRangeParams {
+        let snapshot = buffer.snapshot();
+        lsp::SelectionRangeParams {
+            text_document: lsp::TextDocumentIdentifier::new(
+                lsp::Url::from_file_path(path).unwrap(),
+            ),
+            positions: self
+                .positions
+                .iter()
+                .map(|position| point_to_lsp(position.to_point_utf16(&snapshot)))
+                .collect(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::SelectionRange>>,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<Vec<Range<Anchor>>>> {
+        buffer.read_with(&cx, |buffer, _| {
+            message
+                .unwrap_or_default()
+                .into_iter()
+                .map(|selection_range| {
+                    let mut ranges = Vec::new();
+                    let mut next = Some(Box::new(selection_range));
+                    while let Some(selection_range) = next {
+                        let start = buffer.clip_point_utf16(
+                            point_from_lsp(selection_range.range.start),
+                            Bias::Left,
+                        );
+                        let end = buffer.clip_point_utf16(
+                            point_from_lsp(selection_range.range.end),
+                            Bias::Left,
+                        );
+                        ranges.push(buffer.anchor_before(start)..buffer.anchor_after(end));
+                        next = selection_range.parent;
+                    }
+                    ranges
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSelectionRanges {
+        proto::GetSelectionRanges {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            positions: self.positions.iter().map(serialize_anchor).collect(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSelectionRanges,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        let positions = message
+            .positions
+            .into_iter()
+            .map(|position| deserialize_anchor(position).context("invalid position"))
+            .collect::<Result<Vec<_>>>()?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_anchors(positions.iter().copied())
+            })?
+            .await?;
+        Ok(Self { positions })
+    }
+
+    fn response_to_proto(
+        response: Vec<Vec<Range<Anchor>>>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetSelectionRangesResponse {
+        proto::GetSelectionRangesResponse {
+            chains: response
+                .into_iter()
+                .map(|ranges| proto::SelectionRangeChain {
+                    ranges: ranges
+                        .into_iter()
+                        .map(|range| proto::AnchorRange {
+                            start: Some(serialize_anchor(&range.start)),
+                            end: Some(serialize_anchor(&range.end)),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetSelectionRangesResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<Vec<Range<Anchor>>>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        let chains = message
+            .chains
+            .into_iter()
+            .map(|chain| {
+                chain
+                    .ranges
+                    .into_iter()
+                    .filter_map(|range| {
+                        Some(deserialize_anchor(range.start?)?..deserialize_anchor(range.end?)?)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_anchors(
+                    chains
+                        .iter()
+                        .flatten()
+                        .flat_map(|range| [range.start, range.end]),
+                )
+            })?
+            .await?;
+        Ok(chains)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSelectionRanges) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetDocumentLinks {
+    type Response = Vec<DocumentLink>;
+    type LspRequest = lsp::request::DocumentLinkRequest;
+    type ProtoRequest = proto::GetDocumentLinks;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .document_link_provider
+            .is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::DocumentLinkParams {
+        lsp::DocumentLinkParams {
+            text_document: lsp::TextDocumentIdentifier::new(
+                lsp::Url::from_file_path(path).unwrap(),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::DocumentLink>>,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentLink>> {
+        buffer.read_with(&cx, |buffer, _| {
+            message
+                .unwrap_or_default()
+                .into_iter()
+                .map(|link| {
+                    let start =
+                        buffer.clip_point_utf16(point_from_lsp(link.range.start), Bias::Left);
+                    let end = buffer.clip_point_utf16(point_from_lsp(link.range.end), Bias::Left);
+                    DocumentLink {
+                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                        target: link.target,
+                        tooltip: link.tooltip,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetDocumentLinks {
+        proto::GetDocumentLinks {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetDocumentLinks,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        response: Vec<DocumentLink>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetDocumentLinksResponse {
+        proto::GetDocumentLinksResponse {
+            links: response
+                .into_iter()
+                .map(|link| proto::DocumentLink {
+                    start: Some(serialize_anchor(&link.range.start)),
+                    end: Some(serialize_anchor(&link.range.end)),
+                    target: link.target.map(|target| target.to_string()),
+                    tooltip: link.tooltip,
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetDocumentLinksResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentLink>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        let links = message
+            .links
+            .into_iter()
+            .filter_map(|link| {
+                Some(DocumentLink {
+                    range: deserialize_anchor(link.start?)?..deserialize_anchor(link.end?)?,
+                    target: link.target.and_then(|target| lsp::Url::parse(&target).ok()),
+                    tooltip: link.tooltip,
+                })
+            })
+            .collect::<Vec<_>>();
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_anchors(
+                    links
+                        .iter()
+                        .flat_map(|link| [link.range.start, link.range.end]),
+                )
+            })?
+            .await?;
+        Ok(links)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetDocumentLinks) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+fn code_lens_to_proto(lens: &CodeLens) -> proto::CodeLens {
+    proto::CodeLens {
+        start: Some(serialize_anchor(&lens.range.start)),
+        end: Some(serialize_anchor(&lens.range.end)),
+        lsp_lens: serde_json::to_vec(&lens.lsp_lens).unwrap_or_default(),
+        executable: lens.executable,
+    }
+}
+
+fn code_lens_from_proto(lens: proto::CodeLens) -> Result<CodeLens> {
+    let start = lens
+        .start
+        .and_then(deserialize_anchor)
+        .context("missing code lens start")?;
+    let end = lens
+        .end
+        .and_then(deserialize_anchor)
+        .context("missing code lens end")?;
+    Ok(CodeLens {
+        range: start..end,
+        lsp_lens: serde_json::from_slice(&lens.lsp_lens)?,
+        executable: lens.executable,
+    })
+}
+
+/// The commands that the language server runs through `workspace/executeCommand`.
+fn executable_commands(
+    project: &Model<Project>,
+    server_id: LanguageServerId,
+    cx: &mut AsyncAppContext,
+) -> Result<Vec<String>> {
+    project.update(cx, |project, _| {
+        project
+            .language_server_for_id(server_id)
+            .and_then(|server| server.capabilities().execute_command_provider)
+            .map(|options| options.commands)
+            .unwrap_or_default()
+    })
+}
+
+fn is_executable(lens: &lsp::CodeLens, commands: &[String]) -> bool {
+    lens.command
+        .as_ref()
+        .map_or(false, |command| commands.contains(&command.command))
+}
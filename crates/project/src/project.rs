@@ -1,10 +1,12 @@
 pub mod buffer_store;
 pub mod connection_manager;
 pub mod debounced_delay;
+mod file_operations;
 pub mod lsp_command;
 pub mod lsp_ext_command;
 mod prettier_support;
 pub mod project_settings;
+mod pull_diagnostics;
 pub mod search;
 mod task_inventory;
 pub mod terminals;
@@ -33,7 +35,12 @@ use futures::{
     AsyncWriteExt, Future, FutureExt, StreamExt,
 };
 use fuzzy::CharBag;
-use git::{blame::Blame, repository::GitRepository};
+use git::{
+    blame::Blame,
+    log::{Commit, CommitDetails},
+    repository::{GitDiffBase, GitRepository},
+    Oid,
+};
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use gpui::{
     AnyModel, AppContext, AsyncAppContext, BackgroundExecutor, BorrowAppContext, Context, Entity,
@@ -53,9 +60,9 @@ use language::{
     },
     range_from_lsp, Bias, Buffer, BufferSnapshot, CachedLspAdapter, Capability, CodeLabel,
     ContextProvider, Diagnostic, DiagnosticEntry, DiagnosticSet, Diff, Documentation,
-    Event as BufferEvent, File as _, Language, LanguageRegistry, LanguageServerName, LocalFile,
-    LspAdapterDelegate, Patch, PendingLanguageServer, PointUtf16, TextBufferSnapshot, ToOffset,
-    ToPointUtf16, Transaction, Unclipped,
+    Event as BufferEvent, File as _, FoldingRange, Language, LanguageRegistry, LanguageServerName,
+    LocalFile, LspAdapterDelegate, Patch, PendingLanguageServer, PointUtf16, TextBufferSnapshot,
+    ToOffset, ToPointUtf16, Transaction, Unclipped,
 };
 use log::error;
 use lsp::{
@@ -74,6 +81,7 @@ use paths::{
 use postage::watch;
 use prettier_support::{DefaultPrettier, PrettierInstance};
 use project_settings::{DirenvSettings, LspSettings, ProjectSettings};
+use pull_diagnostics::PullDiagnostics;
 use rand::prelude::*;
 use remote::SshSession;
 use rpc::{proto::AddWorktree, ErrorCode};
@@ -197,6 +205,10 @@ pub struct Project {
             )>,
         >,
     >,
+    /// The diagnostics hidden by the `suppress_diagnostics` setting, kept so that they
+    /// reappear when the setting changes.
+    suppressed_diagnostics:
+        HashMap<(LanguageServerId, ProjectPath), Vec<DiagnosticEntry<Unclipped<PointUtf16>>>>,
     user_store: Model<UserStore>,
     fs: Arc<dyn Fs>,
     ssh_session: Option<Arc<SshSession>>,
@@ -212,7 +224,11 @@ pub struct Project {
         HashMap<Arc<Path>, Shared<Task<Result<Model<Worktree>, Arc<anyhow::Error>>>>>,
     buffer_snapshots: HashMap<BufferId, HashMap<LanguageServerId, Vec<LspBufferSnapshot>>>, // buffer_id -> server_id -> vec of snapshots
     buffers_being_formatted: HashSet<BufferId>,
+    semantic_tokens: HashMap<BufferId, CachedSemanticTokens>,
+    pull_diagnostics: PullDiagnostics,
     buffers_needing_diff: HashSet<WeakModel<Buffer>>,
+    /// The revision that the buffers of local worktrees are diffed against, including worktrees added later.
+    git_diff_base: GitDiffBase,
     git_diff_debouncer: DebouncedDelay<Self>,
     nonce: u128,
     _maintain_buffer_languages: Task<()>,
@@ -322,6 +338,7 @@ pub enum Event {
     WorktreeRemoved(WorktreeId),
     WorktreeUpdatedEntries(WorktreeId, UpdatedEntriesSet),
     WorktreeUpdatedGitRepositories,
+    GitDiffBaseChanged,
     DiskBasedDiagnosticsStarted {
         language_server_id: LanguageServerId,
     },
@@ -346,6 +363,8 @@ pub enum Event {
     Reshared,
     Rejoined,
     RefreshInlayHints,
+    RefreshSemanticTokens,
+    RefreshCodeLens,
     RevealInProjectPanel(ProjectEntryId),
     SnippetEdit(BufferId, Vec<(lsp::Range, Snippet)>),
 }
@@ -520,6 +539,87 @@ pub struct LocationLink {
     pub target: Location,
 }
 
+/// An entry of a call or type hierarchy, as returned by the language server.
+#[derive(Debug, Clone)]
+pub struct HierarchyItem {
+    /// The item's selection range, usually the name of the symbol.
+    pub location: Location,
+    /// For incoming calls, the ranges within the item where the call is made.
+    pub call_sites: Vec<Range<language::Anchor>>,
+    /// The original item, sent back to the server when resolving its neighbors.
+    pub lsp_item: lsp::CallHierarchyItem,
+}
+
+/// A command shown above a range of a buffer, such as a reference count or a way to run a test.
+#[derive(Clone, Debug)]
+pub struct CodeLens {
+    pub range: Range<language::Anchor>,
+    /// The lens as reported by the language server, whose command is missing until it is resolved.
+    pub lsp_lens: lsp::CodeLens,
+    /// Whether the server can run the lens's command. Other commands are meant to be handled by
+    /// the client, so the lens is only shown as a label.
+    pub executable: bool,
+}
+
+impl CodeLens {
+    pub fn command(&self) -> Option<&lsp::Command> {
+        self.lsp_lens.command.as_ref()
+    }
+}
+
+/// A range of a buffer that links to a file or a URL, as reported by a language server.
+#[derive(Clone, Debug)]
+pub struct DocumentLink {
+    pub range: Range<language::Anchor>,
+    pub target: Option<lsp::Url>,
+    pub tooltip: Option<String>,
+}
+
+/// Semantic tokens reported by a language server for a whole buffer.
+#[derive(Debug, Clone, Default)]
+pub struct SemanticTokens {
+    pub(crate) result_id: Option<String>,
+    /// The tokens, sorted by their position in the buffer.
+    pub tokens: Vec<SemanticToken>,
+    /// The server's legend, indexed by [`SemanticToken::token_type`].
+    pub token_types: Arc<[String]>,
+    /// The server's legend, indexed by the bits of [`SemanticToken::token_modifiers`].
+    pub token_modifiers: Arc<[String]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SemanticToken {
+    pub range: Range<language::Anchor>,
+    pub token_type: u32,
+    pub token_modifiers: u32,
+}
+
+impl SemanticTokens {
+    pub fn token_type(&self, token: &SemanticToken) -> Option<&str> {
+        self.token_types
+            .get(token.token_type as usize)
+            .map(String::as_str)
+    }
+
+    pub fn token_modifiers<'a>(&'a self, token: &SemanticToken) -> impl Iterator<Item = &'a str> {
+        let bits = token.token_modifiers;
+        self.token_modifiers
+            .iter()
+            .enumerate()
+            .filter(move |(ix, _)| *ix < 32 && bits & (1 << ix) != 0)
+            .map(|(_, modifier)| modifier.as_str())
+    }
+}
+
+/// The last semantic tokens result for a buffer, which delta requests are relative to.
+/// Only kept by the project that talks to the language server; its peers always request
+/// full tokens.
+#[derive(Debug, Default)]
+pub(crate) struct CachedSemanticTokens {
+    pub result_id: String,
+    pub data: Vec<u32>,
+}
+
 #[derive(Debug)]
 pub struct DocumentHighlight {
     pub range: Range<language::Anchor>,
@@ -707,6 +807,8 @@ impl Project {
         client.add_model_request_handler(Self::handle_inlay_hints);
         client.add_model_request_handler(Self::handle_resolve_inlay_hint);
         client.add_model_request_handler(Self::handle_refresh_inlay_hints);
+        client.add_model_request_handler(Self::handle_refresh_semantic_tokens);
+        client.add_model_request_handler(Self::handle_refresh_code_lens);
         client.add_model_request_handler(Self::handle_reload_buffers);
         client.add_model_request_handler(Self::handle_synchronize_buffers);
         client.add_model_request_handler(Self::handle_format_buffers);
@@ -732,12 +834,28 @@ impl Project {
         client.add_model_request_handler(Self::handle_task_context_for_location);
         client.add_model_request_handler(Self::handle_task_templates);
         client.add_model_request_handler(Self::handle_lsp_command::<LinkedEditingRange>);
-
-        client.add_model_request_handler(WorktreeStore::handle_create_project_entry);
-        client.add_model_request_handler(WorktreeStore::handle_rename_project_entry);
-        client.add_model_request_handler(WorktreeStore::handle_copy_project_entry);
-        client.add_model_request_handler(WorktreeStore::handle_delete_project_entry);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSemanticTokens>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSemanticTokensDelta>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetFoldingRanges>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetCodeLens>);
+        client.add_model_request_handler(Self::handle_lsp_command::<ResolveCodeLens>);
+        client.add_model_request_handler(Self::handle_lsp_command::<ExecuteCodeLensCommand>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSelectionRanges>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentLinks>);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareCallHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetIncomingCalls>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetOutgoingCalls>);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareTypeHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSupertypes>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSubtypes>);
+
+        client.add_model_request_handler(Self::handle_create_project_entry);
+        client.add_model_request_handler(Self::handle_rename_project_entry);
+        client.add_model_request_handler(Self::handle_copy_project_entry);
+        client.add_model_request_handler(Self::handle_delete_project_entry);
         client.add_model_request_handler(WorktreeStore::handle_expand_project_entry);
+        client.add_model_request_handler(WorktreeStore::handle_git_log);
+        client.add_model_request_handler(WorktreeStore::handle_git_commit_details);
 
         client.add_model_message_handler(BufferStore::handle_buffer_reloaded);
         client.add_model_message_handler(BufferStore::handle_buffer_saved);
@@ -804,6 +922,7 @@ impl Project {
                 next_entry_id: Default::default(),
                 next_diagnostic_group_id: Default::default(),
                 diagnostics: Default::default(),
+                suppressed_diagnostics: Default::default(),
                 diagnostic_summaries: Default::default(),
                 supplementary_language_servers: HashMap::default(),
                 language_servers: Default::default(),
@@ -814,7 +933,10 @@ impl Project {
                 language_server_watched_paths: HashMap::default(),
                 language_server_watcher_registrations: HashMap::default(),
                 buffers_being_formatted: Default::default(),
+                semantic_tokens: Default::default(),
+                pull_diagnostics: Default::default(),
                 buffers_needing_diff: Default::default(),
+                git_diff_base: GitDiffBase::default(),
                 git_diff_debouncer: DebouncedDelay::new(),
                 nonce: StdRng::from_entropy().gen(),
                 terminals: Terminals {
@@ -968,6 +1090,7 @@ impl Project {
                 next_diagnostic_group_id: Default::default(),
                 diagnostic_summaries: Default::default(),
                 diagnostics: Default::default(),
+                suppressed_diagnostics: Default::default(),
                 client_subscriptions: Default::default(),
                 _subscriptions: vec![
                     cx.on_release(Self::release),
@@ -1005,7 +1128,10 @@ impl Project {
                 language_server_watched_paths: HashMap::default(),
                 language_server_watcher_registrations: HashMap::default(),
                 buffers_being_formatted: Default::default(),
+                semantic_tokens: Default::default(),
+                pull_diagnostics: Default::default(),
                 buffers_needing_diff: Default::default(),
+                git_diff_base: GitDiffBase::default(),
                 git_diff_debouncer: DebouncedDelay::new(),
                 buffer_snapshots: Default::default(),
                 nonce: StdRng::from_entropy().gen(),
@@ -1283,6 +1409,7 @@ impl Project {
             }
         }
         self.current_lsp_settings = new_lsp_settings;
+        self.reapply_diagnostic_suppression(cx);
 
         // Stop all newly-disabled language servers.
         for (worktree_id, adapter_name) in language_servers_to_stop {
@@ -1534,8 +1661,23 @@ impl Project {
                 "No worktree for path {project_path:?}"
             ))));
         };
-        worktree.update(cx, |worktree, cx| {
+        let abs_path = worktree
+            .read(cx)
+            .as_local()
+            .map(|worktree| worktree.abs_path().join(&project_path.path));
+        let task = worktree.update(cx, |worktree, cx| {
             worktree.create_entry(project_path.path, is_directory, cx)
+        });
+        let Some(abs_path) = abs_path else {
+            return task;
+        };
+        cx.spawn(move |this, mut cx| async move {
+            let entry = task.await?;
+            this.update(&mut cx, |this, _| {
+                this.did_create_entry(project_path.worktree_id, &abs_path, is_directory)
+            })
+            .ok();
+            Ok(entry)
         })
     }
 
@@ -1548,8 +1690,27 @@ impl Project {
         let Some(worktree) = self.worktree_for_entry(entry_id, cx) else {
             return Task::ready(Ok(None));
         };
-        worktree.update(cx, |worktree, cx| {
+        let new_path = new_path.into();
+        let worktree_id = worktree.read(cx).id();
+        let local_path = worktree.read(cx).as_local().and_then(|worktree| {
+            let entry = worktree.entry_for_id(entry_id)?;
+            Some((worktree.abs_path().join(&new_path), entry.is_dir()))
+        });
+        let task = worktree.update(cx, |worktree, cx| {
             worktree.copy_entry(entry_id, new_path, cx)
+        });
+        let Some((abs_path, is_dir)) = local_path else {
+            return task;
+        };
+        cx.spawn(move |this, mut cx| async move {
+            let entry = task.await?;
+            if entry.is_some() {
+                this.update(&mut cx, |this, _| {
+                    this.did_create_entry(worktree_id, &abs_path, is_dir)
+                })
+                .ok();
+            }
+            Ok(entry)
         })
     }
 
@@ -1559,11 +1720,40 @@ impl Project {
         new_path: impl Into<Arc<Path>>,
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<CreatedEntry>> {
+        let new_path = new_path.into();
         let Some(worktree) = self.worktree_for_entry(entry_id, cx) else {
             return Task::ready(Err(anyhow!(format!("No worktree for entry {entry_id:?}"))));
         };
-        worktree.update(cx, |worktree, cx| {
-            worktree.rename_entry(entry_id, new_path, cx)
+        let worktree_id = worktree.read(cx).id();
+        let local_paths = worktree.read(cx).as_local().and_then(|worktree| {
+            let entry = worktree.entry_for_id(entry_id)?;
+            Some((
+                worktree.abs_path().join(&entry.path),
+                worktree.abs_path().join(&new_path),
+                entry.is_dir(),
+            ))
+        });
+        let Some((old_abs_path, new_abs_path, is_dir)) = local_paths else {
+            return worktree.update(cx, |worktree, cx| {
+                worktree.rename_entry(entry_id, new_path, cx)
+            });
+        };
+
+        // Give language servers a chance to update references to the entry before it moves.
+        let will_rename =
+            self.will_rename_entry(worktree_id, &old_abs_path, &new_abs_path, is_dir, cx);
+        cx.spawn(move |this, mut cx| async move {
+            will_rename.await;
+            let entry = worktree
+                .update(&mut cx, |worktree, cx| {
+                    worktree.rename_entry(entry_id, new_path, cx)
+                })?
+                .await?;
+            this.update(&mut cx, |this, _| {
+                this.did_rename_entry(worktree_id, &old_abs_path, &new_abs_path, is_dir)
+            })
+            .ok();
+            Ok(entry)
         })
     }
 
@@ -1574,9 +1764,25 @@ impl Project {
         cx: &mut ModelContext<Self>,
     ) -> Option<Task<Result<()>>> {
         let worktree = self.worktree_for_entry(entry_id, cx)?;
-        worktree.update(cx, |worktree, cx| {
+        let worktree_id = worktree.read(cx).id();
+        let local_path = worktree.read(cx).as_local().and_then(|worktree| {
+            let entry = worktree.entry_for_id(entry_id)?;
+            Some((worktree.abs_path().join(&entry.path), entry.is_dir()))
+        });
+        let task = worktree.update(cx, |worktree, cx| {
             worktree.delete_entry(entry_id, trash, cx)
-        })
+        })?;
+        let Some((abs_path, is_dir)) = local_path else {
+            return Some(task);
+        };
+        Some(cx.spawn(move |this, mut cx| async move {
+            task.await?;
+            this.update(&mut cx, |this, _| {
+                this.did_delete_entry(worktree_id, &abs_path, is_dir)
+            })
+            .ok();
+            Ok(())
+        }))
     }
 
     pub fn expand_entry(
@@ -2156,6 +2362,8 @@ impl Project {
         self.detect_language_for_buffer(buffer, cx);
         self.register_buffer_with_language_servers(buffer, cx);
         cx.observe_release(buffer, |this, buffer, cx| {
+            this.semantic_tokens.remove(&buffer.remote_id());
+            this.forget_pulled_diagnostics_for_buffer(buffer.remote_id());
             if let Some(file) = File::from_dyn(buffer.file()) {
                 if file.is_local() {
                     let uri = lsp::Url::from_file_path(file.abs_path(cx)).unwrap();
@@ -2260,6 +2468,8 @@ impl Project {
                         .insert(server.server_id(), vec![snapshot]);
                 }
             }
+
+            self.pull_diagnostics_for_buffer(buffer_handle, cx);
         }
     }
 
@@ -2439,7 +2649,15 @@ impl Project {
         cx: &mut ModelContext<Self>,
     ) {
         match event {
-            WorktreeStoreEvent::WorktreeAdded(_) => cx.emit(Event::WorktreeAdded),
+            WorktreeStoreEvent::WorktreeAdded(worktree) => {
+                if worktree.read(cx).is_local() {
+                    let base = self.git_diff_base.clone();
+                    self.buffer_store.update(cx, |buffer_store, cx| {
+                        buffer_store.set_git_diff_base(worktree.clone(), base, cx);
+                    });
+                }
+                cx.emit(Event::WorktreeAdded);
+            }
             WorktreeStoreEvent::WorktreeRemoved(_, id) => cx.emit(Event::WorktreeRemoved(*id)),
             WorktreeStoreEvent::WorktreeOrderChanged => cx.emit(Event::WorktreeOrderChanged),
         }
@@ -2497,6 +2715,7 @@ impl Project {
             }
 
             BufferEvent::Edited { .. } => {
+                self.pull_diagnostics_for_buffer(&buffer, cx);
                 let buffer = buffer.read(cx);
                 let file = File::from_dyn(buffer.file())?;
                 let abs_path = file.as_local()?.abs_path(cx);
@@ -2620,6 +2839,7 @@ impl Project {
                 for language_server_id in self.language_server_ids_for_buffer(buffer.read(cx), cx) {
                     self.simulate_disk_based_diagnostics_events_if_needed(language_server_id, cx);
                 }
+                self.pull_diagnostics_after_save(&buffer, cx);
             }
 
             _ => {}
@@ -3517,6 +3737,62 @@ impl Project {
             })
             .detach();
 
+        language_server
+            .on_request::<lsp::request::SemanticTokensRefresh, _, _>({
+                let this = project.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |project, cx| {
+                            cx.emit(Event::RefreshSemanticTokens);
+                            project.remote_id().map(|project_id| {
+                                project
+                                    .client
+                                    .send(proto::RefreshSemanticTokens { project_id })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::WorkspaceDiagnosticRefresh, _, _>({
+                let this = project.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |project, cx| {
+                            project.pull_diagnostics_for_server(server_id, cx);
+                            project.pull_workspace_diagnostics(server_id, cx);
+                        })?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::CodeLensRefresh, _, _>({
+                let this = project.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |project, cx| {
+                            cx.emit(Event::RefreshCodeLens);
+                            project.remote_id().map(|project_id| {
+                                project.client.send(proto::RefreshCodeLens { project_id })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
         language_server
             .on_request::<lsp::request::ShowMessageRequest, _, _>({
                 let this = project.clone();
@@ -3788,6 +4064,9 @@ impl Project {
             anyhow::Ok(())
         })?;
 
+        self.pull_diagnostics_for_server(server_id, cx);
+        self.pull_workspace_diagnostics(server_id, cx);
+
         cx.notify();
         Ok(())
     }
@@ -3804,6 +4083,7 @@ impl Project {
         if let Some(server_id) = self.language_server_ids.remove(&key) {
             let name = key.1 .0;
             log::info!("stopping language server {name}");
+            self.forget_pulled_diagnostics_for_server(server_id);
 
             // Remove other entries for this language server as well
             let mut orphaned_worktrees = vec![worktree_id];
@@ -4310,6 +4590,8 @@ impl Project {
             if let Some(work) = status.pending_work.remove(&token) {
                 if !work.is_disk_based_diagnostics_progress {
                     cx.emit(Event::RefreshInlayHints);
+                    cx.emit(Event::RefreshSemanticTokens);
+                    cx.emit(Event::RefreshCodeLens);
                 }
             }
             cx.notify();
@@ -4618,7 +4900,7 @@ impl Project {
         server_id: LanguageServerId,
         abs_path: PathBuf,
         version: Option<i32>,
-        diagnostics: Vec<DiagnosticEntry<Unclipped<PointUtf16>>>,
+        mut diagnostics: Vec<DiagnosticEntry<Unclipped<PointUtf16>>>,
         cx: &mut ModelContext<Project>,
     ) -> Result<(), anyhow::Error> {
         let (worktree, relative_path) = self
@@ -4629,6 +4911,15 @@ impl Project {
             worktree_id: worktree.read(cx).id(),
             path: relative_path.into(),
         };
+        let suppressed =
+            self.remove_suppressed_diagnostics(server_id, &project_path, &mut diagnostics, cx);
+        if suppressed.is_empty() {
+            self.suppressed_diagnostics
+                .remove(&(server_id, project_path.clone()));
+        } else {
+            self.suppressed_diagnostics
+                .insert((server_id, project_path.clone()), suppressed);
+        }
 
         if let Some(buffer) = self.get_open_buffer(&project_path, cx) {
             self.update_buffer_diagnostics(&buffer, server_id, version, diagnostics.clone(), cx)?;
@@ -4652,6 +4943,95 @@ impl Project {
         Ok(())
     }
 
+    /// Removes every diagnostic group whose primary diagnostic matches one of the
+    /// `suppress_diagnostics` patterns from the project settings, returning the removed entries.
+    fn remove_suppressed_diagnostics(
+        &self,
+        server_id: LanguageServerId,
+        project_path: &ProjectPath,
+        diagnostics: &mut Vec<DiagnosticEntry<Unclipped<PointUtf16>>>,
+        cx: &AppContext,
+    ) -> Vec<DiagnosticEntry<Unclipped<PointUtf16>>> {
+        let settings = ProjectSettings::get(
+            Some(SettingsLocation {
+                worktree_id: project_path.worktree_id.to_proto() as usize,
+                path: &project_path.path,
+            }),
+            cx,
+        );
+        if settings.suppress_diagnostics.is_empty() {
+            return Vec::new();
+        }
+
+        let matchers = settings
+            .suppress_diagnostics
+            .iter()
+            .filter_map(|pattern| pattern.matcher().log_err())
+            .collect::<Vec<_>>();
+        let server_name = self
+            .language_server_statuses
+            .get(&server_id)
+            .map(|status| status.name.as_str());
+        let suppressed_groups = diagnostics
+            .iter()
+            .filter(|entry| {
+                entry.diagnostic.is_primary
+                    && matchers.iter().any(|matcher| {
+                        matcher.matches(&project_path.path, server_name, &entry.diagnostic)
+                    })
+            })
+            .map(|entry| entry.diagnostic.group_id)
+            .collect::<HashSet<_>>();
+        let (suppressed, kept) = mem::take(diagnostics)
+            .into_iter()
+            .partition(|entry| suppressed_groups.contains(&entry.diagnostic.group_id));
+        *diagnostics = kept;
+        suppressed
+    }
+
+    /// Applies the `suppress_diagnostics` setting anew to the diagnostics reported so far,
+    /// updating the paths whose suppressed diagnostics changed.
+    ///
+    /// The diagnostics are reapplied without a version, as the servers that reported them
+    /// republish them once the buffers change anyway.
+    fn reapply_diagnostic_suppression(&mut self, cx: &mut ModelContext<Self>) {
+        let mut reported = HashMap::<_, Vec<_>>::default();
+        for (worktree_id, diagnostics_by_path) in &self.diagnostics {
+            for (path, diagnostics_by_server_id) in diagnostics_by_path {
+                for (server_id, diagnostics) in diagnostics_by_server_id {
+                    let project_path = ProjectPath {
+                        worktree_id: *worktree_id,
+                        path: path.clone(),
+                    };
+                    reported.insert((*server_id, project_path), diagnostics.clone());
+                }
+            }
+        }
+        for (key, suppressed) in &self.suppressed_diagnostics {
+            reported
+                .entry(key.clone())
+                .or_default()
+                .extend(suppressed.iter().cloned());
+        }
+
+        for ((server_id, project_path), diagnostics) in reported {
+            let mut kept = diagnostics.clone();
+            let suppressed =
+                self.remove_suppressed_diagnostics(server_id, &project_path, &mut kept, cx);
+            let previously_suppressed = self
+                .suppressed_diagnostics
+                .get(&(server_id, project_path.clone()))
+                .map_or(&[][..], Vec::as_slice);
+            if suppressed == previously_suppressed {
+                continue;
+            }
+            if let Some(abs_path) = self.absolute_path(&project_path, cx) {
+                self.update_diagnostic_entries(server_id, abs_path, None, diagnostics, cx)
+                    .log_err();
+            }
+        }
+    }
+
     pub fn update_worktree_diagnostics(
         &mut self,
         worktree_id: WorktreeId,
@@ -5559,6 +5939,96 @@ impl Project {
         self.references_impl(buffer, position, cx)
     }
 
+    pub fn prepare_call_hierarchy<T: ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<HierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            PrepareCallHierarchy { position },
+            cx,
+        )
+    }
+
+    pub fn incoming_calls(
+        &self,
+        item: &HierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<HierarchyItem>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetIncomingCalls {
+                item: item.lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
+    pub fn outgoing_calls(
+        &self,
+        item: &HierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<HierarchyItem>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetOutgoingCalls {
+                item: item.lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
+    pub fn prepare_type_hierarchy<T: ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<HierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            PrepareTypeHierarchy { position },
+            cx,
+        )
+    }
+
+    pub fn supertypes(
+        &self,
+        item: &HierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<HierarchyItem>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetSupertypes {
+                item: item.lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
+    pub fn subtypes(
+        &self,
+        item: &HierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<HierarchyItem>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetSubtypes {
+                item: item.lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
     fn document_highlights_impl(
         &self,
         buffer: &Model<Buffer>,
@@ -6016,6 +6486,182 @@ impl Project {
         self.linked_edit_impl(buffer, position, cx)
     }
 
+    /// Requests semantic tokens for the whole buffer from its primary language server.
+    ///
+    /// When the server supports it and a previous result is known, only the changes
+    /// since that result are requested, falling back to a full request otherwise.
+    pub fn semantic_tokens(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Option<SemanticTokens>>> {
+        let enabled = {
+            let buffer = buffer.read(cx);
+            language_settings(buffer.language(), buffer.file(), cx).semantic_tokens
+        };
+        if !enabled {
+            return Task::ready(Ok(None));
+        }
+
+        let buffer_id = buffer.read(cx).remote_id();
+        let Some(previous) = self.semantic_tokens.get(&buffer_id) else {
+            return self.request_lsp(
+                buffer.clone(),
+                LanguageServerToQuery::Primary,
+                GetSemanticTokens,
+                cx,
+            );
+        };
+
+        let delta = self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetSemanticTokensDelta {
+                previous_result_id: previous.result_id.clone(),
+                previous_data: previous.data.clone(),
+            },
+            cx,
+        );
+        let buffer = buffer.clone();
+        cx.spawn(move |this, mut cx| async move {
+            match delta.await {
+                Ok(Some(tokens)) => Ok(Some(tokens)),
+                Ok(None) | Err(_) => {
+                    this.update(&mut cx, |this, cx| {
+                        this.semantic_tokens.remove(&buffer_id);
+                        this.request_lsp(
+                            buffer,
+                            LanguageServerToQuery::Primary,
+                            GetSemanticTokens,
+                            cx,
+                        )
+                    })?
+                    .await
+                }
+            }
+        })
+    }
+
+    /// Requests the ranges that the buffer's primary language server considers foldable.
+    pub fn folding_ranges(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<FoldingRange<Anchor>>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetFoldingRanges,
+            cx,
+        )
+    }
+
+    /// Requests the ranges that the buffer's primary language server would expand a selection at each position to,
+    /// from the innermost to the outermost.
+    pub fn selection_ranges(
+        &self,
+        buffer: &Model<Buffer>,
+        positions: Vec<Anchor>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<Vec<Range<Anchor>>>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetSelectionRanges { positions },
+            cx,
+        )
+    }
+
+    /// Requests the ranges of the buffer that link to files or URLs.
+    pub fn document_links(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<DocumentLink>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetDocumentLinks,
+            cx,
+        )
+    }
+
+    /// Requests the code lenses of the buffer, which may have to be resolved before they can be displayed.
+    pub fn code_lens(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CodeLens>>> {
+        let enabled = {
+            let buffer = buffer.read(cx);
+            language_settings(buffer.language(), buffer.file(), cx).code_lens
+        };
+        if !enabled {
+            return Task::ready(Ok(Vec::new()));
+        }
+
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetCodeLens,
+            cx,
+        )
+    }
+
+    /// Fills in the command of a code lens. Lenses that the server can't resolve are returned unchanged.
+    pub fn resolve_code_lens(
+        &self,
+        buffer: &Model<Buffer>,
+        lens: CodeLens,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<CodeLens>> {
+        let request = self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            ResolveCodeLens { lens: lens.clone() },
+            cx,
+        );
+        cx.background_executor().spawn(async move {
+            let resolved = request.await?;
+            Ok(resolved.unwrap_or(lens))
+        })
+    }
+
+    /// Runs the command of a code lens through `workspace/executeCommand`.
+    ///
+    /// Commands that the server doesn't advertise are meant to be handled by the client, and fail
+    /// as none are implemented.
+    pub fn execute_code_lens_command(
+        &self,
+        buffer: &Model<Buffer>,
+        command: lsp::Command,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        if self.is_local() {
+            let supported = self
+                .primary_language_server_for_buffer(buffer.read(cx), cx)
+                .and_then(|(_, server)| server.capabilities().execute_command_provider)
+                .map_or(false, |options| options.commands.contains(&command.command));
+            if !supported {
+                log::warn!(
+                    "language server doesn't support the {:?} code lens command",
+                    command.command
+                );
+                return Task::ready(Err(anyhow!(
+                    "The language server doesn't support the \"{}\" command",
+                    command.title
+                )));
+            }
+        }
+
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            ExecuteCodeLensCommand { command },
+            cx,
+        )
+    }
+
     #[inline(never)]
     fn completions_impl(
         &self,
@@ -8457,6 +9103,92 @@ impl Project {
         self.buffer_store.read(cx).blame_buffer(buffer, version, cx)
     }
 
+    /// Lists at most `limit` commits of the repository containing the given path, newest first.
+    ///
+    /// Unless the path is the root of the repository, only the commits that changed it are listed.
+    pub fn git_log(
+        &self,
+        project_path: ProjectPath,
+        range: Option<String>,
+        limit: usize,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<Commit>>> {
+        self.worktree_store
+            .read(cx)
+            .git_log(project_path, range, limit, cx)
+    }
+
+    pub fn git_commit_details(
+        &self,
+        project_path: ProjectPath,
+        sha: Oid,
+        cx: &AppContext,
+    ) -> Task<Result<CommitDetails>> {
+        self.worktree_store
+            .read(cx)
+            .git_commit_details(project_path, sha, cx)
+    }
+
+    /// Returns the revision that buffers are diffed against, which is shared by all local worktrees.
+    pub fn git_diff_base(&self) -> Option<GitDiffBase> {
+        self.is_local().then(|| self.git_diff_base.clone())
+    }
+
+    /// Changes the revision that the buffers of local worktrees are diffed against,
+    /// which the hunks shown in editors are then computed from.
+    pub fn set_git_diff_base(&mut self, base: GitDiffBase, cx: &mut ModelContext<Self>) {
+        let worktrees = self
+            .worktrees(cx)
+            .filter(|worktree| worktree.read(cx).is_local())
+            .collect::<Vec<_>>();
+        self.buffer_store.update(cx, |buffer_store, cx| {
+            for worktree in worktrees {
+                buffer_store.set_git_diff_base(worktree, base.clone(), cx);
+            }
+        });
+        self.git_diff_base = base;
+        cx.emit(Event::GitDiffBaseChanged);
+    }
+
+    /// Lists the paths in local worktrees whose contents differ from the current diff base.
+    pub fn git_changed_paths(&self, cx: &AppContext) -> Task<Result<Vec<ProjectPath>>> {
+        let mut tasks = Vec::new();
+        for worktree in self.visible_worktrees(cx) {
+            let worktree = worktree.read(cx);
+            let Some(local_worktree) = worktree.as_local() else {
+                continue;
+            };
+            let worktree_id = worktree.id();
+            let snapshot = worktree.snapshot();
+            for (_, repo_entry) in snapshot.repositories() {
+                let Some(local_repo) = local_worktree.get_local_repo(repo_entry) else {
+                    continue;
+                };
+                let repo = local_repo.repo().clone();
+                let repo_entry = repo_entry.clone();
+                let snapshot = snapshot.clone();
+                let base = local_worktree.git_diff_base().clone();
+                tasks.push(cx.background_executor().spawn(async move {
+                    let paths = repo.changed_paths(&base)?;
+                    anyhow::Ok(
+                        paths
+                            .iter()
+                            .filter_map(|path| repo_entry.unrelativize(&snapshot, path))
+                            .map(|path| ProjectPath { worktree_id, path })
+                            .collect::<Vec<_>>(),
+                    )
+                }));
+            }
+        }
+        cx.background_executor().spawn(async move {
+            let mut project_paths = Vec::new();
+            for task in tasks {
+                project_paths.extend(task.await?);
+            }
+            Ok(project_paths)
+        })
+    }
+
     // RPC message handlers
 
     async fn handle_multi_lsp_query(
@@ -8745,6 +9477,99 @@ impl Project {
         })?
     }
 
+    // Entry operations requested by guests go through the project, so that
+    // language servers are notified about them like about local ones.
+    async fn handle_create_project_entry(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::CreateProjectEntry>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ProjectEntryResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let (scan_id, task) = this.update(&mut cx, |this, cx| {
+            let worktree = this
+                .worktree_for_id(worktree_id, cx)
+                .ok_or_else(|| anyhow!("worktree not found"))?;
+            let scan_id = worktree.read(cx).scan_id();
+            let task = this.create_entry(
+                (worktree_id, PathBuf::from(envelope.payload.path)),
+                envelope.payload.is_directory,
+                cx,
+            );
+            anyhow::Ok((scan_id, task))
+        })??;
+        Ok(proto::ProjectEntryResponse {
+            entry: match &task.await? {
+                CreatedEntry::Included(entry) => Some(entry.into()),
+                CreatedEntry::Excluded { .. } => None,
+            },
+            worktree_scan_id: scan_id as u64,
+        })
+    }
+
+    async fn handle_rename_project_entry(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::RenameProjectEntry>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ProjectEntryResponse> {
+        let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
+        let (scan_id, task) = this.update(&mut cx, |this, cx| {
+            let worktree = this
+                .worktree_for_entry(entry_id, cx)
+                .ok_or_else(|| anyhow!("worktree not found"))?;
+            let scan_id = worktree.read(cx).scan_id();
+            let task = this.rename_entry(entry_id, PathBuf::from(envelope.payload.new_path), cx);
+            anyhow::Ok((scan_id, task))
+        })??;
+        Ok(proto::ProjectEntryResponse {
+            entry: match &task.await? {
+                CreatedEntry::Included(entry) => Some(entry.into()),
+                CreatedEntry::Excluded { .. } => None,
+            },
+            worktree_scan_id: scan_id as u64,
+        })
+    }
+
+    async fn handle_copy_project_entry(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::CopyProjectEntry>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ProjectEntryResponse> {
+        let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
+        let (scan_id, task) = this.update(&mut cx, |this, cx| {
+            let worktree = this
+                .worktree_for_entry(entry_id, cx)
+                .ok_or_else(|| anyhow!("worktree not found"))?;
+            let scan_id = worktree.read(cx).scan_id();
+            let task = this.copy_entry(entry_id, PathBuf::from(envelope.payload.new_path), cx);
+            anyhow::Ok((scan_id, task))
+        })??;
+        Ok(proto::ProjectEntryResponse {
+            entry: task.await?.as_ref().map(|entry| entry.into()),
+            worktree_scan_id: scan_id as u64,
+        })
+    }
+
+    async fn handle_delete_project_entry(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::DeleteProjectEntry>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ProjectEntryResponse> {
+        let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
+        let (scan_id, task) = this.update(&mut cx, |this, cx| {
+            let worktree = this
+                .worktree_for_entry(entry_id, cx)
+                .ok_or_else(|| anyhow!("worktree not found"))?;
+            let scan_id = worktree.read(cx).scan_id();
+            let task = this.delete_entry(entry_id, envelope.payload.use_trash, cx);
+            anyhow::Ok((scan_id, task))
+        })??;
+        task.ok_or_else(|| anyhow!("invalid entry"))?.await?;
+        Ok(proto::ProjectEntryResponse {
+            entry: None,
+            worktree_scan_id: scan_id as u64,
+        })
+    }
+
     async fn handle_update_worktree_settings(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::UpdateWorktreeSettings>,
@@ -9551,6 +10376,28 @@ impl Project {
         Ok(proto::Ack {})
     }
 
+    async fn handle_refresh_semantic_tokens(
+        this: Model<Self>,
+        _: TypedEnvelope<proto::RefreshSemanticTokens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |_, cx| {
+            cx.emit(Event::RefreshSemanticTokens);
+        })?;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_refresh_code_lens(
+        this: Model<Self>,
+        _: TypedEnvelope<proto::RefreshCodeLens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |_, cx| {
+            cx.emit(Event::RefreshCodeLens);
+        })?;
+        Ok(proto::Ack {})
+    }
+
     async fn handle_lsp_command<T: LspCommand>(
         this: Model<Self>,
         envelope: TypedEnvelope<T::ProtoRequest>,
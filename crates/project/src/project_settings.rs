@@ -1,9 +1,13 @@
+use anyhow::Context as _;
 use collections::HashMap;
+use globset::{Glob, GlobMatcher};
 use gpui::AppContext;
+use language::Diagnostic;
+use lsp::DiagnosticSeverity;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
-use std::{sync::Arc, time::Duration};
+use std::{path::Path, sync::Arc, time::Duration};
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct ProjectSettings {
@@ -28,6 +32,120 @@ pub struct ProjectSettings {
     /// Configuration for session-related features
     #[serde(default)]
     pub session: SessionSettings,
+
+    /// Diagnostics to hide everywhere in the project, including the editor.
+    ///
+    /// A diagnostic is hidden if it matches any of these patterns, along with
+    /// the rest of its group. Empty patterns are ignored.
+    /// Default: []
+    #[serde(default)]
+    pub suppress_diagnostics: Vec<DiagnosticPattern>,
+}
+
+/// Describes a set of diagnostics. Every field that is set must match.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct DiagnosticPattern {
+    /// The diagnostic's source, or the name of the language server that reported it.
+    pub source: Option<String>,
+    /// The diagnostic's code, e.g. `E0308` or `unused_variables`.
+    pub code: Option<String>,
+    /// The diagnostic's severity.
+    pub severity: Option<DiagnosticSeverityContent>,
+    /// A glob matched against the worktree-relative path of the file.
+    pub path: Option<String>,
+    /// Text contained in the diagnostic's message, ignoring case.
+    pub message: Option<String>,
+}
+
+impl DiagnosticPattern {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Compiles the pattern. Empty patterns are rejected, as they would match every diagnostic.
+    pub fn matcher(&self) -> anyhow::Result<DiagnosticMatcher> {
+        if self.is_empty() {
+            anyhow::bail!("ignoring empty diagnostic pattern, which would match every diagnostic");
+        }
+        let path = self
+            .path
+            .as_ref()
+            .map(|path| {
+                Glob::new(path)
+                    .map(|glob| glob.compile_matcher())
+                    .with_context(|| format!("invalid diagnostic path glob {path:?}"))
+            })
+            .transpose()?;
+        Ok(DiagnosticMatcher {
+            source: self.source.clone(),
+            code: self.code.clone(),
+            severity: self.severity.map(DiagnosticSeverity::from),
+            path,
+            message: self.message.as_ref().map(|message| message.to_lowercase()),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticSeverityContent {
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+impl From<DiagnosticSeverityContent> for DiagnosticSeverity {
+    fn from(severity: DiagnosticSeverityContent) -> Self {
+        match severity {
+            DiagnosticSeverityContent::Error => DiagnosticSeverity::ERROR,
+            DiagnosticSeverityContent::Warning => DiagnosticSeverity::WARNING,
+            DiagnosticSeverityContent::Info => DiagnosticSeverity::INFORMATION,
+            DiagnosticSeverityContent::Hint => DiagnosticSeverity::HINT,
+        }
+    }
+}
+
+/// A compiled [`DiagnosticPattern`].
+#[derive(Clone, Debug)]
+pub struct DiagnosticMatcher {
+    source: Option<String>,
+    code: Option<String>,
+    severity: Option<DiagnosticSeverity>,
+    path: Option<GlobMatcher>,
+    message: Option<String>,
+}
+
+impl DiagnosticMatcher {
+    pub fn matches_path(&self, path: &Path) -> bool {
+        self.path.as_ref().map_or(true, |glob| glob.is_match(path))
+    }
+
+    pub fn matches(&self, path: &Path, server_name: Option<&str>, diagnostic: &Diagnostic) -> bool {
+        if !self.matches_path(path) {
+            return false;
+        }
+        if let Some(source) = &self.source {
+            if diagnostic.source.as_ref() != Some(source) && server_name != Some(source.as_str()) {
+                return false;
+            }
+        }
+        if self.code.is_some() && diagnostic.code != self.code {
+            return false;
+        }
+        if self
+            .severity
+            .map_or(false, |severity| diagnostic.severity != severity)
+        {
+            return false;
+        }
+        if let Some(message) = &self.message {
+            if !diagnostic.message.to_lowercase().contains(message) {
+                return false;
+            }
+        }
+        true
+    }
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -5,8 +5,8 @@ use gpui::{AppContext, SemanticVersion, UpdateGlobal};
 use http_client::Url;
 use language::{
     language_settings::{AllLanguageSettings, LanguageSettingsContent},
-    tree_sitter_rust, tree_sitter_typescript, Diagnostic, FakeLspAdapter, LanguageConfig,
-    LanguageMatcher, LineEnding, OffsetRangeExt, Point, ToPoint,
+    tree_sitter_rust, tree_sitter_typescript, Diagnostic, FakeLspAdapter, FoldingRangeKind,
+    LanguageConfig, LanguageMatcher, LineEnding, OffsetRangeExt, Point, ToPoint,
 };
 use lsp::NumberOrString;
 use parking_lot::Mutex;
@@ -2014,277 +2014,610 @@ async fn test_diagnostics_from_multiple_language_servers(cx: &mut gpui::TestAppC
 }
 
 #[gpui::test]
-async fn test_edits_from_lsp2_with_past_version(cx: &mut gpui::TestAppContext) {
+async fn test_suppressed_diagnostics(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
-    let text = "
-        fn a() {
-            f1();
-        }
-        fn b() {
-            f2();
-        }
-        fn c() {
-            f3();
-        }
-    "
-    .unindent();
-
     let fs = FakeFs::new(cx.executor());
     fs.insert_tree(
         "/dir",
-        json!({
-            "a.rs": text.clone(),
-        }),
+        json!({ "a.rs": "one two three", "generated.rs": "four five" }),
     )
     .await;
 
-    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
-
-    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
-    language_registry.add(rust_lang());
-    let mut fake_servers =
-        language_registry.register_fake_lsp_adapter("Rust", FakeLspAdapter::default());
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.suppress_diagnostics = vec![
+                    project_settings::DiagnosticPattern {
+                        code: Some("unused_variables".into()),
+                        ..Default::default()
+                    },
+                    project_settings::DiagnosticPattern {
+                        path: Some("generated.rs".into()),
+                        ..Default::default()
+                    },
+                ];
+            });
+        })
+    });
 
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
     let buffer = project
         .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
         .await
         .unwrap();
 
-    let mut fake_server = fake_servers.next().await.unwrap();
-    let lsp_document_version = fake_server
-        .receive_notification::<lsp::notification::DidOpenTextDocument>()
-        .await
-        .text_document
-        .version;
+    project.update(cx, |project, cx| {
+        project
+            .update_diagnostic_entries(
+                LanguageServerId(0),
+                Path::new("/dir/a.rs").to_owned(),
+                None,
+                vec![
+                    DiagnosticEntry {
+                        range: Unclipped(PointUtf16::new(0, 0))..Unclipped(PointUtf16::new(0, 3)),
+                        diagnostic: Diagnostic {
+                            severity: DiagnosticSeverity::WARNING,
+                            code: Some("unused_variables".into()),
+                            is_primary: true,
+                            message: "unused variable".to_string(),
+                            group_id: 0,
+                            ..Default::default()
+                        },
+                    },
+                    DiagnosticEntry {
+                        range: Unclipped(PointUtf16::new(0, 4))..Unclipped(PointUtf16::new(0, 7)),
+                        diagnostic: Diagnostic {
+                            severity: DiagnosticSeverity::INFORMATION,
+                            code: Some("unused_variables".into()),
+                            is_primary: false,
+                            message: "consider removing it".to_string(),
+                            group_id: 0,
+                            ..Default::default()
+                        },
+                    },
+                    DiagnosticEntry {
+                        range: Unclipped(PointUtf16::new(0, 8))..Unclipped(PointUtf16::new(0, 13)),
+                        diagnostic: Diagnostic {
+                            severity: DiagnosticSeverity::ERROR,
+                            code: Some("E0308".into()),
+                            is_primary: true,
+                            message: "mismatched types".to_string(),
+                            group_id: 1,
+                            ..Default::default()
+                        },
+                    },
+                ],
+                cx,
+            )
+            .unwrap();
+        project
+            .update_diagnostic_entries(
+                LanguageServerId(0),
+                Path::new("/dir/generated.rs").to_owned(),
+                None,
+                vec![DiagnosticEntry {
+                    range: Unclipped(PointUtf16::new(0, 0))..Unclipped(PointUtf16::new(0, 4)),
+                    diagnostic: Diagnostic {
+                        severity: DiagnosticSeverity::ERROR,
+                        is_primary: true,
+                        message: "syntax error".to_string(),
+                        ..Default::default()
+                    },
+                }],
+                cx,
+            )
+            .unwrap();
 
-    // Simulate editing the buffer after the language server computes some edits.
-    buffer.update(cx, |buffer, cx| {
-        buffer.edit(
-            [(
-                Point::new(0, 0)..Point::new(0, 0),
-                "// above first function\n",
-            )],
-            None,
-            cx,
+        assert_eq!(
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 1,
+                warning_count: 0,
+            }
         );
-        buffer.edit(
-            [(
-                Point::new(2, 0)..Point::new(2, 0),
-                "    // inside first function\n",
-            )],
-            None,
-            cx,
+    });
+
+    buffer.update(cx, |buffer, _| {
+        let messages = buffer
+            .snapshot()
+            .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+            .map(|entry| entry.diagnostic.message)
+            .collect::<Vec<_>>();
+        assert_eq!(messages, ["mismatched types"]);
+    });
+
+    // Suppressed diagnostics reappear when their pattern is removed.
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.suppress_diagnostics = vec![project_settings::DiagnosticPattern {
+                    path: Some("generated.rs".into()),
+                    ..Default::default()
+                }];
+            });
+        })
+    });
+    cx.executor().run_until_parked();
+
+    project.update(cx, |project, cx| {
+        assert_eq!(
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 1,
+                warning_count: 1,
+            }
         );
-        buffer.edit(
-            [(
-                Point::new(6, 4)..Point::new(6, 4),
-                "// inside second function ",
-            )],
-            None,
-            cx,
+    });
+    buffer.update(cx, |buffer, _| {
+        let messages = buffer
+            .snapshot()
+            .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+            .map(|entry| entry.diagnostic.message)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            [
+                "unused variable",
+                "consider removing it",
+                "mismatched types"
+            ]
         );
+    });
+
+    // Diagnostics are hidden again when a pattern matching them is added.
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.suppress_diagnostics = vec![project_settings::DiagnosticPattern {
+                    message: Some("Mismatched".into()),
+                    ..Default::default()
+                }];
+            });
+        })
+    });
+    cx.executor().run_until_parked();
 
+    project.update(cx, |project, cx| {
         assert_eq!(
-            buffer.text(),
-            "
-                // above first function
-                fn a() {
-                    // inside first function
-                    f1();
-                }
-                fn b() {
-                    // inside second function f2();
-                }
-                fn c() {
-                    f3();
-                }
-            "
-            .unindent()
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 1,
+                warning_count: 1,
+            }
         );
     });
+    buffer.update(cx, |buffer, _| {
+        let messages = buffer
+            .snapshot()
+            .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+            .map(|entry| entry.diagnostic.message)
+            .collect::<Vec<_>>();
+        assert_eq!(messages, ["unused variable", "consider removing it"]);
+    });
 
-    let edits = project
-        .update(cx, |project, cx| {
-            project.edits_from_lsp(
-                &buffer,
-                vec![
-                    // replace body of first function
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(3, 0)),
-                        new_text: "
-                            fn a() {
-                                f10();
-                            }
-                            "
-                        .unindent(),
-                    },
-                    // edit inside second function
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(4, 6), lsp::Position::new(4, 6)),
-                        new_text: "00".into(),
-                    },
-                    // edit inside third function via two distinct edits
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(7, 5), lsp::Position::new(7, 5)),
-                        new_text: "4000".into(),
-                    },
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(7, 5), lsp::Position::new(7, 6)),
-                        new_text: "".into(),
+    // Empty patterns are ignored instead of hiding every diagnostic.
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.suppress_diagnostics = vec![
+                    project_settings::DiagnosticPattern::default(),
+                    project_settings::DiagnosticPattern {
+                        message: Some("Mismatched".into()),
+                        ..Default::default()
                     },
-                ],
-                LanguageServerId(0),
-                Some(lsp_document_version),
-                cx,
-            )
+                ];
+            });
         })
-        .await
-        .unwrap();
+    });
+    cx.executor().run_until_parked();
 
-    buffer.update(cx, |buffer, cx| {
-        for (range, new_text) in edits {
-            buffer.edit([(range, new_text)], None, cx);
-        }
+    project.update(cx, |project, cx| {
         assert_eq!(
-            buffer.text(),
-            "
-                // above first function
-                fn a() {
-                    // inside first function
-                    f10();
-                }
-                fn b() {
-                    // inside second function f200();
-                }
-                fn c() {
-                    f4000();
-                }
-                "
-            .unindent()
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 1,
+                warning_count: 1,
+            }
         );
     });
+    buffer.update(cx, |buffer, _| {
+        let messages = buffer
+            .snapshot()
+            .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+            .map(|entry| entry.diagnostic.message)
+            .collect::<Vec<_>>();
+        assert_eq!(messages, ["unused variable", "consider removing it"]);
+    });
 }
 
 #[gpui::test]
-async fn test_edits_from_lsp2_with_edits_on_adjacent_lines(cx: &mut gpui::TestAppContext) {
+async fn test_pull_diagnostics(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
-    let text = "
-        use a::b;
-        use a::c;
-
-        fn f() {
-            b();
-            c();
-        }
-    "
-    .unindent();
-
     let fs = FakeFs::new(cx.executor());
-    fs.insert_tree(
-        "/dir",
-        json!({
-            "a.rs": text.clone(),
-        }),
+    fs.insert_tree("/dir", json!({ "a.rs": "let a = b;\n" }))
+        .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions {
+                        identifier: Some("rust".to_string()),
+                        inter_file_dependencies: false,
+                        workspace_diagnostics: false,
+                        work_done_progress_options: Default::default(),
+                    },
+                )),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    let previous_result_ids = Arc::new(Mutex::new(Vec::new()));
+    let mut requests =
+        fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>({
+            let previous_result_ids = previous_result_ids.clone();
+            move |params, _| {
+                let previous_result_ids = previous_result_ids.clone();
+                async move {
+                    assert_eq!(params.text_document.uri.as_str(), "file:///dir/a.rs");
+                    assert_eq!(params.identifier.as_deref(), Some("rust"));
+                    previous_result_ids
+                        .lock()
+                        .push(params.previous_result_id.clone());
+                    let report = if params.previous_result_id.is_none() {
+                        lsp::DocumentDiagnosticReport::Full(
+                            lsp::RelatedFullDocumentDiagnosticReport {
+                                related_documents: None,
+                                full_document_diagnostic_report:
+                                    lsp::FullDocumentDiagnosticReport {
+                                        result_id: Some("1".to_string()),
+                                        items: vec![lsp::Diagnostic {
+                                            range: lsp::Range::new(
+                                                lsp::Position::new(0, 8),
+                                                lsp::Position::new(0, 9),
+                                            ),
+                                            severity: Some(lsp::DiagnosticSeverity::ERROR),
+                                            message: "cannot find value `b`".to_string(),
+                                            ..Default::default()
+                                        }],
+                                    },
+                            },
+                        )
+                    } else {
+                        lsp::DocumentDiagnosticReport::Unchanged(
+                            lsp::RelatedUnchangedDocumentDiagnosticReport {
+                                related_documents: None,
+                                unchanged_document_diagnostic_report:
+                                    lsp::UnchangedDocumentDiagnosticReport {
+                                        result_id: "1".to_string(),
+                                    },
+                            },
+                        )
+                    };
+                    Ok(lsp::DocumentDiagnosticReportResult::Report(report))
+                }
+            }
+        });
+
+    let assert_diagnostics = |cx: &mut gpui::TestAppContext| {
+        buffer.update(cx, |buffer, _| {
+            let diagnostics = buffer
+                .snapshot()
+                .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+                .map(|entry| (entry.range, entry.diagnostic.message))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                diagnostics,
+                [(
+                    Point::new(0, 8)..Point::new(0, 9),
+                    "cannot find value `b`".to_string()
+                )]
+            );
+        });
+    };
+
+    // The buffer's diagnostics are pulled once the server has started.
+    cx.executor().advance_clock(Duration::from_secs(1));
+    requests.next().await.unwrap();
+    cx.executor().run_until_parked();
+    assert_diagnostics(cx);
+
+    // After an edit, the server reports that the diagnostics didn't change.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..1, "")], None, cx));
+    cx.executor().advance_clock(Duration::from_secs(1));
+    requests.next().await.unwrap();
+    cx.executor().run_until_parked();
+    assert_diagnostics(cx);
+    assert_eq!(
+        *previous_result_ids.lock(),
+        [None, Some("1".to_string())],
+        "edits in quick succession result in a single request"
+    );
+}
+
+#[gpui::test]
+async fn test_pull_workspace_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "mod b;\n",
+            "b.rs": "fn b() {}\n",
+        }),
     )
     .await;
 
     let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+
+    let workspace_requests = Arc::new(Mutex::new(Vec::new()));
+    let mut fake_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions {
+                        identifier: None,
+                        inter_file_dependencies: true,
+                        workspace_diagnostics: true,
+                        work_done_progress_options: Default::default(),
+                    },
+                )),
+                ..Default::default()
+            },
+            initializer: Some(Box::new({
+                let workspace_requests = workspace_requests.clone();
+                move |fake_server| {
+                    let workspace_requests = workspace_requests.clone();
+                    fake_server.handle_request::<lsp::request::WorkspaceDiagnosticRequest, _, _>(
+                        move |params, _| {
+                            workspace_requests
+                                .lock()
+                                .push(params.previous_result_ids.clone());
+                            async move {
+                                Ok(lsp::WorkspaceDiagnosticReportResult::Report(
+                                    lsp::WorkspaceDiagnosticReport {
+                                        items: vec![lsp::WorkspaceDocumentDiagnosticReport::Full(
+                                            lsp::WorkspaceFullDocumentDiagnosticReport {
+                                                uri: Url::from_file_path("/dir/b.rs").unwrap(),
+                                                version: None,
+                                                full_document_diagnostic_report:
+                                                    lsp::FullDocumentDiagnosticReport {
+                                                        result_id: Some("b1".to_string()),
+                                                        items: vec![lsp::Diagnostic {
+                                                            range: lsp::Range::new(
+                                                                lsp::Position::new(0, 3),
+                                                                lsp::Position::new(0, 4),
+                                                            ),
+                                                            severity: Some(
+                                                                lsp::DiagnosticSeverity::WARNING,
+                                                            ),
+                                                            message: "function `b` is never used"
+                                                                .to_string(),
+                                                            ..Default::default()
+                                                        }],
+                                                    },
+                                            },
+                                        )],
+                                    },
+                                ))
+                            }
+                        },
+                    );
+                    fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>(
+                        |_, _| async move {
+                            Ok(lsp::DocumentDiagnosticReportResult::Report(
+                                lsp::DocumentDiagnosticReport::Full(
+                                    lsp::RelatedFullDocumentDiagnosticReport {
+                                        related_documents: None,
+                                        full_document_diagnostic_report:
+                                            lsp::FullDocumentDiagnosticReport {
+                                                result_id: None,
+                                                items: Vec::new(),
+                                            },
+                                    },
+                                ),
+                            ))
+                        },
+                    );
+                }
+            })),
+            ..Default::default()
+        },
+    );
+
     let buffer = project
         .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
         .await
         .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+    cx.executor().run_until_parked();
 
-    // Simulate the language server sending us a small edit in the form of a very large diff.
-    // Rust-analyzer does this when performing a merge-imports code action.
-    let edits = project
-        .update(cx, |project, cx| {
-            project.edits_from_lsp(
-                &buffer,
-                [
-                    // Replace the first use statement without editing the semicolon.
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(0, 4), lsp::Position::new(0, 8)),
-                        new_text: "a::{b, c}".into(),
-                    },
-                    // Reinsert the remainder of the file between the semicolon and the final
-                    // newline of the file.
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 9)),
-                        new_text: "\n\n".into(),
-                    },
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 9)),
-                        new_text: "
-                            fn f() {
-                                b();
-                                c();
-                            }"
-                        .unindent(),
-                    },
-                    // Delete everything after the first newline of the file.
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(1, 0), lsp::Position::new(7, 0)),
-                        new_text: "".into(),
-                    },
-                ],
-                LanguageServerId(0),
-                None,
-                cx,
-            )
-        })
+    // Diagnostics are reported for files that aren't open.
+    project.update(cx, |project, cx| {
+        assert_eq!(project.diagnostic_summary(false, cx).warning_count, 1);
+    });
+    assert_eq!(*workspace_requests.lock(), [Vec::new()]);
+
+    // When the server asks for a refresh, the result ids of the previous reports are sent back.
+    fake_server
+        .request::<lsp::request::WorkspaceDiagnosticRefresh>(())
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(
+        *workspace_requests.lock(),
+        [
+            Vec::new(),
+            vec![lsp::PreviousResultId {
+                uri: Url::from_file_path("/dir/b.rs").unwrap(),
+                value: "b1".to_string(),
+            }]
+        ]
+    );
+    project.update(cx, |project, cx| {
+        assert_eq!(project.diagnostic_summary(false, cx).warning_count, 1);
+    });
+
+    // The server replied right away, so it isn't asked again until something changes.
+    cx.executor().advance_clock(Duration::from_secs(60));
+    cx.executor().run_until_parked();
+    assert_eq!(workspace_requests.lock().len(), 2);
+
+    // Saving a buffer requests the workspace diagnostics again.
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
         .await
         .unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(workspace_requests.lock().len(), 3);
+}
 
-    buffer.update(cx, |buffer, cx| {
-        let edits = edits
-            .into_iter()
-            .map(|(range, text)| {
-                (
-                    range.start.to_point(buffer)..range.end.to_point(buffer),
-                    text,
-                )
-            })
-            .collect::<Vec<_>>();
+#[gpui::test]
+async fn test_pull_workspace_diagnostics_long_poll_and_retries(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
 
-        assert_eq!(
-            edits,
-            [
-                (Point::new(0, 4)..Point::new(0, 8), "a::{b, c}".into()),
-                (Point::new(1, 0)..Point::new(2, 0), "".into())
-            ]
-        );
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": "fn a() {}\n" }))
+        .await;
 
-        for (range, new_text) in edits {
-            buffer.edit([(range, new_text)], None, cx);
-        }
-        assert_eq!(
-            buffer.text(),
-            "
-                use a::{b, c};
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
 
-                fn f() {
-                    b();
-                    c();
+    // The server holds the first two requests open until its diagnostics change, then fails twice.
+    let workspace_request_count = Arc::new(AtomicUsize::new(0));
+    let mut fake_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions {
+                        identifier: None,
+                        inter_file_dependencies: false,
+                        workspace_diagnostics: true,
+                        work_done_progress_options: Default::default(),
+                    },
+                )),
+                ..Default::default()
+            },
+            initializer: Some(Box::new({
+                let workspace_request_count = workspace_request_count.clone();
+                move |fake_server| {
+                    let workspace_request_count = workspace_request_count.clone();
+                    fake_server.handle_request::<lsp::request::WorkspaceDiagnosticRequest, _, _>(
+                        move |_, cx| {
+                            let request_ix = workspace_request_count.fetch_add(1, SeqCst);
+                            let executor = cx.background_executor().clone();
+                            async move {
+                                match request_ix {
+                                    0 | 1 => executor.timer(Duration::from_secs(10)).await,
+                                    2 | 3 => return Err(anyhow!("workspace is still loading")),
+                                    _ => {}
+                                }
+                                Ok(lsp::WorkspaceDiagnosticReportResult::Report(
+                                    lsp::WorkspaceDiagnosticReport { items: Vec::new() },
+                                ))
+                            }
+                        },
+                    );
+                    fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>(
+                        |_, _| async move {
+                            Ok(lsp::DocumentDiagnosticReportResult::Report(
+                                lsp::DocumentDiagnosticReport::Full(
+                                    lsp::RelatedFullDocumentDiagnosticReport {
+                                        related_documents: None,
+                                        full_document_diagnostic_report:
+                                            lsp::FullDocumentDiagnosticReport {
+                                                result_id: None,
+                                                items: Vec::new(),
+                                            },
+                                    },
+                                ),
+                            ))
+                        },
+                    );
                 }
-            "
-            .unindent()
-        );
-    });
+            })),
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let _fake_server = fake_servers.next().await.unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(workspace_request_count.load(SeqCst), 1);
+
+    // Saving while a request is held open doesn't issue another one.
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(workspace_request_count.load(SeqCst), 1);
+
+    // Requests that were held open are issued again as soon as they return.
+    cx.executor().advance_clock(Duration::from_secs(10));
+    cx.executor().run_until_parked();
+    assert_eq!(workspace_request_count.load(SeqCst), 2);
+    cx.executor().advance_clock(Duration::from_secs(10));
+    cx.executor().run_until_parked();
+    assert_eq!(workspace_request_count.load(SeqCst), 3);
+
+    // Failed requests are retried after an increasing delay.
+    cx.executor().advance_clock(Duration::from_millis(999));
+    cx.executor().run_until_parked();
+    assert_eq!(workspace_request_count.load(SeqCst), 3);
+    cx.executor().advance_clock(Duration::from_millis(1));
+    cx.executor().run_until_parked();
+    assert_eq!(workspace_request_count.load(SeqCst), 4);
+    cx.executor().advance_clock(Duration::from_millis(1999));
+    cx.executor().run_until_parked();
+    assert_eq!(workspace_request_count.load(SeqCst), 4);
+    cx.executor().advance_clock(Duration::from_millis(1));
+    cx.executor().run_until_parked();
+    assert_eq!(workspace_request_count.load(SeqCst), 5);
+
+    // The server replied right away, so it isn't polled.
+    cx.executor().advance_clock(Duration::from_secs(60));
+    cx.executor().run_until_parked();
+    assert_eq!(workspace_request_count.load(SeqCst), 5);
 }
 
 #[gpui::test]
-async fn test_invalid_edits_from_lsp2(cx: &mut gpui::TestAppContext) {
+async fn test_edits_from_lsp2_with_past_version(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
     let text = "
-        use a::b;
-        use a::c;
-
-        fn f() {
-            b();
-            c();
+        fn a() {
+            f1();
+        }
+        fn b() {
+            f2();
+        }
+        fn c() {
+            f3();
         }
     "
     .unindent();
@@ -2299,30 +2632,180 @@ async fn test_invalid_edits_from_lsp2(cx: &mut gpui::TestAppContext) {
     .await;
 
     let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers =
+        language_registry.register_fake_lsp_adapter("Rust", FakeLspAdapter::default());
+
     let buffer = project
         .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
         .await
         .unwrap();
 
-    // Simulate the language server sending us edits in a non-ordered fashion,
-    // with ranges sometimes being inverted or pointing to invalid locations.
-    let edits = project
-        .update(cx, |project, cx| {
-            project.edits_from_lsp(
-                &buffer,
-                [
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 9)),
-                        new_text: "\n\n".into(),
-                    },
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(0, 8), lsp::Position::new(0, 4)),
-                        new_text: "a::{b, c}".into(),
-                    },
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(1, 0), lsp::Position::new(99, 0)),
+    let mut fake_server = fake_servers.next().await.unwrap();
+    let lsp_document_version = fake_server
+        .receive_notification::<lsp::notification::DidOpenTextDocument>()
+        .await
+        .text_document
+        .version;
+
+    // Simulate editing the buffer after the language server computes some edits.
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit(
+            [(
+                Point::new(0, 0)..Point::new(0, 0),
+                "// above first function\n",
+            )],
+            None,
+            cx,
+        );
+        buffer.edit(
+            [(
+                Point::new(2, 0)..Point::new(2, 0),
+                "    // inside first function\n",
+            )],
+            None,
+            cx,
+        );
+        buffer.edit(
+            [(
+                Point::new(6, 4)..Point::new(6, 4),
+                "// inside second function ",
+            )],
+            None,
+            cx,
+        );
+
+        assert_eq!(
+            buffer.text(),
+            "
+                // above first function
+                fn a() {
+                    // inside first function
+                    f1();
+                }
+                fn b() {
+                    // inside second function f2();
+                }
+                fn c() {
+                    f3();
+                }
+            "
+            .unindent()
+        );
+    });
+
+    let edits = project
+        .update(cx, |project, cx| {
+            project.edits_from_lsp(
+                &buffer,
+                vec![
+                    // replace body of first function
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(3, 0)),
+                        new_text: "
+                            fn a() {
+                                f10();
+                            }
+                            "
+                        .unindent(),
+                    },
+                    // edit inside second function
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(4, 6), lsp::Position::new(4, 6)),
+                        new_text: "00".into(),
+                    },
+                    // edit inside third function via two distinct edits
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(7, 5), lsp::Position::new(7, 5)),
+                        new_text: "4000".into(),
+                    },
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(7, 5), lsp::Position::new(7, 6)),
                         new_text: "".into(),
                     },
+                ],
+                LanguageServerId(0),
+                Some(lsp_document_version),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+
+    buffer.update(cx, |buffer, cx| {
+        for (range, new_text) in edits {
+            buffer.edit([(range, new_text)], None, cx);
+        }
+        assert_eq!(
+            buffer.text(),
+            "
+                // above first function
+                fn a() {
+                    // inside first function
+                    f10();
+                }
+                fn b() {
+                    // inside second function f200();
+                }
+                fn c() {
+                    f4000();
+                }
+                "
+            .unindent()
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_edits_from_lsp2_with_edits_on_adjacent_lines(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let text = "
+        use a::b;
+        use a::c;
+
+        fn f() {
+            b();
+            c();
+        }
+    "
+    .unindent();
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": text.clone(),
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+
+    // Simulate the language server sending us a small edit in the form of a very large diff.
+    // Rust-analyzer does this when performing a merge-imports code action.
+    let edits = project
+        .update(cx, |project, cx| {
+            project.edits_from_lsp(
+                &buffer,
+                [
+                    // Replace the first use statement without editing the semicolon.
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(0, 4), lsp::Position::new(0, 8)),
+                        new_text: "a::{b, c}".into(),
+                    },
+                    // Reinsert the remainder of the file between the semicolon and the final
+                    // newline of the file.
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 9)),
+                        new_text: "\n\n".into(),
+                    },
                     lsp::TextEdit {
                         range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 9)),
                         new_text: "
@@ -2332,6 +2815,11 @@ async fn test_invalid_edits_from_lsp2(cx: &mut gpui::TestAppContext) {
                             }"
                         .unindent(),
                     },
+                    // Delete everything after the first newline of the file.
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(1, 0), lsp::Position::new(7, 0)),
+                        new_text: "".into(),
+                    },
                 ],
                 LanguageServerId(0),
                 None,
@@ -2378,57 +2866,161 @@ async fn test_invalid_edits_from_lsp2(cx: &mut gpui::TestAppContext) {
     });
 }
 
-fn chunks_with_diagnostics<T: ToOffset + ToPoint>(
-    buffer: &Buffer,
-    range: Range<T>,
-) -> Vec<(String, Option<DiagnosticSeverity>)> {
-    let mut chunks: Vec<(String, Option<DiagnosticSeverity>)> = Vec::new();
-    for chunk in buffer.snapshot().chunks(range, true) {
-        if chunks.last().map_or(false, |prev_chunk| {
-            prev_chunk.1 == chunk.diagnostic_severity
-        }) {
-            chunks.last_mut().unwrap().0.push_str(chunk.text);
-        } else {
-            chunks.push((chunk.text.to_string(), chunk.diagnostic_severity));
-        }
-    }
-    chunks
-}
-
-#[gpui::test(iterations = 10)]
-async fn test_definition(cx: &mut gpui::TestAppContext) {
+#[gpui::test]
+async fn test_invalid_edits_from_lsp2(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
+    let text = "
+        use a::b;
+        use a::c;
+
+        fn f() {
+            b();
+            c();
+        }
+    "
+    .unindent();
+
     let fs = FakeFs::new(cx.executor());
     fs.insert_tree(
         "/dir",
         json!({
-            "a.rs": "const fn a() { A }",
-            "b.rs": "const y: i32 = crate::a()",
+            "a.rs": text.clone(),
         }),
     )
     .await;
 
-    let project = Project::test(fs, ["/dir/b.rs".as_ref()], cx).await;
-
-    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
-    language_registry.add(rust_lang());
-    let mut fake_servers =
-        language_registry.register_fake_lsp_adapter("Rust", FakeLspAdapter::default());
-
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
     let buffer = project
-        .update(cx, |project, cx| project.open_local_buffer("/dir/b.rs", cx))
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
         .await
         .unwrap();
 
-    let fake_server = fake_servers.next().await.unwrap();
-    fake_server.handle_request::<lsp::request::GotoDefinition, _, _>(|params, _| async move {
-        let params = params.text_document_position_params;
-        assert_eq!(
-            params.text_document.uri.to_file_path().unwrap(),
-            Path::new("/dir/b.rs"),
-        );
-        assert_eq!(params.position, lsp::Position::new(0, 22));
+    // Simulate the language server sending us edits in a non-ordered fashion,
+    // with ranges sometimes being inverted or pointing to invalid locations.
+    let edits = project
+        .update(cx, |project, cx| {
+            project.edits_from_lsp(
+                &buffer,
+                [
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 9)),
+                        new_text: "\n\n".into(),
+                    },
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(0, 8), lsp::Position::new(0, 4)),
+                        new_text: "a::{b, c}".into(),
+                    },
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(1, 0), lsp::Position::new(99, 0)),
+                        new_text: "".into(),
+                    },
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 9)),
+                        new_text: "
+                            fn f() {
+                                b();
+                                c();
+                            }"
+                        .unindent(),
+                    },
+                ],
+                LanguageServerId(0),
+                None,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+
+    buffer.update(cx, |buffer, cx| {
+        let edits = edits
+            .into_iter()
+            .map(|(range, text)| {
+                (
+                    range.start.to_point(buffer)..range.end.to_point(buffer),
+                    text,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            edits,
+            [
+                (Point::new(0, 4)..Point::new(0, 8), "a::{b, c}".into()),
+                (Point::new(1, 0)..Point::new(2, 0), "".into())
+            ]
+        );
+
+        for (range, new_text) in edits {
+            buffer.edit([(range, new_text)], None, cx);
+        }
+        assert_eq!(
+            buffer.text(),
+            "
+                use a::{b, c};
+
+                fn f() {
+                    b();
+                    c();
+                }
+            "
+            .unindent()
+        );
+    });
+}
+
+fn chunks_with_diagnostics<T: ToOffset + ToPoint>(
+    buffer: &Buffer,
+    range: Range<T>,
+) -> Vec<(String, Option<DiagnosticSeverity>)> {
+    let mut chunks: Vec<(String, Option<DiagnosticSeverity>)> = Vec::new();
+    for chunk in buffer.snapshot().chunks(range, true) {
+        if chunks.last().map_or(false, |prev_chunk| {
+            prev_chunk.1 == chunk.diagnostic_severity
+        }) {
+            chunks.last_mut().unwrap().0.push_str(chunk.text);
+        } else {
+            chunks.push((chunk.text.to_string(), chunk.diagnostic_severity));
+        }
+    }
+    chunks
+}
+
+#[gpui::test(iterations = 10)]
+async fn test_definition(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "const fn a() { A }",
+            "b.rs": "const y: i32 = crate::a()",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir/b.rs".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers =
+        language_registry.register_fake_lsp_adapter("Rust", FakeLspAdapter::default());
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/b.rs", cx))
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.handle_request::<lsp::request::GotoDefinition, _, _>(|params, _| async move {
+        let params = params.text_document_position_params;
+        assert_eq!(
+            params.text_document.uri.to_file_path().unwrap(),
+            Path::new("/dir/b.rs"),
+        );
+        assert_eq!(params.position, lsp::Position::new(0, 22));
 
         Ok(Some(lsp::GotoDefinitionResponse::Scalar(
             lsp::Location::new(
@@ -3719,66 +4311,848 @@ async fn test_grouped_diagnostics(cx: &mut gpui::TestAppContext) {
         ]
     );
 
-    assert_eq!(
-        buffer.diagnostic_group::<Point>(0).collect::<Vec<_>>(),
-        &[
-            DiagnosticEntry {
-                range: Point::new(1, 13)..Point::new(1, 15),
-                diagnostic: Diagnostic {
-                    severity: DiagnosticSeverity::HINT,
-                    message: "error 2 hint 1".to_string(),
-                    group_id: 0,
-                    is_primary: false,
-                    ..Default::default()
-                }
-            },
-            DiagnosticEntry {
-                range: Point::new(1, 13)..Point::new(1, 15),
-                diagnostic: Diagnostic {
-                    severity: DiagnosticSeverity::HINT,
-                    message: "error 2 hint 2".to_string(),
-                    group_id: 0,
-                    is_primary: false,
-                    ..Default::default()
-                }
-            },
-            DiagnosticEntry {
-                range: Point::new(2, 8)..Point::new(2, 17),
-                diagnostic: Diagnostic {
-                    severity: DiagnosticSeverity::ERROR,
-                    message: "error 2".to_string(),
-                    group_id: 0,
-                    is_primary: true,
-                    ..Default::default()
-                }
+    assert_eq!(
+        buffer.diagnostic_group::<Point>(0).collect::<Vec<_>>(),
+        &[
+            DiagnosticEntry {
+                range: Point::new(1, 13)..Point::new(1, 15),
+                diagnostic: Diagnostic {
+                    severity: DiagnosticSeverity::HINT,
+                    message: "error 2 hint 1".to_string(),
+                    group_id: 0,
+                    is_primary: false,
+                    ..Default::default()
+                }
+            },
+            DiagnosticEntry {
+                range: Point::new(1, 13)..Point::new(1, 15),
+                diagnostic: Diagnostic {
+                    severity: DiagnosticSeverity::HINT,
+                    message: "error 2 hint 2".to_string(),
+                    group_id: 0,
+                    is_primary: false,
+                    ..Default::default()
+                }
+            },
+            DiagnosticEntry {
+                range: Point::new(2, 8)..Point::new(2, 17),
+                diagnostic: Diagnostic {
+                    severity: DiagnosticSeverity::ERROR,
+                    message: "error 2".to_string(),
+                    group_id: 0,
+                    is_primary: true,
+                    ..Default::default()
+                }
+            }
+        ]
+    );
+
+    assert_eq!(
+        buffer.diagnostic_group::<Point>(1).collect::<Vec<_>>(),
+        &[
+            DiagnosticEntry {
+                range: Point::new(1, 8)..Point::new(1, 9),
+                diagnostic: Diagnostic {
+                    severity: DiagnosticSeverity::WARNING,
+                    message: "error 1".to_string(),
+                    group_id: 1,
+                    is_primary: true,
+                    ..Default::default()
+                }
+            },
+            DiagnosticEntry {
+                range: Point::new(1, 8)..Point::new(1, 9),
+                diagnostic: Diagnostic {
+                    severity: DiagnosticSeverity::HINT,
+                    message: "error 1 hint 1".to_string(),
+                    group_id: 1,
+                    is_primary: false,
+                    ..Default::default()
+                }
+            },
+        ]
+    );
+}
+
+#[gpui::test]
+async fn test_code_lens(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn one() {}\n\nfn two() {}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                code_lens_provider: Some(lsp::CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                execute_command_provider: Some(lsp::ExecuteCommandOptions {
+                    commands: vec!["show-references".to_string()],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    let references_command = lsp::Command {
+        title: "2 references".to_string(),
+        command: "show-references".to_string(),
+        arguments: Some(vec![json!(2)]),
+    };
+    fake_server.handle_request::<lsp::request::CodeLensRequest, _, _>(|params, _| async move {
+        assert_eq!(params.text_document.uri.as_str(), "file:///dir/a.rs");
+        Ok(Some(vec![
+            lsp::CodeLens {
+                range: lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 6)),
+                command: None,
+                data: Some(json!("one")),
+            },
+            lsp::CodeLens {
+                range: lsp::Range::new(lsp::Position::new(2, 3), lsp::Position::new(2, 6)),
+                command: Some(lsp::Command {
+                    title: "Run".to_string(),
+                    command: "run".to_string(),
+                    arguments: None,
+                }),
+                data: None,
+            },
+        ]))
+    });
+    fake_server.handle_request::<lsp::request::CodeLensResolve, _, _>({
+        let references_command = references_command.clone();
+        move |lens, _| {
+            let references_command = references_command.clone();
+            async move {
+                assert_eq!(lens.data, Some(json!("one")));
+                Ok(lsp::CodeLens {
+                    command: Some(references_command),
+                    ..lens
+                })
+            }
+        }
+    });
+
+    let lenses = project
+        .update(cx, |project, cx| project.code_lens(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(lenses.len(), 2);
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            lenses[0].range.to_point(buffer),
+            Point::new(0, 3)..Point::new(0, 6)
+        );
+        assert_eq!(
+            lenses[1].range.to_point(buffer),
+            Point::new(2, 3)..Point::new(2, 6)
+        );
+    });
+    assert!(lenses[0].command().is_none());
+    assert!(!lenses[0].executable);
+    assert_eq!(lenses[1].command().unwrap().title, "Run");
+    // The server doesn't advertise the command, so the lens is only shown as a label.
+    assert!(!lenses[1].executable);
+
+    let resolved = project
+        .update(cx, |project, cx| {
+            project.resolve_code_lens(&buffer, lenses[0].clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(resolved.command(), Some(&references_command));
+    assert!(resolved.executable);
+
+    let mut executed_commands =
+        fake_server.handle_request::<lsp::request::ExecuteCommand, _, _>(|params, _| async move {
+            assert_eq!(params.command, "show-references");
+            assert_eq!(params.arguments, vec![json!(2)]);
+            Ok(None)
+        });
+    project
+        .update(cx, |project, cx| {
+            project.execute_code_lens_command(&buffer, references_command.clone(), cx)
+        })
+        .await
+        .unwrap();
+    executed_commands.next().await.unwrap();
+
+    // Commands that the server doesn't advertise aren't sent to it.
+    let error = project
+        .update(cx, |project, cx| {
+            project.execute_code_lens_command(&buffer, lenses[1].command().unwrap().clone(), cx)
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "The language server doesn't support the \"Run\" command"
+    );
+}
+
+#[gpui::test]
+async fn test_semantic_tokens(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn one() {}\n\nfn two() {}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                semantic_tokens_provider: Some(
+                    lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        lsp::SemanticTokensOptions {
+                            legend: lsp::SemanticTokensLegend {
+                                token_types: vec![
+                                    lsp::SemanticTokenType::FUNCTION,
+                                    lsp::SemanticTokenType::VARIABLE,
+                                ],
+                                token_modifiers: vec![lsp::SemanticTokenModifier::DECLARATION],
+                            },
+                            full: Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                            ..Default::default()
+                        },
+                    ),
+                ),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+    let buffer_id = buffer.read_with(cx, |buffer, _| buffer.remote_id());
+
+    let mut full_requests = fake_server
+        .handle_request::<lsp::request::SemanticTokensFullRequest, _, _>(|params, _| async move {
+            assert_eq!(params.text_document.uri.as_str(), "file:///dir/a.rs");
+            Ok(Some(lsp::SemanticTokensResult::Tokens(
+                lsp::SemanticTokens {
+                    result_id: Some("1".to_string()),
+                    data: vec![
+                        lsp::SemanticToken {
+                            delta_line: 0,
+                            delta_start: 3,
+                            length: 3,
+                            token_type: 0,
+                            token_modifiers_bitset: 1,
+                        },
+                        lsp::SemanticToken {
+                            delta_line: 2,
+                            delta_start: 3,
+                            length: 3,
+                            token_type: 0,
+                            token_modifiers_bitset: 0,
+                        },
+                    ],
+                },
+            )))
+        });
+
+    // Positions are decoded relative to the previous token.
+    let tokens = project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap()
+        .unwrap();
+    full_requests.next().await.unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            tokens
+                .tokens
+                .iter()
+                .map(|token| (
+                    token.range.to_point(buffer),
+                    tokens.token_type(token).unwrap().to_string(),
+                    tokens.token_modifiers(token).collect::<Vec<_>>(),
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    Point::new(0, 3)..Point::new(0, 6),
+                    "function".to_string(),
+                    vec!["declaration"],
+                ),
+                (
+                    Point::new(2, 3)..Point::new(2, 6),
+                    "function".to_string(),
+                    vec![],
+                ),
+            ]
+        );
+    });
+
+    // Later requests only ask for the changes since the previous result.
+    let mut delta_requests = fake_server
+        .handle_request::<lsp::request::SemanticTokensFullDeltaRequest, _, _>(
+            |params, _| async move {
+                assert_eq!(params.previous_result_id, "1");
+                Ok(Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(
+                    lsp::SemanticTokensDelta {
+                        result_id: Some("2".to_string()),
+                        edits: vec![lsp::SemanticTokensEdit {
+                            start: 5,
+                            delete_count: 5,
+                            data: Some(vec![lsp::SemanticToken {
+                                delta_line: 2,
+                                delta_start: 3,
+                                length: 3,
+                                token_type: 1,
+                                token_modifiers_bitset: 1,
+                            }]),
+                        }],
+                    },
+                )))
+            },
+        );
+    let tokens = project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap()
+        .unwrap();
+    delta_requests.next().await.unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            tokens
+                .tokens
+                .iter()
+                .map(|token| (
+                    token.range.to_point(buffer),
+                    tokens.token_type(token).unwrap().to_string(),
+                    tokens.token_modifiers(token).collect::<Vec<_>>(),
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    Point::new(0, 3)..Point::new(0, 6),
+                    "function".to_string(),
+                    vec!["declaration"],
+                ),
+                (
+                    Point::new(2, 3)..Point::new(2, 6),
+                    "variable".to_string(),
+                    vec!["declaration"],
+                ),
+            ]
+        );
+    });
+
+    // Tokens sent to a peer keep their ranges, but aren't cached by the peer, so that
+    // it never sends a delta request for a result only the host has the data for.
+    let response = project.update(cx, |project, cx| {
+        GetSemanticTokens::response_to_proto(
+            Some(tokens.clone()),
+            project,
+            proto::PeerId::default(),
+            &buffer.read(cx).version(),
+            cx,
+        )
+    });
+    project.update(cx, |project, _| project.semantic_tokens.clear());
+    let round_tripped = GetSemanticTokens
+        .response_from_proto(response, project.clone(), buffer.clone(), cx.to_async())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(round_tripped.result_id.as_deref(), Some("2"));
+    assert_eq!(round_tripped.token_types, tokens.token_types);
+    assert_eq!(round_tripped.token_modifiers, tokens.token_modifiers);
+    assert_eq!(
+        round_tripped
+            .tokens
+            .iter()
+            .map(|token| (token.range.clone(), token.token_type, token.token_modifiers))
+            .collect::<Vec<_>>(),
+        tokens
+            .tokens
+            .iter()
+            .map(|token| (token.range.clone(), token.token_type, token.token_modifiers))
+            .collect::<Vec<_>>(),
+    );
+    project.read_with(cx, |project, _| {
+        assert!(project.semantic_tokens.get(&buffer_id).is_none());
+    });
+}
+
+#[gpui::test]
+async fn test_folding_ranges(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "use a;\nuse b;\n\nfn main() {\n    // one\n    // two\n    let x = 1;\n}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                folding_range_provider: Some(lsp::FoldingRangeProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    fake_server.handle_request::<lsp::request::FoldingRangeRequest, _, _>(|params, _| async move {
+        assert_eq!(params.text_document.uri.as_str(), "file:///dir/a.rs");
+        Ok(Some(vec![
+            lsp::FoldingRange {
+                start_line: 4,
+                end_line: 5,
+                kind: Some(lsp::FoldingRangeKind::Comment),
+                ..Default::default()
+            },
+            lsp::FoldingRange {
+                start_line: 3,
+                end_line: 6,
+                ..Default::default()
+            },
+            lsp::FoldingRange {
+                start_line: 0,
+                end_line: 1,
+                kind: Some(lsp::FoldingRangeKind::Imports),
+                ..Default::default()
+            },
+            // Ranges that don't span multiple lines can't be folded.
+            lsp::FoldingRange {
+                start_line: 7,
+                end_line: 7,
+                ..Default::default()
+            },
+        ]))
+    });
+
+    // Ranges span from the end of their first line to the end of their last one,
+    // and enclosing ranges come before the ranges they contain.
+    let ranges = project
+        .update(cx, |project, cx| project.folding_ranges(&buffer, cx))
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            ranges
+                .iter()
+                .map(|range| (range.range.to_point(buffer), range.kind))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    Point::new(0, 6)..Point::new(1, 6),
+                    Some(FoldingRangeKind::Imports)
+                ),
+                (Point::new(3, 11)..Point::new(6, 14), None),
+                (
+                    Point::new(4, 10)..Point::new(5, 10),
+                    Some(FoldingRangeKind::Comment)
+                ),
+            ]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_call_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn one() {\n    two();\n}\n\nfn two() {}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    let lsp_item = |name: &str, row: u32| lsp::CallHierarchyItem {
+        name: name.to_string(),
+        kind: lsp::SymbolKind::FUNCTION,
+        tags: None,
+        detail: None,
+        uri: lsp::Url::from_file_path("/dir/a.rs").unwrap(),
+        range: lsp::Range::new(lsp::Position::new(row, 0), lsp::Position::new(row, 11)),
+        selection_range: lsp::Range::new(lsp::Position::new(row, 3), lsp::Position::new(row, 6)),
+        data: None,
+    };
+    let one = lsp_item("one", 0);
+    let two = lsp_item("two", 4);
+    let call_range = lsp::Range::new(lsp::Position::new(1, 4), lsp::Position::new(1, 7));
+
+    fake_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>({
+        let two = two.clone();
+        move |params, _| {
+            let two = two.clone();
+            async move {
+                assert_eq!(
+                    params.text_document_position_params.position,
+                    lsp::Position::new(4, 4)
+                );
+                Ok(Some(vec![two]))
+            }
+        }
+    });
+    fake_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>({
+        let one = one.clone();
+        move |params, _| {
+            let one = one.clone();
+            async move {
+                assert_eq!(params.item.name, "two");
+                Ok(Some(vec![lsp::CallHierarchyIncomingCall {
+                    from: one,
+                    from_ranges: vec![call_range],
+                }]))
+            }
+        }
+    });
+    fake_server.handle_request::<lsp::request::CallHierarchyOutgoingCalls, _, _>({
+        let two = two.clone();
+        move |params, _| {
+            let two = two.clone();
+            async move {
+                assert_eq!(params.item.name, "one");
+                Ok(Some(vec![lsp::CallHierarchyOutgoingCall {
+                    to: two,
+                    from_ranges: vec![call_range],
+                }]))
+            }
+        }
+    });
+
+    let prepared = project
+        .update(cx, |project, cx| {
+            project.prepare_call_hierarchy(&buffer, Point::new(4, 4), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(prepared.len(), 1);
+    assert_eq!(prepared[0].lsp_item, two);
+    assert!(prepared[0].call_sites.is_empty());
+
+    // Incoming calls keep the ranges of the calls within the caller.
+    let incoming = project
+        .update(cx, |project, cx| project.incoming_calls(&prepared[0], cx))
+        .await
+        .unwrap();
+    assert_eq!(incoming.len(), 1);
+    assert_eq!(incoming[0].lsp_item, one);
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            incoming[0].location.range.to_point(buffer),
+            Point::new(0, 3)..Point::new(0, 6)
+        );
+        assert_eq!(
+            incoming[0]
+                .call_sites
+                .iter()
+                .map(|range| range.to_point(buffer))
+                .collect::<Vec<_>>(),
+            vec![Point::new(1, 4)..Point::new(1, 7)]
+        );
+    });
+
+    // The ranges of outgoing calls lie within the caller, so they aren't kept.
+    let outgoing = project
+        .update(cx, |project, cx| project.outgoing_calls(&incoming[0], cx))
+        .await
+        .unwrap();
+    assert_eq!(outgoing.len(), 1);
+    assert_eq!(outgoing[0].lsp_item, two);
+    assert!(outgoing[0].call_sites.is_empty());
+
+    // Requests and responses survive a round trip through the protocol.
+    let message = buffer.read_with(cx, |buffer, _| {
+        PrepareCallHierarchy {
+            position: PointUtf16::new(4, 4),
+        }
+        .to_proto(1, buffer)
+    });
+    let request =
+        PrepareCallHierarchy::from_proto(message, project.clone(), buffer.clone(), cx.to_async())
+            .await
+            .unwrap();
+    assert_eq!(request.position, PointUtf16::new(4, 4));
+
+    let message = buffer.read_with(cx, |buffer, _| {
+        GetIncomingCalls { item: two.clone() }.to_proto(1, buffer)
+    });
+    let request =
+        GetIncomingCalls::from_proto(message, project.clone(), buffer.clone(), cx.to_async())
+            .await
+            .unwrap();
+    assert_eq!(request.item, two);
+
+    let response = project.update(cx, |project, cx| {
+        GetIncomingCalls::response_to_proto(
+            incoming.clone(),
+            project,
+            proto::PeerId::default(),
+            &clock::Global::new(),
+            cx,
+        )
+    });
+    let round_tripped = request
+        .response_from_proto(response, project.clone(), buffer.clone(), cx.to_async())
+        .await
+        .unwrap();
+    assert_eq!(round_tripped.len(), 1);
+    assert_eq!(round_tripped[0].lsp_item, one);
+    assert_eq!(round_tripped[0].location.buffer, buffer);
+    assert_eq!(round_tripped[0].location.range, incoming[0].location.range);
+    assert_eq!(round_tripped[0].call_sites, incoming[0].call_sites);
+}
+
+#[gpui::test]
+async fn test_type_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "trait Shape {}\n\nstruct Circle;\n\nimpl Shape for Circle {}\n",
+            "b.rs": "struct Square;\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            name: "with-type-hierarchy",
+            capabilities: serde_json::from_value(json!({ "typeHierarchyProvider": {} })).unwrap(),
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    let lsp_item = |name: &str, path: &str, row: u32, column: u32| lsp::TypeHierarchyItem {
+        name: name.to_string(),
+        kind: lsp::SymbolKind::STRUCT,
+        tags: None,
+        detail: None,
+        uri: lsp::Url::from_file_path(path).unwrap(),
+        range: lsp::Range::new(lsp::Position::new(row, 0), lsp::Position::new(row, 20)),
+        selection_range: lsp::Range::new(
+            lsp::Position::new(row, column),
+            lsp::Position::new(row, column + name.len() as u32),
+        ),
+        data: None,
+    };
+    let shape = lsp_item("Shape", "/dir/a.rs", 0, 6);
+    let circle = lsp_item("Circle", "/dir/a.rs", 2, 7);
+    let square = lsp_item("Square", "/dir/b.rs", 0, 7);
+
+    fake_server.handle_request::<lsp::request::TypeHierarchyPrepare, _, _>({
+        let shape = shape.clone();
+        move |_, _| {
+            let shape = shape.clone();
+            async move { Ok(Some(vec![shape])) }
+        }
+    });
+    fake_server.handle_request::<lsp::request::TypeHierarchySubtypes, _, _>({
+        let circle = circle.clone();
+        let square = square.clone();
+        move |params, _| {
+            let circle = circle.clone();
+            let square = square.clone();
+            async move {
+                assert_eq!(params.item.name, "Shape");
+                Ok(Some(vec![circle, square]))
             }
-        ]
-    );
+        }
+    });
+    fake_server.handle_request::<lsp::request::TypeHierarchySupertypes, _, _>({
+        let shape = shape.clone();
+        move |params, _| {
+            let shape = shape.clone();
+            async move {
+                assert_eq!(params.item.name, "Circle");
+                Ok(Some(vec![shape]))
+            }
+        }
+    });
 
+    let prepared = project
+        .update(cx, |project, cx| {
+            project.prepare_type_hierarchy(&buffer, Point::new(0, 8), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(prepared.len(), 1);
+    assert_eq!(prepared[0].lsp_item, shape);
+
+    // Subtypes in other files are opened in their own buffers.
+    let subtypes = project
+        .update(cx, |project, cx| project.subtypes(&prepared[0], cx))
+        .await
+        .unwrap();
     assert_eq!(
-        buffer.diagnostic_group::<Point>(1).collect::<Vec<_>>(),
-        &[
-            DiagnosticEntry {
-                range: Point::new(1, 8)..Point::new(1, 9),
-                diagnostic: Diagnostic {
-                    severity: DiagnosticSeverity::WARNING,
-                    message: "error 1".to_string(),
-                    group_id: 1,
-                    is_primary: true,
-                    ..Default::default()
-                }
-            },
-            DiagnosticEntry {
-                range: Point::new(1, 8)..Point::new(1, 9),
-                diagnostic: Diagnostic {
-                    severity: DiagnosticSeverity::HINT,
-                    message: "error 1 hint 1".to_string(),
-                    group_id: 1,
-                    is_primary: false,
-                    ..Default::default()
-                }
-            },
-        ]
+        subtypes
+            .iter()
+            .map(|item| item.lsp_item.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Circle", "Square"]
+    );
+    assert_eq!(subtypes[0].location.buffer, buffer);
+    subtypes[1].location.buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.file().unwrap().path().as_ref(), Path::new("b.rs"));
+        assert_eq!(
+            subtypes[1].location.range.to_point(buffer),
+            Point::new(0, 7)..Point::new(0, 13)
+        );
+    });
+
+    let supertypes = project
+        .update(cx, |project, cx| project.supertypes(&subtypes[0], cx))
+        .await
+        .unwrap();
+    assert_eq!(supertypes.len(), 1);
+    assert_eq!(supertypes[0].lsp_item, shape);
+
+    let message = buffer.read_with(cx, |buffer, _| {
+        GetSubtypes {
+            item: shape.clone(),
+        }
+        .to_proto(1, buffer)
+    });
+    let request = GetSubtypes::from_proto(message, project.clone(), buffer.clone(), cx.to_async())
+        .await
+        .unwrap();
+    assert_eq!(request.item, shape);
+
+    let response = project.update(cx, |project, cx| {
+        GetSubtypes::response_to_proto(
+            subtypes.clone(),
+            project,
+            proto::PeerId::default(),
+            &clock::Global::new(),
+            cx,
+        )
+    });
+    let round_tripped = request
+        .response_from_proto(response, project.clone(), buffer.clone(), cx.to_async())
+        .await
+        .unwrap();
+    assert_eq!(round_tripped.len(), 2);
+    for (round_tripped, subtype) in round_tripped.iter().zip(&subtypes) {
+        assert_eq!(round_tripped.lsp_item, subtype.lsp_item);
+        assert_eq!(round_tripped.location.buffer, subtype.location.buffer);
+        assert_eq!(round_tripped.location.range, subtype.location.range);
+    }
+}
+
+#[gpui::test]
+async fn test_type_hierarchy_without_capability(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": "struct One;\n" }))
+        .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers =
+        language_registry.register_fake_lsp_adapter("Rust", FakeLspAdapter::default());
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+    let mut requests = fake_server
+        .handle_request::<lsp::request::TypeHierarchyPrepare, _, _>(|_, _| async move { Ok(None) });
+
+    let prepared = project
+        .update(cx, |project, cx| {
+            project.prepare_type_hierarchy(&buffer, Point::new(0, 8), cx)
+        })
+        .await
+        .unwrap();
+    assert!(prepared.is_empty());
+    cx.executor().run_until_parked();
+    assert!(
+        requests.try_next().is_err(),
+        "type hierarchy requested from a server that doesn't support it"
     );
 }
 
@@ -3916,6 +5290,198 @@ async fn test_rename(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_lsp_file_operations(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "main.rs": "mod one;",
+            "one.rs": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+
+    let rust_files = lsp::FileOperationRegistrationOptions {
+        filters: vec![lsp::FileOperationFilter {
+            scheme: Some("file".to_string()),
+            pattern: lsp::FileOperationPattern {
+                glob: "**/*.rs".to_string(),
+                matches: Some(lsp::FileOperationPatternKind::File),
+                options: None,
+            },
+        }],
+    };
+    let mut fake_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                workspace: Some(lsp::WorkspaceServerCapabilities {
+                    workspace_folders: None,
+                    file_operations: Some(lsp::WorkspaceFileOperationsServerCapabilities {
+                        did_create: Some(rust_files.clone()),
+                        will_create: None,
+                        did_rename: Some(rust_files.clone()),
+                        will_rename: Some(rust_files.clone()),
+                        did_delete: Some(rust_files),
+                        will_delete: None,
+                    }),
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/main.rs", cx)
+        })
+        .await
+        .unwrap();
+    let mut fake_server = fake_servers.next().await.unwrap();
+    let worktree_id = project.read_with(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+    let entry_id = |path: &str, cx: &mut gpui::TestAppContext| {
+        project.read_with(cx, |project, cx| {
+            project
+                .entry_for_path(&(worktree_id, path).into(), cx)
+                .unwrap()
+                .id
+        })
+    };
+
+    // Renaming a file lets the server update the references to it beforehand.
+    fake_server.handle_request::<lsp::request::WillRenameFiles, _, _>(|params, _| async move {
+        assert_eq!(
+            params.files,
+            [lsp::FileRename {
+                old_uri: "file:///dir/one.rs".to_string(),
+                new_uri: "file:///dir/two.rs".to_string(),
+            }]
+        );
+        Ok(Some(lsp::WorkspaceEdit {
+            changes: Some(
+                [(
+                    lsp::Url::from_file_path("/dir/main.rs").unwrap(),
+                    vec![lsp::TextEdit::new(
+                        lsp::Range::new(lsp::Position::new(0, 4), lsp::Position::new(0, 7)),
+                        "two".to_string(),
+                    )],
+                )]
+                .into_iter()
+                .collect(),
+            ),
+            ..Default::default()
+        }))
+    });
+    let one_id = entry_id("one.rs", cx);
+    project
+        .update(cx, |project, cx| {
+            project.rename_entry(one_id, Path::new("two.rs"), cx)
+        })
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| assert_eq!(buffer.text(), "mod two;"));
+    assert_eq!(
+        fake_server
+            .receive_notification::<lsp::notification::DidRenameFiles>()
+            .await
+            .files,
+        [lsp::FileRename {
+            old_uri: "file:///dir/one.rs".to_string(),
+            new_uri: "file:///dir/two.rs".to_string(),
+        }]
+    );
+
+    // Directories don't match the server's filters.
+    project
+        .update(cx, |project, cx| {
+            project.create_entry((worktree_id, "three"), true, cx)
+        })
+        .await
+        .unwrap();
+    project
+        .update(cx, |project, cx| {
+            project.create_entry((worktree_id, "three/four.rs"), false, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        fake_server
+            .receive_notification::<lsp::notification::DidCreateFiles>()
+            .await
+            .files,
+        [lsp::FileCreate {
+            uri: "file:///dir/three/four.rs".to_string(),
+        }]
+    );
+
+    // Copies are reported as created files.
+    let four_id = entry_id("three/four.rs", cx);
+    project
+        .update(cx, |project, cx| {
+            project.copy_entry(four_id, Path::new("five.rs"), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        fake_server
+            .receive_notification::<lsp::notification::DidCreateFiles>()
+            .await
+            .files,
+        [lsp::FileCreate {
+            uri: "file:///dir/five.rs".to_string(),
+        }]
+    );
+
+    // Renames don't wait for servers that don't respond in time.
+    fake_server.handle_request::<lsp::request::WillRenameFiles, _, _>(|_, _| async move {
+        futures::future::pending::<()>().await;
+        Ok(None)
+    });
+    let five_id = entry_id("five.rs", cx);
+    let rename = project.update(cx, |project, cx| {
+        project.rename_entry(five_id, Path::new("six.rs"), cx)
+    });
+    cx.executor().run_until_parked();
+    cx.executor().advance_clock(Duration::from_secs(5));
+    rename.await.unwrap();
+    assert_eq!(
+        fake_server
+            .receive_notification::<lsp::notification::DidRenameFiles>()
+            .await
+            .files,
+        [lsp::FileRename {
+            old_uri: "file:///dir/five.rs".to_string(),
+            new_uri: "file:///dir/six.rs".to_string(),
+        }]
+    );
+
+    let two_id = entry_id("two.rs", cx);
+    project
+        .update(cx, |project, cx| project.delete_entry(two_id, false, cx))
+        .unwrap()
+        .await
+        .unwrap();
+    assert_eq!(
+        fake_server
+            .receive_notification::<lsp::notification::DidDeleteFiles>()
+            .await
+            .files,
+        [lsp::FileDelete {
+            uri: "file:///dir/two.rs".to_string(),
+        }]
+    );
+}
+
 #[gpui::test]
 async fn test_search(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -0,0 +1,381 @@
+use std::{sync::Arc, time::Duration};
+
+use collections::HashMap;
+use futures::{channel::mpsc, StreamExt};
+use gpui::{Model, ModelContext, Task};
+use language::{Buffer, CachedLspAdapter};
+use lsp::{LanguageServer, LanguageServerId};
+use text::BufferId;
+use util::ResultExt;
+
+use crate::{File, LanguageServerState, Project};
+
+const PULL_DIAGNOSTICS_DEBOUNCE: Duration = Duration::from_millis(250);
+/// Workspace requests that take longer than this are assumed to have been held open by the server.
+const WORKSPACE_DIAGNOSTICS_LONG_POLL_THRESHOLD: Duration = Duration::from_secs(1);
+const WORKSPACE_DIAGNOSTICS_MIN_RETRY_DELAY: Duration = Duration::from_secs(1);
+const WORKSPACE_DIAGNOSTICS_MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Tracks the diagnostics requested from language servers that support the
+/// pull model (`textDocument/diagnostic` and `workspace/diagnostic`).
+#[derive(Default)]
+pub(crate) struct PullDiagnostics {
+    /// The result id of the last report for each document, which lets servers reply that nothing changed.
+    result_ids: HashMap<LanguageServerId, HashMap<lsp::Url, String>>,
+    document_pulls: HashMap<(BufferId, LanguageServerId), Task<()>>,
+    workspace_pulls: HashMap<LanguageServerId, WorkspacePull>,
+}
+
+struct WorkspacePull {
+    /// Asks for the workspace diagnostics to be requested again once the current request is done.
+    repull: mpsc::UnboundedSender<()>,
+    _task: Task<()>,
+}
+
+fn diagnostic_options(server: &LanguageServer) -> Option<lsp::DiagnosticOptions> {
+    match server.capabilities().diagnostic_provider? {
+        lsp::DiagnosticServerCapabilities::Options(options) => Some(options),
+        lsp::DiagnosticServerCapabilities::RegistrationOptions(options) => {
+            Some(options.diagnostic_options)
+        }
+    }
+}
+
+impl Project {
+    /// Requests the diagnostics of the buffer from its language servers that support pull diagnostics.
+    ///
+    /// Requests are debounced, so that a burst of edits results in a single request per server.
+    pub(crate) fn pull_diagnostics_for_buffer(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if !self.is_local() {
+            return;
+        }
+        let buffer = buffer.read(cx);
+        let buffer_id = buffer.remote_id();
+        let Some(file) = File::from_dyn(buffer.file()).and_then(|file| file.as_local()) else {
+            return;
+        };
+        let Some(uri) = lsp::Url::from_file_path(file.abs_path(cx)).log_err() else {
+            return;
+        };
+
+        let servers = self
+            .language_servers_for_buffer(buffer, cx)
+            .filter_map(|(adapter, server)| {
+                let options = diagnostic_options(server)?;
+                Some((adapter.clone(), server.clone(), options))
+            })
+            .collect::<Vec<_>>();
+        for (adapter, server, options) in servers {
+            let server_id = server.server_id();
+            let uri = uri.clone();
+            let task = cx.spawn(move |this, mut cx| async move {
+                cx.background_executor()
+                    .timer(PULL_DIAGNOSTICS_DEBOUNCE)
+                    .await;
+                // The report describes the document as the server knew it when it was requested.
+                let Some((previous_result_id, version)) = this
+                    .update(&mut cx, |this, _| {
+                        let previous_result_id = this
+                            .pull_diagnostics
+                            .result_ids
+                            .get(&server_id)
+                            .and_then(|result_ids| result_ids.get(&uri))
+                            .cloned();
+                        let version = this
+                            .buffer_snapshots
+                            .get(&buffer_id)
+                            .and_then(|snapshots| snapshots.get(&server_id))
+                            .and_then(|snapshots| snapshots.last())
+                            .map(|snapshot| snapshot.version);
+                        (previous_result_id, version)
+                    })
+                    .ok()
+                else {
+                    return;
+                };
+
+                let report = server
+                    .request::<lsp::request::DocumentDiagnosticRequest>(
+                        lsp::DocumentDiagnosticParams {
+                            text_document: lsp::TextDocumentIdentifier::new(uri.clone()),
+                            identifier: options.identifier,
+                            previous_result_id,
+                            work_done_progress_params: Default::default(),
+                            partial_result_params: Default::default(),
+                        },
+                    )
+                    .await;
+                let Some(report) = report.log_err() else {
+                    return;
+                };
+
+                let (report, related_documents) = match report {
+                    lsp::DocumentDiagnosticReportResult::Report(
+                        lsp::DocumentDiagnosticReport::Full(report),
+                    ) => (
+                        Some(lsp::DocumentDiagnosticReportKind::Full(
+                            report.full_document_diagnostic_report,
+                        )),
+                        report.related_documents,
+                    ),
+                    lsp::DocumentDiagnosticReportResult::Report(
+                        lsp::DocumentDiagnosticReport::Unchanged(report),
+                    ) => (
+                        Some(lsp::DocumentDiagnosticReportKind::Unchanged(
+                            report.unchanged_document_diagnostic_report,
+                        )),
+                        report.related_documents,
+                    ),
+                    lsp::DocumentDiagnosticReportResult::Partial(partial) => {
+                        (None, partial.related_documents)
+                    }
+                };
+                this.update(&mut cx, |this, cx| {
+                    // Related documents may have changed since, so their versions are unknown.
+                    let reports = report
+                        .map(|report| (uri, version, report))
+                        .into_iter()
+                        .chain(
+                            related_documents
+                                .unwrap_or_default()
+                                .into_iter()
+                                .map(|(uri, report)| (uri, None, report)),
+                        );
+                    for (uri, version, report) in reports {
+                        this.apply_pulled_diagnostics(
+                            server_id, &adapter, uri, version, report, cx,
+                        );
+                    }
+                })
+                .ok();
+            });
+            self.pull_diagnostics
+                .document_pulls
+                .insert((buffer_id, server_id), task);
+        }
+    }
+
+    /// Requests the diagnostics of every open buffer that the given language server is running for.
+    pub(crate) fn pull_diagnostics_for_server(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let buffers = self
+            .buffer_store
+            .read(cx)
+            .buffers()
+            .filter(|buffer| {
+                self.language_server_ids_for_buffer(buffer.read(cx), cx)
+                    .contains(&server_id)
+            })
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            self.pull_diagnostics_for_buffer(&buffer, cx);
+        }
+    }
+
+    /// Requests the diagnostics of the whole workspace, for servers that advertise workspace diagnostics.
+    ///
+    /// Servers may hold this request open until their diagnostics change, in which case it's issued
+    /// again as soon as it returns. Otherwise, it's only issued again when this is called, e.g. when
+    /// the server asks for a refresh or a buffer is saved. A request that is already in flight is
+    /// left alone, and failed requests are retried with an increasing delay.
+    pub(crate) fn pull_workspace_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if !self.is_local() {
+            return;
+        }
+        if let Some(pull) = self.pull_diagnostics.workspace_pulls.get(&server_id) {
+            if pull.repull.unbounded_send(()).is_ok() {
+                return;
+            }
+        }
+        let Some(LanguageServerState::Running {
+            adapter, server, ..
+        }) = self.language_servers.get(&server_id)
+        else {
+            return;
+        };
+        let Some(options) =
+            diagnostic_options(server).filter(|options| options.workspace_diagnostics)
+        else {
+            return;
+        };
+        let adapter = adapter.clone();
+        let server = server.clone();
+
+        let (repull, mut repull_requests) = mpsc::unbounded();
+        let task = cx.spawn(move |this, mut cx| async move {
+            let mut retry_delay = WORKSPACE_DIAGNOSTICS_MIN_RETRY_DELAY;
+            loop {
+                // The request below covers any repulls that were asked for in the meantime.
+                while let Ok(Some(())) = repull_requests.try_next() {}
+
+                let Ok(previous_result_ids) = this.update(&mut cx, |this, _| {
+                    this.pull_diagnostics
+                        .result_ids
+                        .get(&server_id)
+                        .into_iter()
+                        .flatten()
+                        .map(|(uri, result_id)| lsp::PreviousResultId {
+                            uri: uri.clone(),
+                            value: result_id.clone(),
+                        })
+                        .collect::<Vec<_>>()
+                }) else {
+                    return;
+                };
+                let requested_at = cx.background_executor().now();
+                let report = server
+                    .request::<lsp::request::WorkspaceDiagnosticRequest>(
+                        lsp::WorkspaceDiagnosticParams {
+                            identifier: options.identifier.clone(),
+                            previous_result_ids,
+                            work_done_progress_params: Default::default(),
+                            partial_result_params: Default::default(),
+                        },
+                    )
+                    .await;
+                let items = match report {
+                    Ok(lsp::WorkspaceDiagnosticReportResult::Report(report)) => report.items,
+                    Ok(lsp::WorkspaceDiagnosticReportResult::Partial(partial)) => partial.items,
+                    Err(error) => {
+                        log::error!(
+                            "failed to pull workspace diagnostics from {}, retrying in {:?}: {error:#}",
+                            server.name(),
+                            retry_delay
+                        );
+                        cx.background_executor().timer(retry_delay).await;
+                        retry_delay = (retry_delay * 2).min(WORKSPACE_DIAGNOSTICS_MAX_RETRY_DELAY);
+                        continue;
+                    }
+                };
+                retry_delay = WORKSPACE_DIAGNOSTICS_MIN_RETRY_DELAY;
+                let long_poll = cx.background_executor().now() - requested_at
+                    >= WORKSPACE_DIAGNOSTICS_LONG_POLL_THRESHOLD;
+
+                let applied = this.update(&mut cx, |this, cx| {
+                    for item in items {
+                        let (uri, version, report) = match item {
+                            lsp::WorkspaceDocumentDiagnosticReport::Full(report) => (
+                                report.uri,
+                                report.version,
+                                lsp::DocumentDiagnosticReportKind::Full(
+                                    report.full_document_diagnostic_report,
+                                ),
+                            ),
+                            lsp::WorkspaceDocumentDiagnosticReport::Unchanged(report) => (
+                                report.uri,
+                                report.version,
+                                lsp::DocumentDiagnosticReportKind::Unchanged(
+                                    report.unchanged_document_diagnostic_report,
+                                ),
+                            ),
+                        };
+                        let version = version.and_then(|version| i32::try_from(version).ok());
+                        this.apply_pulled_diagnostics(
+                            server_id, &adapter, uri, version, report, cx,
+                        );
+                    }
+                });
+                if applied.is_err() {
+                    return;
+                }
+
+                // Servers that reply right away aren't waiting for changes, so only ask them again when told to.
+                if !long_poll && repull_requests.next().await.is_none() {
+                    return;
+                }
+            }
+        });
+        self.pull_diagnostics.workspace_pulls.insert(
+            server_id,
+            WorkspacePull {
+                repull,
+                _task: task,
+            },
+        );
+    }
+
+    /// Pulls diagnostics again after a buffer was saved, which servers with
+    /// inter-file dependencies may need to update the diagnostics of other files.
+    pub(crate) fn pull_diagnostics_after_save(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.pull_diagnostics_for_buffer(buffer, cx);
+        for server_id in self.language_server_ids_for_buffer(buffer.read(cx), cx) {
+            let inter_file_dependencies = self
+                .language_server_for_id(server_id)
+                .and_then(|server| diagnostic_options(&server))
+                .map_or(false, |options| options.inter_file_dependencies);
+            if inter_file_dependencies {
+                self.pull_diagnostics_for_server(server_id, cx);
+            }
+            self.pull_workspace_diagnostics(server_id, cx);
+        }
+    }
+
+    pub(crate) fn forget_pulled_diagnostics_for_buffer(&mut self, buffer_id: BufferId) {
+        self.pull_diagnostics
+            .document_pulls
+            .retain(|(pulled_buffer_id, _), _| *pulled_buffer_id != buffer_id);
+    }
+
+    pub(crate) fn forget_pulled_diagnostics_for_server(&mut self, server_id: LanguageServerId) {
+        self.pull_diagnostics.result_ids.remove(&server_id);
+        self.pull_diagnostics.workspace_pulls.remove(&server_id);
+        self.pull_diagnostics
+            .document_pulls
+            .retain(|(_, pulled_server_id), _| *pulled_server_id != server_id);
+    }
+
+    fn apply_pulled_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        adapter: &Arc<CachedLspAdapter>,
+        uri: lsp::Url,
+        version: Option<i32>,
+        report: lsp::DocumentDiagnosticReportKind,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let result_ids = self
+            .pull_diagnostics
+            .result_ids
+            .entry(server_id)
+            .or_default();
+        match report {
+            lsp::DocumentDiagnosticReportKind::Full(report) => {
+                match report.result_id {
+                    Some(result_id) => result_ids.insert(uri.clone(), result_id),
+                    None => result_ids.remove(&uri),
+                };
+                let mut params = lsp::PublishDiagnosticsParams {
+                    uri,
+                    diagnostics: report.items,
+                    version,
+                };
+                adapter.process_diagnostics(&mut params);
+                self.update_diagnostics(
+                    server_id,
+                    params,
+                    &adapter.disk_based_diagnostic_sources,
+                    cx,
+                )
+                .log_err();
+            }
+            lsp::DocumentDiagnosticReportKind::Unchanged(report) => {
+                result_ids.insert(uri, report.result_id);
+            }
+        }
+    }
+}
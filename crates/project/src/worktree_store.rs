@@ -1,12 +1,29 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
 use anyhow::{anyhow, Context as _, Result};
 use collections::HashMap;
-use gpui::{AppContext, AsyncAppContext, EntityId, EventEmitter, Model, ModelContext, WeakModel};
+use git::{
+    log::{Commit, CommitDetails, CommitFileChange, CommitRef, CommitRefKind},
+    repository::{GitRepository, RepoPath},
+    Oid,
+};
+use gpui::{
+    AppContext, AsyncAppContext, EntityId, EventEmitter, Model, ModelContext, Task, WeakModel,
+};
 use rpc::{
     proto::{self, AnyProtoClient},
     TypedEnvelope,
 };
 use text::ReplicaId;
-use worktree::{ProjectEntryId, Worktree, WorktreeId};
+use worktree::{LocalWorktree, ProjectEntryId, Worktree, WorktreeId};
+
+use crate::{NoRepositoryError, ProjectPath};
+
+/// The most commits a guest can list at once.
+const MAX_REMOTE_GIT_LOG_LIMIT: usize = 10_000;
 
 pub struct WorktreeStore {
     is_shared: bool,
@@ -238,6 +255,91 @@ impl WorktreeStore {
         }
     }
 
+    /// Lists at most `limit` commits of the repository containing the given path, newest first.
+    ///
+    /// Unless the path is the root of the repository, only the commits that changed it are listed.
+    pub fn git_log(
+        &self,
+        project_path: ProjectPath,
+        range: Option<String>,
+        limit: usize,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<Commit>>> {
+        let Some(worktree) = self.worktree_for_id(project_path.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("worktree not found")));
+        };
+
+        match worktree.read(cx) {
+            Worktree::Local(worktree) => {
+                let repo = repository_for_path(worktree, &project_path.path);
+                cx.background_executor().spawn(async move {
+                    let (repo, relative_path) = repo?;
+                    let path = (!relative_path.as_os_str().is_empty()).then_some(relative_path);
+                    repo.log(path.as_deref(), range.as_deref(), limit)
+                        .with_context(|| format!("failed to list commits of {:?}", path))
+                })
+            }
+            Worktree::Remote(worktree) => {
+                if range.is_some() {
+                    return Task::ready(Err(anyhow!(
+                        "revision ranges are only supported in local repositories"
+                    )));
+                }
+                let client = worktree.client();
+                let request = proto::GitLog {
+                    project_id: worktree.project_id(),
+                    worktree_id: project_path.worktree_id.to_proto(),
+                    path: project_path.path.to_string_lossy().into(),
+                    limit: limit as u64,
+                };
+                cx.spawn(|_| async move {
+                    let response = client.request(request).await?;
+                    response
+                        .commits
+                        .into_iter()
+                        .map(deserialize_commit)
+                        .collect()
+                })
+            }
+        }
+    }
+
+    /// Loads the message and the changed files of a commit in the repository containing the given path.
+    pub fn git_commit_details(
+        &self,
+        project_path: ProjectPath,
+        sha: Oid,
+        cx: &AppContext,
+    ) -> Task<Result<CommitDetails>> {
+        let Some(worktree) = self.worktree_for_id(project_path.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("worktree not found")));
+        };
+
+        match worktree.read(cx) {
+            Worktree::Local(worktree) => {
+                let repo = repository_for_path(worktree, &project_path.path);
+                cx.background_executor().spawn(async move {
+                    let (repo, _) = repo?;
+                    repo.commit_details(sha)
+                        .with_context(|| format!("failed to load commit {sha}"))
+                })
+            }
+            Worktree::Remote(worktree) => {
+                let client = worktree.client();
+                let request = proto::GitCommitDetails {
+                    project_id: worktree.project_id(),
+                    worktree_id: project_path.worktree_id.to_proto(),
+                    path: project_path.path.to_string_lossy().into(),
+                    sha: sha.as_bytes().into(),
+                };
+                cx.spawn(|_| async move {
+                    let response = client.request(request).await?;
+                    deserialize_commit_details(response)
+                })
+            }
+        }
+    }
+
     pub async fn handle_create_project_entry(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::CreateProjectEntry>,
@@ -301,6 +403,159 @@ impl WorktreeStore {
             .ok_or_else(|| anyhow!("invalid request"))?;
         Worktree::handle_expand_entry(worktree, envelope.payload, cx).await
     }
+
+    pub async fn handle_git_log(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitLog>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GitLogResponse> {
+        let payload = envelope.payload;
+        let project_path = ProjectPath {
+            worktree_id: WorktreeId::from_proto(payload.worktree_id),
+            path: PathBuf::from(payload.path).into(),
+        };
+        let commits = this
+            .update(&mut cx, |this, cx| {
+                let limit = (payload.limit as usize).min(MAX_REMOTE_GIT_LOG_LIMIT);
+                this.git_log(project_path, None, limit, cx)
+            })?
+            .await?;
+        Ok(proto::GitLogResponse {
+            commits: commits.into_iter().map(serialize_commit).collect(),
+        })
+    }
+
+    pub async fn handle_git_commit_details(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitCommitDetails>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GitCommitDetailsResponse> {
+        let payload = envelope.payload;
+        let project_path = ProjectPath {
+            worktree_id: WorktreeId::from_proto(payload.worktree_id),
+            path: PathBuf::from(payload.path).into(),
+        };
+        let sha = Oid::from_bytes(&payload.sha)?;
+        let details = this
+            .update(&mut cx, |this, cx| {
+                this.git_commit_details(project_path, sha, cx)
+            })?
+            .await?;
+        Ok(serialize_commit_details(details))
+    }
+}
+
+fn repository_for_path(
+    worktree: &LocalWorktree,
+    path: &Path,
+) -> Result<(Arc<dyn GitRepository>, RepoPath)> {
+    let snapshot = worktree.snapshot();
+    let (repo_entry, local_repo_entry) = match snapshot.repo_for_path(path) {
+        Some(repo_for_path) => repo_for_path,
+        None => anyhow::bail!(NoRepositoryError {}),
+    };
+    let relative_path = repo_entry
+        .relativize(&snapshot, path)
+        .context("failed to relativize path")?;
+    Ok((local_repo_entry.repo().clone(), relative_path))
+}
+
+fn serialize_commit(commit: Commit) -> proto::GitCommit {
+    proto::GitCommit {
+        sha: commit.sha.as_bytes().into(),
+        parents: commit
+            .parents
+            .iter()
+            .map(|parent| parent.as_bytes().into())
+            .collect(),
+        author_name: commit.author_name,
+        author_email: commit.author_email,
+        timestamp: commit.timestamp,
+        summary: commit.summary,
+        refs: commit
+            .refs
+            .into_iter()
+            .map(|commit_ref| {
+                let kind = match commit_ref.kind {
+                    CommitRefKind::Head => proto::git_commit_ref::Kind::Head,
+                    CommitRefKind::LocalBranch => proto::git_commit_ref::Kind::LocalBranch,
+                    CommitRefKind::RemoteBranch => proto::git_commit_ref::Kind::RemoteBranch,
+                    CommitRefKind::Tag => proto::git_commit_ref::Kind::Tag,
+                };
+                proto::GitCommitRef {
+                    kind: kind as i32,
+                    name: commit_ref.name,
+                }
+            })
+            .collect(),
+    }
+}
+
+fn deserialize_commit(commit: proto::GitCommit) -> Result<Commit> {
+    Ok(Commit {
+        sha: Oid::from_bytes(&commit.sha)?,
+        parents: commit
+            .parents
+            .iter()
+            .map(|parent| Oid::from_bytes(parent))
+            .collect::<Result<_>>()?,
+        author_name: commit.author_name,
+        author_email: commit.author_email,
+        timestamp: commit.timestamp,
+        summary: commit.summary,
+        refs: commit
+            .refs
+            .into_iter()
+            .filter_map(|commit_ref| {
+                let kind = match proto::git_commit_ref::Kind::from_i32(commit_ref.kind)? {
+                    proto::git_commit_ref::Kind::Head => CommitRefKind::Head,
+                    proto::git_commit_ref::Kind::LocalBranch => CommitRefKind::LocalBranch,
+                    proto::git_commit_ref::Kind::RemoteBranch => CommitRefKind::RemoteBranch,
+                    proto::git_commit_ref::Kind::Tag => CommitRefKind::Tag,
+                };
+                Some(CommitRef {
+                    kind,
+                    name: commit_ref.name,
+                })
+            })
+            .collect(),
+    })
+}
+
+fn serialize_commit_details(details: CommitDetails) -> proto::GitCommitDetailsResponse {
+    proto::GitCommitDetailsResponse {
+        commit: Some(serialize_commit(details.commit)),
+        message: details.message,
+        files: details
+            .files
+            .into_iter()
+            .map(|file| proto::GitCommitFileChange {
+                path: file.path.to_string_lossy().into(),
+                old_path: file
+                    .old_path
+                    .map(|old_path| old_path.to_string_lossy().into()),
+                old_text: file.old_text,
+                new_text: file.new_text,
+            })
+            .collect(),
+    }
+}
+
+fn deserialize_commit_details(response: proto::GitCommitDetailsResponse) -> Result<CommitDetails> {
+    Ok(CommitDetails {
+        commit: deserialize_commit(response.commit.context("missing commit")?)?,
+        message: response.message,
+        files: response
+            .files
+            .into_iter()
+            .map(|file| CommitFileChange {
+                path: RepoPath::new(file.path.into()),
+                old_path: file.old_path.map(|old_path| RepoPath::new(old_path.into())),
+                old_text: file.old_text,
+                new_text: file.new_text,
+            })
+            .collect(),
+    })
 }
 
 #[derive(Clone)]
@@ -1,13 +1,16 @@
+use collections::HashSet;
 use editor::{scroll::Autoscroll, styled_runs_for_code_label, Bias, Editor};
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
     rems, AppContext, DismissEvent, FontWeight, Model, ParentElement, StyledText, Task, View,
     ViewContext, WeakView, WindowContext,
 };
+use language::{CodeLabel, PointUtf16, Unclipped};
 use ordered_float::OrderedFloat;
 use picker::{Picker, PickerDelegate};
-use project::{Project, Symbol};
+use project::{Project, ProjectPath, Symbol};
 use std::{borrow::Cow, cmp::Reverse, sync::Arc};
+use symbol_index::{IndexedSymbol, ProjectSymbolIndex, SymbolIndex};
 use theme::ActiveTheme;
 use util::ResultExt;
 use workspace::{
@@ -15,14 +18,29 @@ use workspace::{
     Workspace,
 };
 
+/// The maximum number of symbols taken from the symbol index for each query.
+const MAX_INDEXED_SYMBOLS: usize = 100;
+
 pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(
-        |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
+        |workspace: &mut Workspace, cx: &mut ViewContext<Workspace>| {
+            // Start indexing local projects right away, so that symbols are
+            // available by the time they are searched for.
+            let project = workspace.project().clone();
+            if project.read(cx).is_local() {
+                SymbolIndex::for_project(&project, cx);
+            }
+
             workspace.register_action(|workspace, _: &workspace::ToggleProjectSymbols, cx| {
                 let project = workspace.project().clone();
+                let symbol_index = if project.read(cx).is_local() {
+                    SymbolIndex::for_project(&project, cx)
+                } else {
+                    None
+                };
                 let handle = cx.view().downgrade();
                 workspace.toggle_modal(cx, move |cx| {
-                    let delegate = ProjectSymbolsDelegate::new(handle, project);
+                    let delegate = ProjectSymbolsDelegate::new(handle, project, symbol_index);
                     Picker::uniform_list(delegate, cx).width(rems(34.))
                 })
             });
@@ -33,11 +51,42 @@ pub fn init(cx: &mut AppContext) {
 
 pub type ProjectSymbols = View<Picker<ProjectSymbolsDelegate>>;
 
+/// A symbol reported by a language server, or found by the project's symbol index.
+#[derive(Clone)]
+enum ProjectSymbol {
+    Lsp(Symbol),
+    Indexed(IndexedSymbol),
+}
+
+impl ProjectSymbol {
+    fn label(&self) -> &CodeLabel {
+        match self {
+            ProjectSymbol::Lsp(symbol) => &symbol.label,
+            ProjectSymbol::Indexed(symbol) => &symbol.label,
+        }
+    }
+
+    fn path(&self) -> &ProjectPath {
+        match self {
+            ProjectSymbol::Lsp(symbol) => &symbol.path,
+            ProjectSymbol::Indexed(symbol) => &symbol.path,
+        }
+    }
+
+    fn start(&self) -> Unclipped<PointUtf16> {
+        match self {
+            ProjectSymbol::Lsp(symbol) => symbol.range.start,
+            ProjectSymbol::Indexed(symbol) => Unclipped(symbol.range.start),
+        }
+    }
+}
+
 pub struct ProjectSymbolsDelegate {
     workspace: WeakView<Workspace>,
     project: Model<Project>,
+    symbol_index: Option<Model<ProjectSymbolIndex>>,
     selected_match_index: usize,
-    symbols: Vec<Symbol>,
+    symbols: Vec<ProjectSymbol>,
     visible_match_candidates: Vec<StringMatchCandidate>,
     external_match_candidates: Vec<StringMatchCandidate>,
     show_worktree_root_name: bool,
@@ -45,10 +94,15 @@ pub struct ProjectSymbolsDelegate {
 }
 
 impl ProjectSymbolsDelegate {
-    fn new(workspace: WeakView<Workspace>, project: Model<Project>) -> Self {
+    fn new(
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        symbol_index: Option<Model<ProjectSymbolIndex>>,
+    ) -> Self {
         Self {
             workspace,
             project,
+            symbol_index,
             selected_match_index: 0,
             symbols: Default::default(),
             visible_match_candidates: Default::default(),
@@ -77,10 +131,10 @@ impl ProjectSymbolsDelegate {
             cx.background_executor().clone(),
         ));
         let sort_key_for_match = |mat: &StringMatch| {
-            let symbol = &self.symbols[mat.candidate_id];
+            let label = self.symbols[mat.candidate_id].label();
             (
                 Reverse(OrderedFloat(mat.score)),
-                &symbol.label.text[symbol.label.filter_range.clone()],
+                &label.text[label.filter_range.clone()],
             )
         };
 
@@ -90,8 +144,7 @@ impl ProjectSymbolsDelegate {
         matches.append(&mut external_matches);
 
         for mat in &mut matches {
-            let symbol = &self.symbols[mat.candidate_id];
-            let filter_start = symbol.label.filter_range.start;
+            let filter_start = self.symbols[mat.candidate_id].label().filter_range.start;
             for position in &mut mat.positions {
                 *position += filter_start;
             }
@@ -114,17 +167,15 @@ impl PickerDelegate for ProjectSymbolsDelegate {
             .get(self.selected_match_index)
             .map(|mat| self.symbols[mat.candidate_id].clone())
         {
-            let buffer = self.project.update(cx, |project, cx| {
-                project.open_buffer_for_symbol(&symbol, cx)
+            let buffer = self.project.update(cx, |project, cx| match &symbol {
+                ProjectSymbol::Lsp(symbol) => project.open_buffer_for_symbol(symbol, cx),
+                ProjectSymbol::Indexed(symbol) => project.open_buffer(symbol.path.clone(), cx),
             });
-            let symbol = symbol.clone();
             let workspace = self.workspace.clone();
             cx.spawn(|_, mut cx| async move {
                 let buffer = buffer.await?;
                 workspace.update(&mut cx, |workspace, cx| {
-                    let position = buffer
-                        .read(cx)
-                        .clip_point_utf16(symbol.range.start, Bias::Left);
+                    let position = buffer.read(cx).clip_point_utf16(symbol.start(), Bias::Left);
                     let pane = if secondary {
                         workspace.adjacent_pane(cx)
                     } else {
@@ -164,37 +215,70 @@ impl PickerDelegate for ProjectSymbolsDelegate {
     fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
         self.filter(&query, cx);
         self.show_worktree_root_name = self.project.read(cx).visible_worktrees(cx).count() > 1;
-        let symbols = self
+        let lsp_symbols = self
             .project
             .update(cx, |project, cx| project.symbols(&query, cx));
+        let indexed_symbols = self.symbol_index.as_ref().map(|symbol_index| {
+            symbol_index
+                .read(cx)
+                .search(query.clone(), MAX_INDEXED_SYMBOLS, cx)
+        });
         cx.spawn(|this, mut cx| async move {
-            let symbols = symbols.await.log_err();
-            if let Some(symbols) = symbols {
-                this.update(&mut cx, |this, cx| {
-                    let delegate = &mut this.delegate;
-                    let project = delegate.project.read(cx);
-                    let (visible_match_candidates, external_match_candidates) = symbols
-                        .iter()
-                        .enumerate()
-                        .map(|(id, symbol)| {
-                            StringMatchCandidate::new(
-                                id,
-                                symbol.label.text[symbol.label.filter_range.clone()].to_string(),
-                            )
+            let lsp_symbols = lsp_symbols.await.log_err();
+            let indexed_symbols = match indexed_symbols {
+                Some(indexed_symbols) => indexed_symbols.await,
+                None => Vec::new(),
+            };
+            if lsp_symbols.is_none() && indexed_symbols.is_empty() {
+                return;
+            }
+
+            let lsp_symbols = lsp_symbols.unwrap_or_default();
+            // Prefer the language server's symbols, which carry richer labels, over
+            // the index's symbols for the same location.
+            let lsp_symbol_locations = lsp_symbols
+                .iter()
+                .map(|symbol| (symbol.path.clone(), symbol.range.start.0.row))
+                .collect::<HashSet<_>>();
+            let symbols = lsp_symbols
+                .into_iter()
+                .map(ProjectSymbol::Lsp)
+                .chain(
+                    indexed_symbols
+                        .into_iter()
+                        .filter(|symbol| {
+                            !lsp_symbol_locations
+                                .contains(&(symbol.path.clone(), symbol.range.start.row))
                         })
-                        .partition(|candidate| {
-                            project
-                                .entry_for_path(&symbols[candidate.id].path, cx)
-                                .map_or(false, |e| !e.is_ignored)
-                        });
+                        .map(ProjectSymbol::Indexed),
+                )
+                .collect::<Vec<_>>();
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                let project = delegate.project.read(cx);
+                let (visible_match_candidates, external_match_candidates) = symbols
+                    .iter()
+                    .enumerate()
+                    .map(|(id, symbol)| {
+                        let label = symbol.label();
+                        StringMatchCandidate::new(
+                            id,
+                            label.text[label.filter_range.clone()].to_string(),
+                        )
+                    })
+                    .partition(|candidate| {
+                        project
+                            .entry_for_path(symbols[candidate.id].path(), cx)
+                            .map_or(false, |e| !e.is_ignored)
+                    });
 
-                    delegate.visible_match_candidates = visible_match_candidates;
-                    delegate.external_match_candidates = external_match_candidates;
-                    delegate.symbols = symbols;
-                    delegate.filter(&query, cx);
-                })
-                .log_err();
-            }
+                delegate.visible_match_candidates = visible_match_candidates;
+                delegate.external_match_candidates = external_match_candidates;
+                delegate.symbols = symbols;
+                delegate.filter(&query, cx);
+            })
+            .log_err();
         })
     }
 
@@ -206,12 +290,12 @@ impl PickerDelegate for ProjectSymbolsDelegate {
     ) -> Option<Self::ListItem> {
         let string_match = &self.matches[ix];
         let symbol = &self.symbols[string_match.candidate_id];
-        let syntax_runs = styled_runs_for_code_label(&symbol.label, cx.theme().syntax());
+        let syntax_runs = styled_runs_for_code_label(symbol.label(), cx.theme().syntax());
 
-        let mut path = symbol.path.path.to_string_lossy();
+        let mut path = symbol.path().path.to_string_lossy();
         if self.show_worktree_root_name {
             let project = self.project.read(cx);
-            if let Some(worktree) = project.worktree_for_id(symbol.path.worktree_id, cx) {
+            if let Some(worktree) = project.worktree_for_id(symbol.path().worktree_id, cx) {
                 path = Cow::Owned(format!(
                     "{}{}{}",
                     worktree.read(cx).root_name(),
@@ -220,7 +304,7 @@ impl PickerDelegate for ProjectSymbolsDelegate {
                 ));
             }
         }
-        let label = symbol.label.text.clone();
+        let label = symbol.label().text.clone();
         let path = path.to_string().clone();
 
         let highlights = gpui::combine_highlights(
@@ -344,7 +428,7 @@ mod tests {
         // Create the project symbols view.
         let symbols = cx.new_view(|cx| {
             Picker::uniform_list(
-                ProjectSymbolsDelegate::new(workspace.downgrade(), project.clone()),
+                ProjectSymbolsDelegate::new(workspace.downgrade(), project.clone(), None),
                 cx,
             )
         });
@@ -389,6 +473,98 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_project_symbols_with_index(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(symbol_index::init);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({ "test.rs": "fn one() {}\nfn two() {}\n" }))
+            .await;
+
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        language_registry.add(Arc::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: vec!["rs".to_string()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Some(language::tree_sitter_rust::language()),
+            )
+            .with_outline_query(
+                r#"
+                (function_item
+                    "fn" @context
+                    name: (_) @name) @item
+                "#,
+            )
+            .unwrap(),
+        ));
+        let mut fake_servers =
+            language_registry.register_fake_lsp_adapter("Rust", FakeLspAdapter::default());
+
+        let _buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/dir/test.rs", cx)
+            })
+            .await
+            .unwrap();
+
+        // The language server only knows about the first function, on the same line as the index.
+        let fake_server = fake_servers.next().await.unwrap();
+        fake_server.handle_request::<lsp::WorkspaceSymbolRequest, _, _>(|_, _| async move {
+            Ok(Some(lsp::WorkspaceSymbolResponse::Flat(vec![symbol(
+                "one",
+                "/dir/test.rs",
+            )])))
+        });
+
+        let symbol_index = cx.update(|cx| SymbolIndex::for_project(&project, cx));
+        assert!(symbol_index.is_some());
+        cx.run_until_parked();
+
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let symbols = cx.new_view(|cx| {
+            Picker::uniform_list(
+                ProjectSymbolsDelegate::new(workspace.downgrade(), project.clone(), symbol_index),
+                cx,
+            )
+        });
+
+        symbols.update(cx, |p, cx| {
+            p.update_matches("o".to_string(), cx);
+        });
+        cx.run_until_parked();
+
+        // Indexed symbols at the location of a language server symbol are dropped in its favor.
+        symbols.update(cx, |symbols, _| {
+            let delegate = &symbols.delegate;
+            let mut matches = delegate
+                .matches
+                .iter()
+                .map(|mat| {
+                    let is_indexed = matches!(
+                        delegate.symbols[mat.candidate_id],
+                        ProjectSymbol::Indexed(_)
+                    );
+                    (mat.string.clone(), is_indexed)
+                })
+                .collect::<Vec<_>>();
+            matches.sort();
+            assert_eq!(
+                matches,
+                [("one".to_string(), false), ("two".to_string(), true)]
+            );
+            assert_eq!(delegate.symbols.len(), 2);
+        });
+    }
+
     fn init_test(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let store = SettingsStore::test(cx);
@@ -400,11 +400,40 @@ messages!(
     (RestartLanguageServers, Foreground),
     (LinkedEditingRange, Background),
     (LinkedEditingRangeResponse, Background),
+    (PrepareCallHierarchy, Background),
+    (GetIncomingCalls, Background),
+    (GetOutgoingCalls, Background),
+    (PrepareTypeHierarchy, Background),
+    (GetSupertypes, Background),
+    (GetSubtypes, Background),
+    (HierarchyItemsResponse, Background),
+    (GetSemanticTokens, Background),
+    (GetSemanticTokensDelta, Background),
+    (SemanticTokensResponse, Background),
+    (RefreshSemanticTokens, Foreground),
+    (GetFoldingRanges, Background),
+    (GetFoldingRangesResponse, Background),
+    (GetCodeLens, Background),
+    (GetCodeLensResponse, Background),
+    (ResolveCodeLens, Background),
+    (ResolveCodeLensResponse, Background),
+    (ExecuteCodeLensCommand, Background),
+    (RefreshCodeLens, Foreground),
+    (GetSelectionRanges, Background),
+    (GetSelectionRangesResponse, Background),
+    (GetDocumentLinks, Background),
+    (GetDocumentLinksResponse, Background),
+    (GitLog, Background),
+    (GitLogResponse, Background),
+    (GitCommitDetails, Background),
+    (GitCommitDetailsResponse, Background),
     (AdvertiseContexts, Foreground),
     (OpenContext, Foreground),
     (OpenContextResponse, Foreground),
     (CreateContext, Foreground),
     (CreateContextResponse, Foreground),
+    (ForkContext, Foreground),
+    (ForkContextResponse, Foreground),
     (UpdateContext, Foreground),
     (SynchronizeContexts, Foreground),
     (SynchronizeContextsResponse, Foreground),
@@ -452,6 +481,24 @@ request_messages!(
     (GetSupermavenApiKey, GetSupermavenApiKeyResponse),
     (GetTypeDefinition, GetTypeDefinitionResponse),
     (LinkedEditingRange, LinkedEditingRangeResponse),
+    (PrepareCallHierarchy, HierarchyItemsResponse),
+    (GetIncomingCalls, HierarchyItemsResponse),
+    (GetOutgoingCalls, HierarchyItemsResponse),
+    (PrepareTypeHierarchy, HierarchyItemsResponse),
+    (GetSupertypes, HierarchyItemsResponse),
+    (GetSubtypes, HierarchyItemsResponse),
+    (GetSemanticTokens, SemanticTokensResponse),
+    (GetSemanticTokensDelta, SemanticTokensResponse),
+    (RefreshSemanticTokens, Ack),
+    (GetFoldingRanges, GetFoldingRangesResponse),
+    (GetCodeLens, GetCodeLensResponse),
+    (ResolveCodeLens, ResolveCodeLensResponse),
+    (ExecuteCodeLensCommand, Ack),
+    (RefreshCodeLens, Ack),
+    (GetSelectionRanges, GetSelectionRangesResponse),
+    (GetDocumentLinks, GetDocumentLinksResponse),
+    (GitLog, GitLogResponse),
+    (GitCommitDetails, GitCommitDetailsResponse),
     (ListRemoteDirectory, ListRemoteDirectoryResponse),
     (UpdateDevServerProject, Ack),
     (GetUsers, UsersResponse),
@@ -533,6 +580,7 @@ request_messages!(
     (RestartLanguageServers, Ack),
     (OpenContext, OpenContextResponse),
     (CreateContext, CreateContextResponse),
+    (ForkContext, ForkContextResponse),
     (SynchronizeContexts, SynchronizeContextsResponse),
     (AddWorktree, AddWorktreeResponse),
 );
@@ -566,6 +614,14 @@ entity_messages!(
     JoinProject,
     LeaveProject,
     LinkedEditingRange,
+    PrepareCallHierarchy,
+    GetIncomingCalls,
+    GetOutgoingCalls,
+    PrepareTypeHierarchy,
+    GetSupertypes,
+    GetSubtypes,
+    GetSemanticTokens,
+    GetSemanticTokensDelta,
     MultiLspQuery,
     RestartLanguageServers,
     OnTypeFormatting,
@@ -576,6 +632,16 @@ entity_messages!(
     PerformRename,
     PrepareRename,
     RefreshInlayHints,
+    RefreshSemanticTokens,
+    GetFoldingRanges,
+    GetCodeLens,
+    ResolveCodeLens,
+    ExecuteCodeLensCommand,
+    RefreshCodeLens,
+    GetSelectionRanges,
+    GetDocumentLinks,
+    GitLog,
+    GitCommitDetails,
     ReloadBuffers,
     RemoveProjectCollaborator,
     RenameProjectEntry,
@@ -601,6 +667,7 @@ entity_messages!(
     AdvertiseContexts,
     OpenContext,
     CreateContext,
+    ForkContext,
     UpdateContext,
     SynchronizeContexts,
 );
@@ -74,6 +74,11 @@ impl HeadlessProject {
             worktree_store.downgrade(),
             WorktreeStore::handle_expand_project_entry,
         );
+        session.add_request_handler(worktree_store.downgrade(), WorktreeStore::handle_git_log);
+        session.add_request_handler(
+            worktree_store.downgrade(),
+            WorktreeStore::handle_git_commit_details,
+        );
 
         HeadlessProject {
             session: session.into(),
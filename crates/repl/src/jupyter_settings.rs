@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use editor::EditorSettings;
-use gpui::AppContext;
+use gpui::{px, AppContext, Pixels};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
@@ -9,6 +9,7 @@ use settings::{Settings, SettingsSources};
 #[derive(Debug, Default)]
 pub struct JupyterSettings {
     pub kernel_selections: HashMap<String, String>,
+    pub variable_explorer: VariableExplorerSettings,
 }
 
 impl JupyterSettings {
@@ -20,18 +21,61 @@ impl JupyterSettings {
     }
 }
 
+#[derive(Debug)]
+pub struct VariableExplorerSettings {
+    pub button: bool,
+    pub dock: VariableExplorerDockPosition,
+    pub default_width: Pixels,
+}
+
+impl Default for VariableExplorerSettings {
+    fn default() -> Self {
+        Self {
+            button: true,
+            dock: VariableExplorerDockPosition::Right,
+            default_width: px(300.),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VariableExplorerDockPosition {
+    Left,
+    Right,
+}
+
 #[derive(Clone, Serialize, Deserialize, JsonSchema, Debug)]
 pub struct JupyterSettingsContent {
     /// Default kernels to select for each language.
     ///
     /// Default: `{}`
     pub kernel_selections: Option<HashMap<String, String>>,
+    /// Settings for the panel showing the variables of the active editor's kernel.
+    pub variable_explorer: Option<VariableExplorerSettingsContent>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct VariableExplorerSettingsContent {
+    /// Whether to show the variable explorer button in the status bar.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// Where to dock the variable explorer.
+    ///
+    /// Default: right
+    pub dock: Option<VariableExplorerDockPosition>,
+    /// Default width of the variable explorer in pixels.
+    ///
+    /// Default: 300
+    pub default_width: Option<f32>,
 }
 
 impl Default for JupyterSettingsContent {
     fn default() -> Self {
         JupyterSettingsContent {
             kernel_selections: Some(HashMap::new()),
+            variable_explorer: None,
         }
     }
 }
@@ -56,6 +100,19 @@ impl Settings for JupyterSettings {
                     settings.kernel_selections.insert(k.clone(), v.clone());
                 }
             }
+
+            if let Some(source) = &value.variable_explorer {
+                let explorer = &mut settings.variable_explorer;
+                if let Some(button) = source.button {
+                    explorer.button = button;
+                }
+                if let Some(dock) = source.dock {
+                    explorer.dock = dock;
+                }
+                if let Some(default_width) = source.default_width {
+                    explorer.default_width = px(default_width);
+                }
+            }
         }
 
         Ok(settings)
@@ -0,0 +1,293 @@
+//! Completions and hovers for editors attached to a session, backed by the kernel's
+//! `complete_request` and `inspect_request`.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use editor::{CompletionProvider, Editor, HoverProvider};
+use gpui::{Model, Task, ViewContext, WeakView};
+use language::{Buffer, CodeLabel, Documentation, LanguageServerId, ToOffset};
+use parking_lot::RwLock;
+use project::{Completion, Hover, HoverBlock, HoverBlockKind, Project};
+use runtimelib::{CompleteReply, MimeType};
+use serde_json::Value;
+
+use crate::repl_editor::{kernel_code_at, KernelCode};
+use crate::Session;
+
+/// The server id of completions provided by a kernel, which no language server resolves.
+const KERNEL_SERVER_ID: LanguageServerId = LanguageServerId(usize::MAX);
+
+/// Adds the kernel's completions to those of the project's language servers.
+pub struct KernelCompletionProvider {
+    session: WeakView<Session>,
+    project: Option<Model<Project>>,
+}
+
+impl KernelCompletionProvider {
+    pub fn new(session: WeakView<Session>, project: Option<Model<Project>>) -> Self {
+        Self { session, project }
+    }
+}
+
+impl CompletionProvider for KernelCompletionProvider {
+    fn completions(
+        &self,
+        buffer: &Model<Buffer>,
+        buffer_position: language::Anchor,
+        trigger: editor::CompletionContext,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<Result<Vec<Completion>>> {
+        let project_completions = self
+            .project
+            .as_ref()
+            .map(|project| project.completions(buffer, buffer_position, trigger, cx));
+
+        let snapshot = buffer.read(cx).snapshot();
+        let code = kernel_code_at(&snapshot, buffer_position.to_offset(&snapshot));
+        let kernel_completions = self.session.upgrade().zip(code).map(|(session, code)| {
+            let reply = session.update(cx, |session, cx| {
+                session.complete(code.text.clone(), code.cursor_pos, cx)
+            });
+            (reply, code)
+        });
+
+        cx.background_executor().spawn(async move {
+            let (mut completions, project_error) = match project_completions {
+                Some(project_completions) => match project_completions.await {
+                    Ok(completions) => (completions, None),
+                    Err(error) => (Vec::new(), Some(error)),
+                },
+                None => (Vec::new(), None),
+            };
+
+            // The kernel may be busy running code, in which case only the project's completions are shown.
+            let mut kernel_replied = false;
+            if let Some((reply, code)) = kernel_completions {
+                if let Ok(reply) = reply.await {
+                    kernel_replied = true;
+                    for completion in kernel_completions_from_reply(reply, &code, &snapshot) {
+                        if !completions
+                            .iter()
+                            .any(|existing| existing.new_text == completion.new_text)
+                        {
+                            completions.push(completion);
+                        }
+                    }
+                }
+            }
+
+            // Only fail when there's nothing to show, so that a failing language server
+            // doesn't hide the kernel's completions.
+            if let Some(error) = project_error {
+                if !kernel_replied {
+                    return Err(error);
+                }
+                log::error!("failed to fetch project completions: {error:#}");
+            }
+
+            Ok(completions)
+        })
+    }
+
+    fn resolve_completions(
+        &self,
+        buffer: Model<Buffer>,
+        completion_indices: Vec<usize>,
+        completions: Arc<RwLock<Box<[Completion]>>>,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<Result<bool>> {
+        // Kernel completions are documented up front, so the project skips resolving them.
+        match &self.project {
+            Some(project) => {
+                project.resolve_completions(buffer, completion_indices, completions, cx)
+            }
+            None => Task::ready(Ok(false)),
+        }
+    }
+
+    fn apply_additional_edits_for_completion(
+        &self,
+        buffer: Model<Buffer>,
+        completion: Completion,
+        push_to_history: bool,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<Result<Option<language::Transaction>>> {
+        match &self.project {
+            Some(project) if completion.server_id != KERNEL_SERVER_ID => project
+                .apply_additional_edits_for_completion(buffer, completion, push_to_history, cx),
+            _ => Task::ready(Ok(None)),
+        }
+    }
+
+    fn is_completion_trigger(
+        &self,
+        buffer: &Model<Buffer>,
+        position: language::Anchor,
+        text: &str,
+        trigger_in_words: bool,
+        cx: &mut ViewContext<Editor>,
+    ) -> bool {
+        match &self.project {
+            Some(project) => {
+                project.is_completion_trigger(buffer, position, text, trigger_in_words, cx)
+            }
+            None => text == ".",
+        }
+    }
+}
+
+fn kernel_completions_from_reply(
+    reply: CompleteReply,
+    code: &KernelCode,
+    snapshot: &language::BufferSnapshot,
+) -> Vec<Completion> {
+    let start = snapshot.anchor_before(code.buffer_offset(reply.cursor_start));
+    let end = snapshot.anchor_after(code.buffer_offset(reply.cursor_end));
+
+    // IPython describes the type of each match in experimental metadata.
+    let types = reply
+        .metadata
+        .get("_jupyter_types_experimental")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+
+    reply
+        .matches
+        .into_iter()
+        .map(|text| {
+            let description = types
+                .iter()
+                .find(|entry| entry.get("text").and_then(Value::as_str) == Some(&text))
+                .and_then(|entry| {
+                    entry
+                        .get("signature")
+                        .and_then(Value::as_str)
+                        .filter(|signature| !signature.is_empty())
+                        .or_else(|| entry.get("type").and_then(Value::as_str))
+                })
+                .map(|description| Documentation::SingleLine(description.to_string()));
+
+            Completion {
+                old_range: start..end,
+                label: CodeLabel::plain(text.clone(), None),
+                new_text: text,
+                server_id: KERNEL_SERVER_ID,
+                documentation: Some(description.unwrap_or(Documentation::Undocumented)),
+                lsp_completion: Default::default(),
+                confirm: None,
+                show_new_completions_on_confirm: false,
+            }
+        })
+        .collect()
+}
+
+/// Shows the kernel's documentation for the code under the cursor.
+pub struct KernelHoverProvider {
+    session: WeakView<Session>,
+}
+
+impl KernelHoverProvider {
+    pub fn new(session: WeakView<Session>) -> Self {
+        Self { session }
+    }
+}
+
+impl HoverProvider for KernelHoverProvider {
+    fn hover(
+        &self,
+        buffer: &Model<Buffer>,
+        buffer_position: language::Anchor,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<Vec<Hover>> {
+        let snapshot = buffer.read(cx).snapshot();
+        let Some((session, code)) = self.session.upgrade().zip(kernel_code_at(
+            &snapshot,
+            buffer_position.to_offset(&snapshot),
+        )) else {
+            return Task::ready(Vec::new());
+        };
+        let reply = session.update(cx, |session, cx| {
+            session.inspect(code.text, code.cursor_pos, cx)
+        });
+
+        cx.background_executor().spawn(async move {
+            let Ok(reply) = reply.await else {
+                return Vec::new();
+            };
+            if !reply.found {
+                return Vec::new();
+            }
+
+            let block = reply
+                .data
+                .content
+                .iter()
+                .find_map(|mime_type| match mime_type {
+                    MimeType::Markdown(text) => Some(HoverBlock {
+                        text: text.clone(),
+                        kind: HoverBlockKind::Markdown,
+                    }),
+                    _ => None,
+                });
+            let block = block.or_else(|| {
+                reply
+                    .data
+                    .content
+                    .iter()
+                    .find_map(|mime_type| match mime_type {
+                        MimeType::Plain(text) => Some(HoverBlock {
+                            text: strip_ansi_escapes(text),
+                            kind: HoverBlockKind::PlainText,
+                        }),
+                        _ => None,
+                    })
+            });
+
+            block
+                .map(|block| Hover {
+                    contents: vec![block],
+                    range: None,
+                    language: None,
+                })
+                .into_iter()
+                .collect()
+        })
+    }
+}
+
+/// Removes the terminal colors kernels like IPython add to their documentation.
+fn strip_ansi_escapes(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // Skip a control sequence, which ends with a character in `@`..=`~`.
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_ansi_escapes() {
+        assert_eq!(
+            strip_ansi_escapes("\u{1b}[0;31mSignature:\u{1b}[0m print(*args)"),
+            "Signature: print(*args)"
+        );
+        assert_eq!(strip_ansi_escapes("no escapes"), "no escapes");
+    }
+}
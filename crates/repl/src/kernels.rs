@@ -57,6 +57,24 @@ impl KernelSpecification {
 
         Ok(cmd)
     }
+
+    /// How the kernel expects to be interrupted, as declared by its kernelspec.
+    pub fn interrupt_mode(&self) -> InterruptMode {
+        match self.kernelspec.interrupt_mode.as_deref() {
+            Some("message") => InterruptMode::Message,
+            // Signals are only available on Unix, so other platforms always send a message.
+            _ if cfg!(unix) => InterruptMode::Signal,
+            _ => InterruptMode::Message,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterruptMode {
+    /// Send `SIGINT` to the kernel process.
+    Signal,
+    /// Send an `interrupt_request` on the control channel.
+    Message,
 }
 
 // Find a set of open ports. This creates a listener with port set to 0. The listener will be closed at the end when it goes out of scope.
@@ -327,6 +345,26 @@ impl RunningKernel {
     }
 }
 
+impl RunningKernel {
+    /// Interrupts the kernel by sending `SIGINT` to its process.
+    pub fn signal_interrupt(&self) -> Result<()> {
+        #[cfg(unix)]
+        {
+            let pid = self.process.id() as libc::pid_t;
+            if unsafe { libc::kill(pid, libc::SIGINT) } != 0 {
+                return Err(std::io::Error::last_os_error())
+                    .context("failed to send SIGINT to the kernel");
+            }
+            Ok(())
+        }
+
+        #[cfg(not(unix))]
+        {
+            anyhow::bail!("interrupting kernels with signals is not supported on this platform")
+        }
+    }
+}
+
 impl Drop for RunningKernel {
     fn drop(&mut self) {
         std::fs::remove_file(&self.connection_path).ok();
@@ -460,6 +498,15 @@ mod test {
                             "env": {}
                         }"#
                     },
+                    "ir": {
+                        "kernel.json": r#"{
+                            "display_name": "R",
+                            "language": "R",
+                            "argv": ["R", "--slave", "-e", "IRkernel::main()", "--args", "{connection_file}"],
+                            "interrupt_mode": "message",
+                            "env": {}
+                        }"#
+                    },
                     "deno": {
                         "kernel.json": r#"{
                             "display_name": "Deno",
@@ -481,7 +528,15 @@ mod test {
 
         assert_eq!(
             kernels.iter().map(|c| c.name.clone()).collect::<Vec<_>>(),
-            vec!["deno", "python"]
+            vec!["deno", "ir", "python"]
         );
+
+        assert_eq!(kernels[1].interrupt_mode(), InterruptMode::Message);
+        let default_interrupt_mode = if cfg!(unix) {
+            InterruptMode::Signal
+        } else {
+            InterruptMode::Message
+        };
+        assert_eq!(kernels[2].interrupt_mode(), default_interrupt_mode);
     }
 }
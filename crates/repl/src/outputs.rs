@@ -1,13 +1,17 @@
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::rc::Rc;
 use std::sync::Arc;
 use std::time::Duration;
 
 use crate::stdio::TerminalOutput;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use base64::prelude::*;
 use gpui::{
     img, percentage, Animation, AnimationExt, AnyElement, FontWeight, ImageData, Render, Task,
     TextRun, Transformation, View,
 };
+use html_to_markdown::{convert_html_to_markdown, markdown, TagHandler};
 use runtimelib::datatable::TableSchema;
 use runtimelib::media::datatable::TabularDataResource;
 use runtimelib::{ExecutionState, JupyterMessageContent, MimeBundle, MimeType};
@@ -21,13 +25,26 @@ use markdown_preview::{
     markdown_renderer::render_markdown_block,
 };
 
+mod json;
+mod latex;
+
+use json::JsonView;
+use latex::latex_to_unicode;
+
 /// When deciding what to render from a collection of mediatypes, we need to rank them in order of importance
+///
+/// Static images rank above HTML, so that plots which also offer an interactive HTML or widget
+/// version are shown as the image, since Zed can't run the scripts those need.
 fn rank_mime_type(mimetype: &MimeType) -> usize {
     match mimetype {
-        MimeType::DataTable(_) => 6,
-        MimeType::Png(_) => 4,
-        MimeType::Jpeg(_) => 3,
-        MimeType::Markdown(_) => 2,
+        MimeType::DataTable(_) => 9,
+        MimeType::Png(_) => 8,
+        MimeType::Jpeg(_) => 7,
+        MimeType::Svg(_) => 6,
+        MimeType::Markdown(_) => 5,
+        MimeType::Latex(_) => 4,
+        MimeType::Html(_) => 3,
+        MimeType::Json(_) => 2,
         MimeType::Plain(_) => 1,
         // All other media types are not supported in Zed at this time
         _ => 0,
@@ -84,6 +101,19 @@ impl ImageView {
             image: Arc::new(gpui_image_data),
         });
     }
+
+    /// Renders an SVG document at the window's scale factor, so that it stays sharp.
+    fn from_svg(svg: &str, cx: &WindowContext) -> Result<Self> {
+        let scale_factor = cx.scale_factor();
+        let image = cx.render_svg_image(svg.as_bytes(), scale_factor)?;
+        let size = image.size(0);
+
+        Ok(ImageView {
+            height: (u32::from(size.height) as f32 / scale_factor) as u32,
+            width: (u32::from(size.width) as f32 / scale_factor) as u32,
+            image,
+        })
+    }
 }
 
 /// TableView renders a static table inline in a buffer.
@@ -154,7 +184,7 @@ impl TableView {
         Self { table, widths }
     }
 
-    pub fn render(&self, cx: &ViewContext<ExecutionView>) -> AnyElement {
+    pub fn render(&self, cx: &WindowContext) -> AnyElement {
         let data = match &self.table.data {
             Some(data) => data,
             None => return div().into_any_element(),
@@ -184,7 +214,7 @@ impl TableView {
         schema: &TableSchema,
         is_header: bool,
         row: &Value,
-        cx: &ViewContext<ExecutionView>,
+        cx: &WindowContext,
     ) -> AnyElement {
         let theme = cx.theme();
 
@@ -325,6 +355,38 @@ impl Render for MarkdownView {
     }
 }
 
+/// Converts `text/html` output, such as the tables pandas renders, to Markdown.
+pub fn html_to_markdown(html: &str) -> Result<String> {
+    let mut handlers: Vec<TagHandler> = vec![
+        // Removes scripts and styles, which don't make sense outside of a browser.
+        Rc::new(RefCell::new(markdown::WebpageChromeRemover)),
+        Rc::new(RefCell::new(markdown::ParagraphHandler)),
+        Rc::new(RefCell::new(markdown::HeadingHandler)),
+        Rc::new(RefCell::new(markdown::ListHandler)),
+        Rc::new(RefCell::new(markdown::TableHandler::new())),
+        Rc::new(RefCell::new(markdown::StyledTextHandler)),
+        Rc::new(RefCell::new(markdown::CodeHandler)),
+    ];
+    let markdown = convert_html_to_markdown(html.as_bytes(), &mut handlers)?;
+
+    // HTML made up only of scripts and styles, like an interactive plot, has nothing to show.
+    if markdown.trim().is_empty() {
+        return Err(anyhow!("HTML output has no static content"));
+    }
+    Ok(markdown)
+}
+
+/// The media types in a bundle that can be rendered, from richest to plainest.
+fn ranked_mime_types(data: &MimeBundle) -> Vec<&MimeType> {
+    let mut mime_types = data
+        .content
+        .iter()
+        .filter(|mime_type| rank_mime_type(mime_type) > 0)
+        .collect::<Vec<_>>();
+    mime_types.sort_by_key(|mime_type| Reverse(rank_mime_type(mime_type)));
+    mime_types
+}
+
 pub struct Output {
     content: OutputContent,
     display_id: Option<String>,
@@ -354,6 +416,7 @@ pub enum OutputContent {
     Message(String),
     Table(TableView),
     Markdown(View<MarkdownView>),
+    Json(View<JsonView>),
     ClearOutputWaitMarker,
 }
 
@@ -364,6 +427,7 @@ impl OutputContent {
             // Here we can just handle either
             Self::Plain(stdio) => Some(stdio.render(cx)),
             Self::Markdown(markdown) => Some(markdown.clone().into_any_element()),
+            Self::Json(json) => Some(json.clone().into_any_element()),
             Self::Stream(stdio) => Some(stdio.render(cx)),
             Self::Image(image) => Some(image.render(cx)),
             Self::Message(message) => Some(div().child(message.clone()).into_any_element()),
@@ -375,23 +439,57 @@ impl OutputContent {
         el
     }
 
+    /// Renders the richest media type in the bundle, falling back to the next richest
+    /// whenever one can't be rendered.
     pub fn new(data: &MimeBundle, cx: &mut WindowContext) -> Self {
-        match data.richest(rank_mime_type) {
-            Some(MimeType::Plain(text)) => OutputContent::Plain(TerminalOutput::from(text, cx)),
-            Some(MimeType::Markdown(text)) => {
+        let mut last_error = None;
+        for mime_type in ranked_mime_types(data) {
+            match Self::from_mime_type(mime_type, cx) {
+                Ok(content) => return content,
+                Err(error) => last_error = Some(error),
+            }
+        }
+
+        match last_error {
+            Some(error) => OutputContent::Message(format!("Failed to render output: {}", error)),
+            // Any other media types are not supported
+            None => OutputContent::Message("Unsupported media type".to_string()),
+        }
+    }
+
+    fn from_mime_type(mime_type: &MimeType, cx: &mut WindowContext) -> Result<Self> {
+        Ok(match mime_type {
+            MimeType::Plain(text) => OutputContent::Plain(TerminalOutput::from(text, cx)),
+            MimeType::Markdown(text) => {
                 let view = cx.new_view(|cx| MarkdownView::from(text.clone(), cx));
                 OutputContent::Markdown(view)
             }
-            Some(MimeType::Png(data)) | Some(MimeType::Jpeg(data)) => match ImageView::from(data) {
-                Ok(view) => OutputContent::Image(view),
-                Err(error) => OutputContent::Message(format!("Failed to load image: {}", error)),
-            },
-            Some(MimeType::DataTable(data)) => {
-                OutputContent::Table(TableView::new(data.clone(), cx))
+            MimeType::Html(html) => {
+                let markdown = html_to_markdown(html)?;
+                let view = cx.new_view(|cx| MarkdownView::from(markdown, cx));
+                OutputContent::Markdown(view)
             }
-            // Any other media types are not supported
-            _ => OutputContent::Message("Unsupported media type".to_string()),
-        }
+            MimeType::Latex(latex) => {
+                let text = latex_to_unicode(latex);
+                if text.is_empty() {
+                    return Err(anyhow!("LaTeX output is empty"));
+                }
+                OutputContent::Plain(TerminalOutput::from(&text, cx))
+            }
+            MimeType::Json(json) => {
+                let value = serde_json::to_value(json)?;
+                OutputContent::Json(cx.new_view(|_| JsonView::new(value)))
+            }
+            MimeType::Png(data) | MimeType::Jpeg(data) => OutputContent::Image(
+                ImageView::from(data).map_err(|error| anyhow!("Failed to load image: {error}"))?,
+            ),
+            MimeType::Svg(svg) => OutputContent::Image(
+                ImageView::from_svg(svg, cx)
+                    .map_err(|error| anyhow!("Failed to load SVG: {error}"))?,
+            ),
+            MimeType::DataTable(data) => OutputContent::Table(TableView::new(data.clone(), cx)),
+            _ => return Err(anyhow!("Unsupported media type")),
+        })
     }
 }
 
@@ -633,3 +731,149 @@ impl Render for ExecutionView {
             .into_any_element()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Parses the data of a `display_data` or `execute_result` message recorded from a kernel.
+    fn recorded_bundle(message: Value) -> MimeBundle {
+        serde_json::from_value(message["content"]["data"].clone()).unwrap()
+    }
+
+    #[test]
+    fn test_pandas_dataframe_renders_as_markdown_table() {
+        let bundle = recorded_bundle(json!({
+            "header": { "msg_type": "execute_result" },
+            "content": {
+                "execution_count": 3,
+                "data": {
+                    "text/plain": "   a  b\n0  1  x\n1  2  y",
+                    "text/html": "<div>\n<style scoped>\n    .dataframe tbody tr th { vertical-align: top; }\n</style>\n<table border=\"1\" class=\"dataframe\">\n  <thead>\n    <tr style=\"text-align: right;\">\n      <th></th>\n      <th>a</th>\n      <th>b</th>\n    </tr>\n  </thead>\n  <tbody>\n    <tr>\n      <th>0</th>\n      <td>1</td>\n      <td>x</td>\n    </tr>\n    <tr>\n      <th>1</th>\n      <td>2</td>\n      <td>y</td>\n    </tr>\n  </tbody>\n</table>\n</div>"
+                },
+                "metadata": {}
+            }
+        }));
+
+        let ranked = ranked_mime_types(&bundle);
+        let Some(MimeType::Html(html)) = ranked.first() else {
+            panic!("expected HTML to be the richest media type, got {ranked:?}");
+        };
+        assert!(matches!(ranked.last(), Some(MimeType::Plain(_))));
+
+        let markdown = html_to_markdown(html).unwrap();
+        assert!(markdown.contains('|'), "expected a table: {markdown}");
+        for cell in ["a", "b", "x", "y"] {
+            assert!(markdown.contains(cell), "missing {cell}: {markdown}");
+        }
+        assert!(!markdown.contains("vertical-align"));
+    }
+
+    #[test]
+    fn test_plots_prefer_static_images_over_scripts() {
+        let bundle = recorded_bundle(json!({
+            "header": { "msg_type": "display_data" },
+            "content": {
+                "data": {
+                    "text/plain": "Figure({\n    'data': [{'type': 'scatter', 'x': [1, 2, 3]}]\n})",
+                    "text/html": "<div id=\"plot\"></div><script type=\"text/javascript\">Plotly.newPlot(\"plot\", [{\"x\": [1, 2, 3]}]);</script>",
+                    "image/png": "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg=="
+                },
+                "metadata": {},
+                "transient": {}
+            }
+        }));
+
+        let ranked = ranked_mime_types(&bundle);
+        assert!(matches!(ranked.first(), Some(MimeType::Png(_))));
+
+        // Without the image, the script-only HTML can't be shown, so plain text is used instead.
+        let html = ranked
+            .iter()
+            .find_map(|mime_type| match mime_type {
+                MimeType::Html(html) => Some(html),
+                _ => None,
+            })
+            .unwrap();
+        assert!(html_to_markdown(html).is_err());
+    }
+
+    #[test]
+    fn test_svg_ranks_above_text() {
+        let bundle = recorded_bundle(json!({
+            "header": { "msg_type": "display_data" },
+            "content": {
+                "data": {
+                    "text/plain": "<IPython.core.display.SVG object>",
+                    "image/svg+xml": "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"10\" height=\"10\"><circle cx=\"5\" cy=\"5\" r=\"4\"/></svg>"
+                },
+                "metadata": {},
+                "transient": {}
+            }
+        }));
+
+        assert!(matches!(
+            ranked_mime_types(&bundle).first(),
+            Some(MimeType::Svg(_))
+        ));
+    }
+
+    #[test]
+    fn test_sympy_latex_renders_as_unicode() {
+        let bundle = recorded_bundle(json!({
+            "header": { "msg_type": "execute_result" },
+            "content": {
+                "execution_count": 5,
+                "data": {
+                    "text/plain": "x**2 + 2*x + 1",
+                    "text/latex": "$\\displaystyle x^{2} + 2 x + 1$"
+                },
+                "metadata": {}
+            }
+        }));
+
+        let ranked = ranked_mime_types(&bundle);
+        let Some(MimeType::Latex(latex)) = ranked.first() else {
+            panic!("expected LaTeX to be the richest media type, got {ranked:?}");
+        };
+        assert_eq!(latex_to_unicode(latex), "x² + 2 x + 1");
+
+        assert_eq!(
+            latex_to_unicode("$\\displaystyle \\sin{\\left(x \\right)}$"),
+            "sin(x)"
+        );
+        assert_eq!(
+            latex_to_unicode("$\\displaystyle \\frac{\\sqrt{\\pi}}{2}$"),
+            "(√π)/2"
+        );
+        assert_eq!(
+            latex_to_unicode("$\\displaystyle \\sum_{n=0}^{\\infty} \\alpha_{n} x^{n}$"),
+            "∑ₙ₌₀^∞ αₙ xⁿ"
+        );
+    }
+
+    #[test]
+    fn test_json_ranks_above_text() {
+        let bundle = recorded_bundle(json!({
+            "header": { "msg_type": "display_data" },
+            "content": {
+                "data": {
+                    "text/plain": "<IPython.core.display.JSON object>",
+                    "application/json": { "name": "zed", "tags": ["editor", "rust"] }
+                },
+                "metadata": { "application/json": { "expanded": false, "root": "root" } },
+                "transient": {}
+            }
+        }));
+
+        let ranked = ranked_mime_types(&bundle);
+        let Some(MimeType::Json(json)) = ranked.first() else {
+            panic!("expected JSON to be the richest media type, got {ranked:?}");
+        };
+        assert_eq!(
+            serde_json::to_value(json).unwrap(),
+            json!({ "name": "zed", "tags": ["editor", "rust"] })
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use collections::HashSet;
+use gpui::{Render, SharedString};
+use serde_json::Value;
+use ui::{prelude::*, Disclosure};
+
+/// The number of characters of a string value shown before it is truncated.
+const MAX_STRING_LENGTH: usize = 120;
+
+/// JsonView renders `application/json` output as a tree, whose objects and arrays can be collapsed.
+pub struct JsonView {
+    value: Value,
+    /// The JSON pointers of the expanded objects and arrays.
+    expanded: HashSet<String>,
+}
+
+impl JsonView {
+    pub fn new(value: Value) -> Self {
+        // Only the root starts out expanded, so large documents stay compact.
+        let mut expanded = HashSet::default();
+        expanded.insert(String::new());
+        Self { value, expanded }
+    }
+
+    fn toggle(&mut self, pointer: &str, cx: &mut ViewContext<Self>) {
+        if !self.expanded.remove(pointer) {
+            self.expanded.insert(pointer.to_string());
+        }
+        cx.notify();
+    }
+
+    fn render_node(
+        &self,
+        key: Option<String>,
+        value: &Value,
+        pointer: String,
+        depth: usize,
+        rows: &mut Vec<AnyElement>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let children: Vec<(String, &Value)> = match value {
+            Value::Object(map) => map
+                .iter()
+                .map(|(key, value)| (key.clone(), value))
+                .collect(),
+            Value::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(ix, value)| (ix.to_string(), value))
+                .collect(),
+            _ => Vec::new(),
+        };
+        let is_container = matches!(value, Value::Object(_) | Value::Array(_));
+        let is_expanded = self.expanded.contains(&pointer);
+
+        let key_label = key.map(|key| Label::new(format!("{key}:")).color(Color::Accent));
+        let row = h_flex()
+            .gap_1()
+            .pl(px(depth as f32 * 16.))
+            .child(if is_container && !children.is_empty() {
+                let pointer = pointer.clone();
+                Disclosure::new(SharedString::from(format!("json-{pointer}")), is_expanded)
+                    .on_click(cx.listener(move |this, _, cx| this.toggle(&pointer, cx)))
+                    .into_any_element()
+            } else {
+                div().w(px(16.)).into_any_element()
+            })
+            .children(key_label)
+            .child(match value {
+                Value::Object(_) | Value::Array(_) if is_expanded => {
+                    Label::new(if value.is_object() { "{" } else { "[" }).color(Color::Muted)
+                }
+                Value::Object(_) | Value::Array(_) => {
+                    Label::new(summary(value)).color(Color::Muted)
+                }
+                Value::String(_) => Label::new(scalar(value)).color(Color::Created),
+                Value::Number(_) => Label::new(scalar(value)).color(Color::Modified),
+                Value::Bool(_) | Value::Null => Label::new(scalar(value)).color(Color::Info),
+            });
+        rows.push(row.into_any_element());
+
+        if is_container && is_expanded {
+            for (key, child) in children {
+                let child_pointer = format!("{pointer}/{}", escape_pointer_token(&key));
+                let key = value.is_object().then_some(key);
+                self.render_node(key, child, child_pointer, depth + 1, rows, cx);
+            }
+            rows.push(
+                h_flex()
+                    .pl(px(depth as f32 * 16. + 16.))
+                    .child(
+                        Label::new(if value.is_object() { "}" } else { "]" }).color(Color::Muted),
+                    )
+                    .into_any_element(),
+            );
+        }
+    }
+}
+
+/// Describes a collapsed object or array.
+fn summary(value: &Value) -> String {
+    match value {
+        Value::Object(map) if map.is_empty() => "{}".to_string(),
+        Value::Object(map) if map.len() == 1 => "{…} 1 key".to_string(),
+        Value::Object(map) => format!("{{…}} {} keys", map.len()),
+        Value::Array(items) if items.is_empty() => "[]".to_string(),
+        Value::Array(items) if items.len() == 1 => "[…] 1 item".to_string(),
+        Value::Array(items) => format!("[…] {} items", items.len()),
+        value => scalar(value),
+    }
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(string) if string.chars().count() > MAX_STRING_LENGTH => {
+            let truncated = string.chars().take(MAX_STRING_LENGTH).collect::<String>();
+            format!("{:?}", format!("{truncated}…"))
+        }
+        value => value.to_string(),
+    }
+}
+
+/// Escapes a key for use in a JSON pointer, as described in RFC 6901.
+fn escape_pointer_token(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+impl Render for JsonView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let mut rows = Vec::new();
+        self.render_node(None, &self.value, String::new(), 0, &mut rows, cx);
+
+        v_flex()
+            .id("json")
+            .w_full()
+            .py_1()
+            .overflow_x_scroll()
+            .children(rows)
+    }
+}
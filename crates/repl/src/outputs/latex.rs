@@ -0,0 +1,320 @@
+//! Renders `text/latex` math as Unicode text.
+//!
+//! This covers the subset of LaTeX that kernels like sympy emit: symbols, fractions, roots,
+//! superscripts and subscripts. Anything it doesn't understand is left as-is.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+pub fn latex_to_unicode(latex: &str) -> String {
+    let mut parser = Parser {
+        chars: latex.trim().chars().peekable(),
+    };
+    let text = parser.parse_until(None);
+
+    let mut result = String::with_capacity(text.len());
+    for line in text.lines() {
+        let line = line
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .replace("( ", "(")
+            .replace(" )", ")");
+        if !line.is_empty() {
+            if !result.is_empty() {
+                result.push('\n');
+            }
+            result.push_str(&line);
+        }
+    }
+    result
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn parse_until(&mut self, close: Option<char>) -> String {
+        let mut output = String::new();
+        while let Some(c) = self.chars.next() {
+            if Some(c) == close {
+                break;
+            }
+            match c {
+                '{' => output.push_str(&self.parse_until(Some('}'))),
+                '\\' => output.push_str(&self.parse_command()),
+                '^' => {
+                    let argument = self.parse_argument();
+                    output.push_str(&superscript(&argument));
+                }
+                '_' => {
+                    let argument = self.parse_argument();
+                    output.push_str(&subscript(&argument));
+                }
+                '$' => {}
+                '&' | '~' => output.push(' '),
+                c => output.push(c),
+            }
+        }
+        output
+    }
+
+    /// Parses the argument of a command or script, which is either a group or a single token.
+    fn parse_argument(&mut self) -> String {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match self.chars.next() {
+            Some('{') => self.parse_until(Some('}')),
+            Some('\\') => self.parse_command(),
+            Some(c) => c.to_string(),
+            None => String::new(),
+        }
+    }
+
+    fn parse_command(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(c) = self.chars.next_if(|c| c.is_ascii_alphabetic()) {
+            name.push(c);
+        }
+        if name.is_empty() {
+            return match self.chars.next() {
+                Some('\\') => "\n".to_string(),
+                Some(',' | ';' | ':' | '!' | ' ') | None => " ".to_string(),
+                Some(c) => c.to_string(),
+            };
+        }
+
+        match name.as_str() {
+            "frac" | "dfrac" | "tfrac" => {
+                let numerator = self.parse_argument();
+                let denominator = self.parse_argument();
+                format!(
+                    "{}/{}",
+                    parenthesize(&numerator),
+                    parenthesize(&denominator)
+                )
+            }
+            "sqrt" => {
+                let mut index = String::new();
+                if self.chars.next_if_eq(&'[').is_some() {
+                    index = self.parse_until(Some(']'));
+                }
+                let radicand = self.parse_argument();
+                let root = match index.as_str() {
+                    "" => "√",
+                    "3" => "∛",
+                    "4" => "∜",
+                    _ => return format!("{}√{}", superscript(&index), parenthesize(&radicand)),
+                };
+                format!("{root}{}", parenthesize(&radicand))
+            }
+            "left" | "right" | "big" | "Big" | "bigg" | "Bigg" => match self.parse_argument() {
+                delimiter if delimiter == "." => String::new(),
+                delimiter => delimiter,
+            },
+            "mathbb" => self.parse_argument().chars().map(double_struck).collect(),
+            "text" | "textrm" | "textbf" | "textit" | "mathrm" | "mathbf" | "mathit" | "mathsf"
+            | "mathtt" | "mathcal" | "boldsymbol" | "operatorname" => self.parse_argument(),
+            "begin" | "end" => {
+                self.parse_argument();
+                String::new()
+            }
+            "displaystyle" | "textstyle" | "limits" | "nolimits" => String::new(),
+            "quad" => " ".to_string(),
+            "qquad" => "  ".to_string(),
+            "sin" | "cos" | "tan" | "cot" | "sec" | "csc" | "sinh" | "cosh" | "tanh" | "arcsin"
+            | "arccos" | "arctan" | "log" | "ln" | "exp" | "lim" | "max" | "min" | "sup"
+            | "inf" | "det" | "gcd" | "deg" | "dim" | "ker" | "arg" => name,
+            _ => match symbol(&name) {
+                Some(symbol) => symbol.to_string(),
+                None => format!("\\{name}"),
+            },
+        }
+    }
+}
+
+/// Wraps an expression in parentheses when it spans more than a single term.
+fn parenthesize(text: &str) -> String {
+    let text = text.trim();
+    if text.chars().all(|c| c.is_alphanumeric() || c == '.') || text.chars().count() == 1 {
+        text.to_string()
+    } else {
+        format!("({text})")
+    }
+}
+
+fn superscript(text: &str) -> String {
+    let text = text.trim();
+    match text
+        .chars()
+        .map(superscript_char)
+        .collect::<Option<String>>()
+    {
+        Some(superscript) => superscript,
+        None if text.chars().count() == 1 => format!("^{text}"),
+        None => format!("^({text})"),
+    }
+}
+
+fn subscript(text: &str) -> String {
+    let text = text.trim();
+    match text.chars().map(subscript_char).collect::<Option<String>>() {
+        Some(subscript) => subscript,
+        None if text.chars().count() == 1 => format!("_{text}"),
+        None => format!("_({text})"),
+    }
+}
+
+fn superscript_char(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '-' | '−' => '⁻',
+        '=' => '⁼',
+        '(' => '⁽',
+        ')' => '⁾',
+        'i' => 'ⁱ',
+        'n' => 'ⁿ',
+        'T' => 'ᵀ',
+        _ => return None,
+    })
+}
+
+fn subscript_char(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '₀',
+        '1' => '₁',
+        '2' => '₂',
+        '3' => '₃',
+        '4' => '₄',
+        '5' => '₅',
+        '6' => '₆',
+        '7' => '₇',
+        '8' => '₈',
+        '9' => '₉',
+        '+' => '₊',
+        '-' | '−' => '₋',
+        '=' => '₌',
+        '(' => '₍',
+        ')' => '₎',
+        'a' => 'ₐ',
+        'e' => 'ₑ',
+        'i' => 'ᵢ',
+        'j' => 'ⱼ',
+        'k' => 'ₖ',
+        'm' => 'ₘ',
+        'n' => 'ₙ',
+        'o' => 'ₒ',
+        't' => 'ₜ',
+        'x' => 'ₓ',
+        _ => return None,
+    })
+}
+
+fn double_struck(c: char) -> char {
+    match c {
+        'C' => 'ℂ',
+        'N' => 'ℕ',
+        'P' => 'ℙ',
+        'Q' => 'ℚ',
+        'R' => 'ℝ',
+        'Z' => 'ℤ',
+        c => c,
+    }
+}
+
+fn symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" | "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" | "vartheta" => "θ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "rho" => "ρ",
+        "sigma" => "σ",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" | "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Upsilon" => "Υ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        "cdot" => "·",
+        "times" => "×",
+        "div" => "÷",
+        "pm" => "±",
+        "mp" => "∓",
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "neq" | "ne" => "≠",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "propto" => "∝",
+        "infty" => "∞",
+        "sum" => "∑",
+        "prod" => "∏",
+        "int" => "∫",
+        "iint" => "∬",
+        "oint" => "∮",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "to" | "rightarrow" => "→",
+        "leftarrow" => "←",
+        "Rightarrow" | "implies" => "⇒",
+        "Leftrightarrow" | "iff" => "⇔",
+        "mapsto" => "↦",
+        "in" => "∈",
+        "notin" => "∉",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "cup" => "∪",
+        "cap" => "∩",
+        "emptyset" => "∅",
+        "forall" => "∀",
+        "exists" => "∃",
+        "neg" => "¬",
+        "wedge" | "land" => "∧",
+        "vee" | "lor" => "∨",
+        "ldots" | "dots" | "cdots" => "…",
+        "hbar" => "ħ",
+        "ell" => "ℓ",
+        "circ" => "∘",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        _ => return None,
+    })
+}
@@ -1,5 +1,6 @@
 mod components;
 mod jupyter_settings;
+mod kernel_providers;
 mod kernels;
 mod outputs;
 mod repl_editor;
@@ -7,6 +8,7 @@ mod repl_sessions_ui;
 mod repl_store;
 mod session;
 mod stdio;
+mod variable_explorer;
 
 use std::{sync::Arc, time::Duration};
 
@@ -20,10 +22,11 @@ pub use crate::jupyter_settings::JupyterSettings;
 pub use crate::kernels::{Kernel, KernelSpecification, KernelStatus};
 pub use crate::repl_editor::*;
 pub use crate::repl_sessions_ui::{
-    ClearOutputs, Interrupt, ReplSessionsPage, Run, Sessions, Shutdown,
+    ClearOutputs, Interrupt, ReplSessionsPage, Restart, Run, Sessions, Shutdown,
 };
 use crate::repl_store::ReplStore;
 pub use crate::session::Session;
+pub use crate::variable_explorer::{RefreshVariables, ToggleVariableExplorer, VariableExplorer};
 use client::telemetry::Telemetry;
 
 pub fn init(fs: Arc<dyn Fs>, telemetry: Arc<Telemetry>, cx: &mut AppContext) {
@@ -31,6 +34,7 @@ pub fn init(fs: Arc<dyn Fs>, telemetry: Arc<Telemetry>, cx: &mut AppContext) {
     JupyterSettings::register(cx);
     ::editor::init_settings(cx);
     repl_sessions_ui::init(cx);
+    variable_explorer::init(cx);
     ReplStore::init(fs, telemetry, cx);
 }
 
@@ -59,17 +59,18 @@ pub fn run(editor: WeakView<Editor>, move_down: bool, cx: &mut WindowContext) ->
                     let store = store.clone();
                     move |_this, _session, event, cx| match event {
                         SessionEvent::Shutdown(shutdown_event) => {
-                            store.update(cx, |store, _cx| {
-                                store.remove_session(shutdown_event.entity_id());
+                            store.update(cx, |store, cx| {
+                                store.remove_session(shutdown_event.entity_id(), cx);
                             });
                         }
+                        SessionEvent::ExecutionFinished => {}
                     }
                 })
                 .detach();
             });
 
-            store.update(cx, |store, _cx| {
-                store.insert_session(editor.entity_id(), session.clone());
+            store.update(cx, |store, cx| {
+                store.insert_session(editor.entity_id(), session.clone(), cx);
             });
 
             session
@@ -155,6 +156,19 @@ pub fn interrupt(editor: WeakView<Editor>, cx: &mut WindowContext) {
     });
 }
 
+pub fn restart(editor: WeakView<Editor>, cx: &mut WindowContext) {
+    let store = ReplStore::global(cx);
+    let entity_id = editor.entity_id();
+    let Some(session) = store.read(cx).get_session(entity_id).cloned() else {
+        return;
+    };
+
+    session.update(cx, |session, cx| {
+        session.restart(cx);
+        cx.notify();
+    });
+}
+
 pub fn shutdown(editor: WeakView<Editor>, cx: &mut WindowContext) {
     let store = ReplStore::global(cx);
     let entity_id = editor.entity_id();
@@ -283,6 +297,52 @@ fn markdown_code_blocks(buffer: &BufferSnapshot, range: Range<Point>) -> Vec<Ran
         .collect()
 }
 
+/// The code sent to a kernel to complete or inspect the code at a position.
+pub(crate) struct KernelCode {
+    pub text: String,
+    /// The offset of the code within the buffer.
+    pub start: usize,
+    /// The position of the cursor within the code, counted in characters as Jupyter expects.
+    pub cursor_pos: usize,
+}
+
+impl KernelCode {
+    /// Returns the buffer offset of a position within the code, counted in characters.
+    pub fn buffer_offset(&self, char_ix: usize) -> usize {
+        let byte_ix = self
+            .text
+            .char_indices()
+            .nth(char_ix)
+            .map_or(self.text.len(), |(ix, _)| ix);
+        self.start + byte_ix
+    }
+}
+
+/// Returns the code surrounding an offset: the code block containing it in Markdown,
+/// and the whole buffer otherwise.
+pub(crate) fn kernel_code_at(buffer: &BufferSnapshot, offset: usize) -> Option<KernelCode> {
+    let range = if buffer
+        .language()
+        .map_or(false, |language| language.name().as_ref() == "Markdown")
+    {
+        let point = buffer.offset_to_point(offset);
+        markdown_code_blocks(buffer, point..point)
+            .into_iter()
+            .map(|range| buffer.point_to_offset(range.start)..buffer.point_to_offset(range.end))
+            .find(|range| range.contains(&offset) || range.end == offset)?
+    } else {
+        0..buffer.len()
+    };
+
+    let text = buffer.text_for_range(range.clone()).collect::<String>();
+    let cursor_pos = text[..offset - range.start].chars().count();
+    Some(KernelCode {
+        text,
+        start: range.start,
+        cursor_pos,
+    })
+}
+
 fn language_supported(language: &Arc<Language>) -> bool {
     match language.name().as_ref() {
         "TypeScript" | "Python" => true,
@@ -586,4 +646,52 @@ mod tests {
             },]
         );
     }
+
+    #[gpui::test]
+    fn test_kernel_code_at(cx: &mut AppContext) {
+        let markdown = languages::language("markdown", tree_sitter_md::language());
+        let python = languages::language("python", tree_sitter_python::language());
+        let language_registry = Arc::new(LanguageRegistry::new(
+            Task::ready(()),
+            cx.background_executor().clone(),
+        ));
+        language_registry.add(markdown.clone());
+        language_registry.add(python.clone());
+
+        // Only the code block around the cursor is sent from Markdown.
+        let buffer = cx.new_model(|cx| {
+            let mut buffer = Buffer::local(
+                indoc! { r#"
+                    Some prose, which isn't Python.
+
+                    ```python
+                    import os
+                    os.pa
+                    ```
+                "# },
+                cx,
+            );
+            buffer.set_language_registry(language_registry.clone());
+            buffer.set_language(Some(markdown.clone()), cx);
+            buffer
+        });
+        let snapshot = buffer.read(cx).snapshot();
+
+        let offset = snapshot.point_to_offset(Point::new(4, 5));
+        let code = kernel_code_at(&snapshot, offset).unwrap();
+        assert_eq!(code.text, "import os\nos.pa\n");
+        assert_eq!(code.cursor_pos, 15);
+        assert_eq!(code.buffer_offset(code.cursor_pos), offset);
+        assert!(kernel_code_at(&snapshot, 3).is_none());
+
+        // The whole buffer is sent otherwise, with the cursor counted in characters.
+        let buffer = cx
+            .new_model(|cx| Buffer::local("π = 3.14\nπ.re", cx).with_language(python.clone(), cx));
+        let snapshot = buffer.read(cx).snapshot();
+
+        let code = kernel_code_at(&snapshot, snapshot.len()).unwrap();
+        assert_eq!(code.start, 0);
+        assert_eq!(code.cursor_pos, 13);
+        assert_eq!(code.buffer_offset(11), snapshot.len() - 2);
+    }
 }
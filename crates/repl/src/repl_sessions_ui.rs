@@ -22,6 +22,7 @@ actions!(
         ClearOutputs,
         Sessions,
         Interrupt,
+        Restart,
         Shutdown,
         RefreshKernelspecs
     ]
@@ -114,6 +115,19 @@ pub fn init(cx: &mut AppContext) {
             })
             .detach();
 
+        editor
+            .register_action({
+                let editor_handle = editor_handle.clone();
+                move |_: &Restart, cx| {
+                    if !JupyterSettings::enabled(cx) {
+                        return;
+                    }
+
+                    crate::restart(editor_handle.clone(), cx);
+                }
+            })
+            .detach();
+
         editor
             .register_action({
                 let editor_handle = editor_handle.clone();
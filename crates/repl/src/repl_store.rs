@@ -150,11 +150,18 @@ impl ReplStore {
         self.sessions.get(&entity_id)
     }
 
-    pub fn insert_session(&mut self, entity_id: EntityId, session: View<Session>) {
+    pub fn insert_session(
+        &mut self,
+        entity_id: EntityId,
+        session: View<Session>,
+        cx: &mut ModelContext<Self>,
+    ) {
         self.sessions.insert(entity_id, session);
+        cx.notify();
     }
 
-    pub fn remove_session(&mut self, entity_id: EntityId) {
+    pub fn remove_session(&mut self, entity_id: EntityId, cx: &mut ModelContext<Self>) {
         self.sessions.remove(&entity_id);
+        cx.notify();
     }
 }
@@ -1,9 +1,11 @@
 use crate::components::KernelListItem;
+use crate::kernel_providers::{KernelCompletionProvider, KernelHoverProvider};
 use crate::KernelStatus;
 use crate::{
-    kernels::{Kernel, KernelSpecification, RunningKernel},
+    kernels::{InterruptMode, Kernel, KernelSpecification, RunningKernel},
     outputs::{ExecutionStatus, ExecutionView},
 };
+use anyhow::{anyhow, Context as _};
 use client::telemetry::Telemetry;
 use collections::{HashMap, HashSet};
 use editor::{
@@ -14,6 +16,7 @@ use editor::{
     scroll::Autoscroll,
     Anchor, AnchorRangeExt as _, Editor, MultiBuffer, ToPoint,
 };
+use futures::channel::oneshot;
 use futures::io::BufReader;
 use futures::{AsyncBufReadExt as _, FutureExt as _, StreamExt as _};
 use gpui::{
@@ -23,24 +26,42 @@ use gpui::{
 use language::Point;
 use project::Fs;
 use runtimelib::{
-    ExecuteRequest, ExecutionState, InterruptRequest, JupyterMessage, JupyterMessageContent,
-    ShutdownRequest,
+    CompleteReply, CompleteRequest, ExecuteRequest, ExecutionState, InspectReply, InspectRequest,
+    InterruptRequest, JupyterMessage, JupyterMessageContent, MimeBundle, ShutdownRequest,
 };
 use settings::Settings as _;
 use std::{env::temp_dir, ops::Range, sync::Arc, time::Duration};
 use theme::{ActiveTheme, ThemeSettings};
 use ui::{prelude::*, IconButtonShape, Tooltip};
+use util::ResultExt as _;
+
+/// How long to wait for the kernel to reply to a request, such as for completions, before
+/// giving up on it.
+const KERNEL_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub struct Session {
     editor: WeakView<Editor>,
+    fs: Arc<dyn Fs>,
     pub kernel: Kernel,
+    /// Incremented whenever the kernel is (re)started.
+    kernel_generation: usize,
     blocks: HashMap<String, EditorBlock>,
+    /// Requests awaiting a reply from the kernel, keyed by message id.
+    pending_requests: HashMap<String, oneshot::Sender<JupyterMessageContent>>,
+    /// Silent executions awaiting the kernel going idle, keyed by message id.
+    pending_evaluations: HashMap<String, PendingEvaluation>,
     messaging_task: Task<()>,
     pub kernel_specification: KernelSpecification,
     telemetry: Arc<Telemetry>,
     _buffer_subscription: Subscription,
 }
 
+struct PendingEvaluation {
+    outputs: Vec<MimeBundle>,
+    error: Option<String>,
+    done_tx: oneshot::Sender<anyhow::Result<Vec<MimeBundle>>>,
+}
+
 struct EditorBlock {
     code_range: Range<Anchor>,
     invalidation_anchor: Anchor,
@@ -193,24 +214,56 @@ impl Session {
         kernel_specification: KernelSpecification,
         cx: &mut ViewContext<Self>,
     ) -> Self {
-        let kernel_language = kernel_specification.kernelspec.language.clone();
+        let subscription = match editor.upgrade() {
+            Some(editor) => {
+                let buffer = editor.read(cx).buffer().clone();
+                cx.subscribe(&buffer, Self::on_buffer_event)
+            }
+            None => Subscription::new(|| {}),
+        };
+
+        let mut session = Self {
+            editor,
+            fs,
+            kernel: Kernel::StartingKernel(Task::ready(()).shared()),
+            kernel_generation: 0,
+            messaging_task: Task::ready(()),
+            blocks: HashMap::default(),
+            pending_requests: HashMap::default(),
+            pending_evaluations: HashMap::default(),
+            kernel_specification,
+            _buffer_subscription: subscription,
+            telemetry,
+        };
+        session.start_kernel(cx);
+        session.set_editor_providers(true, cx);
+        session
+    }
+
+    fn start_kernel(&mut self, cx: &mut ViewContext<Self>) {
+        let kernel_language = self.kernel_specification.kernelspec.language.clone();
 
-        telemetry.report_repl_event(
+        self.telemetry.report_repl_event(
             kernel_language.clone(),
             KernelStatus::Starting.to_string(),
             cx.entity_id().to_string(),
         );
 
-        let entity_id = editor.entity_id();
-        let working_directory = editor
+        // Outputs of a kernel that has since been restarted are ignored.
+        self.kernel_generation += 1;
+        let kernel_generation = self.kernel_generation;
+
+        let entity_id = self.editor.entity_id();
+        let working_directory = self
+            .editor
             .upgrade()
             .and_then(|editor| editor.read(cx).working_directory(cx))
             .unwrap_or_else(temp_dir);
         let kernel = RunningKernel::new(
-            kernel_specification.clone(),
+            self.kernel_specification.clone(),
             entity_id,
             working_directory,
-            fs.clone(),
+            self.fs.clone(),
             cx,
         );
 
@@ -221,6 +274,10 @@ impl Session {
                 match kernel {
                     Ok((mut kernel, mut messages_rx)) => {
                         this.update(&mut cx, |session, cx| {
+                            if session.kernel_generation != kernel_generation {
+                                return;
+                            }
+
                             let stderr = kernel.process.stderr.take();
 
                             cx.spawn(|_session, mut _cx| async move {
@@ -267,35 +324,24 @@ impl Session {
                                     }
                                 };
 
-                                log::error!("{}", error_message);
-
                                 session
                                     .update(&mut cx, |session, cx| {
+                                        // The process of a restarted kernel is expected to exit.
+                                        if session.kernel_generation != kernel_generation {
+                                            return;
+                                        }
+
+                                        log::error!("{}", error_message);
+
                                         session.kernel(
                                             Kernel::ErroredLaunch(error_message.clone()),
                                             cx,
                                         );
 
-                                        session.blocks.values().for_each(|block| {
-                                            block.execution_view.update(
-                                                cx,
-                                                |execution_view, cx| {
-                                                    match execution_view.status {
-                                                        ExecutionStatus::Finished => {
-                                                            // Do nothing when the output was good
-                                                        }
-                                                        _ => {
-                                                            // All other cases, set the status to errored
-                                                            execution_view.status =
-                                                                ExecutionStatus::KernelErrored(
-                                                                    error_message.clone(),
-                                                                )
-                                                        }
-                                                    }
-                                                    cx.notify();
-                                                },
-                                            );
-                                        });
+                                        session.set_unfinished_executions_status(
+                                            ExecutionStatus::KernelErrored(error_message.clone()),
+                                            cx,
+                                        );
 
                                         cx.notify();
                                     })
@@ -329,7 +375,9 @@ impl Session {
                     }
                     Err(err) => {
                         this.update(&mut cx, |session, cx| {
-                            session.kernel(Kernel::ErroredLaunch(err.to_string()), cx);
+                            if session.kernel_generation == kernel_generation {
+                                session.kernel(Kernel::ErroredLaunch(err.to_string()), cx);
+                            }
                         })
                         .ok();
                     }
@@ -337,23 +385,52 @@ impl Session {
             })
             .shared();
 
-        let subscription = match editor.upgrade() {
-            Some(editor) => {
-                let buffer = editor.read(cx).buffer().clone();
-                cx.subscribe(&buffer, Self::on_buffer_event)
-            }
-            None => Subscription::new(|| {}),
-        };
+        self.kernel = Kernel::StartingKernel(pending_kernel);
+        cx.notify();
+    }
 
-        return Self {
-            editor,
-            kernel: Kernel::StartingKernel(pending_kernel),
-            messaging_task: Task::ready(()),
-            blocks: HashMap::default(),
-            kernel_specification,
-            _buffer_subscription: subscription,
-            telemetry,
-        };
+    /// Marks the executions that haven't finished, which won't receive any more output.
+    fn set_unfinished_executions_status(
+        &mut self,
+        status: ExecutionStatus,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.blocks.values().for_each(|block| {
+            block.execution_view.update(cx, |execution_view, cx| {
+                match execution_view.status {
+                    ExecutionStatus::Finished => {
+                        // Do nothing when the output was good
+                    }
+                    _ => {
+                        // All other cases, set the status to errored
+                        execution_view.status = status.clone();
+                    }
+                }
+                cx.notify();
+            });
+        });
+    }
+
+    /// Completes and inspects code in the editor with the kernel while the session is running.
+    fn set_editor_providers(&self, enabled: bool, cx: &mut ViewContext<Self>) {
+        let session = cx.view().downgrade();
+        self.editor
+            .update(cx, |editor, _cx| {
+                let project = editor.project().cloned();
+                if enabled {
+                    editor.set_completion_provider(Box::new(KernelCompletionProvider::new(
+                        session.clone(),
+                        project,
+                    )));
+                    editor.set_hover_provider(Some(Box::new(KernelHoverProvider::new(session))));
+                } else {
+                    if let Some(project) = project {
+                        editor.set_completion_provider(Box::new(project));
+                    }
+                    editor.set_hover_provider(None);
+                }
+            })
+            .ok();
     }
 
     fn on_buffer_event(
@@ -398,6 +475,137 @@ impl Session {
         anyhow::Ok(())
     }
 
+    /// Sends a request on the shell channel, resolving with the kernel's reply.
+    ///
+    /// Requests are only sent while the kernel is idle, since the shell channel handles one
+    /// request at a time and would otherwise wait for the running execution.
+    fn request(
+        &mut self,
+        message: JupyterMessage,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<JupyterMessageContent>> {
+        if let Err(error) = self.ensure_idle() {
+            return Task::ready(Err(error));
+        }
+
+        let message_id = message.header.msg_id.clone();
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.pending_requests.insert(message_id.clone(), reply_tx);
+        if let Err(error) = self.send(message, cx) {
+            self.pending_requests.remove(&message_id);
+            return Task::ready(Err(error));
+        }
+
+        cx.spawn(|this, mut cx| async move {
+            let mut timeout = cx
+                .background_executor()
+                .timer(KERNEL_REQUEST_TIMEOUT)
+                .fuse();
+            futures::select! {
+                reply = reply_rx.fuse() => reply.context("kernel did not reply"),
+                _ = timeout => {
+                    this.update(&mut cx, |this, _| this.pending_requests.remove(&message_id))
+                        .ok();
+                    Err(anyhow!("kernel did not reply in time"))
+                }
+            }
+        })
+    }
+
+    /// Runs code without recording it in the kernel's history, resolving with the data it
+    /// displays once the kernel is idle again.
+    ///
+    /// Like [`Self::request`], this is only done while the kernel is idle.
+    pub fn evaluate(
+        &mut self,
+        code: String,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<Vec<MimeBundle>>> {
+        if let Err(error) = self.ensure_idle() {
+            return Task::ready(Err(error));
+        }
+
+        let message: JupyterMessage = ExecuteRequest {
+            code,
+            silent: true,
+            store_history: false,
+            ..ExecuteRequest::default()
+        }
+        .into();
+
+        let message_id = message.header.msg_id.clone();
+        let (done_tx, done_rx) = oneshot::channel();
+        self.pending_evaluations.insert(
+            message_id.clone(),
+            PendingEvaluation {
+                outputs: Vec::new(),
+                error: None,
+                done_tx,
+            },
+        );
+        if let Err(error) = self.send(message, cx) {
+            self.pending_evaluations.remove(&message_id);
+            return Task::ready(Err(error));
+        }
+
+        cx.background_executor()
+            .spawn(async move { done_rx.await.context("kernel did not finish evaluating")? })
+    }
+
+    /// Whether the kernel is running and not executing any code.
+    pub fn is_idle(&self) -> bool {
+        self.ensure_idle().is_ok()
+    }
+
+    fn ensure_idle(&self) -> anyhow::Result<()> {
+        match &self.kernel {
+            Kernel::RunningKernel(kernel)
+                if matches!(kernel.execution_state, ExecutionState::Idle) =>
+            {
+                Ok(())
+            }
+            Kernel::RunningKernel(_) => Err(anyhow!("kernel is busy")),
+            _ => Err(anyhow!("kernel is not running")),
+        }
+    }
+
+    /// Requests completions for the code, at a cursor position counted in characters.
+    pub fn complete(
+        &mut self,
+        code: String,
+        cursor_pos: usize,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<CompleteReply>> {
+        let reply = self.request(CompleteRequest { code, cursor_pos }.into(), cx);
+        cx.background_executor().spawn(async move {
+            match reply.await? {
+                JupyterMessageContent::CompleteReply(reply) => Ok(reply),
+                _ => Err(anyhow!("unexpected reply to complete_request")),
+            }
+        })
+    }
+
+    /// Requests documentation for the code, at a cursor position counted in characters.
+    pub fn inspect(
+        &mut self,
+        code: String,
+        cursor_pos: usize,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<InspectReply>> {
+        let request = InspectRequest {
+            code,
+            cursor_pos,
+            detail_level: Some(0),
+        };
+        let reply = self.request(request.into(), cx);
+        cx.background_executor().spawn(async move {
+            match reply.await? {
+                JupyterMessageContent::InspectReply(reply) => Ok(reply),
+                _ => Err(anyhow!("unexpected reply to inspect_request")),
+            }
+        })
+    }
+
     pub fn clear_outputs(&mut self, cx: &mut ViewContext<Self>) {
         let blocks_to_remove: HashSet<CustomBlockId> =
             self.blocks.values().map(|block| block.block_id).collect();
@@ -534,10 +742,39 @@ impl Session {
             None => return,
         };
 
+        if let Some(evaluation) = self.pending_evaluations.get_mut(parent_message_id) {
+            match &message.content {
+                JupyterMessageContent::DisplayData(data) => {
+                    evaluation.outputs.push(data.data.clone());
+                }
+                JupyterMessageContent::ErrorOutput(error) => {
+                    evaluation.error = Some(format!("{}: {}", error.ename, error.evalue));
+                }
+                JupyterMessageContent::Status(status)
+                    if matches!(status.execution_state, ExecutionState::Idle) =>
+                {
+                    if let Some(evaluation) = self.pending_evaluations.remove(parent_message_id) {
+                        let result = match evaluation.error {
+                            Some(error) => Err(anyhow!(error)),
+                            None => Ok(evaluation.outputs),
+                        };
+                        evaluation.done_tx.send(result).ok();
+                    }
+                }
+                _ => {}
+            }
+        }
+
         match &message.content {
             JupyterMessageContent::Status(status) => {
                 self.kernel.set_execution_state(&status.execution_state);
 
+                if matches!(status.execution_state, ExecutionState::Idle)
+                    && self.blocks.contains_key(parent_message_id)
+                {
+                    cx.emit(SessionEvent::ExecutionFinished);
+                }
+
                 self.telemetry.report_repl_event(
                     self.kernel_specification.kernelspec.language.clone(),
                     KernelStatus::from(&self.kernel).to_string(),
@@ -550,6 +787,12 @@ impl Session {
                 self.kernel.set_kernel_info(&reply);
                 cx.notify();
             }
+            JupyterMessageContent::CompleteReply(_) | JupyterMessageContent::InspectReply(_) => {
+                if let Some(reply_tx) = self.pending_requests.remove(parent_message_id) {
+                    reply_tx.send(message.content.clone()).ok();
+                }
+                return;
+            }
             JupyterMessageContent::UpdateDisplayData(update) => {
                 let display_id = if let Some(display_id) = update.transient.display_id.clone() {
                     display_id
@@ -575,9 +818,14 @@ impl Session {
 
     pub fn interrupt(&mut self, cx: &mut ViewContext<Self>) {
         match &mut self.kernel {
-            Kernel::RunningKernel(_kernel) => {
-                self.send(InterruptRequest {}.into(), cx).ok();
-            }
+            Kernel::RunningKernel(kernel) => match self.kernel_specification.interrupt_mode() {
+                InterruptMode::Signal => {
+                    kernel.signal_interrupt().log_err();
+                }
+                InterruptMode::Message => {
+                    self.send(InterruptRequest {}.into(), cx).ok();
+                }
+            },
             Kernel::StartingKernel(_task) => {
                 // NOTE: If we switch to a literal queue instead of chaining on to the task, clear all queued executions
             }
@@ -587,9 +835,16 @@ impl Session {
 
     pub fn kernel(&mut self, kernel: Kernel, cx: &mut ViewContext<Self>) {
         if let Kernel::Shutdown = kernel {
+            self.set_editor_providers(false, cx);
             cx.emit(SessionEvent::Shutdown(self.editor.clone()));
         }
 
+        // Replies from the previous kernel will never arrive.
+        if !matches!(kernel, Kernel::RunningKernel(_)) {
+            self.pending_requests.clear();
+            self.pending_evaluations.clear();
+        }
+
         let kernel_status = KernelStatus::from(&kernel).to_string();
         let kernel_language = self.kernel_specification.kernelspec.language.clone();
 
@@ -602,8 +857,45 @@ impl Session {
         self.kernel = kernel;
     }
 
+    /// Restarts the kernel, keeping the outputs of previous executions.
+    pub fn restart(&mut self, cx: &mut ViewContext<Self>) {
+        let kernel = std::mem::replace(&mut self.kernel, Kernel::ShuttingDown);
+        self.pending_requests.clear();
+        self.pending_evaluations.clear();
+        self.set_unfinished_executions_status(
+            ExecutionStatus::KernelErrored("Kernel restarted".to_string()),
+            cx,
+        );
+
+        match kernel {
+            Kernel::RunningKernel(mut kernel) => {
+                let mut request_tx = kernel.request_tx.clone();
+
+                cx.spawn(|this, mut cx| async move {
+                    let message: JupyterMessage = ShutdownRequest { restart: true }.into();
+                    request_tx.try_send(message).ok();
+
+                    // Give the kernel a bit of time to clean up
+                    cx.background_executor().timer(Duration::from_secs(1)).await;
+
+                    kernel.process.kill().ok();
+
+                    this.update(&mut cx, |session, cx| {
+                        session.start_kernel(cx);
+                    })
+                    .ok();
+                })
+                .detach();
+            }
+            _ => self.start_kernel(cx),
+        }
+        cx.notify();
+    }
+
     pub fn shutdown(&mut self, cx: &mut ViewContext<Self>) {
         let kernel = std::mem::replace(&mut self.kernel, Kernel::ShuttingDown);
+        self.pending_requests.clear();
+        self.pending_evaluations.clear();
 
         match kernel {
             Kernel::RunningKernel(mut kernel) => {
@@ -640,6 +932,8 @@ impl Session {
 
 pub enum SessionEvent {
     Shutdown(WeakView<Editor>),
+    /// An execution of code from the editor finished, possibly changing the kernel's state.
+    ExecutionFinished,
 }
 
 impl EventEmitter<SessionEvent> for Session {}
@@ -679,6 +973,14 @@ impl Render for Session {
             })
             .child(Label::new(self.kernel_specification.name.clone()))
             .children(status_text.map(|status_text| Label::new(format!("({status_text})"))))
+            .button(
+                Button::new("restart", "Restart")
+                    .style(ButtonStyle::Subtle)
+                    .disabled(self.kernel.is_shutting_down())
+                    .on_click(cx.listener(move |session, _, cx| {
+                        session.restart(cx);
+                    })),
+            )
             .button(
                 Button::new("shutdown", "Shutdown")
                     .style(ButtonStyle::Subtle)
@@ -0,0 +1,439 @@
+//! A dock panel listing the variables held by the kernel of the active editor's session.
+
+mod introspection;
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use editor::Editor;
+use gpui::{
+    actions, Action, AnyElement, AppContext, AsyncWindowContext, EventEmitter, FocusHandle,
+    FocusableView, Pixels, Subscription, Task, View, ViewContext, WeakView,
+};
+use project::Fs;
+use settings::Settings as _;
+use ui::{prelude::*, ListItem, Tooltip};
+use workspace::dock::{DockPosition, Panel, PanelEvent};
+use workspace::Workspace;
+
+use crate::jupyter_settings::VariableExplorerDockPosition;
+use crate::outputs::TableView;
+use crate::repl_store::ReplStore;
+use crate::session::SessionEvent;
+use crate::{JupyterSettings, Session};
+use introspection::{parse_table, parse_variables, Introspection, KernelVariable};
+
+actions!(repl, [ToggleVariableExplorer, RefreshVariables]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(
+        |workspace: &mut Workspace, _cx: &mut ViewContext<Workspace>| {
+            workspace.register_action(|workspace, _: &ToggleVariableExplorer, cx| {
+                if !JupyterSettings::enabled(cx) {
+                    return;
+                }
+
+                workspace.toggle_panel_focus::<VariableExplorer>(cx);
+            });
+        },
+    )
+    .detach();
+}
+
+pub struct VariableExplorer {
+    fs: Arc<dyn Fs>,
+    workspace: WeakView<Workspace>,
+    width: Option<Pixels>,
+    active: bool,
+    focus_handle: FocusHandle,
+    session: Option<View<Session>>,
+    variables: Vec<KernelVariable>,
+    error: Option<SharedString>,
+    /// The variable being viewed as a table, with its first rows.
+    table: Option<(String, TableView)>,
+    refresh_task: Task<()>,
+    table_task: Task<()>,
+    session_subscription: Option<Subscription>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl VariableExplorer {
+    pub async fn load(
+        workspace: WeakView<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> Result<View<Self>> {
+        workspace.update(&mut cx, |workspace, cx| Self::new(workspace, cx))
+    }
+
+    fn new(workspace: &Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let fs = workspace.app_state().fs.clone();
+        let workspace_handle = cx.view().clone();
+
+        cx.new_view(|cx| {
+            let store = ReplStore::global(cx);
+            let subscriptions = vec![
+                cx.subscribe(&workspace_handle, |this, _, event, cx| {
+                    if let workspace::Event::ActiveItemChanged = event {
+                        this.update_session(cx);
+                    }
+                }),
+                // Sessions are started and shut down by running code in an editor.
+                cx.observe(&store, |this, _, cx| this.update_session(cx)),
+            ];
+
+            let mut this = Self {
+                fs,
+                workspace: workspace_handle.downgrade(),
+                width: None,
+                active: false,
+                focus_handle: cx.focus_handle(),
+                session: None,
+                variables: Vec::new(),
+                error: None,
+                table: None,
+                refresh_task: Task::ready(()),
+                table_task: Task::ready(()),
+                session_subscription: None,
+                _subscriptions: subscriptions,
+            };
+            this.update_session(cx);
+            this
+        })
+    }
+
+    /// Follows the session of the active editor.
+    fn update_session(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let session = workspace
+            .read(cx)
+            .active_item_as::<Editor>(cx)
+            .and_then(|editor| {
+                ReplStore::global(cx)
+                    .read(cx)
+                    .get_session(editor.entity_id())
+                    .cloned()
+            });
+
+        if session.as_ref().map(|session| session.entity_id())
+            == self.session.as_ref().map(|session| session.entity_id())
+        {
+            return;
+        }
+
+        self.session_subscription = session.as_ref().map(|session| {
+            cx.subscribe(session, |this, _, event, cx| {
+                if let SessionEvent::ExecutionFinished = event {
+                    this.refresh(cx);
+                }
+            })
+        });
+        self.session = session;
+        self.variables.clear();
+        self.error = None;
+        self.table = None;
+        self.refresh(cx);
+        cx.notify();
+    }
+
+    fn introspection(&self, cx: &AppContext) -> Option<Introspection> {
+        let session = self.session.as_ref()?;
+        Introspection::for_language(&session.read(cx).kernel_specification.kernelspec.language)
+    }
+
+    /// Whether the panel is shown, as it can be the active panel of a closed dock.
+    fn is_visible(&self, cx: &ViewContext<Self>) -> bool {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return false;
+        };
+        let workspace = workspace.read(cx);
+        [workspace.left_dock(), workspace.right_dock()]
+            .into_iter()
+            .filter_map(|dock| dock.read(cx).visible_panel())
+            .any(|panel| panel.to_any().entity_id() == cx.entity_id())
+    }
+
+    /// Lists the kernel's variables, which is only done while the panel is open since it runs
+    /// code in the kernel.
+    fn refresh(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.active || !self.is_visible(cx) {
+            return;
+        }
+        let Some(session) = self.session.clone() else {
+            return;
+        };
+        let Some(introspection) = self.introspection(cx) else {
+            return;
+        };
+        // The kernel's state is still changing, so wait for the running execution to finish.
+        if !session.read(cx).is_idle() {
+            return;
+        }
+
+        let evaluation = session.update(cx, |session, cx| {
+            session.evaluate(introspection.variables_code(), cx)
+        });
+        self.refresh_task = cx.spawn(|this, mut cx| async move {
+            let result = evaluation
+                .await
+                .and_then(|outputs| parse_variables(&outputs));
+            this.update(&mut cx, |this, cx| {
+                match result {
+                    Ok(variables) => {
+                        this.variables = variables;
+                        this.error = None;
+                    }
+                    Err(error) => this.error = Some(error.to_string().into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn view_table(&mut self, name: String, cx: &mut ViewContext<Self>) {
+        let Some(session) = self.session.clone() else {
+            return;
+        };
+        let Some(introspection) = self.introspection(cx) else {
+            return;
+        };
+
+        let evaluation = session.update(cx, |session, cx| {
+            session.evaluate(introspection.table_code(&name), cx)
+        });
+        self.table_task = cx.spawn(|this, mut cx| async move {
+            let result = evaluation.await.and_then(|outputs| parse_table(&outputs));
+            this.update(&mut cx, |this, cx| {
+                match result {
+                    Ok(table) => {
+                        this.table = Some((name, TableView::new(table, cx)));
+                        this.error = None;
+                    }
+                    Err(error) => this.error = Some(error.to_string().into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn render_variable(
+        &self,
+        ix: usize,
+        variable: &KernelVariable,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let is_selected = self
+            .table
+            .as_ref()
+            .map_or(false, |(name, _)| name == &variable.name);
+
+        ListItem::new(ix)
+            .selected(is_selected)
+            .when(variable.is_table, |item| {
+                let name = variable.name.clone();
+                item.end_slot(Icon::new(IconName::ListTree).color(Color::Muted))
+                    .on_click(cx.listener(move |this, _, cx| this.view_table(name.clone(), cx)))
+            })
+            .child(
+                v_flex()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(Label::new(variable.name.clone()))
+                            .child(
+                                Label::new(variable.type_name.clone())
+                                    .size(LabelSize::Small)
+                                    .color(Color::Accent),
+                            )
+                            .children(variable.shape.clone().map(|shape| {
+                                Label::new(shape).size(LabelSize::Small).color(Color::Muted)
+                            })),
+                    )
+                    .child(
+                        Label::new(variable.preview.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .single_line(),
+                    ),
+            )
+    }
+
+    fn render_message(&self, message: impl Into<SharedString>) -> AnyElement {
+        div()
+            .p_2()
+            .child(Label::new(message.into()).color(Color::Muted))
+            .into_any_element()
+    }
+}
+
+impl FocusableView for VariableExplorer {
+    fn focus_handle(&self, _cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for VariableExplorer {}
+
+impl Panel for VariableExplorer {
+    fn persistent_name() -> &'static str {
+        "Variable Explorer"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        match JupyterSettings::get_global(cx).variable_explorer.dock {
+            VariableExplorerDockPosition::Left => DockPosition::Left,
+            VariableExplorerDockPosition::Right => DockPosition::Right,
+        }
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<JupyterSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings, _| {
+                let dock = match position {
+                    DockPosition::Left | DockPosition::Bottom => VariableExplorerDockPosition::Left,
+                    DockPosition::Right => VariableExplorerDockPosition::Right,
+                };
+                settings
+                    .variable_explorer
+                    .get_or_insert_with(Default::default)
+                    .dock = Some(dock);
+            },
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        self.width.unwrap_or_else(|| {
+            JupyterSettings::get_global(cx)
+                .variable_explorer
+                .default_width
+        })
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.width = size;
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        (JupyterSettings::enabled(cx) && JupyterSettings::get_global(cx).variable_explorer.button)
+            .then_some(IconName::Code)
+    }
+
+    fn icon_tooltip(&self, _cx: &WindowContext) -> Option<&'static str> {
+        Some("Variable Explorer")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleVariableExplorer)
+    }
+
+    fn set_active(&mut self, active: bool, cx: &mut ViewContext<Self>) {
+        self.active = active;
+        if active {
+            // The dock is still being updated, and is only open once it's done.
+            cx.defer(|this, cx| this.refresh(cx));
+        }
+    }
+}
+
+impl Render for VariableExplorer {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let title = match &self.session {
+            Some(session) => session.read(cx).kernel_specification.name.clone(),
+            None => "Variables".to_string(),
+        };
+
+        let body = match &self.session {
+            None => self.render_message("Run code in an editor to see the variables of its kernel"),
+            Some(session) if self.introspection(cx).is_none() => self.render_message(format!(
+                "Variables of {} kernels can't be shown",
+                session.read(cx).kernel_specification.kernelspec.language
+            )),
+            Some(_) if self.variables.is_empty() && self.error.is_none() => {
+                self.render_message("No variables")
+            }
+            Some(_) => v_flex()
+                .id("variables")
+                .flex_1()
+                .overflow_y_scroll()
+                .children(
+                    self.error
+                        .clone()
+                        .map(|error| div().p_2().child(Label::new(error).color(Color::Error))),
+                )
+                .children(
+                    self.variables
+                        .iter()
+                        .enumerate()
+                        .map(|(ix, variable)| self.render_variable(ix, variable, cx)),
+                )
+                .into_any_element(),
+        };
+
+        let table = self.table.as_ref().map(|(name, table)| {
+            v_flex()
+                .flex_1()
+                .border_t_1()
+                .border_color(cx.theme().colors().border)
+                .child(
+                    h_flex()
+                        .justify_between()
+                        .px_2()
+                        .py_1()
+                        .child(Label::new(name.clone()))
+                        .child(
+                            IconButton::new("close-table", IconName::Close)
+                                .icon_size(IconSize::Small)
+                                .tooltip(|cx| Tooltip::text("Close Table", cx))
+                                .on_click(cx.listener(|this, _, cx| {
+                                    this.table = None;
+                                    cx.notify();
+                                })),
+                        ),
+                )
+                .child(
+                    div()
+                        .id("variable-table")
+                        .flex_1()
+                        .overflow_scroll()
+                        .child(table.render(cx)),
+                )
+        });
+
+        v_flex()
+            .key_context("VariableExplorer")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(|this, _: &RefreshVariables, cx| this.refresh(cx)))
+            .size_full()
+            .bg(cx.theme().colors().panel_background)
+            .child(
+                h_flex()
+                    .justify_between()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(Label::new(title))
+                    .child(
+                        IconButton::new("refresh-variables", IconName::ArrowCircle)
+                            .icon_size(IconSize::Small)
+                            .disabled(self.session.is_none())
+                            .tooltip(|cx| {
+                                Tooltip::for_action("Refresh Variables", &RefreshVariables, cx)
+                            })
+                            .on_click(cx.listener(|this, _, cx| this.refresh(cx))),
+                    ),
+            )
+            .child(body)
+            .children(table)
+    }
+}
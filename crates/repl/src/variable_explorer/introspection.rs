@@ -0,0 +1,233 @@
+//! Code injected into kernels to list the variables they hold.
+//!
+//! Each snippet runs silently and reports back by displaying its result as
+//! `application/json`, or as `application/vnd.dataresource+json` for tables.
+
+use anyhow::{anyhow, Result};
+use runtimelib::media::datatable::TabularDataResource;
+use runtimelib::{MimeBundle, MimeType};
+use serde::Deserialize;
+
+/// The number of rows of a table fetched for viewing.
+const MAX_TABLE_ROWS: usize = 1000;
+
+/// The number of variables listed, in order of their names.
+const MAX_VARIABLES: usize = 500;
+
+/// The number of characters shown of each variable's value.
+const MAX_PREVIEW_LENGTH: usize = 200;
+
+const PYTHON_VARIABLES: &str = r#"
+def _zed_variables():
+    import itertools
+    import reprlib
+    from IPython import get_ipython
+    from IPython.display import display
+
+    # Bounds the work done for large values, which are only shown in part.
+    limits = reprlib.Repr()
+    limits.maxlevel = 2
+    limits.maxstring = MAX_PREVIEW_LENGTH
+    limits.maxother = MAX_PREVIEW_LENGTH
+
+    shell = get_ipython()
+    variables = []
+    for name, value in sorted(shell.user_ns.items(), key=lambda item: item[0]):
+        if len(variables) >= MAX_VARIABLES:
+            break
+        if name.startswith("_") or name in shell.user_ns_hidden:
+            continue
+        kind = type(value).__name__
+        if kind in ("module", "function", "builtin_function_or_method", "type"):
+            continue
+
+        shape = getattr(value, "shape", None)
+        if isinstance(shape, tuple):
+            shape = " × ".join(str(dimension) for dimension in shape)
+        elif hasattr(value, "__len__") and not isinstance(value, str):
+            try:
+                shape = str(len(value))
+            except Exception:
+                shape = None
+        else:
+            shape = None
+
+        is_table = kind in ("DataFrame", "Series") and hasattr(value, "to_json")
+        try:
+            if is_table and hasattr(value, "columns"):
+                columns = itertools.islice(value.columns, MAX_PREVIEW_LENGTH)
+                preview = ", ".join(str(column) for column in columns)
+            else:
+                preview = " ".join(limits.repr(value).split())
+        except Exception as error:
+            preview = "<%s>" % type(error).__name__
+        if len(preview) > MAX_PREVIEW_LENGTH:
+            preview = preview[:MAX_PREVIEW_LENGTH] + "…"
+
+        variables.append(
+            {
+                "name": name,
+                "type": kind,
+                "shape": shape,
+                "preview": preview,
+                "table": is_table,
+            }
+        )
+    display({"application/json": variables}, raw=True)
+
+_zed_variables()
+del _zed_variables
+"#;
+
+const PYTHON_TABLE: &str = r#"
+def _zed_table(value):
+    import json
+    from IPython.display import display
+
+    if not hasattr(value, "columns"):
+        value = value.to_frame()
+    table = json.loads(value.head(MAX_TABLE_ROWS).to_json(orient="table", default_handler=str))
+    display({"application/vnd.dataresource+json": table}, raw=True)
+
+_zed_table(get_ipython().user_ns[NAME])
+del _zed_table
+"#;
+
+/// The languages whose kernels can be introspected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Introspection {
+    Python,
+}
+
+impl Introspection {
+    /// Returns the introspection for a kernel's language, as named by its kernelspec.
+    pub fn for_language(language: &str) -> Option<Self> {
+        match language.to_lowercase().as_str() {
+            "python" => Some(Self::Python),
+            _ => None,
+        }
+    }
+
+    /// Code that displays the kernel's variables as a JSON list of [`KernelVariable`]s, with
+    /// previews of their values that are cut short.
+    pub fn variables_code(&self) -> String {
+        match self {
+            Self::Python => PYTHON_VARIABLES
+                .replace("MAX_VARIABLES", &MAX_VARIABLES.to_string())
+                .replace("MAX_PREVIEW_LENGTH", &MAX_PREVIEW_LENGTH.to_string()),
+        }
+    }
+
+    /// Code that displays the first rows of the named variable as a tabular data resource.
+    pub fn table_code(&self, name: &str) -> String {
+        match self {
+            Self::Python => {
+                // A JSON string is also a valid Python string literal.
+                let name = serde_json::to_string(name).unwrap_or_default();
+                PYTHON_TABLE
+                    .replace("MAX_TABLE_ROWS", &MAX_TABLE_ROWS.to_string())
+                    .replace("NAME", &name)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct KernelVariable {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_name: String,
+    /// The dimensions of arrays and tables, or the length of collections.
+    pub shape: Option<String>,
+    pub preview: String,
+    /// Whether the variable can be viewed as a table.
+    #[serde(rename = "table", default)]
+    pub is_table: bool,
+}
+
+pub fn parse_variables(outputs: &[MimeBundle]) -> Result<Vec<KernelVariable>> {
+    let value = outputs
+        .iter()
+        .flat_map(|bundle| bundle.content.iter())
+        .find_map(|mime_type| match mime_type {
+            MimeType::Json(value) => Some(value.clone()),
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("the kernel did not report its variables"))?;
+
+    let mut variables: Vec<KernelVariable> = serde_json::from_value(value)?;
+    variables.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(variables)
+}
+
+pub fn parse_table(outputs: &[MimeBundle]) -> Result<TabularDataResource> {
+    outputs
+        .iter()
+        .flat_map(|bundle| bundle.content.iter())
+        .find_map(|mime_type| match mime_type {
+            MimeType::DataTable(table) => Some(table.clone()),
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("the kernel did not report the table"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_variables() {
+        let bundle: MimeBundle = serde_json::from_value(json!({
+            "application/json": [
+                {"name": "x", "type": "int", "shape": null, "preview": "1", "table": false},
+                {"name": "df", "type": "DataFrame", "shape": "3 × 2", "preview": "a, b", "table": true},
+            ]
+        }))
+        .unwrap();
+
+        let variables = parse_variables(&[bundle]).unwrap();
+        assert_eq!(
+            variables,
+            vec![
+                KernelVariable {
+                    name: "df".into(),
+                    type_name: "DataFrame".into(),
+                    shape: Some("3 × 2".into()),
+                    preview: "a, b".into(),
+                    is_table: true,
+                },
+                KernelVariable {
+                    name: "x".into(),
+                    type_name: "int".into(),
+                    shape: None,
+                    preview: "1".into(),
+                    is_table: false,
+                },
+            ]
+        );
+
+        assert!(parse_variables(&[]).is_err());
+    }
+
+    #[test]
+    fn test_table_code_quotes_name() {
+        let code = Introspection::Python.table_code("my\"df");
+        assert!(code.contains(r#"get_ipython().user_ns["my\"df"]"#));
+        assert!(code.contains("value.head(1000)"));
+        assert_eq!(
+            Introspection::for_language("Python"),
+            Some(Introspection::Python)
+        );
+        assert_eq!(Introspection::for_language("typescript"), None);
+    }
+
+    #[test]
+    fn test_variables_code_limits_previews() {
+        let code = Introspection::Python.variables_code();
+        assert!(code.contains("if len(variables) >= 500:"));
+        assert!(code.contains("limits.maxstring = 200"));
+        assert!(code.contains("preview = preview[:200] + \"…\""));
+        assert!(!code.contains("MAX_"));
+    }
+}
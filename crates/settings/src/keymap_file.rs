@@ -1,7 +1,10 @@
-use crate::{settings_store::parse_json_with_comments, SettingsAssets};
+use crate::{
+    settings_store::{parse_json_with_comments, replace_value_in_json_text, to_pretty_json},
+    SettingsAssets,
+};
 use anyhow::{anyhow, Context, Result};
 use collections::BTreeMap;
-use gpui::{Action, AppContext, KeyBinding, SharedString};
+use gpui::{Action, AppContext, KeyBinding, KeyBindingContextPredicate, SharedString};
 use schemars::{
     gen::{SchemaGenerator, SchemaSettings},
     schema::{InstanceType, Schema, SchemaObject, SingleOrVec, SubschemaValidation},
@@ -58,39 +61,15 @@ impl KeymapFile {
             let bindings = bindings
                 .into_iter()
                 .filter_map(|(keystroke, action)| {
-                    let action = action.0;
-
-                    // This is a workaround for a limitation in serde: serde-rs/json#497
-                    // We want to deserialize the action data as a `RawValue` so that we can
-                    // deserialize the action itself dynamically directly from the JSON
-                    // string. But `RawValue` currently does not work inside of an untagged enum.
-                    match action {
-                        Value::Array(items) => {
-                            let Ok([name, data]): Result<[serde_json::Value; 2], _> =
-                                items.try_into()
-                            else {
-                                return Some(Err(anyhow!("Expected array of length 2")));
-                            };
-                            let serde_json::Value::String(name) = name else {
-                                return Some(Err(anyhow!(
-                                    "Expected first item in array to be a string."
-                                )));
-                            };
-                            cx.build_action(&name, Some(data))
-                        }
-                        Value::String(name) => cx.build_action(&name, None),
-                        Value::Null => Ok(no_action()),
-                        _ => {
-                            return Some(Err(anyhow!("Expected two-element array, got {action:?}")))
-                        }
-                    }
-                    .with_context(|| {
-                        format!(
-                            "invalid binding value for keystroke {keystroke}, context {context:?}"
-                        )
-                    })
-                    .log_err()
-                    .map(|action| KeyBinding::load(&keystroke, action, context.as_deref()))
+                    action
+                        .build(cx)
+                        .with_context(|| {
+                            format!(
+                                "invalid binding value for keystroke {keystroke}, context {context:?}"
+                            )
+                        })
+                        .log_err()
+                        .map(|action| KeyBinding::load(&keystroke, action, context.as_deref()))
                 })
                 .collect::<Result<Vec<_>>>()?;
 
@@ -99,6 +78,111 @@ impl KeymapFile {
         Ok(())
     }
 
+    pub fn blocks(&self) -> &[KeymapBlock] {
+        &self.0
+    }
+
+    /// Returns the given keymap file contents, edited so that `keystrokes` is bound to `action`
+    /// in the block for `context`. The block is appended to the file if it doesn't exist yet.
+    /// Binding to `null` disables any other binding for the keystrokes in that context.
+    ///
+    /// Blocks are matched by comparing their parsed context predicates, and the rest of the
+    /// file, including comments, is left untouched.
+    pub fn update_binding_in_text(
+        text: &str,
+        context: Option<&str>,
+        keystrokes: &str,
+        action: &Value,
+        tab_size: usize,
+    ) -> Result<String> {
+        let context = context
+            .map(KeyBindingContextPredicate::parse)
+            .transpose()
+            .context("invalid context")?;
+
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_json::language())?;
+        let syntax_tree = parser
+            .parse(text, None)
+            .ok_or_else(|| anyhow!("failed to parse keymap"))?;
+        let root = syntax_tree.root_node();
+        let mut cursor = root.walk();
+        let Some(array) = root
+            .named_children(&mut cursor)
+            .find(|node| node.kind() == "array")
+        else {
+            if text.trim().is_empty() {
+                let block = keymap_block_json(context.as_ref(), keystrokes, action, tab_size);
+                return Ok(format!("[\n{block}\n]\n"));
+            }
+            return Err(anyhow!("expected the keymap to be an array of blocks"));
+        };
+
+        let mut cursor = array.walk();
+        let blocks = array
+            .named_children(&mut cursor)
+            .filter(|node| node.kind() == "object")
+            .collect::<Vec<_>>();
+
+        for block in &blocks {
+            let mut block_context = None;
+            let mut block_bindings = None;
+            let mut cursor = block.walk();
+            for pair in block.named_children(&mut cursor) {
+                let (Some(key), Some(value)) = (
+                    pair.child_by_field_name("key"),
+                    pair.child_by_field_name("value"),
+                ) else {
+                    continue;
+                };
+                match &text[key.byte_range()] {
+                    "\"context\"" => {
+                        block_context =
+                            parse_json_with_comments::<Option<String>>(&text[value.byte_range()])
+                                .ok()
+                                .flatten()
+                    }
+                    "\"bindings\"" if value.kind() == "object" => block_bindings = Some(value),
+                    _ => {}
+                }
+            }
+
+            let block_context = block_context
+                .as_deref()
+                .map(KeyBindingContextPredicate::parse)
+                .transpose();
+            let (Ok(block_context), Some(bindings)) = (block_context, block_bindings) else {
+                continue;
+            };
+            if block_context != context {
+                continue;
+            }
+
+            let bindings_range = bindings.byte_range();
+            let (range, replacement) = replace_value_in_json_text(
+                &text[bindings_range.clone()],
+                &[keystrokes],
+                tab_size,
+                action,
+            );
+            let mut new_text = text.to_string();
+            new_text.replace_range(
+                bindings_range.start + range.start..bindings_range.start + range.end,
+                &replacement,
+            );
+            return Ok(new_text);
+        }
+
+        let block = keymap_block_json(context.as_ref(), keystrokes, action, tab_size);
+        let mut new_text = text.to_string();
+        if let Some(last_block) = array.named_children(&mut array.walk()).last() {
+            new_text.insert_str(last_block.end_byte(), &format!(",\n{block}"));
+        } else {
+            new_text.insert_str(array.start_byte() + 1, &format!("\n{block}\n"));
+        }
+        Ok(new_text)
+    }
+
     pub fn generate_json_schema(action_names: &[SharedString]) -> serde_json::Value {
         let mut root_schema = SchemaSettings::draft07()
             .with(|settings| settings.option_add_null_type = false)
@@ -140,6 +224,68 @@ impl KeymapFile {
     }
 }
 
+impl KeymapBlock {
+    pub fn context(&self) -> Option<&str> {
+        self.context.as_deref()
+    }
+
+    pub fn bindings(&self) -> &BTreeMap<String, KeymapAction> {
+        &self.bindings
+    }
+}
+
+impl KeymapAction {
+    pub fn value(&self) -> &Value {
+        &self.0
+    }
+
+    /// Builds the action this entry refers to, using the actions registered in the app.
+    pub fn build(&self, cx: &AppContext) -> Result<Box<dyn Action>> {
+        // This is a workaround for a limitation in serde: serde-rs/json#497
+        // We want to deserialize the action data as a `RawValue` so that we can
+        // deserialize the action itself dynamically directly from the JSON
+        // string. But `RawValue` currently does not work inside of an untagged enum.
+        match &self.0 {
+            Value::Array(items) => {
+                let [name, data] = items.as_slice() else {
+                    return Err(anyhow!("Expected array of length 2"));
+                };
+                let serde_json::Value::String(name) = name else {
+                    return Err(anyhow!("Expected first item in array to be a string."));
+                };
+                cx.build_action(name, Some(data.clone()))
+            }
+            Value::String(name) => cx.build_action(name, None),
+            Value::Null => Ok(no_action()),
+            action => Err(anyhow!("Expected two-element array, got {action:?}")),
+        }
+    }
+}
+
+fn keymap_block_json(
+    context: Option<&KeyBindingContextPredicate>,
+    keystrokes: &str,
+    action: &Value,
+    tab_size: usize,
+) -> String {
+    let indent = " ".repeat(tab_size);
+    let mut bindings = serde_json::Map::new();
+    bindings.insert(keystrokes.to_string(), action.clone());
+    let bindings = to_pretty_json(&Value::Object(bindings), tab_size, tab_size * 2);
+
+    let mut block = format!("{indent}{{\n");
+    if let Some(context) = context {
+        block.push_str(&format!(
+            "{indent}{indent}\"context\": {},\n",
+            Value::String(context.to_string())
+        ));
+    }
+    block.push_str(&format!(
+        "{indent}{indent}\"bindings\": {bindings}\n{indent}}}"
+    ));
+    block
+}
+
 fn no_action() -> Box<dyn gpui::Action> {
     gpui::NoAction.boxed_clone()
 }
@@ -147,6 +293,8 @@ fn no_action() -> Box<dyn gpui::Action> {
 #[cfg(test)]
 mod tests {
     use crate::KeymapFile;
+    use indoc::indoc;
+    use serde_json::json;
 
     #[test]
     fn can_deserialize_keymap_with_trailing_comma() {
@@ -163,4 +311,110 @@ mod tests {
         };
         KeymapFile::parse(json).unwrap();
     }
+
+    #[test]
+    fn test_update_binding_in_text() {
+        let keymap = indoc! {r#"
+            [
+              // Editor overrides
+              {
+                "context": "Editor && mode == full",
+                "bindings": {
+                  "ctrl-a": "editor::SelectAll"
+                }
+              }
+            ]
+        "#};
+
+        // Existing keystrokes within a block whose context is written differently.
+        assert_eq!(
+            KeymapFile::update_binding_in_text(
+                keymap,
+                Some("Editor && (mode==full)"),
+                "ctrl-a",
+                &json!(null),
+                2,
+            )
+            .unwrap(),
+            indoc! {r#"
+                [
+                  // Editor overrides
+                  {
+                    "context": "Editor && mode == full",
+                    "bindings": {
+                      "ctrl-a": null
+                    }
+                  }
+                ]
+            "#}
+        );
+
+        // New keystrokes within an existing block.
+        assert_eq!(
+            KeymapFile::update_binding_in_text(
+                keymap,
+                Some("Editor && mode == full"),
+                "ctrl-b",
+                &json!("editor::MoveLeft"),
+                2,
+            )
+            .unwrap(),
+            indoc! {r#"
+                [
+                  // Editor overrides
+                  {
+                    "context": "Editor && mode == full",
+                    "bindings": {
+                      "ctrl-b": "editor::MoveLeft",
+                      "ctrl-a": "editor::SelectAll"
+                    }
+                  }
+                ]
+            "#}
+        );
+
+        // New block.
+        assert_eq!(
+            KeymapFile::update_binding_in_text(
+                keymap,
+                Some("Workspace"),
+                "ctrl-b",
+                &json!("workspace::ToggleLeftDock"),
+                2,
+            )
+            .unwrap(),
+            indoc! {r#"
+                [
+                  // Editor overrides
+                  {
+                    "context": "Editor && mode == full",
+                    "bindings": {
+                      "ctrl-a": "editor::SelectAll"
+                    }
+                  },
+                  {
+                    "context": "Workspace",
+                    "bindings": {
+                      "ctrl-b": "workspace::ToggleLeftDock"
+                    }
+                  }
+                ]
+            "#}
+        );
+
+        // Empty file.
+        assert_eq!(
+            KeymapFile::update_binding_in_text("", None, "ctrl-b", &json!("menu::Confirm"), 2)
+                .unwrap(),
+            indoc! {r#"
+                [
+                  {
+                    "bindings": {
+                      "ctrl-b": "menu::Confirm"
+                    }
+                  }
+                ]
+            "#}
+        );
+    }
 }
@@ -11,7 +11,7 @@ use util::asset_str;
 
 pub use editable_setting_control::*;
 pub use json_schema::*;
-pub use keymap_file::KeymapFile;
+pub use keymap_file::{KeymapAction, KeymapBlock, KeymapFile};
 pub use settings_file::*;
 pub use settings_store::{Settings, SettingsLocation, SettingsSources, SettingsStore};
 
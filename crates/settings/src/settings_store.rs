@@ -938,7 +938,7 @@ fn update_value_in_json_text<'a>(
     }
 }
 
-fn replace_value_in_json_text(
+pub(crate) fn replace_value_in_json_text(
     text: &str,
     key_path: &[&str],
     tab_size: usize,
@@ -1058,7 +1058,11 @@ fn replace_value_in_json_text(
     }
 }
 
-fn to_pretty_json(value: &impl Serialize, indent_size: usize, indent_prefix_len: usize) -> String {
+pub(crate) fn to_pretty_json(
+    value: &impl Serialize,
+    indent_size: usize,
+    indent_prefix_len: usize,
+) -> String {
     const SPACES: [u8; 32] = [b' '; 32];
 
     debug_assert!(indent_size <= SPACES.len());
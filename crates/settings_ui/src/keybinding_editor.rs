@@ -0,0 +1,927 @@
+use std::{ops::Range, sync::Arc};
+
+use collections::HashMap;
+use editor::{Editor, EditorElement, EditorStyle};
+use fs::Fs;
+use gpui::{
+    uniform_list, AppContext, EventEmitter, FocusHandle, FocusableView, KeyBinding,
+    KeyBindingMatch, KeyBindingMatchStatus, KeyContext, Keymap, KeymapVersion, Keystroke,
+    KeystrokeEvent, NoAction, Subscription, Task, TextStyle, UniformListScrollHandle, View,
+    WeakView,
+};
+use serde_json::Value;
+use settings::{KeymapFile, Settings};
+use theme::ThemeSettings;
+use ui::{prelude::*, ListItem, ToggleButton, Tooltip};
+use util::ResultExt;
+use workspace::{
+    item::{Item, ItemEvent},
+    Workspace, WorkspaceId,
+};
+
+/// The number of recorded keystrokes kept while recording.
+const MAX_RECORDED_KEYSTROKES: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum KeybindingSource {
+    /// A binding that was loaded from anywhere but the user keymap, such as the default,
+    /// base or vim keymaps.
+    Default,
+    User,
+}
+
+impl KeybindingSource {
+    fn label(&self) -> &'static str {
+        match self {
+            KeybindingSource::Default => "Default",
+            KeybindingSource::User => "User",
+        }
+    }
+}
+
+struct KeybindingEntry {
+    source: KeybindingSource,
+    keystrokes: SharedString,
+    action_name: SharedString,
+    /// The action as it is written in a keymap file, if known.
+    action_value: Option<Value>,
+    context: Option<SharedString>,
+    /// The entry whose binding takes effect instead of this one, if any.
+    shadowed_by: Option<usize>,
+}
+
+impl KeybindingEntry {
+    fn new(source: KeybindingSource, binding: &KeyBinding, action_value: Option<Value>) -> Self {
+        let action_name = if binding.action().partial_eq(&NoAction) {
+            "(disabled)".into()
+        } else {
+            binding.action().name().to_string().into()
+        };
+        Self {
+            source,
+            keystrokes: binding
+                .keystrokes()
+                .iter()
+                .map(|keystroke| keystroke.unparse())
+                .collect::<Vec<_>>()
+                .join(" ")
+                .into(),
+            action_name,
+            action_value,
+            context: binding
+                .predicate()
+                .map(|predicate| predicate.to_string().into()),
+            shadowed_by: None,
+        }
+    }
+
+    fn matches_query(&self, query: &str) -> bool {
+        query.split_whitespace().all(|term| {
+            [
+                Some(&self.keystrokes),
+                Some(&self.action_name),
+                self.context.as_ref(),
+            ]
+            .into_iter()
+            .flatten()
+            .any(|field| field.to_lowercase().contains(term))
+        })
+    }
+}
+
+/// A keystroke observed while recording, along with how it was resolved against the keymap.
+struct RecordedKeystroke {
+    keystroke: Keystroke,
+    action_name: Option<SharedString>,
+    context_stack: Vec<KeyContext>,
+    matches: Vec<(KeyBinding, KeyBindingMatch)>,
+}
+
+enum Mode {
+    Browsing,
+    Editing {
+        entry_ix: usize,
+        keystrokes_editor: View<Editor>,
+        error: Option<SharedString>,
+    },
+}
+
+pub struct KeybindingEditor {
+    workspace: WeakView<Workspace>,
+    fs: Arc<dyn Fs>,
+    focus_handle: FocusHandle,
+    query_editor: View<Editor>,
+    entries: Vec<KeybindingEntry>,
+    filtered_entry_indices: Vec<usize>,
+    conflicts_only: bool,
+    selected_index: usize,
+    mode: Mode,
+    recorded_keystrokes: Option<Vec<RecordedKeystroke>>,
+    list: UniformListScrollHandle,
+    /// The version of the app's keymap that the entries were loaded from.
+    keymap_version: KeymapVersion,
+    load_task: Task<()>,
+    _keystroke_observer: Option<Subscription>,
+}
+
+impl KeybindingEditor {
+    pub fn new(workspace: &Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let fs = workspace.app_state().fs.clone();
+        let workspace = workspace.weak_handle();
+        cx.new_view(|cx: &mut ViewContext<Self>| {
+            let query_editor = cx.new_view(|cx| {
+                let mut input = Editor::single_line(cx);
+                input.set_placeholder_text("Search keybindings...", cx);
+                input
+            });
+            cx.subscribe(&query_editor, |this, _, event, cx| {
+                if let editor::EditorEvent::Edited { .. } = event {
+                    this.filter_entries(cx);
+                }
+            })
+            .detach();
+
+            let mut this = Self {
+                workspace,
+                fs,
+                focus_handle: cx.focus_handle(),
+                query_editor,
+                entries: Vec::new(),
+                filtered_entry_indices: Vec::new(),
+                conflicts_only: false,
+                selected_index: 0,
+                mode: Mode::Browsing,
+                recorded_keystrokes: None,
+                list: UniformListScrollHandle::new(),
+                keymap_version: KeymapVersion::default(),
+                load_task: Task::ready(()),
+                _keystroke_observer: None,
+            };
+            this.reload(cx);
+            this
+        })
+    }
+
+    fn reload(&mut self, cx: &mut ViewContext<Self>) {
+        self.keymap_version = cx.keymap().borrow().version();
+        let fs = self.fs.clone();
+        self.load_task = cx.spawn(|this, mut cx| async move {
+            let user_keymap = fs.load(paths::keymap_file()).await.unwrap_or_default();
+            this.update(&mut cx, |this, cx| {
+                this.entries = load_entries(&user_keymap, cx);
+                this.filter_entries(cx);
+            })
+            .ok();
+        });
+    }
+
+    fn filter_entries(&mut self, cx: &mut ViewContext<Self>) {
+        let query = self.query_editor.read(cx).text(cx).to_lowercase();
+        self.filtered_entry_indices = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| !self.conflicts_only || entry.shadowed_by.is_some())
+            .filter(|(_, entry)| entry.matches_query(&query))
+            .map(|(ix, _)| ix)
+            .collect();
+        self.selected_index = self
+            .selected_index
+            .min(self.filtered_entry_indices.len().saturating_sub(1));
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, cx: &mut ViewContext<Self>) {
+        if self.selected_index + 1 < self.filtered_entry_indices.len() {
+            self.select_index(self.selected_index + 1, cx);
+        }
+    }
+
+    fn select_prev(&mut self, _: &menu::SelectPrev, cx: &mut ViewContext<Self>) {
+        if self.selected_index > 0 {
+            self.select_index(self.selected_index - 1, cx);
+        }
+    }
+
+    fn select_index(&mut self, index: usize, cx: &mut ViewContext<Self>) {
+        self.selected_index = index;
+        self.list.scroll_to_item(index);
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        match &self.mode {
+            Mode::Browsing => {
+                if let Some(entry_ix) = self.filtered_entry_indices.get(self.selected_index) {
+                    self.start_editing(*entry_ix, cx);
+                }
+            }
+            Mode::Editing {
+                entry_ix,
+                keystrokes_editor,
+                ..
+            } => {
+                let entry_ix = *entry_ix;
+                let keystrokes = keystrokes_editor.read(cx).text(cx);
+                self.save_keystrokes(entry_ix, keystrokes, cx);
+            }
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        if let Mode::Editing { .. } = self.mode {
+            self.mode = Mode::Browsing;
+            cx.focus_view(&self.query_editor);
+            cx.notify();
+        } else {
+            cx.propagate();
+        }
+    }
+
+    fn start_editing(&mut self, entry_ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(entry) = self.entries.get(entry_ix) else {
+            return;
+        };
+        let keystrokes = entry.keystrokes.clone();
+        let keystrokes_editor = cx.new_view(|cx| {
+            let mut input = Editor::single_line(cx);
+            input.set_placeholder_text("Keystrokes, e.g. ctrl-k ctrl-s", cx);
+            input.set_text(keystrokes.to_string(), cx);
+            input.select_all(&editor::actions::SelectAll, cx);
+            input
+        });
+        cx.focus_view(&keystrokes_editor);
+        self.mode = Mode::Editing {
+            entry_ix,
+            keystrokes_editor,
+            error: None,
+        };
+        cx.notify();
+    }
+
+    /// Binds the entry's action to the given keystrokes in the user keymap,
+    /// disabling the entry's previous keystrokes in the same context.
+    fn save_keystrokes(&mut self, entry_ix: usize, keystrokes: String, cx: &mut ViewContext<Self>) {
+        let keystrokes = match normalize_keystrokes(&keystrokes) {
+            Ok(keystrokes) => keystrokes,
+            Err(error) => {
+                if let Mode::Editing { error: e, .. } = &mut self.mode {
+                    *e = Some(error.to_string().into());
+                }
+                cx.notify();
+                return;
+            }
+        };
+        let Some(entry) = self.entries.get(entry_ix) else {
+            return;
+        };
+        let Some(action_value) = entry.action_value.clone() else {
+            if let Mode::Editing { error, .. } = &mut self.mode {
+                *error = Some("This binding's action can only be rebound in keymap.json".into());
+            }
+            cx.notify();
+            return;
+        };
+
+        let fs = self.fs.clone();
+        let context = entry.context.clone();
+        let old_keystrokes = entry.keystrokes.clone();
+        let tab_size = 2;
+        cx.spawn(|this, mut cx| async move {
+            let text = fs.load(paths::keymap_file()).await.unwrap_or_default();
+            let mut text = KeymapFile::update_binding_in_text(
+                &text,
+                context.as_deref(),
+                &keystrokes,
+                &action_value,
+                tab_size,
+            )?;
+            if keystrokes != old_keystrokes.as_ref() {
+                text = KeymapFile::update_binding_in_text(
+                    &text,
+                    context.as_deref(),
+                    &old_keystrokes,
+                    &Value::Null,
+                    tab_size,
+                )?;
+            }
+            fs.atomic_write(paths::keymap_file().clone(), text).await?;
+
+            this.update(&mut cx, |this, cx| {
+                this.mode = Mode::Browsing;
+                cx.focus_view(&this.query_editor);
+                this.reload(cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn open_keymap_file(&mut self, cx: &mut ViewContext<Self>) {
+        self.workspace
+            .update(cx, |_, cx| {
+                cx.dispatch_action(Box::new(zed_actions::OpenKeymap));
+            })
+            .log_err();
+    }
+
+    fn toggle_recording(&mut self, cx: &mut ViewContext<Self>) {
+        if self.recorded_keystrokes.take().is_some() {
+            self._keystroke_observer = None;
+        } else {
+            self.recorded_keystrokes = Some(Vec::new());
+            let this = cx.view().downgrade();
+            self._keystroke_observer =
+                Some(cx.observe_keystrokes(move |event: &KeystrokeEvent, cx| {
+                    let (context_stack, matches) =
+                        cx.explain_keystrokes(&[event.keystroke.clone()]);
+                    let recorded = RecordedKeystroke {
+                        keystroke: event.keystroke.clone(),
+                        action_name: event.action.as_ref().map(|action| action.name().into()),
+                        context_stack,
+                        matches,
+                    };
+                    this.update(cx, |this, cx| {
+                        if let Some(recorded_keystrokes) = &mut this.recorded_keystrokes {
+                            if recorded_keystrokes.len() == MAX_RECORDED_KEYSTROKES {
+                                recorded_keystrokes.remove(0);
+                            }
+                            recorded_keystrokes.push(recorded);
+                            cx.notify();
+                        }
+                    })
+                    .ok();
+                }));
+        }
+        cx.notify();
+    }
+
+    fn render_entries(&mut self, range: Range<usize>, cx: &mut ViewContext<Self>) -> Vec<ListItem> {
+        range
+            .filter_map(|ix| {
+                let entry_ix = *self.filtered_entry_indices.get(ix)?;
+                let entry = &self.entries[entry_ix];
+                let shadowed_by = entry
+                    .shadowed_by
+                    .and_then(|winner| self.entries.get(winner))
+                    .map(|winner| {
+                        SharedString::from(format!(
+                            "Shadowed by {} ({})",
+                            winner.action_name,
+                            winner.source.label()
+                        ))
+                    });
+
+                Some(
+                    ListItem::new(ix)
+                        .inset(true)
+                        .selected(ix == self.selected_index)
+                        .on_click(cx.listener(move |this, _, cx| {
+                            this.select_index(ix, cx);
+                            this.start_editing(entry_ix, cx);
+                        }))
+                        .start_slot(
+                            h_flex()
+                                .w(rems(12.))
+                                .child(Label::new(entry.keystrokes.clone()).size(LabelSize::Small)),
+                        )
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .child(Label::new(entry.action_name.clone()))
+                                .when_some(entry.context.clone(), |this, context| {
+                                    this.child(
+                                        Label::new(context)
+                                            .size(LabelSize::Small)
+                                            .color(Color::Muted),
+                                    )
+                                }),
+                        )
+                        .end_slot(
+                            h_flex()
+                                .gap_2()
+                                .when_some(shadowed_by, |this, shadowed_by| {
+                                    this.child(
+                                        div()
+                                            .id(("shadowed-by", ix))
+                                            .child(
+                                                Icon::new(IconName::ExclamationTriangle)
+                                                    .size(IconSize::Small)
+                                                    .color(Color::Warning),
+                                            )
+                                            .tooltip(move |cx| {
+                                                Tooltip::text(shadowed_by.clone(), cx)
+                                            }),
+                                    )
+                                })
+                                .child(
+                                    Label::new(entry.source.label())
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                ),
+                        ),
+                )
+            })
+            .collect()
+    }
+
+    fn render_text_input(&self, editor: &View<Editor>, cx: &ViewContext<Self>) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let text_style = TextStyle {
+            color: cx.theme().colors().text,
+            font_family: settings.buffer_font.family.clone(),
+            font_features: settings.buffer_font.features.clone(),
+            font_fallbacks: settings.buffer_font.fallbacks.clone(),
+            font_size: rems(0.875).into(),
+            font_weight: settings.buffer_font.weight,
+            line_height: relative(1.3),
+            ..Default::default()
+        };
+
+        EditorElement::new(
+            editor,
+            EditorStyle {
+                background: cx.theme().colors().editor_background,
+                local_player: cx.theme().players().local(),
+                text: text_style,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn render_edit_bar(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
+        let Mode::Editing {
+            entry_ix,
+            keystrokes_editor,
+            error,
+        } = &self.mode
+        else {
+            return None;
+        };
+        let entry = self.entries.get(*entry_ix)?;
+
+        Some(
+            v_flex()
+                .gap_1()
+                .p_2()
+                .border_1()
+                .rounded_md()
+                .border_color(if error.is_some() {
+                    Color::Error.color(cx)
+                } else {
+                    cx.theme().colors().border
+                })
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(Label::new(format!("Rebind {}", entry.action_name)))
+                        .child(
+                            div()
+                                .flex_1()
+                                .child(self.render_text_input(keystrokes_editor, cx)),
+                        )
+                        .child(
+                            Button::new("save-keystrokes", "Save").on_click(
+                                cx.listener(|this, _, cx| this.confirm(&menu::Confirm, cx)),
+                            ),
+                        )
+                        .child(
+                            Button::new("cancel-keystrokes", "Cancel").on_click(
+                                cx.listener(|this, _, cx| this.cancel(&menu::Cancel, cx)),
+                            ),
+                        ),
+                )
+                .when_some(error.clone(), |this, error| {
+                    this.child(Label::new(error).size(LabelSize::Small).color(Color::Error))
+                }),
+        )
+    }
+
+    fn render_recorded_keystrokes(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
+        let recorded_keystrokes = self.recorded_keystrokes.as_ref()?;
+        Some(
+            v_flex()
+                .id("recorded-keystrokes")
+                .gap_2()
+                .p_2()
+                .max_h(rems(20.))
+                .overflow_y_scroll()
+                .border_1()
+                .rounded_md()
+                .border_color(cx.theme().colors().border)
+                .when(recorded_keystrokes.is_empty(), |this| {
+                    this.child(
+                        Label::new(
+                            "Focus another view and type to see how keystrokes are dispatched.",
+                        )
+                        .color(Color::Muted),
+                    )
+                })
+                .children(recorded_keystrokes.iter().rev().map(|recorded| {
+                    let context_stack = recorded
+                        .context_stack
+                        .iter()
+                        .map(|context| format!("{context:?}"))
+                        .collect::<Vec<_>>()
+                        .join(" > ");
+                    v_flex()
+                        .gap_0p5()
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .child(Label::new(recorded.keystroke.unparse()))
+                                .child(
+                                    Label::new(
+                                        recorded
+                                            .action_name
+                                            .clone()
+                                            .unwrap_or_else(|| "no action".into()),
+                                    )
+                                    .color(
+                                        if recorded.action_name.is_some() {
+                                            Color::Default
+                                        } else {
+                                            Color::Muted
+                                        },
+                                    ),
+                                ),
+                        )
+                        .child(
+                            Label::new(context_stack)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .children(recorded.matches.iter().map(|(binding, binding_match)| {
+                            let status = match binding_match.status {
+                                KeyBindingMatchStatus::Candidate => "candidate",
+                                KeyBindingMatchStatus::Pending => "pending",
+                                KeyBindingMatchStatus::Disabled => "disabled",
+                                KeyBindingMatchStatus::ContextMismatch => "context mismatch",
+                            };
+                            let predicate = binding
+                                .predicate()
+                                .map(|predicate| predicate.to_string())
+                                .unwrap_or_default();
+                            Label::new(format!(
+                                "  {} ({status}) {predicate}",
+                                binding.action().name()
+                            ))
+                            .size(LabelSize::Small)
+                            .color(match binding_match.status {
+                                KeyBindingMatchStatus::Candidate => Color::Default,
+                                _ => Color::Muted,
+                            })
+                        }))
+                })),
+        )
+    }
+}
+
+/// Lists the bindings in the app's keymap, and marks every binding that is shadowed
+/// by a later binding for the same keystrokes and context.
+///
+/// The user keymap is loaded into the app's keymap last. Its bindings are taken from
+/// the given contents instead, in case the app's keymap hasn't been reloaded since it changed.
+fn load_entries(user_keymap: &str, cx: &AppContext) -> Vec<KeybindingEntry> {
+    let user_bindings = keymap_bindings(user_keymap, cx);
+    let default_bindings = keymap_bindings(&settings::default_keymap(), cx);
+
+    let mut bindings = cx.keymap().borrow().bindings().cloned().collect::<Vec<_>>();
+    let user_bindings_start = bindings
+        .len()
+        .checked_sub(user_bindings.len())
+        .filter(|start| {
+            bindings[*start..]
+                .iter()
+                .zip(&user_bindings)
+                .all(|(loaded, (binding, _))| is_same_binding(loaded, binding))
+        })
+        .unwrap_or(bindings.len());
+    bindings.truncate(user_bindings_start);
+
+    // Actions are looked up in the default keymap, so that the ones with arguments can be rebound.
+    let mut entries = bindings
+        .iter()
+        .map(|binding| {
+            let action = binding.action();
+            let action_value = default_bindings
+                .iter()
+                .find(|(default, _)| default.action().partial_eq(action))
+                .map(|(_, value)| value.clone())
+                .or_else(|| {
+                    cx.build_action(action.name(), None)
+                        .ok()
+                        .filter(|built| built.partial_eq(action))
+                        .map(|_| Value::String(action.name().to_string()))
+                });
+            KeybindingEntry::new(KeybindingSource::Default, binding, action_value)
+        })
+        .collect::<Vec<_>>();
+    for (binding, value) in user_bindings {
+        entries.push(KeybindingEntry::new(
+            KeybindingSource::User,
+            &binding,
+            Some(value),
+        ));
+        bindings.push(binding);
+    }
+
+    let shadowed = Keymap::new(bindings)
+        .conflicts()
+        .into_iter()
+        .flat_map(|conflict| {
+            let (winner, shadowed) = conflict.binding_indices.split_last().unwrap();
+            shadowed.iter().map(|ix| (*ix, *winner)).collect::<Vec<_>>()
+        })
+        .collect::<HashMap<_, _>>();
+    for (ix, entry) in entries.iter_mut().enumerate() {
+        entry.shadowed_by = shadowed.get(&ix).copied();
+    }
+
+    entries
+}
+
+/// Parses the bindings in the given keymap file contents, along with their actions as written in the file.
+fn keymap_bindings(content: &str, cx: &AppContext) -> Vec<(KeyBinding, Value)> {
+    let Some(keymap) = KeymapFile::parse(content).log_err() else {
+        return Vec::new();
+    };
+    let mut bindings = Vec::new();
+    for block in keymap.blocks() {
+        for (keystrokes, action) in block.bindings() {
+            let binding = action
+                .build(cx)
+                .and_then(|built| KeyBinding::load(keystrokes, built, block.context()));
+            if let Ok(binding) = binding {
+                bindings.push((binding, action.value().clone()));
+            }
+        }
+    }
+    bindings
+}
+
+fn is_same_binding(a: &KeyBinding, b: &KeyBinding) -> bool {
+    a.keystrokes() == b.keystrokes()
+        && a.predicate() == b.predicate()
+        && a.action().partial_eq(b.action())
+}
+
+/// Parses whitespace-separated keystrokes, returning them in the keymap's canonical syntax.
+fn normalize_keystrokes(keystrokes: &str) -> anyhow::Result<String> {
+    let keystrokes = keystrokes
+        .split_whitespace()
+        .map(|keystroke| Keystroke::parse(keystroke).map(|keystroke| keystroke.unparse()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if keystrokes.is_empty() {
+        anyhow::bail!("Enter at least one keystroke");
+    }
+    Ok(keystrokes.join(" "))
+}
+
+impl EventEmitter<ItemEvent> for KeybindingEditor {}
+
+impl FocusableView for KeybindingEditor {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        match &self.mode {
+            Mode::Browsing => self.query_editor.read(cx).focus_handle(cx),
+            Mode::Editing {
+                keystrokes_editor, ..
+            } => keystrokes_editor.read(cx).focus_handle(cx),
+        }
+    }
+}
+
+impl Item for KeybindingEditor {
+    type Event = ItemEvent;
+
+    fn tab_icon(&self, _cx: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::Settings))
+    }
+
+    fn tab_content_text(&self, _cx: &WindowContext) -> Option<SharedString> {
+        Some("Keybindings".into())
+    }
+
+    fn show_toolbar(&self) -> bool {
+        false
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: Option<WorkspaceId>,
+        _: &mut ViewContext<Self>,
+    ) -> Option<View<Self>> {
+        None
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+}
+
+impl Render for KeybindingEditor {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        if cx.keymap().borrow().version() != self.keymap_version {
+            self.reload(cx);
+        }
+
+        let mut key_context = KeyContext::new_with_defaults();
+        key_context.add("KeybindingEditor");
+        key_context.add("menu");
+
+        let conflict_count = self
+            .entries
+            .iter()
+            .filter(|entry| entry.shadowed_by.is_some())
+            .count();
+
+        v_flex()
+            .key_context(key_context)
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .size_full()
+            .p_4()
+            .gap_2()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .justify_between()
+                    .child(Headline::new("Keybindings").size(HeadlineSize::Large))
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                ToggleButton::new("record-keystrokes", "Record Keystrokes")
+                                    .style(ButtonStyle::Filled)
+                                    .selected(self.recorded_keystrokes.is_some())
+                                    .on_click(cx.listener(|this, _, cx| this.toggle_recording(cx)))
+                                    .tooltip(|cx| {
+                                        Tooltip::text(
+                                            "Show how keystrokes are dispatched in the focused view",
+                                            cx,
+                                        )
+                                    })
+                                    .first(),
+                            )
+                            .child(
+                                ToggleButton::new(
+                                    "conflicts-only",
+                                    format!("Conflicts ({conflict_count})"),
+                                )
+                                .style(ButtonStyle::Filled)
+                                .selected(self.conflicts_only)
+                                .on_click(cx.listener(|this, _, cx| {
+                                    this.conflicts_only = !this.conflicts_only;
+                                    this.filter_entries(cx);
+                                }))
+                                .tooltip(|cx| {
+                                    Tooltip::text("Only show bindings that are shadowed", cx)
+                                })
+                                .middle(),
+                            )
+                            .child(
+                                ToggleButton::new("open-keymap", "Open keymap.json")
+                                    .style(ButtonStyle::Filled)
+                                    .on_click(cx.listener(|this, _, cx| this.open_keymap_file(cx)))
+                                    .last(),
+                            ),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .gap_2()
+                    .border_1()
+                    .rounded_lg()
+                    .border_color(cx.theme().colors().border)
+                    .child(Icon::new(IconName::MagnifyingGlass))
+                    .child(self.render_text_input(&self.query_editor, cx)),
+            )
+            .children(self.render_edit_bar(cx))
+            .children(self.render_recorded_keystrokes(cx))
+            .child(
+                v_flex().size_full().overflow_y_hidden().child(
+                    uniform_list(
+                        cx.view().clone(),
+                        "keybindings",
+                        self.filtered_entry_indices.len(),
+                        Self::render_entries,
+                    )
+                    .flex_grow()
+                    .track_scroll(self.list.clone()),
+                ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{actions, TestAppContext};
+    use serde_json::json;
+
+    actions!(test, [Save, Format]);
+
+    #[gpui::test]
+    fn test_load_entries(cx: &mut TestAppContext) {
+        let user_keymap = r#"[
+            { "bindings": { "ctrl-s": "test::Format" } },
+            { "context": "Editor", "bindings": { "ctrl-f": null } }
+        ]"#;
+        cx.update(|cx| {
+            // Bindings that aren't in the default keymap, such as those of a base keymap, are analyzed too.
+            cx.bind_keys([
+                KeyBinding::new("ctrl-s", Save, None),
+                KeyBinding::new("ctrl-f", Format, Some("Editor")),
+                KeyBinding::new("ctrl-f", Format, Some("Terminal")),
+            ]);
+            KeymapFile::parse(user_keymap)
+                .unwrap()
+                .add_to_cx(cx)
+                .unwrap();
+
+            let entries = load_entries(user_keymap, cx);
+            let summary = entries
+                .iter()
+                .map(|entry| {
+                    (
+                        entry.source,
+                        entry.keystrokes.to_string(),
+                        entry.action_name.to_string(),
+                        entry.context.as_ref().map(|context| context.to_string()),
+                        entry.shadowed_by,
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                summary,
+                [
+                    (
+                        KeybindingSource::Default,
+                        "ctrl-s".to_string(),
+                        "test::Save".to_string(),
+                        None,
+                        Some(3)
+                    ),
+                    (
+                        KeybindingSource::Default,
+                        "ctrl-f".to_string(),
+                        "test::Format".to_string(),
+                        Some("Editor".to_string()),
+                        Some(4)
+                    ),
+                    (
+                        KeybindingSource::Default,
+                        "ctrl-f".to_string(),
+                        "test::Format".to_string(),
+                        Some("Terminal".to_string()),
+                        None
+                    ),
+                    (
+                        KeybindingSource::User,
+                        "ctrl-s".to_string(),
+                        "test::Format".to_string(),
+                        None,
+                        None
+                    ),
+                    (
+                        KeybindingSource::User,
+                        "ctrl-f".to_string(),
+                        "(disabled)".to_string(),
+                        Some("Editor".to_string()),
+                        None
+                    ),
+                ]
+            );
+            assert_eq!(entries[0].action_value, Some(json!("test::Save")));
+            assert_eq!(entries[4].action_value, Some(Value::Null));
+
+            // User bindings that haven't been loaded into the app's keymap yet are analyzed too.
+            let user_keymap = r#"[
+                { "bindings": { "ctrl-s": "test::Format" } },
+                { "context": "Editor", "bindings": { "ctrl-f": null } },
+                { "context": "Terminal", "bindings": { "ctrl-f": "test::Save" } }
+            ]"#;
+            let entries = load_entries(user_keymap, cx);
+            assert_eq!(entries.len(), 8);
+            assert_eq!(entries[2].shadowed_by, Some(7));
+            assert_eq!(entries[7].source, KeybindingSource::User);
+        });
+    }
+
+    #[test]
+    fn test_normalize_keystrokes() {
+        assert_eq!(
+            normalize_keystrokes("  ctrl-k   shift-super-s ").unwrap(),
+            "ctrl-k cmd-shift-s"
+        );
+        assert_eq!(normalize_keystrokes("alt-ctrl-a").unwrap(), "ctrl-alt-a");
+        assert!(normalize_keystrokes("   ").is_err());
+        assert!(normalize_keystrokes("ctrl-a-b").is_err());
+    }
+}
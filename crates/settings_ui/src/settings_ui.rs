@@ -1,4 +1,5 @@
 mod appearance_settings_controls;
+mod keybinding_editor;
 
 use std::any::TypeId;
 
@@ -12,13 +13,15 @@ use workspace::Workspace;
 
 use crate::appearance_settings_controls::AppearanceSettingsControls;
 
+pub use keybinding_editor::KeybindingEditor;
+
 pub struct SettingsUiFeatureFlag;
 
 impl FeatureFlag for SettingsUiFeatureFlag {
     const NAME: &'static str = "settings-ui";
 }
 
-actions!(zed, [OpenSettingsEditor]);
+actions!(zed, [OpenSettingsEditor, OpenKeybindingEditor]);
 
 pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(|workspace: &mut Workspace, cx| {
@@ -37,7 +40,25 @@ pub fn init(cx: &mut AppContext) {
             }
         });
 
-        let settings_ui_actions = [TypeId::of::<OpenSettingsEditor>()];
+        workspace.register_action(|workspace, _: &OpenKeybindingEditor, cx| {
+            let existing = workspace
+                .active_pane()
+                .read(cx)
+                .items()
+                .find_map(|item| item.downcast::<KeybindingEditor>());
+
+            if let Some(existing) = existing {
+                workspace.activate_item(&existing, true, true, cx);
+            } else {
+                let keybinding_editor = KeybindingEditor::new(workspace, cx);
+                workspace.add_item_to_active_pane(Box::new(keybinding_editor), None, true, cx)
+            }
+        });
+
+        let settings_ui_actions = [
+            TypeId::of::<OpenSettingsEditor>(),
+            TypeId::of::<OpenKeybindingEditor>(),
+        ];
 
         CommandPaletteFilter::update_global(cx, |filter, _cx| {
             filter.hide_action_types(&settings_ui_actions);
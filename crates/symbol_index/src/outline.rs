@@ -0,0 +1,99 @@
+use language::{with_parser, with_query_cursor, Language, Rope};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use util::ResultExt as _;
+
+/// A symbol found by running a language's outline query over a file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct OutlineSymbol {
+    /// The text shown for the symbol in the outline, e.g. `pub fn main`.
+    pub text: String,
+    /// The range of the symbol's name within `text`.
+    pub name_range: Range<usize>,
+    /// The row and UTF-16 column at which the symbol starts.
+    pub start: (u32, u32),
+    /// The row and UTF-16 column at which the symbol ends.
+    pub end: (u32, u32),
+}
+
+impl OutlineSymbol {
+    pub fn name(&self) -> &str {
+        &self.text[self.name_range.clone()]
+    }
+}
+
+/// Returns the symbols in the given text, using the language's outline query.
+pub(crate) fn outline_symbols(text: &str, language: &Language) -> Vec<OutlineSymbol> {
+    let Some(grammar) = language.grammar() else {
+        return Vec::new();
+    };
+    let Some(config) = grammar.outline_config.as_ref() else {
+        return Vec::new();
+    };
+    let tree = with_parser(|parser| {
+        parser.set_language(&grammar.ts_language).log_err()?;
+        parser.parse(text, None)
+    });
+    let Some(tree) = tree else {
+        return Vec::new();
+    };
+
+    let rope = Rope::from(text);
+    let mut symbols = with_query_cursor(|cursor| {
+        cursor
+            .matches(&config.query, tree.root_node(), text.as_bytes())
+            .filter_map(|mat| {
+                let item_node = mat
+                    .captures
+                    .iter()
+                    .find(|capture| capture.index == config.item_capture_ix)?
+                    .node;
+
+                let mut text_ranges = Vec::new();
+                for capture in mat.captures {
+                    let is_name = capture.index == config.name_capture_ix;
+                    if !is_name && Some(capture.index) != config.context_capture_ix {
+                        continue;
+                    }
+
+                    // Like the buffer outline, only use the first line of multi-line captures.
+                    let mut range = capture.node.byte_range();
+                    if let Some(newline_ix) = text[range.clone()].find('\n') {
+                        range.end = range.start + newline_ix;
+                    }
+                    if !range.is_empty() {
+                        text_ranges.push((range, is_name));
+                    }
+                }
+
+                let mut symbol_text = String::new();
+                let mut name_range: Option<Range<usize>> = None;
+                let mut last_range_end = 0;
+                for (range, is_name) in text_ranges {
+                    if !symbol_text.is_empty() && range.start > last_range_end {
+                        symbol_text.push(' ');
+                    }
+                    last_range_end = range.end;
+
+                    let start = symbol_text.len();
+                    symbol_text.push_str(&text[range]);
+                    if is_name {
+                        name_range.get_or_insert(start..start).end = symbol_text.len();
+                    }
+                }
+
+                let start = rope.offset_to_point_utf16(item_node.start_byte());
+                let end = rope.offset_to_point_utf16(item_node.end_byte());
+                Some(OutlineSymbol {
+                    text: symbol_text,
+                    name_range: name_range?,
+                    start: (start.row, start.column),
+                    end: (end.row, end.column),
+                })
+            })
+            .collect::<Vec<_>>()
+    });
+
+    symbols.sort_by_key(|symbol| (symbol.start, std::cmp::Reverse(symbol.end)));
+    symbols
+}
@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+
+use db::sqlez_macros::sql;
+use db::{define_connection, query};
+
+define_connection!(
+    // Current schema shape using pseudo-rust syntax:
+    // symbol_index_files(
+    //   worktree_path: PathBuf,
+    //   path: PathBuf,
+    //   mtime_seconds: Option<i64>,
+    //   mtime_nanos: Option<i32>,
+    //   symbols: String, // JSON-encoded `Vec<OutlineSymbol>`
+    // )
+    pub static ref DB: SymbolIndexDb<()> =
+        &[sql!(
+            CREATE TABLE symbol_index_files(
+                worktree_path BLOB NOT NULL,
+                path BLOB NOT NULL,
+                mtime_seconds INTEGER,
+                mtime_nanos INTEGER,
+                symbols TEXT NOT NULL,
+                PRIMARY KEY(worktree_path, path)
+            ) STRICT;
+        )];
+);
+
+impl SymbolIndexDb {
+    query! {
+        pub fn indexed_files(worktree_path: PathBuf) -> Result<Vec<(PathBuf, Option<i64>, Option<i32>, String)>> {
+            SELECT path, mtime_seconds, mtime_nanos, symbols
+            FROM symbol_index_files
+            WHERE worktree_path = ?
+        }
+    }
+
+    query! {
+        pub async fn save_indexed_file(
+            worktree_path: PathBuf,
+            path: PathBuf,
+            mtime_seconds: Option<i64>,
+            mtime_nanos: Option<i32>,
+            symbols: String
+        ) -> Result<()> {
+            INSERT OR REPLACE INTO symbol_index_files
+                (worktree_path, path, mtime_seconds, mtime_nanos, symbols)
+            VALUES
+                (?1, ?2, ?3, ?4, ?5)
+        }
+    }
+
+    query! {
+        pub async fn delete_indexed_file(worktree_path: PathBuf, path: PathBuf) -> Result<()> {
+            DELETE FROM symbol_index_files
+            WHERE worktree_path = ?1 AND path = ?2
+        }
+    }
+}
@@ -0,0 +1,617 @@
+mod outline;
+mod persistence;
+
+use anyhow::Result;
+use collections::HashMap;
+use fs::Fs;
+use fuzzy::StringMatchCandidate;
+use gpui::{
+    AppContext, AsyncAppContext, Context, Global, Model, ModelContext, Subscription, Task,
+    WeakModel,
+};
+use language::{CodeLabel, LanguageRegistry, PointUtf16};
+use outline::{outline_symbols, OutlineSymbol};
+use persistence::DB;
+use project::{Entry, Project, ProjectPath, UpdatedEntriesSet, Worktree, WorktreeId};
+use smol::channel;
+use std::{
+    ops::Range,
+    path::Path,
+    sync::Arc,
+    time::{Duration, UNIX_EPOCH},
+};
+use util::ResultExt;
+
+/// Files larger than this are not indexed, as they are unlikely to be source code.
+const MAX_INDEXED_FILE_SIZE: usize = 1024 * 1024;
+
+pub fn init(cx: &mut AppContext) {
+    cx.set_global(SymbolIndex::default());
+}
+
+/// Maintains a [`ProjectSymbolIndex`] for each project that requested one.
+#[derive(Default)]
+pub struct SymbolIndex {
+    project_indices: HashMap<WeakModel<Project>, Model<ProjectSymbolIndex>>,
+}
+
+impl Global for SymbolIndex {}
+
+impl SymbolIndex {
+    /// Returns the symbol index for the given project, if the symbol index is enabled.
+    pub fn for_project(
+        project: &Model<Project>,
+        cx: &mut AppContext,
+    ) -> Option<Model<ProjectSymbolIndex>> {
+        if !cx.has_global::<Self>() {
+            return None;
+        }
+
+        let project = project.clone();
+        Some(cx.update_global::<Self, _>(|this, cx| this.project_index(project, cx)))
+    }
+
+    pub fn project_index(
+        &mut self,
+        project: Model<Project>,
+        cx: &mut AppContext,
+    ) -> Model<ProjectSymbolIndex> {
+        let project_weak = project.downgrade();
+        self.project_indices
+            .entry(project_weak.clone())
+            .or_insert_with(|| {
+                project.update(cx, move |_, cx| {
+                    cx.on_release(move |_, cx| {
+                        if cx.has_global::<SymbolIndex>() {
+                            cx.update_global::<SymbolIndex, _>(|this, _| {
+                                this.project_indices.remove(&project_weak);
+                            })
+                        }
+                    })
+                    .detach();
+                });
+                cx.new_model(|cx| ProjectSymbolIndex::new(project, cx))
+            })
+            .clone()
+    }
+}
+
+/// A symbol found in a project's files by the symbol index.
+#[derive(Clone, Debug)]
+pub struct IndexedSymbol {
+    pub path: ProjectPath,
+    pub name: String,
+    pub label: CodeLabel,
+    pub range: Range<PointUtf16>,
+}
+
+/// An index of the symbols in a project's local worktrees, built from each
+/// language's outline query, so that symbols can be found without a language server.
+pub struct ProjectSymbolIndex {
+    project: WeakModel<Project>,
+    language_registry: Arc<LanguageRegistry>,
+    fs: Arc<dyn Fs>,
+    worktree_indices: HashMap<WorktreeId, Model<WorktreeSymbolIndex>>,
+    _subscription: Subscription,
+}
+
+impl ProjectSymbolIndex {
+    fn new(project: Model<Project>, cx: &mut ModelContext<Self>) -> Self {
+        let mut this = Self {
+            project: project.downgrade(),
+            language_registry: project.read(cx).languages().clone(),
+            fs: project.read(cx).fs().clone(),
+            worktree_indices: HashMap::default(),
+            _subscription: cx.subscribe(&project, Self::handle_project_event),
+        };
+        this.update_worktree_indices(cx);
+        this
+    }
+
+    fn handle_project_event(
+        &mut self,
+        _: Model<Project>,
+        event: &project::Event,
+        cx: &mut ModelContext<Self>,
+    ) {
+        match event {
+            project::Event::WorktreeAdded | project::Event::WorktreeRemoved(_) => {
+                self.update_worktree_indices(cx);
+            }
+            _ => {}
+        }
+    }
+
+    fn update_worktree_indices(&mut self, cx: &mut ModelContext<Self>) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+
+        let worktrees = project
+            .read(cx)
+            .visible_worktrees(cx)
+            .filter(|worktree| worktree.read(cx).is_local())
+            .map(|worktree| (worktree.read(cx).id(), worktree))
+            .collect::<HashMap<_, _>>();
+
+        self.worktree_indices
+            .retain(|worktree_id, _| worktrees.contains_key(worktree_id));
+        for (worktree_id, worktree) in worktrees {
+            self.worktree_indices.entry(worktree_id).or_insert_with(|| {
+                let language_registry = self.language_registry.clone();
+                let fs = self.fs.clone();
+                cx.new_model(|cx| WorktreeSymbolIndex::new(worktree, language_registry, fs, cx))
+            });
+        }
+    }
+
+    /// Fuzzy-matches the query against the names of all indexed symbols,
+    /// returning at most `limit` symbols, best matches first.
+    pub fn search(&self, query: String, limit: usize, cx: &AppContext) -> Task<Vec<IndexedSymbol>> {
+        let files = self
+            .worktree_indices
+            .iter()
+            .flat_map(|(worktree_id, index)| {
+                index
+                    .read(cx)
+                    .files
+                    .iter()
+                    .map(|(path, symbols)| (*worktree_id, path.clone(), symbols.clone()))
+            })
+            .collect::<Vec<_>>();
+
+        let executor = cx.background_executor().clone();
+        cx.background_executor().spawn(async move {
+            let mut candidates = Vec::new();
+            let mut symbols = Vec::new();
+            for (worktree_id, path, file_symbols) in &files {
+                for symbol in file_symbols.iter() {
+                    candidates.push(StringMatchCandidate::new(
+                        symbols.len(),
+                        symbol.name().to_string(),
+                    ));
+                    symbols.push((*worktree_id, path, symbol));
+                }
+            }
+
+            let matches = fuzzy::match_strings(
+                &candidates,
+                &query,
+                false,
+                limit,
+                &Default::default(),
+                executor,
+            )
+            .await;
+
+            matches
+                .into_iter()
+                .map(|mat| {
+                    let (worktree_id, path, symbol) = symbols[mat.candidate_id];
+                    IndexedSymbol {
+                        path: ProjectPath {
+                            worktree_id,
+                            path: path.clone(),
+                        },
+                        name: symbol.name().to_string(),
+                        label: CodeLabel {
+                            text: symbol.text.clone(),
+                            runs: Vec::new(),
+                            filter_range: symbol.name_range.clone(),
+                        },
+                        range: PointUtf16::new(symbol.start.0, symbol.start.1)
+                            ..PointUtf16::new(symbol.end.0, symbol.end.1),
+                    }
+                })
+                .collect()
+        })
+    }
+
+    /// Returns the number of files whose symbols are currently indexed.
+    pub fn file_count(&self, cx: &AppContext) -> usize {
+        self.worktree_indices
+            .values()
+            .map(|index| index.read(cx).files.len())
+            .sum()
+    }
+}
+
+/// The symbols of a single worktree, kept up to date as its files change and
+/// persisted so that they don't need to be recomputed when the project is reopened.
+struct WorktreeSymbolIndex {
+    worktree: Model<Worktree>,
+    language_registry: Arc<LanguageRegistry>,
+    fs: Arc<dyn Fs>,
+    files: HashMap<Arc<Path>, Arc<[OutlineSymbol]>>,
+    _index_entries: Task<Result<()>>,
+    _subscription: Subscription,
+}
+
+impl WorktreeSymbolIndex {
+    fn new(
+        worktree: Model<Worktree>,
+        language_registry: Arc<LanguageRegistry>,
+        fs: Arc<dyn Fs>,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        let (updated_entries_tx, updated_entries_rx) = channel::unbounded();
+        let _subscription = cx.subscribe(&worktree, move |_this, _worktree, event, _cx| {
+            if let worktree::Event::UpdatedEntries(update) = event {
+                _ = updated_entries_tx.try_send(update.clone());
+            }
+        });
+
+        Self {
+            worktree,
+            language_registry,
+            fs,
+            files: HashMap::default(),
+            _index_entries: cx.spawn(|this, cx| Self::index_entries(this, updated_entries_rx, cx)),
+            _subscription,
+        }
+    }
+
+    async fn index_entries(
+        this: WeakModel<Self>,
+        updated_entries: channel::Receiver<UpdatedEntriesSet>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        let scan_complete = this.update(&mut cx, |this, cx| {
+            this.worktree
+                .read(cx)
+                .as_local()
+                .map(|worktree| worktree.scan_complete())
+        })?;
+        if let Some(scan_complete) = scan_complete {
+            scan_complete.await;
+        }
+
+        let stale_entries = this
+            .update(&mut cx, |this, cx| this.load_persisted_files(cx))?
+            .await;
+        Self::index_files(&this, stale_entries, &mut cx).await?;
+
+        while let Ok(updated_entries) = updated_entries.recv().await {
+            let updated_entries = this.update(&mut cx, |this, cx| {
+                this.process_updated_entries(updated_entries, cx)
+            })?;
+            Self::index_files(&this, updated_entries, &mut cx).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads the symbols persisted for files that haven't changed since they were indexed,
+    /// and returns the entries that need to be indexed again.
+    fn load_persisted_files(&mut self, cx: &mut ModelContext<Self>) -> Task<Vec<Entry>> {
+        let snapshot = self.worktree.read(cx).snapshot();
+        let worktree_abs_path = snapshot.abs_path().to_path_buf();
+        cx.spawn(|this, mut cx| async move {
+            let persisted_files = cx
+                .background_executor()
+                .spawn({
+                    let worktree_abs_path = worktree_abs_path.clone();
+                    async move { DB.indexed_files(worktree_abs_path) }
+                })
+                .await
+                .log_err()
+                .unwrap_or_default();
+            let mut persisted_files = persisted_files
+                .into_iter()
+                .map(|(path, mtime_seconds, mtime_nanos, symbols)| {
+                    let mtime = mtime_seconds.zip(mtime_nanos).map(|(seconds, nanos)| {
+                        UNIX_EPOCH + Duration::new(seconds as u64, nanos as u32)
+                    });
+                    (path, (mtime, symbols))
+                })
+                .collect::<HashMap<_, _>>();
+
+            let mut files = HashMap::<Arc<Path>, Arc<[OutlineSymbol]>>::default();
+            let mut stale_entries = Vec::new();
+            for entry in snapshot.files(false, 0) {
+                let persisted_symbols = persisted_files
+                    .remove(entry.path.as_ref())
+                    .filter(|(mtime, _)| *mtime == entry.mtime)
+                    .and_then(|(_, symbols)| {
+                        serde_json::from_str::<Vec<OutlineSymbol>>(&symbols).log_err()
+                    });
+                match persisted_symbols {
+                    Some(symbols) => {
+                        files.insert(entry.path.clone(), symbols.into());
+                    }
+                    None => stale_entries.push(entry.clone()),
+                }
+            }
+
+            // Forget about files that were deleted while the project was closed.
+            for path in persisted_files.into_keys() {
+                DB.delete_indexed_file(worktree_abs_path.clone(), path)
+                    .await
+                    .log_err();
+            }
+
+            this.update(&mut cx, |this, _| this.files.extend(files))
+                .log_err();
+            stale_entries
+        })
+    }
+
+    /// Forgets the symbols of removed files, and returns the entries that need to be indexed.
+    fn process_updated_entries(
+        &mut self,
+        updated_entries: UpdatedEntriesSet,
+        cx: &mut ModelContext<Self>,
+    ) -> Vec<Entry> {
+        let worktree = self.worktree.read(cx);
+        let worktree_abs_path = worktree.abs_path().to_path_buf();
+        let mut entries = Vec::new();
+        for (path, entry_id, change) in updated_entries.iter() {
+            match change {
+                project::PathChange::Added
+                | project::PathChange::Updated
+                | project::PathChange::AddedOrUpdated => {
+                    if let Some(entry) = worktree.entry_for_id(*entry_id) {
+                        if entry.is_file() && !entry.is_ignored {
+                            entries.push(entry.clone());
+                        }
+                    }
+                }
+                project::PathChange::Removed => {
+                    if self.files.remove(path).is_some() {
+                        let path = path.to_path_buf();
+                        let worktree_abs_path = worktree_abs_path.clone();
+                        cx.background_executor()
+                            .spawn(async move {
+                                DB.delete_indexed_file(worktree_abs_path, path)
+                                    .await
+                                    .log_err();
+                            })
+                            .detach();
+                    }
+                }
+                project::PathChange::Loaded => {
+                    // Newly loaded entries are picked up when the initial scan completes.
+                }
+            }
+        }
+        entries
+    }
+
+    async fn index_files(
+        this: &WeakModel<Self>,
+        entries: Vec<Entry>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<()> {
+        let (worktree_abs_path, language_registry, fs) = this.update(cx, |this, cx| {
+            (
+                this.worktree.read(cx).abs_path(),
+                this.language_registry.clone(),
+                this.fs.clone(),
+            )
+        })?;
+
+        for entry in entries {
+            let Some(symbols) = Self::file_symbols(
+                &entry,
+                &worktree_abs_path,
+                &language_registry,
+                fs.as_ref(),
+                cx,
+            )
+            .await
+            else {
+                // Files that can no longer be indexed must not keep their previous symbols.
+                let was_indexed =
+                    this.update(cx, |this, _| this.files.remove(&entry.path).is_some())?;
+                if was_indexed {
+                    DB.delete_indexed_file(
+                        worktree_abs_path.to_path_buf(),
+                        entry.path.to_path_buf(),
+                    )
+                    .await
+                    .log_err();
+                }
+                continue;
+            };
+
+            let (mtime_seconds, mtime_nanos) = entry
+                .mtime
+                .and_then(|mtime| mtime.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| (duration.as_secs() as i64, duration.subsec_nanos() as i32))
+                .unzip();
+            DB.save_indexed_file(
+                worktree_abs_path.to_path_buf(),
+                entry.path.to_path_buf(),
+                mtime_seconds,
+                mtime_nanos,
+                serde_json::to_string(&symbols)?,
+            )
+            .await
+            .log_err();
+
+            this.update(cx, |this, _| {
+                this.files.insert(entry.path.clone(), symbols.into());
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Extracts the symbols of the given file, or returns `None` if it can't be indexed.
+    async fn file_symbols(
+        entry: &Entry,
+        worktree_abs_path: &Path,
+        language_registry: &LanguageRegistry,
+        fs: &dyn Fs,
+        cx: &AsyncAppContext,
+    ) -> Option<Vec<OutlineSymbol>> {
+        let language = language_registry
+            .language_for_file_path(&entry.path)
+            .await
+            .ok()?;
+        language.grammar()?.outline_config.as_ref()?;
+
+        let text = fs
+            .load(&worktree_abs_path.join(&entry.path))
+            .await
+            .log_err()?;
+        if text.len() > MAX_INDEXED_FILE_SIZE {
+            return None;
+        }
+
+        Some(
+            cx.background_executor()
+                .spawn(async move { outline_symbols(&text, &language) })
+                .await,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use language::{Language, LanguageConfig, LanguageMatcher};
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_symbol_index(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "struct Alpha;\n\nfn beta() {}\n",
+                "two.rs": "mod gamma {\n    fn delta() {}\n}\n",
+                "three.txt": "fn epsilon() {}\n",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        project.update(cx, |project, _| project.languages().add(rust_lang()));
+        let index = cx.update(|cx| SymbolIndex::for_project(&project, cx).unwrap());
+        cx.run_until_parked();
+
+        index.read_with(cx, |index, cx| assert_eq!(index.file_count(cx), 2));
+
+        let symbols = search(&index, "delta", cx).await;
+        assert_eq!(
+            symbols,
+            vec![(
+                "two.rs".to_string(),
+                "fn delta".to_string(),
+                PointUtf16::new(1, 4)..PointUtf16::new(1, 17)
+            )]
+        );
+        assert!(search(&index, "epsilon", cx).await.is_empty());
+
+        let persisted_paths = DB
+            .indexed_files("/dir".into())
+            .unwrap()
+            .into_iter()
+            .map(|(path, ..)| path.to_string_lossy().into_owned())
+            .collect::<collections::HashSet<_>>();
+        assert_eq!(
+            persisted_paths,
+            ["one.rs".to_string(), "two.rs".to_string()]
+                .into_iter()
+                .collect()
+        );
+
+        // Changes to files are reflected in the index.
+        fs.insert_file("/dir/one.rs", "fn zeta() {}\n".as_bytes().to_vec())
+            .await;
+        fs.remove_file("/dir/two.rs".as_ref(), Default::default())
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        assert!(search(&index, "beta", cx).await.is_empty());
+        assert!(search(&index, "delta", cx).await.is_empty());
+        assert_eq!(
+            search(&index, "zeta", cx).await,
+            vec![(
+                "one.rs".to_string(),
+                "fn zeta".to_string(),
+                PointUtf16::new(0, 0)..PointUtf16::new(0, 12)
+            )]
+        );
+        assert_eq!(DB.indexed_files("/dir".into()).unwrap().len(), 1);
+
+        // Files that can no longer be indexed lose their symbols.
+        fs.insert_file(
+            "/dir/one.rs",
+            format!("fn eta() {{}}\n{}", " ".repeat(MAX_INDEXED_FILE_SIZE)).into_bytes(),
+        )
+        .await;
+        cx.run_until_parked();
+
+        assert!(search(&index, "zeta", cx).await.is_empty());
+        assert!(search(&index, "eta", cx).await.is_empty());
+        index.read_with(cx, |index, cx| assert_eq!(index.file_count(cx), 0));
+        assert!(DB.indexed_files("/dir".into()).unwrap().is_empty());
+    }
+
+    async fn search(
+        index: &Model<ProjectSymbolIndex>,
+        query: &str,
+        cx: &mut TestAppContext,
+    ) -> Vec<(String, String, Range<PointUtf16>)> {
+        index
+            .read_with(cx, |index, cx| index.search(query.to_string(), 10, cx))
+            .await
+            .into_iter()
+            .map(|symbol| {
+                (
+                    symbol.path.path.to_string_lossy().into_owned(),
+                    symbol.label.text,
+                    symbol.range,
+                )
+            })
+            .collect()
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            language::init(cx);
+            Project::init_settings(cx);
+            init(cx);
+        });
+    }
+
+    fn rust_lang() -> Arc<Language> {
+        Arc::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: vec!["rs".to_string()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Some(language::tree_sitter_rust::language()),
+            )
+            .with_outline_query(
+                r#"
+                (struct_item
+                    "struct" @context
+                    name: (_) @name) @item
+                (mod_item
+                    "mod" @context
+                    name: (_) @name) @item
+                (function_item
+                    "fn" @context
+                    name: (_) @name) @item
+                "#,
+            )
+            .unwrap(),
+        )
+    }
+}
@@ -0,0 +1,133 @@
+use anyhow::Result;
+use gpui::Hsla;
+use palette::FromColor;
+
+#[allow(unused)]
+pub(crate) fn try_parse_color(color: &str) -> Result<Hsla> {
+    let rgba = gpui::Rgba::try_from(color)?;
+    let rgba = palette::rgb::Srgba::from_components((rgba.r, rgba.g, rgba.b, rgba.a));
+    let hsla = palette::Hsla::from_color(rgba);
+
+    let hsla = gpui::hsla(
+        hsla.hue.into_positive_degrees() / 360.,
+        hsla.saturation,
+        hsla.lightness,
+        hsla.alpha,
+    );
+
+    Ok(hsla)
+}
+
+#[allow(unused)]
+pub(crate) fn pack_color(color: Hsla) -> u32 {
+    let hsla = palette::Hsla::from_components((color.h * 360., color.s, color.l, color.a));
+    let rgba = palette::rgb::Srgba::from_color(hsla);
+    let rgba = rgba.into_format::<u8, u8>();
+
+    u32::from(rgba)
+}
+
+/// Normalizes a color from a TextMate-style theme into the `#rrggbbaa` form used by Zed themes.
+///
+/// Besides hex colors, this accepts `rgb()`/`rgba()` and the `color(<color> alpha(<value>))`
+/// adjuster used by Sublime Text color schemes.
+pub(crate) fn normalize_color(color: &str) -> Option<String> {
+    let color = color.trim();
+
+    let rgba = if let Some(args) = color
+        .strip_prefix("color(")
+        .and_then(|args| args.strip_suffix(')'))
+    {
+        let (base, adjusters) = args.trim().split_once(' ').unwrap_or((args, ""));
+        let mut rgba = gpui::Rgba::try_from(normalize_color(base)?.as_str()).ok()?;
+        for adjuster in adjusters.split_whitespace() {
+            let alpha = adjuster
+                .strip_prefix("alpha(")
+                .or_else(|| adjuster.strip_prefix("a("))
+                .and_then(|alpha| alpha.strip_suffix(')'))
+                .and_then(|alpha| alpha.parse::<f32>().ok());
+            if let Some(alpha) = alpha {
+                rgba.a *= alpha.clamp(0., 1.);
+            }
+        }
+        rgba
+    } else if let Some(args) = color
+        .strip_prefix("rgba(")
+        .or_else(|| color.strip_prefix("rgb("))
+        .and_then(|args| args.strip_suffix(')'))
+    {
+        let components = args
+            .split(',')
+            .map(|component| component.trim().parse::<f32>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        match components.as_slice() {
+            [r, g, b] => gpui::Rgba {
+                r: r / 255.,
+                g: g / 255.,
+                b: b / 255.,
+                a: 1.,
+            },
+            [r, g, b, a] => gpui::Rgba {
+                r: r / 255.,
+                g: g / 255.,
+                b: b / 255.,
+                a: *a,
+            },
+            _ => return None,
+        }
+    } else {
+        gpui::Rgba::try_from(color).ok()?
+    };
+
+    let [r, g, b, a] = [rgba.r, rgba.g, rgba.b, rgba.a]
+        .map(|component| (component.clamp(0., 1.) * 255.).round() as u8);
+    Some(format!("#{r:02x}{g:02x}{b:02x}{a:02x}"))
+}
+
+/// Returns whether the given color is dark, e.g. to pick the appearance of a theme from its background.
+pub(crate) fn is_dark_color(color: &str) -> Option<bool> {
+    try_parse_color(color).ok().map(|color| color.l < 0.5)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_serialize_color() {
+        let color = "#b4637aff";
+        let hsla = try_parse_color(color).unwrap();
+        let packed = pack_color(hsla);
+
+        assert_eq!(format!("#{:x}", packed), color);
+    }
+
+    #[test]
+    pub fn test_serialize_color_with_palette() {
+        let color = "#b4637aff";
+
+        let rgba = gpui::Rgba::try_from(color).unwrap();
+        let rgba = palette::rgb::Srgba::from_components((rgba.r, rgba.g, rgba.b, rgba.a));
+        let hsla = palette::Hsla::from_color(rgba);
+
+        let rgba = palette::rgb::Srgba::from_color(hsla);
+        let rgba = rgba.into_format::<u8, u8>();
+
+        assert_eq!(format!("#{:x}", rgba), color);
+    }
+
+    #[test]
+    pub fn test_normalize_color() {
+        assert_eq!(normalize_color("#B4637A").as_deref(), Some("#b4637aff"));
+        assert_eq!(normalize_color("#b4637a80").as_deref(), Some("#b4637a80"));
+        assert_eq!(
+            normalize_color("rgb(180, 99, 122)").as_deref(),
+            Some("#b4637aff")
+        );
+        assert_eq!(
+            normalize_color("color(#b4637a alpha(0.5))").as_deref(),
+            Some("#b4637a80")
+        );
+        assert_eq!(normalize_color("var(missing)"), None);
+    }
+}
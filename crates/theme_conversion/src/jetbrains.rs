@@ -0,0 +1,466 @@
+use std::collections::HashSet;
+
+use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
+use strum::IntoEnumIterator;
+use theme::{
+    AppearanceContent, FontStyleContent, FontWeightContent, HighlightStyleContent,
+    PlayerColorContent, StatusColorsContent, ThemeColorsContent, ThemeContent, ThemeStyleContent,
+};
+
+use crate::color::is_dark_color;
+use crate::vscode::ZedSyntaxToken;
+
+/// A JetBrains IDE color scheme, as exported to `.icls` files.
+#[derive(Debug, Default)]
+pub struct JetBrainsScheme {
+    pub name: Option<String>,
+    /// The bundled scheme this one is derived from (e.g. `Darcula`).
+    pub parent_scheme: Option<String>,
+    /// Editor colors keyed by name (e.g. `CARET_ROW_COLOR`), as `#rrggbbaa` colors.
+    pub colors: IndexMap<String, String>,
+    /// Text attributes keyed by name (e.g. `DEFAULT_KEYWORD`).
+    pub attributes: IndexMap<String, JetBrainsAttributes>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct JetBrainsAttributes {
+    pub foreground: Option<String>,
+    pub background: Option<String>,
+    pub effect_color: Option<String>,
+    /// `1` for bold, `2` for italic and `3` for both.
+    pub font_type: Option<u32>,
+    /// The attributes to inherit from for anything not set here.
+    pub base_attributes: Option<String>,
+}
+
+impl JetBrainsAttributes {
+    fn inherit_from(&mut self, base: &JetBrainsAttributes) {
+        self.foreground = self.foreground.take().or(base.foreground.clone());
+        self.background = self.background.take().or(base.background.clone());
+        self.effect_color = self.effect_color.take().or(base.effect_color.clone());
+        self.font_type = self.font_type.or(base.font_type);
+    }
+}
+
+impl JetBrainsScheme {
+    pub fn parse(content: &str) -> Result<Self> {
+        let document = roxmltree::Document::parse(content)
+            .context("failed to parse JetBrains color scheme")?;
+        let root = document.root_element();
+        if !root.has_tag_name("scheme") {
+            bail!(
+                "expected a <scheme> element, found <{}>",
+                root.tag_name().name()
+            );
+        }
+
+        let mut scheme = Self {
+            name: root.attribute("name").map(Into::into),
+            parent_scheme: root.attribute("parent_scheme").map(Into::into),
+            ..Default::default()
+        };
+
+        for section in root.children().filter(|node| node.is_element()) {
+            match section.tag_name().name() {
+                "colors" => {
+                    for option in options(section) {
+                        let (Some(name), Some(color)) = (
+                            option.attribute("name"),
+                            option.attribute("value").and_then(parse_color),
+                        ) else {
+                            continue;
+                        };
+                        scheme.colors.insert(name.into(), color);
+                    }
+                }
+                "attributes" => {
+                    for option in options(section) {
+                        let Some(name) = option.attribute("name") else {
+                            continue;
+                        };
+
+                        let mut attributes = JetBrainsAttributes {
+                            base_attributes: option.attribute("baseAttributes").map(Into::into),
+                            ..Default::default()
+                        };
+                        if let Some(value) =
+                            option.children().find(|node| node.has_tag_name("value"))
+                        {
+                            for option in options(value) {
+                                let Some(value) = option.attribute("value") else {
+                                    continue;
+                                };
+                                match option.attribute("name") {
+                                    Some("FOREGROUND") => {
+                                        attributes.foreground = parse_color(value)
+                                    }
+                                    Some("BACKGROUND") => {
+                                        attributes.background = parse_color(value)
+                                    }
+                                    Some("EFFECT_COLOR") => {
+                                        attributes.effect_color = parse_color(value)
+                                    }
+                                    Some("FONT_TYPE") => attributes.font_type = value.parse().ok(),
+                                    _ => {}
+                                }
+                            }
+                        }
+                        scheme.attributes.insert(name.into(), attributes);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(scheme)
+    }
+
+    /// Returns the attributes with the given name, with anything they don't set
+    /// inherited through `baseAttributes`.
+    pub fn resolved_attributes(&self, name: &str) -> Option<JetBrainsAttributes> {
+        let mut attributes = self.attributes.get(name)?.clone();
+        let mut base_name = attributes.base_attributes.clone();
+        let mut visited = HashSet::from([name.to_string()]);
+
+        while let Some(name) = base_name.take() {
+            if !visited.insert(name.clone()) {
+                break;
+            }
+            let Some(base) = self.attributes.get(&name) else {
+                break;
+            };
+            attributes.inherit_from(base);
+            base_name = base.base_attributes.clone();
+        }
+
+        Some(attributes)
+    }
+}
+
+fn options<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+) -> impl Iterator<Item = roxmltree::Node<'a, 'input>> {
+    node.children().filter(|child| child.has_tag_name("option"))
+}
+
+/// Parses a JetBrains color, which is written as hex RGB without a leading `#`
+/// and with leading zeros omitted.
+fn parse_color(value: &str) -> Option<String> {
+    if value.is_empty() || value.len() > 6 || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    Some(format!("#{:0>6}ff", value.to_lowercase()))
+}
+
+/// Returns the JetBrains attribute keys to take the style of the given token from, in order of preference.
+fn attribute_keys(syntax_token: ZedSyntaxToken) -> &'static [&'static str] {
+    match syntax_token {
+        ZedSyntaxToken::Attribute => &["DEFAULT_ATTRIBUTE", "HTML_ATTRIBUTE_NAME"],
+        ZedSyntaxToken::Boolean => &["DEFAULT_KEYWORD"],
+        ZedSyntaxToken::Comment => &["DEFAULT_LINE_COMMENT", "DEFAULT_BLOCK_COMMENT"],
+        ZedSyntaxToken::CommentDoc => &["DEFAULT_DOC_COMMENT"],
+        ZedSyntaxToken::Constant => &["DEFAULT_CONSTANT", "DEFAULT_STATIC_FIELD"],
+        ZedSyntaxToken::Constructor => &["DEFAULT_CLASS_NAME"],
+        ZedSyntaxToken::Embedded => &["DEFAULT_TEMPLATE_LANGUAGE_COLOR"],
+        ZedSyntaxToken::Emphasis => &["MARKDOWN_ITALIC"],
+        ZedSyntaxToken::EmphasisStrong => &["MARKDOWN_BOLD"],
+        ZedSyntaxToken::Enum => &["DEFAULT_CLASS_NAME"],
+        ZedSyntaxToken::Function => &[
+            "DEFAULT_FUNCTION_DECLARATION",
+            "DEFAULT_FUNCTION_CALL",
+            "DEFAULT_INSTANCE_METHOD",
+            "DEFAULT_STATIC_METHOD",
+        ],
+        ZedSyntaxToken::Hint => &["INLAY_DEFAULT"],
+        ZedSyntaxToken::Keyword => &["DEFAULT_KEYWORD"],
+        ZedSyntaxToken::Label => &["DEFAULT_LABEL"],
+        ZedSyntaxToken::LinkText => &["MARKDOWN_LINK_TEXT"],
+        ZedSyntaxToken::LinkUri => &["MARKDOWN_LINK_DESTINATION", "HYPERLINK_ATTRIBUTES"],
+        ZedSyntaxToken::Number => &["DEFAULT_NUMBER"],
+        ZedSyntaxToken::Operator => &["DEFAULT_OPERATION_SIGN"],
+        ZedSyntaxToken::Preproc => &["DEFAULT_METADATA"],
+        ZedSyntaxToken::Property => &["DEFAULT_INSTANCE_FIELD", "DEFAULT_STATIC_FIELD"],
+        ZedSyntaxToken::Punctuation => &["DEFAULT_DOT", "DEFAULT_COMMA", "DEFAULT_SEMICOLON"],
+        ZedSyntaxToken::PunctuationBracket => {
+            &["DEFAULT_BRACES", "DEFAULT_BRACKETS", "DEFAULT_PARENTHS"]
+        }
+        ZedSyntaxToken::PunctuationDelimiter => {
+            &["DEFAULT_COMMA", "DEFAULT_SEMICOLON", "DEFAULT_DOT"]
+        }
+        ZedSyntaxToken::PunctuationListMarker => &["MARKDOWN_LIST_ITEM"],
+        ZedSyntaxToken::String => &["DEFAULT_STRING"],
+        ZedSyntaxToken::StringEscape => &["DEFAULT_VALID_STRING_ESCAPE"],
+        ZedSyntaxToken::Tag => &["DEFAULT_TAG", "HTML_TAG_NAME"],
+        ZedSyntaxToken::TextLiteral => &["MARKDOWN_CODE_SPAN"],
+        ZedSyntaxToken::Title => &["MARKDOWN_HEADER_LEVEL_1"],
+        ZedSyntaxToken::Type => &[
+            "DEFAULT_CLASS_NAME",
+            "DEFAULT_INTERFACE_NAME",
+            "DEFAULT_CLASS_REFERENCE",
+        ],
+        ZedSyntaxToken::Variable => &[
+            "DEFAULT_LOCAL_VARIABLE",
+            "DEFAULT_IDENTIFIER",
+            "DEFAULT_PARAMETER",
+        ],
+        ZedSyntaxToken::VariableSpecial => &["DEFAULT_PREDEFINED_SYMBOL"],
+        ZedSyntaxToken::Predictive
+        | ZedSyntaxToken::Primary
+        | ZedSyntaxToken::PunctuationSpecial
+        | ZedSyntaxToken::StringRegex
+        | ZedSyntaxToken::StringSpecial
+        | ZedSyntaxToken::StringSpecialSymbol
+        | ZedSyntaxToken::Variant => &[],
+    }
+}
+
+pub struct JetBrainsSchemeConverter {
+    scheme: JetBrainsScheme,
+    fallback_name: String,
+}
+
+impl JetBrainsSchemeConverter {
+    pub fn new(scheme: JetBrainsScheme, fallback_name: &str) -> Self {
+        Self {
+            scheme,
+            fallback_name: fallback_name.to_string(),
+        }
+    }
+
+    /// Converts the scheme, returning it along with the attribute keys that weren't
+    /// mapped to any Zed syntax token.
+    pub fn convert(self) -> Result<(ThemeContent, Vec<String>)> {
+        let text = self.scheme.resolved_attributes("TEXT").unwrap_or_default();
+        let appearance = match self.scheme.parent_scheme.as_deref() {
+            Some("Darcula") => AppearanceContent::Dark,
+            Some("Default") => AppearanceContent::Light,
+            _ => match text.background.as_deref().and_then(is_dark_color) {
+                Some(true) => AppearanceContent::Dark,
+                _ => AppearanceContent::Light,
+            },
+        };
+
+        let theme_colors = self.convert_theme_colors(&text);
+        let status_colors = self.convert_status_colors();
+        let players = self.convert_players();
+        let (syntax_theme, unmapped_attributes) = self.convert_syntax_theme();
+
+        let theme = ThemeContent {
+            name: self
+                .scheme
+                .name
+                .clone()
+                .unwrap_or_else(|| self.fallback_name.clone()),
+            appearance,
+            style: ThemeStyleContent {
+                window_background_appearance: Some(theme::WindowBackgroundContent::Opaque),
+                accents: Vec::new(),
+                colors: theme_colors,
+                status: status_colors,
+                players,
+                syntax: syntax_theme,
+            },
+        };
+
+        Ok((theme, unmapped_attributes))
+    }
+
+    fn color(&self, key: &str) -> Option<String> {
+        self.scheme.colors.get(key).cloned()
+    }
+
+    fn convert_theme_colors(&self, text: &JetBrainsAttributes) -> ThemeColorsContent {
+        let background = text.background.clone();
+        let foreground = text.foreground.clone();
+
+        ThemeColorsContent {
+            background: background.clone(),
+            editor_background: background.clone(),
+            editor_gutter_background: self.color("GUTTER_BACKGROUND").or(background.clone()),
+            tab_active_background: background.clone(),
+            toolbar_background: background.clone(),
+            text: foreground.clone(),
+            editor_foreground: foreground.clone(),
+            editor_active_line_background: self.color("CARET_ROW_COLOR"),
+            editor_line_number: self.color("LINE_NUMBERS_COLOR"),
+            editor_active_line_number: self.color("LINE_NUMBER_ON_CARET_ROW_COLOR").or(foreground),
+            editor_invisible: self.color("WHITESPACES"),
+            editor_indent_guide: self.color("INDENT_GUIDE"),
+            editor_indent_guide_active: self.color("SELECTED_INDENT_GUIDE"),
+            editor_wrap_guide: self.color("RIGHT_MARGIN_COLOR"),
+            search_match_background: self
+                .scheme
+                .resolved_attributes("TEXT_SEARCH_RESULT_ATTRIBUTES")
+                .and_then(|attributes| attributes.background),
+            ..Default::default()
+        }
+    }
+
+    fn convert_status_colors(&self) -> StatusColorsContent {
+        let effect_color = |key: &str| {
+            self.scheme
+                .resolved_attributes(key)
+                .and_then(|attributes| attributes.effect_color)
+        };
+
+        StatusColorsContent {
+            error: effect_color("ERRORS_ATTRIBUTES"),
+            warning: effect_color("WARNING_ATTRIBUTES"),
+            ..Default::default()
+        }
+    }
+
+    fn convert_players(&self) -> Vec<PlayerColorContent> {
+        let cursor = self.color("CARET_COLOR");
+        let selection = self.color("SELECTION_BACKGROUND");
+        if cursor.is_none() && selection.is_none() {
+            return Vec::new();
+        }
+
+        vec![PlayerColorContent {
+            cursor: cursor.clone(),
+            background: cursor,
+            selection,
+        }]
+    }
+
+    fn convert_syntax_theme(&self) -> (IndexMap<String, HighlightStyleContent>, Vec<String>) {
+        let mut highlight_styles = IndexMap::new();
+        let mut used_attributes = HashSet::new();
+
+        for syntax_token in ZedSyntaxToken::iter() {
+            let best_match = attribute_keys(syntax_token).iter().find_map(|key| {
+                let attributes = self.scheme.resolved_attributes(key)?;
+                attributes
+                    .foreground
+                    .is_some()
+                    .then_some((*key, attributes))
+            });
+
+            let Some((key, attributes)) = best_match else {
+                log::warn!("No matching attributes found for '{syntax_token}'");
+                continue;
+            };
+
+            log::info!("Matched '{syntax_token}' to '{key}'");
+
+            let highlight_style = HighlightStyleContent {
+                color: attributes.foreground,
+                background_color: attributes.background,
+                font_style: matches!(attributes.font_type, Some(2 | 3))
+                    .then_some(FontStyleContent::Italic),
+                font_weight: matches!(attributes.font_type, Some(1 | 3))
+                    .then_some(FontWeightContent::Bold),
+            };
+
+            used_attributes.insert(key);
+            highlight_styles.insert(syntax_token.to_string(), highlight_style);
+        }
+
+        let unmapped_attributes = self
+            .scheme
+            .attributes
+            .iter()
+            .filter(|(key, attributes)| {
+                attributes.foreground.is_some()
+                    && key.as_str() != "TEXT"
+                    && !used_attributes.contains(key.as_str())
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        (highlight_styles, unmapped_attributes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_import_jetbrains_scheme() {
+        let content = indoc! {r#"
+            <scheme name="Example" version="142" parent_scheme="Darcula">
+              <colors>
+                <option name="CARET_COLOR" value="bbbbbb" />
+                <option name="CARET_ROW_COLOR" value="323232" />
+                <option name="INDENT_GUIDE" value="" />
+                <option name="LINE_NUMBERS_COLOR" value="606366" />
+              </colors>
+              <attributes>
+                <option name="TEXT">
+                  <value>
+                    <option name="FOREGROUND" value="a9b7c6" />
+                    <option name="BACKGROUND" value="2b2b2b" />
+                  </value>
+                </option>
+                <option name="DEFAULT_KEYWORD">
+                  <value>
+                    <option name="FOREGROUND" value="cc7832" />
+                    <option name="FONT_TYPE" value="1" />
+                  </value>
+                </option>
+                <option name="DEFAULT_LINE_COMMENT">
+                  <value>
+                    <option name="FOREGROUND" value="808080" />
+                    <option name="FONT_TYPE" value="2" />
+                  </value>
+                </option>
+                <option name="DEFAULT_DOC_COMMENT" baseAttributes="DEFAULT_LINE_COMMENT" />
+                <option name="ERRORS_ATTRIBUTES">
+                  <value>
+                    <option name="EFFECT_COLOR" value="bc3f3c" />
+                  </value>
+                </option>
+                <option name="BAD_CHARACTER">
+                  <value>
+                    <option name="FOREGROUND" value="ff" />
+                  </value>
+                </option>
+              </attributes>
+            </scheme>
+        "#};
+
+        let scheme = JetBrainsScheme::parse(content).unwrap();
+        assert_eq!(scheme.name.as_deref(), Some("Example"));
+        assert_eq!(
+            scheme.colors.get("CARET_ROW_COLOR").map(String::as_str),
+            Some("#323232ff")
+        );
+        assert!(!scheme.colors.contains_key("INDENT_GUIDE"));
+        assert_eq!(
+            scheme
+                .resolved_attributes("DEFAULT_DOC_COMMENT")
+                .and_then(|attributes| attributes.foreground),
+            Some("#808080ff".to_string())
+        );
+
+        let (theme, unmapped_attributes) = JetBrainsSchemeConverter::new(scheme, "fallback")
+            .convert()
+            .unwrap();
+        assert_eq!(theme.name, "Example");
+        assert!(matches!(theme.appearance, AppearanceContent::Dark));
+        assert_eq!(
+            theme.style.colors.editor_background.as_deref(),
+            Some("#2b2b2bff")
+        );
+        assert_eq!(theme.style.status.error.as_deref(), Some("#bc3f3cff"));
+
+        let keyword = &theme.style.syntax["keyword"];
+        assert_eq!(keyword.color.as_deref(), Some("#cc7832ff"));
+        assert!(matches!(keyword.font_weight, Some(FontWeightContent::Bold)));
+        assert!(keyword.font_style.is_none());
+
+        let comment_doc = &theme.style.syntax["comment.doc"];
+        assert!(matches!(
+            comment_doc.font_style,
+            Some(FontStyleContent::Italic)
+        ));
+
+        assert_eq!(unmapped_attributes, vec!["BAD_CHARACTER".to_string()]);
+    }
+}
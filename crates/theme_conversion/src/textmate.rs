@@ -0,0 +1,546 @@
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use strum::IntoEnumIterator;
+use theme::{
+    AppearanceContent, HighlightStyleContent, PlayerColorContent, ThemeColorsContent, ThemeContent,
+    ThemeStyleContent,
+};
+
+use crate::color::{is_dark_color, normalize_color};
+use crate::vscode::{try_parse_font_style, try_parse_font_weight, ZedSyntaxToken};
+
+/// A theme in the TextMate format, as used by `.tmTheme` files and Sublime Text color schemes.
+#[derive(Debug, Default)]
+pub struct TextMateTheme {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    /// The global settings, keyed by their snake_case names (e.g. `line_highlight`).
+    pub globals: IndexMap<String, String>,
+    pub rules: Vec<TextMateRule>,
+}
+
+#[derive(Debug, Default)]
+pub struct TextMateRule {
+    pub name: Option<String>,
+    /// A comma-separated list of scope selectors.
+    pub scope: String,
+    pub foreground: Option<String>,
+    pub background: Option<String>,
+    pub font_style: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TmThemeFile {
+    name: Option<String>,
+    author: Option<String>,
+    #[serde(default)]
+    settings: Vec<TmThemeSettings>,
+}
+
+#[derive(Deserialize)]
+struct TmThemeSettings {
+    name: Option<String>,
+    scope: Option<String>,
+    #[serde(default)]
+    settings: IndexMap<String, plist::Value>,
+}
+
+#[derive(Deserialize)]
+struct SublimeColorScheme {
+    name: Option<String>,
+    author: Option<String>,
+    #[serde(default)]
+    variables: IndexMap<String, String>,
+    #[serde(default)]
+    globals: IndexMap<String, serde_json::Value>,
+    #[serde(default)]
+    rules: Vec<SublimeColorSchemeRule>,
+}
+
+#[derive(Deserialize)]
+struct SublimeColorSchemeRule {
+    name: Option<String>,
+    scope: Option<String>,
+    /// Either a color, or a list of colors for a gradient.
+    foreground: Option<serde_json::Value>,
+    background: Option<String>,
+    font_style: Option<String>,
+}
+
+impl TextMateTheme {
+    /// Parses a `.tmTheme` property list.
+    pub fn parse_tm_theme(content: &str) -> Result<Self> {
+        let file: TmThemeFile =
+            plist::from_bytes(content.as_bytes()).context("failed to parse TextMate theme")?;
+
+        let mut theme = Self {
+            name: file.name,
+            author: file.author,
+            ..Default::default()
+        };
+        for entry in file.settings {
+            let mut settings = entry
+                .settings
+                .into_iter()
+                .filter_map(|(key, value)| Some((key, value.into_string()?)))
+                .collect::<IndexMap<_, _>>();
+
+            match entry.scope {
+                Some(scope) => theme.rules.push(TextMateRule {
+                    name: entry.name,
+                    scope,
+                    foreground: settings.remove("foreground"),
+                    background: settings.remove("background"),
+                    font_style: settings.remove("fontStyle"),
+                }),
+                None => theme.globals.extend(
+                    settings
+                        .into_iter()
+                        .map(|(key, value)| (to_snake_case(&key), value)),
+                ),
+            }
+        }
+
+        Ok(theme)
+    }
+
+    /// Parses a `.sublime-color-scheme` file, resolving its variables.
+    pub fn parse_sublime_color_scheme(content: &str) -> Result<Self> {
+        let scheme: SublimeColorScheme = serde_json_lenient::from_str(content)
+            .context("failed to parse Sublime Text color scheme")?;
+
+        let resolve = |value: &str| resolve_variables(value, &scheme.variables);
+        let mut theme = Self {
+            name: scheme.name.clone(),
+            author: scheme.author.clone(),
+            ..Default::default()
+        };
+        for (key, value) in &scheme.globals {
+            if let Some(value) = value.as_str() {
+                theme.globals.insert(key.clone(), resolve(value));
+            }
+        }
+        for rule in &scheme.rules {
+            let Some(scope) = rule.scope.clone() else {
+                continue;
+            };
+            let foreground = match &rule.foreground {
+                Some(serde_json::Value::String(color)) => Some(color.as_str()),
+                Some(serde_json::Value::Array(colors)) => {
+                    colors.first().and_then(|color| color.as_str())
+                }
+                _ => None,
+            };
+            theme.rules.push(TextMateRule {
+                name: rule.name.clone(),
+                scope,
+                foreground: foreground.map(resolve),
+                background: rule.background.as_deref().map(resolve),
+                font_style: rule.font_style.clone(),
+            });
+        }
+
+        Ok(theme)
+    }
+}
+
+/// Replaces every `var(name)` reference in the given value with the variable's value.
+fn resolve_variables(value: &str, variables: &IndexMap<String, String>) -> String {
+    let mut value = value.to_string();
+    // Variables may refer to other variables, but guard against cycles.
+    for _ in 0..8 {
+        let Some(start) = value.find("var(") else {
+            break;
+        };
+        let Some(end) = value[start..].find(')').map(|end| start + end) else {
+            break;
+        };
+        let name = value[start + "var(".len()..end].trim();
+        let replacement = variables.get(name).cloned().unwrap_or_default();
+        value.replace_range(start..=end, &replacement);
+    }
+    value
+}
+
+fn to_snake_case(key: &str) -> String {
+    let mut snake_case = String::with_capacity(key.len());
+    for char in key.chars() {
+        if char.is_ascii_uppercase() {
+            snake_case.push('_');
+            snake_case.push(char.to_ascii_lowercase());
+        } else {
+            snake_case.push(char);
+        }
+    }
+    snake_case
+}
+
+pub struct TextMateThemeConverter {
+    theme: TextMateTheme,
+    fallback_name: String,
+}
+
+impl TextMateThemeConverter {
+    pub fn new(theme: TextMateTheme, fallback_name: &str) -> Self {
+        Self {
+            theme,
+            fallback_name: fallback_name.to_string(),
+        }
+    }
+
+    /// Converts the theme, returning it along with the scopes that weren't mapped
+    /// to any Zed syntax token.
+    pub fn convert(self) -> Result<(ThemeContent, Vec<String>)> {
+        let background = self.global_color("background");
+        let appearance = match background.as_deref().and_then(is_dark_color) {
+            Some(false) => AppearanceContent::Light,
+            _ => AppearanceContent::Dark,
+        };
+
+        let theme_colors = self.convert_theme_colors();
+        let players = self.convert_players();
+        let (syntax_theme, unmapped_scopes) = self.convert_syntax_theme();
+
+        let theme = ThemeContent {
+            name: self
+                .theme
+                .name
+                .clone()
+                .unwrap_or_else(|| self.fallback_name.clone()),
+            appearance,
+            style: ThemeStyleContent {
+                window_background_appearance: Some(theme::WindowBackgroundContent::Opaque),
+                accents: Vec::new(),
+                colors: theme_colors,
+                status: Default::default(),
+                players,
+                syntax: syntax_theme,
+            },
+        };
+
+        Ok((theme, unmapped_scopes))
+    }
+
+    fn global_color(&self, key: &str) -> Option<String> {
+        self.theme
+            .globals
+            .get(key)
+            .and_then(|color| normalize_color(color))
+    }
+
+    fn convert_theme_colors(&self) -> ThemeColorsContent {
+        let background = self.global_color("background");
+        let foreground = self.global_color("foreground");
+
+        ThemeColorsContent {
+            background: background.clone(),
+            editor_background: background.clone(),
+            editor_gutter_background: self.global_color("gutter").or(background.clone()),
+            tab_active_background: background.clone(),
+            toolbar_background: background.clone(),
+            scrollbar_track_background: background.clone(),
+            text: foreground.clone(),
+            editor_foreground: foreground.clone(),
+            editor_active_line_background: self.global_color("line_highlight"),
+            editor_line_number: self.global_color("gutter_foreground"),
+            editor_active_line_number: self
+                .global_color("gutter_foreground_highlight")
+                .or(foreground),
+            editor_invisible: self.global_color("invisibles"),
+            editor_indent_guide: self.global_color("guide"),
+            editor_indent_guide_active: self.global_color("active_guide"),
+            search_match_background: self.global_color("find_highlight"),
+            ..Default::default()
+        }
+    }
+
+    fn convert_players(&self) -> Vec<PlayerColorContent> {
+        let cursor = self.global_color("caret");
+        let selection = self.global_color("selection");
+        if cursor.is_none() && selection.is_none() {
+            return Vec::new();
+        }
+
+        vec![PlayerColorContent {
+            cursor: cursor.clone(),
+            background: cursor,
+            selection,
+        }]
+    }
+
+    fn convert_syntax_theme(&self) -> (IndexMap<String, HighlightStyleContent>, Vec<String>) {
+        let mut highlight_styles = IndexMap::new();
+        let mut used_rules = HashSet::new();
+
+        for syntax_token in ZedSyntaxToken::iter() {
+            let best_match = self.find_best_rule(syntax_token).or_else(|| {
+                syntax_token
+                    .fallbacks()
+                    .iter()
+                    .find_map(|fallback| self.find_best_rule(*fallback))
+            });
+
+            let Some(rule_ix) = best_match else {
+                log::warn!("No matching scope found for '{syntax_token}'");
+                continue;
+            };
+            let rule = &self.theme.rules[rule_ix];
+
+            log::info!("Matched '{syntax_token}' to '{}'", rule.scope);
+
+            let highlight_style = HighlightStyleContent {
+                color: rule.foreground.as_deref().and_then(normalize_color),
+                background_color: rule.background.as_deref().and_then(normalize_color),
+                font_style: rule.font_style.as_deref().and_then(try_parse_font_style),
+                font_weight: rule.font_style.as_deref().and_then(try_parse_font_weight),
+            };
+
+            if highlight_style.is_empty() {
+                continue;
+            }
+
+            used_rules.insert(rule_ix);
+            highlight_styles.insert(syntax_token.to_string(), highlight_style);
+        }
+
+        let unmapped_scopes = self
+            .theme
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|(ix, _)| !used_rules.contains(ix))
+            .flat_map(|(_, rule)| rule.scope.split(','))
+            .map(|scope| scope.trim().to_string())
+            .filter(|scope| !scope.is_empty())
+            .collect();
+
+        (highlight_styles, unmapped_scopes)
+    }
+
+    /// Finds the rule that best matches the given token. As in TextMate, when
+    /// several rules match equally well, the last one wins.
+    fn find_best_rule(&self, syntax_token: ZedSyntaxToken) -> Option<usize> {
+        let scopes = syntax_token.to_vscode();
+        let mut best_match: Option<(usize, u32)> = None;
+
+        for (ix, rule) in self.theme.rules.iter().enumerate() {
+            if rule.foreground.is_none() {
+                continue;
+            }
+
+            let Some(rank) = rank_scope_selector(&rule.scope, &scopes) else {
+                continue;
+            };
+
+            if best_match.map_or(true, |(_, best_rank)| rank >= best_rank) {
+                best_match = Some((ix, rank));
+            }
+        }
+
+        best_match.map(|(ix, _)| ix)
+    }
+}
+
+/// Ranks how well a comma-separated list of scope selectors matches any of the given scopes.
+///
+/// A selector matches a scope when it is equal to the scope or one of its prefixes
+/// (`string` matches `string.regex`). Scopes towards the front of the list, and
+/// more specific selectors, are ranked higher.
+fn rank_scope_selector(selectors: &str, scopes: &[&str]) -> Option<u32> {
+    selectors
+        .split(',')
+        .filter_map(|selector| {
+            // Exclusions are ignored, and descendant selectors are matched by their innermost scope.
+            let selector = selector.split(" - ").next()?.split_whitespace().last()?;
+            let specificity = selector.split('.').count() as u32;
+
+            scopes
+                .iter()
+                .enumerate()
+                .filter_map(|(ix, scope)| {
+                    let scope = scope.split_whitespace().last()?;
+                    let is_match = scope == selector
+                        || scope
+                            .strip_prefix(selector)
+                            .map_or(false, |rest| rest.starts_with('.'));
+                    let weight = (scopes.len() - ix) as u32;
+                    is_match.then_some(weight * 100 + specificity)
+                })
+                .max()
+        })
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_rank_scope_selector() {
+        let scopes = ["entity.name.function", "variable.function"];
+
+        assert_eq!(rank_scope_selector("comment", &scopes), None);
+        assert_eq!(
+            rank_scope_selector("entity.name.function.rust", &scopes),
+            None
+        );
+
+        // Exact matches rank above prefix matches, and earlier scopes above later ones.
+        let exact = rank_scope_selector("entity.name.function", &scopes).unwrap();
+        let prefix = rank_scope_selector("entity.name", &scopes).unwrap();
+        let later = rank_scope_selector("variable.function", &scopes).unwrap();
+        assert!(exact > prefix);
+        assert!(prefix > later);
+
+        assert_eq!(
+            rank_scope_selector("comment, source.js entity.name - meta.tag", &scopes),
+            Some(prefix)
+        );
+    }
+
+    #[test]
+    fn test_import_tm_theme() {
+        let content = indoc! {r##"
+            <?xml version="1.0" encoding="UTF-8"?>
+            <!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+            <plist version="1.0">
+            <dict>
+                <key>name</key>
+                <string>Example</string>
+                <key>settings</key>
+                <array>
+                    <dict>
+                        <key>settings</key>
+                        <dict>
+                            <key>background</key>
+                            <string>#272822</string>
+                            <key>foreground</key>
+                            <string>#F8F8F2</string>
+                            <key>lineHighlight</key>
+                            <string>#3E3D32</string>
+                            <key>caret</key>
+                            <string>#F8F8F0</string>
+                        </dict>
+                    </dict>
+                    <dict>
+                        <key>name</key>
+                        <string>Comment</string>
+                        <key>scope</key>
+                        <string>comment</string>
+                        <key>settings</key>
+                        <dict>
+                            <key>foreground</key>
+                            <string>#75715E</string>
+                            <key>fontStyle</key>
+                            <string>italic</string>
+                        </dict>
+                    </dict>
+                    <dict>
+                        <key>scope</key>
+                        <string>keyword, storage</string>
+                        <key>settings</key>
+                        <dict>
+                            <key>foreground</key>
+                            <string>#F92672</string>
+                        </dict>
+                    </dict>
+                    <dict>
+                        <key>scope</key>
+                        <string>meta.diff.header</string>
+                        <key>settings</key>
+                        <dict>
+                            <key>foreground</key>
+                            <string>#75715E</string>
+                        </dict>
+                    </dict>
+                </array>
+            </dict>
+            </plist>
+        "##};
+
+        let theme = TextMateTheme::parse_tm_theme(content).unwrap();
+        assert_eq!(theme.name.as_deref(), Some("Example"));
+        assert_eq!(
+            theme.globals.get("line_highlight").map(String::as_str),
+            Some("#3E3D32")
+        );
+        assert_eq!(theme.rules.len(), 3);
+
+        let (theme, unmapped_scopes) = TextMateThemeConverter::new(theme, "fallback")
+            .convert()
+            .unwrap();
+        assert_eq!(theme.name, "Example");
+        assert!(matches!(theme.appearance, AppearanceContent::Dark));
+        assert_eq!(
+            theme.style.colors.editor_active_line_background.as_deref(),
+            Some("#3e3d32ff")
+        );
+        assert_eq!(theme.style.players[0].cursor.as_deref(), Some("#f8f8f0ff"));
+
+        let comment = &theme.style.syntax["comment"];
+        assert_eq!(comment.color.as_deref(), Some("#75715eff"));
+        assert!(comment.font_style.is_some());
+        // `comment.doc` falls back to the `comment` rule.
+        assert_eq!(
+            theme.style.syntax["comment.doc"].color.as_deref(),
+            Some("#75715eff")
+        );
+        assert_eq!(
+            theme.style.syntax["keyword"].color.as_deref(),
+            Some("#f92672ff")
+        );
+
+        assert_eq!(unmapped_scopes, vec!["meta.diff.header".to_string()]);
+    }
+
+    #[test]
+    fn test_import_sublime_color_scheme() {
+        let content = indoc! {r##"
+            {
+                // Comments are allowed
+                "name": "Example",
+                "variables": {
+                    "black": "#1d1f21",
+                    "background": "var(black)",
+                    "red": "#cc6666",
+                },
+                "globals": {
+                    "background": "var(background)",
+                    "foreground": "#c5c8c6",
+                    "selection": "color(var(red) alpha(0.5))",
+                },
+                "rules": [
+                    {
+                        "scope": "string",
+                        "foreground": "var(red)",
+                        "font_style": "bold",
+                    },
+                ],
+            }
+        "##};
+
+        let theme = TextMateTheme::parse_sublime_color_scheme(content).unwrap();
+        assert_eq!(
+            theme.globals.get("background").map(String::as_str),
+            Some("#1d1f21")
+        );
+        assert_eq!(theme.rules[0].foreground.as_deref(), Some("#cc6666"));
+
+        let (theme, unmapped_scopes) = TextMateThemeConverter::new(theme, "fallback")
+            .convert()
+            .unwrap();
+        assert_eq!(
+            theme.style.players[0].selection.as_deref(),
+            Some("#cc666680")
+        );
+        let string = &theme.style.syntax["string"];
+        assert_eq!(string.color.as_deref(), Some("#cc6666ff"));
+        assert!(string.font_weight.is_some());
+        assert!(unmapped_scopes.is_empty());
+    }
+}
@@ -0,0 +1,152 @@
+mod color;
+mod jetbrains;
+mod textmate;
+mod vscode;
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use strum::IntoEnumIterator;
+use theme::{Appearance, AppearanceContent, ThemeContent, ThemeFamilyContent};
+
+pub use crate::jetbrains::{JetBrainsScheme, JetBrainsSchemeConverter};
+pub use crate::textmate::{TextMateTheme, TextMateThemeConverter};
+pub use crate::vscode::{VsCodeTheme, VsCodeThemeConverter, ZedSyntaxToken};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeAppearanceJson {
+    Light,
+    Dark,
+}
+
+impl From<ThemeAppearanceJson> for AppearanceContent {
+    fn from(value: ThemeAppearanceJson) -> Self {
+        match value {
+            ThemeAppearanceJson::Light => Self::Light,
+            ThemeAppearanceJson::Dark => Self::Dark,
+        }
+    }
+}
+
+impl From<ThemeAppearanceJson> for Appearance {
+    fn from(value: ThemeAppearanceJson) -> Self {
+        match value {
+            ThemeAppearanceJson::Light => Self::Light,
+            ThemeAppearanceJson::Dark => Self::Dark,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ThemeMetadata {
+    pub name: String,
+    pub file_name: String,
+    pub appearance: ThemeAppearanceJson,
+}
+
+/// The theme formats that can be imported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeFormat {
+    /// A VS Code color theme (`.json`).
+    VsCode,
+    /// A TextMate theme (`.tmTheme`), which is also used by Sublime Text.
+    TextMate,
+    /// A Sublime Text color scheme (`.sublime-color-scheme`).
+    SublimeColorScheme,
+    /// A JetBrains IDE color scheme (`.icls`).
+    JetBrains,
+}
+
+impl ThemeFormat {
+    /// Determines the format of a theme from its file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "json" => Some(Self::VsCode),
+            "tmtheme" => Some(Self::TextMate),
+            "sublime-color-scheme" => Some(Self::SublimeColorScheme),
+            "icls" => Some(Self::JetBrains),
+            _ => None,
+        }
+    }
+}
+
+/// A summary of what could not be carried over when importing a theme.
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Scopes (or JetBrains attribute keys) from the source theme that
+    /// were not used for any Zed syntax token.
+    pub unmapped_scopes: Vec<String>,
+    /// Zed syntax tokens that no style in the source theme was found for.
+    pub unmatched_syntax_tokens: Vec<String>,
+}
+
+impl ImportReport {
+    fn new(unmapped_scopes: Vec<String>, theme: &ThemeContent) -> Self {
+        Self {
+            unmapped_scopes,
+            unmatched_syntax_tokens: ZedSyntaxToken::iter()
+                .map(|token| token.to_string())
+                .filter(|token| !theme.style.syntax.contains_key(token))
+                .collect(),
+        }
+    }
+}
+
+/// Converts the contents of a theme file in the given format into a Zed theme family.
+///
+/// `fallback_name` is used when the theme doesn't specify a name, and is typically
+/// derived from the file name.
+pub fn import_theme(
+    format: ThemeFormat,
+    content: &str,
+    fallback_name: &str,
+) -> Result<(ThemeFamilyContent, ImportReport)> {
+    let (theme, author, report) = match format {
+        ThemeFormat::VsCode => {
+            let vscode_theme: VsCodeTheme =
+                serde_json_lenient::from_str(content).context("failed to parse VS Code theme")?;
+            let author = vscode_theme.author.clone();
+            let theme_metadata = ThemeMetadata {
+                name: vscode_theme
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| fallback_name.to_string()),
+                appearance: ThemeAppearanceJson::Dark,
+                file_name: "".to_string(),
+            };
+            let theme = VsCodeThemeConverter::new(vscode_theme, theme_metadata, IndexMap::new())
+                .convert()?;
+            let report = ImportReport::new(Vec::new(), &theme);
+            (theme, author, report)
+        }
+        ThemeFormat::TextMate | ThemeFormat::SublimeColorScheme => {
+            let textmate_theme = if format == ThemeFormat::TextMate {
+                TextMateTheme::parse_tm_theme(content)?
+            } else {
+                TextMateTheme::parse_sublime_color_scheme(content)?
+            };
+            let author = textmate_theme.author.clone();
+            let (theme, unmapped_scopes) =
+                TextMateThemeConverter::new(textmate_theme, fallback_name).convert()?;
+            let report = ImportReport::new(unmapped_scopes, &theme);
+            (theme, author, report)
+        }
+        ThemeFormat::JetBrains => {
+            let scheme = JetBrainsScheme::parse(content)?;
+            let (theme, unmapped_scopes) =
+                JetBrainsSchemeConverter::new(scheme, fallback_name).convert()?;
+            let report = ImportReport::new(unmapped_scopes, &theme);
+            (theme, None, report)
+        }
+    };
+
+    let family = ThemeFamilyContent {
+        name: theme.name.clone(),
+        author: author.unwrap_or_default(),
+        themes: vec![theme],
+    };
+    Ok((family, report))
+}
@@ -1,12 +1,10 @@
 mod assets;
-mod color;
-mod vscode;
 
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use clap::{Parser, Subcommand};
 use indexmap::IndexMap;
 use log::LevelFilter;
@@ -14,10 +12,8 @@ use schemars::schema_for;
 use serde::Deserialize;
 use simplelog::ColorChoice;
 use simplelog::{TermLogger, TerminalMode};
-use theme::{Appearance, AppearanceContent, ThemeFamilyContent};
-
-use crate::vscode::VsCodeTheme;
-use crate::vscode::VsCodeThemeConverter;
+use theme::ThemeFamilyContent;
+use theme_conversion::{import_theme, ThemeFormat, ThemeMetadata};
 
 #[derive(Debug, Deserialize)]
 struct FamilyMetadata {
@@ -34,42 +30,13 @@ struct FamilyMetadata {
     pub syntax: IndexMap<String, Vec<String>>,
 }
 
-#[derive(Debug, Clone, Copy, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum ThemeAppearanceJson {
-    Light,
-    Dark,
-}
-
-impl From<ThemeAppearanceJson> for AppearanceContent {
-    fn from(value: ThemeAppearanceJson) -> Self {
-        match value {
-            ThemeAppearanceJson::Light => Self::Light,
-            ThemeAppearanceJson::Dark => Self::Dark,
-        }
-    }
-}
-
-impl From<ThemeAppearanceJson> for Appearance {
-    fn from(value: ThemeAppearanceJson) -> Self {
-        match value {
-            ThemeAppearanceJson::Light => Self::Light,
-            ThemeAppearanceJson::Dark => Self::Dark,
-        }
-    }
-}
-
-#[derive(Debug, Deserialize)]
-pub struct ThemeMetadata {
-    pub name: String,
-    pub file_name: String,
-    pub appearance: ThemeAppearanceJson,
-}
-
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Args {
     /// The path to the theme to import.
+    ///
+    /// VS Code themes (`.json`), TextMate themes (`.tmTheme`), Sublime Text color
+    /// schemes (`.sublime-color-scheme`) and JetBrains color schemes (`.icls`) are supported.
     theme_path: PathBuf,
 
     /// Whether to warn when values are missing from the theme.
@@ -128,28 +95,35 @@ fn main() -> Result<()> {
 
     let theme_file_path = args.theme_path;
 
-    let theme_file = match File::open(&theme_file_path) {
-        Ok(file) => file,
+    let format = ThemeFormat::from_path(&theme_file_path)
+        .ok_or_else(|| anyhow!("unsupported theme format: {theme_file_path:?}"))?;
+
+    let theme_content = match std::fs::read_to_string(&theme_file_path) {
+        Ok(content) => content,
         Err(err) => {
             log::info!("Failed to open file at path: {:?}", theme_file_path);
             return Err(err)?;
         }
     };
 
-    let vscode_theme: VsCodeTheme = serde_json_lenient::from_reader(theme_file)
-        .context(format!("failed to parse theme {theme_file_path:?}"))?;
+    let fallback_name = theme_file_path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default();
 
-    let theme_metadata = ThemeMetadata {
-        name: vscode_theme.name.clone().unwrap_or("".to_string()),
-        appearance: ThemeAppearanceJson::Dark,
-        file_name: "".to_string(),
-    };
+    let (theme_family, report) = import_theme(format, &theme_content, fallback_name)
+        .context(format!("failed to parse theme {theme_file_path:?}"))?;
 
-    let converter = VsCodeThemeConverter::new(vscode_theme, theme_metadata, IndexMap::new());
+    for scope in &report.unmapped_scopes {
+        log::warn!("Unmapped scope: '{scope}'");
+    }
+    for token in &report.unmatched_syntax_tokens {
+        log::warn!("No style found for syntax token: '{token}'");
+    }
 
-    let theme = converter.convert()?;
+    let theme = &theme_family.themes[0];
 
-    let theme_json = serde_json::to_string_pretty(&theme).unwrap();
+    let theme_json = serde_json::to_string_pretty(theme).unwrap();
 
     if let Some(output) = args.output {
         let mut file = File::create(output)?;
@@ -1,19 +1,26 @@
+use anyhow::{Context as _, Result};
 use client::telemetry::Telemetry;
 use feature_flags::FeatureFlagAppExt;
 use fs::Fs;
 use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
 use gpui::{
-    actions, impl_actions, AppContext, DismissEvent, EventEmitter, FocusableView, Render,
-    UpdateGlobal, View, ViewContext, VisualContext, WeakView,
+    actions, impl_actions, AppContext, DismissEvent, EventEmitter, FocusableView,
+    PathPromptOptions, PromptLevel, Render, UpdateGlobal, View, ViewContext, VisualContext,
+    WeakView,
 };
 use picker::{Picker, PickerDelegate};
+use project::DirectoryLister;
 use serde::Deserialize;
 use settings::{update_settings_file, SettingsStore};
-use std::sync::Arc;
-use theme::{Appearance, Theme, ThemeMeta, ThemeRegistry, ThemeSettings};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use theme::{Appearance, Theme, ThemeFamilyContent, ThemeMeta, ThemeRegistry, ThemeSettings};
+use theme_conversion::{ImportReport, ThemeFormat};
 use ui::{prelude::*, v_flex, ListItem, ListItemSpacing};
 use util::ResultExt;
-use workspace::{ui::HighlightedLabel, ModalView, Workspace};
+use workspace::{notifications::NotificationId, ui::HighlightedLabel, ModalView, Toast, Workspace};
 
 #[derive(PartialEq, Clone, Default, Debug, Deserialize)]
 pub struct Toggle {
@@ -22,12 +29,14 @@ pub struct Toggle {
 }
 
 impl_actions!(theme_selector, [Toggle]);
-actions!(theme_selector, [Reload]);
+actions!(theme_selector, [Reload, ImportTheme]);
 
 pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(
         |workspace: &mut Workspace, _cx: &mut ViewContext<Workspace>| {
-            workspace.register_action(toggle);
+            workspace
+                .register_action(toggle)
+                .register_action(import_theme);
         },
     )
     .detach();
@@ -48,6 +57,137 @@ pub fn toggle(workspace: &mut Workspace, toggle: &Toggle, cx: &mut ViewContext<W
     });
 }
 
+/// Prompts for a VS Code, TextMate, Sublime Text or JetBrains theme file, converts it,
+/// installs it into the themes directory and opens the theme selector on the result.
+pub fn import_theme(workspace: &mut Workspace, _: &ImportTheme, cx: &mut ViewContext<Workspace>) {
+    let fs = workspace.app_state().fs.clone();
+    let prompt = workspace.prompt_for_open_path(
+        PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+        },
+        DirectoryLister::Local(fs.clone()),
+        cx,
+    );
+    let registry = ThemeRegistry::global(cx);
+
+    cx.spawn(|workspace, mut cx| async move {
+        let Ok(Some(mut paths)) = prompt.await else {
+            return Ok(());
+        };
+        let Some(path) = paths.pop() else {
+            return Ok(());
+        };
+
+        let result = async {
+            let (theme_family, report) = convert_theme(&path, fs.as_ref()).await?;
+            let theme_path = theme_file_path(&theme_family);
+            if fs.is_file(&theme_path).await {
+                let answer = workspace.update(&mut cx, |_, cx| {
+                    cx.prompt(
+                        PromptLevel::Warning,
+                        &format!("Replace the existing theme \"{}\"?", theme_family.name),
+                        Some(&format!("{theme_path:?} already exists.")),
+                        &["Replace", "Cancel"],
+                    )
+                })?;
+                if answer.await? != 0 {
+                    return Ok(None);
+                }
+            }
+            install_theme(&theme_path, &theme_family, fs, registry).await?;
+            let theme_names = theme_family
+                .themes
+                .iter()
+                .map(|theme| theme.name.clone())
+                .collect::<Vec<_>>();
+            anyhow::Ok(Some((theme_names, report)))
+        }
+        .await;
+        workspace.update(&mut cx, |workspace, cx| match result {
+            Ok(None) => {}
+            Ok(Some((theme_names, report))) => {
+                if !report.unmapped_scopes.is_empty() || !report.unmatched_syntax_tokens.is_empty()
+                {
+                    log::info!(
+                        "imported theme from {path:?}; unmapped scopes: {:?}, unmatched syntax tokens: {:?}",
+                        report.unmapped_scopes,
+                        report.unmatched_syntax_tokens
+                    );
+                    workspace.show_toast(
+                        Toast::new(
+                            NotificationId::unique::<ImportTheme>(),
+                            format!(
+                                "Imported {}. {} scopes were not mapped and {} syntax tokens have no style; see the log for details.",
+                                theme_names.join(", "),
+                                report.unmapped_scopes.len(),
+                                report.unmatched_syntax_tokens.len()
+                            ),
+                        )
+                        .autohide(),
+                        cx,
+                    );
+                }
+                toggle(
+                    workspace,
+                    &Toggle {
+                        themes_filter: Some(theme_names),
+                    },
+                    cx,
+                );
+            }
+            Err(error) => workspace.show_error(&error, cx),
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Converts the theme at the given path into a Zed theme family.
+async fn convert_theme(path: &Path, fs: &dyn Fs) -> Result<(ThemeFamilyContent, ImportReport)> {
+    let format =
+        ThemeFormat::from_path(path).with_context(|| format!("unsupported theme file {path:?}"))?;
+    let content = fs.load(path).await?;
+    let fallback_name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("Imported Theme");
+    theme_conversion::import_theme(format, &content, fallback_name)
+        .with_context(|| format!("failed to import theme from {path:?}"))
+}
+
+/// The path in the themes directory that the given theme family is installed at.
+fn theme_file_path(theme_family: &ThemeFamilyContent) -> PathBuf {
+    let file_name = theme_family
+        .name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    paths::themes_dir().join(format!("{file_name}.json"))
+}
+
+/// Writes the theme family to the given path and loads it into the registry.
+async fn install_theme(
+    theme_path: &Path,
+    theme_family: &ThemeFamilyContent,
+    fs: Arc<dyn Fs>,
+    registry: Arc<ThemeRegistry>,
+) -> Result<()> {
+    fs.create_dir(paths::themes_dir()).await?;
+    fs.atomic_write(
+        theme_path.to_path_buf(),
+        serde_json::to_string_pretty(theme_family)?,
+    )
+    .await?;
+    registry.load_user_theme(theme_path, fs).await
+}
+
 impl ModalView for ThemeSelector {}
 
 pub struct ThemeSelector {
@@ -385,8 +385,16 @@ impl TitleBar {
             .as_ref()
             .and_then(RepositoryEntry::branch)
             .map(|branch| util::truncate_and_trailoff(&branch, MAX_BRANCH_NAME_LENGTH))?;
+        let label = match entry
+            .as_ref()
+            .and_then(RepositoryEntry::upstream)
+            .and_then(vcs_menu::format_upstream_divergence)
+        {
+            Some(divergence) => format!("{branch_name} {divergence}"),
+            None => branch_name,
+        };
         Some(
-            Button::new("project_branch_trigger", branch_name)
+            Button::new("project_branch_trigger", label)
                 .color(Color::Muted)
                 .style(ButtonStyle::Subtle)
                 .label_size(LabelSize::Small)
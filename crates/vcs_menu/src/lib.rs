@@ -1,32 +1,126 @@
 use anyhow::{Context, Result};
 use fuzzy::{StringMatch, StringMatchCandidate};
-use git::repository::Branch;
+use git::repository::{Branch, GitRepository, Upstream};
 use gpui::{
     actions, rems, AnyElement, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView,
-    InteractiveElement, IntoElement, ParentElement, Render, SharedString, Styled, Subscription,
-    Task, View, ViewContext, VisualContext, WindowContext,
+    InteractiveElement, IntoElement, ParentElement, PromptLevel, Render, SharedString, Styled,
+    Subscription, Task, View, ViewContext, VisualContext, WindowContext,
 };
 use picker::{Picker, PickerDelegate};
 use std::{ops::Not, sync::Arc};
 use ui::{
     h_flex, v_flex, Button, ButtonCommon, Clickable, Color, HighlightedLabel, Label, LabelCommon,
-    LabelSize, ListItem, ListItemSpacing, Selectable,
+    LabelSize, ListItem, ListItemSpacing, Selectable, Tooltip,
 };
 use util::ResultExt;
 use workspace::notifications::NotificationId;
 use workspace::{ModalView, Toast, Workspace};
 
-actions!(branches, [OpenRecent]);
+mod stash_list;
+
+pub use stash_list::StashList;
+
+actions!(
+    branches,
+    [
+        OpenRecent,
+        FetchUpstream,
+        FastForwardToUpstream,
+        StashChanges,
+        PopStash,
+        ShowStashes
+    ]
+);
 
 pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(|workspace: &mut Workspace, _| {
         workspace.register_action(|workspace, action, cx| {
             BranchList::open(workspace, action, cx).log_err();
         });
+        workspace.register_action(|workspace, _: &FetchUpstream, cx| {
+            run_repository_operation(workspace, "fetch", cx, |repo| {
+                let upstream = repo
+                    .upstream()
+                    .context("the current branch has no upstream")?;
+                let (remote, _) = upstream
+                    .name
+                    .split_once('/')
+                    .context("failed to determine the remote of the upstream")?;
+                repo.fetch(remote)
+            });
+        });
+        workspace.register_action(|workspace, _: &FastForwardToUpstream, cx| {
+            run_repository_operation(workspace, "fast-forward", cx, |repo| {
+                let upstream = repo
+                    .upstream()
+                    .context("the current branch has no upstream")?;
+                repo.merge_fast_forward(&upstream.name)
+            });
+        });
+        workspace.register_action(|workspace, _: &StashChanges, cx| {
+            run_repository_operation(workspace, "stash changes", cx, |repo| repo.stash_push(None));
+        });
+        workspace.register_action(|workspace, _: &PopStash, cx| {
+            run_repository_operation(workspace, "pop stash", cx, |repo| {
+                if repo.stash_list()?.is_empty() {
+                    anyhow::bail!("there are no stashed changes");
+                }
+                repo.stash_pop(0)
+            });
+        });
+        workspace.register_action(|workspace, action, cx| {
+            StashList::open(workspace, action, cx).log_err();
+        });
     })
     .detach();
 }
 
+/// Runs an operation on the repository of the first worktree in the background,
+/// showing a toast if it fails.
+fn run_repository_operation(
+    workspace: &mut Workspace,
+    operation_name: &'static str,
+    cx: &mut ViewContext<Workspace>,
+    operation: impl FnOnce(&dyn GitRepository) -> Result<()> + Send + 'static,
+) {
+    let Some(repo) = workspace
+        .project()
+        .read(cx)
+        .get_first_worktree_root_repo(cx)
+    else {
+        return;
+    };
+    let task = cx
+        .background_executor()
+        .spawn(async move { operation(repo.as_ref()) });
+    cx.spawn(|workspace, mut cx| async move {
+        if let Err(error) = task.await {
+            workspace.update(&mut cx, |workspace, cx| {
+                struct GitOperationFailure;
+                let id = NotificationId::unique::<GitOperationFailure>();
+                workspace.show_toast(
+                    Toast::new(id, format!("Failed to {operation_name}: {error}")),
+                    cx,
+                );
+            })?;
+        }
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Formats how far a branch has diverged from its upstream, e.g. `↑1 ↓2`.
+pub fn format_upstream_divergence(upstream: &Upstream) -> Option<String> {
+    let mut parts = Vec::new();
+    if upstream.ahead > 0 {
+        parts.push(format!("↑{}", upstream.ahead));
+    }
+    if upstream.behind > 0 {
+        parts.push(format!("↓{}", upstream.behind));
+    }
+    parts.is_empty().not().then(|| parts.join(" "))
+}
+
 pub struct BranchList {
     pub picker: View<Picker<BranchListDelegate>>,
     rem_width: f32,
@@ -110,6 +204,12 @@ impl BranchListDelegate {
         })
     }
 
+    fn branch(&self, name: &str) -> Option<&Branch> {
+        self.all_branches
+            .iter()
+            .find(|branch| branch.name.as_ref() == name)
+    }
+
     fn display_error_toast(&self, message: String, cx: &mut WindowContext<'_>) {
         self.workspace.update(cx, |model, ctx| {
             struct GitCheckoutFailure;
@@ -118,6 +218,82 @@ impl BranchListDelegate {
             model.show_toast(Toast::new(id, message), ctx)
         });
     }
+
+    fn reload_branches(&mut self, repo: &dyn GitRepository, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(branches) = repo.branches().log_err() {
+            self.all_branches = branches;
+        }
+        cx.spawn(|picker, mut cx| async move {
+            picker.update(&mut cx, |picker, cx| picker.refresh(cx))
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Deletes a branch once the user confirms it, warning them when the branch isn't
+    /// merged into HEAD and has to be force deleted.
+    fn delete_branch(&mut self, name: String, cx: &mut ViewContext<Picker<Self>>) {
+        let project = self.workspace.read(cx).project().read(cx);
+        let Some(repo) = project.get_first_worktree_root_repo(cx) else {
+            return;
+        };
+        let is_merged = match repo.is_branch_merged(&name) {
+            Ok(is_merged) => is_merged,
+            Err(error) => {
+                self.display_error_toast(format!("Failed to delete branch '{name}': {error}"), cx);
+                return;
+            }
+        };
+        let answer = if is_merged {
+            cx.prompt(
+                PromptLevel::Warning,
+                &format!("Delete branch '{name}'?"),
+                None,
+                &["Delete", "Cancel"],
+            )
+        } else {
+            cx.prompt(
+                PromptLevel::Warning,
+                &format!("Branch '{name}' is not fully merged. Delete it anyway?"),
+                Some("Its commits that aren't reachable from another branch will be lost."),
+                &["Force Delete", "Cancel"],
+            )
+        };
+        cx.spawn(|picker, mut cx| async move {
+            if answer.await? != 0 {
+                return Ok(());
+            }
+            let result = repo.delete_branch(&name, !is_merged);
+            picker.update(&mut cx, |picker, cx| {
+                if let Err(error) = result {
+                    picker.delegate.display_error_toast(
+                        format!("Failed to delete branch '{name}': {error}"),
+                        cx,
+                    );
+                    return;
+                }
+                picker.delegate.reload_branches(repo.as_ref(), cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn rename_current_branch(&mut self, new_name: String, cx: &mut ViewContext<Picker<Self>>) {
+        let project = self.workspace.read(cx).project().read(cx);
+        let Some(repo) = project.get_first_worktree_root_repo(cx) else {
+            return;
+        };
+        let Some(current_branch) = repo.branch_name() else {
+            return;
+        };
+        if let Err(error) = repo.rename_branch(&current_branch, &new_name) {
+            self.display_error_toast(
+                format!("Failed to rename branch '{current_branch}' to '{new_name}': {error}"),
+                cx,
+            );
+            return;
+        }
+        cx.emit(DismissEvent);
+    }
 }
 
 impl PickerDelegate for BranchListDelegate {
@@ -210,7 +386,7 @@ impl PickerDelegate for BranchListDelegate {
         })
     }
 
-    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
         let current_pick = self.selected_index();
         let Some(current_pick) = self
             .matches
@@ -219,6 +395,10 @@ impl PickerDelegate for BranchListDelegate {
         else {
             return;
         };
+        if secondary {
+            self.delete_branch(current_pick, cx);
+            return;
+        }
         cx.spawn(|picker, mut cx| async move {
             picker
                 .update(&mut cx, |this, cx| {
@@ -260,12 +440,21 @@ impl PickerDelegate for BranchListDelegate {
             .filter(|index| index < &&self.branch_name_trailoff_after)
             .copied()
             .collect();
+        let divergence = self
+            .branch(&hit.string)
+            .and_then(|branch| branch.upstream.as_ref())
+            .and_then(format_upstream_divergence);
         Some(
             ListItem::new(SharedString::from(format!("vcs-menu-{ix}")))
                 .inset(true)
                 .spacing(ListItemSpacing::Sparse)
                 .selected(selected)
-                .start_slot(HighlightedLabel::new(shortened_branch_name, highlights)),
+                .start_slot(HighlightedLabel::new(shortened_branch_name, highlights))
+                .end_slot::<Label>(divergence.map(|divergence| {
+                    Label::new(divergence)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                })),
         )
     }
 
@@ -302,7 +491,18 @@ impl PickerDelegate for BranchListDelegate {
             h_flex()
                 .mr_3()
                 .pb_2()
+                .gap_2()
                 .child(h_flex().w_full())
+                .child(
+                    Button::new("branch-picker-rename-branch-button", "Rename current branch")
+                        .tooltip(|cx| {
+                            Tooltip::text("Rename the current branch to the query", cx)
+                        })
+                        .on_click(cx.listener(|this, _, cx| {
+                            let new_name = this.delegate.last_query.clone();
+                            this.delegate.rename_current_branch(new_name, cx);
+                        })),
+                )
                 .child(
                     Button::new("branch-picker-create-branch-button", "Create branch")
                         .on_click(cx.listener(|_, _, cx| {
@@ -0,0 +1,217 @@
+use anyhow::{Context, Result};
+use fuzzy::{StringMatch, StringMatchCandidate};
+use git::repository::StashEntry;
+use gpui::{
+    rems, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, InteractiveElement,
+    IntoElement, ParentElement, Render, SharedString, Styled, Subscription, Task, View,
+    ViewContext, VisualContext, WindowContext,
+};
+use picker::{Picker, PickerDelegate};
+use std::sync::Arc;
+use ui::{
+    v_flex, Color, HighlightedLabel, Label, LabelCommon, LabelSize, ListItem, ListItemSpacing,
+    Selectable,
+};
+use util::ResultExt;
+use workspace::notifications::NotificationId;
+use workspace::{ModalView, Toast, Workspace};
+
+use crate::ShowStashes;
+
+/// Lists the stashed changes of the first worktree's repository, popping the confirmed entry.
+pub struct StashList {
+    pub picker: View<Picker<StashListDelegate>>,
+    _subscription: Subscription,
+}
+
+impl StashList {
+    fn new(delegate: StashListDelegate, cx: &mut ViewContext<Self>) -> Self {
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+
+    pub fn open(
+        workspace: &mut Workspace,
+        _: &ShowStashes,
+        cx: &mut ViewContext<Workspace>,
+    ) -> Result<()> {
+        let delegate = StashListDelegate::new(workspace, cx.view().clone(), cx)?;
+        workspace.toggle_modal(cx, |cx| StashList::new(delegate, cx));
+        Ok(())
+    }
+}
+
+impl ModalView for StashList {}
+impl EventEmitter<DismissEvent> for StashList {}
+
+impl FocusableView for StashList {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for StashList {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .w(rems(34.))
+            .child(self.picker.clone())
+            .on_mouse_down_out(cx.listener(|this, _, cx| {
+                this.picker.update(cx, |this, cx| {
+                    this.cancel(&Default::default(), cx);
+                })
+            }))
+    }
+}
+
+pub struct StashListDelegate {
+    matches: Vec<StringMatch>,
+    entries: Vec<StashEntry>,
+    workspace: View<Workspace>,
+    selected_index: usize,
+}
+
+impl StashListDelegate {
+    fn new(workspace: &Workspace, handle: View<Workspace>, cx: &AppContext) -> Result<Self> {
+        let repo = workspace
+            .project()
+            .read(cx)
+            .get_first_worktree_root_repo(cx)
+            .context("failed to get root repository for first worktree")?;
+        let entries = repo.stash_list()?;
+        Ok(Self {
+            matches: Vec::new(),
+            entries,
+            workspace: handle,
+            selected_index: 0,
+        })
+    }
+
+    fn pop_stash(&mut self, index: usize, cx: &mut ViewContext<Picker<Self>>) {
+        let project = self.workspace.read(cx).project().read(cx);
+        let Some(repo) = project.get_first_worktree_root_repo(cx) else {
+            return;
+        };
+        if let Err(error) = repo.stash_pop(index) {
+            self.workspace.update(cx, |workspace, cx| {
+                struct StashPopFailure;
+                let id = NotificationId::unique::<StashPopFailure>();
+                workspace.show_toast(
+                    Toast::new(id, format!("Failed to pop stash@{{{index}}}: {error}")),
+                    cx,
+                );
+            });
+            return;
+        }
+        cx.emit(DismissEvent);
+    }
+}
+
+impl PickerDelegate for StashListDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Select stash to pop...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let candidates = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(ix, entry)| StringMatchCandidate {
+                id: ix,
+                char_bag: entry.message.chars().collect(),
+                string: entry.message.clone(),
+            })
+            .collect::<Vec<_>>();
+        cx.spawn(move |picker, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    true,
+                    10000,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+            };
+            picker
+                .update(&mut cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    delegate.selected_index = delegate
+                        .selected_index
+                        .min(delegate.matches.len().saturating_sub(1));
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(index) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|hit| self.entries.get(hit.candidate_id))
+            .map(|entry| entry.index)
+        else {
+            return;
+        };
+        self.pop_stash(index, cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let hit = &self.matches[ix];
+        let entry = self.entries.get(hit.candidate_id)?;
+        Some(
+            ListItem::new(SharedString::from(format!("stash-list-{ix}")))
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .start_slot(
+                    Label::new(format!("stash@{{{}}}", entry.index))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(HighlightedLabel::new(
+                    hit.string.clone(),
+                    hit.positions.clone(),
+                )),
+        )
+    }
+}
@@ -20,7 +20,7 @@ use futures::{
 };
 use fuzzy::CharBag;
 use git::{
-    repository::{GitFileStatus, GitRepository, RepoPath},
+    repository::{GitDiffBase, GitFileStatus, GitRepository, RepoPath, Upstream},
     status::GitStatus,
     DOT_GIT, GITIGNORE,
 };
@@ -119,6 +119,7 @@ pub struct LocalWorktree {
     next_entry_id: Arc<AtomicUsize>,
     settings: WorktreeSettings,
     share_private_files: bool,
+    git_diff_base: GitDiffBase,
 }
 
 struct ScanRequest {
@@ -166,6 +167,7 @@ pub struct Snapshot {
 pub struct RepositoryEntry {
     pub(crate) work_directory: WorkDirectoryEntry,
     pub(crate) branch: Option<Arc<str>>,
+    pub(crate) upstream: Option<Upstream>,
 
     /// If location_in_repo is set, it means the .git folder is external
     /// and in a parent folder of the project root.
@@ -193,6 +195,11 @@ impl RepositoryEntry {
         self.branch.clone()
     }
 
+    /// The upstream of the current branch, with the number of commits the branch is ahead and behind it.
+    pub fn upstream(&self) -> Option<&Upstream> {
+        self.upstream.as_ref()
+    }
+
     pub fn work_directory_id(&self) -> ProjectEntryId {
         *self.work_directory
     }
@@ -231,6 +238,19 @@ impl RepositoryEntry {
             relativize_path(path)
         }
     }
+
+    /// unrelativize is the inverse of relativize, returning the path in the worktree
+    /// of a path relative to the root folder of the repository.
+    /// Returns `None` for paths of the repository that are outside of the worktree.
+    pub fn unrelativize(&self, worktree: &Snapshot, path: &RepoPath) -> Option<Arc<Path>> {
+        let entry = worktree.entry_for_id(self.work_directory.0)?;
+        let path = if let Some(location_in_repo) = &self.location_in_repo {
+            path.strip_prefix(location_in_repo).ok()?
+        } else {
+            path.0.as_path()
+        };
+        Some(entry.path.join(path).into())
+    }
 }
 
 impl From<&RepositoryEntry> for proto::RepositoryEntry {
@@ -238,10 +258,23 @@ impl From<&RepositoryEntry> for proto::RepositoryEntry {
         proto::RepositoryEntry {
             work_directory_id: value.work_directory.to_proto(),
             branch: value.branch.as_ref().map(|str| str.to_string()),
+            upstream: value.upstream.as_ref().map(|upstream| proto::GitUpstream {
+                name: upstream.name.to_string(),
+                ahead: upstream.ahead as u64,
+                behind: upstream.behind as u64,
+            }),
         }
     }
 }
 
+fn upstream_from_proto(upstream: proto::GitUpstream) -> Upstream {
+    Upstream {
+        name: upstream.name.into(),
+        ahead: upstream.ahead as usize,
+        behind: upstream.behind as usize,
+    }
+}
+
 /// This path corresponds to the 'content path' of a repository in relation
 /// to Zed's project root.
 /// In the majority of the cases, this is the folder that contains the .git folder.
@@ -441,6 +474,7 @@ impl Worktree {
                 fs_case_sensitive,
                 visible,
                 settings,
+                git_diff_base: GitDiffBase::default(),
             };
             worktree.start_background_scanner(scan_requests_rx, path_prefixes_to_scan_rx, cx);
             Worktree::Local(worktree)
@@ -1195,12 +1229,24 @@ impl LocalWorktree {
         self.git_repositories.get(&repo.work_directory.0)
     }
 
+    /// The revision that the buffers of this worktree are diffed against.
+    pub fn git_diff_base(&self) -> &GitDiffBase {
+        &self.git_diff_base
+    }
+
+    /// Changes the revision that newly loaded files are diffed against.
+    /// Already open buffers need their diff bases to be reloaded separately.
+    pub fn set_git_diff_base(&mut self, base: GitDiffBase) {
+        self.git_diff_base = base;
+    }
+
     fn load_file(&self, path: &Path, cx: &mut ModelContext<Worktree>) -> Task<Result<LoadedFile>> {
         let path = Arc::from(path);
         let abs_path = self.absolutize(&path);
         let fs = self.fs.clone();
         let entry = self.refresh_entry(path.clone(), None, cx);
         let is_private = self.is_path_private(path.as_ref());
+        let git_diff_base = self.git_diff_base.clone();
 
         cx.spawn(|this, mut cx| async move {
             let abs_path = abs_path?;
@@ -1211,10 +1257,9 @@ impl LocalWorktree {
                 if let Some(repo_path) = repo.relativize(&snapshot, &path).log_err() {
                     if let Some(git_repo) = snapshot.git_repositories.get(&*repo.work_directory) {
                         let git_repo = git_repo.repo_ptr.clone();
-                        index_task = Some(
-                            cx.background_executor()
-                                .spawn(async move { git_repo.load_index_text(&repo_path) }),
-                        );
+                        index_task = Some(cx.background_executor().spawn(async move {
+                            git_repo.load_diff_base_text(&git_diff_base, &repo_path)
+                        }));
                     }
                 }
             }
@@ -2091,6 +2136,7 @@ impl Snapshot {
                 if self.repository_entries.get(&work_directory).is_some() {
                     self.repository_entries.update(&work_directory, |repo| {
                         repo.branch = repository.branch.map(Into::into);
+                        repo.upstream = repository.upstream.map(upstream_from_proto);
                     });
                 } else {
                     self.repository_entries.insert(
@@ -2098,6 +2144,7 @@ impl Snapshot {
                         RepositoryEntry {
                             work_directory: work_directory_entry,
                             branch: repository.branch.map(Into::into),
+                            upstream: repository.upstream.map(upstream_from_proto),
                             // When syncing repository entries from a peer, we don't need
                             // the location_in_repo field, since git operations don't happen locally
                             // anyway.
@@ -2864,6 +2911,7 @@ impl BackgroundScannerState {
             RepositoryEntry {
                 work_directory: work_dir_id.into(),
                 branch: repository.branch_name().map(Into::into),
+                upstream: repository.upstream(),
                 location_in_repo,
             },
         );
@@ -4373,6 +4421,7 @@ impl BackgroundScanner {
 
                         let repo = &repository.repo_ptr;
                         let branch = repo.branch_name();
+                        let upstream = repo.upstream();
                         repo.reload_index();
 
                         state
@@ -4383,7 +4432,10 @@ impl BackgroundScanner {
                             .snapshot
                             .snapshot
                             .repository_entries
-                            .update(&work_dir, |entry| entry.branch = branch.map(Into::into));
+                            .update(&work_dir, |entry| {
+                                entry.branch = branch.map(Into::into);
+                                entry.upstream = upstream;
+                            });
                         (work_dir, repository.repo_ptr.clone())
                     }
                 };
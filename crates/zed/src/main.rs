@@ -239,8 +239,10 @@ fn init_ui(
     go_to_line::init(cx);
     file_finder::init(cx);
     tab_switcher::init(cx);
+    git_ui::init(cx);
     dev_server_projects::init(app_state.client.clone(), cx);
     outline::init(cx);
+    symbol_index::init(cx);
     project_symbols::init(cx);
     project_panel::init(Assets, cx);
     outline_panel::init(Assets, cx);
@@ -259,6 +259,8 @@ pub fn initialize_workspace(
                 workspace_handle.clone(),
                 cx.clone(),
             );
+            let variable_explorer =
+                repl::VariableExplorer::load(workspace_handle.clone(), cx.clone());
 
             let (
                 project_panel,
@@ -268,6 +270,7 @@ pub fn initialize_workspace(
                 channels_panel,
                 chat_panel,
                 notification_panel,
+                variable_explorer,
             ) = futures::try_join!(
                 project_panel,
                 outline_panel,
@@ -276,6 +279,7 @@ pub fn initialize_workspace(
                 channels_panel,
                 chat_panel,
                 notification_panel,
+                variable_explorer,
             )?;
 
             workspace_handle.update(&mut cx, |workspace, cx| {
@@ -286,6 +290,7 @@ pub fn initialize_workspace(
                 workspace.add_panel(channels_panel, cx);
                 workspace.add_panel(chat_panel, cx);
                 workspace.add_panel(notification_panel, cx);
+                workspace.add_panel(variable_explorer, cx);
                 cx.focus_self();
             })
         })